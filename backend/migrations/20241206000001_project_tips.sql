@@ -0,0 +1,43 @@
+-- Tips & bonuses paid by clients after a project has been completed
+
+DO $$ BEGIN
+    CREATE TYPE tip_status AS ENUM ('pending', 'paid', 'failed', 'cancelled');
+EXCEPTION
+    WHEN duplicate_object THEN null;
+END $$;
+
+CREATE TABLE IF NOT EXISTS project_tips (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    client_id UUID NOT NULL REFERENCES users(id),
+    expert_id UUID NOT NULL REFERENCES users(id),
+    payment_id UUID REFERENCES payments(id),
+    invoice_id UUID REFERENCES invoices(id),
+    amount INTEGER NOT NULL CHECK (amount > 0),  -- Amount in cents
+    currency VARCHAR(3) NOT NULL DEFAULT 'EUR',
+    platform_fee INTEGER NOT NULL DEFAULT 0,     -- May be zero (configurable)
+    net_amount INTEGER NOT NULL,
+    preset_percent SMALLINT,                     -- Set when a preset was chosen
+    message TEXT,
+    status tip_status NOT NULL DEFAULT 'pending',
+    stripe_checkout_session_id VARCHAR(255),
+    paid_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_project_tips_project ON project_tips(project_id);
+CREATE INDEX IF NOT EXISTS idx_project_tips_expert ON project_tips(expert_id);
+CREATE INDEX IF NOT EXISTS idx_project_tips_status ON project_tips(status);
+
+DROP TRIGGER IF EXISTS update_project_tips_updated_at ON project_tips;
+CREATE TRIGGER update_project_tips_updated_at BEFORE UPDATE ON project_tips
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+ALTER TABLE project_tips ENABLE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS "project_tips_service_all" ON project_tips;
+CREATE POLICY "project_tips_service_all" ON project_tips
+    FOR ALL TO service_role
+    USING (true)
+    WITH CHECK (true);
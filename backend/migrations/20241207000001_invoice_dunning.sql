@@ -0,0 +1,29 @@
+-- Dunning (reminders for overdue invoices)
+
+ALTER TABLE invoices ADD COLUMN IF NOT EXISTS dunning_level SMALLINT NOT NULL DEFAULT 0;  -- 0 = none, 1 = Zahlungserinnerung, 2 = 1. Mahnung, 3 = 2. Mahnung
+ALTER TABLE invoices ADD COLUMN IF NOT EXISTS last_dunning_at TIMESTAMPTZ;
+ALTER TABLE invoices ADD COLUMN IF NOT EXISTS late_fee INTEGER NOT NULL DEFAULT 0;       -- Accumulated late fees in cents
+
+-- Clients with seriously overdue invoices cannot start new projects
+ALTER TABLE users ADD COLUMN IF NOT EXISTS projects_paused_at TIMESTAMPTZ;
+
+-- Audit trail of dunning steps
+CREATE TABLE IF NOT EXISTS invoice_dunning_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    invoice_id UUID NOT NULL REFERENCES invoices(id) ON DELETE CASCADE,
+    level SMALLINT NOT NULL,           -- 4 = marked uncollectible
+    late_fee INTEGER NOT NULL DEFAULT 0,
+    email_sent BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_invoices_overdue ON invoices(due_date) WHERE status = 'open';
+CREATE INDEX IF NOT EXISTS idx_invoice_dunning_events_invoice ON invoice_dunning_events(invoice_id);
+
+ALTER TABLE invoice_dunning_events ENABLE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS "invoice_dunning_events_service_all" ON invoice_dunning_events;
+CREATE POLICY "invoice_dunning_events_service_all" ON invoice_dunning_events
+    FOR ALL TO service_role
+    USING (true)
+    WITH CHECK (true);
@@ -0,0 +1,45 @@
+-- Invoice-based billing (Net-30 terms) for verified enterprise clients
+
+DO $$ BEGIN
+    CREATE TYPE billing_mode AS ENUM ('card', 'invoice');
+EXCEPTION
+    WHEN duplicate_object THEN null;
+END $$;
+
+-- Credit terms on the client profile (set by admins)
+ALTER TABLE client_profiles ADD COLUMN IF NOT EXISTS billing_mode billing_mode NOT NULL DEFAULT 'card';
+ALTER TABLE client_profiles ADD COLUMN IF NOT EXISTS credit_limit INTEGER;                        -- in cents
+ALTER TABLE client_profiles ADD COLUMN IF NOT EXISTS payment_terms_days SMALLINT NOT NULL DEFAULT 30;
+ALTER TABLE client_profiles ADD COLUMN IF NOT EXISTS credit_approved_at TIMESTAMPTZ;
+ALTER TABLE client_profiles ADD COLUMN IF NOT EXISTS credit_approved_by UUID REFERENCES users(id);
+ALTER TABLE client_profiles ADD COLUMN IF NOT EXISTS billing_blocked_at TIMESTAMPTZ;
+ALTER TABLE client_profiles ADD COLUMN IF NOT EXISTS billing_blocked_reason VARCHAR(50);       -- 'credit_limit', 'overdue' or 'manual'
+
+-- PO number and billing mode per project; invoice-billed projects are linked to their monthly invoice
+ALTER TABLE projects ADD COLUMN IF NOT EXISTS po_number VARCHAR(100);
+ALTER TABLE projects ADD COLUMN IF NOT EXISTS billing_mode billing_mode NOT NULL DEFAULT 'card';
+ALTER TABLE projects ADD COLUMN IF NOT EXISTS invoice_id UUID REFERENCES invoices(id);
+
+-- Consolidated invoices are issued by the platform itself (no issuer user)
+ALTER TABLE invoices ALTER COLUMN issuer_id DROP NOT NULL;
+ALTER TABLE invoices ADD COLUMN IF NOT EXISTS billing_period_start DATE;
+ALTER TABLE invoices ADD COLUMN IF NOT EXISTS billing_period_end DATE;
+ALTER TABLE invoices ADD COLUMN IF NOT EXISTS payment_reference VARCHAR(27);                  -- QR reference
+ALTER TABLE invoices ADD COLUMN IF NOT EXISTS payment_details JSONB;                          -- IBAN, QR-bill payload
+
+CREATE INDEX IF NOT EXISTS idx_projects_uninvoiced ON projects(client_id)
+    WHERE billing_mode = 'invoice' AND invoice_id IS NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_invoices_billing_period
+    ON invoices(recipient_id, billing_period_start, currency) WHERE billing_period_start IS NOT NULL;
+
+-- Outstanding exposure of an invoice-billed client: uninvoiced projects plus open consolidated invoices (cents)
+CREATE OR REPLACE FUNCTION client_credit_used(client UUID) RETURNS BIGINT AS $$
+    SELECT COALESCE((
+        SELECT SUM(price) FROM projects
+        WHERE client_id = client AND billing_mode = 'invoice' AND invoice_id IS NULL
+          AND status NOT IN ('cancelled', 'refunded')
+    ), 0) + COALESCE((
+        SELECT SUM(total) FROM invoices
+        WHERE recipient_id = client AND billing_period_start IS NOT NULL AND status = 'open'
+    ), 0)
+$$ LANGUAGE sql STABLE;
@@ -0,0 +1,70 @@
+-- Cancellation policies: refund and expert compensation rules applied when a project is cancelled
+
+CREATE TABLE IF NOT EXISTS cancellation_policies (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    owner_id UUID REFERENCES users(id) ON DELETE CASCADE,   -- Expert who created it; NULL = platform policy
+    name VARCHAR(100) NOT NULL,
+    description TEXT,
+    is_platform_default BOOLEAN NOT NULL DEFAULT FALSE,
+    -- Client cancels within this many hours after payment (and before any milestone is done): full refund
+    free_cancellation_hours INTEGER NOT NULL DEFAULT 24,
+    -- Refund percentages of the refundable amount when the client cancels
+    refund_before_start_percent SMALLINT NOT NULL DEFAULT 100 CHECK (refund_before_start_percent BETWEEN 0 AND 100),
+    refund_in_progress_percent SMALLINT NOT NULL DEFAULT 50 CHECK (refund_in_progress_percent BETWEEN 0 AND 100),
+    refund_after_delivery_percent SMALLINT NOT NULL DEFAULT 0 CHECK (refund_after_delivery_percent BETWEEN 0 AND 100),
+    -- Refund percentage when the expert cancels
+    expert_cancel_refund_percent SMALLINT NOT NULL DEFAULT 100 CHECK (expert_cancel_refund_percent BETWEEN 0 AND 100),
+    -- Whether the platform fee is refunded proportionally
+    refund_platform_fee BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_cancellation_policies_default
+    ON cancellation_policies(is_platform_default) WHERE is_platform_default = TRUE;
+CREATE INDEX IF NOT EXISTS idx_cancellation_policies_owner ON cancellation_policies(owner_id);
+
+ALTER TABLE services ADD COLUMN IF NOT EXISTS cancellation_policy_id UUID REFERENCES cancellation_policies(id) ON DELETE SET NULL;
+
+-- Calculation applied to a cancelled project (kept for both parties)
+CREATE TABLE IF NOT EXISTS project_cancellations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    project_id UUID NOT NULL UNIQUE REFERENCES projects(id) ON DELETE CASCADE,
+    cancelled_by UUID NOT NULL REFERENCES users(id),
+    cancelled_by_role VARCHAR(20) NOT NULL,          -- 'client', 'expert' or 'admin'
+    policy_id UUID REFERENCES cancellation_policies(id) ON DELETE SET NULL,
+    payment_id UUID REFERENCES payments(id),
+    paid_amount INTEGER NOT NULL DEFAULT 0,
+    milestones_completed_amount INTEGER NOT NULL DEFAULT 0,
+    refund_percent SMALLINT NOT NULL DEFAULT 0,
+    refund_amount INTEGER NOT NULL DEFAULT 0,
+    expert_compensation INTEGER NOT NULL DEFAULT 0,
+    platform_fee_retained INTEGER NOT NULL DEFAULT 0,
+    explanation JSONB NOT NULL DEFAULT '[]',
+    reason TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+DROP TRIGGER IF EXISTS update_cancellation_policies_updated_at ON cancellation_policies;
+CREATE TRIGGER update_cancellation_policies_updated_at BEFORE UPDATE ON cancellation_policies
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+ALTER TABLE cancellation_policies ENABLE ROW LEVEL SECURITY;
+ALTER TABLE project_cancellations ENABLE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS "cancellation_policies_service_all" ON cancellation_policies;
+CREATE POLICY "cancellation_policies_service_all" ON cancellation_policies
+    FOR ALL TO service_role
+    USING (true)
+    WITH CHECK (true);
+
+DROP POLICY IF EXISTS "project_cancellations_service_all" ON project_cancellations;
+CREATE POLICY "project_cancellations_service_all" ON project_cancellations
+    FOR ALL TO service_role
+    USING (true)
+    WITH CHECK (true);
+
+-- Platform default policy
+INSERT INTO cancellation_policies (name, description, is_platform_default)
+SELECT 'Standard', 'Kostenlose Stornierung innerhalb von 24 Stunden, danach anteilige Erstattung je nach Projektfortschritt.', TRUE
+WHERE NOT EXISTS (SELECT 1 FROM cancellation_policies WHERE is_platform_default = TRUE);
@@ -0,0 +1,19 @@
+-- Project lifecycle: payments only become eligible for payout once the project is settled
+
+ALTER TABLE payments ADD COLUMN IF NOT EXISTS released_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_payments_payee_released
+    ON payments(payee_id) WHERE released_at IS NOT NULL AND stripe_transfer_id IS NULL;
+
+-- Existing tips and payments of finished projects are released
+UPDATE payments p
+SET released_at = COALESCE(p.paid_at, p.created_at)
+WHERE p.released_at IS NULL
+  AND p.status IN ('succeeded', 'partially_refunded')
+  AND (
+      p.metadata->>'kind' = 'tip'
+      OR EXISTS (
+          SELECT 1 FROM projects pr
+          WHERE pr.id = p.project_id AND pr.status IN ('completed', 'cancelled')
+      )
+  );
@@ -0,0 +1,53 @@
+-- Project timeline / audit trail
+
+DO $$ BEGIN
+    CREATE TYPE project_actor AS ENUM ('client', 'expert', 'admin', 'system');
+EXCEPTION
+    WHEN duplicate_object THEN null;
+END $$;
+
+DO $$ BEGIN
+    CREATE TYPE project_event_type AS ENUM (
+        'project_created',
+        'status_changed',
+        'delivery_submitted',
+        'revision_requested',
+        'file_uploaded',
+        'milestone_created',
+        'milestone_updated',
+        'payment_received',
+        'payment_refunded',
+        'payment_disputed'
+    );
+EXCEPTION
+    WHEN duplicate_object THEN null;
+END $$;
+
+CREATE TABLE IF NOT EXISTS project_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    event_type project_event_type NOT NULL,
+    actor project_actor NOT NULL,
+    actor_id UUID REFERENCES users(id) ON DELETE SET NULL,   -- NULL for system events
+    from_status project_status,
+    to_status project_status,
+    message TEXT,
+    data JSONB,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_project_events_project ON project_events(project_id, created_at DESC);
+
+ALTER TABLE project_events ENABLE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS "project_events_service_all" ON project_events;
+CREATE POLICY "project_events_service_all" ON project_events
+    FOR ALL TO service_role
+    USING (true)
+    WITH CHECK (true);
+
+-- Start the timeline of existing projects
+INSERT INTO project_events (project_id, event_type, actor, actor_id, to_status, created_at)
+SELECT p.id, 'project_created', 'client', p.client_id, p.status, p.created_at
+FROM projects p
+WHERE NOT EXISTS (SELECT 1 FROM project_events e WHERE e.project_id = p.id);
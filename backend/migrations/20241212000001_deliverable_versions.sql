@@ -0,0 +1,74 @@
+-- Versioned project deliverables stored privately in object storage
+ALTER TABLE project_deliverables
+    ADD COLUMN IF NOT EXISTS storage_key TEXT,
+    ADD COLUMN IF NOT EXISTS uploaded_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    -- NULL while a presigned upload has not been confirmed yet
+    ADD COLUMN IF NOT EXISTS uploaded_at TIMESTAMPTZ;
+
+-- Only link attachments have a URL; stored files are served via signed URLs
+ALTER TABLE project_deliverables ALTER COLUMN file_url DROP NOT NULL;
+
+ALTER TYPE project_event_type ADD VALUE IF NOT EXISTS 'deliverable_finalized' AFTER 'file_uploaded';
+
+UPDATE project_deliverables SET uploaded_at = created_at WHERE uploaded_at IS NULL AND storage_key IS NULL;
+
+-- Renumber existing versions per title before enforcing uniqueness
+UPDATE project_deliverables d
+SET version = v.version
+FROM (
+    SELECT id, ROW_NUMBER() OVER (PARTITION BY project_id, title ORDER BY created_at, id) AS version
+    FROM project_deliverables
+) v
+WHERE d.id = v.id AND d.version <> v.version;
+
+UPDATE project_deliverables d
+SET is_final = FALSE
+WHERE is_final AND EXISTS (
+    SELECT 1 FROM project_deliverables o
+    WHERE o.project_id = d.project_id AND o.title = d.title AND o.is_final AND o.version > d.version
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_project_deliverables_version
+    ON project_deliverables(project_id, title, version);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_project_deliverables_final
+    ON project_deliverables(project_id, title) WHERE is_final;
+
+-- Deliveries submitted by the expert, referencing specific deliverable versions
+CREATE TABLE IF NOT EXISTS project_deliveries (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    delivered_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    message TEXT NOT NULL,
+    deliverable_ids UUID[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_project_deliveries_project ON project_deliveries(project_id, created_at DESC);
+
+-- Revision requests pointing at the versions the feedback applies to
+CREATE TABLE IF NOT EXISTS project_revision_requests (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    delivery_id UUID REFERENCES project_deliveries(id) ON DELETE SET NULL,
+    requested_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    feedback TEXT NOT NULL,
+    deliverable_ids UUID[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_project_revision_requests_project ON project_revision_requests(project_id, created_at DESC);
+
+ALTER TABLE project_deliveries ENABLE ROW LEVEL SECURITY;
+ALTER TABLE project_revision_requests ENABLE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS "project_deliveries_service_all" ON project_deliveries;
+CREATE POLICY "project_deliveries_service_all" ON project_deliveries
+    FOR ALL TO service_role
+    USING (true)
+    WITH CHECK (true);
+
+DROP POLICY IF EXISTS "project_revision_requests_service_all" ON project_revision_requests;
+CREATE POLICY "project_revision_requests_service_all" ON project_revision_requests
+    FOR ALL TO service_role
+    USING (true)
+    WITH CHECK (true);
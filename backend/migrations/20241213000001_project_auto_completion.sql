@@ -0,0 +1,8 @@
+-- Auto-completion of delivered projects the client does not respond to
+
+-- Number of approval reminders sent for the current delivery
+ALTER TABLE projects ADD COLUMN IF NOT EXISTS auto_complete_reminders SMALLINT NOT NULL DEFAULT 0;
+
+ALTER TYPE project_event_type ADD VALUE IF NOT EXISTS 'completion_reminder_sent' AFTER 'revision_requested';
+
+CREATE INDEX IF NOT EXISTS idx_projects_awaiting_approval ON projects(delivered_at) WHERE status = 'delivered';
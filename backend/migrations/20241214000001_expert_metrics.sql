@@ -0,0 +1,105 @@
+-- Expert performance metrics (response time, on-time delivery, completion and revision rates)
+
+DO $$ BEGIN
+    CREATE TYPE expert_metric_kind AS ENUM ('first_response', 'delivery', 'outcome', 'revision');
+EXCEPTION
+    WHEN duplicate_object THEN null;
+END $$;
+
+-- One sample per measured event; expert profile figures are aggregated from these
+CREATE TABLE IF NOT EXISTS expert_metric_events (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    expert_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind expert_metric_kind NOT NULL,
+    source_id UUID NOT NULL,     -- conversation, booking request or project
+    value REAL NOT NULL,         -- hours for first_response, otherwise 1 (yes) / 0 (no)
+    occurred_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE(kind, source_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_expert_metric_events_expert ON expert_metric_events(expert_id, occurred_at DESC);
+
+ALTER TABLE expert_metric_events ENABLE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS "expert_metric_events_service_all" ON expert_metric_events;
+CREATE POLICY "expert_metric_events_service_all" ON expert_metric_events
+    FOR ALL TO service_role
+    USING (true)
+    WITH CHECK (true);
+
+ALTER TABLE expert_profiles ADD COLUMN IF NOT EXISTS on_time_delivery_rate REAL;
+ALTER TABLE expert_profiles ADD COLUMN IF NOT EXISTS revision_rate REAL;
+ALTER TABLE expert_profiles ADD COLUMN IF NOT EXISTS metrics_updated_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_expert_profiles_completion_rate ON expert_profiles(completion_rate DESC NULLS LAST);
+
+-- Backfill: first replies in conversations started by someone else
+INSERT INTO expert_metric_events (expert_id, kind, source_id, value, occurred_at)
+SELECT x.expert_id, 'first_response', x.id, EXTRACT(EPOCH FROM (x.replied - x.asked)) / 3600.0, x.replied
+FROM (
+    SELECT c.id, u.id AS expert_id,
+           (SELECT MIN(m.created_at) FROM messages m
+            WHERE m.conversation_id = c.id AND m.sender_id <> u.id AND m.message_type <> 'system') AS asked,
+           (SELECT MIN(m.created_at) FROM messages m
+            WHERE m.conversation_id = c.id AND m.sender_id = u.id AND m.message_type <> 'system') AS replied
+    FROM conversations c
+    JOIN users u ON u.id IN (c.participant_one_id, c.participant_two_id) AND u.role = 'expert'
+) x
+WHERE x.asked IS NOT NULL AND x.replied > x.asked
+ON CONFLICT (kind, source_id) DO NOTHING;
+
+-- Backfill: answered booking requests
+INSERT INTO expert_metric_events (expert_id, kind, source_id, value, occurred_at)
+SELECT expert_id, 'first_response', id, EXTRACT(EPOCH FROM (responded_at - created_at)) / 3600.0, responded_at
+FROM booking_requests
+WHERE responded_at IS NOT NULL
+ON CONFLICT (kind, source_id) DO NOTHING;
+
+-- Backfill: deliveries with an agreed delivery date
+INSERT INTO expert_metric_events (expert_id, kind, source_id, value, occurred_at)
+SELECT expert_id, 'delivery', id, CASE WHEN delivered_at <= delivery_date THEN 1 ELSE 0 END, delivered_at
+FROM projects
+WHERE delivered_at IS NOT NULL AND delivery_date IS NOT NULL
+ON CONFLICT (kind, source_id) DO NOTHING;
+
+-- Backfill: completed projects and cancellations after work had started
+INSERT INTO expert_metric_events (expert_id, kind, source_id, value, occurred_at)
+SELECT expert_id, 'outcome', id, 1, completed_at
+FROM projects
+WHERE status = 'completed' AND completed_at IS NOT NULL
+ON CONFLICT (kind, source_id) DO NOTHING;
+
+INSERT INTO expert_metric_events (expert_id, kind, source_id, value, occurred_at)
+SELECT p.expert_id, 'outcome', p.id, 0, p.cancelled_at
+FROM projects p
+WHERE p.status = 'cancelled' AND p.cancelled_at IS NOT NULL
+  AND NOT EXISTS (
+      SELECT 1 FROM project_events e
+      WHERE e.project_id = p.id AND e.to_status = 'cancelled' AND e.from_status IN ('pending', 'accepted')
+  )
+ON CONFLICT (kind, source_id) DO NOTHING;
+
+INSERT INTO expert_metric_events (expert_id, kind, source_id, value, occurred_at)
+SELECT expert_id, 'revision', id, CASE WHEN revisions_used > 0 THEN 1 ELSE 0 END, completed_at
+FROM projects
+WHERE status = 'completed' AND completed_at IS NOT NULL
+ON CONFLICT (kind, source_id) DO NOTHING;
+
+-- Initial profile figures (last 365 days); afterwards updated on every new sample
+UPDATE expert_profiles ep
+SET response_time_hours = s.response_time_hours,
+    completion_rate = s.completion_rate,
+    on_time_delivery_rate = s.on_time_delivery_rate,
+    revision_rate = s.revision_rate,
+    metrics_updated_at = NOW()
+FROM (
+    SELECT expert_id,
+           LEAST(CEIL(percentile_cont(0.5) WITHIN GROUP (ORDER BY value) FILTER (WHERE kind = 'first_response')), 32767)::SMALLINT AS response_time_hours,
+           (AVG(value) FILTER (WHERE kind = 'outcome') * 100)::REAL AS completion_rate,
+           (AVG(value) FILTER (WHERE kind = 'delivery') * 100)::REAL AS on_time_delivery_rate,
+           (AVG(value) FILTER (WHERE kind = 'revision') * 100)::REAL AS revision_rate
+    FROM expert_metric_events
+    WHERE occurred_at > NOW() - INTERVAL '365 days'
+    GROUP BY expert_id
+) s
+WHERE ep.user_id = s.expert_id;
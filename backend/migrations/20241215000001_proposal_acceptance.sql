@@ -0,0 +1,18 @@
+-- Projects created from accepted proposals
+
+ALTER TABLE projects ADD COLUMN IF NOT EXISTS proposal_id UUID REFERENCES proposals(id) ON DELETE SET NULL;
+
+-- A proposal can only ever turn into one project
+CREATE UNIQUE INDEX IF NOT EXISTS idx_projects_proposal ON projects(proposal_id) WHERE proposal_id IS NOT NULL;
+
+-- Only one accepted proposal per posting; earlier duplicates keep the first acceptance
+UPDATE proposals p
+SET status = 'rejected', rejected_at = NOW(), rejection_reason = 'Another proposal was accepted'
+WHERE p.status = 'accepted'
+  AND EXISTS (
+      SELECT 1 FROM proposals o
+      WHERE o.project_posting_id = p.project_posting_id AND o.status = 'accepted'
+        AND (o.accepted_at, o.id) < (p.accepted_at, p.id)
+  );
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_proposals_accepted ON proposals(project_posting_id) WHERE status = 'accepted';
@@ -0,0 +1,44 @@
+-- Calendar-aware booking: bookable time slots, reservations with expiry and double-booking protection
+
+CREATE EXTENSION IF NOT EXISTS btree_gist;
+
+-- Expert booking rules
+ALTER TABLE expert_profiles ADD COLUMN IF NOT EXISTS booking_slot_minutes SMALLINT NOT NULL DEFAULT 60;
+ALTER TABLE expert_profiles ADD COLUMN IF NOT EXISTS booking_buffer_minutes SMALLINT NOT NULL DEFAULT 15;
+ALTER TABLE expert_profiles ADD COLUMN IF NOT EXISTS max_bookings_per_day SMALLINT; -- NULL = unlimited
+
+DO $$ BEGIN
+    ALTER TABLE expert_profiles ADD CONSTRAINT valid_booking_rules CHECK (
+        booking_slot_minutes BETWEEN 15 AND 480
+        AND booking_buffer_minutes BETWEEN 0 AND 240
+        AND (max_bookings_per_day IS NULL OR max_bookings_per_day > 0)
+    );
+EXCEPTION
+    WHEN duplicate_object THEN null;
+END $$;
+
+-- Reserved time slot of a booking request
+ALTER TABLE booking_requests ADD COLUMN IF NOT EXISTS slot_start TIMESTAMPTZ;
+ALTER TABLE booking_requests ADD COLUMN IF NOT EXISTS slot_end TIMESTAMPTZ;
+
+DO $$ BEGIN
+    ALTER TABLE booking_requests ADD CONSTRAINT valid_slot_range CHECK (
+        (slot_start IS NULL AND slot_end IS NULL) OR slot_start < slot_end
+    );
+EXCEPTION
+    WHEN duplicate_object THEN null;
+END $$;
+
+-- Pending requests hold their slot until they expire, accepted ones for good
+DO $$ BEGIN
+    ALTER TABLE booking_requests ADD CONSTRAINT booking_requests_no_overlap
+        EXCLUDE USING gist (expert_id WITH =, tstzrange(slot_start, slot_end) WITH &&)
+        WHERE (slot_start IS NOT NULL AND status IN ('pending', 'accepted'));
+EXCEPTION
+    WHEN duplicate_object OR duplicate_table THEN null;
+END $$;
+
+CREATE INDEX IF NOT EXISTS idx_booking_requests_slot ON booking_requests(expert_id, slot_start)
+    WHERE slot_start IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_booking_requests_expiry ON booking_requests(expires_at)
+    WHERE status = 'pending';
@@ -0,0 +1,58 @@
+-- Calendar sync: ICS feed export per expert and ICS imports that block busy times
+
+-- Secret token of the expert's ICS feed URL
+ALTER TABLE expert_profiles ADD COLUMN IF NOT EXISTS calendar_feed_token VARCHAR(64);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_expert_profiles_calendar_feed_token ON expert_profiles(calendar_feed_token)
+    WHERE calendar_feed_token IS NOT NULL;
+
+-- Imported calendars: subscriptions (source_url) are re-synced periodically, uploads are imported once
+CREATE TABLE IF NOT EXISTS calendar_imports (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    expert_id UUID NOT NULL REFERENCES expert_profiles(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    source_url TEXT,
+    event_count INTEGER NOT NULL DEFAULT 0,
+    last_synced_at TIMESTAMPTZ,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_calendar_imports_expert ON calendar_imports(expert_id);
+CREATE INDEX IF NOT EXISTS idx_calendar_imports_sync ON calendar_imports(last_synced_at NULLS FIRST)
+    WHERE source_url IS NOT NULL;
+
+-- All-day events become blocked dates of their import (replaced on every sync)
+ALTER TABLE blocked_dates ADD COLUMN IF NOT EXISTS import_id UUID REFERENCES calendar_imports(id) ON DELETE CASCADE;
+
+CREATE INDEX IF NOT EXISTS idx_blocked_dates_import ON blocked_dates(import_id) WHERE import_id IS NOT NULL;
+
+-- Timed events block their exact time range
+CREATE TABLE IF NOT EXISTS blocked_times (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    expert_id UUID NOT NULL REFERENCES expert_profiles(id) ON DELETE CASCADE,
+    import_id UUID REFERENCES calendar_imports(id) ON DELETE CASCADE,
+    starts_at TIMESTAMPTZ NOT NULL,
+    ends_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT valid_time_range CHECK (starts_at < ends_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_blocked_times_expert ON blocked_times(expert_id, starts_at);
+CREATE INDEX IF NOT EXISTS idx_blocked_times_import ON blocked_times(import_id);
+
+ALTER TABLE calendar_imports ENABLE ROW LEVEL SECURITY;
+ALTER TABLE blocked_times ENABLE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS "calendar_imports_service_all" ON calendar_imports;
+CREATE POLICY "calendar_imports_service_all" ON calendar_imports
+    FOR ALL TO service_role
+    USING (true)
+    WITH CHECK (true);
+
+DROP POLICY IF EXISTS "blocked_times_service_all" ON blocked_times;
+CREATE POLICY "blocked_times_service_all" ON blocked_times
+    FOR ALL TO service_role
+    USING (true)
+    WITH CHECK (true);
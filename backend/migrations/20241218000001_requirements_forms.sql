@@ -0,0 +1,47 @@
+-- Structured order requirements: typed forms per service, answers per project
+
+-- Paid projects wait here until the client has completed the requirements form
+ALTER TYPE project_status ADD VALUE IF NOT EXISTS 'awaiting_requirements' AFTER 'paid';
+
+ALTER TYPE project_event_type ADD VALUE IF NOT EXISTS 'requirements_submitted' AFTER 'revision_requested';
+
+-- Form schema: array of fields ({id, label, type, required, ...})
+ALTER TABLE services ADD COLUMN IF NOT EXISTS requirements_form JSONB NOT NULL DEFAULT '[]';
+
+-- Snapshot of the form at order time, so later edits of the service do not affect running orders
+ALTER TABLE projects
+    ADD COLUMN IF NOT EXISTS requirements_form JSONB NOT NULL DEFAULT '[]',
+    ADD COLUMN IF NOT EXISTS requirements_completed_at TIMESTAMPTZ;
+
+-- Existing projects have no form and count as complete
+UPDATE projects SET requirements_completed_at = created_at
+WHERE requirements_completed_at IS NULL AND requirements_form = '[]'::jsonb;
+
+CREATE TABLE IF NOT EXISTS project_requirement_answers (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    field_id VARCHAR(50) NOT NULL,
+    field_type VARCHAR(20) NOT NULL,
+    -- String or array of strings; NULL for files and for purged secrets
+    value JSONB,
+    file_name VARCHAR(255),
+    file_type VARCHAR(100),
+    file_size BIGINT,
+    storage_key TEXT,
+    answered_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    -- Secrets are deleted once the project is closed
+    purged_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (project_id, field_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_project_requirement_answers_project ON project_requirement_answers(project_id);
+
+ALTER TABLE project_requirement_answers ENABLE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS "project_requirement_answers_service_all" ON project_requirement_answers;
+CREATE POLICY "project_requirement_answers_service_all" ON project_requirement_answers
+    FOR ALL TO service_role
+    USING (true)
+    WITH CHECK (true);
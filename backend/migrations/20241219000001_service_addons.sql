@@ -0,0 +1,56 @@
+-- Service add-ons: optional extras (express delivery, additional workflows, support) chosen at checkout
+
+CREATE TABLE IF NOT EXISTS service_addons (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    service_id UUID NOT NULL REFERENCES services(id) ON DELETE CASCADE,
+    title VARCHAR(100) NOT NULL,
+    description TEXT,
+    price INTEGER NOT NULL CHECK (price >= 0),
+    -- Change of the delivery time per unit; negative for express delivery
+    extra_days SMALLINT NOT NULL DEFAULT 0 CHECK (extra_days BETWEEN -365 AND 365),
+    extra_revisions SMALLINT NOT NULL DEFAULT 0 CHECK (extra_revisions BETWEEN 0 AND 10),
+    max_quantity SMALLINT NOT NULL DEFAULT 1 CHECK (max_quantity BETWEEN 1 AND 100),
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    sort_order SMALLINT NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_service_addons_service ON service_addons(service_id, sort_order);
+
+-- Add-ons ordered with a project, priced at order time
+CREATE TABLE IF NOT EXISTS project_addons (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    addon_id UUID REFERENCES service_addons(id) ON DELETE SET NULL,
+    title VARCHAR(100) NOT NULL,
+    unit_price INTEGER NOT NULL,
+    quantity SMALLINT NOT NULL CHECK (quantity > 0),
+    -- Totals for the ordered quantity
+    extra_days INTEGER NOT NULL DEFAULT 0,
+    extra_revisions INTEGER NOT NULL DEFAULT 0,
+    amount INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_project_addons_project ON project_addons(project_id);
+
+ALTER TABLE service_addons ENABLE ROW LEVEL SECURITY;
+ALTER TABLE project_addons ENABLE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS "service_addons_public_read" ON service_addons;
+CREATE POLICY "service_addons_public_read" ON service_addons
+    FOR SELECT TO anon, authenticated
+    USING (is_active = true);
+
+DROP POLICY IF EXISTS "service_addons_service_all" ON service_addons;
+CREATE POLICY "service_addons_service_all" ON service_addons
+    FOR ALL TO service_role
+    USING (true)
+    WITH CHECK (true);
+
+DROP POLICY IF EXISTS "project_addons_service_all" ON project_addons;
+CREATE POLICY "project_addons_service_all" ON project_addons
+    FOR ALL TO service_role
+    USING (true)
+    WITH CHECK (true);
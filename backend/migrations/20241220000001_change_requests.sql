@@ -0,0 +1,56 @@
+-- Change requests: either party proposes new scope, price, delivery date or revisions on a running project
+
+DO $$ BEGIN
+    CREATE TYPE change_request_status AS ENUM ('pending', 'accepted', 'rejected', 'withdrawn');
+EXCEPTION
+    WHEN duplicate_object THEN null;
+END $$;
+
+ALTER TYPE project_event_type ADD VALUE IF NOT EXISTS 'change_requested' AFTER 'requirements_submitted';
+ALTER TYPE project_event_type ADD VALUE IF NOT EXISTS 'change_accepted' AFTER 'change_requested';
+ALTER TYPE project_event_type ADD VALUE IF NOT EXISTS 'change_rejected' AFTER 'change_accepted';
+ALTER TYPE project_event_type ADD VALUE IF NOT EXISTS 'change_withdrawn' AFTER 'change_rejected';
+
+CREATE TABLE IF NOT EXISTS project_change_requests (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    requested_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    requested_by_role project_actor NOT NULL,
+    description TEXT NOT NULL,
+    -- Terms when the change was proposed and the proposed terms (unchanged terms repeat the previous value)
+    previous_price INTEGER NOT NULL,
+    new_price INTEGER NOT NULL CHECK (new_price > 0),
+    previous_delivery_date TIMESTAMPTZ,
+    new_delivery_date TIMESTAMPTZ,
+    previous_revisions_allowed SMALLINT NOT NULL,
+    new_revisions_allowed SMALLINT NOT NULL CHECK (new_revisions_allowed >= 0),
+    status change_request_status NOT NULL DEFAULT 'pending',
+    responded_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    response_message TEXT,
+    responded_at TIMESTAMPTZ,
+    -- Additional charge (price increase) or refunded project payment (price decrease)
+    payment_id UUID REFERENCES payments(id) ON DELETE SET NULL,
+    refund_amount INTEGER,
+    stripe_checkout_session_id VARCHAR(255),
+    paid_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_project_change_requests_project ON project_change_requests(project_id, created_at DESC);
+
+-- One open change request per project at a time
+CREATE UNIQUE INDEX IF NOT EXISTS idx_project_change_requests_pending
+    ON project_change_requests(project_id) WHERE status = 'pending';
+
+DROP TRIGGER IF EXISTS update_project_change_requests_updated_at ON project_change_requests;
+CREATE TRIGGER update_project_change_requests_updated_at BEFORE UPDATE ON project_change_requests
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+ALTER TABLE project_change_requests ENABLE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS "project_change_requests_service_all" ON project_change_requests;
+CREATE POLICY "project_change_requests_service_all" ON project_change_requests
+    FOR ALL TO service_role
+    USING (true)
+    WITH CHECK (true);
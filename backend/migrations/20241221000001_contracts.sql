@@ -0,0 +1,86 @@
+-- Contracts: admin-managed templates (NDA, AVV, service agreement) rendered per project and signed by click
+
+DO $$ BEGIN
+    CREATE TYPE contract_kind AS ENUM ('nda', 'dpa', 'service_agreement');
+EXCEPTION
+    WHEN duplicate_object THEN null;
+END $$;
+
+ALTER TYPE project_event_type ADD VALUE IF NOT EXISTS 'contract_created' AFTER 'change_withdrawn';
+ALTER TYPE project_event_type ADD VALUE IF NOT EXISTS 'contract_signed' AFTER 'contract_created';
+
+CREATE TABLE IF NOT EXISTS contract_templates (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    kind contract_kind NOT NULL,
+    language language NOT NULL,
+    title VARCHAR(200) NOT NULL,
+    -- Text with {{placeholders}} filled from project, client and expert data
+    body TEXT NOT NULL,
+    version INTEGER NOT NULL DEFAULT 1,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- One active template per kind and language
+CREATE UNIQUE INDEX IF NOT EXISTS idx_contract_templates_active
+    ON contract_templates(kind, language) WHERE is_active;
+
+CREATE TABLE IF NOT EXISTS project_contracts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    template_id UUID REFERENCES contract_templates(id) ON DELETE SET NULL,
+    template_version INTEGER NOT NULL,
+    kind contract_kind NOT NULL,
+    language language NOT NULL,
+    title VARCHAR(200) NOT NULL,
+    -- Rendered text and the PDF generated from it; the hash identifies the exact document signed
+    content TEXT NOT NULL,
+    document BYTEA NOT NULL,
+    document_hash VARCHAR(64) NOT NULL,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    -- Set once both parties have signed
+    completed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (project_id, kind)
+);
+
+CREATE TABLE IF NOT EXISTS contract_signatures (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    contract_id UUID NOT NULL REFERENCES project_contracts(id) ON DELETE CASCADE,
+    user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    party project_actor NOT NULL,
+    signer_name VARCHAR(200) NOT NULL,
+    document_hash VARCHAR(64) NOT NULL,
+    ip_address VARCHAR(45),
+    user_agent TEXT,
+    signed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (contract_id, party)
+);
+
+DROP TRIGGER IF EXISTS update_contract_templates_updated_at ON contract_templates;
+CREATE TRIGGER update_contract_templates_updated_at BEFORE UPDATE ON contract_templates
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+ALTER TABLE contract_templates ENABLE ROW LEVEL SECURITY;
+ALTER TABLE project_contracts ENABLE ROW LEVEL SECURITY;
+ALTER TABLE contract_signatures ENABLE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS "contract_templates_service_all" ON contract_templates;
+CREATE POLICY "contract_templates_service_all" ON contract_templates
+    FOR ALL TO service_role
+    USING (true)
+    WITH CHECK (true);
+
+DROP POLICY IF EXISTS "project_contracts_service_all" ON project_contracts;
+CREATE POLICY "project_contracts_service_all" ON project_contracts
+    FOR ALL TO service_role
+    USING (true)
+    WITH CHECK (true);
+
+DROP POLICY IF EXISTS "contract_signatures_service_all" ON contract_signatures;
+CREATE POLICY "contract_signatures_service_all" ON contract_signatures
+    FOR ALL TO service_role
+    USING (true)
+    WITH CHECK (true);
@@ -0,0 +1,92 @@
+-- Agencies: several experts selling under one brand, with shared services, projects and inbox
+
+DO $$ BEGIN
+    CREATE TYPE agency_role AS ENUM ('owner', 'manager', 'member');
+EXCEPTION
+    WHEN duplicate_object THEN null;
+END $$;
+
+ALTER TYPE project_event_type ADD VALUE IF NOT EXISTS 'project_assigned' AFTER 'contract_signed';
+
+CREATE TABLE IF NOT EXISTS agencies (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(200) NOT NULL,
+    slug VARCHAR(220) NOT NULL UNIQUE,
+    description TEXT,
+    logo_url TEXT,
+    website VARCHAR(500),
+    -- Share of a payout that goes to the member who did the work, unless set per member
+    default_member_share_percent SMALLINT NOT NULL DEFAULT 70
+        CHECK (default_member_share_percent BETWEEN 0 AND 100),
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- An expert belongs to at most one agency
+CREATE TABLE IF NOT EXISTS agency_members (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    agency_id UUID NOT NULL REFERENCES agencies(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL UNIQUE REFERENCES users(id) ON DELETE CASCADE,
+    role agency_role NOT NULL DEFAULT 'member',
+    revenue_share_percent SMALLINT CHECK (revenue_share_percent BETWEEN 0 AND 100),
+    invited_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    joined_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_agency_members_agency ON agency_members(agency_id);
+
+ALTER TABLE services ADD COLUMN IF NOT EXISTS agency_id UUID REFERENCES agencies(id) ON DELETE SET NULL;
+ALTER TABLE projects ADD COLUMN IF NOT EXISTS agency_id UUID REFERENCES agencies(id) ON DELETE SET NULL;
+ALTER TABLE conversations ADD COLUMN IF NOT EXISTS agency_id UUID REFERENCES agencies(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_services_agency ON services(agency_id) WHERE agency_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_projects_agency ON projects(agency_id) WHERE agency_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_conversations_agency ON conversations(agency_id) WHERE agency_id IS NOT NULL;
+
+-- Split of each released agency payment between the agency and the member who did the work
+CREATE TABLE IF NOT EXISTS agency_revenue_splits (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    agency_id UUID NOT NULL REFERENCES agencies(id) ON DELETE CASCADE,
+    project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    payment_id UUID NOT NULL UNIQUE REFERENCES payments(id) ON DELETE CASCADE,
+    member_id UUID NOT NULL REFERENCES users(id),
+    net_amount INTEGER NOT NULL,
+    member_share_percent SMALLINT NOT NULL,
+    member_amount INTEGER NOT NULL,
+    agency_amount INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_agency_revenue_splits_agency ON agency_revenue_splits(agency_id, created_at DESC);
+
+DROP TRIGGER IF EXISTS update_agencies_updated_at ON agencies;
+CREATE TRIGGER update_agencies_updated_at BEFORE UPDATE ON agencies
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+DROP TRIGGER IF EXISTS update_agency_members_updated_at ON agency_members;
+CREATE TRIGGER update_agency_members_updated_at BEFORE UPDATE ON agency_members
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+ALTER TABLE agencies ENABLE ROW LEVEL SECURITY;
+ALTER TABLE agency_members ENABLE ROW LEVEL SECURITY;
+ALTER TABLE agency_revenue_splits ENABLE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS "agencies_service_all" ON agencies;
+CREATE POLICY "agencies_service_all" ON agencies
+    FOR ALL TO service_role
+    USING (true)
+    WITH CHECK (true);
+
+DROP POLICY IF EXISTS "agency_members_service_all" ON agency_members;
+CREATE POLICY "agency_members_service_all" ON agency_members
+    FOR ALL TO service_role
+    USING (true)
+    WITH CHECK (true);
+
+DROP POLICY IF EXISTS "agency_revenue_splits_service_all" ON agency_revenue_splits;
+CREATE POLICY "agency_revenue_splits_service_all" ON agency_revenue_splits
+    FOR ALL TO service_role
+    USING (true)
+    WITH CHECK (true);
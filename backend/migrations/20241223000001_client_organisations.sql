@@ -0,0 +1,122 @@
+-- Client organisations: several buyers of one company with roles, spending limits,
+-- purchase approvals and consolidated invoicing on the organisation's company details
+
+DO $$ BEGIN
+    CREATE TYPE organisation_role AS ENUM ('admin', 'buyer', 'viewer');
+EXCEPTION
+    WHEN duplicate_object THEN null;
+END $$;
+
+DO $$ BEGIN
+    CREATE TYPE purchase_approval_status AS ENUM ('pending', 'approved', 'rejected');
+EXCEPTION
+    WHEN duplicate_object THEN null;
+END $$;
+
+CREATE TABLE IF NOT EXISTS client_organisations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(200) NOT NULL,
+    -- Invoice recipient (CompanyDetails)
+    company_details JSONB NOT NULL DEFAULT '{}',
+    -- Purchases above this amount (cents) by buyers need an admin's approval
+    approval_threshold INTEGER CHECK (approval_threshold >= 0),
+    -- Admin whose billing terms apply and who receives the consolidated invoices
+    billing_contact_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- A user belongs to at most one organisation
+CREATE TABLE IF NOT EXISTS organisation_members (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    organisation_id UUID NOT NULL REFERENCES client_organisations(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL UNIQUE REFERENCES users(id) ON DELETE CASCADE,
+    role organisation_role NOT NULL DEFAULT 'buyer',
+    -- Monthly spending limit in cents (unlimited if NULL)
+    spending_limit INTEGER CHECK (spending_limit >= 0),
+    invited_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    joined_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_organisation_members_organisation ON organisation_members(organisation_id);
+
+ALTER TABLE projects ADD COLUMN IF NOT EXISTS organisation_id UUID REFERENCES client_organisations(id) ON DELETE SET NULL;
+ALTER TABLE conversations ADD COLUMN IF NOT EXISTS organisation_id UUID REFERENCES client_organisations(id) ON DELETE SET NULL;
+ALTER TABLE invoices ADD COLUMN IF NOT EXISTS organisation_id UUID REFERENCES client_organisations(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_projects_organisation ON projects(organisation_id) WHERE organisation_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_conversations_organisation ON conversations(organisation_id) WHERE organisation_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_invoices_organisation ON invoices(organisation_id) WHERE organisation_id IS NOT NULL;
+
+CREATE TABLE IF NOT EXISTS purchase_approvals (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    organisation_id UUID NOT NULL REFERENCES client_organisations(id) ON DELETE CASCADE,
+    requested_by UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- What is to be bought: a service, an accepted proposal or (neither) a direct project request
+    service_id UUID REFERENCES services(id) ON DELETE SET NULL,
+    proposal_id UUID REFERENCES proposals(id) ON DELETE SET NULL,
+    amount INTEGER NOT NULL CHECK (amount > 0),
+    currency currency NOT NULL,
+    note TEXT,
+    status purchase_approval_status NOT NULL DEFAULT 'pending',
+    decided_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    decision_note TEXT,
+    decided_at TIMESTAMPTZ,
+    -- Set when the approved purchase has been made
+    used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_purchase_approvals_organisation ON purchase_approvals(organisation_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_purchase_approvals_requester ON purchase_approvals(requested_by) WHERE status = 'approved' AND used_at IS NULL;
+
+-- Organisation projects count against the billing contact's credit
+CREATE OR REPLACE FUNCTION client_credit_used(client UUID) RETURNS BIGINT AS $$
+    SELECT COALESCE((
+        SELECT SUM(p.price) FROM projects p
+        LEFT JOIN client_organisations o ON o.id = p.organisation_id
+        WHERE COALESCE(o.billing_contact_id, p.client_id) = client
+          AND p.billing_mode = 'invoice' AND p.invoice_id IS NULL
+          AND p.status NOT IN ('cancelled', 'refunded')
+    ), 0) + COALESCE((
+        SELECT SUM(total) FROM invoices
+        WHERE recipient_id = client AND billing_period_start IS NOT NULL AND status = 'open'
+    ), 0)
+$$ LANGUAGE sql STABLE;
+
+DROP TRIGGER IF EXISTS update_client_organisations_updated_at ON client_organisations;
+CREATE TRIGGER update_client_organisations_updated_at BEFORE UPDATE ON client_organisations
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+DROP TRIGGER IF EXISTS update_organisation_members_updated_at ON organisation_members;
+CREATE TRIGGER update_organisation_members_updated_at BEFORE UPDATE ON organisation_members
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+DROP TRIGGER IF EXISTS update_purchase_approvals_updated_at ON purchase_approvals;
+CREATE TRIGGER update_purchase_approvals_updated_at BEFORE UPDATE ON purchase_approvals
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+ALTER TABLE client_organisations ENABLE ROW LEVEL SECURITY;
+ALTER TABLE organisation_members ENABLE ROW LEVEL SECURITY;
+ALTER TABLE purchase_approvals ENABLE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS "client_organisations_service_all" ON client_organisations;
+CREATE POLICY "client_organisations_service_all" ON client_organisations
+    FOR ALL TO service_role
+    USING (true)
+    WITH CHECK (true);
+
+DROP POLICY IF EXISTS "organisation_members_service_all" ON organisation_members;
+CREATE POLICY "organisation_members_service_all" ON organisation_members
+    FOR ALL TO service_role
+    USING (true)
+    WITH CHECK (true);
+
+DROP POLICY IF EXISTS "purchase_approvals_service_all" ON purchase_approvals;
+CREATE POLICY "purchase_approvals_service_all" ON purchase_approvals
+    FOR ALL TO service_role
+    USING (true)
+    WITH CHECK (true);
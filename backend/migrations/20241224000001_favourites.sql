@@ -0,0 +1,58 @@
+-- Favourites: saved experts and services in named lists, lists shared within a client organisation
+
+CREATE TABLE IF NOT EXISTS saved_lists (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    owner_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    description TEXT,
+    -- Set when the list is shared with the owner's organisation (read-only for other members)
+    organisation_id UUID REFERENCES client_organisations(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE(owner_id, name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_saved_lists_organisation ON saved_lists(organisation_id) WHERE organisation_id IS NOT NULL;
+
+-- A saved expert can be in several lists; list_id NULL is the unsorted favourites
+ALTER TABLE saved_experts ADD COLUMN IF NOT EXISTS list_id UUID REFERENCES saved_lists(id) ON DELETE CASCADE;
+ALTER TABLE saved_experts DROP CONSTRAINT IF EXISTS saved_experts_client_id_expert_id_key;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_saved_experts_unique ON saved_experts(
+    client_id, expert_id, COALESCE(list_id, '00000000-0000-0000-0000-000000000000'::uuid)
+);
+CREATE INDEX IF NOT EXISTS idx_saved_experts_expert ON saved_experts(expert_id);
+CREATE INDEX IF NOT EXISTS idx_saved_experts_list ON saved_experts(list_id) WHERE list_id IS NOT NULL;
+
+CREATE TABLE IF NOT EXISTS saved_services (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    client_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    service_id UUID NOT NULL REFERENCES services(id) ON DELETE CASCADE,
+    list_id UUID REFERENCES saved_lists(id) ON DELETE CASCADE,
+    notes TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_saved_services_unique ON saved_services(
+    client_id, service_id, COALESCE(list_id, '00000000-0000-0000-0000-000000000000'::uuid)
+);
+CREATE INDEX IF NOT EXISTS idx_saved_services_client ON saved_services(client_id);
+CREATE INDEX IF NOT EXISTS idx_saved_services_list ON saved_services(list_id) WHERE list_id IS NOT NULL;
+
+DROP TRIGGER IF EXISTS update_saved_lists_updated_at ON saved_lists;
+CREATE TRIGGER update_saved_lists_updated_at BEFORE UPDATE ON saved_lists
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+ALTER TABLE saved_lists ENABLE ROW LEVEL SECURITY;
+ALTER TABLE saved_services ENABLE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS "saved_lists_service_all" ON saved_lists;
+CREATE POLICY "saved_lists_service_all" ON saved_lists
+    FOR ALL TO service_role
+    USING (true)
+    WITH CHECK (true);
+
+DROP POLICY IF EXISTS "saved_services_service_all" ON saved_services;
+CREATE POLICY "saved_services_service_all" ON saved_services
+    FOR ALL TO service_role
+    USING (true)
+    WITH CHECK (true);
@@ -0,0 +1,43 @@
+-- Posting visibility and invitations: clients invite experts to bid, invite-only postings
+-- accept proposals from invited experts only
+
+DO $$ BEGIN
+    CREATE TYPE posting_visibility AS ENUM ('public', 'invite_only');
+EXCEPTION
+    WHEN duplicate_object THEN null;
+END $$;
+
+DO $$ BEGIN
+    CREATE TYPE posting_invitation_status AS ENUM ('pending', 'accepted', 'declined');
+EXCEPTION
+    WHEN duplicate_object THEN null;
+END $$;
+
+ALTER TABLE project_postings ADD COLUMN IF NOT EXISTS visibility posting_visibility NOT NULL DEFAULT 'public';
+
+CREATE TABLE IF NOT EXISTS posting_invitations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    project_posting_id UUID NOT NULL REFERENCES project_postings(id) ON DELETE CASCADE,
+    expert_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    invited_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    message TEXT,
+    status posting_invitation_status NOT NULL DEFAULT 'pending',
+    responded_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE(project_posting_id, expert_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_posting_invitations_expert ON posting_invitations(expert_id, created_at DESC);
+
+DROP TRIGGER IF EXISTS update_posting_invitations_updated_at ON posting_invitations;
+CREATE TRIGGER update_posting_invitations_updated_at BEFORE UPDATE ON posting_invitations
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+ALTER TABLE posting_invitations ENABLE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS "posting_invitations_service_all" ON posting_invitations;
+CREATE POLICY "posting_invitations_service_all" ON posting_invitations
+    FOR ALL TO service_role
+    USING (true)
+    WITH CHECK (true);
@@ -0,0 +1,16 @@
+-- Posting expiry: postings expire after a configurable lifetime or at their deadline, can be
+-- extended or reposted, and are closed if the client does neither
+
+ALTER TYPE project_posting_status ADD VALUE IF NOT EXISTS 'expired' AFTER 'cancelled';
+
+ALTER TABLE project_postings ADD COLUMN IF NOT EXISTS expires_at TIMESTAMPTZ;
+ALTER TABLE project_postings ADD COLUMN IF NOT EXISTS expired_at TIMESTAMPTZ;
+ALTER TABLE project_postings ADD COLUMN IF NOT EXISTS reposted_from_id UUID REFERENCES project_postings(id) ON DELETE SET NULL;
+
+-- Existing postings get the default lifetime of 30 days, counted from now so none expire at once
+UPDATE project_postings
+SET expires_at = LEAST(deadline, GREATEST(created_at, NOW()) + INTERVAL '30 days')
+WHERE expires_at IS NULL AND status IN ('draft', 'open', 'in_review');
+
+CREATE INDEX IF NOT EXISTS idx_project_postings_expires_at ON project_postings(expires_at)
+    WHERE status IN ('open', 'in_review');
@@ -0,0 +1,86 @@
+-- Job alerts: new postings are matched against expert profiles and delivered in-app and by
+-- instant or daily digest email, narrowed by per-expert alert filters
+
+DO $$ BEGIN
+    CREATE TYPE job_alert_frequency AS ENUM ('off', 'instant', 'daily');
+EXCEPTION
+    WHEN duplicate_object THEN null;
+END $$;
+
+-- Postings are matched once; existing postings are treated as already matched
+ALTER TABLE project_postings ADD COLUMN IF NOT EXISTS alerts_matched_at TIMESTAMPTZ;
+UPDATE project_postings SET alerts_matched_at = NOW() WHERE alerts_matched_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_project_postings_alerts_pending ON project_postings(created_at)
+    WHERE alerts_matched_at IS NULL;
+
+CREATE TABLE IF NOT EXISTS job_alert_preferences (
+    expert_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    in_app BOOLEAN NOT NULL DEFAULT TRUE,
+    email_frequency job_alert_frequency NOT NULL DEFAULT 'daily',
+    min_score SMALLINT NOT NULL DEFAULT 60 CHECK (min_score BETWEEN 0 AND 100),
+    last_digest_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS job_alert_filters (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    expert_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    keywords TEXT[] NOT NULL DEFAULT '{}',
+    skills TEXT[] NOT NULL DEFAULT '{}',
+    category_id UUID REFERENCES categories(id) ON DELETE SET NULL,
+    min_budget INTEGER, -- in cents
+    urgent_only BOOLEAN NOT NULL DEFAULT FALSE,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_job_alert_filters_expert ON job_alert_filters(expert_id);
+
+CREATE TABLE IF NOT EXISTS job_alerts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    expert_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    project_posting_id UUID NOT NULL REFERENCES project_postings(id) ON DELETE CASCADE,
+    score SMALLINT NOT NULL,
+    matched_skills TEXT[] NOT NULL DEFAULT '{}',
+    matched_tools TEXT[] NOT NULL DEFAULT '{}',
+    emailed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE(expert_id, project_posting_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_job_alerts_expert ON job_alerts(expert_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_job_alerts_unsent ON job_alerts(expert_id) WHERE emailed_at IS NULL;
+
+DROP TRIGGER IF EXISTS update_job_alert_preferences_updated_at ON job_alert_preferences;
+CREATE TRIGGER update_job_alert_preferences_updated_at BEFORE UPDATE ON job_alert_preferences
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+DROP TRIGGER IF EXISTS update_job_alert_filters_updated_at ON job_alert_filters;
+CREATE TRIGGER update_job_alert_filters_updated_at BEFORE UPDATE ON job_alert_filters
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+ALTER TABLE job_alert_preferences ENABLE ROW LEVEL SECURITY;
+ALTER TABLE job_alert_filters ENABLE ROW LEVEL SECURITY;
+ALTER TABLE job_alerts ENABLE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS "job_alert_preferences_service_all" ON job_alert_preferences;
+CREATE POLICY "job_alert_preferences_service_all" ON job_alert_preferences
+    FOR ALL TO service_role
+    USING (true)
+    WITH CHECK (true);
+
+DROP POLICY IF EXISTS "job_alert_filters_service_all" ON job_alert_filters;
+CREATE POLICY "job_alert_filters_service_all" ON job_alert_filters
+    FOR ALL TO service_role
+    USING (true)
+    WITH CHECK (true);
+
+DROP POLICY IF EXISTS "job_alerts_service_all" ON job_alerts;
+CREATE POLICY "job_alerts_service_all" ON job_alerts
+    FOR ALL TO service_role
+    USING (true)
+    WITH CHECK (true);
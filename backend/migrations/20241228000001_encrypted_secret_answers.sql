@@ -0,0 +1,5 @@
+-- Secret answers are stored AES-GCM encrypted (nonce followed by ciphertext); their `value` stays NULL
+ALTER TABLE project_requirement_answers ADD COLUMN IF NOT EXISTS encrypted_value BYTEA;
+
+-- Purging a secret drops the ciphertext as well
+UPDATE project_requirement_answers SET encrypted_value = NULL WHERE purged_at IS NOT NULL;
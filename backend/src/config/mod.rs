@@ -1,5 +1,10 @@
 use std::env;
 
+use base64::{engine::general_purpose::STANDARD, Engine};
+use sha2::{Digest, Sha256};
+
+use crate::utils::SecretKey;
+
 #[derive(Debug, Clone)]
 pub struct Settings {
     pub server: ServerSettings,
@@ -12,6 +17,17 @@ pub struct Settings {
     pub frontend_url: String,
     pub cors_origins: Vec<String>,
     pub rate_limit: RateLimitSettings,
+    pub tips: TipSettings,
+    pub dunning: DunningSettings,
+    pub billing: BillingSettings,
+    pub deliverables: DeliverableSettings,
+    pub auto_complete: AutoCompleteSettings,
+    pub proposals: ProposalSettings,
+    pub postings: PostingSettings,
+    pub job_alerts: JobAlertSettings,
+    pub bookings: BookingSettings,
+    pub calendar: CalendarSettings,
+    pub secrets: SecretSettings,
 }
 
 #[derive(Debug, Clone)]
@@ -27,6 +43,130 @@ pub struct RateLimitSettings {
     pub burst: u32,
 }
 
+#[derive(Debug, Clone)]
+pub struct TipSettings {
+    /// Platform fee taken from tips (0.0 = expert receives the full tip)
+    pub platform_fee_rate: f64,
+    /// Preset tip options as percentage of the project price
+    pub preset_percentages: Vec<i16>,
+    /// Minimum/maximum tip in cents
+    pub min_amount: i32,
+    pub max_amount: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct DunningSettings {
+    pub enabled: bool,
+    pub interval_minutes: u64,
+    /// Days after due date for Zahlungserinnerung, 1. Mahnung and 2. Mahnung
+    pub stage_days: Vec<i64>,
+    /// Late fee in cents added at each stage (same order as `stage_days`)
+    pub late_fees: Vec<i32>,
+    /// Stage from which the client can no longer start new projects (0 = never)
+    pub pause_projects_at_level: i16,
+    /// Days after due date after which the invoice is marked uncollectible
+    pub uncollectible_after_days: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct BillingSettings {
+    /// Run the monthly invoicing / credit enforcement job
+    pub enabled: bool,
+    pub interval_minutes: u64,
+    /// Platform company details printed on consolidated invoices
+    pub company_name: String,
+    pub street: String,
+    pub postal_code: String,
+    pub city: String,
+    pub country: String,
+    pub vat_id: Option<String>,
+    /// IBAN (or QR-IBAN) for bank transfers
+    pub iban: String,
+    pub bic: Option<String>,
+    /// Block invoice billing once an invoice is this many days overdue
+    pub overdue_block_days: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct AutoCompleteSettings {
+    pub enabled: bool,
+    pub interval_minutes: u64,
+    /// Business days after delivery until the project is completed automatically
+    pub business_days: i64,
+    /// Business days before auto-completion at which the client is reminded (descending)
+    pub reminder_days: Vec<i64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BookingSettings {
+    /// Run the job that expires unanswered booking requests
+    pub expiry_enabled: bool,
+    pub expiry_interval_minutes: u64,
+    /// Hours a requested slot stays reserved while the expert has not answered
+    pub reservation_hours: i64,
+    /// Slots starting sooner than this many hours cannot be booked
+    pub min_notice_hours: i64,
+    /// Maximum number of days per slot query
+    pub max_range_days: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct CalendarSettings {
+    /// Run the job that re-syncs subscribed calendars
+    pub sync_enabled: bool,
+    pub sync_interval_minutes: u64,
+    /// Minutes after which a subscribed calendar is fetched again
+    pub refresh_minutes: i64,
+    /// Days ahead for which imported events block time
+    pub horizon_days: i64,
+    /// Maximum size of an imported calendar in bytes
+    pub max_import_size: usize,
+    pub fetch_timeout_secs: u64,
+    /// Public base URL of the API, used for feed links
+    pub feed_base_url: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProposalSettings {
+    /// Maximum proposals an expert may submit per period (0 = unlimited)
+    pub max_per_period: i64,
+    pub period_days: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct PostingSettings {
+    /// Run the job that expires and closes postings
+    pub expiry_enabled: bool,
+    pub expiry_interval_minutes: u64,
+    /// Days a posting stays open unless its deadline is earlier
+    pub lifetime_days: i64,
+    /// Days an expired posting waits to be extended or reposted before it is closed
+    pub close_after_days: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct JobAlertSettings {
+    /// Run the job that matches new postings to experts and sends alerts
+    pub enabled: bool,
+    pub interval_minutes: u64,
+    /// Hour of the day (UTC) from which daily digests are sent
+    pub digest_hour: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct DeliverableSettings {
+    /// Maximum size of a single deliverable in bytes (presigned uploads)
+    pub max_file_size: i64,
+    /// Lifetime of signed upload/download URLs in seconds
+    pub url_expiry_secs: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct SecretSettings {
+    /// Key encrypting secret requirement answers at rest
+    pub encryption_key: SecretKey,
+}
+
 #[derive(Debug, Clone)]
 pub struct DatabaseSettings {
     pub url: String,
@@ -145,6 +285,17 @@ impl Settings {
                     .parse()
                     .unwrap_or(50),
             },
+            tips: Self::load_tip_settings(),
+            dunning: Self::load_dunning_settings(),
+            billing: Self::load_billing_settings(),
+            deliverables: Self::load_deliverable_settings(),
+            secrets: Self::load_secret_settings()?,
+            auto_complete: Self::load_auto_complete_settings(),
+            proposals: Self::load_proposal_settings(),
+            postings: Self::load_posting_settings(),
+            job_alerts: Self::load_job_alert_settings(),
+            bookings: Self::load_booking_settings(),
+            calendar: Self::load_calendar_settings(),
         })
     }
 
@@ -235,6 +386,266 @@ impl Settings {
         }
     }
 
+    fn load_tip_settings() -> TipSettings {
+        let fee_percent: f64 = env::var("TIP_PLATFORM_FEE_PERCENT")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse()
+            .unwrap_or(0.0);
+
+        TipSettings {
+            platform_fee_rate: fee_percent.clamp(0.0, 100.0) / 100.0,
+            preset_percentages: env::var("TIP_PRESET_PERCENTAGES")
+                .unwrap_or_else(|_| "5,10,15".to_string())
+                .split(',')
+                .filter_map(|s| s.trim().parse().ok())
+                .collect(),
+            min_amount: env::var("TIP_MIN_AMOUNT")
+                .unwrap_or_else(|_| "100".to_string())
+                .parse()
+                .unwrap_or(100),
+            max_amount: env::var("TIP_MAX_AMOUNT")
+                .unwrap_or_else(|_| "100000".to_string())
+                .parse()
+                .unwrap_or(100000),
+        }
+    }
+
+    fn load_auto_complete_settings() -> AutoCompleteSettings {
+        let business_days: i64 = env::var("AUTO_COMPLETE_BUSINESS_DAYS")
+            .unwrap_or_else(|_| "7".to_string())
+            .parse()
+            .unwrap_or(7);
+
+        let mut reminder_days: Vec<i64> = env::var("AUTO_COMPLETE_REMINDER_DAYS")
+            .unwrap_or_else(|_| "3,1".to_string())
+            .split(',')
+            .filter_map(|s| s.trim().parse().ok())
+            .filter(|&d| d > 0 && d < business_days)
+            .collect();
+        reminder_days.sort_unstable_by(|a, b| b.cmp(a));
+        reminder_days.dedup();
+
+        AutoCompleteSettings {
+            enabled: env::var("AUTO_COMPLETE_ENABLED")
+                .map(|v| v != "false")
+                .unwrap_or(true),
+            interval_minutes: env::var("AUTO_COMPLETE_INTERVAL_MINUTES")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .unwrap_or(60),
+            business_days: business_days.max(1),
+            reminder_days,
+        }
+    }
+
+    fn load_booking_settings() -> BookingSettings {
+        BookingSettings {
+            expiry_enabled: env::var("BOOKING_EXPIRY_ENABLED")
+                .map(|v| v != "false")
+                .unwrap_or(true),
+            expiry_interval_minutes: env::var("BOOKING_EXPIRY_INTERVAL_MINUTES")
+                .unwrap_or_else(|_| "15".to_string())
+                .parse()
+                .unwrap_or(15),
+            reservation_hours: env::var("BOOKING_RESERVATION_HOURS")
+                .unwrap_or_else(|_| "48".to_string())
+                .parse::<i64>()
+                .unwrap_or(48)
+                .max(1),
+            min_notice_hours: env::var("BOOKING_MIN_NOTICE_HOURS")
+                .unwrap_or_else(|_| "12".to_string())
+                .parse::<i64>()
+                .unwrap_or(12)
+                .max(0),
+            max_range_days: env::var("BOOKING_MAX_RANGE_DAYS")
+                .unwrap_or_else(|_| "62".to_string())
+                .parse::<i64>()
+                .unwrap_or(62)
+                .max(1),
+        }
+    }
+
+    fn load_calendar_settings() -> CalendarSettings {
+        let max_mb: usize = env::var("CALENDAR_IMPORT_MAX_SIZE_MB")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse()
+            .unwrap_or(5);
+
+        CalendarSettings {
+            sync_enabled: env::var("CALENDAR_SYNC_ENABLED")
+                .map(|v| v != "false")
+                .unwrap_or(true),
+            sync_interval_minutes: env::var("CALENDAR_SYNC_INTERVAL_MINUTES")
+                .unwrap_or_else(|_| "15".to_string())
+                .parse()
+                .unwrap_or(15),
+            refresh_minutes: env::var("CALENDAR_REFRESH_MINUTES")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse::<i64>()
+                .unwrap_or(60)
+                .max(5),
+            horizon_days: env::var("CALENDAR_IMPORT_HORIZON_DAYS")
+                .unwrap_or_else(|_| "180".to_string())
+                .parse::<i64>()
+                .unwrap_or(180)
+                .clamp(1, 730),
+            max_import_size: max_mb.max(1) * 1024 * 1024,
+            fetch_timeout_secs: env::var("CALENDAR_FETCH_TIMEOUT_SECS")
+                .unwrap_or_else(|_| "20".to_string())
+                .parse()
+                .unwrap_or(20),
+            feed_base_url: env::var("API_PUBLIC_URL")
+                .unwrap_or_else(|_| "http://localhost:8080".to_string())
+                .trim_end_matches('/')
+                .to_string(),
+        }
+    }
+
+    fn load_proposal_settings() -> ProposalSettings {
+        ProposalSettings {
+            max_per_period: env::var("PROPOSAL_LIMIT")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
+            period_days: env::var("PROPOSAL_LIMIT_PERIOD_DAYS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse::<i64>()
+                .unwrap_or(30)
+                .max(1),
+        }
+    }
+
+    fn load_posting_settings() -> PostingSettings {
+        PostingSettings {
+            expiry_enabled: env::var("POSTING_EXPIRY_ENABLED")
+                .map(|v| v != "false")
+                .unwrap_or(true),
+            expiry_interval_minutes: env::var("POSTING_EXPIRY_INTERVAL_MINUTES")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .unwrap_or(60),
+            lifetime_days: env::var("POSTING_LIFETIME_DAYS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse::<i64>()
+                .unwrap_or(30)
+                .clamp(1, 365),
+            close_after_days: env::var("POSTING_CLOSE_AFTER_DAYS")
+                .unwrap_or_else(|_| "14".to_string())
+                .parse::<i64>()
+                .unwrap_or(14)
+                .max(0),
+        }
+    }
+
+    fn load_job_alert_settings() -> JobAlertSettings {
+        JobAlertSettings {
+            enabled: env::var("JOB_ALERTS_ENABLED")
+                .map(|v| v != "false")
+                .unwrap_or(true),
+            interval_minutes: env::var("JOB_ALERTS_INTERVAL_MINUTES")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .unwrap_or(10),
+            digest_hour: env::var("JOB_ALERTS_DIGEST_HOUR")
+                .unwrap_or_else(|_| "7".to_string())
+                .parse::<u32>()
+                .unwrap_or(7)
+                .min(23),
+        }
+    }
+
+    /// `SECRETS_ENCRYPTION_KEY` is 32 bytes, base64-encoded. Outside production the key is
+    /// derived from the JWT secret if it is not set.
+    fn load_secret_settings() -> Result<SecretSettings, String> {
+        let key = match env::var("SECRETS_ENCRYPTION_KEY") {
+            Ok(encoded) => STANDARD
+                .decode(encoded.trim())
+                .ok()
+                .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+                .ok_or("SECRETS_ENCRYPTION_KEY must be 32 bytes, base64-encoded")?,
+            Err(_) if env::var("ENVIRONMENT").is_ok_and(|e| e == "production") => {
+                return Err("SECRETS_ENCRYPTION_KEY is required in production".to_string());
+            }
+            Err(_) => {
+                let jwt_secret = env::var("JWT_SECRET").unwrap_or_default();
+                Sha256::digest(format!("secrets-encryption:{}", jwt_secret)).into()
+            }
+        };
+
+        Ok(SecretSettings { encryption_key: SecretKey::new(key) })
+    }
+
+    fn load_deliverable_settings() -> DeliverableSettings {
+        let max_mb: i64 = env::var("DELIVERABLE_MAX_SIZE_MB")
+            .unwrap_or_else(|_| "500".to_string())
+            .parse()
+            .unwrap_or(500);
+
+        DeliverableSettings {
+            max_file_size: max_mb.max(1) * 1024 * 1024,
+            url_expiry_secs: env::var("DELIVERABLE_URL_EXPIRY_SECS")
+                .unwrap_or_else(|_| "900".to_string())
+                .parse()
+                .unwrap_or(900),
+        }
+    }
+
+    fn load_dunning_settings() -> DunningSettings {
+        DunningSettings {
+            enabled: env::var("DUNNING_ENABLED")
+                .map(|v| v != "false")
+                .unwrap_or(true),
+            interval_minutes: env::var("DUNNING_INTERVAL_MINUTES")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .unwrap_or(60),
+            stage_days: env::var("DUNNING_STAGE_DAYS")
+                .unwrap_or_else(|_| "3,14,28".to_string())
+                .split(',')
+                .filter_map(|s| s.trim().parse().ok())
+                .take(3)
+                .collect(),
+            late_fees: env::var("DUNNING_LATE_FEES")
+                .unwrap_or_else(|_| "0,0,0".to_string())
+                .split(',')
+                .filter_map(|s| s.trim().parse().ok())
+                .collect(),
+            pause_projects_at_level: env::var("DUNNING_PAUSE_PROJECTS_AT_LEVEL")
+                .unwrap_or_else(|_| "2".to_string())
+                .parse()
+                .unwrap_or(2),
+            uncollectible_after_days: env::var("DUNNING_UNCOLLECTIBLE_AFTER_DAYS")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .unwrap_or(60),
+        }
+    }
+
+    fn load_billing_settings() -> BillingSettings {
+        BillingSettings {
+            enabled: env::var("BILLING_ENABLED")
+                .map(|v| v != "false")
+                .unwrap_or(true),
+            interval_minutes: env::var("BILLING_INTERVAL_MINUTES")
+                .unwrap_or_else(|_| "360".to_string())
+                .parse()
+                .unwrap_or(360),
+            company_name: env::var("BILLING_COMPANY_NAME")
+                .unwrap_or_else(|_| "DACH Marketplace".to_string()),
+            street: env::var("BILLING_STREET").unwrap_or_default(),
+            postal_code: env::var("BILLING_POSTAL_CODE").unwrap_or_default(),
+            city: env::var("BILLING_CITY").unwrap_or_default(),
+            country: env::var("BILLING_COUNTRY").unwrap_or_else(|_| "CH".to_string()),
+            vat_id: env::var("BILLING_VAT_ID").ok().filter(|v| !v.is_empty()),
+            iban: env::var("BILLING_IBAN").unwrap_or_default(),
+            bic: env::var("BILLING_BIC").ok().filter(|v| !v.is_empty()),
+            overdue_block_days: env::var("BILLING_OVERDUE_BLOCK_DAYS")
+                .unwrap_or_else(|_| "14".to_string())
+                .parse()
+                .unwrap_or(14),
+        }
+    }
+
     pub fn is_production(&self) -> bool {
         self.server.environment == "production"
     }
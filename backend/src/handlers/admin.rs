@@ -9,11 +9,21 @@ use crate::models::{
     Category, CreateCategoryRequest, AccountStatus,
     PaginationParams, PaginatedResponse,
     ContentReport, ContentReportWithDetails, ResolveReportRequest, ReportFilters,
+    BillingMode, ClientProfile, UpdateBillingTermsRequest,
+    ExportFormat, TaxReport, TaxReportQuery, UserRole,
 };
-use crate::services::{AdminService, AdminStats as ServiceAdminStats, UserRow, CategoryService, PendingExpert, ReportService, PlatformAnalytics};
+use crate::services::{AdminService, BillingService, ClientService, AdminStats as ServiceAdminStats, UserRow, CategoryService, PendingExpert, ReportService, PlatformAnalytics, TaxReportService};
 use crate::middleware::AuthUser;
 use super::{ApiError, ApiResult, SuccessResponse, EmptyResponse};
 
+/// Reject non-admin callers
+pub(crate) fn require_admin(user: &AuthUser) -> Result<(), ApiError> {
+    if user.role != UserRole::Admin {
+        return Err(ApiError::Forbidden("Admin access required".to_string()));
+    }
+    Ok(())
+}
+
 #[derive(Debug, Serialize)]
 pub struct AdminStatsResponse {
     pub total_users: i64,
@@ -305,3 +315,110 @@ pub async fn get_analytics(
 
     Ok(Json(SuccessResponse::new(analytics)))
 }
+
+// ============ Client Billing Handlers ============
+
+/// Set invoice billing terms for a client (admin only)
+pub async fn update_client_billing_terms(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthUser>,
+    Path(client_id): Path<Uuid>,
+    Json(payload): Json<UpdateBillingTermsRequest>,
+) -> ApiResult<ClientProfile> {
+    use validator::Validate;
+    require_admin(&user)?;
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+
+    let profile = ClientService::get_profile_by_user(state.db.pool(), client_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::NotFound("Client profile not found".to_string()))?;
+
+    if payload.billing_mode == BillingMode::Invoice && !profile.is_verified {
+        return Err(ApiError::BadRequest("Invoice billing requires a verified client profile".to_string()));
+    }
+
+    let profile = BillingService::set_billing_terms(state.db.pool(), client_id, user.id, &payload)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => ApiError::NotFound("Client profile not found".into()),
+            _ => ApiError::Internal(e.into()),
+        })?;
+
+    Ok(Json(SuccessResponse::new(profile)))
+}
+
+/// Lift a client's invoice billing block (admin only)
+pub async fn unblock_client_billing(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthUser>,
+    Path(client_id): Path<Uuid>,
+) -> ApiResult<ClientProfile> {
+    require_admin(&user)?;
+
+    let profile = BillingService::unblock(state.db.pool(), client_id)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => ApiError::NotFound("Client profile not found".into()),
+            _ => ApiError::Internal(e.into()),
+        })?;
+
+    Ok(Json(SuccessResponse::new(profile)))
+}
+
+/// Load platform revenue for a quarter and build the VAT report
+async fn build_tax_report(state: &AppState, query: &TaxReportQuery) -> Result<TaxReport, ApiError> {
+    use validator::Validate;
+    query.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+
+    let (start, end) = TaxReportService::quarter_bounds(query.year, query.quarter)
+        .ok_or_else(|| ApiError::BadRequest("Invalid reporting period".to_string()))?;
+
+    let items = TaxReportService::get_taxable_items(state.db.pool(), start, end)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    TaxReportService::build(&state.settings.billing.country, query.year, query.quarter, &items)
+        .ok_or_else(|| ApiError::BadRequest("Invalid reporting period".to_string()))
+}
+
+/// Quarterly VAT report for platform revenue (admin only)
+pub async fn get_tax_report(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthUser>,
+    Query(query): Query<TaxReportQuery>,
+) -> ApiResult<TaxReport> {
+    require_admin(&user)?;
+    let report = build_tax_report(&state, &query).await?;
+
+    Ok(Json(SuccessResponse::new(report)))
+}
+
+/// Export a VAT report section as CSV or XML (admin only)
+pub async fn export_tax_report(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthUser>,
+    Query(query): Query<TaxReportQuery>,
+) -> Result<axum::response::Response, ApiError> {
+    use axum::http::header;
+    use axum::response::IntoResponse;
+
+    require_admin(&user)?;
+    let report = build_tax_report(&state, &query).await?;
+
+    let section = format!("{:?}", query.report).to_lowercase();
+    let (body, content_type, extension) = match query.format {
+        ExportFormat::Csv => (TaxReportService::to_csv(&report, query.report), "text/csv; charset=utf-8", "csv"),
+        ExportFormat::Xml => (TaxReportService::to_xml(&report, query.report), "application/xml; charset=utf-8", "xml"),
+    };
+    let file_name = format!("vat-{}-{}-q{}.{}", section, report.year, report.quarter, extension);
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", file_name)),
+        ],
+        body,
+    )
+        .into_response())
+}
@@ -0,0 +1,327 @@
+//! Agencies: team members, agency services and projects, shared inbox and revenue splits
+
+use axum::{extract::{Path, Query, State}, Extension, Json};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::AppState;
+use crate::models::{
+    AddAgencyMemberRequest, Agency, AgencyEarnings, AgencyMember, AgencyMembership, AgencyReplyRequest, AgencyRole,
+    AssignProjectRequest, Conversation, CreateAgencyRequest, Message, PaginatedResponse, PaginationMeta,
+    PaginationParams, Project, Service, UpdateAgencyMemberRequest, UpdateAgencyRequest,
+};
+use crate::services::{AgencyService, ExpertService, MessageService, ServiceService};
+use crate::middleware::auth::AuthUser;
+use super::{ApiError, ApiResult, EmptyResponse, SuccessResponse};
+
+/// Role of the current user in the agency; fails for non-members
+async fn require_role(state: &AppState, agency_id: Uuid, user_id: Uuid) -> Result<AgencyRole, ApiError> {
+    AgencyService::role(&state.db, agency_id, user_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::Forbidden("Not a member of this agency".to_string()))
+}
+
+/// Role of the current user if they may manage the agency (owner or manager)
+async fn require_manager(state: &AppState, agency_id: Uuid, user_id: Uuid) -> Result<AgencyRole, ApiError> {
+    let role = require_role(state, agency_id, user_id).await?;
+    if !role.can_manage() {
+        return Err(ApiError::Forbidden("Only owners and managers can do this".to_string()));
+    }
+
+    Ok(role)
+}
+
+async fn get_agency_or_404(state: &AppState, id: Uuid) -> Result<Agency, ApiError> {
+    AgencyService::get(&state.db, id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::NotFound("Agency not found".to_string()))
+}
+
+/// Create an agency (expert); the creator becomes its owner
+pub async fn create_agency(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<CreateAgencyRequest>,
+) -> ApiResult<Agency> {
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+
+    ExpertService::get_by_user_id(&state.db, auth_user.id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::BadRequest("You must have an expert profile to create an agency".to_string()))?;
+
+    let agency = AgencyService::create(&state.db, auth_user.id, &payload).await?;
+
+    Ok(Json(SuccessResponse::new(agency)))
+}
+
+/// Agency of the current user with their role
+pub async fn get_my_agency(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> ApiResult<AgencyMembership> {
+    let membership = AgencyService::membership(&state.db, auth_user.id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::NotFound("You are not a member of an agency".to_string()))?;
+
+    Ok(Json(SuccessResponse::new(membership)))
+}
+
+/// Public agency profile
+pub async fn get_agency(State(state): State<AppState>, Path(id): Path<Uuid>) -> ApiResult<Agency> {
+    let agency = get_agency_or_404(&state, id).await?;
+
+    Ok(Json(SuccessResponse::new(agency)))
+}
+
+/// Update the agency profile and default revenue share (owner)
+pub async fn update_agency(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateAgencyRequest>,
+) -> ApiResult<Agency> {
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+    if require_role(&state, id, auth_user.id).await? != AgencyRole::Owner {
+        return Err(ApiError::Forbidden("Only the owner can update the agency".to_string()));
+    }
+
+    let agency = AgencyService::update(&state.db, id, &payload)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::NotFound("Agency not found".to_string()))?;
+
+    Ok(Json(SuccessResponse::new(agency)))
+}
+
+/// Services sold under the agency
+pub async fn get_agency_services(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(pagination): Query<PaginationParams>,
+) -> ApiResult<PaginatedResponse<Service>> {
+    let per_page = pagination.per_page.min(50);
+    let (services, total) = ServiceService::get_by_agency(&state.db, id, pagination.page, per_page)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(PaginatedResponse {
+        data: services,
+        meta: PaginationMeta::new(pagination.page, per_page, total),
+    })))
+}
+
+// ==================== Members ====================
+
+/// Members of the agency (members only)
+pub async fn list_members(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Vec<AgencyMember>> {
+    require_role(&state, id, auth_user.id).await?;
+
+    let members = AgencyService::list_members(&state.db, id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(members)))
+}
+
+/// Add an expert to the agency; managers can only add members with the default share
+pub async fn add_member(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<AddAgencyMemberRequest>,
+) -> ApiResult<AgencyMember> {
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+
+    let role = require_manager(&state, id, auth_user.id).await?;
+    let elevated = payload.role.is_some_and(|r| r != AgencyRole::Member) || payload.revenue_share_percent.is_some();
+    if role != AgencyRole::Owner && elevated {
+        return Err(ApiError::Forbidden("Only the owner can set roles and revenue shares".to_string()));
+    }
+
+    let agency = get_agency_or_404(&state, id).await?;
+    let member = AgencyService::add_member(&state.db, &agency, auth_user.id, &payload).await?;
+
+    Ok(Json(SuccessResponse::new(member)))
+}
+
+/// Change a member's role or revenue share (owner)
+pub async fn update_member(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, user_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<UpdateAgencyMemberRequest>,
+) -> ApiResult<AgencyMember> {
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+    if require_role(&state, id, auth_user.id).await? != AgencyRole::Owner {
+        return Err(ApiError::Forbidden("Only the owner can change members".to_string()));
+    }
+
+    let member = AgencyService::update_member(&state.db, id, user_id, &payload).await?;
+
+    Ok(Json(SuccessResponse::new(member)))
+}
+
+/// Remove a member; members can leave, managers remove members, the owner removes anyone
+pub async fn remove_member(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, user_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<EmptyResponse>, ApiError> {
+    let role = require_role(&state, id, auth_user.id).await?;
+
+    if user_id != auth_user.id {
+        let target = AgencyService::role(&state.db, id, user_id)
+            .await
+            .map_err(|e| ApiError::Internal(e.into()))?
+            .ok_or_else(|| ApiError::NotFound("Member not found".to_string()))?;
+
+        let allowed = match role {
+            AgencyRole::Owner => true,
+            AgencyRole::Manager => target == AgencyRole::Member,
+            AgencyRole::Member => false,
+        };
+        if !allowed {
+            return Err(ApiError::Forbidden("Not authorized to remove this member".to_string()));
+        }
+    }
+
+    AgencyService::remove_member(&state.db, id, user_id).await?;
+
+    Ok(Json(EmptyResponse::new("Member removed")))
+}
+
+// ==================== Projects ====================
+
+/// Projects of the agency (owner or manager)
+pub async fn list_projects(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Query(pagination): Query<PaginationParams>,
+) -> ApiResult<PaginatedResponse<Project>> {
+    require_manager(&state, id, auth_user.id).await?;
+
+    let per_page = pagination.per_page.min(50);
+    let (projects, total) = AgencyService::list_projects(&state.db, id, pagination.page, per_page)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(PaginatedResponse {
+        data: projects,
+        meta: PaginationMeta::new(pagination.page, per_page, total),
+    })))
+}
+
+/// Assign an agency project to a member (owner or manager)
+pub async fn assign_project(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, project_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<AssignProjectRequest>,
+) -> ApiResult<Project> {
+    require_manager(&state, id, auth_user.id).await?;
+
+    let project = AgencyService::assign_project(&state.db, id, project_id, payload.member_id, auth_user.id).await?;
+
+    Ok(Json(SuccessResponse::new(project)))
+}
+
+/// Revenue splits of the agency's released payments (owner or manager)
+pub async fn get_earnings(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<AgencyEarnings> {
+    require_manager(&state, id, auth_user.id).await?;
+
+    let earnings = AgencyService::earnings(&state.db, id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(earnings)))
+}
+
+// ==================== Shared inbox ====================
+
+/// Conversations about the agency's services and projects (members)
+pub async fn get_inbox(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Query(pagination): Query<PaginationParams>,
+) -> ApiResult<PaginatedResponse<Conversation>> {
+    require_role(&state, id, auth_user.id).await?;
+
+    let per_page = pagination.per_page.min(50);
+    let (conversations, total) = AgencyService::inbox(&state.db, id, pagination.page, per_page)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(PaginatedResponse {
+        data: conversations,
+        meta: PaginationMeta::new(pagination.page, per_page, total),
+    })))
+}
+
+/// Messages of a conversation in the shared inbox (members)
+pub async fn get_inbox_messages(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, conversation_id)): Path<(Uuid, Uuid)>,
+    Query(pagination): Query<PaginationParams>,
+) -> ApiResult<PaginatedResponse<Message>> {
+    require_role(&state, id, auth_user.id).await?;
+
+    AgencyService::inbox_conversation(&state.db, id, conversation_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::NotFound("Conversation not found".to_string()))?;
+
+    let per_page = pagination.per_page.min(100);
+    let (messages, total) = MessageService::list_messages(state.db.pool(), conversation_id, pagination.page, per_page)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(PaginatedResponse {
+        data: messages,
+        meta: PaginationMeta::new(pagination.page, per_page, total),
+    })))
+}
+
+/// Reply in a conversation of the shared inbox on behalf of the agency (members)
+pub async fn reply_in_inbox(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, conversation_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<AgencyReplyRequest>,
+) -> ApiResult<Message> {
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+    require_role(&state, id, auth_user.id).await?;
+
+    let (_, participant) = AgencyService::inbox_conversation(&state.db, id, conversation_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::NotFound("Conversation not found".to_string()))?;
+    let participant = participant
+        .ok_or_else(|| ApiError::Conflict("No agency member takes part in this conversation".to_string()))?;
+
+    let message = MessageService::send_on_behalf(
+        state.db.pool(),
+        auth_user.id,
+        participant,
+        conversation_id,
+        &payload.content,
+    )
+    .await
+    .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(message)))
+}
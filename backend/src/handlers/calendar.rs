@@ -0,0 +1,192 @@
+//! Calendar sync handlers: ICS feed of an expert and imported calendars
+
+use axum::{
+    extract::{Multipart, Path, State},
+    http::header,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::{ApiError, ApiResult, EmptyResponse, SuccessResponse};
+use crate::middleware::auth::AuthUser;
+use crate::models::{CalendarFeed, CalendarImport, CreateCalendarImportRequest, ExpertProfile};
+use crate::services::{CalendarError, CalendarService, ExpertService};
+use crate::AppState;
+
+async fn own_profile(state: &AppState, user_id: Uuid) -> Result<ExpertProfile, ApiError> {
+    ExpertService::get_by_user_id(&state.db, user_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::BadRequest("You must have an expert profile".to_string()))
+}
+
+fn feed(state: &AppState, token: String) -> CalendarFeed {
+    CalendarFeed {
+        url: format!("{}/api/v1/experts/calendar/{}", state.settings.calendar.feed_base_url, token),
+        token,
+    }
+}
+
+/// ICS feed URL of the current expert (created on first access)
+pub async fn get_feed(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> ApiResult<CalendarFeed> {
+    let expert = own_profile(&state, auth_user.id).await?;
+
+    let token = CalendarService::feed_token(&state.db, expert.id, false)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(feed(&state, token))))
+}
+
+/// Replace the feed token; the previous URL stops working
+pub async fn rotate_feed(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> ApiResult<CalendarFeed> {
+    let expert = own_profile(&state, auth_user.id).await?;
+
+    let token = CalendarService::feed_token(&state.db, expert.id, true)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(feed(&state, token))))
+}
+
+/// Public ICS feed (the secret token authorizes access)
+pub async fn export_feed(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> Result<Response, ApiError> {
+    let calendar = CalendarService::export_feed(&state.db, token.trim_end_matches(".ics"))
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::NotFound("Calendar not found".to_string()))?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/calendar; charset=utf-8"),
+            (header::CACHE_CONTROL, "private, max-age=300"),
+        ],
+        calendar,
+    )
+        .into_response())
+}
+
+/// Imported calendars of the current expert
+pub async fn list_imports(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> ApiResult<Vec<CalendarImport>> {
+    let expert = own_profile(&state, auth_user.id).await?;
+
+    let imports = CalendarService::list_imports(&state.db, expert.id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(imports)))
+}
+
+/// Subscribe to an ICS calendar URL
+pub async fn subscribe(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<CreateCalendarImportRequest>,
+) -> ApiResult<CalendarImport> {
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+    let expert = own_profile(&state, auth_user.id).await?;
+
+    let import = CalendarService::subscribe(&state.db, expert.id, &payload, &state.settings.calendar).await?;
+
+    Ok(Json(SuccessResponse::new(import)))
+}
+
+/// Import an ICS file (multipart: `name`, `file`)
+pub async fn upload(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    mut multipart: Multipart,
+) -> ApiResult<CalendarImport> {
+    let expert = own_profile(&state, auth_user.id).await?;
+
+    let mut name = None;
+    let mut file = None;
+
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| ApiError::BadRequest(format!("Invalid multipart data: {}", e)))?
+    {
+        match field.name().unwrap_or("") {
+            "name" => name = Some(field.text().await.map_err(|e| ApiError::BadRequest(e.to_string()))?),
+            "file" => {
+                let file_name = field.file_name().unwrap_or("calendar.ics").to_string();
+                let data = field
+                    .bytes()
+                    .await
+                    .map_err(|e| ApiError::BadRequest(format!("Upload failed: {}", e)))?;
+                file = Some((file_name, data));
+            }
+            _ => {}
+        }
+    }
+
+    let (file_name, data) = file.ok_or_else(|| ApiError::Validation("No file provided".to_string()))?;
+    if data.len() > state.settings.calendar.max_import_size {
+        return Err(ApiError::Validation("File too large".to_string()));
+    }
+    let content =
+        std::str::from_utf8(&data).map_err(|_| ApiError::Validation("Calendar is not valid UTF-8".to_string()))?;
+
+    let name = name
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty())
+        .unwrap_or_else(|| file_name.trim_end_matches(".ics").to_string());
+    if name.chars().count() > 100 {
+        return Err(ApiError::Validation("Name too long".to_string()));
+    }
+
+    let import = CalendarService::import_file(&state.db, expert.id, &name, content, &state.settings.calendar).await?;
+
+    Ok(Json(SuccessResponse::new(import)))
+}
+
+/// Fetch a subscribed calendar now
+pub async fn sync_import(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<CalendarImport> {
+    let expert = own_profile(&state, auth_user.id).await?;
+
+    let import = CalendarService::get_import(&state.db, id, expert.id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or(CalendarError::NotFound)?;
+
+    let import = CalendarService::sync(&state.db, &import, &state.settings.calendar).await?;
+
+    Ok(Json(SuccessResponse::new(import)))
+}
+
+/// Remove an imported calendar and the times it blocked
+pub async fn delete_import(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<EmptyResponse>, ApiError> {
+    let expert = own_profile(&state, auth_user.id).await?;
+
+    let deleted = CalendarService::delete_import(&state.db, id, expert.id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+    if !deleted {
+        return Err(CalendarError::NotFound.into());
+    }
+
+    Ok(Json(EmptyResponse::new("Calendar removed")))
+}
@@ -0,0 +1,119 @@
+//! Cancellation policies and project cancellation details
+
+use axum::{extract::{Path, State}, Extension, Json};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::AppState;
+use crate::models::{
+    CancellationBreakdown, CancellationParty, CancellationPolicy, CreateCancellationPolicyRequest,
+    OrganisationRole, Project, ProjectActor, ProjectCancellation, UserRole,
+};
+use crate::services::{AgencyService, CancellationService, OrganisationService, ProjectService};
+use crate::middleware::auth::AuthUser;
+use super::{ApiError, ApiResult, SuccessResponse};
+
+/// Load a project and determine the user's role in it (agency managers count as expert,
+/// organisation admins and buyers as client)
+pub(crate) async fn get_project_for_party(
+    state: &AppState,
+    id: Uuid,
+    user_id: Uuid,
+) -> Result<(Project, CancellationParty), ApiError> {
+    let project = ProjectService::get_by_id(&state.db, id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::NotFound("Project not found".to_string()))?;
+
+    let party = if project.client_id == user_id {
+        CancellationParty::Client
+    } else if project.expert_id == user_id
+        || AgencyService::manages_project(&state.db, &project, user_id)
+            .await
+            .map_err(|e| ApiError::Internal(e.into()))?
+    {
+        // Agency owners and managers act for the member working on the project
+        CancellationParty::Expert
+    } else if OrganisationService::project_role(&state.db, &project, user_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .is_some_and(OrganisationRole::can_purchase)
+    {
+        CancellationParty::Client
+    } else {
+        return Err(ApiError::Forbidden("Not authorized".to_string()));
+    };
+
+    Ok((project, party))
+}
+
+/// Timeline actor of a project party
+pub(crate) fn party_actor(party: CancellationParty) -> ProjectActor {
+    match party {
+        CancellationParty::Client => ProjectActor::Client,
+        CancellationParty::Expert => ProjectActor::Expert,
+        CancellationParty::Admin => ProjectActor::Admin,
+    }
+}
+
+/// List cancellation policies available to the current user
+pub async fn list_policies(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> ApiResult<Vec<CancellationPolicy>> {
+    let policies = CancellationService::list_policies(&state.db, auth_user.id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(policies)))
+}
+
+/// Create a cancellation policy (experts)
+pub async fn create_policy(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<CreateCancellationPolicyRequest>,
+) -> ApiResult<CancellationPolicy> {
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+
+    if auth_user.role != UserRole::Expert && auth_user.role != UserRole::Admin {
+        return Err(ApiError::Forbidden("Only experts can create cancellation policies".to_string()));
+    }
+
+    let policy = CancellationService::create_policy(&state.db, auth_user.id, &payload)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(policy)))
+}
+
+/// Preview refund and compensation if the current user cancelled now
+pub async fn preview_cancellation(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<CancellationBreakdown> {
+    let (project, party) = get_project_for_party(&state, id, auth_user.id).await?;
+
+    let (_, breakdown) = CancellationService::preview(&state.db, &project, party)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(breakdown)))
+}
+
+/// Get the applied cancellation of a project
+pub async fn get_cancellation(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<ProjectCancellation> {
+    get_project_for_party(&state, id, auth_user.id).await?;
+
+    let cancellation = CancellationService::get_for_project(&state.db, id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::NotFound("Project has not been cancelled".to_string()))?;
+
+    Ok(Json(SuccessResponse::new(cancellation)))
+}
@@ -134,8 +134,9 @@ pub async fn get_category_services(
         r#"
         SELECT id, expert_id, category_id, title, slug, description, short_description,
                pricing_type, price, currency, delivery_time_days, revisions_included,
-               features, requirements, tags, images, video_url, is_active, is_featured,
-               view_count, order_count, rating_average, rating_count, created_at, updated_at
+               features, requirements, requirements_form, tags, images, video_url, is_active, is_featured,
+               view_count, order_count, rating_average, rating_count, cancellation_policy_id, agency_id,
+               created_at, updated_at
         FROM services
         WHERE category_id = $1 AND is_active = true
         ORDER BY is_featured DESC, rating_average DESC, order_count DESC
@@ -0,0 +1,250 @@
+//! Change requests: renegotiating scope, price, delivery date and revisions of a running project
+
+use axum::{extract::{Path, State}, Extension, Json};
+use chrono::Utc;
+use std::collections::HashMap;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::AppState;
+use crate::models::{
+    CancellationParty, ChangeRequestCheckoutResponse, ChangeRequestStatus, CreateChangeRequestRequest, Project,
+    ProjectActor, ProjectChangeRequest, ProjectTerms, RespondChangeRequestRequest,
+};
+use crate::services::{ChangeRequestService, PaymentService};
+use crate::middleware::auth::AuthUser;
+use super::{ApiError, ApiResult, SuccessResponse};
+use super::cancellations::{get_project_for_party, party_actor};
+use super::payments::refund_at_gateway;
+
+/// Load a change request of the project
+async fn get_change_request(state: &AppState, project_id: Uuid, id: Uuid) -> Result<ProjectChangeRequest, ApiError> {
+    ChangeRequestService::get(&state.db, project_id, id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::NotFound("Change request not found".to_string()))
+}
+
+/// Load a change request the current user may answer (the party that did not propose it)
+async fn get_for_response(
+    state: &AppState,
+    project_id: Uuid,
+    id: Uuid,
+    user_id: Uuid,
+) -> Result<ProjectActor, ApiError> {
+    let (_, party) = get_project_for_party(state, project_id, user_id).await?;
+    let actor = party_actor(party);
+
+    let request = get_change_request(state, project_id, id).await?;
+    if request.requested_by_role == actor {
+        return Err(ApiError::Forbidden("Only the other party can answer a change request".to_string()));
+    }
+
+    Ok(actor)
+}
+
+/// List change requests of a project (client or expert)
+pub async fn list_change_requests(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Vec<ProjectChangeRequest>> {
+    get_project_for_party(&state, id, auth_user.id).await?;
+
+    let requests = ChangeRequestService::list(&state.db, id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(requests)))
+}
+
+/// Propose a change of scope, price, delivery date or revisions
+pub async fn create_change_request(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<CreateChangeRequestRequest>,
+) -> ApiResult<ProjectChangeRequest> {
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+
+    let (project, party) = get_project_for_party(&state, id, auth_user.id).await?;
+    let terms = payload
+        .proposed_terms(&ProjectTerms::of(&project), project.revisions_used, Utc::now())
+        .map_err(ApiError::Validation)?;
+
+    let request = ChangeRequestService::create(
+        &state.db,
+        id,
+        party_actor(party),
+        auth_user.id,
+        payload.description.trim(),
+        &terms,
+    )
+    .await?;
+
+    Ok(Json(SuccessResponse::new(request)))
+}
+
+/// Accept a change request; the project takes over the new terms
+pub async fn accept_change_request(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, change_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<RespondChangeRequestRequest>,
+) -> ApiResult<Project> {
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+
+    let actor = get_for_response(&state, id, change_id, auth_user.id).await?;
+    let mut tx = state.db.pool().begin().await?;
+    let accepted = ChangeRequestService::accept_tx(
+        &mut tx,
+        id,
+        change_id,
+        actor,
+        auth_user.id,
+        payload.message.as_deref(),
+    )
+    .await?;
+
+    // Refund at the gateway before committing, so a failed refund leaves the request pending
+    if let (Some(payment), Some(amount)) = (&accepted.refunded_payment, accepted.change_request.refund_amount) {
+        refund_at_gateway(payment, amount).await?;
+    }
+    tx.commit().await?;
+
+    Ok(Json(SuccessResponse::new(accepted.project)))
+}
+
+/// Reject a change request (the other party)
+pub async fn reject_change_request(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, change_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<RespondChangeRequestRequest>,
+) -> ApiResult<ProjectChangeRequest> {
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+
+    let actor = get_for_response(&state, id, change_id, auth_user.id).await?;
+    let request = ChangeRequestService::close(
+        &state.db,
+        id,
+        change_id,
+        ChangeRequestStatus::Rejected,
+        actor,
+        auth_user.id,
+        payload.message.as_deref(),
+    )
+    .await?;
+
+    Ok(Json(SuccessResponse::new(request)))
+}
+
+/// Withdraw an own change request before it is answered
+pub async fn withdraw_change_request(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, change_id)): Path<(Uuid, Uuid)>,
+) -> ApiResult<ProjectChangeRequest> {
+    let (_, party) = get_project_for_party(&state, id, auth_user.id).await?;
+    let actor = party_actor(party);
+
+    let request = get_change_request(&state, id, change_id).await?;
+    if request.requested_by_role != actor {
+        return Err(ApiError::Forbidden("Only the requester can withdraw a change request".to_string()));
+    }
+
+    let request = ChangeRequestService::close(
+        &state.db,
+        id,
+        change_id,
+        ChangeRequestStatus::Withdrawn,
+        actor,
+        auth_user.id,
+        None,
+    )
+    .await?;
+
+    Ok(Json(SuccessResponse::new(request)))
+}
+
+/// Start the checkout for the additional charge of an accepted price increase (client)
+pub async fn checkout_change_request(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, change_id)): Path<(Uuid, Uuid)>,
+) -> ApiResult<ChangeRequestCheckoutResponse> {
+    let (project, party) = get_project_for_party(&state, id, auth_user.id).await?;
+    if party != CancellationParty::Client {
+        return Err(ApiError::Forbidden("Only the client can pay a change request".to_string()));
+    }
+
+    let request = get_change_request(&state, id, change_id).await?;
+    let payment_id = match (&request.status, request.payment_id, request.refund_amount, request.paid_at) {
+        (ChangeRequestStatus::Accepted, Some(payment_id), None, None) => payment_id,
+        _ => return Err(ApiError::Conflict("Change request has no outstanding charge".to_string())),
+    };
+    let payment = PaymentService::get_by_id(state.db.pool(), payment_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::NotFound("Payment not found".to_string()))?;
+
+    let mut metadata = HashMap::new();
+    metadata.insert("kind".to_string(), "change_request".to_string());
+    metadata.insert("change_request_id".to_string(), request.id.to_string());
+    metadata.insert("project_id".to_string(), project.id.to_string());
+
+    let success_url = format!("{}/projects/{}?change=success", state.settings.frontend_url, project.id);
+    let cancel_url = format!("{}/projects/{}", state.settings.frontend_url, project.id);
+
+    #[cfg(feature = "payments")]
+    let (session_id, checkout_url) = {
+        use crate::services::payment_service::stripe_service::StripeService;
+
+        let stripe_key = std::env::var("STRIPE_SECRET_KEY")
+            .map_err(|_| ApiError::Internal(anyhow::anyhow!("Stripe not configured")))?;
+
+        let stripe_account: Option<(Option<String>,)> = sqlx::query_as(
+            "SELECT stripe_account_id FROM expert_profiles WHERE user_id = $1"
+        )
+        .bind(project.expert_id)
+        .fetch_optional(state.db.pool())
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+        let session = StripeService::new(&stripe_key)
+            .create_checkout_session(
+                &format!("Leistungsänderung - {}", project.title),
+                payment.amount as i64,
+                &payment.currency,
+                &success_url,
+                &cancel_url,
+                metadata,
+                stripe_account.and_then(|(id,)| id).as_deref(),
+                payment.platform_fee as i64,
+            )
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!("Stripe error: {}", e)))?;
+
+        (session.id.to_string(), session.url.unwrap_or_default())
+    };
+
+    #[cfg(not(feature = "payments"))]
+    let (session_id, checkout_url) = {
+        // Mock checkout for development without Stripe
+        let _ = (metadata, success_url, cancel_url, payment);
+        (
+            format!("cs_test_{}", Uuid::new_v4()),
+            format!("{}/checkout/mock?changeRequest={}", state.settings.frontend_url, request.id),
+        )
+    };
+
+    let change_request = ChangeRequestService::set_checkout_session(&state.db, request.id, &session_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(ChangeRequestCheckoutResponse {
+        change_request,
+        session_id,
+        checkout_url,
+    })))
+}
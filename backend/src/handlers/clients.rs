@@ -10,9 +10,15 @@ use crate::{
         ProjectPosting, CreateProjectPostingRequest, UpdateProjectPostingRequest,
         ProjectPostingFilters, PaginationParams, PaginatedResponse,
         BookingRequest, CreateBookingRequest, RespondBookingRequest,
-        Proposal, CreateProposalRequest,
+        Proposal, ProposalAcceptance, ProposedMilestone, CreateProposalRequest, UpdateProposalRequest,
+        RejectProposalRequest, ProposalComparison, ProposalComparisonQuery, CreditStatus, PurchaseSubject,
+        InviteExpertsRequest, PostingInvitation, ReceivedPostingInvitation,
+        ExtendPostingRequest, RepostPostingRequest, CancelPostingRequest,
+    },
+    services::{
+        BillingService, BookingService, ClientService, OrganisationService, PostingInvitationService,
+        PostingService, ProposalService,
     },
-    services::ClientService,
 };
 
 use super::common::{ApiResponse, ApiError, EmptyResponse};
@@ -56,6 +62,17 @@ pub async fn update_profile(
     Ok(Json(ApiResponse::success(profile)))
 }
 
+pub async fn get_billing_status(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<CreditStatus>>, ApiError> {
+    let status = BillingService::get_credit_status(state.db.pool(), user.id).await
+        .map_err(|e| ApiError::internal(e.to_string()))?
+        .ok_or_else(|| ApiError::not_found("Client profile not found"))?;
+
+    Ok(Json(ApiResponse::success(status)))
+}
+
 // ==================== Project Posting Handlers ====================
 
 pub async fn list_project_postings(
@@ -71,15 +88,35 @@ pub async fn list_project_postings(
 
 pub async fn get_project_posting(
     State(state): State<AppState>,
+    user: Option<Extension<AuthUser>>,
     Path(id): Path<Uuid>,
 ) -> Result<Json<ApiResponse<ProjectPosting>>, ApiError> {
     let posting = ClientService::get_project_posting(state.db.pool(), id).await
         .map_err(|e| ApiError::internal(e.to_string()))?
         .ok_or_else(|| ApiError::not_found("Project posting not found"))?;
 
+    let viewer = user.map(|Extension(user)| user.id);
+    if !PostingInvitationService::can_view(&state.db, &posting, viewer).await
+        .map_err(|e| ApiError::internal(e.to_string()))?
+    {
+        return Err(ApiError::not_found("Project posting not found"));
+    }
+
     Ok(Json(ApiResponse::success(posting)))
 }
 
+/// Postings of the current client, including invite-only ones
+pub async fn list_my_project_postings(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Query(pagination): Query<PaginationParams>,
+) -> Result<Json<PaginatedResponse<ProjectPosting>>, ApiError> {
+    let postings = ClientService::list_client_postings(state.db.pool(), user.id, pagination.page, pagination.per_page).await
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+
+    Ok(Json(postings))
+}
+
 pub async fn create_project_posting(
     State(state): State<AppState>,
     Extension(user): Extension<AuthUser>,
@@ -87,7 +124,7 @@ pub async fn create_project_posting(
 ) -> Result<(StatusCode, Json<ApiResponse<ProjectPosting>>), ApiError> {
     req.validate().map_err(|e| ApiError::validation(e.to_string()))?;
 
-    let posting = ClientService::create_project_posting(state.db.pool(), user.id, req).await
+    let posting = ClientService::create_project_posting(state.db.pool(), user.id, req, &state.settings.postings).await
         .map_err(|e| ApiError::internal(e.to_string()))?;
 
     Ok((StatusCode::CREATED, Json(ApiResponse::success(posting))))
@@ -118,6 +155,107 @@ pub async fn delete_project_posting(
     Ok(Json(ApiResponse::success(EmptyResponse::new("Project posting deleted"))))
 }
 
+/// Keep a posting open for more days; expired postings are reopened
+pub async fn extend_project_posting(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<ExtendPostingRequest>,
+) -> Result<Json<ApiResponse<ProjectPosting>>, ApiError> {
+    req.validate().map_err(|e| ApiError::validation(e.to_string()))?;
+
+    let posting = PostingService::extend(&state.db, id, user.id, &req).await?;
+
+    Ok(Json(ApiResponse::success(posting)))
+}
+
+/// Publish an expired or cancelled posting again as a new posting
+pub async fn repost_project_posting(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<RepostPostingRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<ProjectPosting>>), ApiError> {
+    req.validate().map_err(|e| ApiError::validation(e.to_string()))?;
+
+    let posting = PostingService::repost(&state.db, id, user.id, &req, &state.settings.postings).await?;
+
+    Ok((StatusCode::CREATED, Json(ApiResponse::success(posting))))
+}
+
+/// Cancel a posting; pending proposals are rejected with the given reason
+pub async fn cancel_project_posting(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<CancelPostingRequest>,
+) -> Result<Json<ApiResponse<ProjectPosting>>, ApiError> {
+    req.validate().map_err(|e| ApiError::validation(e.to_string()))?;
+
+    let posting = PostingService::cancel(&state.db, id, user.id, req.reason.as_deref()).await?;
+
+    Ok(Json(ApiResponse::success(posting)))
+}
+
+// ==================== Posting Invitation Handlers ====================
+
+/// Invitations of a posting (its client)
+pub async fn list_posting_invitations(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(posting_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Vec<PostingInvitation>>>, ApiError> {
+    let invitations = PostingInvitationService::list_for_posting(&state.db, posting_id, user.id).await
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(invitations)))
+}
+
+/// Invite experts to submit proposals; they are notified
+pub async fn invite_experts(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(posting_id): Path<Uuid>,
+    Json(req): Json<InviteExpertsRequest>,
+) -> Result<Json<ApiResponse<Vec<PostingInvitation>>>, ApiError> {
+    req.validate().map_err(|e| ApiError::validation(e.to_string()))?;
+
+    let invitations = PostingInvitationService::invite(&state.db, posting_id, user.id, &req).await?;
+
+    Ok(Json(ApiResponse::success(invitations)))
+}
+
+/// Invitations received by the current expert
+pub async fn list_my_invitations(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<Vec<ReceivedPostingInvitation>>>, ApiError> {
+    let invitations = PostingInvitationService::list_for_expert(&state.db, user.id).await
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(invitations)))
+}
+
+pub async fn accept_invitation(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(invitation_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<PostingInvitation>>, ApiError> {
+    let invitation = PostingInvitationService::respond(&state.db, invitation_id, user.id, true).await?;
+
+    Ok(Json(ApiResponse::success(invitation)))
+}
+
+pub async fn decline_invitation(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(invitation_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<PostingInvitation>>, ApiError> {
+    let invitation = PostingInvitationService::respond(&state.db, invitation_id, user.id, false).await?;
+
+    Ok(Json(ApiResponse::success(invitation)))
+}
+
 // ==================== Booking Request Handlers ====================
 
 pub async fn create_booking_request(
@@ -127,8 +265,7 @@ pub async fn create_booking_request(
 ) -> Result<(StatusCode, Json<ApiResponse<BookingRequest>>), ApiError> {
     req.validate().map_err(|e| ApiError::validation(e.to_string()))?;
 
-    let booking = ClientService::create_booking_request(state.db.pool(), user.id, req).await
-        .map_err(|e| ApiError::internal(e.to_string()))?;
+    let booking = BookingService::create(&state.db, user.id, &req, &state.settings.bookings).await?;
 
     Ok((StatusCode::CREATED, Json(ApiResponse::success(booking))))
 }
@@ -151,13 +288,27 @@ pub async fn respond_to_booking(
     Json(req): Json<RespondBookingRequest>,
 ) -> Result<Json<ApiResponse<BookingRequest>>, ApiError> {
     let booking = ClientService::respond_to_booking(state.db.pool(), id, user.id, req).await
-        .map_err(|e| ApiError::internal(e.to_string()))?;
+        .map_err(|e| ApiError::internal(e.to_string()))?
+        .ok_or_else(|| ApiError::Conflict("Booking request not found or no longer pending".to_string()))?;
 
     Ok(Json(ApiResponse::success(booking)))
 }
 
 // ==================== Proposal Handlers ====================
 
+/// Milestones become project milestones on acceptance, so their shape is checked up front
+fn validate_milestones(milestones: Option<&serde_json::Value>) -> Result<(), ApiError> {
+    if let Some(milestones) = milestones {
+        let milestones: Vec<ProposedMilestone> = serde_json::from_value(milestones.clone())
+            .map_err(|e| ApiError::validation(format!("Invalid proposed milestones: {}", e)))?;
+        for milestone in &milestones {
+            milestone.validate().map_err(|e| ApiError::validation(e.to_string()))?;
+        }
+    }
+
+    Ok(())
+}
+
 pub async fn create_proposal(
     State(state): State<AppState>,
     Extension(user): Extension<AuthUser>,
@@ -165,8 +316,15 @@ pub async fn create_proposal(
 ) -> Result<(StatusCode, Json<ApiResponse<Proposal>>), ApiError> {
     req.validate().map_err(|e| ApiError::validation(e.to_string()))?;
 
-    let proposal = ClientService::create_proposal(state.db.pool(), user.id, req).await
-        .map_err(|e| ApiError::internal(e.to_string()))?;
+    validate_milestones(req.proposed_milestones.as_ref())?;
+
+    let posting = ClientService::get_project_posting(state.db.pool(), req.project_posting_id).await
+        .map_err(|e| ApiError::internal(e.to_string()))?
+        .ok_or_else(|| ApiError::not_found("Project posting not found"))?;
+    PostingService::check_accepts_proposals(&posting)?;
+    PostingInvitationService::check_proposal(&state.db, &posting, user.id).await?;
+
+    let proposal = ProposalService::submit(&state.db, user.id, &state.settings.proposals, req).await?;
 
     Ok((StatusCode::CREATED, Json(ApiResponse::success(proposal))))
 }
@@ -182,14 +340,116 @@ pub async fn list_proposals_for_posting(
     Ok(Json(proposals))
 }
 
-pub async fn accept_proposal(
+/// Proposals of a posting side by side, sorted by price, rating or skill fit
+pub async fn compare_proposals(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(posting_id): Path<Uuid>,
+    Query(query): Query<ProposalComparisonQuery>,
+) -> Result<Json<ApiResponse<Vec<ProposalComparison>>>, ApiError> {
+    let comparison = ProposalService::compare(&state.db, posting_id, user.id, &query).await?;
+
+    Ok(Json(ApiResponse::success(comparison)))
+}
+
+/// Get a proposal (its expert or the posting's client; the client's first view is recorded)
+pub async fn get_proposal(
     State(state): State<AppState>,
     Extension(user): Extension<AuthUser>,
     Path(proposal_id): Path<Uuid>,
 ) -> Result<Json<ApiResponse<Proposal>>, ApiError> {
-    let proposal = ClientService::accept_proposal(state.db.pool(), proposal_id, user.id).await
-        .map_err(|e| ApiError::internal(e.to_string()))?;
+    let proposal = ProposalService::get_by_id(&state.db, proposal_id).await
+        .map_err(|e| ApiError::internal(e.to_string()))?
+        .ok_or_else(|| ApiError::not_found("Proposal not found"))?;
+
+    if proposal.expert_id == user.id {
+        return Ok(Json(ApiResponse::success(proposal)));
+    }
+
+    let proposal = ProposalService::view(&state.db, proposal_id, user.id).await?;
+
+    Ok(Json(ApiResponse::success(proposal)))
+}
+
+/// Mark a proposal as viewed by the client
+pub async fn mark_proposal_viewed(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(proposal_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Proposal>>, ApiError> {
+    let proposal = ProposalService::view(&state.db, proposal_id, user.id).await?;
+
+    Ok(Json(ApiResponse::success(proposal)))
+}
+
+pub async fn shortlist_proposal(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(proposal_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Proposal>>, ApiError> {
+    let proposal = ProposalService::shortlist(&state.db, proposal_id, user.id).await?;
+
+    Ok(Json(ApiResponse::success(proposal)))
+}
+
+pub async fn reject_proposal(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(proposal_id): Path<Uuid>,
+    Json(req): Json<RejectProposalRequest>,
+) -> Result<Json<ApiResponse<Proposal>>, ApiError> {
+    req.validate().map_err(|e| ApiError::validation(e.to_string()))?;
+
+    let reason = req.reason.as_deref().map(str::trim).filter(|r| !r.is_empty());
+    let proposal = ProposalService::reject(&state.db, proposal_id, user.id, reason).await?;
 
     Ok(Json(ApiResponse::success(proposal)))
 }
 
+/// Edit a proposal (expert, until the client has looked at it)
+pub async fn update_proposal(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(proposal_id): Path<Uuid>,
+    Json(req): Json<UpdateProposalRequest>,
+) -> Result<Json<ApiResponse<Proposal>>, ApiError> {
+    req.validate().map_err(|e| ApiError::validation(e.to_string()))?;
+    validate_milestones(req.proposed_milestones.as_ref())?;
+
+    let proposal = ProposalService::update(&state.db, proposal_id, user.id, &req).await?;
+
+    Ok(Json(ApiResponse::success(proposal)))
+}
+
+pub async fn withdraw_proposal(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(proposal_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Proposal>>, ApiError> {
+    let proposal = ProposalService::withdraw(&state.db, proposal_id, user.id).await?;
+
+    Ok(Json(ApiResponse::success(proposal)))
+}
+
+/// Accept a proposal: creates the project, assigns the posting and rejects the other proposals
+pub async fn accept_proposal(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(proposal_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<ProposalAcceptance>>, ApiError> {
+    // Only the posting's owner may accept; checked before anything about the buyer is revealed
+    let proposal = ProposalService::get_for_client(&state.db, proposal_id, user.id).await
+        .map_err(|e| ApiError::internal(e.to_string()))?
+        .ok_or_else(|| ApiError::not_found("Proposal not found"))?;
+
+    let billing_mode = super::projects::billing_mode_for(&state, user.id, proposal.proposed_price as i64).await?;
+    let subject = PurchaseSubject { proposal_id: Some(proposal_id), ..Default::default() };
+
+    let mut tx = state.db.pool().begin().await?;
+    OrganisationService::authorize_purchase(&mut tx, user.id, subject, proposal.proposed_price as i64).await?;
+    let acceptance = ProposalService::accept_tx(&mut tx, proposal_id, user.id, billing_mode).await?;
+    tx.commit().await?;
+
+    Ok(Json(ApiResponse::success(acceptance)))
+}
+
@@ -0,0 +1,199 @@
+//! Contracts (NDA, AVV, service agreement): admin templates and click-to-sign per project
+
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::AppState;
+use crate::models::{
+    ContractTemplate, CreateContractTemplateRequest, CreateProjectContractRequest,
+    ProjectContract, ProjectContractWithSignatures, SignContractRequest, UpdateContractTemplateRequest,
+    check_contract_template,
+};
+use crate::services::{ContractService, NewSignature, UserService};
+use crate::middleware::auth::AuthUser;
+use super::{ApiError, ApiResult, SuccessResponse};
+use super::admin::require_admin;
+use super::cancellations::{get_project_for_party, party_actor};
+
+/// Client address as reported by the reverse proxy
+fn client_ip(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get("x-forwarded-for")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(',').next())
+        .or_else(|| headers.get("x-real-ip").and_then(|v| v.to_str().ok()))
+        .map(|ip| ip.trim().chars().take(45).collect())
+}
+
+// ==================== Templates (admin) ====================
+
+/// List contract templates, including retired versions
+pub async fn list_templates(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> ApiResult<Vec<ContractTemplate>> {
+    require_admin(&auth_user)?;
+
+    let templates = ContractService::list_templates(&state.db)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(templates)))
+}
+
+/// Create a contract template; it replaces the active one of the same kind and language
+pub async fn create_template(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<CreateContractTemplateRequest>,
+) -> ApiResult<ContractTemplate> {
+    require_admin(&auth_user)?;
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+    check_contract_template(&payload.title).map_err(ApiError::Validation)?;
+    check_contract_template(&payload.body).map_err(ApiError::Validation)?;
+
+    let template = ContractService::create_template(&state.db, auth_user.id, &payload)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(template)))
+}
+
+/// Update a contract template
+pub async fn update_template(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateContractTemplateRequest>,
+) -> ApiResult<ContractTemplate> {
+    require_admin(&auth_user)?;
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+    for text in [&payload.title, &payload.body].into_iter().flatten() {
+        check_contract_template(text).map_err(ApiError::Validation)?;
+    }
+
+    let template = ContractService::update_template(&state.db, id, &payload)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Contract template not found".to_string()))?;
+
+    Ok(Json(SuccessResponse::new(template)))
+}
+
+// ==================== Project contracts ====================
+
+/// Contracts of a project with their signatures (client or expert)
+pub async fn list_contracts(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Vec<ProjectContractWithSignatures>> {
+    get_project_for_party(&state, id, auth_user.id).await?;
+
+    let contracts = ContractService::list_for_project(&state.db, id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(contracts)))
+}
+
+/// Generate a contract from the active template (either party)
+pub async fn create_contract(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<CreateProjectContractRequest>,
+) -> ApiResult<ProjectContract> {
+    let (project, party) = get_project_for_party(&state, id, auth_user.id).await?;
+    if project.status.is_terminal() {
+        return Err(ApiError::Conflict("Project is already closed".to_string()));
+    }
+
+    let language = match payload.language {
+        Some(language) => language,
+        None => {
+            UserService::find_by_id(&state.db, project.client_id)
+                .await
+                .map_err(|e| ApiError::Internal(e.into()))?
+                .ok_or_else(|| ApiError::NotFound("Client not found".to_string()))?
+                .preferred_language
+        }
+    };
+
+    let contract = ContractService::generate(
+        &state.db,
+        &project,
+        payload.kind,
+        &language,
+        party_actor(party),
+        auth_user.id,
+        &state.settings.billing.company_name,
+    )
+    .await?;
+
+    Ok(Json(SuccessResponse::new(contract)))
+}
+
+/// Download the PDF of a contract (client and expert of the project only)
+pub async fn download_contract(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, contract_id)): Path<(Uuid, Uuid)>,
+) -> Result<Response, ApiError> {
+    get_project_for_party(&state, id, auth_user.id).await?;
+
+    let (title, document) = ContractService::get_document(&state.db, id, contract_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::NotFound("Contract not found".to_string()))?;
+
+    let file_name: String = title
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/pdf".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}.pdf\"", file_name)),
+        ],
+        document,
+    )
+        .into_response())
+}
+
+/// Sign a contract by click; records time, IP address and the hash of the signed document
+pub async fn sign_contract(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, contract_id)): Path<(Uuid, Uuid)>,
+    headers: HeaderMap,
+    Json(payload): Json<SignContractRequest>,
+) -> ApiResult<ProjectContractWithSignatures> {
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+    if !payload.accept {
+        return Err(ApiError::Validation("The contract must be accepted to sign it".to_string()));
+    }
+
+    let (project, party) = get_project_for_party(&state, id, auth_user.id).await?;
+
+    let signature = NewSignature {
+        user_id: auth_user.id,
+        party: party_actor(party),
+        signer_name: payload.signer_name,
+        document_hash: payload.document_hash,
+        ip_address: client_ip(&headers),
+        user_agent: headers
+            .get(header::USER_AGENT)
+            .and_then(|v| v.to_str().ok())
+            .map(|ua| ua.chars().take(500).collect()),
+    };
+    let contract = ContractService::sign(&state.db, &project, contract_id, signature).await?;
+
+    Ok(Json(SuccessResponse::new(contract)))
+}
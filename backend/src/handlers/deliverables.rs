@@ -0,0 +1,400 @@
+//! Versioned project deliverables stored in private object storage
+
+use axum::{extract::{Multipart, Path, State}, Extension, Json};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::AppState;
+use crate::models::{
+    CancellationParty, CreateDeliverableUploadRequest, DeliverableDownload, DeliverableUpload, Project,
+    ProjectActor, ProjectDeliverable, ProjectDelivery, ProjectRevisionRequest,
+};
+use crate::services::{DeliverableService, NewDeliverable};
+use crate::middleware::auth::AuthUser;
+use super::{ApiError, ApiResult, SuccessResponse};
+use super::cancellations::get_project_for_party;
+
+/// Multipart uploads are capped by the global request body limit; larger files use presigned uploads
+pub const MULTIPART_UPLOAD_LIMIT: usize = 10 * 1024 * 1024;
+
+#[cfg(feature = "storage")]
+fn storage(state: &AppState) -> Result<crate::services::StorageService, ApiError> {
+    use crate::config::StorageSettings;
+    use crate::services::StorageService;
+
+    match &state.settings.storage {
+        StorageSettings::S3 { bucket, region, access_key, secret_key, endpoint } => {
+            StorageService::new(bucket, region, access_key, secret_key, endpoint.as_deref())
+                .map_err(|e| ApiError::Internal(anyhow::anyhow!("Storage init failed: {}", e)))
+        }
+        StorageSettings::Local { .. } => Err(storage_unavailable()),
+    }
+}
+
+fn storage_unavailable() -> ApiError {
+    ApiError::BadRequest("File storage is not configured".to_string())
+}
+
+#[cfg(feature = "storage")]
+fn storage_key(project_id: Uuid, file_name: &str) -> String {
+    crate::services::StorageService::deliverable_key(project_id, file_name)
+}
+
+#[cfg(not(feature = "storage"))]
+fn storage_key(project_id: Uuid, file_name: &str) -> String {
+    let extension = file_name.rsplit('.').next().unwrap_or("bin");
+    format!("deliverables/{}/{}.{}", project_id, Uuid::new_v4(), extension)
+}
+
+#[cfg(feature = "storage")]
+pub(super) async fn put_object(state: &AppState, key: &str, data: &[u8], content_type: &str) -> Result<(), ApiError> {
+    storage(state)?
+        .put_object(key, data, content_type)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!("Storage upload failed: {}", e)))
+}
+
+#[cfg(not(feature = "storage"))]
+pub(super) async fn put_object(_state: &AppState, _key: &str, _data: &[u8], _content_type: &str) -> Result<(), ApiError> {
+    Err(storage_unavailable())
+}
+
+#[cfg(feature = "storage")]
+async fn presign_upload(state: &AppState, key: &str) -> Result<String, ApiError> {
+    storage(state)?
+        .presign_upload(key, state.settings.deliverables.url_expiry_secs)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!("Presigning failed: {}", e)))
+}
+
+#[cfg(not(feature = "storage"))]
+async fn presign_upload(_state: &AppState, _key: &str) -> Result<String, ApiError> {
+    Err(storage_unavailable())
+}
+
+#[cfg(feature = "storage")]
+pub(super) async fn presign_download(state: &AppState, key: &str) -> Result<String, ApiError> {
+    storage(state)?
+        .presign_download(key, state.settings.deliverables.url_expiry_secs)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!("Presigning failed: {}", e)))
+}
+
+#[cfg(not(feature = "storage"))]
+pub(super) async fn presign_download(_state: &AppState, _key: &str) -> Result<String, ApiError> {
+    Err(storage_unavailable())
+}
+
+#[cfg(feature = "storage")]
+async fn object_size(state: &AppState, key: &str) -> Result<Option<i64>, ApiError> {
+    storage(state)?
+        .object_size(key)
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!("Storage lookup failed: {}", e)))
+}
+
+#[cfg(not(feature = "storage"))]
+async fn object_size(_state: &AppState, _key: &str) -> Result<Option<i64>, ApiError> {
+    Err(storage_unavailable())
+}
+
+#[cfg(feature = "storage")]
+pub(super) async fn delete_object(state: &AppState, key: &str) {
+    if let Ok(storage) = storage(state)
+        && let Err(e) = storage.delete_file(key).await
+    {
+        tracing::warn!("Failed to delete stored file {}: {}", key, e);
+    }
+}
+
+#[cfg(not(feature = "storage"))]
+pub(super) async fn delete_object(_state: &AppState, _key: &str) {}
+
+/// Load a project the expert may upload deliverables to
+async fn get_project_for_upload(state: &AppState, id: Uuid, user_id: Uuid) -> Result<Project, ApiError> {
+    let (project, party) = get_project_for_party(state, id, user_id).await?;
+    if party != CancellationParty::Expert {
+        return Err(ApiError::Forbidden("Only the expert can upload deliverables".to_string()));
+    }
+    if project.status.is_terminal() {
+        return Err(ApiError::Conflict("Project is already closed".to_string()));
+    }
+
+    Ok(project)
+}
+
+async fn check_milestone(state: &AppState, project_id: Uuid, milestone_id: Option<Uuid>) -> Result<(), ApiError> {
+    if let Some(milestone_id) = milestone_id {
+        let exists = DeliverableService::milestone_exists(&state.db, project_id, milestone_id)
+            .await
+            .map_err(|e| ApiError::Internal(e.into()))?;
+        if !exists {
+            return Err(ApiError::BadRequest("Milestone does not belong to this project".to_string()));
+        }
+    }
+
+    Ok(())
+}
+
+/// List deliverables of a project (all versions)
+pub async fn list_deliverables(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Vec<ProjectDeliverable>> {
+    get_project_for_party(&state, id, auth_user.id).await?;
+
+    let deliverables = DeliverableService::list(&state.db, id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(deliverables)))
+}
+
+/// Upload a deliverable (multipart: `title`, `description`, `milestoneId`, `file`)
+pub async fn upload_deliverable(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    mut multipart: Multipart,
+) -> ApiResult<ProjectDeliverable> {
+    get_project_for_upload(&state, id, auth_user.id).await?;
+
+    let mut title = None;
+    let mut description = None;
+    let mut milestone_id = None;
+    let mut file = None;
+
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| ApiError::BadRequest(format!("Invalid multipart data: {}", e)))?
+    {
+        match field.name().unwrap_or("") {
+            "title" => title = Some(field.text().await.map_err(|e| ApiError::BadRequest(e.to_string()))?),
+            "description" => {
+                description = Some(field.text().await.map_err(|e| ApiError::BadRequest(e.to_string()))?)
+            }
+            "milestoneId" => {
+                let value = field.text().await.map_err(|e| ApiError::BadRequest(e.to_string()))?;
+                milestone_id = Some(
+                    value
+                        .parse::<Uuid>()
+                        .map_err(|_| ApiError::Validation("Invalid milestone ID".to_string()))?,
+                );
+            }
+            "file" => {
+                let file_name = field.file_name().unwrap_or("deliverable").to_string();
+                let content_type = field.content_type().unwrap_or("application/octet-stream").to_string();
+                let data = field
+                    .bytes()
+                    .await
+                    .map_err(|e| ApiError::BadRequest(format!("Upload failed: {}", e)))?;
+                file = Some((file_name, content_type, data));
+            }
+            _ => {}
+        }
+    }
+
+    let (file_name, content_type, data) =
+        file.ok_or_else(|| ApiError::Validation("No file provided".to_string()))?;
+    let title = title
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| file_name.clone());
+
+    if title.chars().count() > 200 || file_name.chars().count() > 255 {
+        return Err(ApiError::Validation("Title or file name too long".to_string()));
+    }
+    if data.is_empty() {
+        return Err(ApiError::Validation("File is empty".to_string()));
+    }
+    if data.len() as i64 > state.settings.deliverables.max_file_size {
+        return Err(ApiError::Validation("File too large".to_string()));
+    }
+    check_milestone(&state, id, milestone_id).await?;
+
+    let key = storage_key(id, &file_name);
+    put_object(&state, &key, &data, &content_type).await?;
+
+    let deliverable = DeliverableService::create(
+        &state.db,
+        id,
+        auth_user.id,
+        NewDeliverable {
+            title,
+            description: description.filter(|d| !d.trim().is_empty()),
+            milestone_id,
+            file_name,
+            file_type: content_type,
+            file_size: data.len() as i64,
+            file_url: None,
+            storage_key: Some(key),
+            uploaded: true,
+        },
+    )
+    .await
+    .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(deliverable)))
+}
+
+/// Reserve a new deliverable version and return a presigned upload URL
+pub async fn create_upload_url(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<CreateDeliverableUploadRequest>,
+) -> ApiResult<DeliverableUpload> {
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+
+    get_project_for_upload(&state, id, auth_user.id).await?;
+
+    if payload.file_size > state.settings.deliverables.max_file_size {
+        return Err(ApiError::Validation("File too large".to_string()));
+    }
+    check_milestone(&state, id, payload.milestone_id).await?;
+
+    let key = storage_key(id, &payload.file_name);
+    let upload_url = presign_upload(&state, &key).await?;
+
+    let deliverable = DeliverableService::create(
+        &state.db,
+        id,
+        auth_user.id,
+        NewDeliverable {
+            title: payload.title,
+            description: payload.description,
+            milestone_id: payload.milestone_id,
+            file_name: payload.file_name,
+            file_type: payload.content_type,
+            file_size: payload.file_size,
+            file_url: None,
+            storage_key: Some(key),
+            uploaded: false,
+        },
+    )
+    .await
+    .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(DeliverableUpload {
+        deliverable,
+        upload_url,
+        expires_at: Utc::now() + Duration::seconds(state.settings.deliverables.url_expiry_secs as i64),
+    })))
+}
+
+/// Confirm a presigned upload once the file has been stored
+pub async fn confirm_upload(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, deliverable_id)): Path<(Uuid, Uuid)>,
+) -> ApiResult<ProjectDeliverable> {
+    get_project_for_upload(&state, id, auth_user.id).await?;
+
+    let deliverable = DeliverableService::get(&state.db, id, deliverable_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::NotFound("Deliverable not found".to_string()))?;
+
+    if deliverable.uploaded_at.is_some() {
+        return Ok(Json(SuccessResponse::new(deliverable)));
+    }
+
+    let key = deliverable
+        .storage_key
+        .as_deref()
+        .ok_or_else(|| ApiError::BadRequest("Deliverable has no stored file".to_string()))?;
+
+    let size = object_size(&state, key)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("File has not been uploaded yet".to_string()))?;
+
+    if size > state.settings.deliverables.max_file_size {
+        delete_object(&state, key).await;
+        return Err(ApiError::Validation("File too large".to_string()));
+    }
+
+    let deliverable = DeliverableService::confirm_upload(&state.db, id, deliverable_id, size, auth_user.id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::Conflict("Upload was already confirmed".to_string()))?;
+
+    Ok(Json(SuccessResponse::new(deliverable)))
+}
+
+/// Time-limited download link (client and expert of the project only)
+pub async fn download_deliverable(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, deliverable_id)): Path<(Uuid, Uuid)>,
+) -> ApiResult<DeliverableDownload> {
+    get_project_for_party(&state, id, auth_user.id).await?;
+
+    let deliverable = DeliverableService::get(&state.db, id, deliverable_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .filter(|d| d.uploaded_at.is_some())
+        .ok_or_else(|| ApiError::NotFound("Deliverable not found".to_string()))?;
+
+    let download = match (&deliverable.storage_key, &deliverable.file_url) {
+        (Some(key), _) => DeliverableDownload {
+            url: presign_download(&state, key).await?,
+            expires_at: Some(Utc::now() + Duration::seconds(state.settings.deliverables.url_expiry_secs as i64)),
+        },
+        (None, Some(url)) => DeliverableDownload {
+            url: url.clone(),
+            expires_at: None,
+        },
+        (None, None) => return Err(ApiError::NotFound("Deliverable has no file".to_string())),
+    };
+
+    Ok(Json(SuccessResponse::new(download)))
+}
+
+/// Mark a deliverable version as final (expert)
+pub async fn mark_final(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, deliverable_id)): Path<(Uuid, Uuid)>,
+) -> ApiResult<ProjectDeliverable> {
+    get_project_for_upload(&state, id, auth_user.id).await?;
+
+    let deliverable = DeliverableService::mark_final(&state.db, id, deliverable_id, ProjectActor::Expert, auth_user.id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::NotFound("Deliverable not found".to_string()))?;
+
+    Ok(Json(SuccessResponse::new(deliverable)))
+}
+
+/// Deliveries of a project with the versions they contain
+pub async fn list_deliveries(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Vec<ProjectDelivery>> {
+    get_project_for_party(&state, id, auth_user.id).await?;
+
+    let deliveries = DeliverableService::list_deliveries(&state.db, id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(deliveries)))
+}
+
+/// Revision requests of a project with the versions they refer to
+pub async fn list_revision_requests(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Vec<ProjectRevisionRequest>> {
+    get_project_for_party(&state, id, auth_user.id).await?;
+
+    let revisions = DeliverableService::list_revision_requests(&state.db, id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(revisions)))
+}
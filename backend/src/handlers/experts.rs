@@ -4,10 +4,10 @@ use validator::Validate;
 
 use crate::AppState;
 use crate::models::{
-    ExpertProfile, CreateExpertProfileRequest, UpdateExpertProfileRequest,
+    AvailabilityStatus, ExpertProfile, CreateExpertProfileRequest, UpdateExpertProfileRequest,
     ExpertSearchFilters, PaginationParams, PaginatedResponse, Service, Review, UserRole,
 };
-use crate::services::{ExpertService, ServiceService, ReviewService};
+use crate::services::{BookingService, ExpertService, FavouriteService, ServiceService, ReviewService};
 use crate::middleware::auth::AuthUser;
 use super::{ApiError, ApiResult, SuccessResponse, EmptyResponse};
 
@@ -82,10 +82,25 @@ pub async fn update_profile(
         return Err(ApiError::Forbidden("Not authorized to update this profile".to_string()));
     }
 
+    if let Some(timezone) = &payload.timezone
+        && !BookingService::is_valid_timezone(state.db.pool(), timezone).await
+            .map_err(|e| ApiError::Internal(e.into()))?
+    {
+        return Err(ApiError::Validation(format!("Unknown timezone: {}", timezone)));
+    }
+
     // Update the profile
     let profile = ExpertService::update_profile(&state.db, id, payload).await
         .map_err(|e| ApiError::Internal(e.into()))?;
 
+    // Clients who saved the expert hear when they are available again
+    if existing.availability_status != AvailabilityStatus::Available
+        && profile.availability_status == AvailabilityStatus::Available
+        && let Err(e) = FavouriteService::notify_expert_available(&state.db, profile.user_id).await
+    {
+        tracing::error!("Failed to notify saved-expert followers of {}: {}", profile.user_id, e);
+    }
+
     Ok(Json(SuccessResponse::new(profile)))
 }
 
@@ -154,6 +169,25 @@ pub async fn get_my_profile(
     Ok(Json(SuccessResponse::new(profile)))
 }
 
+/// Performance metrics of the current expert with monthly history
+pub async fn get_my_metrics(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<crate::models::ExpertMetricsQuery>,
+) -> ApiResult<crate::models::ExpertMetrics> {
+    use crate::services::ExpertMetricsService;
+
+    ExpertService::get_by_user_id(&state.db, auth_user.id).await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::NotFound("Expert profile not found".to_string()))?;
+
+    let months = query.months.unwrap_or(12).clamp(1, 36);
+    let metrics = ExpertMetricsService::get_metrics(&state.db, auth_user.id, months).await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(metrics)))
+}
+
 /// Create a portfolio item
 pub async fn create_portfolio_item(
     State(state): State<AppState>,
@@ -170,6 +204,10 @@ pub async fn create_portfolio_item(
         .map_err(|e| ApiError::Internal(e.into()))?
         .ok_or_else(|| ApiError::BadRequest("You must have an expert profile".to_string()))?;
 
+    if payload.is_featured == Some(true) {
+        check_featured_limit(&state, expert.id, None).await?;
+    }
+
     let item = PortfolioService::create(&state.db, expert.id, payload).await
         .map_err(|e| ApiError::Internal(e.into()))?;
 
@@ -202,6 +240,10 @@ pub async fn update_portfolio_item(
         return Err(ApiError::Forbidden("Not authorized".to_string()));
     }
 
+    if payload.is_featured == Some(true) && !existing.is_featured {
+        check_featured_limit(&state, existing.expert_id, Some(id)).await?;
+    }
+
     let item = PortfolioService::update(&state.db, id, payload).await
         .map_err(|e| ApiError::Internal(e.into()))?;
 
@@ -236,6 +278,52 @@ pub async fn delete_portfolio_item(
     Ok(Json(EmptyResponse::new("Portfolio item deleted")))
 }
 
+/// Reorder own portfolio items
+pub async fn reorder_portfolio(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<crate::models::ReorderPortfolioRequest>,
+) -> ApiResult<Vec<crate::models::PortfolioItem>> {
+    use crate::services::PortfolioService;
+
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+
+    let mut seen = std::collections::HashSet::new();
+    if !payload.item_ids.iter().all(|id| seen.insert(*id)) {
+        return Err(ApiError::Validation("Item ids must be unique".to_string()));
+    }
+
+    let expert = ExpertService::get_by_user_id(&state.db, auth_user.id).await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::BadRequest("You must have an expert profile".to_string()))?;
+
+    let reordered = PortfolioService::reorder(&state.db, expert.id, &payload.item_ids).await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+    if !reordered {
+        return Err(ApiError::Forbidden("Portfolio items do not belong to you".to_string()));
+    }
+
+    let items = PortfolioService::get_by_expert(&state.db, expert.id).await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(items)))
+}
+
+async fn check_featured_limit(state: &AppState, expert_id: Uuid, except: Option<Uuid>) -> Result<(), ApiError> {
+    use crate::services::{PortfolioService, MAX_FEATURED_PORTFOLIO_ITEMS};
+
+    let featured = PortfolioService::count_featured(&state.db, expert_id, except).await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+    if featured >= MAX_FEATURED_PORTFOLIO_ITEMS {
+        return Err(ApiError::Conflict(format!(
+            "At most {} portfolio items can be featured",
+            MAX_FEATURED_PORTFOLIO_ITEMS
+        )));
+    }
+
+    Ok(())
+}
+
 /// Get expert's availability
 pub async fn get_availability(
     State(state): State<AppState>,
@@ -243,12 +331,31 @@ pub async fn get_availability(
 ) -> ApiResult<crate::models::ExpertAvailability> {
     use crate::services::AvailabilityService;
 
+    ExpertService::get_by_id(&state.db, id).await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::NotFound("Expert not found".to_string()))?;
+
     let availability = AvailabilityService::get_availability(&state.db, id).await
         .map_err(|e| ApiError::Internal(e.into()))?;
 
     Ok(Json(SuccessResponse::new(availability)))
 }
 
+/// Bookable time slots of an expert
+pub async fn get_slots(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<crate::models::BookingSlotQuery>,
+) -> ApiResult<crate::models::BookingSlots> {
+    let expert = ExpertService::get_by_id(&state.db, id).await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::NotFound("Expert not found".to_string()))?;
+
+    let slots = BookingService::get_slots(&state.db, expert.user_id, &query, &state.settings.bookings).await?;
+
+    Ok(Json(SuccessResponse::new(slots)))
+}
+
 /// Set expert's weekly availability
 pub async fn set_availability(
     State(state): State<AppState>,
@@ -257,12 +364,15 @@ pub async fn set_availability(
 ) -> ApiResult<Vec<crate::models::AvailabilitySlot>> {
     use crate::services::AvailabilityService;
 
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+    let slots = payload.weekly_slots().map_err(ApiError::Validation)?;
+
     // Get expert profile
     let expert = ExpertService::get_by_user_id(&state.db, auth_user.id).await
         .map_err(|e| ApiError::Internal(e.into()))?
         .ok_or_else(|| ApiError::BadRequest("You must have an expert profile".to_string()))?;
 
-    let slots = AvailabilityService::set_availability(&state.db, expert.id, payload).await
+    let slots = AvailabilityService::set_availability(&state.db, expert.id, &slots).await
         .map_err(|e| ApiError::Internal(e.into()))?;
 
     Ok(Json(SuccessResponse::new(slots)))
@@ -278,12 +388,24 @@ pub async fn block_dates(
     use validator::Validate;
 
     payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+    if payload.start_date > payload.end_date {
+        return Err(ApiError::Validation("Start date must not be after end date".to_string()));
+    }
+    if payload.end_date < chrono::Utc::now().date_naive() {
+        return Err(ApiError::Validation("Dates in the past cannot be blocked".to_string()));
+    }
 
     // Get expert profile
     let expert = ExpertService::get_by_user_id(&state.db, auth_user.id).await
         .map_err(|e| ApiError::Internal(e.into()))?
         .ok_or_else(|| ApiError::BadRequest("You must have an expert profile".to_string()))?;
 
+    let overlaps = AvailabilityService::overlaps_blocked(&state.db, expert.id, payload.start_date, payload.end_date).await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+    if overlaps {
+        return Err(ApiError::Conflict("Dates overlap an already blocked range".to_string()));
+    }
+
     let blocked = AvailabilityService::block_dates(&state.db, expert.id, payload).await
         .map_err(|e| ApiError::Internal(e.into()))?;
 
@@ -299,12 +421,15 @@ pub async fn unblock_dates(
     use crate::services::AvailabilityService;
 
     // Get expert profile (for authorization)
-    let _expert = ExpertService::get_by_user_id(&state.db, auth_user.id).await
+    let expert = ExpertService::get_by_user_id(&state.db, auth_user.id).await
         .map_err(|e| ApiError::Internal(e.into()))?
         .ok_or_else(|| ApiError::BadRequest("You must have an expert profile".to_string()))?;
 
-    AvailabilityService::unblock_dates(&state.db, id).await
+    let removed = AvailabilityService::unblock_dates(&state.db, id, expert.id).await
         .map_err(|e| ApiError::Internal(e.into()))?;
+    if !removed {
+        return Err(ApiError::NotFound("Blocked dates not found".to_string()));
+    }
 
     Ok(Json(EmptyResponse::new("Dates unblocked")))
 }
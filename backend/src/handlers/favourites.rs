@@ -0,0 +1,205 @@
+//! Favourites: saved experts and services, named lists and lists shared within an organisation
+
+use axum::{extract::{Path, Query, State}, Extension, Json};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::AppState;
+use crate::models::{
+    CreateSavedListRequest, SaveExpertRequest, SaveServiceRequest, SavedExpert, SavedItemsQuery, SavedList,
+    SavedListDetails, SavedService, UpdateSavedListRequest, UpdateSavedNotesRequest,
+};
+use crate::services::FavouriteService;
+use crate::middleware::auth::AuthUser;
+use super::{ApiError, ApiResult, EmptyResponse, SuccessResponse};
+
+// ==================== Lists ====================
+
+/// Lists of the current user
+pub async fn list_lists(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> ApiResult<Vec<SavedList>> {
+    let lists = FavouriteService::lists(&state.db, auth_user.id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(lists)))
+}
+
+/// Lists shared with the current user's organisation by other members
+pub async fn list_shared_lists(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> ApiResult<Vec<SavedList>> {
+    let lists = FavouriteService::shared_lists(&state.db, auth_user.id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(lists)))
+}
+
+pub async fn create_list(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<CreateSavedListRequest>,
+) -> ApiResult<SavedList> {
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+
+    let list = FavouriteService::create_list(&state.db, auth_user.id, &payload).await?;
+
+    Ok(Json(SuccessResponse::new(list)))
+}
+
+/// List with its experts and services (owner, or organisation members if shared)
+pub async fn get_list(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<SavedListDetails> {
+    let list = FavouriteService::get_list(&state.db, id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::NotFound("List not found".to_string()))?;
+
+    if !FavouriteService::can_view(&state.db, &list, auth_user.id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+    {
+        return Err(ApiError::NotFound("List not found".to_string()));
+    }
+
+    let experts = FavouriteService::experts(&state.db, list.owner_id, Some(list.id))
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+    let services = FavouriteService::services(&state.db, list.owner_id, Some(list.id))
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(SavedListDetails { list, experts, services })))
+}
+
+/// Rename, describe or share a list (owner)
+pub async fn update_list(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateSavedListRequest>,
+) -> ApiResult<SavedList> {
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+
+    let list = FavouriteService::update_list(&state.db, id, auth_user.id, &payload).await?;
+
+    Ok(Json(SuccessResponse::new(list)))
+}
+
+/// Delete a list and everything saved in it (owner)
+pub async fn delete_list(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<EmptyResponse>, ApiError> {
+    FavouriteService::delete_list(&state.db, id, auth_user.id).await?;
+
+    Ok(Json(EmptyResponse::new("List deleted")))
+}
+
+// ==================== Saved experts ====================
+
+/// Saved experts of the current user, optionally of one list
+pub async fn list_experts(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<SavedItemsQuery>,
+) -> ApiResult<Vec<SavedExpert>> {
+    let experts = FavouriteService::experts(&state.db, auth_user.id, query.list_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(experts)))
+}
+
+pub async fn save_expert(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<SaveExpertRequest>,
+) -> ApiResult<SavedExpert> {
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+
+    let expert = FavouriteService::save_expert(&state.db, auth_user.id, &payload).await?;
+
+    Ok(Json(SuccessResponse::new(expert)))
+}
+
+pub async fn update_expert_notes(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateSavedNotesRequest>,
+) -> ApiResult<SavedExpert> {
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+
+    let expert = FavouriteService::update_expert_notes(&state.db, id, auth_user.id, payload.notes.as_deref()).await?;
+
+    Ok(Json(SuccessResponse::new(expert)))
+}
+
+pub async fn remove_expert(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<EmptyResponse>, ApiError> {
+    FavouriteService::remove_expert(&state.db, id, auth_user.id).await?;
+
+    Ok(Json(EmptyResponse::new("Expert removed from favourites")))
+}
+
+// ==================== Saved services ====================
+
+/// Saved services of the current user, optionally of one list
+pub async fn list_services(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<SavedItemsQuery>,
+) -> ApiResult<Vec<SavedService>> {
+    let services = FavouriteService::services(&state.db, auth_user.id, query.list_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(services)))
+}
+
+pub async fn save_service(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<SaveServiceRequest>,
+) -> ApiResult<SavedService> {
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+
+    let service = FavouriteService::save_service(&state.db, auth_user.id, &payload).await?;
+
+    Ok(Json(SuccessResponse::new(service)))
+}
+
+pub async fn update_service_notes(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateSavedNotesRequest>,
+) -> ApiResult<SavedService> {
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+
+    let service = FavouriteService::update_service_notes(&state.db, id, auth_user.id, payload.notes.as_deref()).await?;
+
+    Ok(Json(SuccessResponse::new(service)))
+}
+
+pub async fn remove_service(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<EmptyResponse>, ApiError> {
+    FavouriteService::remove_service(&state.db, id, auth_user.id).await?;
+
+    Ok(Json(EmptyResponse::new("Service removed from favourites")))
+}
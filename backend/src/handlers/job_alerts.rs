@@ -0,0 +1,95 @@
+//! Job alerts: postings matching the expert's profile, alert preferences and alert filters
+
+use axum::{extract::{Path, Query, State}, Extension, Json};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::AppState;
+use crate::models::{
+    CreateJobAlertFilterRequest, JobAlert, JobAlertFilter, JobAlertPreferences, PaginatedResponse, PaginationParams,
+    UpdateJobAlertFilterRequest, UpdateJobAlertPreferencesRequest,
+};
+use crate::services::JobAlertService;
+use crate::middleware::auth::AuthUser;
+use super::{ApiError, ApiResult, EmptyResponse, SuccessResponse};
+
+/// Alerts received by the current expert, newest first
+pub async fn list_alerts(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(pagination): Query<PaginationParams>,
+) -> Result<Json<PaginatedResponse<JobAlert>>, ApiError> {
+    let alerts = JobAlertService::alerts(&state.db, auth_user.id, pagination.page, pagination.per_page)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(alerts))
+}
+
+pub async fn get_preferences(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> ApiResult<JobAlertPreferences> {
+    let preferences = JobAlertService::preferences(&state.db, auth_user.id).await?;
+
+    Ok(Json(SuccessResponse::new(preferences)))
+}
+
+/// Choose in-app alerts, email frequency (off, instant, daily) and the minimum match score
+pub async fn update_preferences(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<UpdateJobAlertPreferencesRequest>,
+) -> ApiResult<JobAlertPreferences> {
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+
+    let preferences = JobAlertService::update_preferences(&state.db, auth_user.id, &payload).await?;
+
+    Ok(Json(SuccessResponse::new(preferences)))
+}
+
+pub async fn list_filters(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> ApiResult<Vec<JobAlertFilter>> {
+    let filters = JobAlertService::filters(&state.db, auth_user.id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(filters)))
+}
+
+pub async fn create_filter(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<CreateJobAlertFilterRequest>,
+) -> ApiResult<JobAlertFilter> {
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+
+    let filter = JobAlertService::create_filter(&state.db, auth_user.id, &payload).await?;
+
+    Ok(Json(SuccessResponse::new(filter)))
+}
+
+pub async fn update_filter(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateJobAlertFilterRequest>,
+) -> ApiResult<JobAlertFilter> {
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+
+    let filter = JobAlertService::update_filter(&state.db, id, auth_user.id, &payload).await?;
+
+    Ok(Json(SuccessResponse::new(filter)))
+}
+
+pub async fn delete_filter(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<EmptyResponse>, ApiError> {
+    JobAlertService::delete_filter(&state.db, id, auth_user.id).await?;
+
+    Ok(Json(EmptyResponse::new("Alert filter deleted")))
+}
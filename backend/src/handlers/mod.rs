@@ -1,17 +1,29 @@
 pub mod admin;
+pub mod agencies;
 pub mod auth;
+pub mod calendar;
+pub mod cancellations;
 pub mod categories;
+pub mod change_requests;
 pub mod clients;
+pub mod contracts;
+pub mod deliverables;
 pub mod experts;
+pub mod favourites;
+pub mod job_alerts;
 pub mod health;
 pub mod messages;
 pub mod newsletter;
+pub mod notifications;
+pub mod organisations;
 pub mod payments;
 pub mod projects;
 pub mod reports;
+pub mod requirements;
 pub mod reviews;
 pub mod search;
 pub mod services;
+pub mod tips;
 pub mod users;
 
 pub mod common {
@@ -122,6 +134,167 @@ impl IntoResponse for ApiError {
     }
 }
 
+impl From<crate::services::TransitionError> for ApiError {
+    fn from(err: crate::services::TransitionError) -> Self {
+        use crate::services::TransitionError;
+
+        match err {
+            TransitionError::NotAllowed { .. } | TransitionError::GuardFailed(_) => {
+                ApiError::Conflict(err.to_string())
+            }
+            TransitionError::Database(sqlx::Error::RowNotFound) => {
+                ApiError::NotFound("Project not found".to_string())
+            }
+            TransitionError::Database(e) => ApiError::Internal(e.into()),
+        }
+    }
+}
+
+impl From<crate::services::ProposalError> for ApiError {
+    fn from(err: crate::services::ProposalError) -> Self {
+        use crate::services::ProposalError;
+
+        match err {
+            ProposalError::NotFound => ApiError::NotFound(err.to_string()),
+            ProposalError::Conflict(msg) => ApiError::Conflict(msg),
+            ProposalError::Invalid(msg) => ApiError::BadRequest(msg),
+            ProposalError::LimitReached { .. } => ApiError::Forbidden(err.to_string()),
+            ProposalError::Transition(e) => e.into(),
+            ProposalError::Database(e) => ApiError::Internal(e.into()),
+        }
+    }
+}
+
+impl From<crate::services::BookingError> for ApiError {
+    fn from(err: crate::services::BookingError) -> Self {
+        use crate::services::BookingError;
+
+        match err {
+            BookingError::NotFound => ApiError::NotFound(err.to_string()),
+            BookingError::InvalidTimezone(_) | BookingError::Invalid(_) => ApiError::BadRequest(err.to_string()),
+            BookingError::SlotUnavailable => ApiError::Conflict(err.to_string()),
+            BookingError::Database(e) => ApiError::Internal(e.into()),
+        }
+    }
+}
+
+impl From<crate::services::CalendarError> for ApiError {
+    fn from(err: crate::services::CalendarError) -> Self {
+        use crate::services::CalendarError;
+
+        match err {
+            CalendarError::NotFound => ApiError::NotFound(err.to_string()),
+            CalendarError::Invalid(msg) => ApiError::BadRequest(msg),
+            CalendarError::Fetch(_) => ApiError::BadRequest(err.to_string()),
+            CalendarError::Database(e) => ApiError::Internal(e.into()),
+        }
+    }
+}
+
+impl From<crate::services::ChangeRequestError> for ApiError {
+    fn from(err: crate::services::ChangeRequestError) -> Self {
+        use crate::services::ChangeRequestError;
+
+        match err {
+            ChangeRequestError::NotFound => ApiError::NotFound(err.to_string()),
+            ChangeRequestError::Conflict(msg) => ApiError::Conflict(msg),
+            ChangeRequestError::Invalid(msg) => ApiError::BadRequest(msg),
+            ChangeRequestError::Database(e) => ApiError::Internal(e.into()),
+        }
+    }
+}
+
+impl From<crate::services::ContractError> for ApiError {
+    fn from(err: crate::services::ContractError) -> Self {
+        use crate::services::ContractError;
+
+        match err {
+            ContractError::NotFound | ContractError::TemplateMissing => ApiError::NotFound(err.to_string()),
+            ContractError::Conflict(msg) => ApiError::Conflict(msg),
+            ContractError::Database(e) => ApiError::Internal(e.into()),
+        }
+    }
+}
+
+impl From<crate::services::AgencyError> for ApiError {
+    fn from(err: crate::services::AgencyError) -> Self {
+        use crate::services::AgencyError;
+
+        match err {
+            AgencyError::NotFound => ApiError::NotFound(err.to_string()),
+            AgencyError::Conflict(msg) => ApiError::Conflict(msg),
+            AgencyError::Invalid(msg) => ApiError::BadRequest(msg),
+            AgencyError::Database(e) => ApiError::Internal(e.into()),
+        }
+    }
+}
+
+impl From<crate::services::OrganisationError> for ApiError {
+    fn from(err: crate::services::OrganisationError) -> Self {
+        use crate::services::OrganisationError;
+
+        match err {
+            OrganisationError::NotFound => ApiError::NotFound(err.to_string()),
+            OrganisationError::Forbidden(msg) => ApiError::Forbidden(msg),
+            OrganisationError::Conflict(msg) => ApiError::Conflict(msg),
+            OrganisationError::Invalid(msg) => ApiError::BadRequest(msg),
+            OrganisationError::Database(e) => ApiError::Internal(e.into()),
+        }
+    }
+}
+
+impl From<crate::services::FavouriteError> for ApiError {
+    fn from(err: crate::services::FavouriteError) -> Self {
+        use crate::services::FavouriteError;
+
+        match err {
+            FavouriteError::NotFound => ApiError::NotFound(err.to_string()),
+            FavouriteError::Conflict(msg) => ApiError::Conflict(msg),
+            FavouriteError::Invalid(msg) => ApiError::BadRequest(msg),
+            FavouriteError::Database(e) => ApiError::Internal(e.into()),
+        }
+    }
+}
+
+impl From<crate::services::InvitationError> for ApiError {
+    fn from(err: crate::services::InvitationError) -> Self {
+        use crate::services::InvitationError;
+
+        match err {
+            InvitationError::NotFound => ApiError::NotFound(err.to_string()),
+            InvitationError::Forbidden(msg) => ApiError::Forbidden(msg),
+            InvitationError::Conflict(msg) => ApiError::Conflict(msg),
+            InvitationError::Invalid(msg) => ApiError::BadRequest(msg),
+            InvitationError::Database(e) => ApiError::Internal(e.into()),
+        }
+    }
+}
+
+impl From<crate::services::PostingError> for ApiError {
+    fn from(err: crate::services::PostingError) -> Self {
+        use crate::services::PostingError;
+
+        match err {
+            PostingError::NotFound => ApiError::NotFound(err.to_string()),
+            PostingError::Conflict(msg) => ApiError::Conflict(msg),
+            PostingError::Invalid(msg) => ApiError::BadRequest(msg),
+            PostingError::Database(e) => ApiError::Internal(e.into()),
+        }
+    }
+}
+
+impl From<crate::services::JobAlertError> for ApiError {
+    fn from(err: crate::services::JobAlertError) -> Self {
+        use crate::services::JobAlertError;
+
+        match err {
+            JobAlertError::NotFound => ApiError::NotFound(err.to_string()),
+            JobAlertError::Forbidden(msg) => ApiError::Forbidden(msg),
+            JobAlertError::Database(e) => ApiError::Internal(e.into()),
+        }
+    }
+}
+
 /// Success response wrapper
 #[derive(Serialize)]
 pub struct SuccessResponse<T: Serialize> {
@@ -0,0 +1,64 @@
+use axum::{extract::{Query, State}, Extension, Json};
+use serde::Deserialize;
+
+use crate::AppState;
+use crate::models::{MarkNotificationsReadRequest, Notification, PaginatedResponse, PaginationMeta, PaginationParams};
+use crate::services::NotificationService;
+use crate::middleware::auth::AuthUser;
+use super::{ApiError, ApiResult, EmptyResponse, SuccessResponse};
+
+/// Notification list filters
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationFilters {
+    #[serde(default)]
+    pub unread_only: bool,
+}
+
+/// List notifications for current user
+pub async fn list_notifications(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(filters): Query<NotificationFilters>,
+    Query(pagination): Query<PaginationParams>,
+) -> ApiResult<PaginatedResponse<Notification>> {
+    let (notifications, total) = NotificationService::get_for_user(
+        state.db.pool(),
+        auth_user.id,
+        filters.unread_only,
+        pagination.page,
+        pagination.per_page,
+    )
+    .await
+    .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(PaginatedResponse {
+        data: notifications,
+        meta: PaginationMeta::new(pagination.page, pagination.per_page, total),
+    })))
+}
+
+/// Mark notifications as read
+pub async fn mark_as_read(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<MarkNotificationsReadRequest>,
+) -> Result<Json<EmptyResponse>, ApiError> {
+    NotificationService::mark_as_read(state.db.pool(), auth_user.id, payload.notification_ids.as_deref())
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(EmptyResponse::new("Notifications marked as read")))
+}
+
+/// Get unread notification count
+pub async fn get_unread_count(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> ApiResult<i64> {
+    let count = NotificationService::get_unread_count(state.db.pool(), auth_user.id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(count)))
+}
@@ -0,0 +1,355 @@
+//! Client organisations: members with roles and spending limits, purchase approvals,
+//! shared projects, inbox and consolidated invoices
+
+use axum::{extract::{Path, Query, State}, Extension, Json};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::AppState;
+use crate::models::{
+    AddOrganisationMemberRequest, ClientOrganisation, Conversation, CreateOrganisationRequest,
+    CreatePurchaseApprovalRequest, DecidePurchaseApprovalRequest, Invoice, Message, OrganisationMember,
+    OrganisationMembership, OrganisationReplyRequest, OrganisationRole, PaginatedResponse, PaginationMeta,
+    PaginationParams, Project, PurchaseApproval, PurchaseApprovalStatus, UpdateOrganisationMemberRequest,
+    UpdateOrganisationRequest, UserRole,
+};
+use crate::services::{MessageService, OrganisationService};
+use crate::middleware::auth::AuthUser;
+use super::{ApiError, ApiResult, EmptyResponse, SuccessResponse};
+
+/// Role of the current user in the organisation; fails for non-members
+async fn require_role(state: &AppState, organisation_id: Uuid, user_id: Uuid) -> Result<OrganisationRole, ApiError> {
+    OrganisationService::role(&state.db, organisation_id, user_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::Forbidden("Not a member of this organisation".to_string()))
+}
+
+async fn require_admin(state: &AppState, organisation_id: Uuid, user_id: Uuid) -> Result<(), ApiError> {
+    if require_role(state, organisation_id, user_id).await? != OrganisationRole::Admin {
+        return Err(ApiError::Forbidden("Only organisation admins can do this".to_string()));
+    }
+
+    Ok(())
+}
+
+async fn get_organisation_or_404(state: &AppState, id: Uuid) -> Result<ClientOrganisation, ApiError> {
+    OrganisationService::get(&state.db, id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::NotFound("Organisation not found".to_string()))
+}
+
+/// Create an organisation (client); the creator becomes its admin and billing contact
+pub async fn create_organisation(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<CreateOrganisationRequest>,
+) -> ApiResult<ClientOrganisation> {
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+    if auth_user.role != UserRole::Client {
+        return Err(ApiError::Forbidden("Only clients can create an organisation".to_string()));
+    }
+
+    let organisation = OrganisationService::create(&state.db, auth_user.id, &payload).await?;
+
+    Ok(Json(SuccessResponse::new(organisation)))
+}
+
+/// Organisation of the current user with their role and spending this month
+pub async fn get_my_organisation(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> ApiResult<OrganisationMembership> {
+    let membership = OrganisationService::membership(&state.db, auth_user.id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::NotFound("You are not a member of an organisation".to_string()))?;
+
+    Ok(Json(SuccessResponse::new(membership)))
+}
+
+/// Organisation details (members)
+pub async fn get_organisation(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<ClientOrganisation> {
+    require_role(&state, id, auth_user.id).await?;
+    let organisation = get_organisation_or_404(&state, id).await?;
+
+    Ok(Json(SuccessResponse::new(organisation)))
+}
+
+/// Update company details, approval threshold and billing contact (admin)
+pub async fn update_organisation(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateOrganisationRequest>,
+) -> ApiResult<ClientOrganisation> {
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+    require_admin(&state, id, auth_user.id).await?;
+
+    let organisation = OrganisationService::update(&state.db, id, &payload).await?;
+
+    Ok(Json(SuccessResponse::new(organisation)))
+}
+
+// ==================== Members ====================
+
+/// Members of the organisation (members)
+pub async fn list_members(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Vec<OrganisationMember>> {
+    require_role(&state, id, auth_user.id).await?;
+
+    let members = OrganisationService::list_members(&state.db, id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(members)))
+}
+
+/// Add a client to the organisation (admin)
+pub async fn add_member(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<AddOrganisationMemberRequest>,
+) -> ApiResult<OrganisationMember> {
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+    require_admin(&state, id, auth_user.id).await?;
+
+    let organisation = get_organisation_or_404(&state, id).await?;
+    let member = OrganisationService::add_member(&state.db, &organisation, auth_user.id, &payload).await?;
+
+    Ok(Json(SuccessResponse::new(member)))
+}
+
+/// Change a member's role or spending limit (admin)
+pub async fn update_member(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, user_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<UpdateOrganisationMemberRequest>,
+) -> ApiResult<OrganisationMember> {
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+    require_admin(&state, id, auth_user.id).await?;
+
+    let member = OrganisationService::update_member(&state.db, id, user_id, &payload).await?;
+
+    Ok(Json(SuccessResponse::new(member)))
+}
+
+/// Remove a member; members can leave, admins remove anyone
+pub async fn remove_member(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, user_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<EmptyResponse>, ApiError> {
+    let role = require_role(&state, id, auth_user.id).await?;
+    if user_id != auth_user.id && role != OrganisationRole::Admin {
+        return Err(ApiError::Forbidden("Only organisation admins can remove members".to_string()));
+    }
+
+    OrganisationService::remove_member(&state.db, id, user_id).await?;
+
+    Ok(Json(EmptyResponse::new("Member removed")))
+}
+
+// ==================== Projects and invoices ====================
+
+/// Projects bought for the organisation (members)
+pub async fn list_projects(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Query(pagination): Query<PaginationParams>,
+) -> ApiResult<PaginatedResponse<Project>> {
+    require_role(&state, id, auth_user.id).await?;
+
+    let per_page = pagination.per_page.min(50);
+    let (projects, total) = OrganisationService::list_projects(&state.db, id, pagination.page, per_page)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(PaginatedResponse {
+        data: projects,
+        meta: PaginationMeta::new(pagination.page, per_page, total),
+    })))
+}
+
+/// Consolidated invoices of the organisation (admin)
+pub async fn list_invoices(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Vec<Invoice>> {
+    require_admin(&state, id, auth_user.id).await?;
+
+    let invoices = OrganisationService::list_invoices(&state.db, id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(invoices)))
+}
+
+// ==================== Shared inbox ====================
+
+/// Conversations of the organisation's members with experts (members)
+pub async fn get_conversations(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Query(pagination): Query<PaginationParams>,
+) -> ApiResult<PaginatedResponse<Conversation>> {
+    require_role(&state, id, auth_user.id).await?;
+
+    let per_page = pagination.per_page.min(50);
+    let (conversations, total) = OrganisationService::conversations(&state.db, id, pagination.page, per_page)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(PaginatedResponse {
+        data: conversations,
+        meta: PaginationMeta::new(pagination.page, per_page, total),
+    })))
+}
+
+/// Messages of a shared conversation (members)
+pub async fn get_conversation_messages(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, conversation_id)): Path<(Uuid, Uuid)>,
+    Query(pagination): Query<PaginationParams>,
+) -> ApiResult<PaginatedResponse<Message>> {
+    require_role(&state, id, auth_user.id).await?;
+
+    OrganisationService::conversation(&state.db, id, conversation_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::NotFound("Conversation not found".to_string()))?;
+
+    let per_page = pagination.per_page.min(100);
+    let (messages, total) = MessageService::list_messages(state.db.pool(), conversation_id, pagination.page, per_page)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(PaginatedResponse {
+        data: messages,
+        meta: PaginationMeta::new(pagination.page, per_page, total),
+    })))
+}
+
+/// Reply in a shared conversation on behalf of the organisation (admins and buyers)
+pub async fn reply_in_conversation(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, conversation_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<OrganisationReplyRequest>,
+) -> ApiResult<Message> {
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+    if require_role(&state, id, auth_user.id).await? == OrganisationRole::Viewer {
+        return Err(ApiError::Forbidden("Viewers cannot reply for the organisation".to_string()));
+    }
+
+    let (_, participant) = OrganisationService::conversation(&state.db, id, conversation_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::NotFound("Conversation not found".to_string()))?;
+    let participant = participant
+        .ok_or_else(|| ApiError::Conflict("No organisation member takes part in this conversation".to_string()))?;
+
+    let message = MessageService::send_on_behalf(
+        state.db.pool(),
+        auth_user.id,
+        participant,
+        conversation_id,
+        &payload.content,
+    )
+    .await
+    .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(message)))
+}
+
+// ==================== Purchase approvals ====================
+
+/// Purchase approvals of the organisation (members)
+pub async fn list_approvals(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Vec<PurchaseApproval>> {
+    require_role(&state, id, auth_user.id).await?;
+
+    let approvals = OrganisationService::list_approvals(&state.db, id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(approvals)))
+}
+
+/// Ask the admins to approve a purchase (buyers)
+pub async fn request_approval(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<CreatePurchaseApprovalRequest>,
+) -> ApiResult<PurchaseApproval> {
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+    if !require_role(&state, id, auth_user.id).await?.can_purchase() {
+        return Err(ApiError::Forbidden("Viewers cannot make purchases for the organisation".to_string()));
+    }
+
+    let approval = OrganisationService::request_approval(&state.db, id, auth_user.id, &payload).await?;
+
+    Ok(Json(SuccessResponse::new(approval)))
+}
+
+async fn decide_approval(
+    state: &AppState,
+    auth_user: &AuthUser,
+    id: Uuid,
+    approval_id: Uuid,
+    status: PurchaseApprovalStatus,
+    payload: DecidePurchaseApprovalRequest,
+) -> ApiResult<PurchaseApproval> {
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+    require_admin(state, id, auth_user.id).await?;
+
+    let approval = OrganisationService::decide(
+        &state.db,
+        id,
+        approval_id,
+        auth_user.id,
+        status,
+        payload.message.as_deref(),
+    )
+    .await?;
+
+    Ok(Json(SuccessResponse::new(approval)))
+}
+
+/// Approve a pending purchase (admin)
+pub async fn approve_purchase(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, approval_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<DecidePurchaseApprovalRequest>,
+) -> ApiResult<PurchaseApproval> {
+    decide_approval(&state, &auth_user, id, approval_id, PurchaseApprovalStatus::Approved, payload).await
+}
+
+/// Reject a pending purchase (admin)
+pub async fn reject_purchase(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, approval_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<DecidePurchaseApprovalRequest>,
+) -> ApiResult<PurchaseApproval> {
+    decide_approval(&state, &auth_user, id, approval_id, PurchaseApprovalStatus::Rejected, payload).await
+}
@@ -4,8 +4,10 @@ use axum::{
     extract::{Path, Query, State},
     Extension, Json,
 };
+use sqlx::PgConnection;
 use uuid::Uuid;
 use std::collections::HashMap;
+use validator::Validate;
 
 use crate::{
     AppState,
@@ -13,11 +15,13 @@ use crate::{
     models::{
         CreatePaymentRequest, CreateCheckoutSessionRequest, CheckoutSessionResponse,
         CreateConnectAccountRequest, ConnectOnboardingResponse, ConnectAccountStatus,
-        PaginatedResponse, PaginationMeta, PaginationParams, Payment, Payout, Invoice,
+        PaginatedResponse, PaginationMeta, PaginationParams, Payment, Payout, Invoice, InvoiceLineItem,
+        PurchaseSubject, BillingMode, CreateProjectRequest, Currency, Project, ProjectActor, ProjectAddon, ProjectStatus,
     },
-    services::PaymentService,
+    services::{DunningService, OrganisationService, PaymentService, ProjectService, ServiceService},
     handlers::{ApiError, ApiResult, SuccessResponse},
 };
+use super::projects::service_order;
 
 /// Get payment history for authenticated user
 pub async fn get_payment_history(
@@ -325,6 +329,8 @@ fn generate_invoice_html(invoice: &Invoice) -> String {
 
         {}
 
+        {}
+
         <div class="footer">
             <p>DACH Automation Marketplace • Schweiz, Deutschland, Österreich</p>
             <p style="margin-top: 8px;">Bei Fragen zu dieser Rechnung kontaktieren Sie uns unter support@dach-marketplace.com</p>
@@ -374,7 +380,14 @@ fn generate_invoice_html(invoice: &Invoice) -> String {
         invoice.currency,
         invoice.total as f64 / 100.0,
         invoice.currency,
-        invoice.notes.as_ref().map(|n| format!(r#"<div style="margin-top: 40px; padding: 20px; background: #f9fafb; border-radius: 8px;"><strong>Anmerkungen:</strong><br>{}</div>"#, n)).unwrap_or_default(),
+        invoice.notes.as_ref().map(|n| format!(r#"<div style="margin-top: 40px; padding: 20px; background: #f9fafb; border-radius: 8px;"><strong>Anmerkungen:</strong><br>{}</div>"#, n.replace('\n', "<br>"))).unwrap_or_default(),
+        invoice.payment_details.as_ref().map(|p| format!(
+            r#"<div style="margin-top: 24px; padding: 20px; background: #f9fafb; border-radius: 8px;"><strong>Zahlungsinformationen:</strong><br>Kontoinhaber: {}<br>IBAN: {}{}<br>Referenz: {}</div>"#,
+            p.account_holder,
+            p.iban,
+            p.bic.as_ref().map(|b| format!("<br>BIC: {}", b)).unwrap_or_default(),
+            p.reference,
+        )).unwrap_or_default(),
     )
 }
 
@@ -384,46 +397,39 @@ pub async fn create_checkout_session(
     Extension(auth_user): Extension<AuthUser>,
     Json(req): Json<CreateCheckoutSessionRequest>,
 ) -> ApiResult<CheckoutSessionResponse> {
-    // Get the service details
-    let service: Option<(String, i32, String, Uuid)> = sqlx::query_as(
-        r#"
-        SELECT s.title, s.price, s.currency, s.expert_id
-        FROM services s
-        WHERE s.id = $1 AND s.is_active = true
-        "#
-    )
-    .bind(req.service_id)
-    .fetch_optional(state.db.pool())
-    .await
-    .map_err(|e| ApiError::Internal(e.into()))?;
-
-    let (title, base_price, currency, expert_id) = service
-        .ok_or(ApiError::NotFound("Service not found".into()))?;
+    req.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
 
-    // Determine the amount based on package tier or custom amount
-    let amount = if let Some(custom) = req.custom_amount {
-        custom
-    } else if let Some(tier) = &req.package_tier {
-        // Get package price
-        let package_price: Option<(i32,)> = sqlx::query_as(
-            "SELECT price FROM service_packages WHERE service_id = $1 AND tier = $2"
-        )
-        .bind(req.service_id)
-        .bind(tier)
-        .fetch_optional(state.db.pool())
+    // Clients with seriously overdue invoices cannot start new projects; organisation
+    // purchases depend on the billing contact's invoices
+    let billing_user = OrganisationService::billing_user(&state.db, auth_user.id)
         .await
         .map_err(|e| ApiError::Internal(e.into()))?;
+    if DunningService::is_paused(state.db.pool(), billing_user)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+    {
+        return Err(ApiError::Forbidden(
+            "New projects are paused until your overdue invoices are settled".to_string(),
+        ));
+    }
 
-        package_price.map(|(p,)| p).unwrap_or(base_price)
-    } else {
-        base_price
-    };
+    let mut metadata = HashMap::new();
+    metadata.insert("service_id".to_string(), req.service_id.to_string());
+    metadata.insert("buyer_id".to_string(), auth_user.id.to_string());
 
-    let currency = req.currency.as_deref().unwrap_or(&currency);
+    // Direct service purchases create (and authorise) their project in this transaction; it
+    // is committed only once the session exists. Project checkouts were authorised when the
+    // project was created.
+    let mut tx = state.db.pool().begin().await?;
+    let (line_items, currency, expert_id) = match req.project_id {
+        Some(project_id) => project_checkout(&state, &auth_user, &req, project_id, &mut metadata).await?,
+        None => service_checkout(&state, &mut tx, &auth_user, &req, &mut metadata).await?,
+    };
+    let amount: i32 = line_items.iter().map(|item| item.amount).sum();
 
     // Get expert's Stripe Connect account ID (if they have one)
     let expert_stripe_account: Option<(Option<String>,)> = sqlx::query_as(
-        "SELECT stripe_account_id FROM expert_profiles WHERE id = $1"
+        "SELECT stripe_connect_id FROM expert_profiles WHERE id = $1"
     )
     .bind(expert_id)
     .fetch_optional(state.db.pool())
@@ -435,15 +441,6 @@ pub async fn create_checkout_session(
     // Calculate platform fee (10%)
     let platform_fee = (amount as f64 * 0.10) as i64;
 
-    // Build metadata
-    let mut metadata = HashMap::new();
-    metadata.insert("service_id".to_string(), req.service_id.to_string());
-    metadata.insert("buyer_id".to_string(), auth_user.id.to_string());
-    metadata.insert("expert_id".to_string(), expert_id.to_string());
-    if let Some(tier) = &req.package_tier {
-        metadata.insert("package_tier".to_string(), tier.clone());
-    }
-
     // Get frontend URL from config
     let frontend_url = std::env::var("FRONTEND_URL").unwrap_or_else(|_| "http://localhost:5173".to_string());
     let success_url = format!("{}/checkout/success?session_id={{CHECKOUT_SESSION_ID}}", frontend_url);
@@ -452,25 +449,25 @@ pub async fn create_checkout_session(
     // Create Stripe checkout session
     #[cfg(feature = "payments")]
     {
-        use crate::services::payment_service::stripe_service::StripeService;
+        use crate::services::payment_service::stripe_service::{ItemizedCheckout, StripeService};
 
         let stripe_key = std::env::var("STRIPE_SECRET_KEY")
             .map_err(|_| ApiError::Internal(anyhow::anyhow!("Stripe not configured")))?;
 
         let stripe = StripeService::new(&stripe_key);
 
-        let session = stripe.create_checkout_session(
-            &title,
-            amount as i64,
-            currency,
-            &success_url,
-            &cancel_url,
+        let session = stripe.create_itemized_checkout_session(ItemizedCheckout {
+            items: &line_items,
+            currency: &currency,
+            success_url: &success_url,
+            cancel_url: &cancel_url,
             metadata,
-            stripe_account_id.as_deref(),
+            expert_stripe_account_id: stripe_account_id.as_deref(),
             platform_fee,
-        )
+        })
         .await
         .map_err(|e| ApiError::Internal(anyhow::anyhow!("Stripe error: {}", e)))?;
+        tx.commit().await?;
 
         Ok(Json(SuccessResponse::new(CheckoutSessionResponse {
             session_id: session.id.to_string(),
@@ -480,14 +477,175 @@ pub async fn create_checkout_session(
 
     #[cfg(not(feature = "payments"))]
     {
+        let _ = (currency, stripe_account_id, platform_fee, success_url, cancel_url);
         // Return mock response for development without Stripe
+        let session_id = format!("cs_test_{}", uuid::Uuid::new_v4());
+        tx.commit().await?;
+
         Ok(Json(SuccessResponse::new(CheckoutSessionResponse {
-            session_id: format!("cs_test_{}", uuid::Uuid::new_v4()),
+            session_id,
             checkout_url: format!("{}/checkout/mock?service={}", frontend_url, req.service_id),
         })))
     }
 }
 
+/// Line items of a card-billed project the expert accepted. The price was agreed (and
+/// authorised for organisation members) when the project was created.
+async fn project_checkout(
+    state: &AppState,
+    auth_user: &AuthUser,
+    req: &CreateCheckoutSessionRequest,
+    project_id: Uuid,
+    metadata: &mut HashMap<String, String>,
+) -> Result<(Vec<InvoiceLineItem>, String, Uuid), ApiError> {
+    let project = ProjectService::get_by_id(&state.db, project_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .filter(|project| project.client_id == auth_user.id)
+        .ok_or(ApiError::NotFound("Project not found".into()))?;
+
+    if project.service_id.is_some_and(|id| id != req.service_id) {
+        return Err(ApiError::BadRequest("Service does not match the project".into()));
+    }
+    if project.billing_mode != BillingMode::Card || project.status != ProjectStatus::Accepted {
+        return Err(ApiError::Conflict("Only accepted card-billed projects can be paid".into()));
+    }
+
+    let (expert_profile_id,): (Uuid,) = sqlx::query_as("SELECT id FROM expert_profiles WHERE user_id = $1")
+        .bind(project.expert_id)
+        .fetch_one(state.db.pool())
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    metadata.insert("project_id".to_string(), project.id.to_string());
+    metadata.insert("expert_id".to_string(), project.expert_id.to_string());
+
+    let addons = ProjectService::get_addons(&state.db, project.id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok((project_line_items(&project, &addons), format!("{:?}", project.currency), expert_profile_id))
+}
+
+/// Line items of a project: its price without add-ons, then each add-on as a separate line
+/// (as on the monthly invoice)
+fn project_line_items(project: &Project, addons: &[ProjectAddon]) -> Vec<InvoiceLineItem> {
+    let base_price = project.price - addons.iter().map(|a| a.amount).sum::<i32>();
+
+    std::iter::once(InvoiceLineItem {
+        description: project.title.clone(),
+        quantity: 1,
+        unit_price: base_price,
+        amount: base_price,
+    })
+    .chain(addons.iter().map(|addon| InvoiceLineItem {
+        description: format!("{} – {}", project.title, addon.title),
+        quantity: addon.quantity as i32,
+        unit_price: addon.unit_price,
+        amount: addon.amount,
+    }))
+    .collect()
+}
+
+/// Direct service purchase: creates the project the checkout pays for, accepted at the
+/// package or custom price, inside the checkout's transaction. Add-ons are separate line items.
+async fn service_checkout(
+    state: &AppState,
+    conn: &mut PgConnection,
+    auth_user: &AuthUser,
+    req: &CreateCheckoutSessionRequest,
+    metadata: &mut HashMap<String, String>,
+) -> Result<(Vec<InvoiceLineItem>, String, Uuid), ApiError> {
+    let service = ServiceService::get_by_id(&state.db, req.service_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .filter(|service| service.is_active)
+        .ok_or(ApiError::NotFound("Service not found".into()))?;
+
+    let (expert_id,): (Uuid,) = sqlx::query_as("SELECT user_id FROM expert_profiles WHERE id = $1")
+        .bind(service.expert_id)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    // Determine the amount based on package tier or custom amount
+    let package = match &req.package_tier {
+        Some(tier) => Some(
+            ServiceService::get_packages(&state.db, service.id)
+                .await
+                .map_err(|e| ApiError::Internal(e.into()))?
+                .into_iter()
+                .find(|p| p.name.eq_ignore_ascii_case(tier))
+                .ok_or_else(|| ApiError::BadRequest(format!("Service has no {} package", tier)))?,
+        ),
+        None => None,
+    };
+    let amount = req
+        .custom_amount
+        .or(package.as_ref().map(|p| p.price))
+        .unwrap_or(service.price);
+
+    let currency = match req.currency.as_deref().map(str::to_uppercase).as_deref() {
+        None => service.currency.clone(),
+        Some("CHF") => Currency::CHF,
+        Some("EUR") => Currency::EUR,
+        Some(other) => return Err(ApiError::Validation(format!("Unsupported currency: {}", other))),
+    };
+
+    let payload = CreateProjectRequest {
+        expert_id,
+        service_id: Some(service.id),
+        package_id: package.as_ref().map(|p| p.id),
+        title: match &package {
+            Some(package) => format!("{} ({})", service.title, package.name),
+            None => service.title.clone(),
+        },
+        description: service.description.clone(),
+        requirements: None,
+        requirement_answers: None,
+        addons: req.addons.clone(),
+        budget: Some(amount),
+        currency,
+        deadline: None,
+        po_number: None,
+    };
+    let order = service_order(state, &payload).await?;
+    let total = amount as i64 + order.addons.iter().map(|a| a.amount as i64).sum::<i64>();
+
+    let subject = PurchaseSubject { service_id: Some(service.id), ..Default::default() };
+    OrganisationService::authorize_purchase(conn, auth_user.id, subject, total).await?;
+
+    // Buying at the listed price needs no acceptance by the expert
+    let secret_key = &state.settings.secrets.encryption_key;
+    let project = ProjectService::create(conn, secret_key, auth_user.id, payload, BillingMode::Card, &order)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+    let project = ProjectService::transition_tx(conn, project.id, ProjectStatus::Accepted, ProjectActor::System, None, None)
+        .await?;
+    let addons = ProjectService::get_addons_tx(conn, project.id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    // An expired session cancels the project again
+    metadata.insert("kind".to_string(), "service".to_string());
+    metadata.insert("project_id".to_string(), project.id.to_string());
+    metadata.insert("expert_id".to_string(), expert_id.to_string());
+    if let Some(tier) = &req.package_tier {
+        metadata.insert("package_tier".to_string(), tier.clone());
+    }
+    if !order.addons.is_empty() {
+        let addons = order
+            .addons
+            .iter()
+            .map(|addon| format!("{}:{}", addon.addon_id, addon.quantity))
+            .collect::<Vec<_>>()
+            .join(",");
+        metadata.insert("addons".to_string(), addons);
+    }
+
+    Ok((project_line_items(&project, &addons), format!("{:?}", project.currency), service.expert_id))
+}
+
 /// Get Connect account status for the authenticated expert
 pub async fn get_connect_status(
     State(state): State<AppState>,
@@ -701,6 +859,7 @@ pub async fn stripe_webhook(
     #[cfg(feature = "payments")]
     {
         use stripe::{Webhook, EventType, EventObject};
+        use crate::services::{ChangeRequestService, CheckoutPayment, TipService};
 
         // Get the Stripe signature from headers
         let signature = headers
@@ -718,13 +877,65 @@ pub async fn stripe_webhook(
 
         // Handle different event types
         match event.type_ {
+            EventType::CheckoutSessionCompleted if checkout_kind(&event.data.object) == Some("tip") => {
+                if let EventObject::CheckoutSession(session) = event.data.object {
+                    let tip_id = checkout_tip_id(&session)?;
+                    let payment_intent_id = session.payment_intent
+                        .map(|pi| match pi {
+                            stripe::Expandable::Id(id) => id.to_string(),
+                            stripe::Expandable::Object(obj) => obj.id.to_string(),
+                        });
+
+                    if TipService::mark_paid(&state.db, tip_id, payment_intent_id.as_deref())
+                        .await
+                        .map_err(|e| ApiError::Internal(e.into()))?
+                        .is_some()
+                    {
+                        tracing::info!("Tip paid: {}", tip_id);
+                    }
+                }
+            }
+
+            EventType::CheckoutSessionExpired if checkout_kind(&event.data.object) == Some("tip") => {
+                if let EventObject::CheckoutSession(session) = event.data.object {
+                    let tip_id = checkout_tip_id(&session)?;
+                    TipService::cancel_pending(&state.db, tip_id)
+                        .await
+                        .map_err(|e| ApiError::Internal(e.into()))?;
+                    tracing::info!("Tip checkout expired: {}", tip_id);
+                }
+            }
+
+            EventType::CheckoutSessionCompleted if checkout_kind(&event.data.object) == Some("change_request") => {
+                if let EventObject::CheckoutSession(session) = event.data.object {
+                    let change_id = checkout_change_request_id(&session)?;
+                    let payment_intent_id = session.payment_intent
+                        .map(|pi| match pi {
+                            stripe::Expandable::Id(id) => id.to_string(),
+                            stripe::Expandable::Object(obj) => obj.id.to_string(),
+                        });
+
+                    if ChangeRequestService::mark_paid(&state.db, change_id, payment_intent_id.as_deref())
+                        .await
+                        .map_err(|e| ApiError::Internal(e.into()))?
+                        .is_some()
+                    {
+                        tracing::info!("Change request charge paid: {}", change_id);
+                    }
+                }
+            }
+
             EventType::CheckoutSessionCompleted => {
                 if let EventObject::CheckoutSession(session) = event.data.object {
-                    // Extract metadata
+                    // Every checkout pays for a project; failing lets Stripe retry instead of
+                    // acknowledging money we cannot book
+                    let project_id = checkout_project_id(&session)?;
+                    let project = ProjectService::get_by_id(&state.db, project_id)
+                        .await
+                        .map_err(|e| ApiError::Internal(e.into()))?
+                        .ok_or_else(|| ApiError::NotFound(format!("Project {} of checkout {} not found", project_id, session.id)))?;
+
                     let metadata = session.metadata.unwrap_or_default();
-                    let service_id = metadata.get("service_id").cloned();
-                    let buyer_id = metadata.get("buyer_id").cloned();
-                    let expert_id = metadata.get("expert_id").cloned();
                     let package_tier = metadata.get("package_tier").cloned();
 
                     // Get payment intent ID
@@ -740,80 +951,72 @@ pub async fn stripe_webhook(
                         .map(|c| c.to_string())
                         .unwrap_or_else(|| "eur".to_string());
 
-                    // Create payment record if we have the required info
-                    if let (Some(buyer_id_str), Some(expert_id_str)) = (buyer_id, expert_id) {
-                        if let (Ok(buyer_uuid), Ok(expert_uuid)) = (
-                            buyer_id_str.parse::<Uuid>(),
-                            expert_id_str.parse::<Uuid>()
-                        ) {
-                            // Calculate platform fee (10%)
-                            let platform_fee = (amount as f64 * 0.10) as i32;
-                            let net_amount = amount - platform_fee;
-
-                            // Create payment record
-                            let _payment = sqlx::query(
-                                r#"
-                                INSERT INTO payments (
-                                    payer_id, payee_id, amount, currency,
-                                    platform_fee, net_amount, status,
-                                    stripe_payment_intent_id, paid_at,
-                                    description, metadata
-                                )
-                                VALUES ($1, $2, $3, $4, $5, $6, 'succeeded', $7, NOW(), $8, $9)
-                                "#
-                            )
-                            .bind(buyer_uuid)
-                            .bind(expert_uuid)
-                            .bind(amount)
-                            .bind(&currency)
-                            .bind(platform_fee)
-                            .bind(net_amount)
-                            .bind(payment_intent_id.as_deref())
-                            .bind(format!("Service purchase{}",
-                                package_tier.as_ref().map(|t| format!(" - {} package", t)).unwrap_or_default()
-                            ))
-                            .bind(sqlx::types::Json(serde_json::json!({
-                                "service_id": service_id,
-                                "package_tier": package_tier,
-                                "checkout_session_id": session.id.to_string()
-                            })))
-                            .execute(state.db.pool())
-                            .await
-                            .map_err(|e| ApiError::Internal(e.into()))?;
+                    let checkout = CheckoutPayment {
+                        session_id: session.id.to_string(),
+                        project_id,
+                        payer_id: project.client_id,
+                        payee_id: project.expert_id,
+                        amount,
+                        currency: currency.clone(),
+                        payment_intent_id,
+                        description: format!("Service purchase{}",
+                            package_tier.as_ref().map(|t| format!(" - {} package", t)).unwrap_or_default()
+                        ),
+                        metadata: serde_json::json!({
+                            "service_id": metadata.get("service_id"),
+                            "package_tier": package_tier,
+                            "addons": metadata.get("addons"),
+                        }),
+                    };
+                    if PaymentService::record_checkout(state.db.pool(), &checkout)
+                        .await
+                        .map_err(|e| ApiError::Internal(e.into()))?
+                        .is_none()
+                    {
+                        tracing::info!("Checkout session {} already recorded", session.id);
+                        return Ok(Json(SuccessResponse::new(())));
+                    }
 
-                            tracing::info!(
-                                "Payment recorded: {} {} from {} to {}",
-                                amount, currency, buyer_uuid, expert_uuid
-                            );
-
-                            // Send order confirmation email
-                            #[cfg(feature = "email")]
-                            if let Some(email_service) = &state.email {
-                                // Get buyer email
-                                if let Ok(Some((buyer_email,))) = sqlx::query_as::<_, (String,)>(
-                                    "SELECT email FROM users WHERE id = $1"
-                                )
-                                .bind(buyer_uuid)
-                                .fetch_optional(state.db.pool())
-                                .await {
-                                    let service_name = metadata.get("service_title")
-                                        .cloned()
-                                        .unwrap_or_else(|| "Dienstleistung".to_string());
-                                    
-                                    let _ = email_service.send_order_confirmation(
-                                        &buyer_email,
-                                        amount,
-                                        &currency,
-                                        &service_name,
-                                        &session.id.to_string(),
-                                    ).await;
-                                }
-                            }
+                    tracing::info!(
+                        "Payment recorded: {} {} from {} to {} for project {}",
+                        amount, currency, project.client_id, project.expert_id, project_id
+                    );
+
+                    // Send order confirmation email
+                    #[cfg(feature = "email")]
+                    if let Some(email_service) = &state.email {
+                        // Get buyer email
+                        if let Ok(Some((buyer_email,))) = sqlx::query_as::<_, (String,)>(
+                            "SELECT email FROM users WHERE id = $1"
+                        )
+                        .bind(project.client_id)
+                        .fetch_optional(state.db.pool())
+                        .await {
+                            let _ = email_service.send_order_confirmation(
+                                &buyer_email,
+                                amount,
+                                &currency,
+                                &project.title,
+                                session.id.as_str(),
+                            ).await;
                         }
                     }
                 }
             }
 
+            EventType::CheckoutSessionExpired if checkout_kind(&event.data.object) == Some("service") => {
+                if let EventObject::CheckoutSession(session) = event.data.object {
+                    let project_id = checkout_project_id(&session)?;
+                    if ProjectService::cancel_unpaid(&state.db, project_id, "Checkout expired")
+                        .await
+                        .map_err(|e| ApiError::Internal(e.into()))?
+                        .is_some()
+                    {
+                        tracing::info!("Checkout session {} expired, project {} cancelled", session.id, project_id);
+                    }
+                }
+            }
+
             EventType::CheckoutSessionExpired => {
                 if let EventObject::CheckoutSession(session) = event.data.object {
                     tracing::info!("Checkout session expired: {}", session.id);
@@ -823,16 +1026,9 @@ pub async fn stripe_webhook(
             EventType::PaymentIntentSucceeded => {
                 if let EventObject::PaymentIntent(intent) = event.data.object {
                     // Update any pending payment with this intent ID
-                    let _ = sqlx::query(
-                        r#"
-                        UPDATE payments
-                        SET status = 'succeeded', paid_at = NOW(), updated_at = NOW()
-                        WHERE stripe_payment_intent_id = $1 AND status = 'pending'
-                        "#
-                    )
-                    .bind(intent.id.to_string())
-                    .execute(state.db.pool())
-                    .await;
+                    PaymentService::record_intent_succeeded(state.db.pool(), intent.id.as_str())
+                        .await
+                        .map_err(|e| ApiError::Internal(e.into()))?;
 
                     tracing::info!("Payment intent succeeded: {}", intent.id);
                 }
@@ -872,23 +1068,9 @@ pub async fn stripe_webhook(
 
                     if let Some(pi_id) = payment_intent_id {
                         // Update payment with refund info
-                        let _ = sqlx::query(
-                            r#"
-                            UPDATE payments
-                            SET status = CASE
-                                WHEN $2 >= amount THEN 'refunded'::payment_status
-                                ELSE 'partially_refunded'::payment_status
-                            END,
-                            refund_amount = $2,
-                            refunded_at = NOW(),
-                            updated_at = NOW()
-                            WHERE stripe_payment_intent_id = $1
-                            "#
-                        )
-                        .bind(&pi_id)
-                        .bind(refund_amount)
-                        .execute(state.db.pool())
-                        .await;
+                        PaymentService::record_refund(state.db.pool(), &pi_id, refund_amount)
+                            .await
+                            .map_err(|e| ApiError::Internal(e.into()))?;
 
                         tracing::info!("Charge refunded: {} - {} cents", pi_id, refund_amount);
                     }
@@ -905,16 +1087,9 @@ pub async fn stripe_webhook(
 
                     if let Some(pi_id) = payment_intent_id {
                         // Mark payment as disputed
-                        let _ = sqlx::query(
-                            r#"
-                            UPDATE payments
-                            SET status = 'disputed'::payment_status, updated_at = NOW()
-                            WHERE stripe_payment_intent_id = $1
-                            "#
-                        )
-                        .bind(&pi_id)
-                        .execute(state.db.pool())
-                        .await;
+                        PaymentService::record_dispute(state.db.pool(), &pi_id)
+                            .await
+                            .map_err(|e| ApiError::Internal(e.into()))?;
 
                         tracing::warn!("Dispute created for payment: {}", pi_id);
                     }
@@ -965,3 +1140,70 @@ pub async fn stripe_webhook(
     }
 }
 
+/// Kind of checkout (e.g. "tip") stored in the session metadata
+#[cfg(feature = "payments")]
+fn checkout_kind(object: &stripe::EventObject) -> Option<&str> {
+    match object {
+        stripe::EventObject::CheckoutSession(session) => {
+            session.metadata.as_ref()?.get("kind").map(String::as_str)
+        }
+        _ => None,
+    }
+}
+
+/// Tip ID stored in the checkout session metadata
+#[cfg(feature = "payments")]
+fn checkout_tip_id(session: &stripe::CheckoutSession) -> Result<Uuid, ApiError> {
+    session
+        .metadata
+        .as_ref()
+        .and_then(|m| m.get("tip_id"))
+        .and_then(|id| id.parse().ok())
+        .ok_or_else(|| ApiError::BadRequest("Missing tip_id in checkout metadata".into()))
+}
+
+/// Project ID stored in the checkout session metadata
+#[cfg(feature = "payments")]
+fn checkout_project_id(session: &stripe::CheckoutSession) -> Result<Uuid, ApiError> {
+    session
+        .metadata
+        .as_ref()
+        .and_then(|m| m.get("project_id"))
+        .and_then(|id| id.parse().ok())
+        .ok_or_else(|| ApiError::BadRequest("Missing project_id in checkout metadata".into()))
+}
+
+/// Change request ID stored in the checkout session metadata
+#[cfg(feature = "payments")]
+fn checkout_change_request_id(session: &stripe::CheckoutSession) -> Result<Uuid, ApiError> {
+    session
+        .metadata
+        .as_ref()
+        .and_then(|m| m.get("change_request_id"))
+        .and_then(|id| id.parse().ok())
+        .ok_or_else(|| ApiError::BadRequest("Missing change_request_id in checkout metadata".into()))
+}
+
+/// Refund (part of) a card payment at the gateway. Payments without a payment
+/// intent (invoice billing) have nothing to refund there.
+pub(crate) async fn refund_at_gateway(payment: &Payment, amount: i32) -> Result<(), ApiError> {
+    #[cfg(feature = "payments")]
+    if let Some(intent_id) = payment.stripe_payment_intent_id.as_deref()
+        && amount > 0
+    {
+        use crate::services::payment_service::stripe_service::StripeService;
+
+        let stripe_key = std::env::var("STRIPE_SECRET_KEY")
+            .map_err(|_| ApiError::Internal(anyhow::anyhow!("Stripe not configured")))?;
+
+        StripeService::new(&stripe_key)
+            .create_refund(intent_id, amount as i64)
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!("Stripe refund for payment {} failed: {}", payment.id, e)))?;
+    }
+
+    #[cfg(not(feature = "payments"))]
+    let _ = (payment, amount);
+
+    Ok(())
+}
@@ -5,18 +5,29 @@ use validator::Validate;
 
 use crate::AppState;
 use crate::models::{
-    Project, CreateProjectRequest, UpdateProjectStatusRequest, RequestRevisionRequest,
-    ProjectFilters, PaginationParams, PaginatedResponse, PaginationMeta,
+    BillingMode, Project, ProjectActor, ProjectStatus, CreateProjectRequest, UpdateProjectStatusRequest, RequestRevisionRequest,
+    ProjectFilters, PaginationParams, PaginatedResponse, PaginationMeta, UserRole,
+    CreateMilestoneRequest, MilestoneStatus, ProjectAddon, ProjectEvent, ProjectMilestone, UpdateMilestoneStatusRequest,
+    PurchaseSubject, check_requirement_answers, price_addons,
+};
+use crate::services::{
+    BillingService, CancellationService, ClientService, DunningService, OrganisationService, ProjectEventService,
+    ProjectService, ServiceOrder, ServiceService,
 };
-use crate::services::ProjectService;
 use crate::middleware::auth::AuthUser;
 use super::{ApiError, ApiResult, SuccessResponse};
+use super::cancellations::get_project_for_party;
+use super::payments::refund_at_gateway;
 
 /// Delivery request body
 #[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct DeliverRequest {
     pub message: String,
+    /// External links, stored as new deliverable versions
     pub attachments: Option<Vec<String>>,
+    /// Previously uploaded deliverable versions to include
+    pub deliverable_ids: Option<Vec<Uuid>>,
 }
 
 /// Cancel request body
@@ -25,6 +36,30 @@ pub struct CancelRequest {
     pub reason: Option<String>,
 }
 
+/// Load a project and determine the user's role in its lifecycle
+async fn get_project_for_actor(
+    state: &AppState,
+    id: Uuid,
+    auth_user: &AuthUser,
+) -> Result<(Project, ProjectActor), ApiError> {
+    let project = ProjectService::get_by_id(&state.db, id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::NotFound("Project not found".to_string()))?;
+
+    let actor = if project.client_id == auth_user.id {
+        ProjectActor::Client
+    } else if project.expert_id == auth_user.id {
+        ProjectActor::Expert
+    } else if auth_user.role == UserRole::Admin {
+        ProjectActor::Admin
+    } else {
+        return Err(ApiError::Forbidden("Not authorized".to_string()));
+    };
+
+    Ok((project, actor))
+}
+
 /// List projects for current user
 pub async fn list_projects(
     State(state): State<AppState>,
@@ -50,13 +85,105 @@ pub async fn create_project(
 ) -> ApiResult<Project> {
     payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
 
-    let project = ProjectService::create(&state.db, auth_user.id, payload)
+    let order = service_order(&state, &payload).await?;
+    let amount = payload.budget.unwrap_or(0) as i64 + order.addons.iter().map(|a| a.amount as i64).sum::<i64>();
+
+    let billing_mode = billing_mode_for(&state, auth_user.id, amount).await?;
+    let subject = PurchaseSubject { service_id: payload.service_id, ..Default::default() };
+
+    // Authorised and created in one transaction, so concurrent purchases see each other's spend
+    let mut tx = state.db.pool().begin().await?;
+    OrganisationService::authorize_purchase(&mut tx, auth_user.id, subject, amount).await?;
+
+    let secret_key = &state.settings.secrets.encryption_key;
+    let project = ProjectService::create(&mut tx, secret_key, auth_user.id, payload, billing_mode, &order)
         .await
         .map_err(|e| ApiError::Internal(e.into()))?;
+    tx.commit().await?;
 
     Ok(Json(SuccessResponse::new(project)))
 }
 
+/// Check an order against the ordered service: package, add-ons and requirements answers.
+/// Missing answers can be submitted later.
+pub(crate) async fn service_order(state: &AppState, payload: &CreateProjectRequest) -> Result<ServiceOrder, ApiError> {
+    let selected_addons = payload.addons.as_deref().unwrap_or_default();
+    let Some(service_id) = payload.service_id else {
+        if !selected_addons.is_empty() {
+            return Err(ApiError::Validation("Add-ons can only be ordered with a service".to_string()));
+        }
+        return Ok(ServiceOrder::default());
+    };
+
+    let service = ServiceService::get_offered(&state.db, service_id, payload.expert_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::BadRequest("Service is not offered by this expert".to_string()))?;
+
+    let mut order = ServiceOrder {
+        delivery_days: Some(service.delivery_time_days),
+        agency_id: service.agency_id,
+        ..Default::default()
+    };
+    if let Some(package_id) = payload.package_id {
+        let package = ServiceService::get_packages(&state.db, service_id)
+            .await
+            .map_err(|e| ApiError::Internal(e.into()))?
+            .into_iter()
+            .find(|p| p.id == package_id)
+            .ok_or_else(|| ApiError::BadRequest("Package does not belong to this service".to_string()))?;
+        order.delivery_days = Some(package.delivery_time_days);
+    }
+    if !selected_addons.is_empty() {
+        let addons = ServiceService::get_addons(&state.db, service_id, false)
+            .await
+            .map_err(|e| ApiError::Internal(e.into()))?;
+        order.addons = price_addons(&addons, selected_addons).map_err(ApiError::Validation)?;
+    }
+    if let Some(answers) = &payload.requirement_answers {
+        order.answers = check_requirement_answers(&service.requirements_form, answers).map_err(ApiError::Validation)?;
+    }
+    order.requirements_form = service.requirements_form.0;
+
+    Ok(order)
+}
+
+/// Billing mode for a new project of the client.
+/// Fails if the client's new projects are paused or the amount exceeds their credit line.
+pub(crate) async fn billing_mode_for(state: &AppState, client_id: Uuid, amount: i64) -> Result<BillingMode, ApiError> {
+    // Organisation purchases use the billing contact's terms
+    let client_id = OrganisationService::billing_user(&state.db, client_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    // Clients with seriously overdue invoices cannot start new projects
+    if DunningService::is_paused(state.db.pool(), client_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+    {
+        return Err(ApiError::Forbidden(
+            "New projects are paused until your overdue invoices are settled".to_string(),
+        ));
+    }
+
+    // Verified clients with approved credit order without upfront payment
+    let profile = ClientService::get_profile_by_user(state.db.pool(), client_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    match profile {
+        Some(profile) if profile.billing_mode == BillingMode::Invoice => {
+            let credit_used = BillingService::get_credit_used(state.db.pool(), client_id)
+                .await
+                .map_err(|e| ApiError::Internal(e.into()))?;
+            BillingService::check_credit(&profile, credit_used, amount)
+                .map_err(ApiError::Forbidden)?;
+            Ok(BillingMode::Invoice)
+        }
+        _ => Ok(BillingMode::Card),
+    }
+}
+
 /// Get project by ID
 pub async fn get_project(
     State(state): State<AppState>,
@@ -68,8 +195,14 @@ pub async fn get_project(
         .map_err(|e| ApiError::Internal(e.into()))?
         .ok_or_else(|| ApiError::NotFound("Project not found".to_string()))?;
 
-    // Check authorization - only client or expert can view
-    if project.client_id != auth_user.id && project.expert_id != auth_user.id {
+    // Check authorization - only client, expert or members of the client's organisation can view
+    if project.client_id != auth_user.id
+        && project.expert_id != auth_user.id
+        && OrganisationService::project_role(&state.db, &project, auth_user.id)
+            .await
+            .map_err(|e| ApiError::Internal(e.into()))?
+            .is_none()
+    {
         return Err(ApiError::Forbidden("Not authorized to view this project".to_string()));
     }
 
@@ -83,24 +216,31 @@ pub async fn update_status(
     Path(id): Path<Uuid>,
     Json(payload): Json<UpdateProjectStatusRequest>,
 ) -> ApiResult<Project> {
-    // Get existing project
-    let existing = ProjectService::get_by_id(&state.db, id)
-        .await
-        .map_err(|e| ApiError::Internal(e.into()))?
-        .ok_or_else(|| ApiError::NotFound("Project not found".to_string()))?;
+    let (existing, actor) = get_project_for_actor(&state, id, &auth_user).await?;
 
-    // Check authorization
-    if existing.client_id != auth_user.id && existing.expert_id != auth_user.id {
-        return Err(ApiError::Forbidden("Not authorized".to_string()));
-    }
+    let mut project = ProjectService::transition(&state.db, id, payload.status, actor, Some(auth_user.id), payload.message.as_deref())
+        .await?;
 
-    let project = ProjectService::update_status(&state.db, id, payload.status)
-        .await
-        .map_err(|e| ApiError::Internal(e.into()))?;
+    // Invoice-billed projects need no upfront payment
+    if project.status == ProjectStatus::Accepted && existing.billing_mode == BillingMode::Invoice {
+        project = ProjectService::transition(&state.db, id, ProjectStatus::Paid, ProjectActor::System, None, None)
+            .await?;
+    }
 
     Ok(Json(SuccessResponse::new(project)))
 }
 
+/// Statuses the current user may move the project to
+pub async fn get_transitions(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Vec<ProjectStatus>> {
+    let (project, actor) = get_project_for_actor(&state, id, &auth_user).await?;
+
+    Ok(Json(SuccessResponse::new(project.status.allowed_transitions(actor))))
+}
+
 /// Deliver project (expert submits deliverables)
 pub async fn deliver_project(
     State(state): State<AppState>,
@@ -119,9 +259,17 @@ pub async fn deliver_project(
         return Err(ApiError::Forbidden("Only the expert can deliver".to_string()));
     }
 
-    let project = ProjectService::deliver(&state.db, id, &payload.message)
-        .await
-        .map_err(|e| ApiError::Internal(e.into()))?;
+    let attachments = payload.attachments.unwrap_or_default();
+    let deliverable_ids = payload.deliverable_ids.unwrap_or_default();
+    let project = ProjectService::deliver(
+        &state.db,
+        id,
+        auth_user.id,
+        &payload.message,
+        &attachments,
+        &deliverable_ids,
+    )
+    .await?;
 
     Ok(Json(SuccessResponse::new(project)))
 }
@@ -146,14 +294,15 @@ pub async fn request_revision(
         return Err(ApiError::Forbidden("Only the client can request revision".to_string()));
     }
 
-    // Check revision limit
-    if existing.revisions_used >= existing.revisions_allowed {
-        return Err(ApiError::BadRequest("Revision limit reached".to_string()));
-    }
-
-    let project = ProjectService::request_revision(&state.db, id, &payload.feedback)
-        .await
-        .map_err(|e| ApiError::Internal(e.into()))?;
+    let deliverable_ids = payload.deliverable_ids.unwrap_or_default();
+    let project = ProjectService::request_revision(
+        &state.db,
+        id,
+        auth_user.id,
+        &payload.feedback,
+        &deliverable_ids,
+    )
+    .await?;
 
     Ok(Json(SuccessResponse::new(project)))
 }
@@ -175,35 +324,145 @@ pub async fn complete_project(
         return Err(ApiError::Forbidden("Only the client can complete the project".to_string()));
     }
 
-    let project = ProjectService::complete(&state.db, id)
-        .await
-        .map_err(|e| ApiError::Internal(e.into()))?;
+    let project = ProjectService::complete(&state.db, id, ProjectActor::Client, Some(auth_user.id)).await?;
 
     Ok(Json(SuccessResponse::new(project)))
 }
 
-/// Cancel project
+/// Cancel project, applying the cancellation policy to the payment
 pub async fn cancel_project(
     State(state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
     Path(id): Path<Uuid>,
     Json(payload): Json<CancelRequest>,
 ) -> ApiResult<Project> {
-    // Get existing project
-    let existing = ProjectService::get_by_id(&state.db, id)
+    let (existing, party) = get_project_for_party(&state, id, auth_user.id).await?;
+
+    let (payment, breakdown) = CancellationService::preview(&state.db, &existing, party)
         .await
-        .map_err(|e| ApiError::Internal(e.into()))?
-        .ok_or_else(|| ApiError::NotFound("Project not found".to_string()))?;
+        .map_err(|e| ApiError::Internal(e.into()))?;
 
-    // Check authorization
-    if existing.client_id != auth_user.id && existing.expert_id != auth_user.id {
-        return Err(ApiError::Forbidden("Not authorized".to_string()));
+    let mut tx = state.db.pool().begin().await?;
+    let (project, cancellation) = CancellationService::apply_tx(
+        &mut tx,
+        &existing,
+        auth_user.id,
+        party,
+        payment.as_ref(),
+        &breakdown,
+        payload.reason.as_deref(),
+    )
+    .await?;
+
+    // Refund at the gateway before committing, so a failed refund leaves the project open
+    if let Some(payment) = &payment {
+        refund_at_gateway(payment, cancellation.refund_amount).await?;
     }
+    tx.commit().await?;
 
-    let project = ProjectService::cancel(&state.db, id, payload.reason.as_deref())
+    if let Err(e) = CancellationService::notify_parties(&state.db, &project, &cancellation).await {
+        tracing::error!("Failed to notify parties about cancelled project {}: {}", project.id, e);
+    }
+
+    Ok(Json(SuccessResponse::new(project)))
+}
+
+/// Project timeline (status changes, deliveries, payments, ...), newest first
+pub async fn get_timeline(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Query(pagination): Query<PaginationParams>,
+) -> ApiResult<PaginatedResponse<ProjectEvent>> {
+    get_project_for_actor(&state, id, &auth_user).await?;
+
+    let (events, total) = ProjectEventService::get_timeline(&state.db, id, &pagination)
         .await
         .map_err(|e| ApiError::Internal(e.into()))?;
 
-    Ok(Json(SuccessResponse::new(project)))
+    Ok(Json(SuccessResponse::new(PaginatedResponse {
+        data: events,
+        meta: PaginationMeta::new(pagination.page, pagination.per_page, total),
+    })))
 }
 
+/// Add-ons ordered with the project
+pub async fn list_addons(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Vec<ProjectAddon>> {
+    get_project_for_actor(&state, id, &auth_user).await?;
+
+    let addons = ProjectService::get_addons(&state.db, id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(addons)))
+}
+
+/// List project milestones
+pub async fn list_milestones(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Vec<ProjectMilestone>> {
+    get_project_for_actor(&state, id, &auth_user).await?;
+
+    let milestones = ProjectService::get_milestones(&state.db, id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(milestones)))
+}
+
+/// Add a milestone (client or expert, while the project is open)
+pub async fn create_milestone(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<CreateMilestoneRequest>,
+) -> ApiResult<ProjectMilestone> {
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+
+    let (project, actor) = get_project_for_actor(&state, id, &auth_user).await?;
+    if project.status.is_terminal() {
+        return Err(ApiError::Conflict("Project is already closed".to_string()));
+    }
+
+    let milestone = ProjectService::create_milestone(&state.db, id, actor, auth_user.id, &payload)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(milestone)))
+}
+
+/// Update milestone status; only the client can confirm a milestone as completed
+pub async fn update_milestone_status(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, milestone_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<UpdateMilestoneStatusRequest>,
+) -> ApiResult<ProjectMilestone> {
+    let (project, actor) = get_project_for_actor(&state, id, &auth_user).await?;
+    if project.status.is_terminal() {
+        return Err(ApiError::Conflict("Project is already closed".to_string()));
+    }
+    if payload.status == MilestoneStatus::Completed && actor == ProjectActor::Expert {
+        return Err(ApiError::Forbidden("Only the client can confirm a milestone".to_string()));
+    }
+
+    let milestone = ProjectService::update_milestone_status(
+        &state.db,
+        id,
+        milestone_id,
+        payload.status,
+        actor,
+        auth_user.id,
+    )
+    .await
+    .map_err(|e| ApiError::Internal(e.into()))?
+    .ok_or_else(|| ApiError::NotFound("Milestone not found".to_string()))?;
+
+    Ok(Json(SuccessResponse::new(milestone)))
+}
@@ -0,0 +1,170 @@
+//! Requirements form of a project: answers of the client, files and secrets for the expert
+
+use axum::{extract::{Multipart, Path, State}, Extension, Json};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::AppState;
+use crate::models::{
+    CancellationParty, DeliverableDownload, Project, ProjectRequirements, RequirementField, RequirementFieldType,
+    SubmitRequirementsRequest, check_requirement_answers,
+};
+use crate::services::{RequirementFile, RequirementService};
+use crate::middleware::auth::AuthUser;
+use super::{ApiError, ApiResult, SuccessResponse};
+use super::cancellations::get_project_for_party;
+use super::deliverables::{delete_object, presign_download, put_object};
+
+/// Load a project whose requirements the client may still answer
+async fn get_project_for_answers(state: &AppState, id: Uuid, user_id: Uuid) -> Result<Project, ApiError> {
+    let (project, party) = get_project_for_party(state, id, user_id).await?;
+    if party != CancellationParty::Client {
+        return Err(ApiError::Forbidden("Only the client can answer the requirements".to_string()));
+    }
+    if project.status.is_terminal() {
+        return Err(ApiError::Conflict("Project is already closed".to_string()));
+    }
+
+    Ok(project)
+}
+
+fn form_field<'a>(project: &'a Project, field_id: &str) -> Result<&'a RequirementField, ApiError> {
+    project
+        .requirements_form
+        .iter()
+        .find(|f| f.id == field_id)
+        .ok_or_else(|| ApiError::NotFound("Requirements field not found".to_string()))
+}
+
+fn requirement_key(project_id: Uuid, file_name: &str) -> String {
+    let extension = file_name.rsplit('.').next().unwrap_or("bin");
+    format!("requirements/{}/{}.{}", project_id, Uuid::new_v4(), extension)
+}
+
+/// Requirements form of a project with the answers (secrets only visible to the expert)
+pub async fn get_requirements(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<ProjectRequirements> {
+    let (project, party) = get_project_for_party(&state, id, auth_user.id).await?;
+
+    let answers = RequirementService::list_answers(&state.db, id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+    // Secrets are decrypted for the expert only
+    let secret_key = (party == CancellationParty::Expert).then_some(&state.settings.secrets.encryption_key);
+
+    Ok(Json(SuccessResponse::new(ProjectRequirements {
+        missing: RequirementService::missing_fields(&project.requirements_form, &answers),
+        answers: answers.iter().map(|a| a.view(secret_key)).collect(),
+        form: project.requirements_form.0,
+        completed_at: project.requirements_completed_at,
+    })))
+}
+
+/// Answer fields of the requirements form (client); completing it starts the work
+pub async fn submit_requirements(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<SubmitRequirementsRequest>,
+) -> ApiResult<Project> {
+    let project = get_project_for_answers(&state, id, auth_user.id).await?;
+
+    if payload.answers.is_empty() {
+        return Err(ApiError::Validation("No answers provided".to_string()));
+    }
+    let answers = check_requirement_answers(&project.requirements_form, &payload.answers)
+        .map_err(ApiError::Validation)?;
+
+    let project =
+        RequirementService::submit(&state.db, &state.settings.secrets.encryption_key, id, auth_user.id, &answers).await?;
+
+    Ok(Json(SuccessResponse::new(project)))
+}
+
+/// Upload the file answering a file field (multipart: `file`)
+pub async fn upload_requirement_file(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, field_id)): Path<(Uuid, String)>,
+    mut multipart: Multipart,
+) -> ApiResult<Project> {
+    let project = get_project_for_answers(&state, id, auth_user.id).await?;
+    let field = form_field(&project, &field_id)?;
+    if field.field_type != RequirementFieldType::File {
+        return Err(ApiError::Validation(format!("Field '{}' is not a file field", field.id)));
+    }
+
+    let mut file = None;
+    while let Some(upload) = multipart
+        .next_field()
+        .await
+        .map_err(|e| ApiError::BadRequest(format!("Invalid multipart data: {}", e)))?
+    {
+        if upload.name() == Some("file") {
+            let file_name = upload.file_name().unwrap_or("file").to_string();
+            let content_type = upload.content_type().unwrap_or("application/octet-stream").to_string();
+            let data = upload
+                .bytes()
+                .await
+                .map_err(|e| ApiError::BadRequest(format!("Upload failed: {}", e)))?;
+            file = Some((file_name, content_type, data));
+        }
+    }
+
+    let (file_name, content_type, data) =
+        file.ok_or_else(|| ApiError::Validation("No file provided".to_string()))?;
+    if file_name.chars().count() > 255 || content_type.len() > 100 {
+        return Err(ApiError::Validation("File name or type too long".to_string()));
+    }
+    if data.is_empty() {
+        return Err(ApiError::Validation("File is empty".to_string()));
+    }
+    if data.len() as i64 > state.settings.deliverables.max_file_size {
+        return Err(ApiError::Validation("File too large".to_string()));
+    }
+
+    let key = requirement_key(id, &file_name);
+    put_object(&state, &key, &data, &content_type).await?;
+
+    let file = RequirementFile {
+        file_name,
+        file_type: content_type,
+        file_size: data.len() as i64,
+        storage_key: key.clone(),
+    };
+    let (project, replaced) = match RequirementService::submit_file(&state.db, id, auth_user.id, field, file).await {
+        Ok(result) => result,
+        Err(e) => {
+            delete_object(&state, &key).await;
+            return Err(e.into());
+        }
+    };
+    if let Some(replaced) = replaced {
+        delete_object(&state, &replaced).await;
+    }
+
+    Ok(Json(SuccessResponse::new(project)))
+}
+
+/// Time-limited download link of a file answer (client and expert of the project only)
+pub async fn download_requirement_file(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, field_id)): Path<(Uuid, String)>,
+) -> ApiResult<DeliverableDownload> {
+    get_project_for_party(&state, id, auth_user.id).await?;
+
+    let key = RequirementService::get_answer(&state.db, id, &field_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .and_then(|a| a.storage_key)
+        .ok_or_else(|| ApiError::NotFound("File not found".to_string()))?;
+
+    Ok(Json(SuccessResponse::new(DeliverableDownload {
+        url: presign_download(&state, &key).await?,
+        expires_at: Some(Utc::now() + Duration::seconds(state.settings.deliverables.url_expiry_secs as i64)),
+    })))
+}
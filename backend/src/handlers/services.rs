@@ -4,13 +4,75 @@ use validator::Validate;
 
 use crate::AppState;
 use crate::models::{
-    Service, ServicePackage, CreateServiceRequest, ServiceSearchFilters,
-    PaginationParams, PaginatedResponse, UserRole,
+    Service, ServiceAddon, ServicePackage, CreateServiceAddonRequest, CreateServiceRequest, ServiceSearchFilters,
+    PaginationParams, PaginatedResponse, UserRole, check_requirements_form,
+};
+use crate::services::{
+    AgencyService, CancellationService, ServiceService, ExpertService, FavouriteService, MAX_SERVICE_ADDONS,
 };
-use crate::services::{ServiceService, ExpertService};
 use crate::middleware::auth::AuthUser;
 use super::{ApiError, ApiResult, SuccessResponse, EmptyResponse};
 
+/// Ensure a cancellation policy exists and may be used by the user
+async fn check_cancellation_policy(
+    state: &AppState,
+    policy_id: Option<Uuid>,
+    auth_user: &AuthUser,
+) -> Result<(), ApiError> {
+    let Some(policy_id) = policy_id else {
+        return Ok(());
+    };
+
+    let policy = CancellationService::get_policy(&state.db, policy_id).await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::NotFound("Cancellation policy not found".to_string()))?;
+
+    let usable = policy.owner_id.map(|owner| owner == auth_user.id).unwrap_or(true);
+    if !usable && auth_user.role != UserRole::Admin {
+        return Err(ApiError::Forbidden("Cancellation policy belongs to another expert".to_string()));
+    }
+
+    Ok(())
+}
+
+/// Ensure the user may sell services under the agency (owner or manager)
+async fn check_agency(state: &AppState, agency_id: Option<Uuid>, auth_user: &AuthUser) -> Result<(), ApiError> {
+    let Some(agency_id) = agency_id else {
+        return Ok(());
+    };
+
+    let role = AgencyService::role(&state.db, agency_id, auth_user.id).await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+    if !role.is_some_and(|r| r.can_manage()) && auth_user.role != UserRole::Admin {
+        return Err(ApiError::Forbidden("Only agency owners and managers can add services to an agency".to_string()));
+    }
+
+    Ok(())
+}
+
+/// Whether the user created the service (or is an admin)
+async fn is_service_owner(state: &AppState, service: &Service, auth_user: &AuthUser) -> Result<bool, ApiError> {
+    let expert = ExpertService::get_by_user_id(&state.db, auth_user.id).await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(expert.map(|e| e.id == service.expert_id).unwrap_or(false) || auth_user.role == UserRole::Admin)
+}
+
+/// Whether the user may manage the service: its creator, an owner or manager of its agency, or an admin
+async fn can_manage_service(state: &AppState, service: &Service, auth_user: &AuthUser) -> Result<bool, ApiError> {
+    if is_service_owner(state, service, auth_user).await? {
+        return Ok(true);
+    }
+
+    let Some(agency_id) = service.agency_id else {
+        return Ok(false);
+    };
+    let role = AgencyService::role(&state.db, agency_id, auth_user.id).await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(role.is_some_and(|r| r.can_manage()))
+}
+
 /// List services with filters
 pub async fn list_services(
     State(state): State<AppState>,
@@ -34,16 +96,26 @@ pub async fn create_service(
 ) -> ApiResult<Service> {
     // Validate input
     payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+    if let Some(form) = &payload.requirements_form {
+        check_requirements_form(form).map_err(ApiError::Validation)?;
+    }
 
     // Get expert profile for this user
     let expert = ExpertService::get_by_user_id(&state.db, auth_user.id).await
         .map_err(|e| ApiError::Internal(e.into()))?
         .ok_or_else(|| ApiError::BadRequest("You must have an expert profile to create services".to_string()))?;
 
+    check_cancellation_policy(&state, payload.cancellation_policy_id, &auth_user).await?;
+    check_agency(&state, payload.agency_id, &auth_user).await?;
+
     // Create the service
     let service = ServiceService::create(&state.db, expert.id, payload).await
         .map_err(|e| ApiError::Internal(e.into()))?;
 
+    if let Err(e) = FavouriteService::notify_new_service(&state.db, &service).await {
+        tracing::error!("Failed to notify saved-expert followers of service {}: {}", service.id, e);
+    }
+
     Ok(Json(SuccessResponse::new(service)))
 }
 
@@ -71,22 +143,30 @@ pub async fn update_service(
 ) -> ApiResult<Service> {
     // Validate input
     payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+    if let Some(form) = &payload.requirements_form {
+        check_requirements_form(form).map_err(ApiError::Validation)?;
+    }
 
     // Get existing service
     let existing = ServiceService::get_by_id(&state.db, id).await
         .map_err(|e| ApiError::Internal(e.into()))?
         .ok_or_else(|| ApiError::NotFound("Service not found".to_string()))?;
 
-    // Get expert profile
-    let expert = ExpertService::get_by_user_id(&state.db, auth_user.id).await
-        .map_err(|e| ApiError::Internal(e.into()))?;
-
-    // Check ownership (or admin)
-    let is_owner = expert.map(|e| e.id == existing.expert_id).unwrap_or(false);
-    if !is_owner && auth_user.role != UserRole::Admin {
+    // Check ownership (agency managers and admins too)
+    if !can_manage_service(&state, &existing, &auth_user).await? {
         return Err(ApiError::Forbidden("Not authorized to update this service".to_string()));
     }
 
+    check_cancellation_policy(&state, payload.cancellation_policy_id, &auth_user).await?;
+
+    // Only the creator can move a service into or out of an agency
+    if payload.agency_id != existing.agency_id {
+        if !is_service_owner(&state, &existing, &auth_user).await? {
+            return Err(ApiError::Forbidden("Only the creator can change the agency of a service".to_string()));
+        }
+        check_agency(&state, payload.agency_id, &auth_user).await?;
+    }
+
     // Update the service
     let service = ServiceService::update(&state.db, id, payload).await
         .map_err(|e| ApiError::Internal(e.into()))?;
@@ -105,13 +185,8 @@ pub async fn delete_service(
         .map_err(|e| ApiError::Internal(e.into()))?
         .ok_or_else(|| ApiError::NotFound("Service not found".to_string()))?;
 
-    // Get expert profile
-    let expert = ExpertService::get_by_user_id(&state.db, auth_user.id).await
-        .map_err(|e| ApiError::Internal(e.into()))?;
-
-    // Check ownership (or admin)
-    let is_owner = expert.map(|e| e.id == existing.expert_id).unwrap_or(false);
-    if !is_owner && auth_user.role != UserRole::Admin {
+    // Check ownership (agency managers and admins too)
+    if !can_manage_service(&state, &existing, &auth_user).await? {
         return Err(ApiError::Forbidden("Not authorized to delete this service".to_string()));
     }
 
@@ -138,6 +213,90 @@ pub async fn get_packages(
     Ok(Json(SuccessResponse::new(packages)))
 }
 
+/// Load a service the user may manage (owner, agency manager or admin)
+async fn get_service_for_owner(state: &AppState, id: Uuid, auth_user: &AuthUser) -> Result<Service, ApiError> {
+    let service = ServiceService::get_by_id(&state.db, id).await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::NotFound("Service not found".to_string()))?;
+
+    if !can_manage_service(state, &service, auth_user).await? {
+        return Err(ApiError::Forbidden("Not authorized to manage this service".to_string()));
+    }
+
+    Ok(service)
+}
+
+/// Get the active add-ons of a service
+pub async fn get_addons(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Vec<ServiceAddon>> {
+    let _service = ServiceService::get_by_id(&state.db, id).await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::NotFound("Service not found".to_string()))?;
+
+    let addons = ServiceService::get_addons(&state.db, id, false).await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(addons)))
+}
+
+/// Create an add-on (service owner)
+pub async fn create_addon(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<CreateServiceAddonRequest>,
+) -> ApiResult<ServiceAddon> {
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+    get_service_for_owner(&state, id, &auth_user).await?;
+
+    let count = ServiceService::count_addons(&state.db, id).await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+    if count >= MAX_SERVICE_ADDONS {
+        return Err(ApiError::Conflict(format!("A service can have at most {} add-ons", MAX_SERVICE_ADDONS)));
+    }
+
+    let addon = ServiceService::create_addon(&state.db, id, &payload).await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(addon)))
+}
+
+/// Update an add-on (service owner); projects keep the price they were ordered at
+pub async fn update_addon(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, addon_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<CreateServiceAddonRequest>,
+) -> ApiResult<ServiceAddon> {
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+    get_service_for_owner(&state, id, &auth_user).await?;
+
+    let addon = ServiceService::update_addon(&state.db, id, addon_id, &payload).await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::NotFound("Add-on not found".to_string()))?;
+
+    Ok(Json(SuccessResponse::new(addon)))
+}
+
+/// Delete an add-on (service owner)
+pub async fn delete_addon(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, addon_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<EmptyResponse>, ApiError> {
+    get_service_for_owner(&state, id, &auth_user).await?;
+
+    let deleted = ServiceService::delete_addon(&state.db, id, addon_id).await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+    if !deleted {
+        return Err(ApiError::NotFound("Add-on not found".to_string()));
+    }
+
+    Ok(Json(EmptyResponse::new("Add-on deleted")))
+}
+
 /// Get featured services
 pub async fn get_featured_services(
     State(state): State<AppState>,
@@ -0,0 +1,190 @@
+//! Tips & bonuses for completed projects
+
+use axum::{extract::{Path, State}, Extension, Json};
+use std::collections::HashMap;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::AppState;
+use crate::models::{
+    CreateTipRequest, Project, ProjectStatus, ProjectTip, TipCheckoutResponse, TipOptions, TipPreset,
+};
+use crate::services::{ProjectService, TipService};
+use crate::middleware::auth::AuthUser;
+use super::{ApiError, ApiResult, SuccessResponse};
+
+/// Load a completed project and ensure the user is its client
+async fn get_tippable_project(state: &AppState, id: Uuid, user_id: Uuid) -> Result<Project, ApiError> {
+    let project = ProjectService::get_by_id(&state.db, id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::NotFound("Project not found".to_string()))?;
+
+    if project.client_id != user_id {
+        return Err(ApiError::Forbidden("Only the client can tip the expert".to_string()));
+    }
+
+    if project.status != ProjectStatus::Completed {
+        return Err(ApiError::BadRequest("Tips are only possible for completed projects".to_string()));
+    }
+
+    Ok(project)
+}
+
+/// Get preset tip options for a completed project
+pub async fn get_tip_options(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<TipOptions> {
+    let project = get_tippable_project(&state, id, auth_user.id).await?;
+    let settings = &state.settings.tips;
+
+    let presets = settings
+        .preset_percentages
+        .iter()
+        .map(|&percent| TipPreset {
+            percent,
+            amount: TipService::preset_amount(project.price, percent)
+                .clamp(settings.min_amount, settings.max_amount),
+        })
+        .collect();
+
+    Ok(Json(SuccessResponse::new(TipOptions {
+        project_id: project.id,
+        currency: format!("{:?}", project.currency),
+        presets,
+        min_amount: settings.min_amount,
+        max_amount: settings.max_amount,
+        platform_fee_percent: settings.platform_fee_rate * 100.0,
+    })))
+}
+
+/// List tips for a project (client or expert)
+pub async fn list_tips(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Vec<ProjectTip>> {
+    let project = ProjectService::get_by_id(&state.db, id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?
+        .ok_or_else(|| ApiError::NotFound("Project not found".to_string()))?;
+
+    if project.client_id != auth_user.id && project.expert_id != auth_user.id {
+        return Err(ApiError::Forbidden("Not authorized".to_string()));
+    }
+
+    let tips = TipService::get_for_project(&state.db, id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(tips)))
+}
+
+/// Create a tip and start the checkout for it
+pub async fn create_tip(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<CreateTipRequest>,
+) -> ApiResult<TipCheckoutResponse> {
+    payload.validate().map_err(|e| ApiError::Validation(e.to_string()))?;
+
+    let project = get_tippable_project(&state, id, auth_user.id).await?;
+    let settings = &state.settings.tips;
+
+    let amount = match (payload.preset_percent, payload.custom_amount) {
+        (Some(percent), None) => {
+            if !settings.preset_percentages.contains(&percent) {
+                return Err(ApiError::Validation("Unknown tip preset".to_string()));
+            }
+            TipService::preset_amount(project.price, percent)
+                .clamp(settings.min_amount, settings.max_amount)
+        }
+        (None, Some(amount)) => amount,
+        _ => {
+            return Err(ApiError::Validation(
+                "Either presetPercent or customAmount is required".to_string(),
+            ))
+        }
+    };
+
+    if amount < settings.min_amount || amount > settings.max_amount {
+        return Err(ApiError::Validation(format!(
+            "Tip must be between {} and {} cents",
+            settings.min_amount, settings.max_amount
+        )));
+    }
+
+    let tip = TipService::create(
+        &state.db,
+        &project,
+        amount,
+        settings.platform_fee_rate,
+        payload.preset_percent,
+        payload.message.as_deref(),
+    )
+    .await
+    .map_err(|e| ApiError::Internal(e.into()))?;
+
+    let mut metadata = HashMap::new();
+    metadata.insert("kind".to_string(), "tip".to_string());
+    metadata.insert("tip_id".to_string(), tip.id.to_string());
+    metadata.insert("project_id".to_string(), project.id.to_string());
+
+    let success_url = format!("{}/projects/{}?tip=success", state.settings.frontend_url, project.id);
+    let cancel_url = format!("{}/projects/{}", state.settings.frontend_url, project.id);
+
+    #[cfg(feature = "payments")]
+    let (session_id, checkout_url) = {
+        use crate::services::payment_service::stripe_service::StripeService;
+
+        let stripe_key = std::env::var("STRIPE_SECRET_KEY")
+            .map_err(|_| ApiError::Internal(anyhow::anyhow!("Stripe not configured")))?;
+
+        let stripe_account: Option<(Option<String>,)> = sqlx::query_as(
+            "SELECT stripe_account_id FROM expert_profiles WHERE user_id = $1"
+        )
+        .bind(project.expert_id)
+        .fetch_optional(state.db.pool())
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+        let session = StripeService::new(&stripe_key)
+            .create_checkout_session(
+                &format!("Trinkgeld - {}", project.title),
+                tip.amount as i64,
+                &tip.currency,
+                &success_url,
+                &cancel_url,
+                metadata,
+                stripe_account.and_then(|(id,)| id).as_deref(),
+                tip.platform_fee as i64,
+            )
+            .await
+            .map_err(|e| ApiError::Internal(anyhow::anyhow!("Stripe error: {}", e)))?;
+
+        (session.id.to_string(), session.url.unwrap_or_default())
+    };
+
+    #[cfg(not(feature = "payments"))]
+    let (session_id, checkout_url) = {
+        // Mock checkout for development without Stripe
+        let _ = (metadata, success_url, cancel_url);
+        (
+            format!("cs_test_{}", Uuid::new_v4()),
+            format!("{}/checkout/mock?tip={}", state.settings.frontend_url, tip.id),
+        )
+    };
+
+    let tip = TipService::set_checkout_session(&state.db, tip.id, &session_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))?;
+
+    Ok(Json(SuccessResponse::new(TipCheckoutResponse {
+        tip,
+        session_id,
+        checkout_url,
+    })))
+}
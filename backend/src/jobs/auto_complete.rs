@@ -0,0 +1,58 @@
+//! Auto-completion job: reminds clients of pending deliveries and completes them after the approval period
+
+use chrono::Utc;
+
+use crate::AppState;
+use crate::services::AutoCompleteService;
+use crate::utils::business_days_between;
+
+pub async fn run(state: AppState) -> anyhow::Result<()> {
+    let settings = &state.settings.auto_complete;
+    let today = Utc::now().date_naive();
+
+    for project in AutoCompleteService::get_awaiting_approval(&state.db).await? {
+        let deadline = AutoCompleteService::deadline(project.delivered_at.date_naive(), settings);
+
+        if today >= deadline {
+            let reason = AutoCompleteService::completion_reason(settings);
+            match AutoCompleteService::complete(&state.db, &project, &reason).await {
+                Ok(()) => tracing::info!("Project {} completed automatically", project.id),
+                Err(e) => tracing::warn!("Failed to auto-complete project {}: {}", project.id, e),
+            }
+            continue;
+        }
+
+        let remaining = business_days_between(today, deadline);
+        let level = AutoCompleteService::reminder_level(remaining, settings);
+        if level <= project.auto_complete_reminders {
+            continue;
+        }
+
+        match AutoCompleteService::remind(&state.db, &project, level, deadline).await {
+            Ok(true) => {}
+            Ok(false) => continue,
+            Err(e) => {
+                tracing::warn!("Failed to remind client of project {}: {}", project.id, e);
+                continue;
+            }
+        }
+
+        #[cfg(feature = "email")]
+        if let Some(email_service) = &state.email
+            && let Err(e) = email_service
+                .send_completion_reminder(
+                    &project.client_email,
+                    &project.client_first_name,
+                    &project.client_language,
+                    &project.title,
+                    project.id,
+                    deadline,
+                )
+                .await
+        {
+            tracing::warn!("Failed to send completion reminder for project {}: {}", project.id, e);
+        }
+    }
+
+    Ok(())
+}
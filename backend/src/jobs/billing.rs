@@ -0,0 +1,50 @@
+//! Billing job: consolidated monthly invoices and credit limit enforcement
+
+use chrono::{Datelike, Utc};
+
+use crate::AppState;
+use crate::services::{BillingService, NotificationService};
+
+pub async fn run(state: AppState) -> anyhow::Result<()> {
+    let pool = state.db.pool();
+    let settings = &state.settings.billing;
+
+    // Bill the previous calendar month
+    let today = Utc::now().date_naive();
+    let period_end = today
+        .with_day(1)
+        .and_then(|first| first.pred_opt())
+        .ok_or_else(|| anyhow::anyhow!("Invalid billing period"))?;
+    let period_start = period_end.with_day(1).unwrap_or(period_end);
+
+    for (client_id, currency) in BillingService::get_uninvoiced_clients(pool, period_end).await? {
+        let Some(invoice) = BillingService::create_monthly_invoice(
+            pool, settings, client_id, &currency, period_start, period_end,
+        )
+        .await?
+        else {
+            continue;
+        };
+
+        NotificationService::create(
+            pool,
+            client_id,
+            "invoice_issued",
+            "Neue Sammelrechnung",
+            &format!(
+                "Ihre Sammelrechnung {} über {:.2} {} ist verfügbar.",
+                invoice.invoice_number,
+                invoice.total as f64 / 100.0,
+                invoice.currency
+            ),
+            Some(serde_json::json!({ "invoiceId": invoice.id })),
+        )
+        .await?;
+
+        tracing::info!("Consolidated invoice {} created for client {}", invoice.invoice_number, client_id);
+    }
+
+    BillingService::enforce_limits(pool, settings.overdue_block_days).await?;
+
+    Ok(())
+}
@@ -0,0 +1,14 @@
+//! Booking expiry job: expires booking requests the expert did not answer in time and frees their slots
+
+use crate::AppState;
+use crate::services::BookingService;
+
+pub async fn run(state: AppState) -> anyhow::Result<()> {
+    let expired = BookingService::expire_due(&state.db).await?;
+
+    if !expired.is_empty() {
+        tracing::info!("Expired {} unanswered booking requests", expired.len());
+    }
+
+    Ok(())
+}
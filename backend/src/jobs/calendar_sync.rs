@@ -0,0 +1,14 @@
+//! Calendar sync job: fetches subscribed expert calendars again and refreshes the times they block
+
+use crate::AppState;
+use crate::services::CalendarService;
+
+pub async fn run(state: AppState) -> anyhow::Result<()> {
+    let (synced, failed) = CalendarService::sync_due(&state.db, &state.settings.calendar).await?;
+
+    if synced + failed > 0 {
+        tracing::info!("Synced {} imported calendars ({} failed)", synced, failed);
+    }
+
+    Ok(())
+}
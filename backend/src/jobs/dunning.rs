@@ -0,0 +1,63 @@
+//! Dunning job: escalates overdue invoices and writes off the hopeless ones
+
+use crate::AppState;
+use crate::services::DunningService;
+#[cfg(feature = "email")]
+use crate::services::DunningNotice;
+
+pub async fn run(state: AppState) -> anyhow::Result<()> {
+    let pool = state.db.pool();
+    let settings = &state.settings.dunning;
+    let today = chrono::Utc::now().date_naive();
+
+    for invoice in DunningService::get_overdue_invoices(pool).await? {
+        let days_overdue = (today - invoice.due_date).num_days();
+
+        if days_overdue >= settings.uncollectible_after_days {
+            DunningService::mark_uncollectible(pool, invoice.id).await?;
+            if settings.pause_projects_at_level > 0 {
+                DunningService::pause_projects(pool, invoice.recipient_id).await?;
+            }
+            tracing::warn!("Invoice {} marked uncollectible", invoice.invoice_number);
+            continue;
+        }
+
+        let level = DunningService::level_for(days_overdue, settings);
+        if level <= invoice.dunning_level {
+            continue;
+        }
+
+        // Charge fees for every stage reached since the last run
+        let late_fee: i32 = (invoice.dunning_level + 1..=level)
+            .map(|l| DunningService::late_fee_for(l, settings))
+            .sum();
+        let Some(total) = DunningService::escalate(pool, invoice.id, level, late_fee).await? else {
+            // Escalated concurrently, or paid in the meantime
+            continue;
+        };
+
+        if settings.pause_projects_at_level > 0 && level >= settings.pause_projects_at_level {
+            DunningService::pause_projects(pool, invoice.recipient_id).await?;
+        }
+
+        #[cfg(feature = "email")]
+        if let Some(email_service) = &state.email {
+            let notice = DunningNotice { invoice: &invoice, level, total, late_fee };
+            match email_service.send_dunning_notice(&notice).await {
+                Ok(()) => DunningService::mark_email_sent(pool, invoice.id, level).await?,
+                Err(e) => tracing::warn!("Failed to send dunning notice for {}: {}", invoice.invoice_number, e),
+            }
+        }
+
+        tracing::info!(
+            "Invoice {} escalated to dunning level {} (total {})",
+            invoice.invoice_number, level, total
+        );
+    }
+
+    if settings.pause_projects_at_level > 0 {
+        DunningService::release_settled_clients(pool, settings.pause_projects_at_level).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,45 @@
+//! Job alert job: matches new postings to experts and emails instant alerts and daily digests
+
+use crate::AppState;
+use crate::services::JobAlertService;
+
+pub async fn run(state: AppState) -> anyhow::Result<()> {
+    let instant = JobAlertService::match_new_postings(&state.db).await?;
+    if !instant.is_empty() {
+        tracing::info!("Matched new postings, {} instant job alerts to send", instant.len());
+    }
+
+    #[cfg(feature = "email")]
+    if let Some(email_service) = &state.email {
+        use chrono::Timelike;
+
+        for alert in &instant {
+            match email_service
+                .send_job_alerts(&alert.email, &alert.first_name, &alert.language, std::slice::from_ref(alert))
+                .await
+            {
+                Ok(()) => JobAlertService::mark_emailed(&state.db, &[alert.alert_id]).await?,
+                Err(e) => tracing::warn!("Failed to send job alert {}: {}", alert.alert_id, e),
+            }
+        }
+
+        if chrono::Utc::now().hour() >= state.settings.job_alerts.digest_hour {
+            let due = JobAlertService::due_digests(&state.db).await?;
+            for alerts in due.chunk_by(|a, b| a.expert_id == b.expert_id) {
+                let expert = &alerts[0];
+                match email_service
+                    .send_job_alerts(&expert.email, &expert.first_name, &expert.language, alerts)
+                    .await
+                {
+                    Ok(()) => {
+                        let ids: Vec<_> = alerts.iter().map(|a| a.alert_id).collect();
+                        JobAlertService::mark_digest_sent(&state.db, expert.expert_id, &ids).await?;
+                    }
+                    Err(e) => tracing::warn!("Failed to send job alert digest to {}: {}", expert.expert_id, e),
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,94 @@
+//! Periodic background jobs
+//! Jobs run on tokio intervals inside the API process and share its `AppState`.
+
+pub mod auto_complete;
+pub mod billing;
+pub mod booking_expiry;
+pub mod calendar_sync;
+pub mod dunning;
+pub mod job_alerts;
+pub mod posting_expiry;
+
+use std::future::Future;
+use std::time::Duration;
+
+use crate::AppState;
+
+/// Spawn all enabled background jobs
+pub fn spawn_all(state: &AppState) {
+    let settings = &state.settings;
+
+    if settings.dunning.enabled {
+        spawn_periodic("dunning", settings.dunning.interval_minutes * 60, state.clone(), dunning::run);
+    }
+
+    if settings.billing.enabled {
+        spawn_periodic("billing", settings.billing.interval_minutes * 60, state.clone(), billing::run);
+    }
+
+    if settings.auto_complete.enabled {
+        spawn_periodic(
+            "auto_complete",
+            settings.auto_complete.interval_minutes * 60,
+            state.clone(),
+            auto_complete::run,
+        );
+    }
+
+    if settings.bookings.expiry_enabled {
+        spawn_periodic(
+            "booking_expiry",
+            settings.bookings.expiry_interval_minutes * 60,
+            state.clone(),
+            booking_expiry::run,
+        );
+    }
+
+    if settings.postings.expiry_enabled {
+        spawn_periodic(
+            "posting_expiry",
+            settings.postings.expiry_interval_minutes * 60,
+            state.clone(),
+            posting_expiry::run,
+        );
+    }
+
+    if settings.job_alerts.enabled {
+        spawn_periodic(
+            "job_alerts",
+            settings.job_alerts.interval_minutes * 60,
+            state.clone(),
+            job_alerts::run,
+        );
+    }
+
+    if settings.calendar.sync_enabled {
+        spawn_periodic(
+            "calendar_sync",
+            settings.calendar.sync_interval_minutes * 60,
+            state.clone(),
+            calendar_sync::run,
+        );
+    }
+}
+
+/// Run `job` every `interval_secs` seconds, logging failures without stopping the loop
+fn spawn_periodic<F, Fut>(name: &'static str, interval_secs: u64, state: AppState, job: F)
+where
+    F: Fn(AppState) -> Fut + Send + 'static,
+    Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs.max(60)));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = job(state.clone()).await {
+                tracing::error!("Background job '{}' failed: {:?}", name, e);
+            }
+        }
+    });
+
+    tracing::info!("⏱️ Background job '{}' scheduled every {}s", name, interval_secs.max(60));
+}
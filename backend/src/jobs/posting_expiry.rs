@@ -0,0 +1,20 @@
+//! Posting expiry job: expires postings past their lifetime or deadline and closes those left expired
+
+use crate::AppState;
+use crate::services::PostingService;
+
+pub async fn run(state: AppState) -> anyhow::Result<()> {
+    let settings = &state.settings.postings;
+
+    let expired = PostingService::expire_due(&state.db, settings).await?;
+    if !expired.is_empty() {
+        tracing::info!("Expired {} project postings", expired.len());
+    }
+
+    let closed = PostingService::close_expired(&state.db, settings).await?;
+    if !closed.is_empty() {
+        tracing::info!("Closed {} expired project postings", closed.len());
+    }
+
+    Ok(())
+}
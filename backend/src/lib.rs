@@ -14,6 +14,7 @@ use tracing::Level;
 pub mod config;
 pub mod db;
 pub mod handlers;
+pub mod jobs;
 pub mod middleware;
 pub mod models;
 pub mod routes;
@@ -85,7 +86,7 @@ pub fn create_app(state: AppState) -> Router {
     let x_request_id = axum::http::HeaderName::from_static("x-request-id");
 
     let app = Router::new()
-        .nest("/api/v1", routes::api_routes())
+        .nest("/api/v1", routes::api_routes(&state))
         // Rate limiting middleware (in production only)
         .layer(axum::middleware::from_fn(move |req, next| {
             let limiter = rate_limiter.clone();
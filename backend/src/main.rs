@@ -8,6 +8,7 @@ use dach_marketplace_api::{
     config::Settings,
     db::Database,
     create_app,
+    jobs,
     AppState,
 };
 #[cfg(feature = "email")]
@@ -191,6 +192,9 @@ async fn try_init_full_app(is_production: bool) -> anyhow::Result<Router> {
         }
     }
 
+    // Start background jobs (dunning, ...)
+    jobs::spawn_all(&state);
+
     // Build the application
     Ok(create_app(state))
 }
@@ -39,23 +39,17 @@ impl From<Claims> for AuthUser {
     }
 }
 
-/// Authentication middleware
-pub async fn auth_middleware(
-    State(state): State<AppState>,
-    mut request: Request,
-    next: Next,
-) -> Result<Response, StatusCode> {
+/// User of a valid bearer token; `None` without an `Authorization` header
+fn bearer_user(state: &AppState, request: &Request) -> Result<Option<AuthUser>, StatusCode> {
     // Extract token from Authorization header
-    let auth_header = request
-        .headers()
-        .get(header::AUTHORIZATION)
-        .and_then(|h| h.to_str().ok())
-        .and_then(|h| h.strip_prefix("Bearer "));
-
-    let token = match auth_header {
-        Some(t) => t,
-        None => return Err(StatusCode::UNAUTHORIZED),
+    let Some(header) = request.headers().get(header::AUTHORIZATION) else {
+        return Ok(None);
     };
+    let token = header
+        .to_str()
+        .ok()
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .ok_or(StatusCode::UNAUTHORIZED)?;
 
     // Decode and validate JWT
     let claims = decode::<Claims>(
@@ -72,8 +66,32 @@ pub async fn auth_middleware(
         return Err(StatusCode::UNAUTHORIZED);
     }
 
+    Ok(Some(AuthUser::from(claims)))
+}
+
+/// Authentication middleware
+pub async fn auth_middleware(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let user = bearer_user(&state, &request)?.ok_or(StatusCode::UNAUTHORIZED)?;
+
     // Insert authenticated user into request extensions
-    request.extensions_mut().insert(AuthUser::from(claims));
+    request.extensions_mut().insert(user);
+
+    Ok(next.run(request).await)
+}
+
+/// Optional authentication: anonymous requests pass, a given token must be valid
+pub async fn optional_auth_middleware(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    if let Some(user) = bearer_user(&state, &request)? {
+        request.extensions_mut().insert(user);
+    }
 
     Ok(next.run(request).await)
 }
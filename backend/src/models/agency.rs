@@ -0,0 +1,186 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Role of an expert within an agency
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "agency_role", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum AgencyRole {
+    /// Manages members, revenue splits and the agency profile
+    Owner,
+    /// Manages agency services, assigns projects and adds members
+    Manager,
+    Member,
+}
+
+impl AgencyRole {
+    /// Owners and managers run the agency's services, projects and team
+    pub fn can_manage(self) -> bool {
+        matches!(self, AgencyRole::Owner | AgencyRole::Manager)
+    }
+}
+
+/// Agency selling services under one brand with several experts
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Agency {
+    pub id: Uuid,
+    pub name: String,
+    pub slug: String,
+    pub description: Option<String>,
+    pub logo_url: Option<String>,
+    pub website: Option<String>,
+    /// Share of a payout for the member who did the work, unless set per member
+    pub default_member_share_percent: i16,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Member of an agency with public user details
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct AgencyMember {
+    pub id: Uuid,
+    pub agency_id: Uuid,
+    pub user_id: Uuid,
+    pub role: AgencyRole,
+    /// Overrides the agency's default share
+    pub revenue_share_percent: Option<i16>,
+    pub first_name: String,
+    pub last_name: String,
+    pub avatar_url: Option<String>,
+    pub joined_at: DateTime<Utc>,
+}
+
+/// Agency of the current user with their role in it
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgencyMembership {
+    pub agency: Agency,
+    pub role: AgencyRole,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateAgencyRequest {
+    #[validate(length(min = 2, max = 200))]
+    pub name: String,
+    #[validate(length(max = 5000))]
+    pub description: Option<String>,
+    #[validate(url)]
+    pub logo_url: Option<String>,
+    #[validate(url, length(max = 500))]
+    pub website: Option<String>,
+    #[validate(range(min = 0, max = 100))]
+    pub default_member_share_percent: Option<i16>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateAgencyRequest {
+    #[validate(length(min = 2, max = 200))]
+    pub name: Option<String>,
+    #[validate(length(max = 5000))]
+    pub description: Option<String>,
+    #[validate(url)]
+    pub logo_url: Option<String>,
+    #[validate(url, length(max = 500))]
+    pub website: Option<String>,
+    #[validate(range(min = 0, max = 100))]
+    pub default_member_share_percent: Option<i16>,
+}
+
+/// Add an expert to the agency
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct AddAgencyMemberRequest {
+    pub user_id: Uuid,
+    pub role: Option<AgencyRole>,
+    #[validate(range(min = 0, max = 100))]
+    pub revenue_share_percent: Option<i16>,
+}
+
+/// Change a member's role or revenue share (owner only)
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateAgencyMemberRequest {
+    pub role: Option<AgencyRole>,
+    #[validate(range(min = 0, max = 100))]
+    pub revenue_share_percent: Option<i16>,
+    /// Fall back to the agency's default share
+    #[serde(default)]
+    pub use_default_share: bool,
+}
+
+/// Assign an agency project to a member
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssignProjectRequest {
+    pub member_id: Uuid,
+}
+
+/// Reply from the shared inbox
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct AgencyReplyRequest {
+    #[validate(length(min = 1, max = 5000))]
+    pub content: String,
+}
+
+/// Split of a released payment between agency and member
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct AgencyRevenueSplit {
+    pub id: Uuid,
+    pub agency_id: Uuid,
+    pub project_id: Uuid,
+    pub payment_id: Uuid,
+    pub member_id: Uuid,
+    pub net_amount: i32,
+    pub member_share_percent: i16,
+    pub member_amount: i32,
+    pub agency_amount: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Earnings of an agency from its revenue splits
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgencyEarnings {
+    pub net_amount: i64,
+    pub member_amount: i64,
+    pub agency_amount: i64,
+    pub splits: Vec<AgencyRevenueSplit>,
+}
+
+/// Split a net payout (in cents): the member gets their share rounded down, the agency the rest
+pub fn revenue_split(net_amount: i32, member_share_percent: i16) -> (i32, i32) {
+    let percent = member_share_percent.clamp(0, 100) as i64;
+    let member_amount = (net_amount as i64 * percent / 100) as i32;
+
+    (member_amount, net_amount - member_amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_revenue_split() {
+        assert_eq!(revenue_split(85_000, 70), (59_500, 25_500));
+        assert_eq!(revenue_split(999, 33), (329, 670));
+        assert_eq!(revenue_split(1_000, 100), (1_000, 0));
+        assert_eq!(revenue_split(1_000, 0), (0, 1_000));
+    }
+
+    #[test]
+    fn test_agency_roles() {
+        assert!(AgencyRole::Owner.can_manage());
+        assert!(AgencyRole::Manager.can_manage());
+        assert!(!AgencyRole::Member.can_manage());
+    }
+}
@@ -1,4 +1,4 @@
-use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
@@ -27,12 +27,27 @@ pub struct BlockedDate {
     pub start_date: NaiveDate,
     pub end_date: NaiveDate,
     pub reason: Option<String>,
+    /// Calendar import the range was synced from (None if entered manually)
+    pub import_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Busy time range synced from an imported calendar
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockedTime {
+    pub id: Uuid,
+    pub expert_id: Uuid,
+    pub import_id: Option<Uuid>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
     pub created_at: DateTime<Utc>,
 }
 
 /// Request to set availability slots
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct SetAvailabilityRequest {
+    #[validate(length(max = 100))]
     pub slots: Vec<AvailabilitySlotInput>,
 }
 
@@ -45,6 +60,59 @@ pub struct AvailabilitySlotInput {
     pub is_available: bool,
 }
 
+/// Validated weekly slot
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeeklySlot {
+    pub day_of_week: i16,
+    pub start_time: NaiveTime,
+    pub end_time: NaiveTime,
+    pub is_available: bool,
+}
+
+impl SetAvailabilityRequest {
+    /// Parse and check the slots: valid day and "HH:MM" times, start before end,
+    /// no overlapping slots on the same day
+    pub fn weekly_slots(&self) -> Result<Vec<WeeklySlot>, String> {
+        let mut slots = Vec::with_capacity(self.slots.len());
+
+        for input in &self.slots {
+            if !(0..=6).contains(&input.day_of_week) {
+                return Err(format!("Invalid day of week: {} (0 = Sunday, 6 = Saturday)", input.day_of_week));
+            }
+            let parse = |value: &str| {
+                NaiveTime::parse_from_str(value, "%H:%M").map_err(|_| format!("Invalid time '{}', expected HH:MM", value))
+            };
+            let (start_time, end_time) = (parse(&input.start_time)?, parse(&input.end_time)?);
+            if start_time >= end_time {
+                return Err(format!("Slot {}-{} must end after it starts", input.start_time, input.end_time));
+            }
+
+            slots.push(WeeklySlot {
+                day_of_week: input.day_of_week,
+                start_time,
+                end_time,
+                is_available: input.is_available,
+            });
+        }
+
+        slots.sort_by_key(|s| (s.day_of_week, s.start_time));
+        if let Some(pair) = slots
+            .windows(2)
+            .find(|pair| pair[0].day_of_week == pair[1].day_of_week && pair[1].start_time < pair[0].end_time)
+        {
+            return Err(format!(
+                "Slots {}-{} and {}-{} overlap",
+                pair[0].start_time.format("%H:%M"),
+                pair[0].end_time.format("%H:%M"),
+                pair[1].start_time.format("%H:%M"),
+                pair[1].end_time.format("%H:%M"),
+            ));
+        }
+
+        Ok(slots)
+    }
+}
+
 /// Request to block dates
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 #[serde(rename_all = "camelCase")]
@@ -61,7 +129,117 @@ pub struct BlockDatesRequest {
 pub struct ExpertAvailability {
     pub weekly_slots: Vec<AvailabilitySlot>,
     pub blocked_dates: Vec<BlockedDate>,
+    pub blocked_times: Vec<BlockedTime>,
     pub timezone: String,
     pub available_hours_per_week: i16,
 }
 
+/// Imported calendar (ICS subscription or uploaded file)
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarImport {
+    pub id: Uuid,
+    pub expert_id: Uuid,
+    pub name: String,
+    /// Subscribed URL; None for uploaded files, which are not re-synced
+    pub source_url: Option<String>,
+    /// Busy occurrences found in the last sync
+    pub event_count: i32,
+    pub last_synced_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Request to subscribe to an ICS calendar URL
+#[derive(Debug, Clone, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateCalendarImportRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    #[validate(length(max = 2000))]
+    pub url: String,
+}
+
+/// ICS feed of an expert's bookings and project deadlines
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarFeed {
+    pub token: String,
+    pub url: String,
+}
+
+/// Bookable slots query
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookingSlotQuery {
+    /// First and last day (inclusive) in the display timezone
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// IANA timezone to show the slots in (defaults to the expert's)
+    pub timezone: Option<String>,
+}
+
+/// A time slot that can be booked
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BookableSlot {
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    /// Start and end as wall-clock time in the requested timezone
+    pub local_start: NaiveDateTime,
+    pub local_end: NaiveDateTime,
+}
+
+/// Bookable slots of an expert
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookingSlots {
+    pub timezone: String,
+    pub expert_timezone: String,
+    pub slot_minutes: i16,
+    pub slots: Vec<BookableSlot>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(day_of_week: i16, start: &str, end: &str) -> AvailabilitySlotInput {
+        AvailabilitySlotInput {
+            day_of_week,
+            start_time: start.to_string(),
+            end_time: end.to_string(),
+            is_available: true,
+        }
+    }
+
+    #[test]
+    fn test_weekly_slots() {
+        let req = SetAvailabilityRequest {
+            slots: vec![input(2, "13:00", "17:00"), input(1, "09:00", "12:00"), input(2, "08:00", "12:00")],
+        };
+        let slots = req.weekly_slots().unwrap();
+        assert_eq!(slots.len(), 3);
+        assert_eq!((slots[0].day_of_week, slots[1].start_time), (1, NaiveTime::from_hms_opt(8, 0, 0).unwrap()));
+    }
+
+    #[test]
+    fn test_weekly_slots_rejects_invalid_input() {
+        let invalid = [
+            vec![input(7, "09:00", "12:00")],
+            vec![input(1, "9am", "12:00")],
+            vec![input(1, "12:00", "09:00")],
+            vec![input(1, "09:00", "12:00"), input(1, "11:30", "14:00")],
+        ];
+        for slots in invalid {
+            assert!(SetAvailabilityRequest { slots }.weekly_slots().is_err());
+        }
+
+        // Adjacent slots do not overlap
+        let req = SetAvailabilityRequest {
+            slots: vec![input(1, "09:00", "12:00"), input(1, "12:00", "14:00")],
+        };
+        assert!(req.weekly_slots().is_ok());
+    }
+}
@@ -0,0 +1,108 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Cancellation policy (platform default or defined by an expert for their services)
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct CancellationPolicy {
+    pub id: Uuid,
+    pub owner_id: Option<Uuid>,
+    pub name: String,
+    pub description: Option<String>,
+    pub is_platform_default: bool,
+    pub free_cancellation_hours: i32,
+    pub refund_before_start_percent: i16,
+    pub refund_in_progress_percent: i16,
+    pub refund_after_delivery_percent: i16,
+    pub expert_cancel_refund_percent: i16,
+    pub refund_platform_fee: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Create cancellation policy request
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateCancellationPolicyRequest {
+    #[validate(length(min = 3, max = 100))]
+    pub name: String,
+    #[validate(length(max = 1000))]
+    pub description: Option<String>,
+    #[validate(range(min = 0, max = 720))]
+    pub free_cancellation_hours: i32,
+    #[validate(range(min = 0, max = 100))]
+    pub refund_before_start_percent: i16,
+    #[validate(range(min = 0, max = 100))]
+    pub refund_in_progress_percent: i16,
+    #[validate(range(min = 0, max = 100))]
+    pub refund_after_delivery_percent: i16,
+    #[validate(range(min = 0, max = 100))]
+    pub expert_cancel_refund_percent: i16,
+    pub refund_platform_fee: bool,
+}
+
+/// Who cancels a project
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CancellationParty {
+    Client,
+    Expert,
+    Admin,
+}
+
+impl CancellationParty {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CancellationParty::Client => "client",
+            CancellationParty::Expert => "expert",
+            CancellationParty::Admin => "admin",
+        }
+    }
+}
+
+/// Single step of a cancellation calculation, shown to both parties
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancellationStep {
+    pub code: String,
+    pub description: String,
+    pub amount: Option<i32>,
+}
+
+/// Result of applying a cancellation policy
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancellationBreakdown {
+    pub policy_id: Option<Uuid>,
+    pub paid_amount: i32,
+    pub milestones_completed_amount: i32,
+    pub refund_percent: i16,
+    pub refund_amount: i32,
+    pub expert_compensation: i32,
+    pub platform_fee_retained: i32,
+    pub steps: Vec<CancellationStep>,
+}
+
+/// Cancellation applied to a project
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectCancellation {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub cancelled_by: Uuid,
+    pub cancelled_by_role: String,
+    pub policy_id: Option<Uuid>,
+    pub payment_id: Option<Uuid>,
+    pub paid_amount: i32,
+    pub milestones_completed_amount: i32,
+    pub refund_percent: i16,
+    pub refund_amount: i32,
+    pub expert_compensation: i32,
+    pub platform_fee_retained: i32,
+    pub explanation: sqlx::types::Json<Vec<CancellationStep>>,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,196 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+use super::{Project, ProjectActor};
+
+/// Change request status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "change_request_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeRequestStatus {
+    Pending,
+    Accepted,
+    Rejected,
+    Withdrawn,
+}
+
+/// Proposed change of scope, price, delivery date or revisions of a running project
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectChangeRequest {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub requested_by: Option<Uuid>,
+    pub requested_by_role: ProjectActor,
+    pub description: String,
+    pub previous_price: i32,        // in cents
+    pub new_price: i32,             // in cents
+    pub previous_delivery_date: Option<DateTime<Utc>>,
+    pub new_delivery_date: Option<DateTime<Utc>>,
+    pub previous_revisions_allowed: i16,
+    pub new_revisions_allowed: i16,
+    pub status: ChangeRequestStatus,
+    pub responded_by: Option<Uuid>,
+    pub response_message: Option<String>,
+    pub responded_at: Option<DateTime<Utc>>,
+    pub payment_id: Option<Uuid>,   // Additional charge or refunded project payment
+    pub refund_amount: Option<i32>, // in cents
+    pub stripe_checkout_session_id: Option<String>,
+    pub paid_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ProjectChangeRequest {
+    /// Positive for an additional charge, negative for a partial refund
+    pub fn price_difference(&self) -> i32 {
+        self.new_price - self.previous_price
+    }
+
+    pub fn previous_terms(&self) -> ProjectTerms {
+        ProjectTerms {
+            price: self.previous_price,
+            delivery_date: self.previous_delivery_date,
+            revisions_allowed: self.previous_revisions_allowed,
+        }
+    }
+
+    pub fn new_terms(&self) -> ProjectTerms {
+        ProjectTerms {
+            price: self.new_price,
+            delivery_date: self.new_delivery_date,
+            revisions_allowed: self.new_revisions_allowed,
+        }
+    }
+}
+
+/// Commercial terms of a project that a change request can modify
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectTerms {
+    pub price: i32,
+    pub delivery_date: Option<DateTime<Utc>>,
+    pub revisions_allowed: i16,
+}
+
+impl ProjectTerms {
+    pub fn of(project: &Project) -> Self {
+        Self {
+            price: project.price,
+            delivery_date: project.delivery_date,
+            revisions_allowed: project.revisions_allowed,
+        }
+    }
+}
+
+/// Propose a change; omitted terms stay as they are
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateChangeRequestRequest {
+    /// Changed scope and reasoning
+    #[validate(length(min = 10, max = 5000))]
+    pub description: String,
+
+    /// New total price in cents
+    #[validate(range(min = 1))]
+    pub new_price: Option<i32>,
+
+    pub new_delivery_date: Option<DateTime<Utc>>,
+
+    #[validate(range(min = 0, max = 50))]
+    pub new_revisions_allowed: Option<i16>,
+}
+
+impl CreateChangeRequestRequest {
+    /// Terms after the change, checked against the current terms and the revisions already used
+    pub fn proposed_terms(
+        &self,
+        current: &ProjectTerms,
+        revisions_used: i16,
+        now: DateTime<Utc>,
+    ) -> Result<ProjectTerms, String> {
+        if let Some(date) = self.new_delivery_date
+            && date <= now
+        {
+            return Err("New delivery date must be in the future".to_string());
+        }
+        if let Some(revisions) = self.new_revisions_allowed
+            && revisions < revisions_used
+        {
+            return Err(format!("{} revisions have already been used", revisions_used));
+        }
+
+        Ok(ProjectTerms {
+            price: self.new_price.unwrap_or(current.price),
+            delivery_date: self.new_delivery_date.or(current.delivery_date),
+            revisions_allowed: self.new_revisions_allowed.unwrap_or(current.revisions_allowed),
+        })
+    }
+}
+
+/// Accept, reject or withdraw a change request
+#[derive(Debug, Default, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct RespondChangeRequestRequest {
+    #[validate(length(max = 2000))]
+    pub message: Option<String>,
+}
+
+/// Checkout of the additional charge of an accepted change request
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeRequestCheckoutResponse {
+    pub change_request: ProjectChangeRequest,
+    pub session_id: String,
+    pub checkout_url: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn request() -> CreateChangeRequestRequest {
+        CreateChangeRequestRequest {
+            description: "Add a second workflow for invoices".to_string(),
+            new_price: None,
+            new_delivery_date: None,
+            new_revisions_allowed: None,
+        }
+    }
+
+    #[test]
+    fn test_omitted_terms_are_kept() {
+        let now = Utc::now();
+        let current = ProjectTerms {
+            price: 100_000,
+            delivery_date: Some(now + Duration::days(7)),
+            revisions_allowed: 2,
+        };
+
+        let terms = CreateChangeRequestRequest { new_price: Some(150_000), ..request() }
+            .proposed_terms(&current, 0, now)
+            .unwrap();
+        assert_eq!(terms.price, 150_000);
+        assert_eq!(terms.delivery_date, current.delivery_date);
+        assert_eq!(terms.revisions_allowed, 2);
+
+        assert_eq!(request().proposed_terms(&current, 0, now).unwrap(), current);
+    }
+
+    #[test]
+    fn test_proposed_terms_are_checked() {
+        let now = Utc::now();
+        let current = ProjectTerms { price: 100_000, delivery_date: None, revisions_allowed: 3 };
+
+        let past = CreateChangeRequestRequest { new_delivery_date: Some(now - Duration::hours(1)), ..request() };
+        assert!(past.proposed_terms(&current, 0, now).is_err());
+
+        let fewer = CreateChangeRequestRequest { new_revisions_allowed: Some(1), ..request() };
+        assert!(fewer.proposed_terms(&current, 2, now).is_err());
+        assert_eq!(fewer.proposed_terms(&current, 1, now).unwrap().revisions_allowed, 1);
+    }
+}
@@ -25,10 +25,51 @@ pub struct ClientProfile {
     pub total_spent: i32,
     pub is_verified: bool,
     pub verified_at: Option<DateTime<Utc>>,
+    pub billing_mode: BillingMode,
+    pub credit_limit: Option<i32>,      // in cents
+    pub payment_terms_days: i16,
+    pub credit_approved_at: Option<DateTime<Utc>>,
+    pub credit_approved_by: Option<Uuid>,
+    pub billing_blocked_at: Option<DateTime<Utc>>,
+    pub billing_blocked_reason: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// How a client pays for projects
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, Eq, Default)]
+#[sqlx(type_name = "billing_mode", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum BillingMode {
+    #[default]
+    Card,       // Upfront card payment via checkout
+    Invoice,    // Approved credit, billed monthly with payment terms
+}
+
+/// Set billing terms request (admin)
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateBillingTermsRequest {
+    pub billing_mode: BillingMode,
+    #[validate(range(min = 0))]
+    pub credit_limit: Option<i32>,
+    #[validate(range(min = 0, max = 120))]
+    pub payment_terms_days: Option<i16>,
+}
+
+/// Credit status of an invoice-billed client
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreditStatus {
+    pub billing_mode: BillingMode,
+    pub credit_limit: Option<i32>,
+    pub credit_used: i64,
+    pub credit_available: Option<i64>,
+    pub payment_terms_days: i16,
+    pub is_blocked: bool,
+    pub blocked_reason: Option<String>,
+}
+
 /// Create client profile request
 #[derive(Debug, Deserialize, Validate)]
 #[serde(rename_all = "camelCase")]
@@ -97,6 +138,8 @@ pub enum ProjectPostingStatus {
     Assigned,
     Completed,
     Cancelled,
+    /// Ran past its lifetime or deadline; can be extended or reposted
+    Expired,
 }
 
 /// Project posting budget type
@@ -108,6 +151,17 @@ pub enum ProjectPostingBudgetType {
     Range,
 }
 
+/// Who can see and bid on a posting
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, Eq, Default)]
+#[sqlx(type_name = "posting_visibility", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum PostingVisibility {
+    #[default]
+    Public,
+    /// Only invited experts see the posting and can submit proposals
+    InviteOnly,
+}
+
 /// Project posting - clients post projects for experts to bid on
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 #[serde(rename_all = "camelCase")]
@@ -139,6 +193,12 @@ pub struct ProjectPosting {
     pub cancellation_reason: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub visibility: PostingVisibility,
+    /// When the posting stops accepting proposals
+    pub expires_at: Option<DateTime<Utc>>,
+    pub expired_at: Option<DateTime<Utc>>,
+    /// Expired or cancelled posting this one was reposted from
+    pub reposted_from_id: Option<Uuid>,
 }
 
 /// Create project posting request
@@ -160,6 +220,7 @@ pub struct CreateProjectPostingRequest {
     pub deadline: Option<DateTime<Utc>>,
     pub estimated_duration: Option<String>,
     pub is_urgent: Option<bool>,
+    pub visibility: Option<PostingVisibility>,
 }
 
 /// Update project posting request
@@ -181,6 +242,32 @@ pub struct UpdateProjectPostingRequest {
     pub estimated_duration: Option<String>,
     pub is_urgent: Option<bool>,
     pub status: Option<ProjectPostingStatus>,
+    pub visibility: Option<PostingVisibility>,
+}
+
+/// Keep an open or expired posting running for more days
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtendPostingRequest {
+    #[validate(range(min = 1, max = 90))]
+    pub days: i64,
+    /// New deadline; required once the current one has passed
+    pub deadline: Option<DateTime<Utc>>,
+}
+
+/// Publish an expired or cancelled posting again as a new posting
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct RepostPostingRequest {
+    pub deadline: Option<DateTime<Utc>>,
+}
+
+/// Cancel a posting; the reason is sent to experts with pending proposals
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelPostingRequest {
+    #[validate(length(max = 1000))]
+    pub reason: Option<String>,
 }
 
 /// Project posting filters
@@ -247,6 +334,148 @@ pub struct CreateProposalRequest {
     pub proposed_milestones: Option<serde_json::Value>,
 }
 
+/// Milestone as proposed by the expert (`proposedMilestones` entries)
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct ProposedMilestone {
+    #[validate(length(min = 3, max = 200))]
+    pub title: String,
+    #[validate(length(max = 2000))]
+    pub description: Option<String>,
+    #[validate(range(min = 0))]
+    pub amount: i32,
+    pub due_date: Option<DateTime<Utc>>,
+}
+
+impl Proposal {
+    /// Parsed milestones; `None` if the JSON does not match the expected shape
+    pub fn milestones(&self) -> Option<Vec<ProposedMilestone>> {
+        match &self.proposed_milestones {
+            None | Some(serde_json::Value::Null) => Some(Vec::new()),
+            Some(value) => serde_json::from_value(value.clone()).ok(),
+        }
+    }
+}
+
+/// Result of accepting a proposal
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProposalAcceptance {
+    pub proposal: Proposal,
+    pub project: super::Project,
+    pub conversation_id: Uuid,
+    /// Competing proposals that were rejected
+    pub rejected_proposals: i64,
+}
+
+/// Update proposal request (expert, until the client has looked at it)
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateProposalRequest {
+    #[validate(length(min = 100, max = 5000))]
+    pub cover_letter: Option<String>,
+    #[validate(range(min = 0))]
+    pub proposed_price: Option<i32>,
+    pub currency: Option<Currency>,
+    pub proposed_duration: Option<String>,
+    pub proposed_milestones: Option<serde_json::Value>,
+}
+
+/// Reject proposal request
+#[derive(Debug, Deserialize, Validate)]
+pub struct RejectProposalRequest {
+    #[validate(length(max = 1000))]
+    pub reason: Option<String>,
+}
+
+/// Proposal comparison sort order
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProposalSortBy {
+    /// Lowest price first
+    Price,
+    /// Best rated expert first
+    Rating,
+    /// Best skill match first
+    #[default]
+    Fit,
+}
+
+/// Proposal comparison query
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProposalComparisonQuery {
+    pub sort_by: Option<ProposalSortBy>,
+    /// Also list rejected and withdrawn proposals
+    pub include_closed: Option<bool>,
+}
+
+/// Proposal with the expert figures used to compare it
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProposalComparison {
+    #[serde(flatten)]
+    pub proposal: Proposal,
+    pub expert_name: String,
+    pub expert_avatar: Option<String>,
+    pub expert_headline: Option<String>,
+    pub rating_average: f32,
+    pub rating_count: i32,
+    pub completion_rate: Option<f32>,
+    pub response_time_hours: Option<i16>,
+    /// Required skills and tools of the posting the expert offers
+    pub matched_skills: Vec<String>,
+    /// Share of required skills and tools matched in percent (`None` if the posting lists none)
+    pub fit_score: Option<f32>,
+}
+
+/// Status of an invitation to bid on a posting
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, Eq)]
+#[sqlx(type_name = "posting_invitation_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum PostingInvitationStatus {
+    Pending,
+    Accepted,
+    Declined,
+}
+
+/// Invitation of an expert to submit a proposal for a posting
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct PostingInvitation {
+    pub id: Uuid,
+    pub project_posting_id: Uuid,
+    pub posting_title: String,
+    /// User ID of the invited expert
+    pub expert_id: Uuid,
+    pub expert_first_name: String,
+    pub expert_last_name: String,
+    pub invited_by: Option<Uuid>,
+    pub message: Option<String>,
+    pub status: PostingInvitationStatus,
+    pub responded_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Invitation received by an expert with the posting, which may be invite-only
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceivedPostingInvitation {
+    #[serde(flatten)]
+    pub invitation: PostingInvitation,
+    pub posting: ProjectPosting,
+}
+
+/// Invite experts (by user ID) to bid on a posting
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct InviteExpertsRequest {
+    #[validate(length(min = 1, max = 20))]
+    pub expert_ids: Vec<Uuid>,
+    #[validate(length(max = 2000))]
+    pub message: Option<String>,
+}
+
 /// Booking status
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq)]
 #[sqlx(type_name = "booking_status", rename_all = "snake_case")]
@@ -272,6 +501,9 @@ pub struct BookingRequest {
     pub currency: Currency,
     pub proposed_start_date: Option<DateTime<Utc>>,
     pub proposed_deadline: Option<DateTime<Utc>>,
+    /// Reserved time slot (calendar bookings)
+    pub slot_start: Option<DateTime<Utc>>,
+    pub slot_end: Option<DateTime<Utc>>,
     pub status: BookingStatus,
     pub expert_response: Option<String>,
     pub responded_at: Option<DateTime<Utc>>,
@@ -293,6 +525,8 @@ pub struct CreateBookingRequest {
     pub currency: Currency,
     pub proposed_start_date: Option<DateTime<Utc>>,
     pub proposed_deadline: Option<DateTime<Utc>>,
+    /// Start of a bookable slot to reserve (see `GET /experts/{id}/slots`)
+    pub slot_start: Option<DateTime<Utc>>,
 }
 
 /// Respond to booking request
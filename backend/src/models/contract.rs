@@ -0,0 +1,204 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::collections::HashMap;
+use uuid::Uuid;
+use validator::Validate;
+
+use super::{Language, ProjectActor};
+
+/// Kind of contract
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "contract_kind", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ContractKind {
+    Nda,
+    /// Auftragsverarbeitungsvertrag (data processing agreement, Art. 28 GDPR)
+    Dpa,
+    ServiceAgreement,
+}
+
+/// Placeholders available in contract templates, written as `{{name}}`
+pub const CONTRACT_PLACEHOLDERS: &[&str] = &[
+    "date",
+    "platform.name",
+    "project.title",
+    "project.description",
+    "project.price",
+    "project.delivery_date",
+    "client.name",
+    "client.address",
+    "client.email",
+    "client.vat_id",
+    "expert.name",
+    "expert.address",
+    "expert.email",
+    "expert.vat_id",
+];
+
+/// Admin-managed contract template
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractTemplate {
+    pub id: Uuid,
+    pub kind: ContractKind,
+    pub language: Language,
+    pub title: String,
+    pub body: String,
+    pub version: i32,
+    pub is_active: bool,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Create a template; it replaces the active template of the same kind and language
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateContractTemplateRequest {
+    pub kind: ContractKind,
+    pub language: Language,
+    #[validate(length(min = 3, max = 200))]
+    pub title: String,
+    #[validate(length(min = 50, max = 100000))]
+    pub body: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateContractTemplateRequest {
+    #[validate(length(min = 3, max = 200))]
+    pub title: Option<String>,
+    #[validate(length(min = 50, max = 100000))]
+    pub body: Option<String>,
+    pub is_active: Option<bool>,
+}
+
+/// Names of the `{{placeholders}}` in a template body
+fn placeholders(body: &str) -> Result<Vec<&str>, String> {
+    let mut names = Vec::new();
+    let mut rest = body;
+
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let end = after.find("}}").ok_or_else(|| "Unclosed placeholder in template".to_string())?;
+        names.push(after[..end].trim());
+        rest = &after[end + 2..];
+    }
+
+    Ok(names)
+}
+
+/// Check that a template only uses known placeholders
+pub fn check_contract_template(body: &str) -> Result<(), String> {
+    for name in placeholders(body)? {
+        if !CONTRACT_PLACEHOLDERS.contains(&name) {
+            return Err(format!("Unknown placeholder '{{{{{}}}}}'", name));
+        }
+    }
+
+    Ok(())
+}
+
+/// Fill the placeholders of a template; missing values are left blank
+pub fn render_contract(body: &str, values: &HashMap<&str, String>) -> String {
+    let mut output = String::with_capacity(body.len());
+    let mut rest = body;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        output.push_str(&rest[..start]);
+        let name = rest[start + 2..start + 2 + end].trim();
+        output.push_str(values.get(name).map(String::as_str).unwrap_or(""));
+        rest = &rest[start + 2 + end + 2..];
+    }
+    output.push_str(rest);
+
+    output
+}
+
+/// Contract generated for a project (the PDF itself is only served as download)
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectContract {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub template_id: Option<Uuid>,
+    pub template_version: i32,
+    pub kind: ContractKind,
+    pub language: Language,
+    pub title: String,
+    pub content: String,
+    /// SHA-256 of the PDF (hex)
+    pub document_hash: String,
+    pub created_by: Option<Uuid>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Click-to-sign record of one party
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractSignature {
+    pub id: Uuid,
+    pub contract_id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub party: ProjectActor,
+    pub signer_name: String,
+    pub document_hash: String,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub signed_at: DateTime<Utc>,
+}
+
+/// Contract with the signatures collected so far
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectContractWithSignatures {
+    #[serde(flatten)]
+    pub contract: ProjectContract,
+    pub signatures: Vec<ContractSignature>,
+}
+
+/// Generate a contract for a project from the active template
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateProjectContractRequest {
+    pub kind: ContractKind,
+    /// Defaults to the client's language
+    pub language: Option<Language>,
+}
+
+/// Sign a contract; the hash confirms which document the signer has seen
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct SignContractRequest {
+    #[validate(length(equal = 64))]
+    pub document_hash: String,
+    #[validate(length(min = 2, max = 200))]
+    pub signer_name: String,
+    pub accept: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template_placeholders_are_checked() {
+        assert!(check_contract_template("Zwischen {{client.name}} und {{ expert.name }}").is_ok());
+        assert!(check_contract_template("Zwischen {{client.iban}}").is_err());
+        assert!(check_contract_template("Zwischen {{client.name").is_err());
+    }
+
+    #[test]
+    fn test_render_contract() {
+        let values = HashMap::from([("client.name", "Muster AG".to_string()), ("date", "01.12.2024".to_string())]);
+        assert_eq!(
+            render_contract("{{client.name}}, {{ date }}: {{expert.name}}.", &values),
+            "Muster AG, 01.12.2024: ."
+        );
+    }
+}
@@ -28,6 +28,11 @@ pub struct ExpertProfile {
     pub availability_status: AvailabilityStatus,
     pub available_hours_per_week: i16,
     pub timezone: String,
+    /// Length of a bookable slot in minutes
+    pub booking_slot_minutes: i16,
+    /// Minimum gap to other bookings in minutes
+    pub booking_buffer_minutes: i16,
+    pub max_bookings_per_day: Option<i16>,
     pub is_verified: bool,
     pub verification_date: Option<DateTime<Utc>>,
     pub rating_average: f32,
@@ -36,6 +41,9 @@ pub struct ExpertProfile {
     pub total_earnings: i64,        // in cents
     pub response_time_hours: Option<i16>,
     pub completion_rate: Option<f32>,
+    pub on_time_delivery_rate: Option<f32>,
+    pub revision_rate: Option<f32>,
+    pub metrics_updated_at: Option<DateTime<Utc>>,
     pub stripe_connect_id: Option<String>,
     pub stripe_onboarding_complete: bool,
     pub featured: bool,
@@ -117,6 +125,13 @@ pub struct UpdateExpertProfileRequest {
     pub availability_status: Option<AvailabilityStatus>,
     pub available_hours_per_week: Option<i16>,
     pub timezone: Option<String>,
+    #[validate(range(min = 15, max = 480))]
+    pub booking_slot_minutes: Option<i16>,
+    #[validate(range(min = 0, max = 240))]
+    pub booking_buffer_minutes: Option<i16>,
+    /// 0 removes the limit
+    #[validate(range(min = 0, max = 50))]
+    pub max_bookings_per_day: Option<i16>,
 }
 
 /// Expert search filters
@@ -145,6 +160,8 @@ pub enum ExpertSortBy {
     Experience,
     TotalProjects,
     ResponseTime,
+    /// Completion rate, then on-time delivery
+    Reliability,
     Newest,
 }
 
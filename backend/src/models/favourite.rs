@@ -0,0 +1,140 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+use super::{AvailabilityStatus, Currency};
+
+/// Named list of saved experts and services
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedList {
+    pub id: Uuid,
+    pub owner_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    /// Organisation the list is shared with
+    pub organisation_id: Option<Uuid>,
+    pub expert_count: i64,
+    pub service_count: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Saved expert with profile summary
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedExpert {
+    pub id: Uuid,
+    /// User ID of the expert
+    pub expert_id: Uuid,
+    pub list_id: Option<Uuid>,
+    pub notes: Option<String>,
+    pub first_name: String,
+    pub last_name: String,
+    pub avatar_url: Option<String>,
+    pub headline: Option<String>,
+    pub availability_status: Option<AvailabilityStatus>,
+    pub rating_average: Option<f32>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Saved service with listing summary
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedService {
+    pub id: Uuid,
+    pub service_id: Uuid,
+    pub list_id: Option<Uuid>,
+    pub notes: Option<String>,
+    pub title: String,
+    pub slug: String,
+    pub price: i32,
+    pub currency: Currency,
+    pub rating_average: f32,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// List with its saved experts and services
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedListDetails {
+    pub list: SavedList,
+    pub experts: Vec<SavedExpert>,
+    pub services: Vec<SavedService>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateSavedListRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    #[validate(length(max = 1000))]
+    pub description: Option<String>,
+    /// Share the list with your organisation
+    #[serde(default)]
+    pub shared: bool,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateSavedListRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: Option<String>,
+    #[validate(length(max = 1000))]
+    pub description: Option<String>,
+    pub shared: Option<bool>,
+}
+
+/// Save an expert (by user ID), optionally into a list
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveExpertRequest {
+    pub expert_id: Uuid,
+    pub list_id: Option<Uuid>,
+    #[validate(length(max = 2000))]
+    pub notes: Option<String>,
+}
+
+/// Save a service, optionally into a list
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveServiceRequest {
+    pub service_id: Uuid,
+    pub list_id: Option<Uuid>,
+    #[validate(length(max = 2000))]
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateSavedNotesRequest {
+    #[validate(length(max = 2000))]
+    pub notes: Option<String>,
+}
+
+/// Filter saved experts or services by list
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedItemsQuery {
+    pub list_id: Option<Uuid>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_sharing_requests() {
+        let create: CreateSavedListRequest = serde_json::from_value(serde_json::json!({ "name": "ERP" })).unwrap();
+        assert!(!create.shared);
+
+        // Updates only change the sharing when it is given
+        let update: UpdateSavedListRequest = serde_json::from_value(serde_json::json!({ "name": "CRM" })).unwrap();
+        assert_eq!(update.shared, None);
+        let update: UpdateSavedListRequest = serde_json::from_value(serde_json::json!({ "shared": false })).unwrap();
+        assert_eq!(update.shared, Some(false));
+    }
+}
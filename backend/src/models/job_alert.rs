@@ -0,0 +1,155 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+use super::{AvailabilityStatus, Currency, Language, ProjectPosting};
+
+/// How often matching postings are emailed
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "job_alert_frequency", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum JobAlertFrequency {
+    Off,
+    Instant,
+    #[default]
+    Daily,
+}
+
+/// Job alert preferences of an expert
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct JobAlertPreferences {
+    pub expert_id: Uuid,
+    /// Notify in the app about matching postings
+    pub in_app: bool,
+    pub email_frequency: JobAlertFrequency,
+    /// Minimum match score (0-100) for an alert
+    pub min_score: i16,
+    pub last_digest_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateJobAlertPreferencesRequest {
+    pub in_app: Option<bool>,
+    pub email_frequency: Option<JobAlertFrequency>,
+    #[validate(range(min = 0, max = 100))]
+    pub min_score: Option<i16>,
+}
+
+/// Alert filter; when an expert has active filters, a posting must pass one of them
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct JobAlertFilter {
+    pub id: Uuid,
+    pub expert_id: Uuid,
+    pub name: String,
+    /// Any of these must appear in the title or description
+    pub keywords: Vec<String>,
+    /// Any of these must be among the required skills
+    pub skills: Vec<String>,
+    pub category_id: Option<Uuid>,
+    /// Minimum budget in cents
+    pub min_budget: Option<i32>,
+    pub urgent_only: bool,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateJobAlertFilterRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    #[validate(length(max = 20))]
+    pub keywords: Option<Vec<String>>,
+    #[validate(length(max = 20))]
+    pub skills: Option<Vec<String>>,
+    pub category_id: Option<Uuid>,
+    #[validate(range(min = 0))]
+    pub min_budget: Option<i32>,
+    pub urgent_only: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateJobAlertFilterRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: Option<String>,
+    #[validate(length(max = 20))]
+    pub keywords: Option<Vec<String>>,
+    #[validate(length(max = 20))]
+    pub skills: Option<Vec<String>>,
+    pub category_id: Option<Uuid>,
+    #[validate(range(min = 0))]
+    pub min_budget: Option<i32>,
+    pub urgent_only: Option<bool>,
+    pub is_active: Option<bool>,
+}
+
+/// Posting an expert was alerted about
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct JobAlert {
+    pub id: Uuid,
+    pub project_posting_id: Uuid,
+    pub posting_title: String,
+    /// Match score (0-100)
+    pub score: i16,
+    pub matched_skills: Vec<String>,
+    pub matched_tools: Vec<String>,
+    pub emailed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// New posting to match, with the client details used for scoring
+#[derive(Debug, Clone, FromRow)]
+pub struct AlertPosting {
+    #[sqlx(flatten)]
+    pub posting: ProjectPosting,
+    pub client_industry: Option<String>,
+    pub client_language: Language,
+}
+
+/// Expert who may receive alerts, with profile and alert preferences
+#[derive(Debug, Clone, FromRow)]
+pub struct AlertCandidate {
+    pub expert_id: Uuid,
+    pub email: String,
+    pub first_name: String,
+    pub language: Language,
+    pub skills: Vec<String>,
+    pub tools: Vec<String>,
+    pub industries: Vec<String>,
+    pub languages_spoken: Vec<String>,
+    pub hourly_rate: i32,
+    pub currency: Currency,
+    pub availability_status: AvailabilityStatus,
+    pub in_app: bool,
+    pub email_frequency: JobAlertFrequency,
+    pub min_score: i16,
+}
+
+/// How well a posting fits an expert
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobMatch {
+    pub score: i16,
+    pub matched_skills: Vec<String>,
+    pub matched_tools: Vec<String>,
+}
+
+/// Alert to be emailed, with the expert's contact details
+#[derive(Debug, Clone, FromRow)]
+pub struct JobAlertEmail {
+    pub alert_id: Uuid,
+    pub expert_id: Uuid,
+    pub email: String,
+    pub first_name: String,
+    pub language: Language,
+    pub posting_id: Uuid,
+    pub title: String,
+    pub score: i16,
+}
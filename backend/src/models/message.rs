@@ -13,6 +13,8 @@ pub struct Conversation {
     pub participant_two_id: Uuid,
     pub project_id: Option<Uuid>,   // Optional link to project
     pub service_id: Option<Uuid>,   // Optional link to service inquiry
+    pub agency_id: Option<Uuid>,    // Shared inbox of the agency
+    pub organisation_id: Option<Uuid>,  // Shared with the client's organisation
     pub last_message_at: Option<DateTime<Utc>>,
     pub last_message_preview: Option<String>,
     pub unread_count_one: i32,      // Unread for participant one
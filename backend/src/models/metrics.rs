@@ -0,0 +1,64 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Kind of measured expert performance event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "expert_metric_kind", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ExpertMetricKind {
+    /// Hours until the first reply to a new conversation or booking request
+    FirstResponse,
+    /// Delivered by the agreed delivery date (1) or late (0)
+    Delivery,
+    /// Completed (1) or cancelled after work had started (0)
+    Outcome,
+    /// Completed project needed at least one revision (1) or none (0)
+    Revision,
+}
+
+/// Single measured sample
+#[derive(Debug, Clone, FromRow)]
+pub struct MetricSample {
+    pub kind: ExpertMetricKind,
+    pub value: f32,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Aggregated expert metrics; rates are percentages
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpertMetricsSummary {
+    /// Median hours until the first reply
+    pub response_time_hours: Option<i16>,
+    pub completion_rate: Option<f32>,
+    pub on_time_delivery_rate: Option<f32>,
+    pub revision_rate: Option<f32>,
+    pub samples: i64,
+}
+
+/// Metrics of one calendar month
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpertMetricsPoint {
+    /// First day of the month
+    pub period: NaiveDate,
+    #[serde(flatten)]
+    pub metrics: ExpertMetricsSummary,
+}
+
+/// Current figures and monthly history
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpertMetrics {
+    pub current: ExpertMetricsSummary,
+    pub series: Vec<ExpertMetricsPoint>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Time series query
+#[derive(Debug, Deserialize)]
+pub struct ExpertMetricsQuery {
+    /// Number of months to include (default 12)
+    pub months: Option<u32>,
+}
@@ -10,6 +10,18 @@ pub mod availability;
 pub mod client;
 pub mod payment;
 pub mod report;
+pub mod notification;
+pub mod tip;
+pub mod cancellation;
+pub mod tax;
+pub mod metrics;
+pub mod requirements;
+pub mod change_request;
+pub mod contract;
+pub mod agency;
+pub mod organisation;
+pub mod favourite;
+pub mod job_alert;
 
 pub use user::*;
 pub use expert::*;
@@ -23,6 +35,18 @@ pub use availability::*;
 pub use client::*;
 pub use payment::*;
 pub use report::*;
+pub use notification::*;
+pub use tip::*;
+pub use cancellation::*;
+pub use tax::*;
+pub use metrics::*;
+pub use requirements::*;
+pub use change_request::*;
+pub use contract::*;
+pub use agency::*;
+pub use organisation::*;
+pub use favourite::*;
+pub use job_alert::*;
 
 use serde::{Deserialize, Serialize};
 
@@ -76,10 +100,13 @@ impl PaginationMeta {
 #[sqlx(type_name = "country", rename_all = "lowercase")]
 pub enum Country {
     #[serde(rename = "ch")]
+    #[sqlx(rename = "ch")]
     Switzerland,
     #[serde(rename = "de")]
+    #[sqlx(rename = "de")]
     Germany,
     #[serde(rename = "at")]
+    #[sqlx(rename = "at")]
     Austria,
 }
 
@@ -0,0 +1,272 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+use super::{CompanyDetails, Currency};
+
+/// Role of a user within a client organisation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "organisation_role", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum OrganisationRole {
+    /// Manages members, limits and billing; approves purchases
+    Admin,
+    /// Buys services within their spending limit
+    Buyer,
+    /// Follows projects and conversations only
+    Viewer,
+}
+
+impl OrganisationRole {
+    pub fn can_purchase(self) -> bool {
+        matches!(self, OrganisationRole::Admin | OrganisationRole::Buyer)
+    }
+}
+
+/// Company with several buyers; its projects are invoiced together
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientOrganisation {
+    pub id: Uuid,
+    pub name: String,
+    pub company_details: sqlx::types::Json<CompanyDetails>,
+    /// Purchases above this amount (cents) by buyers need an admin's approval
+    pub approval_threshold: Option<i32>,
+    /// Admin whose billing terms apply and who receives the consolidated invoices
+    pub billing_contact_id: Option<Uuid>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Member of an organisation with user details
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct OrganisationMember {
+    pub id: Uuid,
+    pub organisation_id: Uuid,
+    pub user_id: Uuid,
+    pub role: OrganisationRole,
+    /// Monthly spending limit in cents (unlimited if None)
+    pub spending_limit: Option<i32>,
+    pub first_name: String,
+    pub last_name: String,
+    pub email: String,
+    pub joined_at: DateTime<Utc>,
+}
+
+/// Organisation of the current user with their role
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrganisationMembership {
+    pub organisation: ClientOrganisation,
+    pub role: OrganisationRole,
+    pub spending_limit: Option<i32>,
+    /// Spent by the user in the current month (cents)
+    pub spent_this_month: i64,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateOrganisationRequest {
+    #[validate(length(min = 2, max = 200))]
+    pub name: String,
+    pub company_details: Option<CompanyDetails>,
+    #[validate(range(min = 0))]
+    pub approval_threshold: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateOrganisationRequest {
+    #[validate(length(min = 2, max = 200))]
+    pub name: Option<String>,
+    pub company_details: Option<CompanyDetails>,
+    #[validate(range(min = 0))]
+    pub approval_threshold: Option<i32>,
+    /// Remove the approval threshold
+    #[serde(default)]
+    pub remove_approval_threshold: bool,
+    /// Must be an admin of the organisation
+    pub billing_contact_id: Option<Uuid>,
+}
+
+/// Add a client to the organisation
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct AddOrganisationMemberRequest {
+    pub user_id: Uuid,
+    pub role: Option<OrganisationRole>,
+    #[validate(range(min = 0))]
+    pub spending_limit: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateOrganisationMemberRequest {
+    pub role: Option<OrganisationRole>,
+    #[validate(range(min = 0))]
+    pub spending_limit: Option<i32>,
+    /// Remove the spending limit
+    #[serde(default)]
+    pub remove_spending_limit: bool,
+}
+
+/// Reply in a shared organisation conversation
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct OrganisationReplyRequest {
+    #[validate(length(min = 1, max = 5000))]
+    pub content: String,
+}
+
+/// Status of a purchase approval
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "purchase_approval_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum PurchaseApprovalStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+/// Request of a buyer to make a purchase above the approval threshold
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct PurchaseApproval {
+    pub id: Uuid,
+    pub organisation_id: Uuid,
+    pub requested_by: Uuid,
+    pub service_id: Option<Uuid>,
+    pub proposal_id: Option<Uuid>,
+    /// Maximum amount approved (cents)
+    pub amount: i32,
+    pub currency: Currency,
+    pub note: Option<String>,
+    pub status: PurchaseApprovalStatus,
+    pub decided_by: Option<Uuid>,
+    pub decision_note: Option<String>,
+    pub decided_at: Option<DateTime<Utc>>,
+    pub used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Ask for approval of a purchase: a service, a proposal or (neither) a direct project request
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatePurchaseApprovalRequest {
+    pub service_id: Option<Uuid>,
+    pub proposal_id: Option<Uuid>,
+    #[validate(range(min = 1))]
+    pub amount: i32,
+    pub currency: Currency,
+    #[validate(length(max = 2000))]
+    pub note: Option<String>,
+}
+
+/// Approve or reject a purchase
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct DecidePurchaseApprovalRequest {
+    #[validate(length(max = 2000))]
+    pub message: Option<String>,
+}
+
+/// What an organisation purchase is for
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PurchaseSubject {
+    pub service_id: Option<Uuid>,
+    pub proposal_id: Option<Uuid>,
+}
+
+/// Why a member cannot make a purchase
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PurchaseBlock {
+    NotAllowed,
+    ApprovalRequired { threshold: i32 },
+    SpendingLimit { limit: i32, spent: i64 },
+}
+
+impl std::fmt::Display for PurchaseBlock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PurchaseBlock::NotAllowed => write!(f, "Viewers cannot make purchases for the organisation"),
+            PurchaseBlock::ApprovalRequired { threshold } => write!(
+                f,
+                "Purchases above {} cents need an admin's approval",
+                threshold
+            ),
+            PurchaseBlock::SpendingLimit { limit, spent } => write!(
+                f,
+                "Purchase exceeds your monthly spending limit ({} of {} cents spent)",
+                spent, limit
+            ),
+        }
+    }
+}
+
+/// Check a purchase of `amount` cents by a member. An approved purchase is covered by the
+/// admin's decision; admins only have to stay within their own spending limit.
+pub fn check_purchase(
+    role: OrganisationRole,
+    spending_limit: Option<i32>,
+    spent_this_month: i64,
+    approval_threshold: Option<i32>,
+    amount: i64,
+    approved: bool,
+) -> Result<(), PurchaseBlock> {
+    if !role.can_purchase() {
+        return Err(PurchaseBlock::NotAllowed);
+    }
+    if approved {
+        return Ok(());
+    }
+    if let Some(threshold) = approval_threshold
+        && role != OrganisationRole::Admin
+        && amount > threshold as i64
+    {
+        return Err(PurchaseBlock::ApprovalRequired { threshold });
+    }
+    if let Some(limit) = spending_limit
+        && spent_this_month + amount > limit as i64
+    {
+        return Err(PurchaseBlock::SpendingLimit { limit, spent: spent_this_month });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_purchase_threshold_and_approval() {
+        let buyer = OrganisationRole::Buyer;
+        assert_eq!(check_purchase(buyer, None, 0, Some(100_000), 100_000, false), Ok(()));
+        assert_eq!(
+            check_purchase(buyer, None, 0, Some(100_000), 100_001, false),
+            Err(PurchaseBlock::ApprovalRequired { threshold: 100_000 })
+        );
+        assert_eq!(check_purchase(buyer, None, 0, Some(100_000), 100_001, true), Ok(()));
+        assert_eq!(check_purchase(OrganisationRole::Admin, None, 0, Some(100_000), 500_000, false), Ok(()));
+        assert_eq!(
+            check_purchase(OrganisationRole::Viewer, None, 0, None, 1, true),
+            Err(PurchaseBlock::NotAllowed)
+        );
+    }
+
+    #[test]
+    fn test_purchase_spending_limit() {
+        let buyer = OrganisationRole::Buyer;
+        assert_eq!(check_purchase(buyer, Some(50_000), 30_000, None, 20_000, false), Ok(()));
+        assert_eq!(
+            check_purchase(buyer, Some(50_000), 30_000, None, 20_001, false),
+            Err(PurchaseBlock::SpendingLimit { limit: 50_000, spent: 30_000 })
+        );
+        assert_eq!(check_purchase(buyer, Some(50_000), 30_000, None, 20_001, true), Ok(()));
+    }
+}
@@ -63,6 +63,7 @@ pub struct Payment {
     pub refund_reason: Option<String>,
     pub paid_at: Option<DateTime<Utc>>,
     pub refunded_at: Option<DateTime<Utc>>,
+    pub released_at: Option<DateTime<Utc>>,    // Eligible for payout since
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -96,7 +97,7 @@ pub struct Invoice {
     pub invoice_number: String,
     pub project_id: Option<Uuid>,
     pub payment_id: Option<Uuid>,
-    pub issuer_id: Uuid,
+    pub issuer_id: Option<Uuid>,    // None for invoices issued by the platform
     pub recipient_id: Uuid,
     pub subtotal: i32,
     pub tax_rate: Option<rust_decimal::Decimal>,
@@ -112,10 +113,33 @@ pub struct Invoice {
     pub issuer_details: sqlx::types::Json<CompanyDetails>,
     pub recipient_details: sqlx::types::Json<CompanyDetails>,
     pub pdf_url: Option<String>,
+    pub dunning_level: i16,
+    pub last_dunning_at: Option<DateTime<Utc>>,
+    pub late_fee: i32,
+    pub billing_period_start: Option<NaiveDate>,
+    pub billing_period_end: Option<NaiveDate>,
+    pub payment_reference: Option<String>,
+    pub payment_details: Option<sqlx::types::Json<InvoicePaymentDetails>>,
+    pub organisation_id: Option<Uuid>,  // Consolidated invoice of a client organisation
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// Open invoice past its due date, with the recipient's contact details
+#[derive(Debug, Clone, FromRow)]
+pub struct OverdueInvoice {
+    pub id: Uuid,
+    pub invoice_number: String,
+    pub recipient_id: Uuid,
+    pub recipient_email: String,
+    pub recipient_first_name: String,
+    pub recipient_language: super::Language,
+    pub total: i32,
+    pub currency: String,
+    pub due_date: NaiveDate,
+    pub dunning_level: i16,
+}
+
 /// Invoice line item
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -126,6 +150,18 @@ pub struct InvoiceLineItem {
     pub amount: i32,
 }
 
+/// Bank transfer details for invoices paid by transfer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvoicePaymentDetails {
+    pub account_holder: String,
+    pub iban: String,
+    pub bic: Option<String>,
+    pub reference: String,
+    /// Swiss QR-bill payload (to be rendered as QR code)
+    pub qr_bill_payload: Option<String>,
+}
+
 /// Company details for invoices
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
@@ -168,6 +204,8 @@ pub struct CreatePayoutRequest {
 #[serde(rename_all = "camelCase")]
 pub struct CreateCheckoutSessionRequest {
     pub service_id: Uuid,
+    /// Accepted card-billed project the checkout pays for; the project's price is charged
+    pub project_id: Option<Uuid>,
     /// Optional package tier: "basic", "standard", or "premium"
     pub package_tier: Option<String>,
     /// Custom amount in cents (for custom quotes)
@@ -175,6 +213,9 @@ pub struct CreateCheckoutSessionRequest {
     /// Currency code (EUR, CHF)
     #[validate(length(min = 3, max = 3))]
     pub currency: Option<String>,
+    /// Add-ons of the service, charged as separate line items
+    #[validate(length(max = 10))]
+    pub addons: Option<Vec<super::SelectedAddon>>,
 }
 
 /// Checkout session response
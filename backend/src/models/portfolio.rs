@@ -80,3 +80,11 @@ pub struct UpdatePortfolioItemRequest {
     pub sort_order: Option<i16>,
 }
 
+/// Request to reorder portfolio items
+#[derive(Debug, Clone, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct ReorderPortfolioRequest {
+    /// Item ids in the new order
+    #[validate(length(min = 1, max = 100))]
+    pub item_ids: Vec<Uuid>,
+}
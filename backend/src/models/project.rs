@@ -1,10 +1,12 @@
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 use validator::Validate;
 
-use super::Currency;
+use super::{BillingMode, Currency, RequirementField, RequirementValue, SelectedAddon};
 
 /// Project/Order - when a client hires an expert
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -34,6 +36,14 @@ pub struct Project {
     pub stripe_transfer_id: Option<String>,
     pub is_disputed: bool,
     pub dispute_reason: Option<String>,
+    pub po_number: Option<String>,
+    pub billing_mode: BillingMode,
+    pub invoice_id: Option<Uuid>,   // Consolidated invoice (invoice billing only)
+    pub proposal_id: Option<Uuid>,  // Accepted proposal the project was created from
+    pub requirements_form: sqlx::types::Json<Vec<RequirementField>>,  // Snapshot of the service's form
+    pub requirements_completed_at: Option<DateTime<Utc>>,
+    pub agency_id: Option<Uuid>,    // Agency of the ordered service
+    pub organisation_id: Option<Uuid>,  // Client organisation the project was bought for
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -45,6 +55,7 @@ pub enum ProjectStatus {
     Pending,        // Awaiting expert acceptance
     Accepted,       // Expert accepted, awaiting payment
     Paid,           // Payment received, work can begin
+    AwaitingRequirements, // Paid, waiting for the client to complete the requirements form
     InProgress,     // Expert is working
     Delivered,      // Expert delivered, awaiting client review
     Revision,       // Client requested revision
@@ -54,6 +65,94 @@ pub enum ProjectStatus {
     Refunded,       // Refund issued
 }
 
+/// Who triggers a project status change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "project_actor", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectActor {
+    Client,
+    Expert,
+    Admin,
+    /// Background jobs and payment webhooks
+    System,
+}
+
+impl ProjectStatus {
+    /// Statuses `actor` may move a project to from this status
+    pub fn allowed_transitions(&self, actor: ProjectActor) -> Vec<ProjectStatus> {
+        use ProjectActor::*;
+        use ProjectStatus::*;
+
+        let edges: &[(ProjectStatus, &[ProjectActor])] = match self {
+            // System: the client accepted the expert's proposal
+            Pending => &[(Accepted, &[Expert, Admin, System]), (Cancelled, &[Client, Expert, Admin, System])],
+            Accepted => &[(Paid, &[System, Admin]), (Cancelled, &[Client, Expert, Admin, System])],
+            Paid => &[
+                (InProgress, &[Expert, Admin]),
+                (AwaitingRequirements, &[System, Admin]),
+                (Cancelled, &[Client, Expert, Admin]),
+            ],
+            // System: the client completed the requirements form
+            AwaitingRequirements => &[(InProgress, &[System, Admin]), (Cancelled, &[Client, Expert, Admin])],
+            InProgress | Revision => &[
+                (Delivered, &[Expert, Admin]),
+                (Disputed, &[Client, Expert, Admin]),
+                (Cancelled, &[Client, Expert, Admin]),
+            ],
+            Delivered => &[
+                (Revision, &[Client, Admin]),
+                (Completed, &[Client, Admin, System]),
+                (Disputed, &[Client, Expert, Admin]),
+                (Cancelled, &[Client, Expert, Admin]),
+            ],
+            Disputed => &[
+                (InProgress, &[Admin]),
+                (Completed, &[Admin]),
+                (Cancelled, &[Admin]),
+                (Refunded, &[Admin]),
+            ],
+            Completed => &[(Refunded, &[Admin])],
+            Cancelled => &[(Refunded, &[Admin, System])],
+            Refunded => &[],
+        };
+
+        edges
+            .iter()
+            .filter(|(_, actors)| actors.contains(&actor))
+            .map(|(status, _)| status.clone())
+            .collect()
+    }
+
+    /// Whether `actor` may move a project from this status to `to`
+    pub fn can_transition_to(&self, to: &ProjectStatus, actor: ProjectActor) -> bool {
+        self.allowed_transitions(actor).contains(to)
+    }
+
+    /// No further work or money movement expected
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, ProjectStatus::Completed | ProjectStatus::Cancelled | ProjectStatus::Refunded)
+    }
+
+    /// Agreed project whose terms can still be changed by mutual agreement
+    pub fn accepts_change_requests(&self) -> bool {
+        !self.is_terminal() && !matches!(self, ProjectStatus::Pending | ProjectStatus::Disputed)
+    }
+}
+
+/// Delivered project awaiting the client's approval, with the client's contact details
+#[derive(Debug, Clone, FromRow)]
+pub struct AwaitingApproval {
+    pub id: Uuid,
+    pub title: String,
+    pub client_id: Uuid,
+    pub expert_id: Uuid,
+    pub client_email: String,
+    pub client_first_name: String,
+    pub client_language: super::Language,
+    pub delivered_at: DateTime<Utc>,
+    pub auto_complete_reminders: i16,
+}
+
 /// Project milestone
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 #[serde(rename_all = "camelCase")]
@@ -79,6 +178,49 @@ pub enum MilestoneStatus {
     Cancelled,
 }
 
+/// Project timeline event type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "project_event_type", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectEventType {
+    ProjectCreated,
+    StatusChanged,
+    DeliverySubmitted,
+    RevisionRequested,
+    RequirementsSubmitted,
+    ChangeRequested,
+    ChangeAccepted,
+    ChangeRejected,
+    ChangeWithdrawn,
+    ContractCreated,
+    ContractSigned,
+    ProjectAssigned,
+    CompletionReminderSent,
+    FileUploaded,
+    DeliverableFinalized,
+    MilestoneCreated,
+    MilestoneUpdated,
+    PaymentReceived,
+    PaymentRefunded,
+    PaymentDisputed,
+}
+
+/// Entry of the project timeline (audit trail)
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectEvent {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub event_type: ProjectEventType,
+    pub actor: ProjectActor,
+    pub actor_id: Option<Uuid>,
+    pub from_status: Option<ProjectStatus>,
+    pub to_status: Option<ProjectStatus>,
+    pub message: Option<String>,
+    pub data: Option<sqlx::types::Json<serde_json::Value>>,
+    pub created_at: DateTime<Utc>,
+}
+
 /// Project deliverable
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 #[serde(rename_all = "camelCase")]
@@ -88,13 +230,78 @@ pub struct ProjectDeliverable {
     pub milestone_id: Option<Uuid>,
     pub title: String,
     pub description: Option<String>,
-    pub file_url: String,
+    /// External link (attachments submitted as URLs)
+    pub file_url: Option<String>,
     pub file_name: String,
     pub file_type: String,
     pub file_size: i64,
     pub version: i16,
     pub is_final: bool,
     pub created_at: DateTime<Utc>,
+    /// Object key in private storage; clients download via signed URLs
+    #[serde(skip_serializing)]
+    pub storage_key: Option<String>,
+    pub uploaded_by: Option<Uuid>,
+    pub uploaded_at: Option<DateTime<Utc>>,
+}
+
+/// Delivery submitted by the expert
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectDelivery {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub delivered_by: Option<Uuid>,
+    pub message: String,
+    pub deliverable_ids: Vec<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Revision request referencing the deliverable versions it applies to
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectRevisionRequest {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub delivery_id: Option<Uuid>,
+    pub requested_by: Option<Uuid>,
+    pub feedback: String,
+    pub deliverable_ids: Vec<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Request a presigned upload URL for a deliverable
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateDeliverableUploadRequest {
+    #[validate(length(min = 1, max = 200))]
+    pub title: String,
+    #[validate(length(max = 2000))]
+    pub description: Option<String>,
+    pub milestone_id: Option<Uuid>,
+    #[validate(length(min = 1, max = 255))]
+    pub file_name: String,
+    #[validate(length(min = 1, max = 100))]
+    pub content_type: String,
+    #[validate(range(min = 1))]
+    pub file_size: i64,
+}
+
+/// Presigned upload for a new deliverable version
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeliverableUpload {
+    pub deliverable: ProjectDeliverable,
+    pub upload_url: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Time-limited download link
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeliverableDownload {
+    pub url: String,
+    pub expires_at: Option<DateTime<Utc>>,
 }
 
 /// Create project request (client initiates)
@@ -112,12 +319,23 @@ pub struct CreateProjectRequest {
     pub description: String,
     
     pub requirements: Option<String>,
+
+    /// Answers to the service's requirements form by field id (may be incomplete)
+    pub requirement_answers: Option<HashMap<String, RequirementValue>>,
+
+    /// Add-ons of the service, added to the budget
+    #[validate(length(max = 20))]
+    pub addons: Option<Vec<SelectedAddon>>,
     
     #[validate(range(min = 0))]
     pub budget: Option<i32>,
     
     pub currency: Currency,
     pub deadline: Option<DateTime<Utc>>,
+
+    /// Purchase order number (invoice billing)
+    #[validate(length(max = 100))]
+    pub po_number: Option<String>,
 }
 
 /// Update project status
@@ -127,11 +345,33 @@ pub struct UpdateProjectStatusRequest {
     pub message: Option<String>,
 }
 
+/// Create milestone request
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateMilestoneRequest {
+    #[validate(length(min = 3, max = 200))]
+    pub title: String,
+    #[validate(length(max = 2000))]
+    pub description: Option<String>,
+    #[validate(range(min = 0))]
+    pub amount: i32,
+    pub due_date: Option<DateTime<Utc>>,
+}
+
+/// Update milestone status request
+#[derive(Debug, Deserialize)]
+pub struct UpdateMilestoneStatusRequest {
+    pub status: MilestoneStatus,
+}
+
 /// Request revision
 #[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
 pub struct RequestRevisionRequest {
     #[validate(length(min = 20, max = 2000))]
     pub feedback: String,
+    /// Deliverable versions the feedback refers to (defaults to the latest delivery)
+    pub deliverable_ids: Option<Vec<Uuid>>,
 }
 
 /// Project filters
@@ -148,3 +388,40 @@ pub struct ProjectFilters {
     pub to_date: Option<DateTime<Utc>>,
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cannot_skip_to_completed() {
+        assert!(!ProjectStatus::Pending.can_transition_to(&ProjectStatus::Completed, ProjectActor::Client));
+        assert!(!ProjectStatus::InProgress.can_transition_to(&ProjectStatus::Completed, ProjectActor::Client));
+        assert!(ProjectStatus::Delivered.can_transition_to(&ProjectStatus::Completed, ProjectActor::Client));
+    }
+
+    #[test]
+    fn test_transitions_depend_on_actor() {
+        assert!(ProjectStatus::Pending.can_transition_to(&ProjectStatus::Accepted, ProjectActor::Expert));
+        assert!(!ProjectStatus::Pending.can_transition_to(&ProjectStatus::Accepted, ProjectActor::Client));
+        assert!(!ProjectStatus::Accepted.can_transition_to(&ProjectStatus::Paid, ProjectActor::Client));
+        assert!(ProjectStatus::Accepted.can_transition_to(&ProjectStatus::Paid, ProjectActor::System));
+        assert!(!ProjectStatus::Delivered.can_transition_to(&ProjectStatus::Revision, ProjectActor::Expert));
+    }
+
+    #[test]
+    fn test_awaiting_requirements_only_left_by_system() {
+        assert!(ProjectStatus::Paid.can_transition_to(&ProjectStatus::AwaitingRequirements, ProjectActor::System));
+        assert!(!ProjectStatus::AwaitingRequirements.can_transition_to(&ProjectStatus::InProgress, ProjectActor::Expert));
+        assert!(ProjectStatus::AwaitingRequirements.can_transition_to(&ProjectStatus::InProgress, ProjectActor::System));
+        assert!(ProjectStatus::AwaitingRequirements.can_transition_to(&ProjectStatus::Cancelled, ProjectActor::Client));
+    }
+
+    #[test]
+    fn test_terminal_statuses() {
+        for actor in [ProjectActor::Client, ProjectActor::Expert, ProjectActor::System] {
+            assert!(ProjectStatus::Completed.allowed_transitions(actor).is_empty());
+            assert!(ProjectStatus::Refunded.allowed_transitions(actor).is_empty());
+        }
+        assert_eq!(ProjectStatus::Completed.allowed_transitions(ProjectActor::Admin), vec![ProjectStatus::Refunded]);
+    }
+}
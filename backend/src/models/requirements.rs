@@ -0,0 +1,413 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::utils::{is_valid_url, SecretKey};
+
+/// Maximum number of fields of a requirements form
+pub const MAX_REQUIREMENT_FIELDS: usize = 30;
+/// Default maximum length of text answers
+pub const DEFAULT_TEXT_ANSWER_LENGTH: usize = 5000;
+/// Maximum length of secret answers (credentials, API keys)
+pub const MAX_SECRET_ANSWER_LENGTH: usize = 1000;
+
+/// Type of a requirements form field
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RequirementFieldType {
+    Text,
+    Choice,
+    /// Uploaded by the client via the project's requirements endpoints
+    File,
+    Url,
+    /// Access credentials; only shown to the expert and deleted when the project is closed
+    Secret,
+}
+
+impl RequirementFieldType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RequirementFieldType::Text => "text",
+            RequirementFieldType::Choice => "choice",
+            RequirementFieldType::File => "file",
+            RequirementFieldType::Url => "url",
+            RequirementFieldType::Secret => "secret",
+        }
+    }
+}
+
+/// Field of a service's requirements form
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RequirementField {
+    /// Stable key of the field, referenced by answers
+    pub id: String,
+    pub label: String,
+    #[serde(rename = "type")]
+    pub field_type: RequirementFieldType,
+    #[serde(default)]
+    pub required: bool,
+    pub help_text: Option<String>,
+    /// Choices (choice fields only)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<String>,
+    /// Allow several choices (choice fields only)
+    #[serde(default)]
+    pub multiple: bool,
+    /// Maximum answer length (text and secret fields)
+    pub max_length: Option<usize>,
+}
+
+impl RequirementField {
+    /// Check the field definition
+    fn check(&self) -> Result<(), String> {
+        let id_valid = !self.id.is_empty()
+            && self.id.len() <= 50
+            && self.id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if !id_valid {
+            return Err(format!(
+                "Invalid field id '{}': use 1-50 letters, digits, '-' or '_'",
+                self.id
+            ));
+        }
+        let label_length = self.label.trim().chars().count();
+        if !(1..=200).contains(&label_length) {
+            return Err(format!("Field '{}' needs a label of 1-200 characters", self.id));
+        }
+        if self.help_text.as_ref().is_some_and(|h| h.chars().count() > 1000) {
+            return Err(format!("Help text of field '{}' is too long", self.id));
+        }
+
+        if self.field_type == RequirementFieldType::Choice {
+            if self.options.is_empty() || self.options.len() > 50 {
+                return Err(format!("Choice field '{}' needs 1-50 options", self.id));
+            }
+            let mut seen = HashSet::new();
+            for option in &self.options {
+                if option.trim().is_empty() || option.chars().count() > 200 {
+                    return Err(format!("Options of field '{}' must be 1-200 characters", self.id));
+                }
+                if !seen.insert(option.as_str()) {
+                    return Err(format!("Duplicate option '{}' in field '{}'", option, self.id));
+                }
+            }
+        } else if !self.options.is_empty() || self.multiple {
+            return Err(format!("Only choice fields can have options (field '{}')", self.id));
+        }
+
+        if let Some(max_length) = self.max_length {
+            let limit = match self.field_type {
+                RequirementFieldType::Text => DEFAULT_TEXT_ANSWER_LENGTH,
+                RequirementFieldType::Secret => MAX_SECRET_ANSWER_LENGTH,
+                _ => return Err(format!("Only text and secret fields can have a maximum length (field '{}')", self.id)),
+            };
+            if max_length == 0 || max_length > limit {
+                return Err(format!("Maximum length of field '{}' must be 1-{}", self.id, limit));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check an answer to this field and return it trimmed
+    pub fn check_answer(&self, value: &RequirementValue) -> Result<RequirementValue, String> {
+        match (self.field_type, value) {
+            (RequirementFieldType::File, _) => {
+                Err(format!("Field '{}' expects a file upload", self.id))
+            }
+            (RequirementFieldType::Choice, RequirementValue::Choices(choices)) if self.multiple => {
+                let mut seen = HashSet::new();
+                for choice in choices {
+                    if !self.options.contains(choice) {
+                        return Err(format!("'{}' is not an option of field '{}'", choice, self.id));
+                    }
+                    if !seen.insert(choice) {
+                        return Err(format!("Option '{}' selected twice in field '{}'", choice, self.id));
+                    }
+                }
+                if choices.is_empty() {
+                    return Err(format!("Select at least one option in field '{}'", self.id));
+                }
+                Ok(value.clone())
+            }
+            (RequirementFieldType::Choice, RequirementValue::Text(choice)) => {
+                if !self.options.contains(choice) {
+                    return Err(format!("'{}' is not an option of field '{}'", choice, self.id));
+                }
+                Ok(if self.multiple {
+                    RequirementValue::Choices(vec![choice.clone()])
+                } else {
+                    value.clone()
+                })
+            }
+            (_, RequirementValue::Text(text)) => {
+                let text = text.trim();
+                if text.is_empty() {
+                    return Err(format!("Field '{}' must not be empty", self.id));
+                }
+                let limit = match self.field_type {
+                    RequirementFieldType::Secret => self.max_length.unwrap_or(MAX_SECRET_ANSWER_LENGTH),
+                    RequirementFieldType::Url => 2000,
+                    _ => self.max_length.unwrap_or(DEFAULT_TEXT_ANSWER_LENGTH),
+                };
+                if text.chars().count() > limit {
+                    return Err(format!("Answer to field '{}' must be at most {} characters", self.id, limit));
+                }
+                if self.field_type == RequirementFieldType::Url && !is_valid_url(text) {
+                    return Err(format!("Field '{}' expects an http(s) URL", self.id));
+                }
+                Ok(RequirementValue::Text(text.to_string()))
+            }
+            (_, RequirementValue::Choices(_)) => {
+                Err(format!("Field '{}' expects a single value", self.id))
+            }
+        }
+    }
+}
+
+/// Check a requirements form: field definitions, unique ids and the field limit
+pub fn check_requirements_form(fields: &[RequirementField]) -> Result<(), String> {
+    if fields.len() > MAX_REQUIREMENT_FIELDS {
+        return Err(format!("A requirements form can have at most {} fields", MAX_REQUIREMENT_FIELDS));
+    }
+
+    let mut ids = HashSet::new();
+    for field in fields {
+        field.check()?;
+        if !ids.insert(field.id.as_str()) {
+            return Err(format!("Duplicate field id '{}'", field.id));
+        }
+    }
+
+    Ok(())
+}
+
+/// Check answers against a form. Unknown fields are rejected; missing answers
+/// are allowed, the project then waits for the client to complete the form.
+pub fn check_requirement_answers(
+    fields: &[RequirementField],
+    answers: &HashMap<String, RequirementValue>,
+) -> Result<Vec<(RequirementField, RequirementValue)>, String> {
+    let mut checked = Vec::with_capacity(answers.len());
+
+    for (field_id, value) in answers {
+        let field = fields
+            .iter()
+            .find(|f| &f.id == field_id)
+            .ok_or_else(|| format!("Unknown requirements field '{}'", field_id))?;
+        checked.push((field.clone(), field.check_answer(value)?));
+    }
+
+    Ok(checked)
+}
+
+/// Answer value: text (text, URL, secret and single choice fields) or several choices
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum RequirementValue {
+    Text(String),
+    Choices(Vec<String>),
+}
+
+/// Stored answer of a project's requirements form
+#[derive(Debug, Clone, FromRow)]
+pub struct ProjectRequirementAnswer {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub field_id: String,
+    pub field_type: String,
+    pub value: Option<sqlx::types::Json<RequirementValue>>,
+    pub file_name: Option<String>,
+    pub file_type: Option<String>,
+    pub file_size: Option<i64>,
+    pub storage_key: Option<String>,
+    pub answered_by: Option<Uuid>,
+    /// Encrypted value of secret answers (`value` is `None` for them)
+    pub encrypted_value: Option<Vec<u8>>,
+    pub purged_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Answer as shown to a party of the project
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequirementAnswer {
+    pub field_id: String,
+    /// `None` for files, purged secrets and secrets hidden from the viewer
+    pub value: Option<RequirementValue>,
+    pub file_name: Option<String>,
+    pub file_size: Option<i64>,
+    /// Secret answers are only visible to the expert
+    pub is_hidden: bool,
+    pub purged_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ProjectRequirementAnswer {
+    /// Whether the field was answered (with a value, a secret or a file)
+    pub fn is_answered(&self) -> bool {
+        self.value.is_some() || self.encrypted_value.is_some() || self.storage_key.is_some()
+    }
+
+    /// Answer as shown to the client, or to the expert who gets the key to decrypt secrets
+    pub fn view(&self, secret_key: Option<&SecretKey>) -> RequirementAnswer {
+        let is_secret = self.field_type == RequirementFieldType::Secret.as_str();
+        let is_hidden = is_secret && secret_key.is_none();
+        let value = match (is_secret, secret_key) {
+            (true, Some(key)) => self
+                .encrypted_value
+                .as_deref()
+                .and_then(|data| key.decrypt(data))
+                .and_then(|plaintext| serde_json::from_slice(&plaintext).ok()),
+            (true, None) => None,
+            (false, _) => self.value.as_ref().map(|v| v.0.clone()),
+        };
+        RequirementAnswer {
+            field_id: self.field_id.clone(),
+            value,
+            file_name: self.file_name.clone(),
+            file_size: self.file_size,
+            is_hidden,
+            purged_at: self.purged_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+/// Requirements form of a project with its answers
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectRequirements {
+    pub form: Vec<RequirementField>,
+    pub answers: Vec<RequirementAnswer>,
+    /// Required fields without an answer
+    pub missing: Vec<String>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// Submit answers to a project's requirements form (client)
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmitRequirementsRequest {
+    pub answers: HashMap<String, RequirementValue>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(id: &str, field_type: RequirementFieldType) -> RequirementField {
+        RequirementField {
+            id: id.to_string(),
+            label: format!("Field {}", id),
+            field_type,
+            required: true,
+            help_text: None,
+            options: Vec::new(),
+            multiple: false,
+            max_length: None,
+        }
+    }
+
+    fn choice(id: &str, multiple: bool) -> RequirementField {
+        RequirementField {
+            options: vec!["WordPress".to_string(), "Shopify".to_string()],
+            multiple,
+            ..field(id, RequirementFieldType::Choice)
+        }
+    }
+
+    fn text(value: &str) -> RequirementValue {
+        RequirementValue::Text(value.to_string())
+    }
+
+    #[test]
+    fn test_form_definition() {
+        assert!(check_requirements_form(&[field("brief", RequirementFieldType::Text), choice("cms", false)]).is_ok());
+
+        let duplicate = [field("brief", RequirementFieldType::Text), field("brief", RequirementFieldType::Url)];
+        assert!(check_requirements_form(&duplicate).is_err());
+
+        assert!(check_requirements_form(&[field("cms", RequirementFieldType::Choice)]).is_err());
+        assert!(check_requirements_form(&[field("bad id", RequirementFieldType::Text)]).is_err());
+
+        let url_with_options = RequirementField {
+            options: vec!["a".to_string()],
+            ..field("site", RequirementFieldType::Url)
+        };
+        assert!(check_requirements_form(&[url_with_options]).is_err());
+
+        let too_long = RequirementField {
+            max_length: Some(MAX_SECRET_ANSWER_LENGTH + 1),
+            ..field("password", RequirementFieldType::Secret)
+        };
+        assert!(check_requirements_form(&[too_long]).is_err());
+    }
+
+    #[test]
+    fn test_answers_are_typed() {
+        let url = field("site", RequirementFieldType::Url);
+        assert_eq!(url.check_answer(&text(" https://example.ch ")), Ok(text("https://example.ch")));
+        assert!(url.check_answer(&text("example")).is_err());
+
+        assert!(field("brief", RequirementFieldType::Text).check_answer(&text("   ")).is_err());
+        assert!(field("logo", RequirementFieldType::File).check_answer(&text("logo.png")).is_err());
+
+        let single = choice("cms", false);
+        assert!(single.check_answer(&text("Shopify")).is_ok());
+        assert!(single.check_answer(&text("Joomla")).is_err());
+        assert!(single.check_answer(&RequirementValue::Choices(vec!["Shopify".to_string()])).is_err());
+
+        let multiple = choice("cms", true);
+        let both = RequirementValue::Choices(vec!["Shopify".to_string(), "WordPress".to_string()]);
+        assert_eq!(multiple.check_answer(&both), Ok(both.clone()));
+        assert!(multiple.check_answer(&RequirementValue::Choices(Vec::new())).is_err());
+        assert_eq!(
+            multiple.check_answer(&text("Shopify")),
+            Ok(RequirementValue::Choices(vec!["Shopify".to_string()]))
+        );
+    }
+
+    #[test]
+    fn test_unknown_fields_rejected_and_partial_answers_allowed() {
+        let form = [field("brief", RequirementFieldType::Text), field("password", RequirementFieldType::Secret)];
+
+        let partial = HashMap::from([("brief".to_string(), text("Landing page"))]);
+        assert_eq!(check_requirement_answers(&form, &partial).unwrap().len(), 1);
+
+        let unknown = HashMap::from([("budget".to_string(), text("1000"))]);
+        assert!(check_requirement_answers(&form, &unknown).is_err());
+    }
+
+    #[test]
+    fn test_secrets_hidden_from_client() {
+        let key = SecretKey::new([1; 32]);
+        let answer = ProjectRequirementAnswer {
+            id: Uuid::new_v4(),
+            project_id: Uuid::new_v4(),
+            field_id: "password".to_string(),
+            field_type: "secret".to_string(),
+            value: None,
+            file_name: None,
+            file_type: None,
+            file_size: None,
+            storage_key: None,
+            answered_by: None,
+            encrypted_value: Some(key.encrypt(&serde_json::to_vec(&text("hunter2")).unwrap())),
+            purged_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        assert!(answer.is_answered());
+        assert_eq!(answer.view(Some(&key)).value, Some(text("hunter2")));
+        let hidden = answer.view(None);
+        assert!(hidden.is_hidden && hidden.value.is_none());
+        // A wrong key reveals nothing
+        assert!(answer.view(Some(&SecretKey::new([2; 32]))).value.is_none());
+    }
+}
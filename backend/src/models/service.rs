@@ -4,7 +4,7 @@ use sqlx::FromRow;
 use uuid::Uuid;
 use validator::Validate;
 
-use super::Currency;
+use super::{Currency, InvoiceLineItem, RequirementField};
 
 /// Service listing - what experts offer
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -24,6 +24,8 @@ pub struct Service {
     pub revisions_included: i16,
     pub features: Vec<String>,
     pub requirements: Option<String>,
+    /// Typed questionnaire the client answers when ordering
+    pub requirements_form: sqlx::types::Json<Vec<RequirementField>>,
     pub tags: Vec<String>,
     pub images: Vec<String>,
     pub video_url: Option<String>,
@@ -33,6 +35,8 @@ pub struct Service {
     pub order_count: i32,
     pub rating_average: f32,
     pub rating_count: i32,
+    pub cancellation_policy_id: Option<Uuid>,   // None = platform default policy
+    pub agency_id: Option<Uuid>,    // Sold under an agency's brand
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -95,11 +99,20 @@ pub struct CreateServiceRequest {
     pub features: Vec<String>,
     
     pub requirements: Option<String>,
+
+    /// Requirements form (replaces the current form; empty if omitted)
+    pub requirements_form: Option<Vec<RequirementField>>,
     
     #[validate(length(max = 10))]
     pub tags: Option<Vec<String>>,
     
     pub packages: Option<Vec<CreateServicePackageRequest>>,
+
+    /// Cancellation policy (platform default if omitted)
+    pub cancellation_policy_id: Option<Uuid>,
+
+    /// Agency the service is sold under (owner or manager of it)
+    pub agency_id: Option<Uuid>,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -152,3 +165,191 @@ pub enum ServiceSortBy {
     Newest,
 }
 
+
+/// Optional extra of a service (express delivery, additional workflow, support period)
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceAddon {
+    pub id: Uuid,
+    pub service_id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub price: i32,                 // in cents, per unit
+    pub extra_days: i16,            // per unit; negative for faster delivery
+    pub extra_revisions: i16,       // per unit
+    pub max_quantity: i16,
+    pub is_active: bool,
+    pub sort_order: i16,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Create or update a service add-on
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateServiceAddonRequest {
+    #[validate(length(min = 3, max = 100))]
+    pub title: String,
+
+    #[validate(length(max = 500))]
+    pub description: Option<String>,
+
+    #[validate(range(min = 0, max = 10000000))]
+    pub price: i32,
+
+    #[validate(range(min = -365, max = 365))]
+    #[serde(default)]
+    pub extra_days: i16,
+
+    #[validate(range(min = 0, max = 10))]
+    #[serde(default)]
+    pub extra_revisions: i16,
+
+    #[validate(range(min = 1, max = 100))]
+    pub max_quantity: Option<i16>,
+
+    pub is_active: Option<bool>,
+    pub sort_order: Option<i16>,
+}
+
+/// Add-on chosen by the client at checkout
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelectedAddon {
+    pub addon_id: Uuid,
+    #[serde(default = "default_addon_quantity")]
+    pub quantity: i16,
+}
+
+fn default_addon_quantity() -> i16 {
+    1
+}
+
+/// Selected add-on with its price
+#[derive(Debug, Clone, PartialEq)]
+pub struct PricedAddon {
+    pub addon_id: Uuid,
+    pub title: String,
+    pub unit_price: i32,
+    pub quantity: i16,
+    pub extra_days: i32,            // total for the quantity
+    pub extra_revisions: i32,       // total for the quantity
+    pub amount: i32,
+}
+
+impl PricedAddon {
+    /// Separate invoice line for the add-on
+    pub fn line_item(&self) -> InvoiceLineItem {
+        InvoiceLineItem {
+            description: self.title.clone(),
+            quantity: self.quantity as i32,
+            unit_price: self.unit_price,
+            amount: self.amount,
+        }
+    }
+}
+
+/// Price the client's selection against the service's active add-ons: each add-on
+/// at most once, quantities between 1 and the add-on's maximum
+pub fn price_addons(addons: &[ServiceAddon], selected: &[SelectedAddon]) -> Result<Vec<PricedAddon>, String> {
+    let mut priced: Vec<PricedAddon> = Vec::with_capacity(selected.len());
+
+    for selection in selected {
+        let addon = addons
+            .iter()
+            .find(|a| a.id == selection.addon_id && a.is_active)
+            .ok_or_else(|| format!("Add-on {} is not available for this service", selection.addon_id))?;
+        if priced.iter().any(|p| p.addon_id == addon.id) {
+            return Err(format!("Add-on '{}' selected twice", addon.title));
+        }
+        if selection.quantity < 1 || selection.quantity > addon.max_quantity {
+            return Err(format!(
+                "Quantity of add-on '{}' must be between 1 and {}",
+                addon.title, addon.max_quantity
+            ));
+        }
+
+        priced.push(PricedAddon {
+            addon_id: addon.id,
+            title: addon.title.clone(),
+            unit_price: addon.price,
+            quantity: selection.quantity,
+            extra_days: addon.extra_days as i32 * selection.quantity as i32,
+            extra_revisions: addon.extra_revisions as i32 * selection.quantity as i32,
+            amount: addon.price * selection.quantity as i32,
+        });
+    }
+
+    Ok(priced)
+}
+
+/// Add-on ordered with a project
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectAddon {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub addon_id: Option<Uuid>,
+    pub title: String,
+    pub unit_price: i32,
+    pub quantity: i16,
+    pub extra_days: i32,            // total for the quantity
+    pub extra_revisions: i32,       // total for the quantity
+    pub amount: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addon(title: &str, price: i32, extra_days: i16, max_quantity: i16) -> ServiceAddon {
+        ServiceAddon {
+            id: Uuid::new_v4(),
+            service_id: Uuid::nil(),
+            title: title.to_string(),
+            description: None,
+            price,
+            extra_days,
+            extra_revisions: 0,
+            max_quantity,
+            is_active: true,
+            sort_order: 0,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn select(addon: &ServiceAddon, quantity: i16) -> SelectedAddon {
+        SelectedAddon { addon_id: addon.id, quantity }
+    }
+
+    #[test]
+    fn test_addons_priced_per_unit() {
+        let express = addon("Express-Lieferung", 20000, -3, 1);
+        let workflow = addon("Zusätzlicher Workflow", 35000, 2, 5);
+        let addons = [express.clone(), workflow.clone()];
+
+        let priced = price_addons(&addons, &[select(&express, 1), select(&workflow, 3)]).unwrap();
+        assert_eq!(priced[0].amount, 20000);
+        assert_eq!(priced[0].extra_days, -3);
+        assert_eq!(priced[1].amount, 105000);
+        assert_eq!(priced[1].extra_days, 6);
+        assert_eq!(priced[1].line_item().quantity, 3);
+        assert_eq!(priced[1].line_item().unit_price, 35000);
+    }
+
+    #[test]
+    fn test_addon_selection_checked() {
+        let express = addon("Express-Lieferung", 20000, -3, 1);
+        let mut retired = addon("Alter Support", 10000, 0, 1);
+        retired.is_active = false;
+        let addons = [express.clone(), retired.clone()];
+
+        assert!(price_addons(&addons, &[select(&express, 2)]).is_err());
+        assert!(price_addons(&addons, &[select(&express, 0)]).is_err());
+        assert!(price_addons(&addons, &[select(&express, 1), select(&express, 1)]).is_err());
+        assert!(price_addons(&addons, &[select(&retired, 1)]).is_err());
+        assert!(price_addons(&addons, &[select(&addon("Fremd", 1, 0, 1), 1)]).is_err());
+    }
+}
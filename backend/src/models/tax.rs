@@ -0,0 +1,120 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::Validate;
+
+use crate::utils::VatTreatment;
+
+/// Whether the customer is a business (has a VAT ID) or a consumer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CustomerType {
+    Business,
+    Consumer,
+}
+
+/// Report section to export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TaxReportKind {
+    /// All aggregated lines
+    #[default]
+    Lines,
+    /// German / Austrian VAT return
+    Domestic,
+    /// EU One-Stop-Shop return
+    Oss,
+    /// Swiss ESTV quarterly statement
+    Estv,
+}
+
+/// Export file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Xml,
+}
+
+/// Tax report query parameters
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct TaxReportQuery {
+    #[validate(range(min = 2020, max = 2100))]
+    pub year: i32,
+    #[validate(range(min = 1, max = 4))]
+    pub quarter: u32,
+    #[serde(default)]
+    pub report: TaxReportKind,
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+/// Single revenue item of the platform (fee or platform-issued invoice)
+#[derive(Debug, Clone, FromRow)]
+pub struct TaxableItem {
+    /// "platform_fee" or "platform_sale"
+    pub source: String,
+    pub customer_country: Option<String>,
+    pub customer_vat_id: Option<String>,
+    pub currency: String,
+    /// Gross amount for platform fees, net amount for platform sales (in cents)
+    pub amount: i64,
+    /// VAT already charged on the invoice (platform sales only)
+    pub vat_amount: Option<i64>,
+}
+
+/// Aggregated revenue by customer country, customer type and VAT treatment
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaxReportLine {
+    pub source: String,
+    pub customer_country: String,
+    pub customer_type: CustomerType,
+    pub treatment: VatTreatment,
+    pub currency: String,
+    pub vat_rate: Decimal,
+    pub net_amount: i64,
+    pub vat_amount: i64,
+    pub transactions: i64,
+}
+
+/// Field of a VAT return (Kennzahl / Ziffer)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VatReturnField {
+    pub code: String,
+    pub label: String,
+    pub currency: String,
+    pub net_amount: i64,
+    pub vat_amount: i64,
+}
+
+/// OSS line per member state of consumption and rate
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OssLine {
+    pub member_state: String,
+    pub vat_rate: Decimal,
+    pub currency: String,
+    pub taxable_amount: i64,
+    pub vat_amount: i64,
+}
+
+/// Quarterly VAT report for platform revenue
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaxReport {
+    pub year: i32,
+    pub quarter: u32,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    pub seller_country: String,
+    pub lines: Vec<TaxReportLine>,
+    /// German or Austrian return (empty for a Swiss seller)
+    pub domestic: Vec<VatReturnField>,
+    pub oss: Vec<OssLine>,
+    pub estv: Vec<VatReturnField>,
+}
@@ -0,0 +1,79 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Tip status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "tip_status", rename_all = "snake_case")]
+pub enum TipStatus {
+    Pending,
+    Paid,
+    Failed,
+    Cancelled,
+}
+
+/// Tip or bonus paid by the client after project completion
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectTip {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub client_id: Uuid,
+    pub expert_id: Uuid,
+    pub payment_id: Option<Uuid>,
+    pub invoice_id: Option<Uuid>,
+    pub amount: i32,                // in cents
+    pub currency: String,
+    pub platform_fee: i32,          // in cents
+    pub net_amount: i32,            // in cents
+    pub preset_percent: Option<i16>,
+    pub message: Option<String>,
+    pub status: TipStatus,
+    pub stripe_checkout_session_id: Option<String>,
+    pub paid_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Create tip request - either a preset percentage or a custom amount
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateTipRequest {
+    /// Preset percentage of the project price (e.g. 10)
+    pub preset_percent: Option<i16>,
+    /// Custom amount in cents
+    pub custom_amount: Option<i32>,
+    #[validate(length(max = 1000))]
+    pub message: Option<String>,
+}
+
+/// Preset tip option
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TipPreset {
+    pub percent: i16,
+    pub amount: i32,
+}
+
+/// Tip options offered to the client for a completed project
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TipOptions {
+    pub project_id: Uuid,
+    pub currency: String,
+    pub presets: Vec<TipPreset>,
+    pub min_amount: i32,
+    pub max_amount: i32,
+    pub platform_fee_percent: f64,
+}
+
+/// Tip checkout response
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TipCheckoutResponse {
+    pub tip: ProjectTip,
+    pub session_id: String,
+    pub checkout_url: String,
+}
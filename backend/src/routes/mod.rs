@@ -1,4 +1,6 @@
 use axum::{
+    extract::DefaultBodyLimit,
+    middleware::{from_fn, from_fn_with_state},
     routing::{delete, get, post, put},
     Router,
 };
@@ -7,7 +9,7 @@ use crate::handlers;
 use crate::AppState;
 
 /// Build all API routes
-pub fn api_routes() -> Router<AppState> {
+pub fn api_routes(state: &AppState) -> Router<AppState> {
     Router::new()
         // Health check
         .route("/health", get(handlers::health::health_check))
@@ -19,13 +21,13 @@ pub fn api_routes() -> Router<AppState> {
         // User routes
         .nest("/users", user_routes())
         // Expert routes
-        .nest("/experts", expert_routes())
+        .nest("/experts", expert_routes(state))
         // Service routes
-        .nest("/services", service_routes())
+        .nest("/services", service_routes(state))
         // Category routes
         .nest("/categories", category_routes())
         // Project routes
-        .nest("/projects", project_routes())
+        .nest("/projects", project_routes(state))
         // Message routes
         .nest("/messages", message_routes())
         // Review routes
@@ -33,19 +35,164 @@ pub fn api_routes() -> Router<AppState> {
         // Search routes
         .nest("/search", search_routes())
         // Client routes
-        .nest("/clients", client_routes())
+        .nest("/clients", client_routes(state))
         // Project postings routes
-        .nest("/postings", posting_routes())
+        .nest("/postings", posting_routes(state))
         // Booking routes
-        .nest("/bookings", booking_routes())
+        .nest("/bookings", booking_routes(state))
         // Admin routes
-        .nest("/admin", admin_routes())
+        .nest("/admin", admin_routes(state))
         // Payment routes
-        .nest("/payments", payment_routes())
+        .nest("/payments", payment_routes(state))
         // Report routes (content moderation)
         .nest("/reports", report_routes())
         // Newsletter routes
         .nest("/newsletter", newsletter_routes())
+        // Notification routes
+        .nest("/notifications", notification_routes(state))
+        // Cancellation policies
+        .nest("/cancellation-policies", cancellation_policy_routes(state))
+        // Agencies
+        .nest("/agencies", agency_routes(state))
+        // Client organisations
+        .nest("/organisations", organisation_routes(state))
+        // Favourites
+        .nest("/favourites", favourite_routes(state))
+        // Job alerts
+        .nest("/job-alerts", job_alert_routes(state))
+}
+
+fn favourite_routes(state: &AppState) -> Router<AppState> {
+    Router::new()
+        .route("/lists", get(handlers::favourites::list_lists))
+        .route("/lists", post(handlers::favourites::create_list))
+        .route("/lists/shared", get(handlers::favourites::list_shared_lists))
+        .route("/lists/{id}", get(handlers::favourites::get_list))
+        .route("/lists/{id}", put(handlers::favourites::update_list))
+        .route("/lists/{id}", delete(handlers::favourites::delete_list))
+        .route("/experts", get(handlers::favourites::list_experts))
+        .route("/experts", post(handlers::favourites::save_expert))
+        .route("/experts/{id}", put(handlers::favourites::update_expert_notes))
+        .route("/experts/{id}", delete(handlers::favourites::remove_expert))
+        .route("/services", get(handlers::favourites::list_services))
+        .route("/services", post(handlers::favourites::save_service))
+        .route("/services/{id}", put(handlers::favourites::update_service_notes))
+        .route("/services/{id}", delete(handlers::favourites::remove_service))
+        .route_layer(from_fn_with_state(
+            state.clone(),
+            crate::middleware::auth::auth_middleware,
+        ))
+}
+
+fn job_alert_routes(state: &AppState) -> Router<AppState> {
+    Router::new()
+        .route("/", get(handlers::job_alerts::list_alerts))
+        .route("/preferences", get(handlers::job_alerts::get_preferences))
+        .route("/preferences", put(handlers::job_alerts::update_preferences))
+        .route("/filters", get(handlers::job_alerts::list_filters))
+        .route("/filters", post(handlers::job_alerts::create_filter))
+        .route("/filters/{id}", put(handlers::job_alerts::update_filter))
+        .route("/filters/{id}", delete(handlers::job_alerts::delete_filter))
+        .route_layer(from_fn_with_state(
+            state.clone(),
+            crate::middleware::auth::auth_middleware,
+        ))
+}
+
+fn organisation_routes(state: &AppState) -> Router<AppState> {
+    Router::new()
+        .route("/", post(handlers::organisations::create_organisation))
+        .route("/me", get(handlers::organisations::get_my_organisation))
+        .route("/{id}", get(handlers::organisations::get_organisation))
+        .route("/{id}", put(handlers::organisations::update_organisation))
+        .route("/{id}/members", get(handlers::organisations::list_members))
+        .route("/{id}/members", post(handlers::organisations::add_member))
+        .route("/{id}/members/{user_id}", put(handlers::organisations::update_member))
+        .route("/{id}/members/{user_id}", delete(handlers::organisations::remove_member))
+        .route("/{id}/projects", get(handlers::organisations::list_projects))
+        .route("/{id}/invoices", get(handlers::organisations::list_invoices))
+        .route("/{id}/conversations", get(handlers::organisations::get_conversations))
+        .route(
+            "/{id}/conversations/{conversation_id}/messages",
+            get(handlers::organisations::get_conversation_messages),
+        )
+        .route(
+            "/{id}/conversations/{conversation_id}/messages",
+            post(handlers::organisations::reply_in_conversation),
+        )
+        .route("/{id}/approvals", get(handlers::organisations::list_approvals))
+        .route("/{id}/approvals", post(handlers::organisations::request_approval))
+        .route(
+            "/{id}/approvals/{approval_id}/approve",
+            post(handlers::organisations::approve_purchase),
+        )
+        .route(
+            "/{id}/approvals/{approval_id}/reject",
+            post(handlers::organisations::reject_purchase),
+        )
+        .route_layer(from_fn_with_state(
+            state.clone(),
+            crate::middleware::auth::auth_middleware,
+        ))
+}
+
+fn agency_routes(state: &AppState) -> Router<AppState> {
+    let authenticated = Router::new()
+        .route("/", post(handlers::agencies::create_agency))
+        .route("/me", get(handlers::agencies::get_my_agency))
+        .route("/{id}", put(handlers::agencies::update_agency))
+        .route("/{id}/members", get(handlers::agencies::list_members))
+        .route("/{id}/members", post(handlers::agencies::add_member))
+        .route("/{id}/members/{user_id}", put(handlers::agencies::update_member))
+        .route("/{id}/members/{user_id}", delete(handlers::agencies::remove_member))
+        .route("/{id}/projects", get(handlers::agencies::list_projects))
+        .route(
+            "/{id}/projects/{project_id}/assign",
+            post(handlers::agencies::assign_project),
+        )
+        .route("/{id}/earnings", get(handlers::agencies::get_earnings))
+        .route("/{id}/inbox", get(handlers::agencies::get_inbox))
+        .route(
+            "/{id}/inbox/{conversation_id}/messages",
+            get(handlers::agencies::get_inbox_messages),
+        )
+        .route(
+            "/{id}/inbox/{conversation_id}/messages",
+            post(handlers::agencies::reply_in_inbox),
+        )
+        .route_layer(from_fn_with_state(
+            state.clone(),
+            crate::middleware::auth::auth_middleware,
+        ));
+
+    Router::new()
+        .route("/{id}", get(handlers::agencies::get_agency))
+        .route("/{id}/services", get(handlers::agencies::get_agency_services))
+        .merge(authenticated)
+}
+
+fn cancellation_policy_routes(state: &AppState) -> Router<AppState> {
+    Router::new()
+        .route("/", get(handlers::cancellations::list_policies))
+        .route("/", post(handlers::cancellations::create_policy))
+        .route_layer(from_fn_with_state(
+            state.clone(),
+            crate::middleware::auth::auth_middleware,
+        ))
+}
+
+fn notification_routes(state: &AppState) -> Router<AppState> {
+    Router::new()
+        .route("/", get(handlers::notifications::list_notifications))
+        .route("/read", post(handlers::notifications::mark_as_read))
+        .route(
+            "/unread-count",
+            get(handlers::notifications::get_unread_count),
+        )
+        .route_layer(from_fn_with_state(
+            state.clone(),
+            crate::middleware::auth::auth_middleware,
+        ))
 }
 
 fn newsletter_routes() -> Router<AppState> {
@@ -78,30 +225,84 @@ fn user_routes() -> Router<AppState> {
         )
 }
 
-fn expert_routes() -> Router<AppState> {
+fn expert_routes(state: &AppState) -> Router<AppState> {
+    let authenticated = Router::new()
+        .route("/", post(handlers::experts::create_profile))
+        .route("/{id}", put(handlers::experts::update_profile))
+        .route("/me/metrics", get(handlers::experts::get_my_metrics))
+        .route("/me/availability", put(handlers::experts::set_availability))
+        .route("/me/availability/blocked", post(handlers::experts::block_dates))
+        .route(
+            "/me/availability/blocked/{id}",
+            delete(handlers::experts::unblock_dates),
+        )
+        .route("/me/portfolio", post(handlers::experts::create_portfolio_item))
+        .route("/me/portfolio/order", put(handlers::experts::reorder_portfolio))
+        .route(
+            "/me/portfolio/{item_id}",
+            put(handlers::experts::update_portfolio_item),
+        )
+        .route(
+            "/me/portfolio/{item_id}",
+            delete(handlers::experts::delete_portfolio_item),
+        )
+        .route("/me/calendar/feed", get(handlers::calendar::get_feed))
+        .route("/me/calendar/feed/rotate", post(handlers::calendar::rotate_feed))
+        .route("/me/calendar/imports", get(handlers::calendar::list_imports))
+        .route("/me/calendar/imports", post(handlers::calendar::subscribe))
+        .route("/me/calendar/imports/upload", post(handlers::calendar::upload))
+        .route(
+            "/me/calendar/imports/{id}/sync",
+            post(handlers::calendar::sync_import),
+        )
+        .route(
+            "/me/calendar/imports/{id}",
+            delete(handlers::calendar::delete_import),
+        )
+        .route_layer(from_fn_with_state(
+            state.clone(),
+            crate::middleware::auth::auth_middleware,
+        ));
+
     Router::new()
         .route("/", get(handlers::experts::list_experts))
-        .route("/", post(handlers::experts::create_profile))
         .route("/{id}", get(handlers::experts::get_expert))
-        .route("/{id}", put(handlers::experts::update_profile))
         .route(
             "/{id}/services",
             get(handlers::experts::get_expert_services),
         )
         .route("/{id}/reviews", get(handlers::experts::get_expert_reviews))
         .route("/{id}/portfolio", get(handlers::experts::get_portfolio))
+        .route("/{id}/slots", get(handlers::experts::get_slots))
+        .route(
+            "/{id}/availability",
+            get(handlers::experts::get_availability),
+        )
         .route("/featured", get(handlers::experts::get_featured_experts))
+        .route("/calendar/{token}", get(handlers::calendar::export_feed))
+        .merge(authenticated)
 }
 
-fn service_routes() -> Router<AppState> {
-    Router::new()
-        .route("/", get(handlers::services::list_services))
+fn service_routes(state: &AppState) -> Router<AppState> {
+    let authenticated = Router::new()
         .route("/", post(handlers::services::create_service))
-        .route("/{id}", get(handlers::services::get_service))
         .route("/{id}", put(handlers::services::update_service))
         .route("/{id}", delete(handlers::services::delete_service))
+        .route("/{id}/addons", post(handlers::services::create_addon))
+        .route("/{id}/addons/{addon_id}", put(handlers::services::update_addon))
+        .route("/{id}/addons/{addon_id}", delete(handlers::services::delete_addon))
+        .route_layer(from_fn_with_state(
+            state.clone(),
+            crate::middleware::auth::auth_middleware,
+        ));
+
+    Router::new()
+        .route("/", get(handlers::services::list_services))
+        .route("/{id}", get(handlers::services::get_service))
         .route("/{id}/packages", get(handlers::services::get_packages))
+        .route("/{id}/addons", get(handlers::services::get_addons))
         .route("/featured", get(handlers::services::get_featured_services))
+        .merge(authenticated)
 }
 
 fn category_routes() -> Router<AppState> {
@@ -119,16 +320,104 @@ fn category_routes() -> Router<AppState> {
         )
 }
 
-fn project_routes() -> Router<AppState> {
+fn project_routes(state: &AppState) -> Router<AppState> {
     Router::new()
         .route("/", get(handlers::projects::list_projects))
         .route("/", post(handlers::projects::create_project))
         .route("/{id}", get(handlers::projects::get_project))
         .route("/{id}/status", put(handlers::projects::update_status))
+        .route("/{id}/transitions", get(handlers::projects::get_transitions))
+        .route("/{id}/timeline", get(handlers::projects::get_timeline))
+        .route("/{id}/addons", get(handlers::projects::list_addons))
+        .route("/{id}/milestones", get(handlers::projects::list_milestones))
+        .route("/{id}/milestones", post(handlers::projects::create_milestone))
+        .route(
+            "/{id}/milestones/{milestone_id}/status",
+            put(handlers::projects::update_milestone_status),
+        )
+        .route("/{id}/deliverables", get(handlers::deliverables::list_deliverables))
+        .route(
+            "/{id}/deliverables",
+            post(handlers::deliverables::upload_deliverable)
+                .layer(DefaultBodyLimit::max(handlers::deliverables::MULTIPART_UPLOAD_LIMIT)),
+        )
+        .route(
+            "/{id}/deliverables/upload-url",
+            post(handlers::deliverables::create_upload_url),
+        )
+        .route(
+            "/{id}/deliverables/{deliverable_id}/confirm",
+            post(handlers::deliverables::confirm_upload),
+        )
+        .route(
+            "/{id}/deliverables/{deliverable_id}/download",
+            get(handlers::deliverables::download_deliverable),
+        )
+        .route(
+            "/{id}/deliverables/{deliverable_id}/final",
+            post(handlers::deliverables::mark_final),
+        )
+        // Requirements form
+        .route("/{id}/requirements", get(handlers::requirements::get_requirements))
+        .route("/{id}/requirements", put(handlers::requirements::submit_requirements))
+        .route(
+            "/{id}/requirements/{field_id}/file",
+            post(handlers::requirements::upload_requirement_file)
+                .layer(DefaultBodyLimit::max(handlers::deliverables::MULTIPART_UPLOAD_LIMIT)),
+        )
+        .route(
+            "/{id}/requirements/{field_id}/file",
+            get(handlers::requirements::download_requirement_file),
+        )
+        .route("/{id}/deliveries", get(handlers::deliverables::list_deliveries))
+        .route("/{id}/revisions", get(handlers::deliverables::list_revision_requests))
         .route("/{id}/deliver", post(handlers::projects::deliver_project))
         .route("/{id}/revision", post(handlers::projects::request_revision))
         .route("/{id}/complete", post(handlers::projects::complete_project))
         .route("/{id}/cancel", post(handlers::projects::cancel_project))
+        .route("/{id}/cancellation", get(handlers::cancellations::get_cancellation))
+        .route(
+            "/{id}/cancellation/preview",
+            get(handlers::cancellations::preview_cancellation),
+        )
+        // Contracts
+        .route("/{id}/contracts", get(handlers::contracts::list_contracts))
+        .route("/{id}/contracts", post(handlers::contracts::create_contract))
+        .route(
+            "/{id}/contracts/{contract_id}/document",
+            get(handlers::contracts::download_contract),
+        )
+        .route(
+            "/{id}/contracts/{contract_id}/sign",
+            post(handlers::contracts::sign_contract),
+        )
+        // Change requests
+        .route("/{id}/change-requests", get(handlers::change_requests::list_change_requests))
+        .route("/{id}/change-requests", post(handlers::change_requests::create_change_request))
+        .route(
+            "/{id}/change-requests/{change_id}/accept",
+            post(handlers::change_requests::accept_change_request),
+        )
+        .route(
+            "/{id}/change-requests/{change_id}/reject",
+            post(handlers::change_requests::reject_change_request),
+        )
+        .route(
+            "/{id}/change-requests/{change_id}/withdraw",
+            post(handlers::change_requests::withdraw_change_request),
+        )
+        .route(
+            "/{id}/change-requests/{change_id}/checkout",
+            post(handlers::change_requests::checkout_change_request),
+        )
+        // Tips & bonuses
+        .route("/{id}/tips", get(handlers::tips::list_tips))
+        .route("/{id}/tips", post(handlers::tips::create_tip))
+        .route("/{id}/tips/options", get(handlers::tips::get_tip_options))
+        .route_layer(from_fn_with_state(
+            state.clone(),
+            crate::middleware::auth::auth_middleware,
+        ))
 }
 
 fn message_routes() -> Router<AppState> {
@@ -179,7 +468,7 @@ fn search_routes() -> Router<AppState> {
         .route("/suggestions", get(handlers::search::get_suggestions))
 }
 
-fn admin_routes() -> Router<AppState> {
+fn admin_routes(state: &AppState) -> Router<AppState> {
     Router::new()
         .route("/stats", get(handlers::admin::get_stats))
         .route("/users", get(handlers::admin::list_all_users))
@@ -214,20 +503,59 @@ fn admin_routes() -> Router<AppState> {
         )
         // Analytics
         .route("/analytics", get(handlers::admin::get_analytics))
+        // Client billing terms
+        .route(
+            "/clients/{id}/billing",
+            put(handlers::admin::update_client_billing_terms),
+        )
+        .route(
+            "/clients/{id}/billing/unblock",
+            post(handlers::admin::unblock_client_billing),
+        )
+        // VAT reporting
+        .route("/tax-reports", get(handlers::admin::get_tax_report))
+        .route("/tax-reports/export", get(handlers::admin::export_tax_report))
+        // Contract templates
+        .route("/contract-templates", get(handlers::contracts::list_templates))
+        .route("/contract-templates", post(handlers::contracts::create_template))
+        .route("/contract-templates/{id}", put(handlers::contracts::update_template))
+        .route_layer(from_fn(crate::middleware::auth::admin_middleware))
+        .route_layer(from_fn_with_state(
+            state.clone(),
+            crate::middleware::auth::auth_middleware,
+        ))
 }
 
-fn client_routes() -> Router<AppState> {
+fn client_routes(state: &AppState) -> Router<AppState> {
     Router::new()
         .route("/profile", get(handlers::clients::get_my_profile))
         .route("/profile", post(handlers::clients::create_profile))
         .route("/profile", put(handlers::clients::update_profile))
+        .route("/billing", get(handlers::clients::get_billing_status))
+        .route_layer(from_fn_with_state(
+            state.clone(),
+            crate::middleware::auth::auth_middleware,
+        ))
 }
 
-fn posting_routes() -> Router<AppState> {
-    Router::new()
-        .route("/", get(handlers::clients::list_project_postings))
+fn posting_routes(state: &AppState) -> Router<AppState> {
+    let authenticated = Router::new()
+        .route("/mine", get(handlers::clients::list_my_project_postings))
+        .route("/invitations", get(handlers::clients::list_my_invitations))
+        .route(
+            "/invitations/{invitation_id}/accept",
+            post(handlers::clients::accept_invitation),
+        )
+        .route(
+            "/invitations/{invitation_id}/decline",
+            post(handlers::clients::decline_invitation),
+        )
+        .route("/{id}/invitations", get(handlers::clients::list_posting_invitations))
+        .route("/{id}/invitations", post(handlers::clients::invite_experts))
+        .route("/{id}/extend", post(handlers::clients::extend_project_posting))
+        .route("/{id}/repost", post(handlers::clients::repost_project_posting))
+        .route("/{id}/cancel", post(handlers::clients::cancel_project_posting))
         .route("/", post(handlers::clients::create_project_posting))
-        .route("/{id}", get(handlers::clients::get_project_posting))
         .route("/{id}", put(handlers::clients::update_project_posting))
         .route("/{id}", delete(handlers::clients::delete_project_posting))
         .route(
@@ -235,21 +563,64 @@ fn posting_routes() -> Router<AppState> {
             get(handlers::clients::list_proposals_for_posting),
         )
         .route("/{id}/proposals", post(handlers::clients::create_proposal))
+        .route(
+            "/{id}/proposals/compare",
+            get(handlers::clients::compare_proposals),
+        )
+        .route("/proposals/{proposal_id}", get(handlers::clients::get_proposal))
+        .route("/proposals/{proposal_id}", put(handlers::clients::update_proposal))
+        .route(
+            "/proposals/{proposal_id}/view",
+            post(handlers::clients::mark_proposal_viewed),
+        )
+        .route(
+            "/proposals/{proposal_id}/shortlist",
+            post(handlers::clients::shortlist_proposal),
+        )
+        .route(
+            "/proposals/{proposal_id}/reject",
+            post(handlers::clients::reject_proposal),
+        )
+        .route(
+            "/proposals/{proposal_id}/withdraw",
+            post(handlers::clients::withdraw_proposal),
+        )
         .route(
             "/proposals/{proposal_id}/accept",
             post(handlers::clients::accept_proposal),
         )
+        .route_layer(from_fn_with_state(
+            state.clone(),
+            crate::middleware::auth::auth_middleware,
+        ));
+
+    // Invite-only postings are visible to their client and the invited experts
+    let optionally_authenticated = Router::new()
+        .route("/{id}", get(handlers::clients::get_project_posting))
+        .route_layer(from_fn_with_state(
+            state.clone(),
+            crate::middleware::auth::optional_auth_middleware,
+        ));
+
+    Router::new()
+        .route("/", get(handlers::clients::list_project_postings))
+        .merge(optionally_authenticated)
+        .merge(authenticated)
 }
 
-fn booking_routes() -> Router<AppState> {
+fn booking_routes(state: &AppState) -> Router<AppState> {
     Router::new()
         .route("/", get(handlers::clients::list_my_bookings))
         .route("/", post(handlers::clients::create_booking_request))
         .route("/{id}/respond", post(handlers::clients::respond_to_booking))
+        .route_layer(from_fn_with_state(
+            state.clone(),
+            crate::middleware::auth::auth_middleware,
+        ))
 }
 
-fn payment_routes() -> Router<AppState> {
-    Router::new()
+fn payment_routes(state: &AppState) -> Router<AppState> {
+    let authenticated = Router::new()
         .route("/", get(handlers::payments::get_payment_history))
         .route("/", post(handlers::payments::create_payment))
         .route("/{id}", get(handlers::payments::get_payment))
@@ -268,7 +639,6 @@ fn payment_routes() -> Router<AppState> {
             "/checkout",
             post(handlers::payments::create_checkout_session),
         )
-        .route("/webhook", post(handlers::payments::stripe_webhook))
         // Stripe Connect routes
         .route(
             "/connect/status",
@@ -282,4 +652,13 @@ fn payment_routes() -> Router<AppState> {
             "/connect/refresh",
             post(handlers::payments::refresh_connect_onboarding),
         )
+        .route_layer(from_fn_with_state(
+            state.clone(),
+            crate::middleware::auth::auth_middleware,
+        ));
+
+    // Stripe signs webhooks instead of sending a bearer token
+    Router::new()
+        .route("/webhook", post(handlers::payments::stripe_webhook))
+        .merge(authenticated)
 }
@@ -0,0 +1,518 @@
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::models::{
+    AddAgencyMemberRequest, Agency, AgencyEarnings, AgencyMember, AgencyMembership, AgencyRevenueSplit, AgencyRole,
+    Conversation, CreateAgencyRequest, Project, ProjectActor, ProjectEventType, UpdateAgencyMemberRequest,
+    UpdateAgencyRequest, revenue_split,
+};
+use crate::services::{NewProjectEvent, NotificationService, ProjectEventService, ServiceService};
+
+/// Error of an agency operation
+#[derive(Debug, thiserror::Error)]
+pub enum AgencyError {
+    #[error("Agency not found")]
+    NotFound,
+
+    #[error("{0}")]
+    Conflict(String),
+
+    #[error("{0}")]
+    Invalid(String),
+
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+const MEMBER_COLUMNS: &str = r#"
+    m.id, m.agency_id, m.user_id, m.role, m.revenue_share_percent,
+    u.first_name, u.last_name, u.avatar_url, m.joined_at
+"#;
+
+pub struct AgencyService;
+
+impl AgencyService {
+    pub async fn get(db: &Database, id: Uuid) -> Result<Option<Agency>, sqlx::Error> {
+        sqlx::query_as::<_, Agency>("SELECT * FROM agencies WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&db.pool)
+            .await
+    }
+
+    /// Agency the user belongs to, with their role
+    pub async fn membership(db: &Database, user_id: Uuid) -> Result<Option<AgencyMembership>, sqlx::Error> {
+        let role: Option<(Uuid, AgencyRole)> =
+            sqlx::query_as("SELECT agency_id, role FROM agency_members WHERE user_id = $1")
+                .bind(user_id)
+                .fetch_optional(&db.pool)
+                .await?;
+
+        let Some((agency_id, role)) = role else {
+            return Ok(None);
+        };
+
+        Ok(Self::get(db, agency_id).await?.map(|agency| AgencyMembership { agency, role }))
+    }
+
+    /// Role of the user in the agency, if they are a member
+    pub async fn role(db: &Database, agency_id: Uuid, user_id: Uuid) -> Result<Option<AgencyRole>, sqlx::Error> {
+        sqlx::query_scalar("SELECT role FROM agency_members WHERE agency_id = $1 AND user_id = $2")
+            .bind(agency_id)
+            .bind(user_id)
+            .fetch_optional(&db.pool)
+            .await
+    }
+
+    /// Create an agency; the creating expert becomes its owner
+    pub async fn create(db: &Database, user_id: Uuid, req: &CreateAgencyRequest) -> Result<Agency, AgencyError> {
+        let mut tx = db.pool.begin().await?;
+
+        let is_member: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM agency_members WHERE user_id = $1)")
+            .bind(user_id)
+            .fetch_one(&mut *tx)
+            .await?;
+        if is_member {
+            return Err(AgencyError::Conflict("You are already a member of an agency".to_string()));
+        }
+
+        let mut slug = ServiceService::generate_slug(&req.name);
+        let taken: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM agencies WHERE slug = $1)")
+            .bind(&slug)
+            .fetch_one(&mut *tx)
+            .await?;
+        if taken || slug.is_empty() {
+            let suffix = &Uuid::new_v4().simple().to_string()[..6];
+            slug = if slug.is_empty() { suffix.to_string() } else { format!("{}-{}", slug, suffix) };
+        }
+
+        let agency = sqlx::query_as::<_, Agency>(
+            r#"
+            INSERT INTO agencies (name, slug, description, logo_url, website, default_member_share_percent, created_by)
+            VALUES ($1, $2, $3, $4, $5, COALESCE($6, 70), $7)
+            RETURNING *
+            "#,
+        )
+        .bind(req.name.trim())
+        .bind(&slug)
+        .bind(&req.description)
+        .bind(&req.logo_url)
+        .bind(&req.website)
+        .bind(req.default_member_share_percent)
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query("INSERT INTO agency_members (agency_id, user_id, role) VALUES ($1, $2, 'owner')")
+            .bind(agency.id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(agency)
+    }
+
+    pub async fn update(db: &Database, id: Uuid, req: &UpdateAgencyRequest) -> Result<Option<Agency>, sqlx::Error> {
+        sqlx::query_as::<_, Agency>(
+            r#"
+            UPDATE agencies SET
+                name = COALESCE($2, name),
+                description = COALESCE($3, description),
+                logo_url = COALESCE($4, logo_url),
+                website = COALESCE($5, website),
+                default_member_share_percent = COALESCE($6, default_member_share_percent),
+                updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(req.name.as_deref().map(str::trim))
+        .bind(&req.description)
+        .bind(&req.logo_url)
+        .bind(&req.website)
+        .bind(req.default_member_share_percent)
+        .fetch_optional(&db.pool)
+        .await
+    }
+
+    pub async fn list_members(db: &Database, agency_id: Uuid) -> Result<Vec<AgencyMember>, sqlx::Error> {
+        sqlx::query_as::<_, AgencyMember>(&format!(
+            r#"
+            SELECT {MEMBER_COLUMNS}
+            FROM agency_members m
+            JOIN users u ON u.id = m.user_id
+            WHERE m.agency_id = $1
+            ORDER BY m.role, m.joined_at
+            "#
+        ))
+        .bind(agency_id)
+        .fetch_all(&db.pool)
+        .await
+    }
+
+    async fn get_member(conn: &mut PgConnection, agency_id: Uuid, user_id: Uuid) -> Result<AgencyMember, AgencyError> {
+        sqlx::query_as::<_, AgencyMember>(&format!(
+            r#"
+            SELECT {MEMBER_COLUMNS}
+            FROM agency_members m
+            JOIN users u ON u.id = m.user_id
+            WHERE m.agency_id = $1 AND m.user_id = $2
+            "#
+        ))
+        .bind(agency_id)
+        .bind(user_id)
+        .fetch_optional(conn)
+        .await?
+        .ok_or_else(|| AgencyError::Invalid("User is not a member of this agency".to_string()))
+    }
+
+    /// Add an expert to the agency and notify them
+    pub async fn add_member(
+        db: &Database,
+        agency: &Agency,
+        invited_by: Uuid,
+        req: &AddAgencyMemberRequest,
+    ) -> Result<AgencyMember, AgencyError> {
+        let role = req.role.unwrap_or(AgencyRole::Member);
+        if role == AgencyRole::Owner {
+            return Err(AgencyError::Invalid("An agency has exactly one owner".to_string()));
+        }
+
+        let mut tx = db.pool.begin().await?;
+
+        let is_expert: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM expert_profiles WHERE user_id = $1)")
+            .bind(req.user_id)
+            .fetch_one(&mut *tx)
+            .await?;
+        if !is_expert {
+            return Err(AgencyError::Invalid("Only experts can join an agency".to_string()));
+        }
+
+        let inserted = sqlx::query(
+            r#"
+            INSERT INTO agency_members (agency_id, user_id, role, revenue_share_percent, invited_by)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (user_id) DO NOTHING
+            "#,
+        )
+        .bind(agency.id)
+        .bind(req.user_id)
+        .bind(role)
+        .bind(req.revenue_share_percent)
+        .bind(invited_by)
+        .execute(&mut *tx)
+        .await?;
+        if inserted.rows_affected() == 0 {
+            return Err(AgencyError::Conflict("The expert is already a member of an agency".to_string()));
+        }
+
+        NotificationService::create(
+            &mut *tx,
+            req.user_id,
+            "agency_member_added",
+            "Einer Agentur hinzugefügt",
+            &format!("Sie wurden der Agentur \"{}\" hinzugefügt.", agency.name),
+            Some(serde_json::json!({ "agencyId": agency.id })),
+        )
+        .await?;
+
+        let member = Self::get_member(&mut tx, agency.id, req.user_id).await?;
+        tx.commit().await?;
+
+        Ok(member)
+    }
+
+    /// Change a member's role or revenue share; the owner keeps their role
+    pub async fn update_member(
+        db: &Database,
+        agency_id: Uuid,
+        user_id: Uuid,
+        req: &UpdateAgencyMemberRequest,
+    ) -> Result<AgencyMember, AgencyError> {
+        let mut tx = db.pool.begin().await?;
+        let member = Self::get_member(&mut tx, agency_id, user_id).await?;
+
+        if let Some(role) = req.role
+            && role != member.role
+            && (role == AgencyRole::Owner || member.role == AgencyRole::Owner)
+        {
+            return Err(AgencyError::Invalid("The owner of an agency cannot be changed".to_string()));
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE agency_members SET
+                role = COALESCE($3, role),
+                revenue_share_percent = CASE WHEN $5 THEN NULL ELSE COALESCE($4, revenue_share_percent) END,
+                updated_at = NOW()
+            WHERE agency_id = $1 AND user_id = $2
+            "#,
+        )
+        .bind(agency_id)
+        .bind(user_id)
+        .bind(req.role)
+        .bind(req.revenue_share_percent)
+        .bind(req.use_default_share)
+        .execute(&mut *tx)
+        .await?;
+
+        let member = Self::get_member(&mut tx, agency_id, user_id).await?;
+        tx.commit().await?;
+
+        Ok(member)
+    }
+
+    /// Remove a member; their projects and services stay with the agency
+    pub async fn remove_member(db: &Database, agency_id: Uuid, user_id: Uuid) -> Result<(), AgencyError> {
+        let mut tx = db.pool.begin().await?;
+        let member = Self::get_member(&mut tx, agency_id, user_id).await?;
+        if member.role == AgencyRole::Owner {
+            return Err(AgencyError::Invalid("The owner cannot leave the agency".to_string()));
+        }
+
+        sqlx::query("DELETE FROM agency_members WHERE agency_id = $1 AND user_id = $2")
+            .bind(agency_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Projects ordered from the agency's services, newest first
+    pub async fn list_projects(
+        db: &Database,
+        agency_id: Uuid,
+        page: u32,
+        per_page: u32,
+    ) -> Result<(Vec<Project>, i64), sqlx::Error> {
+        let offset = (page.saturating_sub(1)) * per_page;
+
+        let projects = sqlx::query_as::<_, Project>(
+            "SELECT * FROM projects WHERE agency_id = $1 ORDER BY created_at DESC LIMIT $2 OFFSET $3",
+        )
+        .bind(agency_id)
+        .bind(per_page as i64)
+        .bind(offset as i64)
+        .fetch_all(&db.pool)
+        .await?;
+
+        let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM projects WHERE agency_id = $1")
+            .bind(agency_id)
+            .fetch_one(&db.pool)
+            .await?;
+
+        Ok((projects, total))
+    }
+
+    /// Whether the user manages the agency a project belongs to
+    pub async fn manages_project(db: &Database, project: &Project, user_id: Uuid) -> Result<bool, sqlx::Error> {
+        let Some(agency_id) = project.agency_id else {
+            return Ok(false);
+        };
+
+        Ok(Self::role(db, agency_id, user_id).await?.is_some_and(AgencyRole::can_manage))
+    }
+
+    /// Hand an agency project over to another member; payments not yet released move with it
+    pub async fn assign_project(
+        db: &Database,
+        agency_id: Uuid,
+        project_id: Uuid,
+        member_id: Uuid,
+        assigned_by: Uuid,
+    ) -> Result<Project, AgencyError> {
+        let mut tx = db.pool.begin().await?;
+
+        let project = sqlx::query_as::<_, Project>("SELECT * FROM projects WHERE id = $1 AND agency_id = $2 FOR UPDATE")
+            .bind(project_id)
+            .bind(agency_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(AgencyError::NotFound)?;
+
+        if project.status.is_terminal() {
+            return Err(AgencyError::Conflict("Project is already closed".to_string()));
+        }
+        if project.expert_id == member_id {
+            return Ok(project);
+        }
+        Self::get_member(&mut tx, agency_id, member_id).await?;
+
+        let updated = sqlx::query_as::<_, Project>(
+            "UPDATE projects SET expert_id = $2, updated_at = NOW() WHERE id = $1 RETURNING *",
+        )
+        .bind(project_id)
+        .bind(member_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            UPDATE payments SET payee_id = $3, updated_at = NOW()
+            WHERE project_id = $1 AND payee_id = $2 AND released_at IS NULL
+            "#,
+        )
+        .bind(project_id)
+        .bind(project.expert_id)
+        .bind(member_id)
+        .execute(&mut *tx)
+        .await?;
+
+        ProjectEventService::record(
+            &mut *tx,
+            NewProjectEvent::new(project_id, ProjectEventType::ProjectAssigned, ProjectActor::Expert, Some(assigned_by))
+                .data(serde_json::json!({
+                    "agencyId": agency_id,
+                    "previousExpertId": project.expert_id,
+                    "expertId": member_id,
+                })),
+        )
+        .await?;
+
+        NotificationService::create(
+            &mut *tx,
+            member_id,
+            "project_assigned",
+            "Projekt zugewiesen",
+            &format!("Ihnen wurde das Projekt \"{}\" zugewiesen.", project.title),
+            Some(serde_json::json!({ "projectId": project_id, "agencyId": agency_id })),
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(updated)
+    }
+
+    /// Split the released payments of an agency project between the agency and the member
+    /// they are paid to. Tips go to the member in full.
+    pub async fn record_splits(conn: &mut PgConnection, project_id: Uuid) -> Result<(), sqlx::Error> {
+        let payments: Vec<(Uuid, Uuid, Uuid, i32, i16)> = sqlx::query_as(
+            r#"
+            SELECT p.id, pr.agency_id, p.payee_id, p.net_amount,
+                   COALESCE(m.revenue_share_percent, a.default_member_share_percent)
+            FROM payments p
+            JOIN projects pr ON pr.id = p.project_id
+            JOIN agencies a ON a.id = pr.agency_id
+            LEFT JOIN agency_members m ON m.agency_id = a.id AND m.user_id = p.payee_id
+            WHERE p.project_id = $1 AND p.released_at IS NOT NULL
+              AND p.status IN ('succeeded', 'partially_refunded')
+              AND COALESCE(p.metadata->>'kind', '') <> 'tip'
+              AND NOT EXISTS (SELECT 1 FROM agency_revenue_splits s WHERE s.payment_id = p.id)
+            "#,
+        )
+        .bind(project_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        for (payment_id, agency_id, member_id, net_amount, percent) in payments {
+            let (member_amount, agency_amount) = revenue_split(net_amount, percent);
+            sqlx::query(
+                r#"
+                INSERT INTO agency_revenue_splits (
+                    agency_id, project_id, payment_id, member_id, net_amount,
+                    member_share_percent, member_amount, agency_amount
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                ON CONFLICT (payment_id) DO NOTHING
+                "#,
+            )
+            .bind(agency_id)
+            .bind(project_id)
+            .bind(payment_id)
+            .bind(member_id)
+            .bind(net_amount)
+            .bind(percent)
+            .bind(member_amount)
+            .bind(agency_amount)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Revenue splits of the agency with totals
+    pub async fn earnings(db: &Database, agency_id: Uuid) -> Result<AgencyEarnings, sqlx::Error> {
+        let splits = sqlx::query_as::<_, AgencyRevenueSplit>(
+            "SELECT * FROM agency_revenue_splits WHERE agency_id = $1 ORDER BY created_at DESC",
+        )
+        .bind(agency_id)
+        .fetch_all(&db.pool)
+        .await?;
+
+        Ok(AgencyEarnings {
+            net_amount: splits.iter().map(|s| s.net_amount as i64).sum(),
+            member_amount: splits.iter().map(|s| s.member_amount as i64).sum(),
+            agency_amount: splits.iter().map(|s| s.agency_amount as i64).sum(),
+            splits,
+        })
+    }
+
+    /// Conversations about the agency's services and projects, latest first
+    pub async fn inbox(
+        db: &Database,
+        agency_id: Uuid,
+        page: u32,
+        per_page: u32,
+    ) -> Result<(Vec<Conversation>, i64), sqlx::Error> {
+        let offset = (page.saturating_sub(1)) * per_page;
+
+        let conversations = sqlx::query_as::<_, Conversation>(
+            r#"
+            SELECT * FROM conversations
+            WHERE agency_id = $1
+            ORDER BY last_message_at DESC NULLS LAST
+            LIMIT $2 OFFSET $3
+            "#,
+        )
+        .bind(agency_id)
+        .bind(per_page as i64)
+        .bind(offset as i64)
+        .fetch_all(&db.pool)
+        .await?;
+
+        let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM conversations WHERE agency_id = $1")
+            .bind(agency_id)
+            .fetch_one(&db.pool)
+            .await?;
+
+        Ok((conversations, total))
+    }
+
+    /// Conversation of the shared inbox with the participant the agency replies for
+    pub async fn inbox_conversation(
+        db: &Database,
+        agency_id: Uuid,
+        conversation_id: Uuid,
+    ) -> Result<Option<(Conversation, Option<Uuid>)>, sqlx::Error> {
+        let conversation = sqlx::query_as::<_, Conversation>(
+            "SELECT * FROM conversations WHERE id = $1 AND agency_id = $2",
+        )
+        .bind(conversation_id)
+        .bind(agency_id)
+        .fetch_optional(&db.pool)
+        .await?;
+
+        let Some(conversation) = conversation else {
+            return Ok(None);
+        };
+
+        let participant: Option<Uuid> = sqlx::query_scalar(
+            "SELECT user_id FROM agency_members WHERE agency_id = $1 AND user_id IN ($2, $3) LIMIT 1",
+        )
+        .bind(agency_id)
+        .bind(conversation.participant_one_id)
+        .bind(conversation.participant_two_id)
+        .fetch_optional(&db.pool)
+        .await?;
+
+        Ok(Some((conversation, participant)))
+    }
+}
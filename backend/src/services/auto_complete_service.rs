@@ -0,0 +1,168 @@
+//! Auto-completion of delivered projects
+//! Clients are reminded before the approval period ends; afterwards the project
+//! is completed by the system so the expert's payment is released.
+
+use chrono::NaiveDate;
+
+use crate::config::AutoCompleteSettings;
+use crate::db::Database;
+use crate::models::{AwaitingApproval, ProjectActor, ProjectEventType, ProjectStatus};
+use crate::services::{NewProjectEvent, NotificationService, ProjectEventService, ProjectService, TransitionError};
+use crate::utils::add_business_days;
+
+pub struct AutoCompleteService;
+
+impl AutoCompleteService {
+    /// Date on which a delivery is completed automatically
+    pub fn deadline(delivered_on: NaiveDate, settings: &AutoCompleteSettings) -> NaiveDate {
+        add_business_days(delivered_on, settings.business_days)
+    }
+
+    /// Number of reminders that should have been sent with `remaining` business days left
+    pub fn reminder_level(remaining: i64, settings: &AutoCompleteSettings) -> i16 {
+        settings
+            .reminder_days
+            .iter()
+            .filter(|&&days| remaining <= days)
+            .count() as i16
+    }
+
+    /// Timeline reason for a system completion
+    pub fn completion_reason(settings: &AutoCompleteSettings) -> String {
+        format!(
+            "Automatisch abgeschlossen: keine Rückmeldung innerhalb von {} Werktagen nach der Lieferung",
+            settings.business_days
+        )
+    }
+
+    /// Delivered projects awaiting the client's approval
+    pub async fn get_awaiting_approval(db: &Database) -> Result<Vec<AwaitingApproval>, sqlx::Error> {
+        sqlx::query_as::<_, AwaitingApproval>(
+            r#"
+            SELECT p.id, p.title, p.client_id, p.expert_id,
+                   u.email AS client_email, u.first_name AS client_first_name,
+                   u.preferred_language AS client_language,
+                   p.delivered_at, p.auto_complete_reminders
+            FROM projects p
+            JOIN users u ON u.id = p.client_id
+            WHERE p.status = 'delivered' AND p.delivered_at IS NOT NULL
+            ORDER BY p.delivered_at
+            "#,
+        )
+        .fetch_all(&db.pool)
+        .await
+    }
+
+    /// Remind the client of a pending delivery. The reminder is recorded together with its
+    /// notification; returns false if this reminder level was already sent.
+    pub async fn remind(
+        db: &Database,
+        project: &AwaitingApproval,
+        level: i16,
+        deadline: NaiveDate,
+    ) -> Result<bool, sqlx::Error> {
+        let mut tx = db.pool.begin().await?;
+
+        let updated = sqlx::query(
+            r#"
+            UPDATE projects SET auto_complete_reminders = $2
+            WHERE id = $1 AND status = 'delivered' AND auto_complete_reminders < $2
+            "#,
+        )
+        .bind(project.id)
+        .bind(level)
+        .execute(&mut *tx)
+        .await?;
+
+        if updated.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        ProjectEventService::record(
+            &mut *tx,
+            NewProjectEvent::system(project.id, ProjectEventType::CompletionReminderSent)
+                .data(serde_json::json!({ "reminder": level, "autoCompleteOn": deadline })),
+        )
+        .await?;
+
+        NotificationService::create(
+            &mut *tx,
+            project.client_id,
+            "delivery_approval_reminder",
+            "Lieferung wartet auf Abnahme",
+            &format!(
+                "Bitte prüfen Sie die Lieferung für \"{}\". Ohne Rückmeldung wird das Projekt am {} automatisch abgeschlossen.",
+                project.title,
+                deadline.format("%d.%m.%Y")
+            ),
+            Some(serde_json::json!({ "projectId": project.id, "autoCompleteOn": deadline })),
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(true)
+    }
+
+    /// Complete a delivered project whose approval period ran out and tell the expert
+    pub async fn complete(db: &Database, project: &AwaitingApproval, reason: &str) -> Result<(), TransitionError> {
+        let mut tx = db.pool.begin().await?;
+
+        ProjectService::transition_tx(
+            &mut tx,
+            project.id,
+            ProjectStatus::Completed,
+            ProjectActor::System,
+            None,
+            Some(reason),
+        )
+        .await?;
+
+        NotificationService::create(
+            &mut *tx,
+            project.expert_id,
+            "project_completed",
+            "Projekt automatisch abgeschlossen",
+            &format!(
+                "Das Projekt \"{}\" wurde automatisch abgeschlossen. Ihre Zahlung wird freigegeben.",
+                project.title
+            ),
+            Some(serde_json::json!({ "projectId": project.id })),
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> AutoCompleteSettings {
+        AutoCompleteSettings {
+            enabled: true,
+            interval_minutes: 60,
+            business_days: 7,
+            reminder_days: vec![3, 1],
+        }
+    }
+
+    #[test]
+    fn test_deadline_counts_business_days() {
+        // Friday delivery + 7 business days = Tuesday week after next
+        let delivered = NaiveDate::from_ymd_opt(2024, 12, 6).unwrap();
+        assert_eq!(AutoCompleteService::deadline(delivered, &settings()), NaiveDate::from_ymd_opt(2024, 12, 17).unwrap());
+    }
+
+    #[test]
+    fn test_reminder_level() {
+        let settings = settings();
+        assert_eq!(AutoCompleteService::reminder_level(5, &settings), 0);
+        assert_eq!(AutoCompleteService::reminder_level(3, &settings), 1);
+        assert_eq!(AutoCompleteService::reminder_level(2, &settings), 1);
+        assert_eq!(AutoCompleteService::reminder_level(1, &settings), 2);
+    }
+}
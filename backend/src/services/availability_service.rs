@@ -1,7 +1,7 @@
-use chrono::NaiveTime;
+use chrono::NaiveDate;
 use uuid::Uuid;
 use crate::db::Database;
-use crate::models::{AvailabilitySlot, BlockedDate, SetAvailabilityRequest, BlockDatesRequest, ExpertAvailability};
+use crate::models::{AvailabilitySlot, BlockedDate, BlockedTime, WeeklySlot, BlockDatesRequest, ExpertAvailability};
 
 pub struct AvailabilityService;
 
@@ -22,6 +22,17 @@ impl AvailabilityService {
         .fetch_all(&db.pool)
         .await?;
 
+        let blocked_times: Vec<BlockedTime> = sqlx::query_as(
+            r#"
+            SELECT * FROM blocked_times
+            WHERE expert_id = $1 AND ends_at > NOW() AND starts_at < NOW() + INTERVAL '90 days'
+            ORDER BY starts_at
+            "#,
+        )
+        .bind(expert_id)
+        .fetch_all(&db.pool)
+        .await?;
+
         // Get expert's timezone and hours
         let (timezone, hours): (String, i16) = sqlx::query_as(
             "SELECT timezone, available_hours_per_week FROM expert_profiles WHERE id = $1"
@@ -33,6 +44,7 @@ impl AvailabilityService {
         Ok(ExpertAvailability {
             weekly_slots,
             blocked_dates,
+            blocked_times,
             timezone,
             available_hours_per_week: hours,
         })
@@ -42,22 +54,19 @@ impl AvailabilityService {
     pub async fn set_availability(
         db: &Database,
         expert_id: Uuid,
-        req: SetAvailabilityRequest,
+        slots: &[WeeklySlot],
     ) -> Result<Vec<AvailabilitySlot>, sqlx::Error> {
+        let mut tx = db.pool.begin().await?;
+
         // Delete existing slots
         sqlx::query("DELETE FROM availability_slots WHERE expert_id = $1")
             .bind(expert_id)
-            .execute(&db.pool)
+            .execute(&mut *tx)
             .await?;
 
         // Insert new slots
-        let mut slots = Vec::new();
-        for slot_input in req.slots {
-            let start_time = NaiveTime::parse_from_str(&slot_input.start_time, "%H:%M")
-                .unwrap_or_else(|_| NaiveTime::from_hms_opt(9, 0, 0).unwrap());
-            let end_time = NaiveTime::parse_from_str(&slot_input.end_time, "%H:%M")
-                .unwrap_or_else(|_| NaiveTime::from_hms_opt(17, 0, 0).unwrap());
-
+        let mut created = Vec::with_capacity(slots.len());
+        for slot in slots {
             let slot: AvailabilitySlot = sqlx::query_as(
                 r#"
                 INSERT INTO availability_slots (id, expert_id, day_of_week, start_time, end_time, is_available, created_at, updated_at)
@@ -67,17 +76,41 @@ impl AvailabilityService {
             )
             .bind(Uuid::new_v4())
             .bind(expert_id)
-            .bind(slot_input.day_of_week)
-            .bind(start_time)
-            .bind(end_time)
-            .bind(slot_input.is_available)
-            .fetch_one(&db.pool)
+            .bind(slot.day_of_week)
+            .bind(slot.start_time)
+            .bind(slot.end_time)
+            .bind(slot.is_available)
+            .fetch_one(&mut *tx)
             .await?;
 
-            slots.push(slot);
+            created.push(slot);
         }
 
-        Ok(slots)
+        tx.commit().await?;
+
+        Ok(created)
+    }
+
+    /// Whether the range overlaps dates the expert already blocked manually
+    pub async fn overlaps_blocked(
+        db: &Database,
+        expert_id: Uuid,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM blocked_dates
+                WHERE expert_id = $1 AND import_id IS NULL AND start_date <= $3 AND end_date >= $2
+            )
+            "#,
+        )
+        .bind(expert_id)
+        .bind(start_date)
+        .bind(end_date)
+        .fetch_one(&db.pool)
+        .await
     }
 
     /// Block dates (vacation, etc.)
@@ -104,13 +137,14 @@ impl AvailabilityService {
         Ok(blocked)
     }
 
-    /// Remove blocked dates
-    pub async fn unblock_dates(db: &Database, id: Uuid) -> Result<(), sqlx::Error> {
-        sqlx::query("DELETE FROM blocked_dates WHERE id = $1")
+    /// Remove blocked dates of an expert; returns false if there was no such entry
+    pub async fn unblock_dates(db: &Database, id: Uuid, expert_id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM blocked_dates WHERE id = $1 AND expert_id = $2")
             .bind(id)
+            .bind(expert_id)
             .execute(&db.pool)
             .await?;
-        Ok(())
+        Ok(result.rows_affected() > 0)
     }
 }
 
@@ -0,0 +1,458 @@
+//! Invoice-based billing for enterprise clients
+//! Verified clients with approved credit order without upfront payment; their completed
+//! projects are collected into one consolidated invoice per month with QR-bill/IBAN details.
+
+use chrono::{Duration, NaiveDate, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::config::BillingSettings;
+use crate::models::{
+    BillingMode, ClientProfile, CompanyDetails, CreditStatus, Invoice, InvoiceLineItem,
+    InvoicePaymentDetails, ProjectAddon, UpdateBillingTermsRequest,
+};
+use crate::services::PaymentService;
+use crate::utils::qr_bill::{self, QrBillAddress, QrBillData};
+use crate::utils::vat_rate_for_supply;
+
+/// Invoice recipient: name, email, country, VAT id, billing address, company and payment terms
+type RecipientRow = (
+    String,
+    String,
+    String,
+    String,
+    Option<String>,
+    Option<sqlx::types::Json<CompanyDetails>>,
+    Option<String>,
+    Option<i16>,
+);
+
+/// Reasons set by the automatic credit enforcement (manual blocks are left alone)
+const AUTO_BLOCK_REASONS: [&str; 2] = ["credit_limit", "overdue"];
+
+pub struct BillingService;
+
+impl BillingService {
+    /// Check whether an order of `amount` cents may be placed on credit
+    pub fn check_credit(profile: &ClientProfile, credit_used: i64, amount: i64) -> Result<(), String> {
+        if profile.billing_mode != BillingMode::Invoice {
+            return Err("Invoice billing is not enabled for this client".to_string());
+        }
+        if !profile.is_verified {
+            return Err("Invoice billing requires a verified client profile".to_string());
+        }
+        if profile.billing_blocked_at.is_some() {
+            return Err(match profile.billing_blocked_reason.as_deref() {
+                Some("overdue") => "Invoice billing is blocked because of overdue invoices".to_string(),
+                Some("credit_limit") => "Invoice billing is blocked because the credit limit is reached".to_string(),
+                _ => "Invoice billing is blocked".to_string(),
+            });
+        }
+        if let Some(limit) = profile.credit_limit
+            && credit_used + amount > limit as i64
+        {
+            return Err(format!(
+                "Order exceeds the available credit ({} of {} cents used)",
+                credit_used, limit
+            ));
+        }
+        Ok(())
+    }
+
+    /// Outstanding exposure of a client in cents
+    pub async fn get_credit_used(pool: &PgPool, client_id: Uuid) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar("SELECT client_credit_used($1)")
+            .bind(client_id)
+            .fetch_one(pool)
+            .await
+    }
+
+    /// Get credit status for a client
+    pub async fn get_credit_status(pool: &PgPool, client_id: Uuid) -> Result<Option<CreditStatus>, sqlx::Error> {
+        let profile: Option<ClientProfile> = sqlx::query_as("SELECT * FROM client_profiles WHERE user_id = $1")
+            .bind(client_id)
+            .fetch_optional(pool)
+            .await?;
+
+        let Some(profile) = profile else {
+            return Ok(None);
+        };
+
+        let credit_used = Self::get_credit_used(pool, client_id).await?;
+
+        Ok(Some(CreditStatus {
+            billing_mode: profile.billing_mode,
+            credit_limit: profile.credit_limit,
+            credit_used,
+            credit_available: profile.credit_limit.map(|limit| (limit as i64 - credit_used).max(0)),
+            payment_terms_days: profile.payment_terms_days,
+            is_blocked: profile.billing_blocked_at.is_some(),
+            blocked_reason: profile.billing_blocked_reason,
+        }))
+    }
+
+    /// Set billing terms for a client (admin)
+    pub async fn set_billing_terms(
+        pool: &PgPool,
+        client_id: Uuid,
+        admin_id: Uuid,
+        req: &UpdateBillingTermsRequest,
+    ) -> Result<ClientProfile, sqlx::Error> {
+        sqlx::query_as::<_, ClientProfile>(
+            r#"
+            UPDATE client_profiles SET
+                billing_mode = $3,
+                credit_limit = $4,
+                payment_terms_days = COALESCE($5, payment_terms_days),
+                credit_approved_at = CASE WHEN $3 = 'invoice'::billing_mode THEN NOW() ELSE NULL END,
+                credit_approved_by = CASE WHEN $3 = 'invoice'::billing_mode THEN $2 ELSE NULL END,
+                updated_at = NOW()
+            WHERE user_id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(client_id)
+        .bind(admin_id)
+        .bind(req.billing_mode)
+        .bind(req.credit_limit)
+        .bind(req.payment_terms_days)
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Block invoice billing for a client
+    pub async fn block(pool: &PgPool, client_id: Uuid, reason: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE client_profiles
+            SET billing_blocked_at = NOW(), billing_blocked_reason = $2, updated_at = NOW()
+            WHERE user_id = $1 AND billing_blocked_at IS NULL
+            "#,
+        )
+        .bind(client_id)
+        .bind(reason)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Lift a billing block
+    pub async fn unblock(pool: &PgPool, client_id: Uuid) -> Result<ClientProfile, sqlx::Error> {
+        sqlx::query_as::<_, ClientProfile>(
+            r#"
+            UPDATE client_profiles
+            SET billing_blocked_at = NULL, billing_blocked_reason = NULL, updated_at = NOW()
+            WHERE user_id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(client_id)
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Automatically block clients over their credit limit or with overdue invoices,
+    /// and release automatic blocks that no longer apply
+    pub async fn enforce_limits(pool: &PgPool, overdue_block_days: i64) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE client_profiles cp
+            SET billing_blocked_at = NOW(), billing_blocked_reason = 'overdue', updated_at = NOW()
+            WHERE cp.billing_mode = 'invoice' AND cp.billing_blocked_at IS NULL
+              AND EXISTS (
+                  SELECT 1 FROM invoices i
+                  WHERE i.recipient_id = cp.user_id AND i.status IN ('open', 'uncollectible')
+                    AND i.due_date <= CURRENT_DATE - $1::int
+              )
+            "#,
+        )
+        .bind(overdue_block_days as i32)
+        .execute(pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            UPDATE client_profiles cp
+            SET billing_blocked_at = NOW(), billing_blocked_reason = 'credit_limit', updated_at = NOW()
+            WHERE cp.billing_mode = 'invoice' AND cp.billing_blocked_at IS NULL
+              AND cp.credit_limit IS NOT NULL
+              AND client_credit_used(cp.user_id) >= cp.credit_limit
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            UPDATE client_profiles cp
+            SET billing_blocked_at = NULL, billing_blocked_reason = NULL, updated_at = NOW()
+            WHERE cp.billing_blocked_reason = ANY($2)
+              AND NOT EXISTS (
+                  SELECT 1 FROM invoices i
+                  WHERE i.recipient_id = cp.user_id AND i.status IN ('open', 'uncollectible')
+                    AND i.due_date <= CURRENT_DATE - $1::int
+              )
+              AND (cp.credit_limit IS NULL OR client_credit_used(cp.user_id) < cp.credit_limit)
+            "#,
+        )
+        .bind(overdue_block_days as i32)
+        .bind(&AUTO_BLOCK_REASONS[..])
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Clients (and currencies) with completed, not yet invoiced projects up to `period_end`.
+    /// Projects of an organisation are billed to its billing contact.
+    pub async fn get_uninvoiced_clients(
+        pool: &PgPool,
+        period_end: NaiveDate,
+    ) -> Result<Vec<(Uuid, String)>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT DISTINCT COALESCE(o.billing_contact_id, p.client_id), UPPER(p.currency::text)
+            FROM projects p
+            LEFT JOIN client_organisations o ON o.id = p.organisation_id
+            WHERE p.billing_mode = 'invoice' AND p.invoice_id IS NULL
+              AND p.status = 'completed' AND p.completed_at < ($1::date + 1)
+            "#,
+        )
+        .bind(period_end)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Create the consolidated invoice for one client, currency and billing period.
+    /// Invoices of an organisation's billing contact are addressed to the organisation.
+    pub async fn create_monthly_invoice(
+        pool: &PgPool,
+        settings: &BillingSettings,
+        client_id: Uuid,
+        currency: &str,
+        period_start: NaiveDate,
+        period_end: NaiveDate,
+    ) -> Result<Option<Invoice>, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let projects: Vec<(Uuid, String, Option<String>, i32)> = sqlx::query_as(
+            r#"
+            SELECT p.id, p.title, p.po_number, p.price
+            FROM projects p
+            LEFT JOIN client_organisations o ON o.id = p.organisation_id
+            WHERE COALESCE(o.billing_contact_id, p.client_id) = $1 AND UPPER(p.currency::text) = $2
+              AND p.billing_mode = 'invoice' AND p.invoice_id IS NULL
+              AND p.status = 'completed' AND p.completed_at < ($3::date + 1)
+            ORDER BY p.completed_at
+            FOR UPDATE OF p
+            "#,
+        )
+        .bind(client_id)
+        .bind(currency)
+        .bind(period_end)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        if projects.is_empty() {
+            return Ok(None);
+        }
+        let project_ids: Vec<Uuid> = projects.iter().map(|(id, _, _, _)| *id).collect();
+
+        let addons: Vec<ProjectAddon> =
+            sqlx::query_as("SELECT * FROM project_addons WHERE project_id = ANY($1) ORDER BY created_at")
+                .bind(&project_ids)
+                .fetch_all(&mut *tx)
+                .await?;
+
+        let (first_name, last_name, email, country, vat_id, address, company_name, terms_days): RecipientRow = sqlx::query_as(
+            r#"
+            SELECT u.first_name, u.last_name, u.email, u.country::text, u.vat_id, u.billing_address,
+                   cp.company_name, cp.payment_terms_days
+            FROM users u
+            LEFT JOIN client_profiles cp ON cp.user_id = u.id
+            WHERE u.id = $1
+            "#,
+        )
+        .bind(client_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let organisation: Option<(Uuid, String, sqlx::types::Json<CompanyDetails>)> = sqlx::query_as(
+            "SELECT id, name, company_details FROM client_organisations WHERE billing_contact_id = $1",
+        )
+        .bind(client_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let mut recipient = address.map(|a| a.0).unwrap_or_default();
+        recipient.name = company_name.or(recipient.name).or(Some(format!("{} {}", first_name, last_name)));
+        recipient.email = Some(email);
+        recipient.vat_id = vat_id.or(recipient.vat_id);
+        recipient.country = recipient.country.or(Some(country.to_uppercase()));
+        let mut organisation_id = None;
+        if let Some((id, name, details)) = organisation {
+            recipient = organisation_recipient(name, details.0, recipient);
+            organisation_id = Some(id);
+        }
+
+        let issuer = CompanyDetails {
+            name: Some(settings.company_name.clone()),
+            address_line1: Some(settings.street.clone()),
+            address_line2: None,
+            city: Some(settings.city.clone()),
+            postal_code: Some(settings.postal_code.clone()),
+            country: Some(settings.country.clone()),
+            vat_id: settings.vat_id.clone(),
+            email: None,
+        };
+
+        // Add-ons are listed as separate lines below their project
+        let line_items: Vec<InvoiceLineItem> = projects
+            .iter()
+            .flat_map(|(id, title, po_number, price)| {
+                let project_addons: Vec<&ProjectAddon> = addons.iter().filter(|a| a.project_id == *id).collect();
+                let base_price = price - project_addons.iter().map(|a| a.amount).sum::<i32>();
+
+                std::iter::once(InvoiceLineItem {
+                    description: match po_number {
+                        Some(po) => format!("{} (PO {})", title, po),
+                        None => title.clone(),
+                    },
+                    quantity: 1,
+                    unit_price: base_price,
+                    amount: base_price,
+                })
+                .chain(project_addons.into_iter().map(move |addon| InvoiceLineItem {
+                    description: format!("{} – {}", title, addon.title),
+                    quantity: addon.quantity as i32,
+                    unit_price: addon.unit_price,
+                    amount: addon.amount,
+                }))
+            })
+            .collect();
+        let subtotal: i32 = line_items.iter().map(|item| item.amount).sum();
+
+        let vat_rate = vat_rate_for_supply(
+            &settings.country,
+            recipient.country.as_deref().unwrap_or(""),
+            recipient.vat_id.is_some(),
+        )
+        .rate();
+        let tax_amount = (Decimal::from(subtotal) * vat_rate / Decimal::from(100))
+            .round()
+            .to_i32()
+            .unwrap_or(0);
+        let total = subtotal + tax_amount;
+
+        let invoice_number = PaymentService::generate_invoice_number();
+        let reference = qr_bill::payment_reference(
+            &settings.iban,
+            &format!("{}{}", invoice_number, &client_id.simple().to_string()[..6]),
+        );
+        let qr_bill_payload = (!settings.iban.is_empty()).then(|| {
+            qr_bill::build_payload(&QrBillData {
+                iban: settings.iban.clone(),
+                creditor: QrBillAddress {
+                    name: settings.company_name.clone(),
+                    street: settings.street.clone(),
+                    postal_code: settings.postal_code.clone(),
+                    town: settings.city.clone(),
+                    country: settings.country.clone(),
+                },
+                amount: total as i64,
+                currency: currency.to_string(),
+                debtor: Some(QrBillAddress {
+                    name: recipient.name.clone().unwrap_or_default(),
+                    street: recipient.address_line1.clone().unwrap_or_default(),
+                    postal_code: recipient.postal_code.clone().unwrap_or_default(),
+                    town: recipient.city.clone().unwrap_or_default(),
+                    country: recipient.country.clone().unwrap_or_default(),
+                }),
+                reference: reference.clone(),
+                message: format!("Rechnung {}", invoice_number),
+            })
+        });
+        let payment_details = InvoicePaymentDetails {
+            account_holder: settings.company_name.clone(),
+            iban: settings.iban.clone(),
+            bic: settings.bic.clone(),
+            reference: reference.clone(),
+            qr_bill_payload,
+        };
+
+        let po_numbers: Vec<&str> = projects.iter().filter_map(|(_, _, po, _)| po.as_deref()).collect();
+        let mut notes = format!(
+            "Sammelrechnung {} – {}",
+            period_start.format("%d.%m.%Y"),
+            period_end.format("%d.%m.%Y")
+        );
+        if !po_numbers.is_empty() {
+            notes.push_str(&format!("\nBestellnummern (PO): {}", po_numbers.join(", ")));
+        }
+        if vat_rate.is_zero() && recipient.vat_id.is_some() {
+            notes.push_str("\nSteuerschuldnerschaft des Leistungsempfängers (Reverse Charge)");
+        }
+
+        let due_date = Utc::now().date_naive() + Duration::days(terms_days.unwrap_or(30) as i64);
+
+        let invoice = sqlx::query_as::<_, Invoice>(
+            r#"
+            INSERT INTO invoices (
+                invoice_number, issuer_id, recipient_id, subtotal, tax_rate, tax_amount, total,
+                currency, status, due_date, notes, line_items, issuer_details, recipient_details,
+                billing_period_start, billing_period_end, payment_reference, payment_details, organisation_id
+            )
+            VALUES ($1, NULL, $2, $3, $4, $5, $6, $7, 'open', $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
+            RETURNING *
+            "#,
+        )
+        .bind(&invoice_number)
+        .bind(client_id)
+        .bind(subtotal)
+        .bind(vat_rate)
+        .bind(tax_amount)
+        .bind(total)
+        .bind(currency)
+        .bind(due_date)
+        .bind(&notes)
+        .bind(sqlx::types::Json(&line_items))
+        .bind(sqlx::types::Json(&issuer))
+        .bind(sqlx::types::Json(&recipient))
+        .bind(period_start)
+        .bind(period_end)
+        .bind(&reference)
+        .bind(sqlx::types::Json(&payment_details))
+        .bind(organisation_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query("UPDATE projects SET invoice_id = $1, updated_at = NOW() WHERE id = ANY($2)")
+            .bind(invoice.id)
+            .bind(&project_ids)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(Some(invoice))
+    }
+}
+
+/// Invoice recipient for an organisation: its company details, completed from the billing contact's
+fn organisation_recipient(name: String, details: CompanyDetails, contact: CompanyDetails) -> CompanyDetails {
+    let has_address = details.address_line1.is_some();
+
+    CompanyDetails {
+        name: details.name.or(Some(name)),
+        address_line1: if has_address { details.address_line1 } else { contact.address_line1 },
+        address_line2: if has_address { details.address_line2 } else { contact.address_line2 },
+        city: if has_address { details.city } else { contact.city },
+        postal_code: if has_address { details.postal_code } else { contact.postal_code },
+        country: details.country.or(contact.country),
+        vat_id: details.vat_id.or(contact.vat_id),
+        email: details.email.or(contact.email),
+    }
+}
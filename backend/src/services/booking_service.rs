@@ -0,0 +1,503 @@
+//! Calendar booking
+//! Bookable slots are derived from the expert's weekly availability, blocked dates, busy times
+//! imported from their calendars and existing bookings (plus buffer and daily limit). Wall-clock times are converted with
+//! PostgreSQL's time zone database, so slots stay correct across daylight saving changes.
+//! A booking request reserves its slot until the expert answers or the request expires.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, Utc};
+use sqlx::{FromRow, PgConnection, PgExecutor};
+use uuid::Uuid;
+
+use crate::config::BookingSettings;
+use crate::db::Database;
+use crate::models::{
+    AvailabilitySlot, BlockedDate, BookableSlot, BookingRequest, BookingSlotQuery, BookingSlots, CreateBookingRequest,
+};
+use crate::services::NotificationService;
+
+/// Exclusion constraint preventing overlapping reservations
+const NO_OVERLAP_CONSTRAINT: &str = "booking_requests_no_overlap";
+
+/// Error of a booking operation
+#[derive(Debug, thiserror::Error)]
+pub enum BookingError {
+    #[error("Expert not found")]
+    NotFound,
+
+    #[error("Unknown timezone: {0}")]
+    InvalidTimezone(String),
+
+    #[error("{0}")]
+    Invalid(String),
+
+    #[error("The requested time slot is no longer available")]
+    SlotUnavailable,
+
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+/// Booking rules of an expert
+#[derive(Debug, FromRow)]
+struct ExpertCalendar {
+    id: Uuid,
+    timezone: String,
+    booking_slot_minutes: i16,
+    booking_buffer_minutes: i16,
+    max_bookings_per_day: Option<i16>,
+}
+
+/// Time an expert is booked or has reserved
+#[derive(Debug, Clone, FromRow)]
+pub struct BusyInterval {
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    /// Day of the start in the expert's timezone
+    pub local_day: NaiveDate,
+}
+
+/// Candidate slot converted from the expert's wall-clock time
+#[derive(Debug, FromRow)]
+struct ConvertedSlot {
+    starts_at: DateTime<Utc>,
+    ends_at: DateTime<Utc>,
+    local_start: NaiveDateTime,
+    local_end: NaiveDateTime,
+    /// False if the wall-clock time does not exist (skipped by a DST change)
+    exists_locally: bool,
+}
+
+pub struct BookingService;
+
+impl BookingService {
+    /// Slot starts (expert wall-clock time) of the weekly availability between `from` and `to`,
+    /// skipping blocked dates
+    pub fn candidate_slots(
+        weekly: &[AvailabilitySlot],
+        blocked: &[BlockedDate],
+        from: NaiveDate,
+        to: NaiveDate,
+        slot_minutes: i64,
+    ) -> Vec<NaiveDateTime> {
+        let length = Duration::minutes(slot_minutes.max(1));
+        let mut starts = Vec::new();
+
+        for day in from.iter_days().take_while(|d| *d <= to) {
+            if blocked.iter().any(|b| b.start_date <= day && day <= b.end_date) {
+                continue;
+            }
+
+            let weekday = day.weekday().num_days_from_sunday() as i16;
+            for slot in weekly.iter().filter(|s| s.is_available && s.day_of_week == weekday) {
+                let end = day.and_time(slot.end_time);
+                let mut start = day.and_time(slot.start_time);
+                while start + length <= end {
+                    starts.push(start);
+                    start += length;
+                }
+            }
+        }
+
+        starts.sort();
+        starts.dedup();
+        starts
+    }
+
+    /// Whether `start..end` keeps at least `buffer` distance to all busy intervals
+    pub fn is_free(start: DateTime<Utc>, end: DateTime<Utc>, busy: &[BusyInterval], buffer: Duration) -> bool {
+        busy.iter()
+            .all(|b| end + buffer <= b.starts_at || start >= b.ends_at + buffer)
+    }
+
+    /// Whether the expert already has the maximum number of bookings on `day`
+    pub fn day_is_full(day: NaiveDate, busy: &[BusyInterval], max_per_day: Option<i16>) -> bool {
+        max_per_day.is_some_and(|max| busy.iter().filter(|b| b.local_day == day).count() >= max as usize)
+    }
+
+    /// Whether PostgreSQL knows the IANA timezone
+    pub async fn is_valid_timezone<'e, E: PgExecutor<'e>>(executor: E, timezone: &str) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM pg_timezone_names WHERE name = $1)")
+            .bind(timezone)
+            .fetch_one(executor)
+            .await
+    }
+
+    async fn get_calendar(
+        conn: &mut PgConnection,
+        expert_id: Uuid,
+        for_update: bool,
+    ) -> Result<ExpertCalendar, BookingError> {
+        let sql = if for_update {
+            r#"
+            SELECT id, timezone, booking_slot_minutes, booking_buffer_minutes, max_bookings_per_day
+            FROM expert_profiles WHERE user_id = $1
+            FOR UPDATE
+            "#
+        } else {
+            r#"
+            SELECT id, timezone, booking_slot_minutes, booking_buffer_minutes, max_bookings_per_day
+            FROM expert_profiles WHERE user_id = $1
+            "#
+        };
+
+        sqlx::query_as::<_, ExpertCalendar>(sql)
+            .bind(expert_id)
+            .fetch_optional(conn)
+            .await?
+            .ok_or(BookingError::NotFound)
+    }
+
+    /// Bookable slots whose start falls between `from` and `to` in `timezone`
+    async fn compute_slots(
+        conn: &mut PgConnection,
+        expert_id: Uuid,
+        calendar: &ExpertCalendar,
+        from: NaiveDate,
+        to: NaiveDate,
+        timezone: &str,
+        settings: &BookingSettings,
+    ) -> Result<Vec<BookableSlot>, sqlx::Error> {
+        // Display days may start up to a day earlier or later in the expert's timezone
+        let (local_from, local_to) = (from - Duration::days(1), to + Duration::days(1));
+
+        let weekly = sqlx::query_as::<_, AvailabilitySlot>(
+            "SELECT * FROM availability_slots WHERE expert_id = $1 AND is_available ORDER BY day_of_week, start_time",
+        )
+        .bind(calendar.id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let blocked = sqlx::query_as::<_, BlockedDate>(
+            "SELECT * FROM blocked_dates WHERE expert_id = $1 AND end_date >= $2 AND start_date <= $3",
+        )
+        .bind(calendar.id)
+        .bind(local_from)
+        .bind(local_to)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let candidates = Self::candidate_slots(
+            &weekly,
+            &blocked,
+            local_from,
+            local_to,
+            calendar.booking_slot_minutes as i64,
+        );
+        if candidates.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let converted = sqlx::query_as::<_, ConvertedSlot>(
+            r#"
+            SELECT c.local_start AT TIME ZONE $2 AS starts_at,
+                   (c.local_start AT TIME ZONE $2) + make_interval(mins => $4) AS ends_at,
+                   (c.local_start AT TIME ZONE $2) AT TIME ZONE $3 AS local_start,
+                   ((c.local_start AT TIME ZONE $2) + make_interval(mins => $4)) AT TIME ZONE $3 AS local_end,
+                   (c.local_start AT TIME ZONE $2) AT TIME ZONE $2 = c.local_start AS exists_locally
+            FROM unnest($1::timestamp[]) WITH ORDINALITY AS c(local_start, n)
+            ORDER BY c.n
+            "#,
+        )
+        .bind(&candidates)
+        .bind(&calendar.timezone)
+        .bind(timezone)
+        .bind(calendar.booking_slot_minutes as i32)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let busy = sqlx::query_as::<_, BusyInterval>(
+            r#"
+            SELECT slot_start AS starts_at, slot_end AS ends_at,
+                   (slot_start AT TIME ZONE $2)::date AS local_day
+            FROM booking_requests
+            WHERE expert_id = $1 AND slot_start IS NOT NULL
+              AND (status = 'accepted' OR (status = 'pending' AND expires_at > NOW()))
+              AND slot_end > $3 AND slot_start < $4
+            "#,
+        )
+        .bind(expert_id)
+        .bind(&calendar.timezone)
+        .bind(converted[0].starts_at - Duration::days(2))
+        .bind(converted[converted.len() - 1].ends_at + Duration::days(2))
+        .fetch_all(&mut *conn)
+        .await?;
+
+        // Imported calendar events; they do not count towards the daily booking limit
+        let blocked_times = sqlx::query_as::<_, BusyInterval>(
+            r#"
+            SELECT starts_at, ends_at, (starts_at AT TIME ZONE $2)::date AS local_day
+            FROM blocked_times
+            WHERE expert_id = $1 AND ends_at > $3 AND starts_at < $4
+            "#,
+        )
+        .bind(calendar.id)
+        .bind(&calendar.timezone)
+        .bind(converted[0].starts_at - Duration::days(2))
+        .bind(converted[converted.len() - 1].ends_at + Duration::days(2))
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let earliest = Utc::now() + Duration::hours(settings.min_notice_hours);
+        let buffer = Duration::minutes(calendar.booking_buffer_minutes as i64);
+
+        Ok(candidates
+            .iter()
+            .zip(converted)
+            .filter(|(expert_local, slot)| {
+                let day = slot.local_start.date();
+                slot.exists_locally
+                    && slot.starts_at >= earliest
+                    && from <= day
+                    && day <= to
+                    && Self::is_free(slot.starts_at, slot.ends_at, &busy, buffer)
+                    && Self::is_free(slot.starts_at, slot.ends_at, &blocked_times, buffer)
+                    && !Self::day_is_full(expert_local.date(), &busy, calendar.max_bookings_per_day)
+            })
+            .map(|(_, slot)| BookableSlot {
+                starts_at: slot.starts_at,
+                ends_at: slot.ends_at,
+                local_start: slot.local_start,
+                local_end: slot.local_end,
+            })
+            .collect())
+    }
+
+    /// Bookable slots of an expert (user id)
+    pub async fn get_slots(
+        db: &Database,
+        expert_id: Uuid,
+        query: &BookingSlotQuery,
+        settings: &BookingSettings,
+    ) -> Result<BookingSlots, BookingError> {
+        if query.to < query.from {
+            return Err(BookingError::Invalid("'to' must not be before 'from'".to_string()));
+        }
+        if (query.to - query.from).num_days() >= settings.max_range_days {
+            return Err(BookingError::Invalid(format!(
+                "At most {} days can be queried at once",
+                settings.max_range_days
+            )));
+        }
+
+        let mut conn = db.pool.acquire().await?;
+        let calendar = Self::get_calendar(&mut conn, expert_id, false).await?;
+
+        let timezone = query.timezone.clone().unwrap_or_else(|| calendar.timezone.clone());
+        if !Self::is_valid_timezone(&mut *conn, &timezone).await? {
+            return Err(BookingError::InvalidTimezone(timezone));
+        }
+
+        let slots =
+            Self::compute_slots(&mut conn, expert_id, &calendar, query.from, query.to, &timezone, settings).await?;
+
+        Ok(BookingSlots {
+            timezone,
+            expert_timezone: calendar.timezone,
+            slot_minutes: calendar.booking_slot_minutes,
+            slots,
+        })
+    }
+
+    /// Create a booking request; with `slot_start` the slot is reserved until the request expires
+    pub async fn create(
+        db: &Database,
+        client_id: Uuid,
+        req: &CreateBookingRequest,
+        settings: &BookingSettings,
+    ) -> Result<BookingRequest, BookingError> {
+        if req.expert_id == client_id {
+            return Err(BookingError::Invalid("You cannot book yourself".to_string()));
+        }
+
+        let mut tx = db.pool.begin().await?;
+
+        let slot = match req.slot_start {
+            Some(slot_start) => {
+                // Serializes reservations per expert so buffers and daily limits hold
+                let calendar = Self::get_calendar(&mut tx, req.expert_id, true).await?;
+                Self::expire_tx(&mut tx, Some(req.expert_id)).await?;
+
+                let day: NaiveDate = sqlx::query_scalar("SELECT ($1 AT TIME ZONE $2)::date")
+                    .bind(slot_start)
+                    .bind(&calendar.timezone)
+                    .fetch_one(&mut *tx)
+                    .await?;
+
+                let slots =
+                    Self::compute_slots(&mut tx, req.expert_id, &calendar, day, day, &calendar.timezone, settings)
+                        .await?;
+                let slot = slots
+                    .into_iter()
+                    .find(|s| s.starts_at == slot_start)
+                    .ok_or(BookingError::SlotUnavailable)?;
+                Some(slot)
+            }
+            None => None,
+        };
+
+        let booking = sqlx::query_as::<_, BookingRequest>(
+            r#"
+            INSERT INTO booking_requests (
+                client_id, expert_id, service_id, package_id, message, proposed_budget, currency,
+                proposed_start_date, proposed_deadline, slot_start, slot_end, expires_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, COALESCE($8, $10), $9, $10, $11,
+                    CASE WHEN $10::TIMESTAMPTZ IS NULL THEN NOW() + INTERVAL '7 days'
+                         ELSE LEAST(NOW() + make_interval(hours => $12), $10) END)
+            RETURNING *
+            "#,
+        )
+        .bind(client_id)
+        .bind(req.expert_id)
+        .bind(req.service_id)
+        .bind(req.package_id)
+        .bind(&req.message)
+        .bind(req.proposed_budget)
+        .bind(&req.currency)
+        .bind(req.proposed_start_date)
+        .bind(req.proposed_deadline)
+        .bind(slot.as_ref().map(|s| s.starts_at))
+        .bind(slot.as_ref().map(|s| s.ends_at))
+        .bind(settings.reservation_hours as i32)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db_err) if db_err.constraint() == Some(NO_OVERLAP_CONSTRAINT) => {
+                BookingError::SlotUnavailable
+            }
+            e => BookingError::Database(e),
+        })?;
+
+        if let Some(slot) = &slot {
+            NotificationService::create(
+                &mut *tx,
+                booking.expert_id,
+                "booking_requested",
+                "Neue Terminanfrage",
+                &format!(
+                    "Sie haben eine neue Terminanfrage für {} (UTC). Bitte antworten Sie bis {} (UTC).",
+                    slot.starts_at.format("%d.%m.%Y %H:%M"),
+                    booking.expires_at.format("%d.%m.%Y %H:%M")
+                ),
+                Some(serde_json::json!({ "bookingId": booking.id })),
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(booking)
+    }
+
+    /// Expire unanswered booking requests (of one expert or all) and notify the clients
+    async fn expire_tx(conn: &mut PgConnection, expert_id: Option<Uuid>) -> Result<Vec<BookingRequest>, sqlx::Error> {
+        let expired = sqlx::query_as::<_, BookingRequest>(
+            r#"
+            UPDATE booking_requests
+            SET status = 'expired', updated_at = NOW()
+            WHERE status = 'pending' AND expires_at <= NOW()
+              AND ($1::UUID IS NULL OR expert_id = $1)
+            RETURNING *
+            "#,
+        )
+        .bind(expert_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        for booking in &expired {
+            NotificationService::create(
+                &mut *conn,
+                booking.client_id,
+                "booking_expired",
+                "Buchungsanfrage abgelaufen",
+                "Ihre Buchungsanfrage wurde nicht rechtzeitig beantwortet und ist abgelaufen. Ein reservierter Termin ist wieder frei.",
+                Some(serde_json::json!({ "bookingId": booking.id })),
+            )
+            .await?;
+        }
+
+        Ok(expired)
+    }
+
+    /// Expire all unanswered booking requests whose reservation ran out
+    pub async fn expire_due(db: &Database) -> Result<Vec<BookingRequest>, sqlx::Error> {
+        let mut tx = db.pool.begin().await?;
+        let expired = Self::expire_tx(&mut tx, None).await?;
+        tx.commit().await?;
+
+        Ok(expired)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveTime, TimeZone};
+
+    fn date(m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, m, d).unwrap()
+    }
+
+    fn weekly(day_of_week: i16, start: u32, end: u32) -> AvailabilitySlot {
+        AvailabilitySlot {
+            id: Uuid::new_v4(),
+            expert_id: Uuid::nil(),
+            day_of_week,
+            start_time: NaiveTime::from_hms_opt(start, 0, 0).unwrap(),
+            end_time: NaiveTime::from_hms_opt(end, 0, 0).unwrap(),
+            is_available: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn busy(start_hour: u32, end_hour: u32) -> BusyInterval {
+        BusyInterval {
+            starts_at: Utc.with_ymd_and_hms(2024, 12, 16, start_hour, 0, 0).unwrap(),
+            ends_at: Utc.with_ymd_and_hms(2024, 12, 16, end_hour, 0, 0).unwrap(),
+            local_day: date(12, 16),
+        }
+    }
+
+    #[test]
+    fn test_candidate_slots() {
+        // Monday 9-12 in 90 minute slots, Wednesday blocked
+        let slots = vec![weekly(1, 9, 12), weekly(3, 9, 10)];
+        let blocked = vec![BlockedDate {
+            id: Uuid::new_v4(),
+            expert_id: Uuid::nil(),
+            start_date: date(12, 18),
+            end_date: date(12, 18),
+            reason: None,
+            import_id: None,
+            created_at: Utc::now(),
+        }];
+
+        let starts = BookingService::candidate_slots(&slots, &blocked, date(12, 15), date(12, 21), 90);
+        assert_eq!(
+            starts,
+            vec![date(12, 16).and_hms_opt(9, 0, 0).unwrap(), date(12, 16).and_hms_opt(10, 30, 0).unwrap()]
+        );
+    }
+
+    #[test]
+    fn test_is_free_respects_buffer() {
+        let booked = vec![busy(10, 11)];
+        let buffer = Duration::minutes(15);
+        let at = |h: u32, m: u32| Utc.with_ymd_and_hms(2024, 12, 16, h, m, 0).unwrap();
+
+        assert!(BookingService::is_free(at(8, 45), at(9, 45), &booked, buffer));
+        assert!(!BookingService::is_free(at(9, 0), at(10, 0), &booked, buffer));
+        assert!(!BookingService::is_free(at(11, 0), at(12, 0), &booked, buffer));
+        assert!(BookingService::is_free(at(11, 15), at(12, 15), &booked, buffer));
+    }
+
+    #[test]
+    fn test_day_is_full() {
+        let booked = vec![busy(8, 9), busy(10, 11)];
+        assert!(BookingService::day_is_full(date(12, 16), &booked, Some(2)));
+        assert!(!BookingService::day_is_full(date(12, 16), &booked, Some(3)));
+        assert!(!BookingService::day_is_full(date(12, 17), &booked, Some(1)));
+        assert!(!BookingService::day_is_full(date(12, 16), &booked, None));
+    }
+}
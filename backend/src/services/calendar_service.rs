@@ -0,0 +1,714 @@
+//! Calendar sync
+//! Experts get a secret ICS feed with their confirmed bookings and project deadlines, and can
+//! import their own calendars: busy all-day events become blocked dates, timed events blocked
+//! times. Subscribed calendars are re-synced by the calendar sync job; every sync replaces the
+//! blocks of its import. Event times are converted with PostgreSQL's time zone database.
+
+use std::collections::HashSet;
+use std::net::{IpAddr, Ipv4Addr};
+#[cfg(feature = "calendar")]
+use std::net::SocketAddr;
+#[cfg(feature = "calendar")]
+use std::sync::Arc;
+
+use chrono::{Duration, NaiveDate, NaiveDateTime, Utc};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::config::CalendarSettings;
+use crate::db::Database;
+use crate::models::{CalendarImport, CreateCalendarImportRequest};
+use crate::utils::generate_token;
+use crate::utils::ical::{self, ExportEvent, IcsEvent, IcsTime};
+
+/// Calendars an expert can import
+pub const MAX_CALENDAR_IMPORTS: i64 = 10;
+
+/// Busy occurrences stored per import; the rest of a huge calendar is ignored
+const MAX_IMPORTED_OCCURRENCES: usize = 5000;
+
+/// Past days included in the exported feed
+const FEED_PAST_DAYS: i64 = 30;
+
+/// Error of a calendar operation
+#[derive(Debug, thiserror::Error)]
+pub enum CalendarError {
+    #[error("Calendar not found")]
+    NotFound,
+
+    #[error("{0}")]
+    Invalid(String),
+
+    #[error("Calendar could not be fetched: {0}")]
+    Fetch(String),
+
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+#[derive(Debug, FromRow)]
+struct FeedOwner {
+    user_id: Uuid,
+    timezone: String,
+}
+
+#[derive(Debug, FromRow)]
+struct FeedBooking {
+    id: Uuid,
+    slot_start: chrono::DateTime<Utc>,
+    slot_end: chrono::DateTime<Utc>,
+    message: String,
+    client_name: String,
+    service_title: Option<String>,
+}
+
+#[derive(Debug, FromRow)]
+struct FeedDeadline {
+    id: Uuid,
+    title: String,
+    due: NaiveDate,
+}
+
+/// Busy times of an import, split by kind
+#[derive(Debug, Default, PartialEq)]
+struct ImportedBlocks {
+    /// All-day ranges (inclusive)
+    dates: Vec<(NaiveDate, NaiveDate)>,
+    /// Wall-clock start and end with their time zone names
+    times: Vec<(NaiveDateTime, String, NaiveDateTime, String)>,
+}
+
+pub struct CalendarService;
+
+impl CalendarService {
+    /// Feed token of an expert (profile id), created on first use; `rotate` replaces it
+    pub async fn feed_token(db: &Database, expert_id: Uuid, rotate: bool) -> Result<String, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+            UPDATE expert_profiles
+            SET calendar_feed_token = CASE
+                    WHEN $3 OR calendar_feed_token IS NULL THEN $2
+                    ELSE calendar_feed_token
+                END
+            WHERE id = $1
+            RETURNING calendar_feed_token
+            "#,
+        )
+        .bind(expert_id)
+        .bind(generate_token(32))
+        .bind(rotate)
+        .fetch_one(&db.pool)
+        .await
+    }
+
+    /// ICS feed of the expert owning `token` (None for unknown tokens)
+    pub async fn export_feed(db: &Database, token: &str) -> Result<Option<String>, sqlx::Error> {
+        let Some(owner) = sqlx::query_as::<_, FeedOwner>(
+            "SELECT user_id, timezone FROM expert_profiles WHERE calendar_feed_token = $1",
+        )
+        .bind(token)
+        .fetch_optional(&db.pool)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let bookings = sqlx::query_as::<_, FeedBooking>(
+            r#"
+            SELECT b.id, b.slot_start, b.slot_end, b.message,
+                   u.first_name || ' ' || u.last_name AS client_name,
+                   s.title AS service_title
+            FROM booking_requests b
+            JOIN users u ON u.id = b.client_id
+            LEFT JOIN services s ON s.id = b.service_id
+            WHERE b.expert_id = $1 AND b.status = 'accepted'
+              AND b.slot_start IS NOT NULL AND b.slot_end > NOW() - make_interval(days => $2)
+            ORDER BY b.slot_start
+            "#,
+        )
+        .bind(owner.user_id)
+        .bind(FEED_PAST_DAYS as i32)
+        .fetch_all(&db.pool)
+        .await?;
+
+        let deadlines = sqlx::query_as::<_, FeedDeadline>(
+            r#"
+            SELECT id, title, (delivery_date AT TIME ZONE $2)::date AS due
+            FROM projects
+            WHERE expert_id = $1 AND delivery_date IS NOT NULL
+              AND delivery_date > NOW() - make_interval(days => $3)
+              AND status IN ('accepted', 'paid', 'awaiting_requirements', 'in_progress', 'revision')
+            ORDER BY delivery_date
+            "#,
+        )
+        .bind(owner.user_id)
+        .bind(&owner.timezone)
+        .bind(FEED_PAST_DAYS as i32)
+        .fetch_all(&db.pool)
+        .await?;
+
+        let events: Vec<ExportEvent> = bookings
+            .into_iter()
+            .map(|b| ExportEvent {
+                uid: format!("booking-{}@dach-marketplace", b.id),
+                summary: match b.service_title {
+                    Some(service) => format!("Termin: {} mit {}", service, b.client_name),
+                    None => format!("Termin mit {}", b.client_name),
+                },
+                description: Some(b.message),
+                start: IcsTime::Utc(b.slot_start.naive_utc()),
+                end: IcsTime::Utc(b.slot_end.naive_utc()),
+            })
+            .chain(deadlines.into_iter().map(|d| ExportEvent {
+                uid: format!("deadline-{}@dach-marketplace", d.id),
+                summary: format!("Abgabe: {}", d.title),
+                description: None,
+                start: IcsTime::Date(d.due),
+                end: IcsTime::Date(d.due + Duration::days(1)),
+            }))
+            .collect();
+
+        Ok(Some(ical::write_calendar("DACH Marketplace", &events, Utc::now())))
+    }
+
+    /// Imported calendars of an expert (profile id)
+    pub async fn list_imports(db: &Database, expert_id: Uuid) -> Result<Vec<CalendarImport>, sqlx::Error> {
+        sqlx::query_as::<_, CalendarImport>(
+            "SELECT * FROM calendar_imports WHERE expert_id = $1 ORDER BY created_at",
+        )
+        .bind(expert_id)
+        .fetch_all(&db.pool)
+        .await
+    }
+
+    /// Imported calendar of an expert
+    pub async fn get_import(db: &Database, id: Uuid, expert_id: Uuid) -> Result<Option<CalendarImport>, sqlx::Error> {
+        sqlx::query_as::<_, CalendarImport>("SELECT * FROM calendar_imports WHERE id = $1 AND expert_id = $2")
+            .bind(id)
+            .bind(expert_id)
+            .fetch_optional(&db.pool)
+            .await
+    }
+
+    /// Subscribe to an ICS URL; the calendar is fetched right away and not stored if that fails
+    pub async fn subscribe(
+        db: &Database,
+        expert_id: Uuid,
+        req: &CreateCalendarImportRequest,
+        settings: &CalendarSettings,
+    ) -> Result<CalendarImport, CalendarError> {
+        let url = subscription_url(&req.url).map_err(CalendarError::Invalid)?;
+        Self::check_import_limit(db, expert_id).await?;
+
+        let events = fetch_events(&url, settings).await?;
+
+        let import = sqlx::query_as::<_, CalendarImport>(
+            "INSERT INTO calendar_imports (expert_id, name, source_url) VALUES ($1, $2, $3) RETURNING *",
+        )
+        .bind(expert_id)
+        .bind(req.name.trim())
+        .bind(&url)
+        .fetch_one(&db.pool)
+        .await?;
+
+        Ok(Self::replace_blocks(db, &import, &events, settings).await?)
+    }
+
+    /// Import an uploaded ICS file once
+    pub async fn import_file(
+        db: &Database,
+        expert_id: Uuid,
+        name: &str,
+        content: &str,
+        settings: &CalendarSettings,
+    ) -> Result<CalendarImport, CalendarError> {
+        let events = ical::parse(content).map_err(CalendarError::Invalid)?;
+        Self::check_import_limit(db, expert_id).await?;
+
+        let import = sqlx::query_as::<_, CalendarImport>(
+            "INSERT INTO calendar_imports (expert_id, name) VALUES ($1, $2) RETURNING *",
+        )
+        .bind(expert_id)
+        .bind(name)
+        .fetch_one(&db.pool)
+        .await?;
+
+        Ok(Self::replace_blocks(db, &import, &events, settings).await?)
+    }
+
+    /// Fetch a subscribed calendar again; failures are recorded on the import
+    pub async fn sync(
+        db: &Database,
+        import: &CalendarImport,
+        settings: &CalendarSettings,
+    ) -> Result<CalendarImport, CalendarError> {
+        let Some(url) = &import.source_url else {
+            return Err(CalendarError::Invalid("Uploaded calendars cannot be synced".to_string()));
+        };
+
+        match fetch_events(url, settings).await {
+            Ok(events) => Ok(Self::replace_blocks(db, import, &events, settings).await?),
+            Err(e) => {
+                sqlx::query(
+                    "UPDATE calendar_imports SET last_synced_at = NOW(), last_error = $2, updated_at = NOW() WHERE id = $1",
+                )
+                .bind(import.id)
+                .bind(e.to_string())
+                .execute(&db.pool)
+                .await?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Re-sync subscriptions not fetched within the refresh interval; returns (synced, failed)
+    pub async fn sync_due(db: &Database, settings: &CalendarSettings) -> Result<(usize, usize), sqlx::Error> {
+        let due = sqlx::query_as::<_, CalendarImport>(
+            r#"
+            SELECT * FROM calendar_imports
+            WHERE source_url IS NOT NULL
+              AND (last_synced_at IS NULL OR last_synced_at < NOW() - make_interval(mins => $1))
+            ORDER BY last_synced_at NULLS FIRST
+            LIMIT 100
+            "#,
+        )
+        .bind(settings.refresh_minutes as i32)
+        .fetch_all(&db.pool)
+        .await?;
+
+        let (mut synced, mut failed) = (0, 0);
+        for import in &due {
+            match Self::sync(db, import, settings).await {
+                Ok(_) => synced += 1,
+                Err(CalendarError::Database(e)) => return Err(e),
+                Err(e) => {
+                    tracing::warn!("Calendar import {} failed to sync: {}", import.id, e);
+                    failed += 1;
+                }
+            }
+        }
+
+        Ok((synced, failed))
+    }
+
+    /// Remove an imported calendar together with its blocks
+    pub async fn delete_import(db: &Database, id: Uuid, expert_id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM calendar_imports WHERE id = $1 AND expert_id = $2")
+            .bind(id)
+            .bind(expert_id)
+            .execute(&db.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn check_import_limit(db: &Database, expert_id: Uuid) -> Result<(), CalendarError> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM calendar_imports WHERE expert_id = $1")
+            .bind(expert_id)
+            .fetch_one(&db.pool)
+            .await?;
+
+        if count >= MAX_CALENDAR_IMPORTS {
+            return Err(CalendarError::Invalid(format!(
+                "At most {} calendars can be imported",
+                MAX_CALENDAR_IMPORTS
+            )));
+        }
+        Ok(())
+    }
+
+    /// Replace the blocked dates and times of an import with the busy occurrences of `events`
+    async fn replace_blocks(
+        db: &Database,
+        import: &CalendarImport,
+        events: &[IcsEvent],
+        settings: &CalendarSettings,
+    ) -> Result<CalendarImport, sqlx::Error> {
+        let mut tx = db.pool.begin().await?;
+
+        let timezone: String = sqlx::query_scalar("SELECT timezone FROM expert_profiles WHERE id = $1")
+            .bind(import.expert_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        let now = Utc::now().naive_utc();
+        let occurrences = ical::busy_occurrences(events, now - Duration::days(1), now + Duration::days(settings.horizon_days));
+        let mut blocks = split_occurrences(&occurrences, &timezone);
+
+        // Unknown zone names (and floating times) are read in the expert's timezone
+        let zones: Vec<String> = blocks
+            .times
+            .iter()
+            .flat_map(|(_, start_tz, _, end_tz)| [start_tz.clone(), end_tz.clone()])
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        let known: HashSet<String> =
+            sqlx::query_scalar::<_, String>("SELECT name FROM pg_timezone_names WHERE name = ANY($1)")
+                .bind(&zones)
+                .fetch_all(&mut *tx)
+                .await?
+                .into_iter()
+                .collect();
+        for (_, start_tz, _, end_tz) in &mut blocks.times {
+            for tz in [start_tz, end_tz] {
+                if !known.contains(tz.as_str()) {
+                    *tz = timezone.clone();
+                }
+            }
+        }
+
+        sqlx::query("DELETE FROM blocked_dates WHERE import_id = $1")
+            .bind(import.id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM blocked_times WHERE import_id = $1")
+            .bind(import.id)
+            .execute(&mut *tx)
+            .await?;
+
+        let (starts, ends): (Vec<NaiveDate>, Vec<NaiveDate>) = blocks.dates.iter().copied().unzip();
+        sqlx::query(
+            r#"
+            INSERT INTO blocked_dates (expert_id, import_id, start_date, end_date, reason)
+            SELECT $1, $2, d.start_date, d.end_date, $5
+            FROM unnest($3::date[], $4::date[]) AS d(start_date, end_date)
+            "#,
+        )
+        .bind(import.expert_id)
+        .bind(import.id)
+        .bind(&starts)
+        .bind(&ends)
+        .bind(&import.name)
+        .execute(&mut *tx)
+        .await?;
+
+        let mut times: (Vec<NaiveDateTime>, Vec<String>, Vec<NaiveDateTime>, Vec<String>) = Default::default();
+        for (start, start_tz, end, end_tz) in blocks.times {
+            times.0.push(start);
+            times.1.push(start_tz);
+            times.2.push(end);
+            times.3.push(end_tz);
+        }
+        sqlx::query(
+            r#"
+            INSERT INTO blocked_times (expert_id, import_id, starts_at, ends_at)
+            SELECT $1, $2, t.local_start AT TIME ZONE t.start_zone, t.local_end AT TIME ZONE t.end_zone
+            FROM unnest($3::timestamp[], $4::text[], $5::timestamp[], $6::text[])
+                AS t(local_start, start_zone, local_end, end_zone)
+            WHERE t.local_start AT TIME ZONE t.start_zone < t.local_end AT TIME ZONE t.end_zone
+            "#,
+        )
+        .bind(import.expert_id)
+        .bind(import.id)
+        .bind(&times.0)
+        .bind(&times.1)
+        .bind(&times.2)
+        .bind(&times.3)
+        .execute(&mut *tx)
+        .await?;
+
+        let import = sqlx::query_as::<_, CalendarImport>(
+            r#"
+            UPDATE calendar_imports
+            SET event_count = $2, last_synced_at = NOW(), last_error = NULL, updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(import.id)
+        .bind(occurrences.len().min(MAX_IMPORTED_OCCURRENCES) as i32)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(import)
+    }
+}
+
+/// Split busy occurrences into all-day ranges and timed ranges; floating times get `timezone`
+fn split_occurrences(occurrences: &[ical::Occurrence], timezone: &str) -> ImportedBlocks {
+    let zone = |time: &IcsTime| match time {
+        IcsTime::Utc(_) => "UTC".to_string(),
+        IcsTime::Local(_, tzid) => tzid.clone().unwrap_or_else(|| timezone.to_string()),
+        IcsTime::Date(_) => timezone.to_string(),
+    };
+    let naive = |time: &IcsTime| match time {
+        IcsTime::Date(date) => date.and_time(chrono::NaiveTime::MIN),
+        IcsTime::Utc(time) | IcsTime::Local(time, _) => *time,
+    };
+
+    let mut blocks = ImportedBlocks::default();
+    for occurrence in occurrences.iter().take(MAX_IMPORTED_OCCURRENCES) {
+        match (&occurrence.start, &occurrence.end) {
+            (IcsTime::Date(start), IcsTime::Date(end)) => {
+                // The end of all-day events is exclusive
+                blocks.dates.push((*start, end.pred_opt().unwrap_or(*start).max(*start)));
+            }
+            (start, end) => {
+                blocks.times.push((naive(start), zone(start), naive(end), zone(end)));
+            }
+        }
+    }
+
+    blocks
+}
+
+/// Validate a subscription URL: HTTPS (`webcal://` is read as HTTPS) to a public host
+pub fn subscription_url(url: &str) -> Result<String, String> {
+    let url = url.trim();
+    let lower = url.to_ascii_lowercase();
+    let rest = if let Some(rest) = lower.strip_prefix("webcal://").or_else(|| lower.strip_prefix("webcals://")) {
+        rest
+    } else if let Some(rest) = lower.strip_prefix("https://") {
+        rest
+    } else {
+        return Err("Calendar URL must start with https:// or webcal://".to_string());
+    };
+
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or("");
+    if authority.contains('@') || authority.starts_with('[') {
+        return Err("Calendar URL host is not allowed".to_string());
+    }
+    let host = authority.split(':').next().unwrap_or("").trim_end_matches('.');
+
+    let internal = host.is_empty()
+        || !host.contains('.')
+        || host == "localhost"
+        || [".localhost", ".local", ".internal", ".lan"].iter().any(|suffix| host.ends_with(suffix))
+        || host.parse::<Ipv4Addr>().is_ok_and(|ip| !is_public_ip(IpAddr::V4(ip)));
+    if internal {
+        return Err("Calendar URL host is not allowed".to_string());
+    }
+
+    Ok(format!("https://{}", &url[url.len() - rest.len()..]))
+}
+
+/// Whether an address is publicly routable (`IpAddr::is_global` is not stable yet)
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, c, _] = ip.octets();
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                || a == 0
+                || a >= 240
+                // Shared address space (carrier-grade NAT)
+                || (a == 100 && (64..128).contains(&b))
+                // IETF protocol assignments
+                || (a == 192 && b == 0 && c == 0)
+                // Benchmarking
+                || (a == 198 && (b == 18 || b == 19)))
+        }
+        IpAddr::V6(ip) => {
+            if let Some(ip) = ip.to_ipv4_mapped() {
+                return is_public_ip(IpAddr::V4(ip));
+            }
+            let segments = ip.segments();
+            // NAT64 embeds the IPv4 address in the last 32 bits
+            if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+                let [.., high, low] = segments;
+                return is_public_ip(IpAddr::V4(Ipv4Addr::from((u32::from(high) << 16) | u32::from(low))));
+            }
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                // Unique local
+                || (segments[0] & 0xfe00) == 0xfc00
+                // Link-local
+                || (segments[0] & 0xffc0) == 0xfe80
+                // Documentation
+                || (segments[0] == 0x2001 && segments[1] == 0x0db8))
+        }
+    }
+}
+
+/// Resolves host names to their public addresses only, so a name pointing into the internal
+/// network cannot be fetched. reqwest connects to the addresses returned here, on every hop.
+#[cfg(feature = "calendar")]
+struct PublicResolver;
+
+#[cfg(feature = "calendar")]
+impl reqwest::dns::Resolve for PublicResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        Box::pin(async move {
+            let host = name.as_str();
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, 0))
+                .await?
+                .filter(|addr| is_public_ip(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{} does not resolve to a public address", host).into());
+            }
+
+            Ok(Box::new(addrs.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+/// Check a (possibly redirected) request URL. Host names are vetted by [`PublicResolver`];
+/// IP literals never reach a resolver, so they are checked here after URL normalisation.
+#[cfg(feature = "calendar")]
+fn fetchable_url(url: &reqwest::Url) -> bool {
+    let host = url.host_str().unwrap_or_default();
+    let host_allowed = match host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+        Ok(ip) => is_public_ip(ip),
+        Err(_) => subscription_url(url.as_str()).is_ok(),
+    };
+
+    url.scheme() == "https" && url.username().is_empty() && host_allowed
+}
+
+#[cfg(feature = "calendar")]
+async fn fetch_events(url: &str, settings: &CalendarSettings) -> Result<Vec<IcsEvent>, CalendarError> {
+    let url = reqwest::Url::parse(url).map_err(|e| CalendarError::Invalid(e.to_string()))?;
+    if !fetchable_url(&url) {
+        return Err(CalendarError::Invalid("Calendar URL host is not allowed".to_string()));
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(settings.fetch_timeout_secs))
+        .dns_resolver(Arc::new(PublicResolver))
+        .no_proxy()
+        .redirect(reqwest::redirect::Policy::custom(|attempt| {
+            if attempt.previous().len() >= 3 {
+                attempt.error("too many redirects")
+            } else if fetchable_url(attempt.url()) {
+                attempt.follow()
+            } else {
+                attempt.stop()
+            }
+        }))
+        .build()
+        .map_err(|e| CalendarError::Fetch(e.to_string()))?;
+
+    let mut response = client
+        .get(url)
+        .header(reqwest::header::ACCEPT, "text/calendar")
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| CalendarError::Fetch(e.to_string()))?;
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| CalendarError::Fetch(e.to_string()))? {
+        body.extend_from_slice(&chunk);
+        if body.len() > settings.max_import_size {
+            return Err(CalendarError::Invalid("Calendar is too large".to_string()));
+        }
+    }
+
+    let content = String::from_utf8(body).map_err(|_| CalendarError::Invalid("Calendar is not valid UTF-8".to_string()))?;
+    ical::parse(&content).map_err(CalendarError::Invalid)
+}
+
+#[cfg(not(feature = "calendar"))]
+async fn fetch_events(_url: &str, _settings: &CalendarSettings) -> Result<Vec<IcsEvent>, CalendarError> {
+    Err(CalendarError::Invalid("Calendar subscriptions are not enabled".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscription_url() {
+        assert_eq!(
+            subscription_url(" webcal://p01-caldav.icloud.com/published/2/AbC ").unwrap(),
+            "https://p01-caldav.icloud.com/published/2/AbC"
+        );
+        assert_eq!(
+            subscription_url("https://calendar.google.com/calendar/ical/x%40group/basic.ics").unwrap(),
+            "https://calendar.google.com/calendar/ical/x%40group/basic.ics"
+        );
+
+        for url in [
+            "http://example.com/cal.ics",
+            "file:///etc/passwd",
+            "https://localhost/cal.ics",
+            "https://127.0.0.1/cal.ics",
+            "https://10.0.0.5:8443/cal.ics",
+            "https://169.254.169.254/latest",
+            "https://[::1]/cal.ics",
+            "https://user@example.com/cal.ics",
+            "https://intranet/cal.ics",
+        ] {
+            assert!(subscription_url(url).is_err(), "{} should be rejected", url);
+        }
+    }
+
+    #[test]
+    fn test_is_public_ip() {
+        for ip in ["8.8.8.8", "185.199.108.153", "2a00:1450:4001:80b::200e", "64:ff9b::808:808"] {
+            assert!(is_public_ip(ip.parse().unwrap()), "{} should be public", ip);
+        }
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:10.0.0.1",
+            "64:ff9b::a9fe:a9fe",
+        ] {
+            assert!(!is_public_ip(ip.parse().unwrap()), "{} should not be public", ip);
+        }
+    }
+
+    #[cfg(feature = "calendar")]
+    #[test]
+    fn test_fetchable_url() {
+        let fetchable = |url: &str| fetchable_url(&reqwest::Url::parse(url).unwrap());
+
+        assert!(fetchable("https://calendar.google.com/calendar/ical/basic.ics"));
+        assert!(fetchable("https://8.8.8.8/cal.ics"));
+        // Normalised by the URL parser to 127.0.0.1
+        assert!(!fetchable("https://0x7f.0.0.1/cal.ics"));
+        assert!(!fetchable("https://[::ffff:127.0.0.1]/cal.ics"));
+        assert!(!fetchable("http://calendar.google.com/cal.ics"));
+        assert!(!fetchable("https://user@calendar.google.com/cal.ics"));
+    }
+
+    #[cfg(feature = "calendar")]
+    #[tokio::test]
+    async fn test_resolver_skips_internal_addresses() {
+        use reqwest::dns::Resolve;
+
+        let name: reqwest::dns::Name = "localhost".parse().unwrap();
+        assert!(PublicResolver.resolve(name).await.is_err());
+    }
+
+    #[test]
+    fn test_split_occurrences() {
+        let date = |d: u32| NaiveDate::from_ymd_opt(2024, 12, d).unwrap();
+        let at = |d: u32, h: u32| date(d).and_hms_opt(h, 0, 0).unwrap();
+
+        let occurrences = vec![
+            ical::Occurrence { start: IcsTime::Date(date(23)), end: IcsTime::Date(date(28)) },
+            ical::Occurrence { start: IcsTime::Utc(at(16, 8)), end: IcsTime::Utc(at(16, 9)) },
+            ical::Occurrence {
+                start: IcsTime::Local(at(17, 9), None),
+                end: IcsTime::Local(at(17, 10), Some("Europe/Vienna".to_string())),
+            },
+        ];
+
+        let blocks = split_occurrences(&occurrences, "Europe/Zurich");
+        assert_eq!(blocks.dates, vec![(date(23), date(27))]);
+        assert_eq!(
+            blocks.times,
+            vec![
+                (at(16, 8), "UTC".to_string(), at(16, 9), "UTC".to_string()),
+                (at(17, 9), "Europe/Zurich".to_string(), at(17, 10), "Europe/Vienna".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,532 @@
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::models::{
+    CancellationBreakdown, CancellationParty, CancellationPolicy, CancellationStep,
+    CreateCancellationPolicyRequest, Payment, Project, ProjectActor, ProjectCancellation, ProjectEventType,
+    ProjectStatus,
+};
+use crate::services::{
+    MessageService, NewProjectEvent, NotificationService, ProjectEventService, ProjectService, TransitionError,
+};
+
+/// Facts about a project at the moment it is cancelled
+#[derive(Debug, Clone)]
+pub struct CancellationInput {
+    pub status: ProjectStatus,
+    pub cancelled_by: CancellationParty,
+    pub paid_amount: i32,
+    pub platform_fee: i32,
+    pub paid_at: Option<DateTime<Utc>>,
+    pub milestones_completed_amount: i32,
+    pub now: DateTime<Utc>,
+}
+
+pub struct CancellationService;
+
+impl CancellationService {
+    /// Apply a policy to a cancellation. Amounts are in cents; refunds are rounded
+    /// down in favour of the expert, retained platform fees are rounded to the nearest cent.
+    pub fn calculate(policy: &CancellationPolicy, input: &CancellationInput) -> CancellationBreakdown {
+        let mut steps = Vec::new();
+
+        if input.paid_amount <= 0 {
+            steps.push(step("no_payment", "Es wurde noch keine Zahlung geleistet, daher fällt keine Erstattung an.", None));
+            return CancellationBreakdown {
+                policy_id: Some(policy.id),
+                paid_amount: 0,
+                milestones_completed_amount: 0,
+                refund_percent: 0,
+                refund_amount: 0,
+                expert_compensation: 0,
+                platform_fee_retained: 0,
+                steps,
+            };
+        }
+
+        steps.push(step("paid", "Bezahlter Betrag", Some(input.paid_amount)));
+
+        let milestones = input.milestones_completed_amount.clamp(0, input.paid_amount);
+        if milestones > 0 {
+            steps.push(step(
+                "milestones_completed",
+                "Abgeschlossene Meilensteine (nicht erstattungsfähig)",
+                Some(milestones),
+            ));
+        }
+        let refundable = input.paid_amount - milestones;
+
+        let within_free_window = input
+            .paid_at
+            .map(|paid_at| input.now < paid_at + Duration::hours(policy.free_cancellation_hours as i64))
+            .unwrap_or(false);
+
+        let (percent, rule) = match input.cancelled_by {
+            CancellationParty::Expert => (
+                policy.expert_cancel_refund_percent,
+                "Stornierung durch den Experten".to_string(),
+            ),
+            CancellationParty::Admin => (100, "Stornierung durch die Plattform".to_string()),
+            CancellationParty::Client if within_free_window && milestones == 0 => (
+                100,
+                format!(
+                    "Kostenlose Stornierung innerhalb von {} Stunden nach Zahlung",
+                    policy.free_cancellation_hours
+                ),
+            ),
+            CancellationParty::Client => match input.status {
+                ProjectStatus::Delivered => (
+                    policy.refund_after_delivery_percent,
+                    "Stornierung durch den Kunden nach Lieferung".to_string(),
+                ),
+                ProjectStatus::InProgress | ProjectStatus::Revision | ProjectStatus::Disputed => (
+                    policy.refund_in_progress_percent,
+                    "Stornierung durch den Kunden während der Bearbeitung".to_string(),
+                ),
+                _ => (
+                    policy.refund_before_start_percent,
+                    "Stornierung durch den Kunden vor Arbeitsbeginn".to_string(),
+                ),
+            },
+        };
+        let percent = percent.clamp(0, 100);
+
+        let refund_amount = ((refundable as i64 * percent as i64) / 100) as i32;
+        steps.push(step(
+            "refund",
+            &format!("{}: {} % des erstattungsfähigen Betrags", rule, percent),
+            Some(refund_amount),
+        ));
+
+        let kept = input.paid_amount - refund_amount;
+        let platform_fee_retained = if policy.refund_platform_fee {
+            ((input.platform_fee as f64) * (kept as f64) / (input.paid_amount as f64)).round() as i32
+        } else {
+            input.platform_fee.min(kept)
+        };
+        steps.push(step(
+            "platform_fee",
+            if policy.refund_platform_fee {
+                "Anteilige Plattformgebühr"
+            } else {
+                "Plattformgebühr (nicht erstattungsfähig)"
+            },
+            Some(platform_fee_retained),
+        ));
+
+        let expert_compensation = kept - platform_fee_retained;
+        steps.push(step("expert_compensation", "Vergütung für den Experten", Some(expert_compensation)));
+
+        CancellationBreakdown {
+            policy_id: Some(policy.id),
+            paid_amount: input.paid_amount,
+            milestones_completed_amount: milestones,
+            refund_percent: percent,
+            refund_amount,
+            expert_compensation,
+            platform_fee_retained,
+            steps,
+        }
+    }
+
+    /// Policy of the project's service, falling back to the platform default
+    pub async fn get_policy_for_project(db: &Database, project: &Project) -> Result<CancellationPolicy, sqlx::Error> {
+        sqlx::query_as::<_, CancellationPolicy>(
+            r#"
+            SELECT cp.* FROM cancellation_policies cp
+            WHERE cp.id = (SELECT cancellation_policy_id FROM services WHERE id = $1)
+               OR cp.is_platform_default = TRUE
+            ORDER BY cp.is_platform_default ASC
+            LIMIT 1
+            "#,
+        )
+        .bind(project.service_id)
+        .fetch_one(&db.pool)
+        .await
+    }
+
+    /// Get policy by ID
+    pub async fn get_policy(db: &Database, id: Uuid) -> Result<Option<CancellationPolicy>, sqlx::Error> {
+        sqlx::query_as::<_, CancellationPolicy>("SELECT * FROM cancellation_policies WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&db.pool)
+            .await
+    }
+
+    /// Platform policies plus the user's own
+    pub async fn list_policies(db: &Database, user_id: Uuid) -> Result<Vec<CancellationPolicy>, sqlx::Error> {
+        sqlx::query_as::<_, CancellationPolicy>(
+            r#"
+            SELECT * FROM cancellation_policies
+            WHERE owner_id IS NULL OR owner_id = $1
+            ORDER BY is_platform_default DESC, owner_id NULLS FIRST, name
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&db.pool)
+        .await
+    }
+
+    /// Create a policy owned by an expert
+    pub async fn create_policy(
+        db: &Database,
+        owner_id: Uuid,
+        req: &CreateCancellationPolicyRequest,
+    ) -> Result<CancellationPolicy, sqlx::Error> {
+        sqlx::query_as::<_, CancellationPolicy>(
+            r#"
+            INSERT INTO cancellation_policies (
+                owner_id, name, description, free_cancellation_hours,
+                refund_before_start_percent, refund_in_progress_percent, refund_after_delivery_percent,
+                expert_cancel_refund_percent, refund_platform_fee
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING *
+            "#,
+        )
+        .bind(owner_id)
+        .bind(&req.name)
+        .bind(&req.description)
+        .bind(req.free_cancellation_hours)
+        .bind(req.refund_before_start_percent)
+        .bind(req.refund_in_progress_percent)
+        .bind(req.refund_after_delivery_percent)
+        .bind(req.expert_cancel_refund_percent)
+        .bind(req.refund_platform_fee)
+        .fetch_one(&db.pool)
+        .await
+    }
+
+    /// Successful project payment (tips and change request charges excluded); it may
+    /// already be partially refunded after a price reduction
+    pub async fn get_project_payment(db: &Database, project_id: Uuid) -> Result<Option<Payment>, sqlx::Error> {
+        sqlx::query_as::<_, Payment>(
+            r#"
+            SELECT * FROM payments
+            WHERE project_id = $1 AND status IN ('succeeded', 'partially_refunded')
+              AND COALESCE(metadata->>'kind', '') NOT IN ('tip', 'change_request')
+            ORDER BY created_at
+            LIMIT 1
+            "#,
+        )
+        .bind(project_id)
+        .fetch_optional(&db.pool)
+        .await
+    }
+
+    /// Compute the cancellation without applying it
+    pub async fn preview(
+        db: &Database,
+        project: &Project,
+        cancelled_by: CancellationParty,
+    ) -> Result<(Option<Payment>, CancellationBreakdown), sqlx::Error> {
+        let policy = Self::get_policy_for_project(db, project).await?;
+        let payment = Self::get_project_payment(db, project.id).await?;
+
+        let milestones_completed_amount: i64 = sqlx::query_scalar(
+            "SELECT COALESCE(SUM(amount), 0)::BIGINT FROM project_milestones WHERE project_id = $1 AND status = 'completed'",
+        )
+        .bind(project.id)
+        .fetch_one(&db.pool)
+        .await?;
+
+        let input = CancellationInput {
+            status: project.status.clone(),
+            cancelled_by,
+            paid_amount: payment.as_ref().map(|p| p.amount - p.refund_amount.unwrap_or(0)).unwrap_or(0),
+            platform_fee: payment.as_ref().map(|p| p.platform_fee).unwrap_or(0),
+            paid_at: payment.as_ref().and_then(|p| p.paid_at),
+            milestones_completed_amount: milestones_completed_amount as i32,
+            now: Utc::now(),
+        };
+
+        Ok((payment, Self::calculate(&policy, &input)))
+    }
+
+    /// Cancel the project and record the refund on its payment inside the caller's
+    /// transaction. The caller issues the gateway refund before committing, so a
+    /// failed refund leaves project and payment untouched.
+    pub async fn apply_tx(
+        conn: &mut PgConnection,
+        project: &Project,
+        cancelled_by: Uuid,
+        role: CancellationParty,
+        payment: Option<&Payment>,
+        breakdown: &CancellationBreakdown,
+        reason: Option<&str>,
+    ) -> Result<(Project, ProjectCancellation), TransitionError> {
+        let actor = match role {
+            CancellationParty::Client => ProjectActor::Client,
+            CancellationParty::Expert => ProjectActor::Expert,
+            CancellationParty::Admin => ProjectActor::Admin,
+        };
+
+        // Record the refund first so releasing the payment pays out only the compensation
+        if let Some(payment) = payment {
+            sqlx::query(
+                r#"
+                UPDATE payments
+                SET status = CASE
+                        WHEN COALESCE(refund_amount, 0) + $2 >= amount THEN 'refunded'::payment_status
+                        WHEN $2 > 0 THEN 'partially_refunded'::payment_status
+                        ELSE status
+                    END,
+                    refund_amount = NULLIF(COALESCE(refund_amount, 0) + $2, 0),
+                    refund_reason = CASE WHEN $2 > 0 THEN 'Project cancelled' ELSE refund_reason END,
+                    refunded_at = CASE WHEN $2 > 0 THEN NOW() ELSE refunded_at END,
+                    net_amount = $3,
+                    platform_fee = $4,
+                    updated_at = NOW()
+                WHERE id = $1
+                "#,
+            )
+            .bind(payment.id)
+            .bind(breakdown.refund_amount)
+            .bind(breakdown.expert_compensation)
+            .bind(breakdown.platform_fee_retained)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        let project = ProjectService::transition_tx(
+            conn,
+            project.id,
+            ProjectStatus::Cancelled,
+            actor,
+            Some(cancelled_by),
+            reason,
+        )
+        .await?;
+
+        if let (Some(payment), true) = (payment, breakdown.refund_amount > 0) {
+            ProjectEventService::record(
+                &mut *conn,
+                NewProjectEvent::system(project.id, ProjectEventType::PaymentRefunded).data(serde_json::json!({
+                    "paymentId": payment.id,
+                    "amount": breakdown.refund_amount,
+                    "currency": payment.currency,
+                    "reason": "cancellation",
+                })),
+            )
+            .await?;
+        }
+
+        let cancellation = sqlx::query_as::<_, ProjectCancellation>(
+            r#"
+            INSERT INTO project_cancellations (
+                project_id, cancelled_by, cancelled_by_role, policy_id, payment_id,
+                paid_amount, milestones_completed_amount, refund_percent, refund_amount,
+                expert_compensation, platform_fee_retained, explanation, reason
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            RETURNING *
+            "#,
+        )
+        .bind(project.id)
+        .bind(cancelled_by)
+        .bind(role.as_str())
+        .bind(breakdown.policy_id)
+        .bind(payment.map(|p| p.id))
+        .bind(breakdown.paid_amount)
+        .bind(breakdown.milestones_completed_amount)
+        .bind(breakdown.refund_percent)
+        .bind(breakdown.refund_amount)
+        .bind(breakdown.expert_compensation)
+        .bind(breakdown.platform_fee_retained)
+        .bind(sqlx::types::Json(&breakdown.steps))
+        .bind(reason)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok((project, cancellation))
+    }
+
+    /// Recorded cancellation of a project
+    pub async fn get_for_project(db: &Database, project_id: Uuid) -> Result<Option<ProjectCancellation>, sqlx::Error> {
+        sqlx::query_as::<_, ProjectCancellation>("SELECT * FROM project_cancellations WHERE project_id = $1")
+            .bind(project_id)
+            .fetch_optional(&db.pool)
+            .await
+    }
+
+    /// Explain the applied calculation to both parties
+    pub async fn notify_parties(
+        db: &Database,
+        project: &Project,
+        cancellation: &ProjectCancellation,
+    ) -> Result<(), sqlx::Error> {
+        let currency = format!("{:?}", project.currency);
+        let fmt = |amount: i32| format!("{:.2} {}", amount as f64 / 100.0, currency);
+        let by = if cancellation.cancelled_by_role == CancellationParty::Expert.as_str() {
+            "vom Experten"
+        } else if cancellation.cancelled_by_role == CancellationParty::Client.as_str() {
+            "vom Kunden"
+        } else {
+            "von der Plattform"
+        };
+
+        let summary = format!(
+            "Erstattung an den Kunden: {}. Vergütung für den Experten: {}.",
+            fmt(cancellation.refund_amount),
+            fmt(cancellation.expert_compensation)
+        );
+        let data = serde_json::json!({
+            "projectId": project.id,
+            "cancellationId": cancellation.id,
+        });
+
+        for user_id in [project.client_id, project.expert_id] {
+            NotificationService::create(
+                &db.pool,
+                user_id,
+                "project_cancelled",
+                "Projekt storniert",
+                &format!("Das Projekt \"{}\" wurde {} storniert. {}", project.title, by, summary),
+                Some(data.clone()),
+            )
+            .await?;
+        }
+
+        let mut content = format!("Das Projekt \"{}\" wurde {} storniert.\n", project.title, by);
+        if let Some(reason) = &cancellation.reason {
+            content.push_str(&format!("Grund: {}\n", reason));
+        }
+        content.push_str("\nBerechnung:\n");
+        for step in cancellation.explanation.iter() {
+            match step.amount {
+                Some(amount) => content.push_str(&format!("- {}: {}\n", step.description, fmt(amount))),
+                None => content.push_str(&format!("- {}\n", step.description)),
+            }
+        }
+
+        let recipient_id = if cancellation.cancelled_by == project.client_id {
+            project.expert_id
+        } else {
+            project.client_id
+        };
+
+        MessageService::send_system_message(
+            &db.pool,
+            cancellation.cancelled_by,
+            recipient_id,
+            Some(project.id),
+            &content,
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+fn step(code: &str, description: &str, amount: Option<i32>) -> CancellationStep {
+    CancellationStep {
+        code: code.to_string(),
+        description: description.to_string(),
+        amount,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> CancellationPolicy {
+        CancellationPolicy {
+            id: Uuid::new_v4(),
+            owner_id: None,
+            name: "Standard".to_string(),
+            description: None,
+            is_platform_default: true,
+            free_cancellation_hours: 24,
+            refund_before_start_percent: 100,
+            refund_in_progress_percent: 50,
+            refund_after_delivery_percent: 0,
+            expert_cancel_refund_percent: 100,
+            refund_platform_fee: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn input(status: ProjectStatus, by: CancellationParty, hours_since_payment: i64) -> CancellationInput {
+        let now = Utc::now();
+        CancellationInput {
+            status,
+            cancelled_by: by,
+            paid_amount: 10_000,
+            platform_fee: 10_00,
+            paid_at: Some(now - Duration::hours(hours_since_payment)),
+            milestones_completed_amount: 0,
+            now,
+        }
+    }
+
+    #[test]
+    fn test_unpaid_project_has_nothing_to_refund() {
+        let mut input = input(ProjectStatus::Accepted, CancellationParty::Client, 0);
+        input.paid_amount = 0;
+        let result = CancellationService::calculate(&policy(), &input);
+        assert_eq!(result.refund_amount, 0);
+        assert_eq!(result.expert_compensation, 0);
+        assert_eq!(result.steps[0].code, "no_payment");
+    }
+
+    #[test]
+    fn test_free_cancellation_window() {
+        let result = CancellationService::calculate(
+            &policy(),
+            &input(ProjectStatus::InProgress, CancellationParty::Client, 2),
+        );
+        assert_eq!(result.refund_percent, 100);
+        assert_eq!(result.refund_amount, 10_000);
+        assert_eq!(result.platform_fee_retained, 0);
+        assert_eq!(result.expert_compensation, 0);
+    }
+
+    #[test]
+    fn test_client_cancels_in_progress() {
+        let result = CancellationService::calculate(
+            &policy(),
+            &input(ProjectStatus::InProgress, CancellationParty::Client, 48),
+        );
+        assert_eq!(result.refund_amount, 50_00);
+        assert_eq!(result.platform_fee_retained, 5_00);
+        assert_eq!(result.expert_compensation, 45_00);
+    }
+
+    #[test]
+    fn test_completed_milestones_are_not_refunded() {
+        let mut input = input(ProjectStatus::InProgress, CancellationParty::Client, 2);
+        input.milestones_completed_amount = 40_00;
+        let result = CancellationService::calculate(&policy(), &input);
+        // Free window does not apply once a milestone is done
+        assert_eq!(result.refund_percent, 50);
+        assert_eq!(result.refund_amount, 30_00);
+        assert_eq!(result.paid_amount - result.refund_amount, 70_00);
+        assert_eq!(result.platform_fee_retained + result.expert_compensation, 70_00);
+    }
+
+    #[test]
+    fn test_expert_cancels() {
+        let result = CancellationService::calculate(
+            &policy(),
+            &input(ProjectStatus::Delivered, CancellationParty::Expert, 100),
+        );
+        assert_eq!(result.refund_amount, 10_000);
+        assert_eq!(result.expert_compensation, 0);
+    }
+
+    #[test]
+    fn test_platform_fee_not_refunded() {
+        let mut policy = policy();
+        policy.refund_platform_fee = false;
+        let result = CancellationService::calculate(
+            &policy,
+            &input(ProjectStatus::InProgress, CancellationParty::Client, 48),
+        );
+        assert_eq!(result.platform_fee_retained, 10_00);
+        assert_eq!(result.expert_compensation, 40_00);
+    }
+}
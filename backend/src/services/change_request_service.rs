@@ -0,0 +1,556 @@
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::models::{
+    BillingMode, ChangeRequestStatus, Payment, PaymentStatus, Project, ProjectActor, ProjectChangeRequest,
+    ProjectEventType, ProjectTerms,
+};
+use crate::services::{
+    MessageService, NewProjectEvent, NotificationService, PaymentService, ProjectEventService, ProjectService,
+};
+
+/// Error of a change request operation
+#[derive(Debug, thiserror::Error)]
+pub enum ChangeRequestError {
+    #[error("Change request not found")]
+    NotFound,
+
+    #[error("{0}")]
+    Conflict(String),
+
+    #[error("{0}")]
+    Invalid(String),
+
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+/// Accepted change request, with the project payment to refund at the gateway for a price decrease
+#[derive(Debug)]
+pub struct AcceptedChange {
+    pub change_request: ProjectChangeRequest,
+    pub project: Project,
+    pub refunded_payment: Option<Payment>,
+}
+
+pub struct ChangeRequestService;
+
+impl ChangeRequestService {
+    /// Change requests of a project, newest first
+    pub async fn list(db: &Database, project_id: Uuid) -> Result<Vec<ProjectChangeRequest>, sqlx::Error> {
+        sqlx::query_as::<_, ProjectChangeRequest>(
+            "SELECT * FROM project_change_requests WHERE project_id = $1 ORDER BY created_at DESC",
+        )
+        .bind(project_id)
+        .fetch_all(&db.pool)
+        .await
+    }
+
+    pub async fn get(db: &Database, project_id: Uuid, id: Uuid) -> Result<Option<ProjectChangeRequest>, sqlx::Error> {
+        sqlx::query_as::<_, ProjectChangeRequest>(
+            "SELECT * FROM project_change_requests WHERE id = $1 AND project_id = $2",
+        )
+        .bind(id)
+        .bind(project_id)
+        .fetch_optional(&db.pool)
+        .await
+    }
+
+    /// Card payment of the project that price changes are settled against
+    async fn settled_payment(conn: &mut PgConnection, project: &Project) -> Result<Option<Payment>, sqlx::Error> {
+        if project.billing_mode != BillingMode::Card {
+            return Ok(None);
+        }
+
+        sqlx::query_as::<_, Payment>(
+            r#"
+            SELECT * FROM payments
+            WHERE project_id = $1 AND status IN ('succeeded', 'partially_refunded')
+              AND COALESCE(metadata->>'kind', '') NOT IN ('tip', 'change_request')
+            ORDER BY created_at
+            LIMIT 1
+            FOR UPDATE
+            "#,
+        )
+        .bind(project.id)
+        .fetch_optional(conn)
+        .await
+    }
+
+    /// A price decrease is refunded from the project payment, so it cannot exceed what is left of it
+    fn check_refundable(payment: Option<&Payment>, price_difference: i32) -> Result<(), ChangeRequestError> {
+        if let Some(payment) = payment
+            && price_difference < 0
+            && -price_difference > payment.amount - payment.refund_amount.unwrap_or(0)
+        {
+            return Err(ChangeRequestError::Invalid(
+                "The price cannot be reduced by more than the remaining payment".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    async fn lock_project(conn: &mut PgConnection, project_id: Uuid) -> Result<Project, ChangeRequestError> {
+        let project = sqlx::query_as::<_, Project>("SELECT * FROM projects WHERE id = $1 FOR UPDATE")
+            .bind(project_id)
+            .fetch_optional(&mut *conn)
+            .await?
+            .ok_or(ChangeRequestError::NotFound)?;
+
+        if !project.status.accepts_change_requests() {
+            return Err(ChangeRequestError::Conflict(
+                "The terms of this project can no longer be changed".to_string(),
+            ));
+        }
+
+        Ok(project)
+    }
+
+    async fn lock_pending(
+        conn: &mut PgConnection,
+        project_id: Uuid,
+        id: Uuid,
+    ) -> Result<ProjectChangeRequest, ChangeRequestError> {
+        let request = sqlx::query_as::<_, ProjectChangeRequest>(
+            "SELECT * FROM project_change_requests WHERE id = $1 AND project_id = $2 FOR UPDATE",
+        )
+        .bind(id)
+        .bind(project_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or(ChangeRequestError::NotFound)?;
+
+        if request.status != ChangeRequestStatus::Pending {
+            return Err(ChangeRequestError::Conflict("Change request has already been answered".to_string()));
+        }
+
+        Ok(request)
+    }
+
+    /// Propose new terms; the other party is asked to accept or reject them
+    pub async fn create(
+        db: &Database,
+        project_id: Uuid,
+        actor: ProjectActor,
+        user_id: Uuid,
+        description: &str,
+        terms: &ProjectTerms,
+    ) -> Result<ProjectChangeRequest, ChangeRequestError> {
+        let mut tx = db.pool.begin().await?;
+        let project = Self::lock_project(&mut tx, project_id).await?;
+
+        let open: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM project_change_requests WHERE project_id = $1 AND status = 'pending')",
+        )
+        .bind(project_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        if open {
+            return Err(ChangeRequestError::Conflict(
+                "There is already an open change request for this project".to_string(),
+            ));
+        }
+
+        let payment = Self::settled_payment(&mut tx, &project).await?;
+        Self::check_refundable(payment.as_ref(), terms.price - project.price)?;
+
+        let previous = ProjectTerms::of(&project);
+        let request = sqlx::query_as::<_, ProjectChangeRequest>(
+            r#"
+            INSERT INTO project_change_requests (
+                project_id, requested_by, requested_by_role, description,
+                previous_price, new_price, previous_delivery_date, new_delivery_date,
+                previous_revisions_allowed, new_revisions_allowed
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING *
+            "#,
+        )
+        .bind(project_id)
+        .bind(user_id)
+        .bind(actor)
+        .bind(description)
+        .bind(previous.price)
+        .bind(terms.price)
+        .bind(previous.delivery_date)
+        .bind(terms.delivery_date)
+        .bind(previous.revisions_allowed)
+        .bind(terms.revisions_allowed)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        ProjectEventService::record(
+            &mut *tx,
+            NewProjectEvent::new(project_id, ProjectEventType::ChangeRequested, actor, Some(user_id))
+                .message(Some(description))
+                .data(serde_json::json!({
+                    "changeRequestId": request.id,
+                    "previous": previous,
+                    "proposed": terms,
+                    "priceDifference": request.price_difference(),
+                })),
+        )
+        .await?;
+
+        let recipient = if actor == ProjectActor::Client { project.expert_id } else { project.client_id };
+        NotificationService::create(
+            &mut *tx,
+            recipient,
+            "change_requested",
+            "Änderungsanfrage erhalten",
+            &format!("Für das Projekt \"{}\" wurde eine Änderung vorgeschlagen.", project.title),
+            Some(serde_json::json!({ "projectId": project_id, "changeRequestId": request.id })),
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(request)
+    }
+
+    /// Accept a change request inside the caller's transaction: the project takes over the
+    /// new terms, an increase becomes a pending additional charge and a decrease is refunded
+    /// from the project payment. The caller issues the gateway refund before committing, so
+    /// a failed refund leaves the request pending.
+    pub async fn accept_tx(
+        conn: &mut PgConnection,
+        project_id: Uuid,
+        id: Uuid,
+        actor: ProjectActor,
+        user_id: Uuid,
+        message: Option<&str>,
+    ) -> Result<AcceptedChange, ChangeRequestError> {
+        let project = Self::lock_project(conn, project_id).await?;
+        let request = Self::lock_pending(conn, project_id, id).await?;
+
+        if request.new_revisions_allowed < project.revisions_used {
+            return Err(ChangeRequestError::Invalid(format!(
+                "{} revisions have already been used",
+                project.revisions_used
+            )));
+        }
+
+        let difference = request.new_price - project.price;
+        let payment = Self::settled_payment(conn, &project).await?;
+        Self::check_refundable(payment.as_ref(), difference)?;
+
+        let (platform_fee, expert_payout) = ProjectService::calculate_fees(request.new_price);
+        let updated = sqlx::query_as::<_, Project>(
+            r#"
+            UPDATE projects
+            SET price = $2, platform_fee = $3, expert_payout = $4,
+                delivery_date = $5, revisions_allowed = $6, updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(project_id)
+        .bind(request.new_price)
+        .bind(platform_fee)
+        .bind(expert_payout)
+        .bind(request.new_delivery_date)
+        .bind(request.new_revisions_allowed)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        // Unpaid projects are simply charged the new price; paid ones settle the difference
+        let mut settlement_payment = None;
+        let mut refunded_payment = None;
+        if let Some(payment) = payment {
+            if difference > 0 {
+                let (fee, net) = ProjectService::calculate_fees(difference);
+                let charge_id: Uuid = sqlx::query_scalar(
+                    r#"
+                    INSERT INTO payments (project_id, payer_id, payee_id, amount, currency, platform_fee, net_amount, description, metadata)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                    RETURNING id
+                    "#,
+                )
+                .bind(project_id)
+                .bind(project.client_id)
+                .bind(project.expert_id)
+                .bind(difference)
+                .bind(&payment.currency)
+                .bind(fee)
+                .bind(net)
+                .bind(format!("Change request - {}", project.title))
+                .bind(sqlx::types::Json(serde_json::json!({
+                    "kind": "change_request",
+                    "change_request_id": request.id,
+                })))
+                .fetch_one(&mut *conn)
+                .await?;
+                settlement_payment = Some(charge_id);
+            } else if difference < 0 {
+                let refund = -difference;
+                let remaining = payment.amount - payment.refund_amount.unwrap_or(0) - refund;
+                let (fee, net) = ProjectService::calculate_fees(remaining);
+                let refunded = sqlx::query_as::<_, Payment>(
+                    r#"
+                    UPDATE payments
+                    SET status = CASE WHEN $3 <= 0 THEN 'refunded'::payment_status ELSE 'partially_refunded'::payment_status END,
+                        refund_amount = COALESCE(refund_amount, 0) + $2,
+                        refund_reason = 'Price reduced by change request',
+                        refunded_at = NOW(),
+                        platform_fee = $4,
+                        net_amount = $5,
+                        updated_at = NOW()
+                    WHERE id = $1
+                    RETURNING *
+                    "#,
+                )
+                .bind(payment.id)
+                .bind(refund)
+                .bind(remaining)
+                .bind(fee)
+                .bind(net)
+                .fetch_one(&mut *conn)
+                .await?;
+
+                ProjectEventService::record(
+                    &mut *conn,
+                    NewProjectEvent::system(project_id, ProjectEventType::PaymentRefunded).data(serde_json::json!({
+                        "paymentId": payment.id,
+                        "amount": refund,
+                        "currency": payment.currency,
+                        "reason": "change_request",
+                        "changeRequestId": request.id,
+                    })),
+                )
+                .await?;
+
+                settlement_payment = Some(payment.id);
+                refunded_payment = Some(refunded);
+            }
+        }
+
+        let request = sqlx::query_as::<_, ProjectChangeRequest>(
+            r#"
+            UPDATE project_change_requests
+            SET status = 'accepted', responded_by = $2, response_message = $3, responded_at = NOW(),
+                payment_id = $4, refund_amount = $5
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(message)
+        .bind(settlement_payment)
+        .bind(refunded_payment.as_ref().map(|_| -difference))
+        .fetch_one(&mut *conn)
+        .await?;
+
+        ProjectEventService::record(
+            &mut *conn,
+            NewProjectEvent::new(project_id, ProjectEventType::ChangeAccepted, actor, Some(user_id))
+                .message(message)
+                .data(serde_json::json!({
+                    "changeRequestId": request.id,
+                    "previous": ProjectTerms::of(&project),
+                    "agreed": ProjectTerms::of(&updated),
+                    "priceDifference": difference,
+                    "paymentId": request.payment_id,
+                })),
+        )
+        .await?;
+
+        if let Some(requested_by) = request.requested_by {
+            NotificationService::create(
+                &mut *conn,
+                requested_by,
+                "change_accepted",
+                "Änderung angenommen",
+                &format!("Ihre Änderungsanfrage für \"{}\" wurde angenommen.", project.title),
+                Some(serde_json::json!({ "projectId": project_id, "changeRequestId": request.id })),
+            )
+            .await?;
+        }
+
+        Ok(AcceptedChange {
+            change_request: request,
+            project: updated,
+            refunded_payment,
+        })
+    }
+
+    /// Reject (other party) or withdraw (requester) a pending change request
+    pub async fn close(
+        db: &Database,
+        project_id: Uuid,
+        id: Uuid,
+        status: ChangeRequestStatus,
+        actor: ProjectActor,
+        user_id: Uuid,
+        message: Option<&str>,
+    ) -> Result<ProjectChangeRequest, ChangeRequestError> {
+        let event_type = match status {
+            ChangeRequestStatus::Rejected => ProjectEventType::ChangeRejected,
+            ChangeRequestStatus::Withdrawn => ProjectEventType::ChangeWithdrawn,
+            _ => return Err(ChangeRequestError::Invalid("Unsupported change request status".to_string())),
+        };
+
+        let mut tx = db.pool.begin().await?;
+        Self::lock_pending(&mut tx, project_id, id).await?;
+
+        let request = sqlx::query_as::<_, ProjectChangeRequest>(
+            r#"
+            UPDATE project_change_requests
+            SET status = $2, responded_by = $3, response_message = $4, responded_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(status)
+        .bind(user_id)
+        .bind(message)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        ProjectEventService::record(
+            &mut *tx,
+            NewProjectEvent::new(project_id, event_type, actor, Some(user_id))
+                .message(message)
+                .data(serde_json::json!({ "changeRequestId": request.id })),
+        )
+        .await?;
+
+        if status == ChangeRequestStatus::Rejected
+            && let Some(requested_by) = request.requested_by
+        {
+            NotificationService::create(
+                &mut *tx,
+                requested_by,
+                "change_rejected",
+                "Änderung abgelehnt",
+                "Ihre Änderungsanfrage wurde abgelehnt.",
+                Some(serde_json::json!({ "projectId": project_id, "changeRequestId": request.id })),
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(request)
+    }
+
+    /// Attach the gateway checkout session of the additional charge
+    pub async fn set_checkout_session(
+        db: &Database,
+        id: Uuid,
+        session_id: &str,
+    ) -> Result<ProjectChangeRequest, sqlx::Error> {
+        sqlx::query_as::<_, ProjectChangeRequest>(
+            "UPDATE project_change_requests SET stripe_checkout_session_id = $2 WHERE id = $1 RETURNING *",
+        )
+        .bind(id)
+        .bind(session_id)
+        .fetch_one(&db.pool)
+        .await
+    }
+
+    /// Mark the additional charge of a change request as paid once the gateway confirms it.
+    ///
+    /// Issues a paid invoice for the difference and informs the expert. Returns `None` if
+    /// the charge was already processed, so repeated webhook deliveries are harmless.
+    pub async fn mark_paid(
+        db: &Database,
+        id: Uuid,
+        stripe_payment_intent_id: Option<&str>,
+    ) -> Result<Option<ProjectChangeRequest>, sqlx::Error> {
+        let mut tx = db.pool.begin().await?;
+
+        // Marked paid in the same transaction, so a failed step leaves the charge for the retry
+        let request: Option<ProjectChangeRequest> = sqlx::query_as(
+            r#"
+            UPDATE project_change_requests
+            SET paid_at = NOW()
+            WHERE id = $1 AND status = 'accepted' AND paid_at IS NULL AND refund_amount IS NULL
+              AND payment_id IS NOT NULL
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(request) = request else {
+            return Ok(None);
+        };
+        let Some(payment_id) = request.payment_id else {
+            tx.commit().await?;
+            return Ok(Some(request));
+        };
+
+        let payment =
+            PaymentService::update_status(&mut *tx, payment_id, PaymentStatus::Succeeded, stripe_payment_intent_id)
+                .await?;
+        let project = sqlx::query_as::<_, Project>("SELECT * FROM projects WHERE id = $1")
+            .bind(request.project_id)
+            .fetch_one(&mut *tx)
+            .await?;
+        // Paid after completion: nothing else releases it any more
+        if project.status.is_terminal() {
+            PaymentService::release(&mut *tx, payment_id).await?;
+        }
+
+        ProjectEventService::record(
+            &mut *tx,
+            NewProjectEvent::system(project.id, ProjectEventType::PaymentReceived).data(serde_json::json!({
+                "kind": "change_request",
+                "changeRequestId": request.id,
+                "paymentId": payment.id,
+                "amount": payment.amount,
+                "currency": payment.currency,
+            })),
+        )
+        .await?;
+
+        // Invoice from expert to client
+        let line_items = serde_json::json!([{
+            "description": format!("Leistungsänderung – {}", project.title),
+            "quantity": 1,
+            "unitPrice": payment.amount,
+            "amount": payment.amount,
+        }]);
+        let invoice = PaymentService::create_invoice(
+            &mut *tx,
+            project.expert_id,
+            project.client_id,
+            Some(project.id),
+            payment.amount,
+            0.0,
+            &payment.currency,
+            line_items,
+        )
+        .await?;
+        PaymentService::mark_invoice_paid(&mut *tx, invoice.id, Some(payment.id)).await?;
+
+        let amount = format!("{:.2} {}", payment.amount as f64 / 100.0, payment.currency);
+        NotificationService::create(
+            &mut *tx,
+            project.expert_id,
+            "change_paid",
+            "Mehrpreis bezahlt",
+            &format!("Der Kunde hat den Mehrpreis von {} für \"{}\" bezahlt.", amount, project.title),
+            Some(serde_json::json!({ "projectId": project.id, "changeRequestId": request.id })),
+        )
+        .await?;
+
+        MessageService::send_system_message_tx(
+            &mut tx,
+            project.client_id,
+            project.expert_id,
+            Some(project.id),
+            &format!("Der Mehrpreis von {} für die vereinbarte Änderung wurde bezahlt.", amount),
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(Some(request))
+    }
+}
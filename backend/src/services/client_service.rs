@@ -1,13 +1,15 @@
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool};
 use uuid::Uuid;
 
 use crate::models::{
     ClientProfile, CreateClientProfileRequest, UpdateClientProfileRequest,
     ProjectPosting, CreateProjectPostingRequest, UpdateProjectPostingRequest,
     ProjectPostingFilters, PaginatedResponse, PaginationMeta,
-    BookingRequest, CreateBookingRequest, RespondBookingRequest, BookingStatus,
+    BookingRequest, RespondBookingRequest, BookingStatus,
     Proposal, CreateProposalRequest,
 };
+use crate::config::PostingSettings;
+use crate::services::{ExpertMetricsService, PostingService};
 
 pub struct ClientService;
 
@@ -75,10 +77,11 @@ impl ClientService {
 
     // ==================== Project Postings ====================
 
-    pub async fn create_project_posting(pool: &PgPool, client_id: Uuid, req: CreateProjectPostingRequest) -> Result<ProjectPosting, sqlx::Error> {
+    pub async fn create_project_posting(pool: &PgPool, client_id: Uuid, req: CreateProjectPostingRequest, settings: &PostingSettings) -> Result<ProjectPosting, sqlx::Error> {
+        let expires_at = PostingService::expiry_for(chrono::Utc::now(), req.deadline, settings.lifetime_days);
         let posting = sqlx::query_as::<_, ProjectPosting>(
-            r#"INSERT INTO project_postings (client_id, title, description, requirements, category_id, skills_required, tools_required, budget_type, budget_min, budget_max, currency, deadline, estimated_duration, is_urgent)
-               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+            r#"INSERT INTO project_postings (client_id, title, description, requirements, category_id, skills_required, tools_required, budget_type, budget_min, budget_max, currency, deadline, estimated_duration, is_urgent, visibility, expires_at)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
                RETURNING *"#
         )
         .bind(client_id)
@@ -95,6 +98,8 @@ impl ClientService {
         .bind(req.deadline)
         .bind(&req.estimated_duration)
         .bind(req.is_urgent.unwrap_or(false))
+        .bind(req.visibility.unwrap_or_default())
+        .bind(expires_at)
         .fetch_one(pool)
         .await?;
         Ok(posting)
@@ -110,8 +115,9 @@ impl ClientService {
 
     pub async fn list_project_postings(pool: &PgPool, filters: ProjectPostingFilters, page: u32, per_page: u32) -> Result<PaginatedResponse<ProjectPosting>, sqlx::Error> {
         let offset = (page - 1) * per_page;
-        let mut query = String::from("SELECT * FROM project_postings WHERE 1=1");
-        let mut count_query = String::from("SELECT COUNT(*) FROM project_postings WHERE 1=1");
+        // Invite-only postings are only shown to invited experts
+        let mut query = String::from("SELECT * FROM project_postings WHERE visibility = 'public'");
+        let mut count_query = String::from("SELECT COUNT(*) FROM project_postings WHERE visibility = 'public'");
 
         if let Some(ref status) = filters.status {
             let filter = format!(" AND status = '{:?}'", status).to_lowercase();
@@ -140,6 +146,7 @@ impl ClientService {
                title = COALESCE($3, title),
                description = COALESCE($4, description),
                requirements = COALESCE($5, requirements),
+               visibility = COALESCE($6, visibility),
                updated_at = NOW()
                WHERE id = $1 AND client_id = $2 RETURNING *"#
         )
@@ -148,33 +155,37 @@ impl ClientService {
         .bind(&req.title)
         .bind(&req.description)
         .bind(&req.requirements)
+        .bind(req.visibility)
         .fetch_one(pool)
         .await?;
         Ok(posting)
     }
 
-    // ==================== Booking Requests ====================
-
-    pub async fn create_booking_request(pool: &PgPool, client_id: Uuid, req: CreateBookingRequest) -> Result<BookingRequest, sqlx::Error> {
-        let booking = sqlx::query_as::<_, BookingRequest>(
-            r#"INSERT INTO booking_requests (client_id, expert_id, service_id, package_id, message, proposed_budget, currency, proposed_start_date, proposed_deadline)
-               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
-               RETURNING *"#
+    /// Postings of a client of any visibility, newest first
+    pub async fn list_client_postings(pool: &PgPool, client_id: Uuid, page: u32, per_page: u32) -> Result<PaginatedResponse<ProjectPosting>, sqlx::Error> {
+        let offset = (page - 1) * per_page;
+        let postings = sqlx::query_as::<_, ProjectPosting>(
+            "SELECT * FROM project_postings WHERE client_id = $1 ORDER BY created_at DESC LIMIT $2 OFFSET $3"
         )
         .bind(client_id)
-        .bind(req.expert_id)
-        .bind(req.service_id)
-        .bind(req.package_id)
-        .bind(&req.message)
-        .bind(req.proposed_budget)
-        .bind(&req.currency)
-        .bind(req.proposed_start_date)
-        .bind(req.proposed_deadline)
-        .fetch_one(pool)
+        .bind(per_page as i64)
+        .bind(offset as i64)
+        .fetch_all(pool)
         .await?;
-        Ok(booking)
+
+        let total: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM project_postings WHERE client_id = $1")
+            .bind(client_id)
+            .fetch_one(pool)
+            .await?;
+
+        Ok(PaginatedResponse {
+            data: postings,
+            meta: PaginationMeta::new(page, per_page, total.0),
+        })
     }
 
+    // ==================== Booking Requests ====================
+
     pub async fn get_booking_request(pool: &PgPool, id: Uuid) -> Result<Option<BookingRequest>, sqlx::Error> {
         let booking = sqlx::query_as::<_, BookingRequest>("SELECT * FROM booking_requests WHERE id = $1")
             .bind(id)
@@ -227,28 +238,47 @@ impl ClientService {
         })
     }
 
-    pub async fn respond_to_booking(pool: &PgPool, id: Uuid, expert_id: Uuid, req: RespondBookingRequest) -> Result<BookingRequest, sqlx::Error> {
+    /// Answer a pending booking request; `None` if it does not exist or is no longer pending
+    pub async fn respond_to_booking(pool: &PgPool, id: Uuid, expert_id: Uuid, req: RespondBookingRequest) -> Result<Option<BookingRequest>, sqlx::Error> {
         let status = if req.accept { BookingStatus::Accepted } else { BookingStatus::Declined };
-        let booking = sqlx::query_as::<_, BookingRequest>(
+        let mut tx = pool.begin().await?;
+
+        let Some(booking) = sqlx::query_as::<_, BookingRequest>(
             r#"UPDATE booking_requests SET
                status = $3,
                expert_response = $4,
                responded_at = NOW(),
                updated_at = NOW()
-               WHERE id = $1 AND expert_id = $2 RETURNING *"#
+               WHERE id = $1 AND expert_id = $2 AND status = 'pending' AND expires_at > NOW()
+               RETURNING *"#
         )
         .bind(id)
         .bind(expert_id)
         .bind(status)
         .bind(&req.response)
-        .fetch_one(pool)
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        ExpertMetricsService::on_booking_response(
+            &mut tx,
+            booking.id,
+            booking.expert_id,
+            booking.created_at,
+            booking.responded_at.unwrap_or(booking.updated_at),
+        )
         .await?;
-        Ok(booking)
+
+        tx.commit().await?;
+
+        Ok(Some(booking))
     }
 
     // ==================== Proposals ====================
 
-    pub async fn create_proposal(pool: &PgPool, expert_id: Uuid, req: CreateProposalRequest) -> Result<Proposal, sqlx::Error> {
+    pub async fn create_proposal(conn: &mut PgConnection, expert_id: Uuid, req: CreateProposalRequest) -> Result<Proposal, sqlx::Error> {
         let proposal = sqlx::query_as::<_, Proposal>(
             r#"INSERT INTO proposals (project_posting_id, expert_id, cover_letter, proposed_price, currency, proposed_duration, proposed_milestones)
                VALUES ($1, $2, $3, $4, $5, $6, $7)
@@ -261,13 +291,13 @@ impl ClientService {
         .bind(&req.currency)
         .bind(&req.proposed_duration)
         .bind(&req.proposed_milestones)
-        .fetch_one(pool)
+        .fetch_one(&mut *conn)
         .await?;
 
         // Increment proposal count
         sqlx::query("UPDATE project_postings SET proposal_count = proposal_count + 1 WHERE id = $1")
             .bind(req.project_posting_id)
-            .execute(pool)
+            .execute(&mut *conn)
             .await?;
 
         Ok(proposal)
@@ -295,24 +325,6 @@ impl ClientService {
         })
     }
 
-    pub async fn accept_proposal(pool: &PgPool, proposal_id: Uuid, client_id: Uuid) -> Result<Proposal, sqlx::Error> {
-        // First verify the client owns the project posting
-        let proposal = sqlx::query_as::<_, Proposal>(
-            r#"UPDATE proposals p SET
-               status = 'accepted',
-               accepted_at = NOW(),
-               updated_at = NOW()
-               FROM project_postings pp
-               WHERE p.id = $1 AND p.project_posting_id = pp.id AND pp.client_id = $2
-               RETURNING p.*"#
-        )
-        .bind(proposal_id)
-        .bind(client_id)
-        .fetch_one(pool)
-        .await?;
-        Ok(proposal)
-    }
-
     pub async fn delete_project_posting(pool: &PgPool, id: Uuid, client_id: Uuid) -> Result<(), sqlx::Error> {
         sqlx::query("DELETE FROM project_postings WHERE id = $1 AND client_id = $2")
             .bind(id)
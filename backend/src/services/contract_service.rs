@@ -0,0 +1,497 @@
+use chrono::Utc;
+use sha2::{Digest, Sha256};
+use sqlx::PgConnection;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::models::{
+    CompanyDetails, ContractKind, ContractSignature, ContractTemplate, CreateContractTemplateRequest, Language,
+    Project, ProjectActor, ProjectContract, ProjectContractWithSignatures, ProjectEventType,
+    UpdateContractTemplateRequest, render_contract,
+};
+use crate::services::{NewProjectEvent, NotificationService, ProjectEventService};
+use crate::utils::pdf::render_text_pdf;
+
+/// Error of a contract operation
+#[derive(Debug, thiserror::Error)]
+pub enum ContractError {
+    #[error("Contract not found")]
+    NotFound,
+
+    #[error("No contract template available for this kind")]
+    TemplateMissing,
+
+    #[error("{0}")]
+    Conflict(String),
+
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+/// Signature details captured with the click
+#[derive(Debug, Clone)]
+pub struct NewSignature {
+    pub user_id: Uuid,
+    pub party: ProjectActor,
+    pub signer_name: String,
+    pub document_hash: String,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+/// User row with the details shown for a contract party
+#[derive(Debug, sqlx::FromRow)]
+struct PartyRow {
+    first_name: String,
+    last_name: String,
+    email: String,
+    country: String,
+    vat_id: Option<String>,
+    billing_address: Option<sqlx::types::Json<CompanyDetails>>,
+    company_name: Option<String>,
+}
+
+pub struct ContractService;
+
+impl ContractService {
+    // ==================== Templates ====================
+
+    pub async fn list_templates(db: &Database) -> Result<Vec<ContractTemplate>, sqlx::Error> {
+        sqlx::query_as::<_, ContractTemplate>(
+            "SELECT * FROM contract_templates ORDER BY kind, language, is_active DESC, version DESC",
+        )
+        .fetch_all(&db.pool)
+        .await
+    }
+
+    /// Create a template; the active template of the same kind and language is retired
+    pub async fn create_template(
+        db: &Database,
+        admin_id: Uuid,
+        req: &CreateContractTemplateRequest,
+    ) -> Result<ContractTemplate, sqlx::Error> {
+        let mut tx = db.pool.begin().await?;
+
+        let version: i32 = sqlx::query_scalar(
+            "SELECT COALESCE(MAX(version), 0) + 1 FROM contract_templates WHERE kind = $1 AND language = $2",
+        )
+        .bind(req.kind)
+        .bind(&req.language)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query("UPDATE contract_templates SET is_active = FALSE WHERE kind = $1 AND language = $2 AND is_active")
+            .bind(req.kind)
+            .bind(&req.language)
+            .execute(&mut *tx)
+            .await?;
+
+        let template = sqlx::query_as::<_, ContractTemplate>(
+            r#"
+            INSERT INTO contract_templates (kind, language, title, body, version, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING *
+            "#,
+        )
+        .bind(req.kind)
+        .bind(&req.language)
+        .bind(&req.title)
+        .bind(&req.body)
+        .bind(version)
+        .bind(admin_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(template)
+    }
+
+    /// Update a template in place; text changes raise the version. Generated contracts keep their text.
+    pub async fn update_template(
+        db: &Database,
+        id: Uuid,
+        req: &UpdateContractTemplateRequest,
+    ) -> Result<Option<ContractTemplate>, ContractError> {
+        let mut tx = db.pool.begin().await?;
+
+        if req.is_active == Some(true) {
+            let other_active: bool = sqlx::query_scalar(
+                r#"
+                SELECT EXISTS (
+                    SELECT 1 FROM contract_templates o JOIN contract_templates t ON t.id = $1
+                    WHERE o.kind = t.kind AND o.language = t.language AND o.is_active AND o.id <> t.id
+                )
+                "#,
+            )
+            .bind(id)
+            .fetch_one(&mut *tx)
+            .await?;
+            if other_active {
+                return Err(ContractError::Conflict(
+                    "Another template of this kind and language is active".to_string(),
+                ));
+            }
+        }
+
+        let template = sqlx::query_as::<_, ContractTemplate>(
+            r#"
+            UPDATE contract_templates
+            SET title = COALESCE($2, title),
+                body = COALESCE($3, body),
+                is_active = COALESCE($4, is_active),
+                version = CASE WHEN $2 IS NOT NULL OR $3 IS NOT NULL THEN version + 1 ELSE version END
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(&req.title)
+        .bind(&req.body)
+        .bind(req.is_active)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(template)
+    }
+
+    /// Active template for a language, falling back to German
+    async fn active_template(
+        conn: &mut PgConnection,
+        kind: ContractKind,
+        language: &Language,
+    ) -> Result<Option<ContractTemplate>, sqlx::Error> {
+        sqlx::query_as::<_, ContractTemplate>(
+            r#"
+            SELECT * FROM contract_templates
+            WHERE kind = $1 AND is_active AND language IN ($2, 'de')
+            ORDER BY language = $2 DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(kind)
+        .bind(language)
+        .fetch_optional(conn)
+        .await
+    }
+
+    // ==================== Project contracts ====================
+
+    /// Name, address and contact of a party as used on invoices
+    async fn party_details(conn: &mut PgConnection, user_id: Uuid) -> Result<CompanyDetails, sqlx::Error> {
+        let party = sqlx::query_as::<_, PartyRow>(
+            r#"
+            SELECT u.first_name, u.last_name, u.email, u.country::text AS country, u.vat_id, u.billing_address,
+                   cp.company_name
+            FROM users u
+            LEFT JOIN client_profiles cp ON cp.user_id = u.id
+            WHERE u.id = $1
+            "#,
+        )
+        .bind(user_id)
+        .fetch_one(conn)
+        .await?;
+
+        let mut details = party.billing_address.map(|a| a.0).unwrap_or_default();
+        details.name = party
+            .company_name
+            .or(details.name)
+            .or(Some(format!("{} {}", party.first_name, party.last_name)));
+        details.email = Some(party.email);
+        details.vat_id = party.vat_id.or(details.vat_id);
+        details.country = details.country.or(Some(party.country.to_uppercase()));
+
+        Ok(details)
+    }
+
+    fn address(details: &CompanyDetails) -> String {
+        let city = [details.postal_code.as_deref(), details.city.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        [details.address_line1.as_deref(), details.address_line2.as_deref(), Some(city.as_str()), details.country.as_deref()]
+            .into_iter()
+            .flatten()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Values of the name, address, email and VAT ID placeholders of a party
+    fn party_values(keys: [&'static str; 4], details: CompanyDetails) -> [(&'static str, String); 4] {
+        let address = Self::address(&details);
+        [
+            (keys[0], details.name.unwrap_or_default()),
+            (keys[1], address),
+            (keys[2], details.email.unwrap_or_default()),
+            (keys[3], details.vat_id.unwrap_or_default()),
+        ]
+    }
+
+    /// Placeholder values of a project
+    async fn values(
+        conn: &mut PgConnection,
+        project: &Project,
+        platform_name: &str,
+    ) -> Result<HashMap<&'static str, String>, sqlx::Error> {
+        let mut values = HashMap::from([
+            ("date", Utc::now().format("%d.%m.%Y").to_string()),
+            ("platform.name", platform_name.to_string()),
+            ("project.title", project.title.clone()),
+            ("project.description", project.description.clone()),
+            ("project.price", format!("{:.2} {:?}", project.price as f64 / 100.0, project.currency)),
+            (
+                "project.delivery_date",
+                project.delivery_date.map(|d| d.format("%d.%m.%Y").to_string()).unwrap_or_default(),
+            ),
+        ]);
+
+        let client = Self::party_details(&mut *conn, project.client_id).await?;
+        values.extend(Self::party_values(["client.name", "client.address", "client.email", "client.vat_id"], client));
+        let expert = Self::party_details(&mut *conn, project.expert_id).await?;
+        values.extend(Self::party_values(["expert.name", "expert.address", "expert.email", "expert.vat_id"], expert));
+
+        Ok(values)
+    }
+
+    pub async fn list_for_project(
+        db: &Database,
+        project_id: Uuid,
+    ) -> Result<Vec<ProjectContractWithSignatures>, sqlx::Error> {
+        let contracts = sqlx::query_as::<_, ProjectContract>(
+            "SELECT * FROM project_contracts WHERE project_id = $1 ORDER BY created_at",
+        )
+        .bind(project_id)
+        .fetch_all(&db.pool)
+        .await?;
+
+        let ids: Vec<Uuid> = contracts.iter().map(|c| c.id).collect();
+        let signatures = sqlx::query_as::<_, ContractSignature>(
+            "SELECT * FROM contract_signatures WHERE contract_id = ANY($1) ORDER BY signed_at",
+        )
+        .bind(&ids)
+        .fetch_all(&db.pool)
+        .await?;
+
+        Ok(contracts
+            .into_iter()
+            .map(|contract| ProjectContractWithSignatures {
+                signatures: signatures.iter().filter(|s| s.contract_id == contract.id).cloned().collect(),
+                contract,
+            })
+            .collect())
+    }
+
+    /// PDF of a contract: (title, bytes)
+    pub async fn get_document(
+        db: &Database,
+        project_id: Uuid,
+        id: Uuid,
+    ) -> Result<Option<(String, Vec<u8>)>, sqlx::Error> {
+        sqlx::query_as("SELECT title, document FROM project_contracts WHERE id = $1 AND project_id = $2")
+            .bind(id)
+            .bind(project_id)
+            .fetch_optional(&db.pool)
+            .await
+    }
+
+    /// Render the active template with the project's data and attach it to the project.
+    /// An unsigned contract of the same kind is replaced, e.g. after the parties updated their details.
+    pub async fn generate(
+        db: &Database,
+        project: &Project,
+        kind: ContractKind,
+        language: &Language,
+        actor: ProjectActor,
+        user_id: Uuid,
+        platform_name: &str,
+    ) -> Result<ProjectContract, ContractError> {
+        let mut tx = db.pool.begin().await?;
+
+        let signed: Option<bool> = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (SELECT 1 FROM contract_signatures s WHERE s.contract_id = c.id)
+            FROM project_contracts c
+            WHERE c.project_id = $1 AND c.kind = $2
+            FOR UPDATE
+            "#,
+        )
+        .bind(project.id)
+        .bind(kind)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if signed == Some(true) {
+            return Err(ContractError::Conflict("This contract has already been signed".to_string()));
+        }
+
+        let template = Self::active_template(&mut tx, kind, language)
+            .await?
+            .ok_or(ContractError::TemplateMissing)?;
+        let values = Self::values(&mut tx, project, platform_name).await?;
+        let content = render_contract(&template.body, &values);
+        let title = render_contract(&template.title, &values);
+        let document = render_text_pdf(&title, &content);
+        let document_hash = format!("{:x}", Sha256::digest(&document));
+
+        sqlx::query("DELETE FROM project_contracts WHERE project_id = $1 AND kind = $2")
+            .bind(project.id)
+            .bind(kind)
+            .execute(&mut *tx)
+            .await?;
+
+        let contract = sqlx::query_as::<_, ProjectContract>(
+            r#"
+            INSERT INTO project_contracts (
+                project_id, template_id, template_version, kind, language, title, content,
+                document, document_hash, created_by
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING *
+            "#,
+        )
+        .bind(project.id)
+        .bind(template.id)
+        .bind(template.version)
+        .bind(kind)
+        .bind(&template.language)
+        .bind(&title)
+        .bind(&content)
+        .bind(&document)
+        .bind(&document_hash)
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        ProjectEventService::record(
+            &mut *tx,
+            NewProjectEvent::new(project.id, ProjectEventType::ContractCreated, actor, Some(user_id)).data(
+                serde_json::json!({
+                    "contractId": contract.id,
+                    "kind": kind,
+                    "templateVersion": template.version,
+                    "documentHash": document_hash,
+                }),
+            ),
+        )
+        .await?;
+
+        let recipient = if actor == ProjectActor::Client { project.expert_id } else { project.client_id };
+        NotificationService::create(
+            &mut *tx,
+            recipient,
+            "contract_created",
+            "Vertrag zur Unterschrift",
+            &format!("Für das Projekt \"{}\" liegt der Vertrag \"{}\" zur Unterschrift bereit.", project.title, title),
+            Some(serde_json::json!({ "projectId": project.id, "contractId": contract.id })),
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(contract)
+    }
+
+    /// Record the signature of one party; the contract is complete once both have signed
+    pub async fn sign(
+        db: &Database,
+        project: &Project,
+        id: Uuid,
+        signature: NewSignature,
+    ) -> Result<ProjectContractWithSignatures, ContractError> {
+        let mut tx = db.pool.begin().await?;
+
+        let contract = sqlx::query_as::<_, ProjectContract>(
+            "SELECT * FROM project_contracts WHERE id = $1 AND project_id = $2 FOR UPDATE",
+        )
+        .bind(id)
+        .bind(project.id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(ContractError::NotFound)?;
+
+        if !contract.document_hash.eq_ignore_ascii_case(&signature.document_hash) {
+            return Err(ContractError::Conflict(
+                "The document has changed; please review the current version".to_string(),
+            ));
+        }
+
+        let inserted = sqlx::query(
+            r#"
+            INSERT INTO contract_signatures
+                (contract_id, user_id, party, signer_name, document_hash, ip_address, user_agent)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (contract_id, party) DO NOTHING
+            "#,
+        )
+        .bind(id)
+        .bind(signature.user_id)
+        .bind(signature.party)
+        .bind(signature.signer_name.trim())
+        .bind(&contract.document_hash)
+        .bind(&signature.ip_address)
+        .bind(&signature.user_agent)
+        .execute(&mut *tx)
+        .await?;
+        if inserted.rows_affected() == 0 {
+            return Err(ContractError::Conflict("You have already signed this contract".to_string()));
+        }
+
+        let signatures = sqlx::query_as::<_, ContractSignature>(
+            "SELECT * FROM contract_signatures WHERE contract_id = $1 ORDER BY signed_at",
+        )
+        .bind(id)
+        .fetch_all(&mut *tx)
+        .await?;
+        let completed = [ProjectActor::Client, ProjectActor::Expert]
+            .iter()
+            .all(|party| signatures.iter().any(|s| s.party == *party));
+
+        let contract = if completed {
+            sqlx::query_as::<_, ProjectContract>(
+                "UPDATE project_contracts SET completed_at = NOW() WHERE id = $1 RETURNING *",
+            )
+            .bind(id)
+            .fetch_one(&mut *tx)
+            .await?
+        } else {
+            contract
+        };
+
+        ProjectEventService::record(
+            &mut *tx,
+            NewProjectEvent::new(project.id, ProjectEventType::ContractSigned, signature.party, Some(signature.user_id))
+                .data(serde_json::json!({
+                    "contractId": id,
+                    "kind": contract.kind,
+                    "documentHash": contract.document_hash,
+                    "completed": completed,
+                })),
+        )
+        .await?;
+
+        let other = if signature.party == ProjectActor::Client { project.expert_id } else { project.client_id };
+        let message = if completed {
+            format!("Der Vertrag \"{}\" wurde von beiden Parteien unterzeichnet.", contract.title)
+        } else {
+            format!("Der Vertrag \"{}\" wurde unterzeichnet und wartet auf Ihre Unterschrift.", contract.title)
+        };
+        NotificationService::create(
+            &mut *tx,
+            other,
+            "contract_signed",
+            "Vertrag unterzeichnet",
+            &message,
+            Some(serde_json::json!({ "projectId": project.id, "contractId": id })),
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(ProjectContractWithSignatures { contract, signatures })
+    }
+}
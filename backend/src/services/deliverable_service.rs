@@ -0,0 +1,358 @@
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::models::{ProjectActor, ProjectDeliverable, ProjectDelivery, ProjectEventType, ProjectRevisionRequest};
+use crate::services::{NewProjectEvent, ProjectEventService};
+
+/// Deliverable version to be stored
+#[derive(Debug, Clone)]
+pub struct NewDeliverable {
+    pub title: String,
+    pub description: Option<String>,
+    pub milestone_id: Option<Uuid>,
+    pub file_name: String,
+    pub file_type: String,
+    pub file_size: i64,
+    pub file_url: Option<String>,
+    pub storage_key: Option<String>,
+    /// False for presigned uploads that still have to be confirmed
+    pub uploaded: bool,
+}
+
+impl NewDeliverable {
+    /// Deliverable referencing an external URL
+    pub fn link(url: &str) -> Self {
+        let file_name = url.rsplit('/').next().unwrap_or(url).to_string();
+        Self {
+            title: file_name.clone(),
+            description: None,
+            milestone_id: None,
+            file_name,
+            file_type: "application/octet-stream".to_string(),
+            file_size: 0,
+            file_url: Some(url.to_string()),
+            storage_key: None,
+            uploaded: true,
+        }
+    }
+}
+
+pub struct DeliverableService;
+
+impl DeliverableService {
+    /// Uploaded deliverables of a project, latest version of each title first
+    pub async fn list(db: &Database, project_id: Uuid) -> Result<Vec<ProjectDeliverable>, sqlx::Error> {
+        sqlx::query_as::<_, ProjectDeliverable>(
+            r#"
+            SELECT * FROM project_deliverables
+            WHERE project_id = $1 AND uploaded_at IS NOT NULL
+            ORDER BY title, version DESC
+            "#,
+        )
+        .bind(project_id)
+        .fetch_all(&db.pool)
+        .await
+    }
+
+    /// Get a deliverable of a project (including unconfirmed uploads)
+    pub async fn get(db: &Database, project_id: Uuid, id: Uuid) -> Result<Option<ProjectDeliverable>, sqlx::Error> {
+        sqlx::query_as::<_, ProjectDeliverable>(
+            "SELECT * FROM project_deliverables WHERE id = $1 AND project_id = $2",
+        )
+        .bind(id)
+        .bind(project_id)
+        .fetch_optional(&db.pool)
+        .await
+    }
+
+    /// Check that a milestone belongs to the project
+    pub async fn milestone_exists(db: &Database, project_id: Uuid, milestone_id: Uuid) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM project_milestones WHERE id = $1 AND project_id = $2)",
+        )
+        .bind(milestone_id)
+        .bind(project_id)
+        .fetch_one(&db.pool)
+        .await
+    }
+
+    /// Store a new deliverable version
+    pub async fn create(
+        db: &Database,
+        project_id: Uuid,
+        uploaded_by: Uuid,
+        deliverable: NewDeliverable,
+    ) -> Result<ProjectDeliverable, sqlx::Error> {
+        let mut tx = db.pool.begin().await?;
+        let created = Self::insert_tx(&mut tx, project_id, uploaded_by, deliverable).await?;
+        tx.commit().await?;
+
+        Ok(created)
+    }
+
+    /// Store a new deliverable version; the version is numbered per title
+    pub async fn insert_tx(
+        conn: &mut PgConnection,
+        project_id: Uuid,
+        uploaded_by: Uuid,
+        deliverable: NewDeliverable,
+    ) -> Result<ProjectDeliverable, sqlx::Error> {
+        // Serialize version numbering per project
+        sqlx::query("SELECT id FROM projects WHERE id = $1 FOR UPDATE")
+            .bind(project_id)
+            .fetch_one(&mut *conn)
+            .await?;
+
+        let created = sqlx::query_as::<_, ProjectDeliverable>(
+            r#"
+            INSERT INTO project_deliverables (
+                project_id, milestone_id, title, description, file_url, file_name, file_type, file_size,
+                version, storage_key, uploaded_by, uploaded_at
+            )
+            VALUES (
+                $1, $2, $3, $4, $5, $6, $7, $8,
+                (SELECT COALESCE(MAX(version), 0) + 1 FROM project_deliverables WHERE project_id = $1 AND title = $3),
+                $9, $10, CASE WHEN $11 THEN NOW() END
+            )
+            RETURNING *
+            "#,
+        )
+        .bind(project_id)
+        .bind(deliverable.milestone_id)
+        .bind(&deliverable.title)
+        .bind(&deliverable.description)
+        .bind(&deliverable.file_url)
+        .bind(&deliverable.file_name)
+        .bind(&deliverable.file_type)
+        .bind(deliverable.file_size)
+        .bind(&deliverable.storage_key)
+        .bind(uploaded_by)
+        .bind(deliverable.uploaded)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        if created.uploaded_at.is_some() {
+            Self::record_upload(&mut *conn, &created, uploaded_by).await?;
+        }
+
+        Ok(created)
+    }
+
+    /// Confirm a presigned upload once the object exists in storage
+    pub async fn confirm_upload(
+        db: &Database,
+        project_id: Uuid,
+        id: Uuid,
+        file_size: i64,
+        actor_id: Uuid,
+    ) -> Result<Option<ProjectDeliverable>, sqlx::Error> {
+        let mut tx = db.pool.begin().await?;
+
+        let confirmed = sqlx::query_as::<_, ProjectDeliverable>(
+            r#"
+            UPDATE project_deliverables
+            SET uploaded_at = NOW(), file_size = $3
+            WHERE id = $1 AND project_id = $2 AND uploaded_at IS NULL
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(project_id)
+        .bind(file_size)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if let Some(deliverable) = &confirmed {
+            Self::record_upload(&mut tx, deliverable, actor_id).await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(confirmed)
+    }
+
+    async fn record_upload(
+        conn: &mut PgConnection,
+        deliverable: &ProjectDeliverable,
+        actor_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        ProjectEventService::record(
+            conn,
+            NewProjectEvent::new(
+                deliverable.project_id,
+                ProjectEventType::FileUploaded,
+                ProjectActor::Expert,
+                Some(actor_id),
+            )
+            .message(Some(&deliverable.title))
+            .data(serde_json::json!({
+                "deliverableId": deliverable.id,
+                "fileName": deliverable.file_name,
+                "version": deliverable.version,
+            })),
+        )
+        .await
+    }
+
+    /// Mark a version as final; other versions of the same title lose the flag
+    pub async fn mark_final(
+        db: &Database,
+        project_id: Uuid,
+        id: Uuid,
+        actor: ProjectActor,
+        actor_id: Uuid,
+    ) -> Result<Option<ProjectDeliverable>, sqlx::Error> {
+        let mut tx = db.pool.begin().await?;
+
+        let Some(deliverable) = sqlx::query_as::<_, ProjectDeliverable>(
+            r#"
+            SELECT * FROM project_deliverables
+            WHERE id = $1 AND project_id = $2 AND uploaded_at IS NOT NULL
+            FOR UPDATE
+            "#,
+        )
+        .bind(id)
+        .bind(project_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        sqlx::query(
+            r#"
+            UPDATE project_deliverables SET is_final = FALSE
+            WHERE project_id = $1 AND title = $2 AND is_final AND id <> $3
+            "#,
+        )
+        .bind(project_id)
+        .bind(&deliverable.title)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+        let updated = sqlx::query_as::<_, ProjectDeliverable>(
+            "UPDATE project_deliverables SET is_final = TRUE WHERE id = $1 RETURNING *",
+        )
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        ProjectEventService::record(
+            &mut *tx,
+            NewProjectEvent::new(project_id, ProjectEventType::DeliverableFinalized, actor, Some(actor_id))
+                .message(Some(&updated.title))
+                .data(serde_json::json!({ "deliverableId": updated.id, "version": updated.version })),
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(Some(updated))
+    }
+
+    /// Check that all ids are uploaded deliverables of the project
+    pub async fn all_belong_to_project(
+        conn: &mut PgConnection,
+        project_id: Uuid,
+        ids: &[Uuid],
+    ) -> Result<bool, sqlx::Error> {
+        let found: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM project_deliverables
+            WHERE project_id = $1 AND id = ANY($2) AND uploaded_at IS NOT NULL
+            "#,
+        )
+        .bind(project_id)
+        .bind(ids)
+        .fetch_one(conn)
+        .await?;
+
+        Ok(found == ids.len() as i64)
+    }
+
+    /// Record a delivery with the versions it contains
+    pub async fn create_delivery_tx(
+        conn: &mut PgConnection,
+        project_id: Uuid,
+        delivered_by: Uuid,
+        message: &str,
+        deliverable_ids: &[Uuid],
+    ) -> Result<ProjectDelivery, sqlx::Error> {
+        sqlx::query_as::<_, ProjectDelivery>(
+            r#"
+            INSERT INTO project_deliveries (project_id, delivered_by, message, deliverable_ids)
+            VALUES ($1, $2, $3, $4)
+            RETURNING *
+            "#,
+        )
+        .bind(project_id)
+        .bind(delivered_by)
+        .bind(message)
+        .bind(deliverable_ids)
+        .fetch_one(conn)
+        .await
+    }
+
+    /// Most recent delivery of a project
+    pub async fn latest_delivery_tx(
+        conn: &mut PgConnection,
+        project_id: Uuid,
+    ) -> Result<Option<ProjectDelivery>, sqlx::Error> {
+        sqlx::query_as::<_, ProjectDelivery>(
+            "SELECT * FROM project_deliveries WHERE project_id = $1 ORDER BY created_at DESC LIMIT 1",
+        )
+        .bind(project_id)
+        .fetch_optional(conn)
+        .await
+    }
+
+    /// Record a revision request against specific versions
+    pub async fn create_revision_request_tx(
+        conn: &mut PgConnection,
+        project_id: Uuid,
+        delivery_id: Option<Uuid>,
+        requested_by: Uuid,
+        feedback: &str,
+        deliverable_ids: &[Uuid],
+    ) -> Result<ProjectRevisionRequest, sqlx::Error> {
+        sqlx::query_as::<_, ProjectRevisionRequest>(
+            r#"
+            INSERT INTO project_revision_requests (project_id, delivery_id, requested_by, feedback, deliverable_ids)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING *
+            "#,
+        )
+        .bind(project_id)
+        .bind(delivery_id)
+        .bind(requested_by)
+        .bind(feedback)
+        .bind(deliverable_ids)
+        .fetch_one(conn)
+        .await
+    }
+
+    /// Deliveries of a project, newest first
+    pub async fn list_deliveries(db: &Database, project_id: Uuid) -> Result<Vec<ProjectDelivery>, sqlx::Error> {
+        sqlx::query_as::<_, ProjectDelivery>(
+            "SELECT * FROM project_deliveries WHERE project_id = $1 ORDER BY created_at DESC",
+        )
+        .bind(project_id)
+        .fetch_all(&db.pool)
+        .await
+    }
+
+    /// Revision requests of a project, newest first
+    pub async fn list_revision_requests(
+        db: &Database,
+        project_id: Uuid,
+    ) -> Result<Vec<ProjectRevisionRequest>, sqlx::Error> {
+        sqlx::query_as::<_, ProjectRevisionRequest>(
+            "SELECT * FROM project_revision_requests WHERE project_id = $1 ORDER BY created_at DESC",
+        )
+        .bind(project_id)
+        .fetch_all(&db.pool)
+        .await
+    }
+}
@@ -0,0 +1,215 @@
+//! Dunning for overdue invoices
+//! Escalates open invoices past their due date through the reminder stages
+//! (Zahlungserinnerung, 1. Mahnung, 2. Mahnung) and finally marks them uncollectible.
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::config::DunningSettings;
+use crate::models::OverdueInvoice;
+
+/// Level recorded when an invoice is written off
+pub const UNCOLLECTIBLE_LEVEL: i16 = 4;
+
+pub struct DunningService;
+
+impl DunningService {
+    /// Dunning level an invoice should have reached after `days_overdue` days
+    pub fn level_for(days_overdue: i64, settings: &DunningSettings) -> i16 {
+        settings
+            .stage_days
+            .iter()
+            .take_while(|&&days| days_overdue >= days)
+            .count() as i16
+    }
+
+    /// Late fee charged when reaching `level`
+    pub fn late_fee_for(level: i16, settings: &DunningSettings) -> i32 {
+        settings
+            .late_fees
+            .get((level as usize).wrapping_sub(1))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Get all open invoices that are past their due date
+    pub async fn get_overdue_invoices(pool: &PgPool) -> Result<Vec<OverdueInvoice>, sqlx::Error> {
+        sqlx::query_as::<_, OverdueInvoice>(
+            r#"
+            SELECT i.id, i.invoice_number, i.recipient_id,
+                   u.email AS recipient_email, u.first_name AS recipient_first_name,
+                   u.preferred_language AS recipient_language,
+                   i.total, i.currency, i.due_date, i.dunning_level
+            FROM invoices i
+            JOIN users u ON u.id = i.recipient_id
+            WHERE i.status = 'open' AND i.due_date < CURRENT_DATE
+            ORDER BY i.due_date
+            "#,
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Raise an open invoice to `level`, adding the late fee as a line item.
+    /// Returns the new total in cents, or `None` if the invoice is no longer open
+    /// or already reached the level, in which case no fee is charged.
+    pub async fn escalate(
+        pool: &PgPool,
+        invoice_id: Uuid,
+        level: i16,
+        late_fee: i32,
+    ) -> Result<Option<i32>, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let total: Option<i32> = sqlx::query_scalar(
+            r#"
+            UPDATE invoices
+            SET dunning_level = $2,
+                last_dunning_at = NOW(),
+                late_fee = late_fee + $3,
+                total = total + $3,
+                line_items = CASE WHEN $3 > 0 THEN line_items || jsonb_build_array(jsonb_build_object(
+                    'description', 'Mahngebühr',
+                    'quantity', 1,
+                    'unitPrice', $3,
+                    'amount', $3
+                )) ELSE line_items END,
+                updated_at = NOW()
+            WHERE id = $1 AND status = 'open' AND dunning_level < $2
+            RETURNING total
+            "#,
+        )
+        .bind(invoice_id)
+        .bind(level)
+        .bind(late_fee)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(total) = total else {
+            return Ok(None);
+        };
+
+        sqlx::query(
+            "INSERT INTO invoice_dunning_events (invoice_id, level, late_fee) VALUES ($1, $2, $3)"
+        )
+        .bind(invoice_id)
+        .bind(level)
+        .bind(late_fee)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(Some(total))
+    }
+
+    /// Record that the reminder email for the latest dunning step went out
+    pub async fn mark_email_sent(pool: &PgPool, invoice_id: Uuid, level: i16) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE invoice_dunning_events SET email_sent = TRUE WHERE invoice_id = $1 AND level = $2"
+        )
+        .bind(invoice_id)
+        .bind(level)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Write off an invoice after the final deadline
+    pub async fn mark_uncollectible(pool: &PgPool, invoice_id: Uuid) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query(
+            "UPDATE invoices SET status = 'uncollectible', updated_at = NOW() WHERE id = $1 AND status = 'open'"
+        )
+        .bind(invoice_id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("INSERT INTO invoice_dunning_events (invoice_id, level) VALUES ($1, $2)")
+            .bind(invoice_id)
+            .bind(UNCOLLECTIBLE_LEVEL)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Block the client from starting new projects
+    pub async fn pause_projects(pool: &PgPool, user_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE users SET projects_paused_at = NOW() WHERE id = $1 AND projects_paused_at IS NULL")
+            .bind(user_id)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Lift the pause for clients that no longer have invoices at or above `level` open
+    pub async fn release_settled_clients(pool: &PgPool, level: i16) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE users u SET projects_paused_at = NULL
+            WHERE u.projects_paused_at IS NOT NULL
+              AND NOT EXISTS (
+                  SELECT 1 FROM invoices i
+                  WHERE i.recipient_id = u.id
+                    AND (i.status = 'uncollectible' OR (i.status = 'open' AND i.dunning_level >= $1))
+              )
+            "#,
+        )
+        .bind(level)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Whether the client is currently blocked from starting new projects
+    pub async fn is_paused(pool: &PgPool, user_id: Uuid) -> Result<bool, sqlx::Error> {
+        let paused: Option<bool> = sqlx::query_scalar(
+            "SELECT projects_paused_at IS NOT NULL FROM users WHERE id = $1"
+        )
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(paused.unwrap_or(false))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> DunningSettings {
+        DunningSettings {
+            enabled: true,
+            interval_minutes: 60,
+            stage_days: vec![3, 14, 28],
+            late_fees: vec![0, 500, 1000],
+            pause_projects_at_level: 2,
+            uncollectible_after_days: 60,
+        }
+    }
+
+    #[test]
+    fn test_level_for() {
+        let s = settings();
+        assert_eq!(DunningService::level_for(1, &s), 0);
+        assert_eq!(DunningService::level_for(3, &s), 1);
+        assert_eq!(DunningService::level_for(20, &s), 2);
+        assert_eq!(DunningService::level_for(45, &s), 3);
+    }
+
+    #[test]
+    fn test_late_fee_for() {
+        let s = settings();
+        assert_eq!(DunningService::late_fee_for(0, &s), 0);
+        assert_eq!(DunningService::late_fee_for(1, &s), 0);
+        assert_eq!(DunningService::late_fee_for(3, &s), 1000);
+    }
+}
@@ -15,6 +15,19 @@ pub struct EmailService {
     from_name: String,
 }
 
+/// Dunning notice for an overdue invoice
+#[cfg(feature = "email")]
+#[derive(Clone, Copy)]
+pub struct DunningNotice<'a> {
+    pub invoice: &'a crate::models::OverdueInvoice,
+    /// Dunning level reached
+    pub level: i16,
+    /// Invoice total including all late fees, in cents
+    pub total: i32,
+    /// Late fee charged with this notice, in cents
+    pub late_fee: i32,
+}
+
 #[cfg(feature = "email")]
 impl EmailService {
     /// Create new email service
@@ -192,5 +205,189 @@ impl EmailService {
 
         self.send_email(to, &format!("Bestätigung Ihrer Bestellung: {}", service_name), &html).await
     }
-}
 
+    /// Send dunning notice (Zahlungserinnerung / 1. Mahnung / 2. Mahnung) in the recipient's language
+    pub async fn send_dunning_notice(&self, notice: &DunningNotice<'_>) -> Result<(), lettre::transport::smtp::Error> {
+        use crate::models::Language;
+
+        let DunningNotice { invoice, level, total, late_fee } = *notice;
+        let (to, name, invoice_number, currency) = (
+            &invoice.recipient_email,
+            &invoice.recipient_first_name,
+            &invoice.invoice_number,
+            &invoice.currency,
+        );
+        let amount = format!("{:.2} {}", total as f64 / 100.0, currency.to_uppercase());
+        let fee = format!("{:.2} {}", late_fee as f64 / 100.0, currency.to_uppercase());
+        let due = invoice.due_date.format("%d.%m.%Y");
+
+        let (subject, greeting, body, fee_line, closing) = match invoice.recipient_language {
+            Language::En => (
+                match level { 1 => "Payment reminder", 2 => "First reminder notice", _ => "Second reminder notice" },
+                format!("Dear {name},"),
+                format!("Our records show that invoice {invoice_number}, due on {due}, has not been paid yet. Please transfer the outstanding amount of <strong>{amount}</strong> promptly."),
+                format!("A late fee of {fee} has been added to the invoice."),
+                "If you have already paid, please disregard this message.<br><br>Kind regards,<br>The DACH Marketplace Team",
+            ),
+            Language::Fr => (
+                match level { 1 => "Rappel de paiement", 2 => "1er rappel", _ => "2e rappel" },
+                format!("Bonjour {name},"),
+                format!("Selon nos informations, la facture {invoice_number}, échue le {due}, n'a pas encore été réglée. Merci de verser le montant dû de <strong>{amount}</strong> dans les meilleurs délais."),
+                format!("Des frais de rappel de {fee} ont été ajoutés à la facture."),
+                "Si vous avez déjà payé, veuillez ignorer ce message.<br><br>Meilleures salutations,<br>L'équipe DACH Marketplace",
+            ),
+            Language::It => (
+                match level { 1 => "Promemoria di pagamento", 2 => "1° sollecito", _ => "2° sollecito" },
+                format!("Gentile {name},"),
+                format!("Secondo i nostri dati, la fattura {invoice_number}, scaduta il {due}, non è ancora stata saldata. La preghiamo di versare l'importo dovuto di <strong>{amount}</strong> al più presto."),
+                format!("Alla fattura sono state aggiunte spese di sollecito di {fee}."),
+                "Se ha già effettuato il pagamento, non tenga conto di questo messaggio.<br><br>Cordiali saluti,<br>Il team DACH Marketplace",
+            ),
+            Language::De => (
+                match level { 1 => "Zahlungserinnerung", 2 => "1. Mahnung", _ => "2. Mahnung" },
+                format!("Hallo {name},"),
+                format!("Laut unseren Unterlagen ist die Rechnung {invoice_number} mit Fälligkeit am {due} noch nicht beglichen. Bitte überweisen Sie den offenen Betrag von <strong>{amount}</strong> umgehend."),
+                format!("Für diese Mahnung wurde eine Mahngebühr von {fee} berechnet."),
+                "Falls Sie bereits bezahlt haben, betrachten Sie diese Nachricht bitte als gegenstandslos.<br><br>Mit freundlichen Grüßen,<br>Das DACH Marketplace Team",
+            ),
+        };
+
+        let fee_html = if late_fee > 0 { format!("<p>{fee_line}</p>") } else { String::new() };
+
+        let html = format!(
+            r#"
+            <h1>{subject}</h1>
+            <p>{greeting}</p>
+            <p>{body}</p>
+            {fee_html}
+            <p><a href="https://dach-marketplace.com/dashboard/invoices">{invoice_number}</a></p>
+            <p>{closing}</p>
+            "#
+        );
+
+        self.send_email(to, &format!("{}: {}", subject, invoice_number), &html).await
+    }
+
+    /// Remind a client to approve or reject a delivery before it is completed automatically
+    pub async fn send_completion_reminder(
+        &self,
+        to: &str,
+        name: &str,
+        language: &crate::models::Language,
+        project_title: &str,
+        project_id: uuid::Uuid,
+        auto_complete_on: chrono::NaiveDate,
+    ) -> Result<(), lettre::transport::smtp::Error> {
+        use crate::models::Language;
+
+        let date = auto_complete_on.format("%d.%m.%Y");
+
+        let (subject, greeting, body, closing) = match language {
+            Language::En => (
+                "Please review your delivery",
+                format!("Dear {name},"),
+                format!("The expert has delivered <strong>{project_title}</strong>. Please approve the delivery or request a revision. Without a response, the project will be completed automatically on {date} and the payment released to the expert."),
+                "Kind regards,<br>The DACH Marketplace Team",
+            ),
+            Language::Fr => (
+                "Veuillez vérifier la livraison",
+                format!("Bonjour {name},"),
+                format!("L'expert a livré <strong>{project_title}</strong>. Merci d'accepter la livraison ou de demander une révision. Sans réponse de votre part, le projet sera clôturé automatiquement le {date} et le paiement versé à l'expert."),
+                "Meilleures salutations,<br>L'équipe DACH Marketplace",
+            ),
+            Language::It => (
+                "Verifichi la consegna",
+                format!("Gentile {name},"),
+                format!("L'esperto ha consegnato <strong>{project_title}</strong>. La preghiamo di approvare la consegna o di richiedere una revisione. In assenza di risposta, il progetto verrà concluso automaticamente il {date} e il pagamento versato all'esperto."),
+                "Cordiali saluti,<br>Il team DACH Marketplace",
+            ),
+            Language::De => (
+                "Bitte prüfen Sie die Lieferung",
+                format!("Hallo {name},"),
+                format!("Der Experte hat <strong>{project_title}</strong> geliefert. Bitte nehmen Sie die Lieferung ab oder fordern Sie eine Überarbeitung an. Ohne Rückmeldung wird das Projekt am {date} automatisch abgeschlossen und die Zahlung an den Experten freigegeben."),
+                "Mit freundlichen Grüßen,<br>Das DACH Marketplace Team",
+            ),
+        };
+
+        let html = format!(
+            r#"
+            <h1>{subject}</h1>
+            <p>{greeting}</p>
+            <p>{body}</p>
+            <p><a href="https://dach-marketplace.com/dashboard/projects/{project_id}">{project_title}</a></p>
+            <p>{closing}</p>
+            "#
+        );
+
+        self.send_email(to, &format!("{}: {}", subject, project_title), &html).await
+    }
+
+    /// Postings matching an expert's profile: a single one for instant alerts, several for the daily digest
+    pub async fn send_job_alerts(
+        &self,
+        to: &str,
+        name: &str,
+        language: &crate::models::Language,
+        alerts: &[crate::models::JobAlertEmail],
+    ) -> Result<(), lettre::transport::smtp::Error> {
+        use crate::models::Language;
+
+        let (subject, greeting, body, match_label, closing) = match language {
+            Language::En => (
+                "New projects matching your profile",
+                format!("Dear {name},"),
+                "These new project postings match your profile:",
+                "match",
+                "Kind regards,<br>The DACH Marketplace Team",
+            ),
+            Language::Fr => (
+                "Nouveaux projets correspondant à votre profil",
+                format!("Bonjour {name},"),
+                "Ces nouvelles offres de projet correspondent à votre profil :",
+                "de correspondance",
+                "Meilleures salutations,<br>L'équipe DACH Marketplace",
+            ),
+            Language::It => (
+                "Nuovi progetti adatti al suo profilo",
+                format!("Gentile {name},"),
+                "Questi nuovi annunci di progetto corrispondono al suo profilo:",
+                "di corrispondenza",
+                "Cordiali saluti,<br>Il team DACH Marketplace",
+            ),
+            Language::De => (
+                "Neue passende Projekte",
+                format!("Hallo {name},"),
+                "Diese neuen Ausschreibungen passen zu Ihrem Profil:",
+                "Übereinstimmung",
+                "Mit freundlichen Grüßen,<br>Das DACH Marketplace Team",
+            ),
+        };
+
+        let items: String = alerts
+            .iter()
+            .map(|alert| {
+                format!(
+                    r#"<li><a href="https://dach-marketplace.com/postings/{}">{}</a> ({} % {match_label})</li>"#,
+                    alert.posting_id, alert.title, alert.score
+                )
+            })
+            .collect();
+
+        let html = format!(
+            r#"
+            <h1>{subject}</h1>
+            <p>{greeting}</p>
+            <p>{body}</p>
+            <ul>{items}</ul>
+            <p>{closing}</p>
+            "#
+        );
+
+        let subject = match alerts {
+            [alert] => format!("{}: {}", subject, alert.title),
+            _ => subject.to_string(),
+        };
+
+        self.send_email(to, &subject, &html).await
+    }
+}
@@ -0,0 +1,321 @@
+//! Expert performance metrics
+//! Samples are recorded as they happen (first replies, deliveries, project outcomes);
+//! after each new sample the expert's profile figures are re-aggregated from their
+//! samples of the last year.
+
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, Utc};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::models::{
+    ExpertMetricKind, ExpertMetrics, ExpertMetricsPoint, ExpertMetricsSummary, MetricSample, Project, ProjectStatus,
+};
+
+/// Samples older than this no longer count towards the profile figures
+pub const METRICS_WINDOW_DAYS: i64 = 365;
+
+pub struct ExpertMetricsService;
+
+impl ExpertMetricsService {
+    /// Aggregate samples into profile figures
+    pub fn summarize<'a>(samples: impl IntoIterator<Item = &'a MetricSample>) -> ExpertMetricsSummary {
+        let mut response_hours = Vec::new();
+        let mut rates: [(f32, u32); 3] = [(0.0, 0); 3];
+        let mut count = 0;
+
+        for sample in samples {
+            count += 1;
+            let slot = match sample.kind {
+                ExpertMetricKind::FirstResponse => {
+                    response_hours.push(sample.value);
+                    continue;
+                }
+                ExpertMetricKind::Outcome => 0,
+                ExpertMetricKind::Delivery => 1,
+                ExpertMetricKind::Revision => 2,
+            };
+            rates[slot].0 += sample.value;
+            rates[slot].1 += 1;
+        }
+
+        let rate = |(sum, n): (f32, u32)| (n > 0).then(|| sum / n as f32 * 100.0);
+
+        ExpertMetricsSummary {
+            response_time_hours: median(&mut response_hours).map(|h| h.ceil().min(i16::MAX as f32) as i16),
+            completion_rate: rate(rates[0]),
+            on_time_delivery_rate: rate(rates[1]),
+            revision_rate: rate(rates[2]),
+            samples: count,
+        }
+    }
+
+    /// Monthly figures for the `months` calendar months up to and including `until`
+    pub fn monthly_series(samples: &[MetricSample], until: NaiveDate, months: u32) -> Vec<ExpertMetricsPoint> {
+        let last = until.with_day(1).unwrap_or(until);
+
+        (0..months)
+            .rev()
+            .filter_map(|offset| last.checked_sub_months(Months::new(offset)))
+            .map(|period| {
+                let next = period.checked_add_months(Months::new(1)).unwrap_or(period);
+                let metrics = Self::summarize(samples.iter().filter(|s| {
+                    let day = s.occurred_at.date_naive();
+                    day >= period && day < next
+                }));
+                ExpertMetricsPoint { period, metrics }
+            })
+            .collect()
+    }
+
+    /// Store a sample; returns false if the event was already measured
+    pub async fn record(
+        conn: &mut PgConnection,
+        expert_id: Uuid,
+        kind: ExpertMetricKind,
+        source_id: Uuid,
+        value: f32,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO expert_metric_events (expert_id, kind, source_id, value)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (kind, source_id) DO NOTHING
+            "#,
+        )
+        .bind(expert_id)
+        .bind(kind)
+        .bind(source_id)
+        .bind(value)
+        .execute(conn)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Re-aggregate the profile figures of one expert (user id)
+    pub async fn recalculate(conn: &mut PgConnection, expert_id: Uuid) -> Result<ExpertMetricsSummary, sqlx::Error> {
+        let since = Utc::now() - Duration::days(METRICS_WINDOW_DAYS);
+        let samples = Self::get_samples(&mut *conn, expert_id, since).await?;
+        let summary = Self::summarize(&samples);
+
+        sqlx::query(
+            r#"
+            UPDATE expert_profiles
+            SET response_time_hours = $2,
+                completion_rate = $3,
+                on_time_delivery_rate = $4,
+                revision_rate = $5,
+                metrics_updated_at = NOW()
+            WHERE user_id = $1
+            "#,
+        )
+        .bind(expert_id)
+        .bind(summary.response_time_hours)
+        .bind(summary.completion_rate)
+        .bind(summary.on_time_delivery_rate)
+        .bind(summary.revision_rate)
+        .execute(conn)
+        .await?;
+
+        Ok(summary)
+    }
+
+    /// Record the measurable outcome of a project status change and update the expert's figures.
+    /// `project` is the state after the change, `from` the previous status.
+    pub async fn on_project_transition(
+        conn: &mut PgConnection,
+        project: &Project,
+        from: &ProjectStatus,
+    ) -> Result<(), sqlx::Error> {
+        let mut samples: Vec<(ExpertMetricKind, f32)> = Vec::new();
+
+        match project.status {
+            ProjectStatus::Delivered => {
+                if let (Some(due), Some(delivered)) = (project.delivery_date, project.delivered_at) {
+                    samples.push((ExpertMetricKind::Delivery, if delivered <= due { 1.0 } else { 0.0 }));
+                }
+            }
+            ProjectStatus::Completed => {
+                samples.push((ExpertMetricKind::Outcome, 1.0));
+                samples.push((ExpertMetricKind::Revision, if project.revisions_used > 0 { 1.0 } else { 0.0 }));
+            }
+            // Requests withdrawn before work started do not count against the expert
+            ProjectStatus::Cancelled if !matches!(from, ProjectStatus::Pending | ProjectStatus::Accepted) => {
+                samples.push((ExpertMetricKind::Outcome, 0.0));
+            }
+            _ => {}
+        }
+
+        let mut recorded = false;
+        for (kind, value) in samples {
+            recorded |= Self::record(&mut *conn, project.expert_id, kind, project.id, value).await?;
+        }
+        if recorded {
+            Self::recalculate(conn, project.expert_id).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Measure the first reply of an expert to a conversation started by someone else
+    pub async fn on_message(conn: &mut PgConnection, conversation_id: Uuid, sender_id: Uuid) -> Result<(), sqlx::Error> {
+        let inserted = sqlx::query(
+            r#"
+            INSERT INTO expert_metric_events (expert_id, kind, source_id, value)
+            SELECT u.id, 'first_response', $1, EXTRACT(EPOCH FROM (NOW() - x.asked)) / 3600.0
+            FROM users u,
+                 LATERAL (
+                     SELECT MIN(m.created_at) AS asked,
+                            COUNT(*) FILTER (WHERE m.sender_id = u.id) AS own_messages
+                     FROM messages m
+                     WHERE m.conversation_id = $1 AND m.message_type <> 'system'
+                 ) x
+            WHERE u.id = $2 AND u.role = 'expert'
+              AND x.own_messages = 1
+              AND x.asked < (
+                  SELECT MIN(created_at) FROM messages
+                  WHERE conversation_id = $1 AND sender_id = $2 AND message_type <> 'system'
+              )
+            ON CONFLICT (kind, source_id) DO NOTHING
+            "#,
+        )
+        .bind(conversation_id)
+        .bind(sender_id)
+        .execute(&mut *conn)
+        .await?;
+
+        if inserted.rows_affected() > 0 {
+            Self::recalculate(conn, sender_id).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Measure the response time to a booking request
+    pub async fn on_booking_response(
+        conn: &mut PgConnection,
+        booking_id: Uuid,
+        expert_id: Uuid,
+        requested_at: DateTime<Utc>,
+        responded_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        let hours = (responded_at - requested_at).num_seconds().max(0) as f32 / 3600.0;
+        if Self::record(&mut *conn, expert_id, ExpertMetricKind::FirstResponse, booking_id, hours).await? {
+            Self::recalculate(conn, expert_id).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn get_samples(
+        conn: &mut PgConnection,
+        expert_id: Uuid,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<MetricSample>, sqlx::Error> {
+        sqlx::query_as::<_, MetricSample>(
+            r#"
+            SELECT kind, value, occurred_at FROM expert_metric_events
+            WHERE expert_id = $1 AND occurred_at > $2
+            ORDER BY occurred_at
+            "#,
+        )
+        .bind(expert_id)
+        .bind(since)
+        .fetch_all(conn)
+        .await
+    }
+
+    /// Current figures and monthly history of an expert (user id)
+    pub async fn get_metrics(db: &Database, expert_id: Uuid, months: u32) -> Result<ExpertMetrics, sqlx::Error> {
+        let today = Utc::now().date_naive();
+        let first_month = today
+            .with_day(1)
+            .and_then(|d| d.checked_sub_months(Months::new(months.saturating_sub(1))))
+            .unwrap_or(today);
+        let window_start = Utc::now() - Duration::days(METRICS_WINDOW_DAYS);
+        let since = window_start.min(first_month.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc());
+
+        let mut conn = db.pool.acquire().await?;
+        let samples = Self::get_samples(&mut conn, expert_id, since).await?;
+
+        let updated_at: Option<DateTime<Utc>> =
+            sqlx::query_scalar("SELECT metrics_updated_at FROM expert_profiles WHERE user_id = $1")
+                .bind(expert_id)
+                .fetch_optional(&mut *conn)
+                .await?
+                .flatten();
+
+        Ok(ExpertMetrics {
+            current: Self::summarize(samples.iter().filter(|s| s.occurred_at > window_start)),
+            series: Self::monthly_series(&samples, today, months),
+            updated_at,
+        })
+    }
+}
+
+/// Median of the values (sorts in place)
+fn median(values: &mut [f32]) -> Option<f32> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(|a, b| a.total_cmp(b));
+    let mid = values.len() / 2;
+    Some(if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn sample(kind: ExpertMetricKind, value: f32, month: u32) -> MetricSample {
+        MetricSample {
+            kind,
+            value,
+            occurred_at: Utc.with_ymd_and_hms(2024, month, 15, 12, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_summarize() {
+        let samples = vec![
+            sample(ExpertMetricKind::FirstResponse, 1.5, 11),
+            sample(ExpertMetricKind::FirstResponse, 10.0, 11),
+            sample(ExpertMetricKind::FirstResponse, 3.2, 11),
+            sample(ExpertMetricKind::Outcome, 1.0, 11),
+            sample(ExpertMetricKind::Outcome, 1.0, 11),
+            sample(ExpertMetricKind::Outcome, 1.0, 11),
+            sample(ExpertMetricKind::Outcome, 0.0, 11),
+            sample(ExpertMetricKind::Delivery, 1.0, 11),
+            sample(ExpertMetricKind::Delivery, 0.0, 11),
+        ];
+
+        let summary = ExpertMetricsService::summarize(&samples);
+        assert_eq!(summary.response_time_hours, Some(4));
+        assert_eq!(summary.completion_rate, Some(75.0));
+        assert_eq!(summary.on_time_delivery_rate, Some(50.0));
+        assert_eq!(summary.revision_rate, None);
+        assert_eq!(summary.samples, 9);
+    }
+
+    #[test]
+    fn test_monthly_series() {
+        let samples = vec![
+            sample(ExpertMetricKind::Outcome, 1.0, 10),
+            sample(ExpertMetricKind::Outcome, 0.0, 12),
+            sample(ExpertMetricKind::Outcome, 1.0, 12),
+        ];
+
+        let series = ExpertMetricsService::monthly_series(&samples, NaiveDate::from_ymd_opt(2024, 12, 20).unwrap(), 3);
+        assert_eq!(series.len(), 3);
+        assert_eq!(series[0].period, NaiveDate::from_ymd_opt(2024, 10, 1).unwrap());
+        assert_eq!(series[0].metrics.completion_rate, Some(100.0));
+        assert_eq!(series[1].metrics, ExpertMetricsSummary::default());
+        assert_eq!(series[2].metrics.completion_rate, Some(50.0));
+    }
+}
@@ -1,3 +1,4 @@
+use sqlx::PgConnection;
 use uuid::Uuid;
 
 use crate::db::Database;
@@ -104,6 +105,9 @@ impl ExpertService {
                 availability_status = COALESCE($15, availability_status),
                 available_hours_per_week = COALESCE($16, available_hours_per_week),
                 timezone = COALESCE($17, timezone),
+                booking_slot_minutes = COALESCE($18, booking_slot_minutes),
+                booking_buffer_minutes = COALESCE($19, booking_buffer_minutes),
+                max_bookings_per_day = CASE WHEN $20::SMALLINT IS NULL THEN max_bookings_per_day ELSE NULLIF($20, 0) END,
                 updated_at = NOW()
             WHERE id = $1
             RETURNING *
@@ -126,6 +130,9 @@ impl ExpertService {
         .bind(&req.availability_status)
         .bind(req.available_hours_per_week)
         .bind(&req.timezone)
+        .bind(req.booking_slot_minutes)
+        .bind(req.booking_buffer_minutes)
+        .bind(req.max_bookings_per_day)
         .fetch_one(&db.pool)
         .await?;
 
@@ -270,8 +277,11 @@ impl ExpertService {
             Some(crate::models::ExpertSortBy::Experience) => "ep.years_experience DESC",
             Some(crate::models::ExpertSortBy::TotalProjects) => "ep.total_projects DESC",
             Some(crate::models::ExpertSortBy::ResponseTime) => "ep.response_time_hours ASC NULLS LAST",
+            Some(crate::models::ExpertSortBy::Reliability) => {
+                "ep.completion_rate DESC NULLS LAST, ep.on_time_delivery_rate DESC NULLS LAST, ep.rating_average DESC"
+            }
             Some(crate::models::ExpertSortBy::Newest) => "ep.created_at DESC",
-            None => "ep.rating_average DESC, ep.total_projects DESC",
+            None => "ep.rating_average DESC, ep.completion_rate DESC NULLS LAST, ep.total_projects DESC",
         };
         query.push_str(&format!(" ORDER BY {}", order_by));
 
@@ -317,11 +327,16 @@ impl ExpertService {
 
     /// Increment total projects count
     pub async fn increment_projects(db: &Database, expert_id: Uuid) -> Result<(), sqlx::Error> {
+        Self::increment_projects_tx(&mut *db.pool.acquire().await?, expert_id).await
+    }
+
+    /// Increment total projects count inside the caller's transaction
+    pub async fn increment_projects_tx(conn: &mut PgConnection, expert_id: Uuid) -> Result<(), sqlx::Error> {
         sqlx::query(
             "UPDATE expert_profiles SET total_projects = total_projects + 1, updated_at = NOW() WHERE id = $1"
         )
         .bind(expert_id)
-        .execute(&db.pool)
+        .execute(conn)
         .await?;
 
         Ok(())
@@ -329,12 +344,17 @@ impl ExpertService {
 
     /// Add to total earnings
     pub async fn add_earnings(db: &Database, expert_id: Uuid, amount: i64) -> Result<(), sqlx::Error> {
+        Self::add_earnings_tx(&mut *db.pool.acquire().await?, expert_id, amount).await
+    }
+
+    /// Add to total earnings inside the caller's transaction
+    pub async fn add_earnings_tx(conn: &mut PgConnection, expert_id: Uuid, amount: i64) -> Result<(), sqlx::Error> {
         sqlx::query(
             "UPDATE expert_profiles SET total_earnings = total_earnings + $2, updated_at = NOW() WHERE id = $1"
         )
         .bind(expert_id)
         .bind(amount)
-        .execute(&db.pool)
+        .execute(conn)
         .await?;
 
         Ok(())
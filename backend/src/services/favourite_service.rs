@@ -0,0 +1,463 @@
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::models::{
+    CreateSavedListRequest, SaveExpertRequest, SaveServiceRequest, SavedExpert, SavedList, SavedService, Service,
+    UpdateSavedListRequest,
+};
+
+/// Error of a favourites operation
+#[derive(Debug, thiserror::Error)]
+pub enum FavouriteError {
+    #[error("Not found")]
+    NotFound,
+
+    #[error("{0}")]
+    Conflict(String),
+
+    #[error("{0}")]
+    Invalid(String),
+
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+const LIST_COLUMNS: &str = r#"
+    l.id, l.owner_id, l.name, l.description, l.organisation_id,
+    (SELECT COUNT(*) FROM saved_experts se WHERE se.list_id = l.id) AS expert_count,
+    (SELECT COUNT(*) FROM saved_services ss WHERE ss.list_id = l.id) AS service_count,
+    l.created_at, l.updated_at
+"#;
+
+const EXPERT_COLUMNS: &str = r#"
+    s.id, s.expert_id, s.list_id, s.notes, u.first_name, u.last_name, u.avatar_url,
+    ep.headline, ep.availability_status, ep.rating_average, s.created_at
+"#;
+
+const SERVICE_COLUMNS: &str = r#"
+    s.id, s.service_id, s.list_id, s.notes, sv.title, sv.slug, sv.price, sv.currency,
+    sv.rating_average, sv.is_active, s.created_at
+"#;
+
+pub struct FavouriteService;
+
+impl FavouriteService {
+    // ==================== Lists ====================
+
+    /// Lists of the user
+    pub async fn lists(db: &Database, user_id: Uuid) -> Result<Vec<SavedList>, sqlx::Error> {
+        sqlx::query_as::<_, SavedList>(&format!(
+            "SELECT {LIST_COLUMNS} FROM saved_lists l WHERE l.owner_id = $1 ORDER BY l.name"
+        ))
+        .bind(user_id)
+        .fetch_all(&db.pool)
+        .await
+    }
+
+    /// Lists other members shared with the user's organisation
+    pub async fn shared_lists(db: &Database, user_id: Uuid) -> Result<Vec<SavedList>, sqlx::Error> {
+        sqlx::query_as::<_, SavedList>(&format!(
+            r#"
+            SELECT {LIST_COLUMNS}
+            FROM saved_lists l
+            JOIN organisation_members m ON m.organisation_id = l.organisation_id
+            WHERE m.user_id = $1 AND l.owner_id <> $1
+            ORDER BY l.updated_at DESC
+            "#
+        ))
+        .bind(user_id)
+        .fetch_all(&db.pool)
+        .await
+    }
+
+    pub async fn get_list(db: &Database, id: Uuid) -> Result<Option<SavedList>, sqlx::Error> {
+        sqlx::query_as::<_, SavedList>(&format!("SELECT {LIST_COLUMNS} FROM saved_lists l WHERE l.id = $1"))
+            .bind(id)
+            .fetch_optional(&db.pool)
+            .await
+    }
+
+    /// The owner and, for shared lists, members of the organisation can view a list
+    pub async fn can_view(db: &Database, list: &SavedList, user_id: Uuid) -> Result<bool, sqlx::Error> {
+        if list.owner_id == user_id {
+            return Ok(true);
+        }
+        let Some(organisation_id) = list.organisation_id else {
+            return Ok(false);
+        };
+
+        sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM organisation_members WHERE organisation_id = $1 AND user_id = $2)",
+        )
+        .bind(organisation_id)
+        .bind(user_id)
+        .fetch_one(&db.pool)
+        .await
+    }
+
+    /// Organisation a list of the user is shared with
+    async fn share_target(db: &Database, user_id: Uuid, shared: bool) -> Result<Option<Uuid>, FavouriteError> {
+        if !shared {
+            return Ok(None);
+        }
+
+        let organisation_id: Option<Uuid> =
+            sqlx::query_scalar("SELECT organisation_id FROM organisation_members WHERE user_id = $1")
+                .bind(user_id)
+                .fetch_optional(&db.pool)
+                .await?;
+
+        organisation_id
+            .map(Some)
+            .ok_or_else(|| FavouriteError::Invalid("Only members of an organisation can share lists".to_string()))
+    }
+
+    pub async fn create_list(
+        db: &Database,
+        user_id: Uuid,
+        req: &CreateSavedListRequest,
+    ) -> Result<SavedList, FavouriteError> {
+        let organisation_id = Self::share_target(db, user_id, req.shared).await?;
+
+        let id: Option<Uuid> = sqlx::query_scalar(
+            r#"
+            INSERT INTO saved_lists (owner_id, name, description, organisation_id)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (owner_id, name) DO NOTHING
+            RETURNING id
+            "#,
+        )
+        .bind(user_id)
+        .bind(req.name.trim())
+        .bind(&req.description)
+        .bind(organisation_id)
+        .fetch_optional(&db.pool)
+        .await?;
+
+        let id = id.ok_or_else(|| FavouriteError::Conflict("You already have a list with this name".to_string()))?;
+
+        Self::get_list(db, id).await?.ok_or(FavouriteError::NotFound)
+    }
+
+    pub async fn update_list(
+        db: &Database,
+        id: Uuid,
+        user_id: Uuid,
+        req: &UpdateSavedListRequest,
+    ) -> Result<SavedList, FavouriteError> {
+        let organisation_id = match req.shared {
+            Some(shared) => Some(Self::share_target(db, user_id, shared).await?),
+            None => None,
+        };
+
+        let updated = sqlx::query(
+            r#"
+            UPDATE saved_lists SET
+                name = COALESCE($3, name),
+                description = COALESCE($4, description),
+                organisation_id = CASE WHEN $5 THEN $6 ELSE organisation_id END,
+                updated_at = NOW()
+            WHERE id = $1 AND owner_id = $2
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(req.name.as_deref().map(str::trim))
+        .bind(&req.description)
+        .bind(organisation_id.is_some())
+        .bind(organisation_id.flatten())
+        .execute(&db.pool)
+        .await;
+
+        match updated {
+            Ok(result) if result.rows_affected() == 0 => return Err(FavouriteError::NotFound),
+            Ok(_) => {}
+            Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+                return Err(FavouriteError::Conflict("You already have a list with this name".to_string()));
+            }
+            Err(e) => return Err(e.into()),
+        }
+
+        Self::get_list(db, id).await?.ok_or(FavouriteError::NotFound)
+    }
+
+    /// Delete a list with the experts and services saved in it
+    pub async fn delete_list(db: &Database, id: Uuid, user_id: Uuid) -> Result<(), FavouriteError> {
+        let deleted = sqlx::query("DELETE FROM saved_lists WHERE id = $1 AND owner_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .execute(&db.pool)
+            .await?;
+
+        if deleted.rows_affected() == 0 {
+            return Err(FavouriteError::NotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn check_list_owner(db: &Database, list_id: Option<Uuid>, user_id: Uuid) -> Result<(), FavouriteError> {
+        let Some(list_id) = list_id else {
+            return Ok(());
+        };
+
+        let owned: bool =
+            sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM saved_lists WHERE id = $1 AND owner_id = $2)")
+                .bind(list_id)
+                .bind(user_id)
+                .fetch_one(&db.pool)
+                .await?;
+        if !owned {
+            return Err(FavouriteError::Invalid("List not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    // ==================== Saved experts ====================
+
+    /// Saved experts of the user, all or those of one list
+    pub async fn experts(db: &Database, user_id: Uuid, list_id: Option<Uuid>) -> Result<Vec<SavedExpert>, sqlx::Error> {
+        sqlx::query_as::<_, SavedExpert>(&format!(
+            r#"
+            SELECT {EXPERT_COLUMNS}
+            FROM saved_experts s
+            JOIN users u ON u.id = s.expert_id
+            LEFT JOIN expert_profiles ep ON ep.user_id = s.expert_id
+            WHERE s.client_id = $1 AND ($2::UUID IS NULL OR s.list_id = $2)
+            ORDER BY s.created_at DESC
+            "#
+        ))
+        .bind(user_id)
+        .bind(list_id)
+        .fetch_all(&db.pool)
+        .await
+    }
+
+    async fn get_expert(db: &Database, id: Uuid) -> Result<SavedExpert, FavouriteError> {
+        sqlx::query_as::<_, SavedExpert>(&format!(
+            r#"
+            SELECT {EXPERT_COLUMNS}
+            FROM saved_experts s
+            JOIN users u ON u.id = s.expert_id
+            LEFT JOIN expert_profiles ep ON ep.user_id = s.expert_id
+            WHERE s.id = $1
+            "#
+        ))
+        .bind(id)
+        .fetch_optional(&db.pool)
+        .await?
+        .ok_or(FavouriteError::NotFound)
+    }
+
+    /// Save an expert; saving again into the same list updates the notes
+    pub async fn save_expert(db: &Database, user_id: Uuid, req: &SaveExpertRequest) -> Result<SavedExpert, FavouriteError> {
+        Self::check_list_owner(db, req.list_id, user_id).await?;
+
+        let is_expert: bool =
+            sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM expert_profiles WHERE user_id = $1)")
+                .bind(req.expert_id)
+                .fetch_one(&db.pool)
+                .await?;
+        if !is_expert {
+            return Err(FavouriteError::Invalid("Expert not found".to_string()));
+        }
+
+        let id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO saved_experts (client_id, expert_id, list_id, notes)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (client_id, expert_id, COALESCE(list_id, '00000000-0000-0000-0000-000000000000'::uuid))
+            DO UPDATE SET notes = COALESCE(EXCLUDED.notes, saved_experts.notes)
+            RETURNING id
+            "#,
+        )
+        .bind(user_id)
+        .bind(req.expert_id)
+        .bind(req.list_id)
+        .bind(&req.notes)
+        .fetch_one(&db.pool)
+        .await?;
+
+        Self::get_expert(db, id).await
+    }
+
+    pub async fn update_expert_notes(
+        db: &Database,
+        id: Uuid,
+        user_id: Uuid,
+        notes: Option<&str>,
+    ) -> Result<SavedExpert, FavouriteError> {
+        let updated = sqlx::query("UPDATE saved_experts SET notes = $3 WHERE id = $1 AND client_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .bind(notes)
+            .execute(&db.pool)
+            .await?;
+        if updated.rows_affected() == 0 {
+            return Err(FavouriteError::NotFound);
+        }
+
+        Self::get_expert(db, id).await
+    }
+
+    pub async fn remove_expert(db: &Database, id: Uuid, user_id: Uuid) -> Result<(), FavouriteError> {
+        let deleted = sqlx::query("DELETE FROM saved_experts WHERE id = $1 AND client_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .execute(&db.pool)
+            .await?;
+        if deleted.rows_affected() == 0 {
+            return Err(FavouriteError::NotFound);
+        }
+
+        Ok(())
+    }
+
+    // ==================== Saved services ====================
+
+    /// Saved services of the user, all or those of one list
+    pub async fn services(db: &Database, user_id: Uuid, list_id: Option<Uuid>) -> Result<Vec<SavedService>, sqlx::Error> {
+        sqlx::query_as::<_, SavedService>(&format!(
+            r#"
+            SELECT {SERVICE_COLUMNS}
+            FROM saved_services s
+            JOIN services sv ON sv.id = s.service_id
+            WHERE s.client_id = $1 AND ($2::UUID IS NULL OR s.list_id = $2)
+            ORDER BY s.created_at DESC
+            "#
+        ))
+        .bind(user_id)
+        .bind(list_id)
+        .fetch_all(&db.pool)
+        .await
+    }
+
+    async fn get_service(db: &Database, id: Uuid) -> Result<SavedService, FavouriteError> {
+        sqlx::query_as::<_, SavedService>(&format!(
+            r#"
+            SELECT {SERVICE_COLUMNS}
+            FROM saved_services s
+            JOIN services sv ON sv.id = s.service_id
+            WHERE s.id = $1
+            "#
+        ))
+        .bind(id)
+        .fetch_optional(&db.pool)
+        .await?
+        .ok_or(FavouriteError::NotFound)
+    }
+
+    /// Save a service; saving again into the same list updates the notes
+    pub async fn save_service(
+        db: &Database,
+        user_id: Uuid,
+        req: &SaveServiceRequest,
+    ) -> Result<SavedService, FavouriteError> {
+        Self::check_list_owner(db, req.list_id, user_id).await?;
+
+        let is_active: bool =
+            sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM services WHERE id = $1 AND is_active = true)")
+                .bind(req.service_id)
+                .fetch_one(&db.pool)
+                .await?;
+        if !is_active {
+            return Err(FavouriteError::Invalid("Service not found".to_string()));
+        }
+
+        let id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO saved_services (client_id, service_id, list_id, notes)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (client_id, service_id, COALESCE(list_id, '00000000-0000-0000-0000-000000000000'::uuid))
+            DO UPDATE SET notes = COALESCE(EXCLUDED.notes, saved_services.notes)
+            RETURNING id
+            "#,
+        )
+        .bind(user_id)
+        .bind(req.service_id)
+        .bind(req.list_id)
+        .bind(&req.notes)
+        .fetch_one(&db.pool)
+        .await?;
+
+        Self::get_service(db, id).await
+    }
+
+    pub async fn update_service_notes(
+        db: &Database,
+        id: Uuid,
+        user_id: Uuid,
+        notes: Option<&str>,
+    ) -> Result<SavedService, FavouriteError> {
+        let updated = sqlx::query("UPDATE saved_services SET notes = $3 WHERE id = $1 AND client_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .bind(notes)
+            .execute(&db.pool)
+            .await?;
+        if updated.rows_affected() == 0 {
+            return Err(FavouriteError::NotFound);
+        }
+
+        Self::get_service(db, id).await
+    }
+
+    pub async fn remove_service(db: &Database, id: Uuid, user_id: Uuid) -> Result<(), FavouriteError> {
+        let deleted = sqlx::query("DELETE FROM saved_services WHERE id = $1 AND client_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .execute(&db.pool)
+            .await?;
+        if deleted.rows_affected() == 0 {
+            return Err(FavouriteError::NotFound);
+        }
+
+        Ok(())
+    }
+
+    // ==================== Notifications ====================
+
+    /// Notify everyone who saved the expert that they are available again
+    pub async fn notify_expert_available(db: &Database, expert_user_id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO notifications (user_id, type, title, message, data)
+            SELECT DISTINCT s.client_id, 'saved_expert_available', 'Gespeicherter Experte verfügbar',
+                   u.first_name || ' ' || u.last_name || ' ist wieder verfügbar.',
+                   jsonb_build_object('expertId', s.expert_id)
+            FROM saved_experts s
+            JOIN users u ON u.id = s.expert_id
+            WHERE s.expert_id = $1
+            "#,
+        )
+        .bind(expert_user_id)
+        .execute(&db.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Notify everyone who saved the expert about a newly published service
+    pub async fn notify_new_service(db: &Database, service: &Service) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO notifications (user_id, type, title, message, data)
+            SELECT DISTINCT s.client_id, 'saved_expert_new_service', 'Neuer Service',
+                   u.first_name || ' ' || u.last_name || ' bietet einen neuen Service an: ' || $2,
+                   jsonb_build_object('expertId', s.expert_id, 'serviceId', $3::UUID)
+            FROM expert_profiles ep
+            JOIN saved_experts s ON s.expert_id = ep.user_id
+            JOIN users u ON u.id = ep.user_id
+            WHERE ep.id = $1
+            "#,
+        )
+        .bind(service.expert_id)
+        .bind(&service.title)
+        .bind(service.id)
+        .execute(&db.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,639 @@
+//! Job alerts: new postings are scored against expert profiles (skills, tools, industries,
+//! languages, rate and availability) and delivered in-app and by instant or daily digest
+//! email according to each expert's preferences and alert filters.
+
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::models::{
+    AlertCandidate, AlertPosting, AvailabilityStatus, CreateJobAlertFilterRequest, JobAlert, JobAlertEmail,
+    JobAlertFilter, JobAlertFrequency, JobAlertPreferences, JobMatch, PaginatedResponse, PaginationMeta,
+    ProjectPosting, ProjectPostingBudgetType, ProjectPostingStatus, PostingVisibility, UpdateJobAlertFilterRequest,
+    UpdateJobAlertPreferencesRequest,
+};
+use crate::services::NotificationService;
+
+/// Error of a job alert operation
+#[derive(Debug, thiserror::Error)]
+pub enum JobAlertError {
+    #[error("Alert filter not found")]
+    NotFound,
+
+    #[error("{0}")]
+    Forbidden(String),
+
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+/// Minimum match score for experts without preferences (matches the column default)
+const DEFAULT_MIN_SCORE: i16 = 60;
+
+/// Postings matched per job run
+const MATCH_BATCH_SIZE: i64 = 50;
+
+pub struct JobAlertService;
+
+impl JobAlertService {
+    /// Entries of `required` also in `offered`, compared case-insensitively
+    fn overlap(required: &[String], offered: &[String]) -> Vec<String> {
+        required
+            .iter()
+            .filter(|r| offered.iter().any(|o| o.eq_ignore_ascii_case(r)))
+            .cloned()
+            .collect()
+    }
+
+    /// Points for the share of `required` the expert covers; half the points if nothing is required
+    fn coverage_points(required: &[String], matched: &[String], points: f32) -> f32 {
+        if required.is_empty() {
+            points / 2.0
+        } else {
+            points * matched.len() as f32 / required.len() as f32
+        }
+    }
+
+    /// Score how well a posting fits an expert (0-100); unavailable experts do not match.
+    /// Weights: skills 35, tools 20, availability 15, industry 10, language 10, rate 10.
+    pub fn score(posting: &AlertPosting, expert: &AlertCandidate) -> Option<JobMatch> {
+        let availability = match expert.availability_status {
+            AvailabilityStatus::Available => 15.0,
+            AvailabilityStatus::PartiallyAvailable => 10.0,
+            AvailabilityStatus::Busy => 0.0,
+            AvailabilityStatus::NotAvailable => return None,
+        };
+
+        let p = &posting.posting;
+        let matched_skills = Self::overlap(&p.skills_required, &expert.skills);
+        let matched_tools = Self::overlap(&p.tools_required, &expert.tools);
+
+        let industry = match &posting.client_industry {
+            Some(industry) if expert.industries.iter().any(|i| i.eq_ignore_ascii_case(industry)) => 10.0,
+            Some(_) => 0.0,
+            None => 5.0,
+        };
+
+        let client_language = format!("{:?}", posting.client_language);
+        let language = if expert.languages_spoken.is_empty() {
+            5.0
+        } else if expert.languages_spoken.iter().any(|l| l.eq_ignore_ascii_case(&client_language)) {
+            10.0
+        } else {
+            0.0
+        };
+
+        // Only hourly budgets in the expert's currency can be compared with the hourly rate
+        let rate = match (&p.budget_type, p.budget_max) {
+            (ProjectPostingBudgetType::Hourly, Some(max)) if p.currency == expert.currency => {
+                if expert.hourly_rate <= max { 10.0 } else { 0.0 }
+            }
+            _ => 5.0,
+        };
+
+        let score = Self::coverage_points(&p.skills_required, &matched_skills, 35.0)
+            + Self::coverage_points(&p.tools_required, &matched_tools, 20.0)
+            + availability
+            + industry
+            + language
+            + rate;
+
+        Some(JobMatch { score: score.round() as i16, matched_skills, matched_tools })
+    }
+
+    /// Whether a posting passes an alert filter
+    pub fn filter_matches(filter: &JobAlertFilter, posting: &ProjectPosting) -> bool {
+        let text = format!("{} {}", posting.title, posting.description).to_lowercase();
+        let keywords = filter.keywords.is_empty() || filter.keywords.iter().any(|k| text.contains(&k.to_lowercase()));
+        let skills = filter.skills.is_empty() || !Self::overlap(&filter.skills, &posting.skills_required).is_empty();
+        let category = filter.category_id.is_none() || filter.category_id == posting.category_id;
+        // Postings without a budget are not filtered out
+        let budget = match (filter.min_budget, posting.budget_max.or(posting.budget_min)) {
+            (Some(min), Some(budget)) => budget >= min,
+            _ => true,
+        };
+        let urgent = !filter.urgent_only || posting.is_urgent;
+
+        keywords && skills && category && budget && urgent
+    }
+
+    async fn ensure_expert(db: &Database, user_id: Uuid) -> Result<(), JobAlertError> {
+        let is_expert: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM expert_profiles WHERE user_id = $1)")
+            .bind(user_id)
+            .fetch_one(&db.pool)
+            .await?;
+
+        if !is_expert {
+            return Err(JobAlertError::Forbidden("Job alerts are only available to experts".to_string()));
+        }
+
+        Ok(())
+    }
+
+    // ==================== Preferences ====================
+
+    /// Alert preferences of an expert, with defaults if never set
+    pub async fn preferences(db: &Database, expert_id: Uuid) -> Result<JobAlertPreferences, JobAlertError> {
+        Self::ensure_expert(db, expert_id).await?;
+
+        let preferences = sqlx::query_as::<_, JobAlertPreferences>(
+            r#"
+            SELECT expert_id, in_app, email_frequency, min_score, last_digest_at
+            FROM job_alert_preferences WHERE expert_id = $1
+            "#,
+        )
+        .bind(expert_id)
+        .fetch_optional(&db.pool)
+        .await?;
+
+        Ok(preferences.unwrap_or(JobAlertPreferences {
+            expert_id,
+            in_app: true,
+            email_frequency: JobAlertFrequency::default(),
+            min_score: DEFAULT_MIN_SCORE,
+            last_digest_at: None,
+        }))
+    }
+
+    pub async fn update_preferences(
+        db: &Database,
+        expert_id: Uuid,
+        req: &UpdateJobAlertPreferencesRequest,
+    ) -> Result<JobAlertPreferences, JobAlertError> {
+        let current = Self::preferences(db, expert_id).await?;
+
+        let preferences = sqlx::query_as::<_, JobAlertPreferences>(
+            r#"
+            INSERT INTO job_alert_preferences (expert_id, in_app, email_frequency, min_score)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (expert_id) DO UPDATE
+            SET in_app = EXCLUDED.in_app, email_frequency = EXCLUDED.email_frequency,
+                min_score = EXCLUDED.min_score, updated_at = NOW()
+            RETURNING expert_id, in_app, email_frequency, min_score, last_digest_at
+            "#,
+        )
+        .bind(expert_id)
+        .bind(req.in_app.unwrap_or(current.in_app))
+        .bind(req.email_frequency.unwrap_or(current.email_frequency))
+        .bind(req.min_score.unwrap_or(current.min_score))
+        .fetch_one(&db.pool)
+        .await?;
+
+        Ok(preferences)
+    }
+
+    // ==================== Filters ====================
+
+    pub async fn filters(db: &Database, expert_id: Uuid) -> Result<Vec<JobAlertFilter>, sqlx::Error> {
+        sqlx::query_as::<_, JobAlertFilter>("SELECT * FROM job_alert_filters WHERE expert_id = $1 ORDER BY created_at")
+            .bind(expert_id)
+            .fetch_all(&db.pool)
+            .await
+    }
+
+    pub async fn create_filter(
+        db: &Database,
+        expert_id: Uuid,
+        req: &CreateJobAlertFilterRequest,
+    ) -> Result<JobAlertFilter, JobAlertError> {
+        Self::ensure_expert(db, expert_id).await?;
+
+        let filter = sqlx::query_as::<_, JobAlertFilter>(
+            r#"
+            INSERT INTO job_alert_filters (expert_id, name, keywords, skills, category_id, min_budget, urgent_only)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING *
+            "#,
+        )
+        .bind(expert_id)
+        .bind(&req.name)
+        .bind(req.keywords.clone().unwrap_or_default())
+        .bind(req.skills.clone().unwrap_or_default())
+        .bind(req.category_id)
+        .bind(req.min_budget)
+        .bind(req.urgent_only.unwrap_or(false))
+        .fetch_one(&db.pool)
+        .await?;
+
+        Ok(filter)
+    }
+
+    pub async fn update_filter(
+        db: &Database,
+        id: Uuid,
+        expert_id: Uuid,
+        req: &UpdateJobAlertFilterRequest,
+    ) -> Result<JobAlertFilter, JobAlertError> {
+        sqlx::query_as::<_, JobAlertFilter>(
+            r#"
+            UPDATE job_alert_filters
+            SET name = COALESCE($3, name),
+                keywords = COALESCE($4, keywords),
+                skills = COALESCE($5, skills),
+                category_id = COALESCE($6, category_id),
+                min_budget = COALESCE($7, min_budget),
+                urgent_only = COALESCE($8, urgent_only),
+                is_active = COALESCE($9, is_active),
+                updated_at = NOW()
+            WHERE id = $1 AND expert_id = $2
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(expert_id)
+        .bind(&req.name)
+        .bind(&req.keywords)
+        .bind(&req.skills)
+        .bind(req.category_id)
+        .bind(req.min_budget)
+        .bind(req.urgent_only)
+        .bind(req.is_active)
+        .fetch_optional(&db.pool)
+        .await?
+        .ok_or(JobAlertError::NotFound)
+    }
+
+    pub async fn delete_filter(db: &Database, id: Uuid, expert_id: Uuid) -> Result<(), JobAlertError> {
+        let result = sqlx::query("DELETE FROM job_alert_filters WHERE id = $1 AND expert_id = $2")
+            .bind(id)
+            .bind(expert_id)
+            .execute(&db.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(JobAlertError::NotFound);
+        }
+
+        Ok(())
+    }
+
+    // ==================== Alerts ====================
+
+    /// Alerts received by an expert, newest first
+    pub async fn alerts(
+        db: &Database,
+        expert_id: Uuid,
+        page: u32,
+        per_page: u32,
+    ) -> Result<PaginatedResponse<JobAlert>, sqlx::Error> {
+        let offset = (page.max(1) - 1) * per_page;
+
+        let alerts = sqlx::query_as::<_, JobAlert>(
+            r#"
+            SELECT a.id, a.project_posting_id, pp.title AS posting_title, a.score,
+                   a.matched_skills, a.matched_tools, a.emailed_at, a.created_at
+            FROM job_alerts a
+            JOIN project_postings pp ON pp.id = a.project_posting_id
+            WHERE a.expert_id = $1
+            ORDER BY a.created_at DESC
+            LIMIT $2 OFFSET $3
+            "#,
+        )
+        .bind(expert_id)
+        .bind(per_page as i64)
+        .bind(offset as i64)
+        .fetch_all(&db.pool)
+        .await?;
+
+        let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM job_alerts WHERE expert_id = $1")
+            .bind(expert_id)
+            .fetch_one(&db.pool)
+            .await?;
+
+        Ok(PaginatedResponse {
+            data: alerts,
+            meta: PaginationMeta::new(page, per_page, total),
+        })
+    }
+
+    /// Match postings not yet matched against all experts, record alerts and notify in-app.
+    /// Returns the alerts of experts who want them emailed instantly.
+    pub async fn match_new_postings(db: &Database) -> Result<Vec<JobAlertEmail>, sqlx::Error> {
+        let mut tx = db.pool.begin().await?;
+
+        let postings = sqlx::query_as::<_, AlertPosting>(
+            r#"
+            SELECT pp.*, cp.industry AS client_industry, u.preferred_language AS client_language
+            FROM project_postings pp
+            JOIN users u ON u.id = pp.client_id
+            LEFT JOIN client_profiles cp ON cp.user_id = pp.client_id
+            WHERE pp.alerts_matched_at IS NULL
+            ORDER BY pp.created_at
+            LIMIT $1
+            FOR UPDATE OF pp SKIP LOCKED
+            "#,
+        )
+        .bind(MATCH_BATCH_SIZE)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        if postings.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let candidates = sqlx::query_as::<_, AlertCandidate>(
+            r#"
+            SELECT ep.user_id AS expert_id, u.email, u.first_name, u.preferred_language AS language,
+                   ep.skills, ep.tools, ep.industries, ep.languages_spoken, ep.hourly_rate, ep.currency,
+                   ep.availability_status,
+                   COALESCE(p.in_app, TRUE) AS in_app,
+                   COALESCE(p.email_frequency, 'daily') AS email_frequency,
+                   COALESCE(p.min_score, $1) AS min_score
+            FROM expert_profiles ep
+            JOIN users u ON u.id = ep.user_id
+            LEFT JOIN job_alert_preferences p ON p.expert_id = ep.user_id
+            WHERE u.status = 'active' AND ep.availability_status <> 'not_available'
+              AND (COALESCE(p.in_app, TRUE) OR COALESCE(p.email_frequency, 'daily') <> 'off')
+            "#,
+        )
+        .bind(DEFAULT_MIN_SCORE)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut filters: HashMap<Uuid, Vec<JobAlertFilter>> = HashMap::new();
+        for filter in sqlx::query_as::<_, JobAlertFilter>("SELECT * FROM job_alert_filters WHERE is_active")
+            .fetch_all(&mut *tx)
+            .await?
+        {
+            filters.entry(filter.expert_id).or_default().push(filter);
+        }
+
+        let mut emails = Vec::new();
+        for alert_posting in &postings {
+            let posting = &alert_posting.posting;
+            // Invite-only postings reach their invited experts only
+            let published = posting.visibility == PostingVisibility::Public
+                && matches!(
+                    posting.status,
+                    ProjectPostingStatus::Draft | ProjectPostingStatus::Open | ProjectPostingStatus::InReview
+                );
+            if !published {
+                continue;
+            }
+
+            for expert in &candidates {
+                if expert.expert_id == posting.client_id {
+                    continue;
+                }
+                let Some(job_match) = Self::score(alert_posting, expert) else {
+                    continue;
+                };
+                if job_match.score < expert.min_score {
+                    continue;
+                }
+                if let Some(expert_filters) = filters.get(&expert.expert_id)
+                    && !expert_filters.iter().any(|f| Self::filter_matches(f, posting))
+                {
+                    continue;
+                }
+
+                let alert_id: Option<Uuid> = sqlx::query_scalar(
+                    r#"
+                    INSERT INTO job_alerts (expert_id, project_posting_id, score, matched_skills, matched_tools)
+                    VALUES ($1, $2, $3, $4, $5)
+                    ON CONFLICT (expert_id, project_posting_id) DO NOTHING
+                    RETURNING id
+                    "#,
+                )
+                .bind(expert.expert_id)
+                .bind(posting.id)
+                .bind(job_match.score)
+                .bind(&job_match.matched_skills)
+                .bind(&job_match.matched_tools)
+                .fetch_optional(&mut *tx)
+                .await?;
+                let Some(alert_id) = alert_id else {
+                    continue;
+                };
+
+                if expert.in_app {
+                    NotificationService::create(
+                        &mut *tx,
+                        expert.expert_id,
+                        "job_alert",
+                        "Neues passendes Projekt",
+                        &format!(
+                            "Die Ausschreibung \"{}\" passt zu Ihrem Profil ({} % Übereinstimmung).",
+                            posting.title, job_match.score
+                        ),
+                        Some(serde_json::json!({
+                            "alertId": alert_id,
+                            "postingId": posting.id,
+                            "score": job_match.score,
+                        })),
+                    )
+                    .await?;
+                }
+
+                if expert.email_frequency == JobAlertFrequency::Instant {
+                    emails.push(JobAlertEmail {
+                        alert_id,
+                        expert_id: expert.expert_id,
+                        email: expert.email.clone(),
+                        first_name: expert.first_name.clone(),
+                        language: expert.language.clone(),
+                        posting_id: posting.id,
+                        title: posting.title.clone(),
+                        score: job_match.score,
+                    });
+                }
+            }
+        }
+
+        let posting_ids: Vec<Uuid> = postings.iter().map(|p| p.posting.id).collect();
+        sqlx::query("UPDATE project_postings SET alerts_matched_at = NOW() WHERE id = ANY($1)")
+            .bind(&posting_ids)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(emails)
+    }
+
+    /// Unsent alerts of experts on daily digests who have not had today's digest yet,
+    /// grouped by expert and best matches first
+    pub async fn due_digests(db: &Database) -> Result<Vec<JobAlertEmail>, sqlx::Error> {
+        sqlx::query_as::<_, JobAlertEmail>(
+            r#"
+            SELECT a.id AS alert_id, a.expert_id, u.email, u.first_name, u.preferred_language AS language,
+                   pp.id AS posting_id, pp.title, a.score
+            FROM job_alerts a
+            JOIN users u ON u.id = a.expert_id
+            JOIN project_postings pp ON pp.id = a.project_posting_id
+            LEFT JOIN job_alert_preferences p ON p.expert_id = a.expert_id
+            WHERE a.emailed_at IS NULL
+              AND COALESCE(p.email_frequency, 'daily') = 'daily'
+              AND (p.last_digest_at IS NULL
+                   OR p.last_digest_at < date_trunc('day', NOW() AT TIME ZONE 'UTC') AT TIME ZONE 'UTC')
+              AND a.created_at > COALESCE(p.last_digest_at, NOW() - INTERVAL '1 day')
+              AND pp.status IN ('draft', 'open', 'in_review')
+            ORDER BY a.expert_id, a.score DESC, a.created_at
+            "#,
+        )
+        .fetch_all(&db.pool)
+        .await
+    }
+
+    /// Record that alerts were emailed
+    pub async fn mark_emailed(db: &Database, alert_ids: &[Uuid]) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE job_alerts SET emailed_at = NOW() WHERE id = ANY($1)")
+            .bind(alert_ids)
+            .execute(&db.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Record that an expert's daily digest with these alerts was sent
+    pub async fn mark_digest_sent(db: &Database, expert_id: Uuid, alert_ids: &[Uuid]) -> Result<(), sqlx::Error> {
+        let mut tx = db.pool.begin().await?;
+
+        sqlx::query("UPDATE job_alerts SET emailed_at = NOW() WHERE id = ANY($1)")
+            .bind(alert_ids)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO job_alert_preferences (expert_id, last_digest_at) VALUES ($1, NOW())
+            ON CONFLICT (expert_id) DO UPDATE SET last_digest_at = NOW()
+            "#,
+        )
+        .bind(expert_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Currency, Language};
+    use chrono::Utc;
+
+    fn posting() -> AlertPosting {
+        AlertPosting {
+            posting: ProjectPosting {
+                id: Uuid::new_v4(),
+                client_id: Uuid::new_v4(),
+                title: "n8n Workflow für CRM-Synchronisation".to_string(),
+                description: "Wir suchen Unterstützung bei der Automatisierung unseres Vertriebs.".to_string(),
+                requirements: None,
+                category_id: None,
+                skills_required: vec!["n8n".to_string(), "API Integration".to_string()],
+                tools_required: vec!["HubSpot".to_string()],
+                budget_type: ProjectPostingBudgetType::Hourly,
+                budget_min: Some(8000),
+                budget_max: Some(12000),
+                currency: Currency::CHF,
+                deadline: None,
+                estimated_duration: None,
+                status: ProjectPostingStatus::Open,
+                is_urgent: false,
+                is_featured: false,
+                attachments: vec![],
+                view_count: 0,
+                proposal_count: 0,
+                assigned_expert_id: None,
+                assigned_at: None,
+                completed_at: None,
+                cancelled_at: None,
+                cancellation_reason: None,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                visibility: PostingVisibility::Public,
+                expires_at: None,
+                expired_at: None,
+                reposted_from_id: None,
+            },
+            client_industry: Some("Retail".to_string()),
+            client_language: Language::De,
+        }
+    }
+
+    fn expert() -> AlertCandidate {
+        AlertCandidate {
+            expert_id: Uuid::new_v4(),
+            email: "expert@example.com".to_string(),
+            first_name: "Anna".to_string(),
+            language: Language::De,
+            skills: vec!["N8N".to_string(), "api integration".to_string()],
+            tools: vec!["HubSpot".to_string()],
+            industries: vec!["Retail".to_string()],
+            languages_spoken: vec!["de".to_string(), "en".to_string()],
+            hourly_rate: 10000,
+            currency: Currency::CHF,
+            availability_status: AvailabilityStatus::Available,
+            in_app: true,
+            email_frequency: JobAlertFrequency::Daily,
+            min_score: DEFAULT_MIN_SCORE,
+        }
+    }
+
+    fn filter() -> JobAlertFilter {
+        JobAlertFilter {
+            id: Uuid::new_v4(),
+            expert_id: Uuid::new_v4(),
+            name: "CRM".to_string(),
+            keywords: vec![],
+            skills: vec![],
+            category_id: None,
+            min_budget: None,
+            urgent_only: false,
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_score_perfect_match() {
+        let job_match = JobAlertService::score(&posting(), &expert()).unwrap();
+        assert_eq!(job_match.score, 100);
+        assert_eq!(job_match.matched_skills, vec!["n8n".to_string(), "API Integration".to_string()]);
+        assert_eq!(job_match.matched_tools, vec!["HubSpot".to_string()]);
+    }
+
+    #[test]
+    fn test_score_partial_match() {
+        let mut expert = expert();
+        expert.skills = vec!["n8n".to_string()];
+        expert.tools = vec![];
+        expert.industries = vec![];
+        expert.hourly_rate = 15000;
+        expert.availability_status = AvailabilityStatus::PartiallyAvailable;
+
+        // Half the skills (17.5), no tools, partly available (10), language (10)
+        assert_eq!(JobAlertService::score(&posting(), &expert).unwrap().score, 38);
+
+        expert.availability_status = AvailabilityStatus::NotAvailable;
+        assert!(JobAlertService::score(&posting(), &expert).is_none());
+    }
+
+    #[test]
+    fn test_filter_matches() {
+        let posting = posting();
+        assert!(JobAlertService::filter_matches(&filter(), &posting.posting));
+
+        let mut keyword = filter();
+        keyword.keywords = vec!["crm".to_string()];
+        assert!(JobAlertService::filter_matches(&keyword, &posting.posting));
+        keyword.keywords = vec!["Shopify".to_string()];
+        assert!(!JobAlertService::filter_matches(&keyword, &posting.posting));
+
+        let mut budget = filter();
+        budget.min_budget = Some(15000);
+        assert!(!JobAlertService::filter_matches(&budget, &posting.posting));
+
+        let mut urgent = filter();
+        urgent.urgent_only = true;
+        urgent.skills = vec!["N8N".to_string()];
+        assert!(!JobAlertService::filter_matches(&urgent, &posting.posting));
+    }
+}
@@ -1,11 +1,12 @@
 use chrono::Utc;
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool};
 use uuid::Uuid;
 
 use crate::models::{
     Conversation, ConversationPreview, Message, MessagePreview, MessageType,
     ParticipantInfo, SendMessageRequest, StartConversationRequest,
 };
+use crate::services::ExpertMetricsService;
 
 pub struct MessageService;
 
@@ -114,7 +115,7 @@ impl MessageService {
 
     /// Start or get existing conversation
     pub async fn get_or_create_conversation(
-        pool: &PgPool,
+        conn: &mut PgConnection,
         user_one: Uuid,
         user_two: Uuid,
         service_id: Option<Uuid>,
@@ -135,7 +136,7 @@ impl MessageService {
         )
         .bind(p1)
         .bind(p2)
-        .fetch_optional(pool)
+        .fetch_optional(&mut *conn)
         .await?;
 
         if let Some(conv) = existing {
@@ -145,15 +146,18 @@ impl MessageService {
         // Create new
         let conv: Conversation = sqlx::query_as(
             r#"
-            INSERT INTO conversations (participant_one_id, participant_two_id, service_id)
-            VALUES ($1, $2, $3)
+            INSERT INTO conversations (participant_one_id, participant_two_id, service_id, agency_id, organisation_id)
+            VALUES (
+                $1, $2, $3, (SELECT agency_id FROM services WHERE id = $3),
+                (SELECT organisation_id FROM organisation_members WHERE user_id IN ($1, $2) LIMIT 1)
+            )
             RETURNING *
             "#,
         )
         .bind(p1)
         .bind(p2)
         .bind(service_id)
-        .fetch_one(pool)
+        .fetch_one(&mut *conn)
         .await?;
 
         Ok(conv)
@@ -165,17 +169,21 @@ impl MessageService {
         sender_id: Uuid,
         req: StartConversationRequest,
     ) -> Result<Conversation, sqlx::Error> {
-        let conv = Self::get_or_create_conversation(pool, sender_id, req.recipient_id, req.service_id).await?;
+        let mut tx = pool.begin().await?;
+
+        let conv = Self::get_or_create_conversation(&mut tx, sender_id, req.recipient_id, req.service_id).await?;
 
         // Send initial message
-        Self::send_message_internal(pool, sender_id, conv.id, &req.initial_message, MessageType::Text).await?;
+        Self::send_message_internal(&mut tx, sender_id, sender_id, conv.id, &req.initial_message, MessageType::Text).await?;
 
         // Refresh conversation to get updated fields
         let updated: Conversation = sqlx::query_as("SELECT * FROM conversations WHERE id = $1")
             .bind(conv.id)
-            .fetch_one(pool)
+            .fetch_one(&mut *tx)
             .await?;
 
+        tx.commit().await?;
+
         Ok(updated)
     }
 
@@ -185,22 +193,90 @@ impl MessageService {
         sender_id: Uuid,
         req: SendMessageRequest,
     ) -> Result<Message, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
         let conversation_id = if let Some(cid) = req.conversation_id {
             cid
         } else if let Some(recipient_id) = req.recipient_id {
-            let conv = Self::get_or_create_conversation(pool, sender_id, recipient_id, req.service_id).await?;
+            let conv = Self::get_or_create_conversation(&mut tx, sender_id, recipient_id, req.service_id).await?;
             conv.id
         } else {
             return Err(sqlx::Error::Protocol("conversation_id or recipient_id required".into()));
         };
 
         let msg_type = req.message_type.unwrap_or(MessageType::Text);
-        Self::send_message_internal(pool, sender_id, conversation_id, &req.content, msg_type).await
+        let message = Self::send_message_internal(&mut tx, sender_id, sender_id, conversation_id, &req.content, msg_type).await?;
+
+        tx.commit().await?;
+
+        Ok(message)
     }
 
-    async fn send_message_internal(
+    /// Post a system message into the conversation between two users
+    pub async fn send_system_message(
         pool: &PgPool,
         sender_id: Uuid,
+        recipient_id: Uuid,
+        project_id: Option<Uuid>,
+        content: &str,
+    ) -> Result<Message, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let message = Self::send_system_message_tx(&mut tx, sender_id, recipient_id, project_id, content).await?;
+        tx.commit().await?;
+
+        Ok(message)
+    }
+
+    /// Post a system message as part of the caller's transaction
+    pub async fn send_system_message_tx(
+        conn: &mut PgConnection,
+        sender_id: Uuid,
+        recipient_id: Uuid,
+        project_id: Option<Uuid>,
+        content: &str,
+    ) -> Result<Message, sqlx::Error> {
+        let conv = Self::get_or_create_conversation(conn, sender_id, recipient_id, None).await?;
+
+        if project_id.is_some() && conv.project_id.is_none() {
+            sqlx::query(
+                r#"
+                UPDATE conversations
+                SET project_id = $2,
+                    agency_id = COALESCE(agency_id, (SELECT agency_id FROM projects WHERE id = $2)),
+                    organisation_id = COALESCE(organisation_id, (SELECT organisation_id FROM projects WHERE id = $2))
+                WHERE id = $1
+                "#,
+            )
+                .bind(conv.id)
+                .bind(project_id)
+                .execute(&mut *conn)
+                .await?;
+        }
+
+        Self::send_message_internal(conn, sender_id, sender_id, conv.id, content, MessageType::System).await
+    }
+
+    /// Reply in a conversation for one of its participants (shared agency inbox)
+    pub async fn send_on_behalf(
+        pool: &PgPool,
+        sender_id: Uuid,
+        participant_id: Uuid,
+        conversation_id: Uuid,
+        content: &str,
+    ) -> Result<Message, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let message = Self::send_message_internal(&mut tx, sender_id, participant_id, conversation_id, content, MessageType::Text).await?;
+        tx.commit().await?;
+
+        Ok(message)
+    }
+
+    /// Insert a message; `side_id` is the participant the sender writes for,
+    /// the other participant's unread counter is incremented
+    async fn send_message_internal(
+        conn: &mut PgConnection,
+        sender_id: Uuid,
+        side_id: Uuid,
         conversation_id: Uuid,
         content: &str,
         message_type: MessageType,
@@ -219,9 +295,13 @@ impl MessageService {
         .bind(sender_id)
         .bind(content)
         .bind(&message_type)
-        .fetch_one(pool)
+        .fetch_one(&mut *conn)
         .await?;
 
+        if message_type != MessageType::System {
+            ExpertMetricsService::on_message(conn, conversation_id, sender_id).await?;
+        }
+
         // Update conversation
         let preview = if content.len() > 200 {
             format!("{}...", &content[..197])
@@ -232,10 +312,10 @@ impl MessageService {
         // Determine which unread counter to increment
         let conv: Conversation = sqlx::query_as("SELECT * FROM conversations WHERE id = $1")
             .bind(conversation_id)
-            .fetch_one(pool)
+            .fetch_one(&mut *conn)
             .await?;
 
-        let (inc_one, inc_two) = if sender_id == conv.participant_one_id {
+        let (inc_one, inc_two) = if side_id == conv.participant_one_id {
             (0, 1)
         } else {
             (1, 0)
@@ -256,7 +336,7 @@ impl MessageService {
         .bind(&preview)
         .bind(inc_one)
         .bind(inc_two)
-        .execute(pool)
+        .execute(&mut *conn)
         .await?;
 
         Ok(message)
@@ -286,6 +366,16 @@ impl MessageService {
             return Err(sqlx::Error::RowNotFound);
         }
 
+        Self::list_messages(pool, conversation_id, page, per_page).await
+    }
+
+    /// Messages of a conversation, newest first (access must be checked by the caller)
+    pub async fn list_messages(
+        pool: &PgPool,
+        conversation_id: Uuid,
+        page: u32,
+        per_page: u32,
+    ) -> Result<(Vec<Message>, i64), sqlx::Error> {
         let offset = (page.saturating_sub(1) * per_page) as i64;
         let limit = per_page as i64;
 
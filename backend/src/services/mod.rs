@@ -11,6 +11,28 @@ pub mod payment_service;
 pub mod admin_service;
 pub mod category_service;
 pub mod report_service;
+pub mod notification_service;
+pub mod tip_service;
+pub mod dunning_service;
+pub mod billing_service;
+pub mod cancellation_service;
+pub mod tax_report_service;
+pub mod project_event_service;
+pub mod deliverable_service;
+pub mod auto_complete_service;
+pub mod expert_metrics_service;
+pub mod proposal_service;
+pub mod booking_service;
+pub mod calendar_service;
+pub mod requirement_service;
+pub mod change_request_service;
+pub mod contract_service;
+pub mod agency_service;
+pub mod organisation_service;
+pub mod favourite_service;
+pub mod posting_invitation_service;
+pub mod posting_service;
+pub mod job_alert_service;
 
 // Optional feature-gated modules
 #[cfg(feature = "search")]
@@ -33,6 +55,28 @@ pub use payment_service::*;
 pub use admin_service::*;
 pub use category_service::*;
 pub use report_service::*;
+pub use notification_service::*;
+pub use tip_service::*;
+pub use dunning_service::*;
+pub use billing_service::*;
+pub use cancellation_service::*;
+pub use tax_report_service::*;
+pub use project_event_service::*;
+pub use deliverable_service::*;
+pub use auto_complete_service::*;
+pub use expert_metrics_service::*;
+pub use proposal_service::*;
+pub use booking_service::*;
+pub use calendar_service::*;
+pub use requirement_service::*;
+pub use change_request_service::*;
+pub use contract_service::*;
+pub use agency_service::*;
+pub use organisation_service::*;
+pub use favourite_service::*;
+pub use posting_invitation_service::*;
+pub use posting_service::*;
+pub use job_alert_service::*;
 
 #[cfg(feature = "search")]
 pub use search_service::*;
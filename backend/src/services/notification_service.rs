@@ -0,0 +1,101 @@
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+
+use crate::models::Notification;
+
+pub struct NotificationService;
+
+impl NotificationService {
+    /// Create an in-app notification for a user (inside or outside a transaction)
+    pub async fn create<'e, E: PgExecutor<'e>>(
+        executor: E,
+        user_id: Uuid,
+        notification_type: &str,
+        title: &str,
+        message: &str,
+        data: Option<serde_json::Value>,
+    ) -> Result<Notification, sqlx::Error> {
+        sqlx::query_as::<_, Notification>(
+            r#"
+            INSERT INTO notifications (user_id, type, title, message, data)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING *
+            "#,
+        )
+        .bind(user_id)
+        .bind(notification_type)
+        .bind(title)
+        .bind(message)
+        .bind(data.map(sqlx::types::Json))
+        .fetch_one(executor)
+        .await
+    }
+
+    /// Get notifications for a user (newest first)
+    pub async fn get_for_user(
+        pool: &PgPool,
+        user_id: Uuid,
+        unread_only: bool,
+        page: u32,
+        per_page: u32,
+    ) -> Result<(Vec<Notification>, i64), sqlx::Error> {
+        let offset = (page.saturating_sub(1)) * per_page;
+
+        let notifications = sqlx::query_as::<_, Notification>(
+            r#"
+            SELECT * FROM notifications
+            WHERE user_id = $1 AND (NOT $2 OR is_read = FALSE)
+            ORDER BY created_at DESC
+            LIMIT $3 OFFSET $4
+            "#,
+        )
+        .bind(user_id)
+        .bind(unread_only)
+        .bind(per_page as i64)
+        .bind(offset as i64)
+        .fetch_all(pool)
+        .await?;
+
+        let total: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM notifications WHERE user_id = $1 AND (NOT $2 OR is_read = FALSE)"
+        )
+        .bind(user_id)
+        .bind(unread_only)
+        .fetch_one(pool)
+        .await?;
+
+        Ok((notifications, total))
+    }
+
+    /// Mark notifications as read (all unread ones when no IDs are given)
+    pub async fn mark_as_read(
+        pool: &PgPool,
+        user_id: Uuid,
+        notification_ids: Option<&[Uuid]>,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE notifications
+            SET is_read = TRUE, read_at = NOW()
+            WHERE user_id = $1 AND is_read = FALSE
+              AND ($2::uuid[] IS NULL OR id = ANY($2))
+            "#,
+        )
+        .bind(user_id)
+        .bind(notification_ids)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Get unread notification count
+    pub async fn get_unread_count(pool: &PgPool, user_id: Uuid) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar(
+            "SELECT COUNT(*) FROM notifications WHERE user_id = $1 AND is_read = FALSE"
+        )
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+    }
+}
@@ -0,0 +1,683 @@
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::models::{
+    AddOrganisationMemberRequest, ClientOrganisation, Conversation, CreateOrganisationRequest,
+    CreatePurchaseApprovalRequest, Invoice, OrganisationMember, OrganisationMembership, OrganisationRole, Project,
+    PurchaseApproval, PurchaseApprovalStatus, PurchaseSubject, UpdateOrganisationMemberRequest,
+    UpdateOrganisationRequest, check_purchase,
+};
+use crate::services::NotificationService;
+
+/// Error of a client organisation operation
+#[derive(Debug, thiserror::Error)]
+pub enum OrganisationError {
+    #[error("Organisation not found")]
+    NotFound,
+
+    #[error("{0}")]
+    Forbidden(String),
+
+    #[error("{0}")]
+    Conflict(String),
+
+    #[error("{0}")]
+    Invalid(String),
+
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+const MEMBER_COLUMNS: &str = r#"
+    m.id, m.organisation_id, m.user_id, m.role, m.spending_limit,
+    u.first_name, u.last_name, u.email, m.joined_at
+"#;
+
+pub struct OrganisationService;
+
+impl OrganisationService {
+    pub async fn get(db: &Database, id: Uuid) -> Result<Option<ClientOrganisation>, sqlx::Error> {
+        sqlx::query_as::<_, ClientOrganisation>("SELECT * FROM client_organisations WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&db.pool)
+            .await
+    }
+
+    /// Role of the user in the organisation, if they are a member
+    pub async fn role(db: &Database, organisation_id: Uuid, user_id: Uuid) -> Result<Option<OrganisationRole>, sqlx::Error> {
+        sqlx::query_scalar("SELECT role FROM organisation_members WHERE organisation_id = $1 AND user_id = $2")
+            .bind(organisation_id)
+            .bind(user_id)
+            .fetch_optional(&db.pool)
+            .await
+    }
+
+    /// Organisation of the user with their role, limit and spending this month
+    pub async fn membership(db: &Database, user_id: Uuid) -> Result<Option<OrganisationMembership>, sqlx::Error> {
+        let member: Option<(Uuid, OrganisationRole, Option<i32>)> = sqlx::query_as(
+            "SELECT organisation_id, role, spending_limit FROM organisation_members WHERE user_id = $1",
+        )
+        .bind(user_id)
+        .fetch_optional(&db.pool)
+        .await?;
+
+        let Some((organisation_id, role, spending_limit)) = member else {
+            return Ok(None);
+        };
+        let Some(organisation) = Self::get(db, organisation_id).await? else {
+            return Ok(None);
+        };
+
+        let mut conn = db.pool.acquire().await?;
+        let spent_this_month = Self::spent_this_month(&mut conn, organisation_id, user_id).await?;
+
+        Ok(Some(OrganisationMembership {
+            organisation,
+            role,
+            spending_limit,
+            spent_this_month,
+        }))
+    }
+
+    /// User whose billing terms apply to the purchases of `user_id`:
+    /// the billing contact of their organisation, or the user themselves
+    pub async fn billing_user(db: &Database, user_id: Uuid) -> Result<Uuid, sqlx::Error> {
+        let contact: Option<Option<Uuid>> = sqlx::query_scalar(
+            r#"
+            SELECT o.billing_contact_id
+            FROM organisation_members m
+            JOIN client_organisations o ON o.id = m.organisation_id
+            WHERE m.user_id = $1 AND m.role <> 'viewer'
+            "#,
+        )
+        .bind(user_id)
+        .fetch_optional(&db.pool)
+        .await?;
+
+        Ok(contact.flatten().unwrap_or(user_id))
+    }
+
+    /// Create an organisation; the creator becomes its admin and billing contact
+    pub async fn create(
+        db: &Database,
+        user_id: Uuid,
+        req: &CreateOrganisationRequest,
+    ) -> Result<ClientOrganisation, OrganisationError> {
+        let mut tx = db.pool.begin().await?;
+
+        let is_member: bool =
+            sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM organisation_members WHERE user_id = $1)")
+                .bind(user_id)
+                .fetch_one(&mut *tx)
+                .await?;
+        if is_member {
+            return Err(OrganisationError::Conflict("You are already a member of an organisation".to_string()));
+        }
+
+        let organisation = sqlx::query_as::<_, ClientOrganisation>(
+            r#"
+            INSERT INTO client_organisations (name, company_details, approval_threshold, billing_contact_id, created_by)
+            VALUES ($1, $2, $3, $4, $4)
+            RETURNING *
+            "#,
+        )
+        .bind(req.name.trim())
+        .bind(sqlx::types::Json(req.company_details.clone().unwrap_or_default()))
+        .bind(req.approval_threshold)
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query("INSERT INTO organisation_members (organisation_id, user_id, role) VALUES ($1, $2, 'admin')")
+            .bind(organisation.id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(organisation)
+    }
+
+    pub async fn update(
+        db: &Database,
+        id: Uuid,
+        req: &UpdateOrganisationRequest,
+    ) -> Result<ClientOrganisation, OrganisationError> {
+        let mut tx = db.pool.begin().await?;
+
+        if let Some(contact_id) = req.billing_contact_id
+            && Self::get_member(&mut tx, id, contact_id).await?.role != OrganisationRole::Admin
+        {
+            return Err(OrganisationError::Invalid("The billing contact must be an admin".to_string()));
+        }
+
+        let organisation = sqlx::query_as::<_, ClientOrganisation>(
+            r#"
+            UPDATE client_organisations SET
+                name = COALESCE($2, name),
+                company_details = COALESCE($3, company_details),
+                approval_threshold = CASE WHEN $5 THEN NULL ELSE COALESCE($4, approval_threshold) END,
+                billing_contact_id = COALESCE($6, billing_contact_id),
+                updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(req.name.as_deref().map(str::trim))
+        .bind(req.company_details.as_ref().map(sqlx::types::Json))
+        .bind(req.approval_threshold)
+        .bind(req.remove_approval_threshold)
+        .bind(req.billing_contact_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(OrganisationError::NotFound)?;
+
+        tx.commit().await?;
+
+        Ok(organisation)
+    }
+
+    pub async fn list_members(db: &Database, organisation_id: Uuid) -> Result<Vec<OrganisationMember>, sqlx::Error> {
+        sqlx::query_as::<_, OrganisationMember>(&format!(
+            r#"
+            SELECT {MEMBER_COLUMNS}
+            FROM organisation_members m
+            JOIN users u ON u.id = m.user_id
+            WHERE m.organisation_id = $1
+            ORDER BY m.role, m.joined_at
+            "#
+        ))
+        .bind(organisation_id)
+        .fetch_all(&db.pool)
+        .await
+    }
+
+    async fn get_member(
+        conn: &mut PgConnection,
+        organisation_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<OrganisationMember, OrganisationError> {
+        sqlx::query_as::<_, OrganisationMember>(&format!(
+            r#"
+            SELECT {MEMBER_COLUMNS}
+            FROM organisation_members m
+            JOIN users u ON u.id = m.user_id
+            WHERE m.organisation_id = $1 AND m.user_id = $2
+            "#
+        ))
+        .bind(organisation_id)
+        .bind(user_id)
+        .fetch_optional(conn)
+        .await?
+        .ok_or_else(|| OrganisationError::Invalid("User is not a member of this organisation".to_string()))
+    }
+
+    /// Add a client user to the organisation and notify them
+    pub async fn add_member(
+        db: &Database,
+        organisation: &ClientOrganisation,
+        invited_by: Uuid,
+        req: &AddOrganisationMemberRequest,
+    ) -> Result<OrganisationMember, OrganisationError> {
+        let mut tx = db.pool.begin().await?;
+
+        let is_client: bool =
+            sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM users WHERE id = $1 AND role = 'client')")
+                .bind(req.user_id)
+                .fetch_one(&mut *tx)
+                .await?;
+        if !is_client {
+            return Err(OrganisationError::Invalid("Only client accounts can join an organisation".to_string()));
+        }
+
+        let inserted = sqlx::query(
+            r#"
+            INSERT INTO organisation_members (organisation_id, user_id, role, spending_limit, invited_by)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (user_id) DO NOTHING
+            "#,
+        )
+        .bind(organisation.id)
+        .bind(req.user_id)
+        .bind(req.role.unwrap_or(OrganisationRole::Buyer))
+        .bind(req.spending_limit)
+        .bind(invited_by)
+        .execute(&mut *tx)
+        .await?;
+        if inserted.rows_affected() == 0 {
+            return Err(OrganisationError::Conflict("The user is already a member of an organisation".to_string()));
+        }
+
+        NotificationService::create(
+            &mut *tx,
+            req.user_id,
+            "organisation_member_added",
+            "Einer Organisation hinzugefügt",
+            &format!("Sie wurden der Organisation \"{}\" hinzugefügt.", organisation.name),
+            Some(serde_json::json!({ "organisationId": organisation.id })),
+        )
+        .await?;
+
+        let member = Self::get_member(&mut tx, organisation.id, req.user_id).await?;
+        tx.commit().await?;
+
+        Ok(member)
+    }
+
+    /// The billing contact must stay an admin and an organisation keeps at least one admin
+    async fn check_admin_change(
+        conn: &mut PgConnection,
+        organisation_id: Uuid,
+        member: &OrganisationMember,
+    ) -> Result<(), OrganisationError> {
+        if member.role != OrganisationRole::Admin {
+            return Ok(());
+        }
+
+        let (admins, is_billing_contact): (i64, bool) = sqlx::query_as(
+            r#"
+            SELECT (SELECT COUNT(*) FROM organisation_members WHERE organisation_id = $1 AND role = 'admin'),
+                   EXISTS (SELECT 1 FROM client_organisations WHERE id = $1 AND billing_contact_id = $2)
+            "#,
+        )
+        .bind(organisation_id)
+        .bind(member.user_id)
+        .fetch_one(conn)
+        .await?;
+
+        if is_billing_contact {
+            return Err(OrganisationError::Invalid("Choose another billing contact first".to_string()));
+        }
+        if admins <= 1 {
+            return Err(OrganisationError::Invalid("An organisation needs at least one admin".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Change a member's role or spending limit
+    pub async fn update_member(
+        db: &Database,
+        organisation_id: Uuid,
+        user_id: Uuid,
+        req: &UpdateOrganisationMemberRequest,
+    ) -> Result<OrganisationMember, OrganisationError> {
+        let mut tx = db.pool.begin().await?;
+        let member = Self::get_member(&mut tx, organisation_id, user_id).await?;
+        if req.role.is_some_and(|role| role != member.role) {
+            Self::check_admin_change(&mut tx, organisation_id, &member).await?;
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE organisation_members SET
+                role = COALESCE($3, role),
+                spending_limit = CASE WHEN $5 THEN NULL ELSE COALESCE($4, spending_limit) END,
+                updated_at = NOW()
+            WHERE organisation_id = $1 AND user_id = $2
+            "#,
+        )
+        .bind(organisation_id)
+        .bind(user_id)
+        .bind(req.role)
+        .bind(req.spending_limit)
+        .bind(req.remove_spending_limit)
+        .execute(&mut *tx)
+        .await?;
+
+        let member = Self::get_member(&mut tx, organisation_id, user_id).await?;
+        tx.commit().await?;
+
+        Ok(member)
+    }
+
+    /// Remove a member; their projects stay with the organisation, their shared lists are unshared
+    pub async fn remove_member(db: &Database, organisation_id: Uuid, user_id: Uuid) -> Result<(), OrganisationError> {
+        let mut tx = db.pool.begin().await?;
+        let member = Self::get_member(&mut tx, organisation_id, user_id).await?;
+        Self::check_admin_change(&mut tx, organisation_id, &member).await?;
+
+        sqlx::query("DELETE FROM organisation_members WHERE organisation_id = $1 AND user_id = $2")
+            .bind(organisation_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        // Favourite lists the member shared stay private to them
+        sqlx::query("UPDATE saved_lists SET organisation_id = NULL WHERE owner_id = $2 AND organisation_id = $1")
+            .bind(organisation_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Amount of the member's organisation projects created this calendar month (cents), including
+    /// the projects of open service checkouts
+    async fn spent_this_month(conn: &mut PgConnection, organisation_id: Uuid, user_id: Uuid) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+            SELECT COALESCE(SUM(price), 0)::BIGINT FROM projects
+            WHERE organisation_id = $1 AND client_id = $2
+              AND created_at >= date_trunc('month', NOW())
+              AND status NOT IN ('cancelled', 'refunded')
+            "#,
+        )
+        .bind(organisation_id)
+        .bind(user_id)
+        .fetch_one(conn)
+        .await
+    }
+
+    /// Check a purchase of `amount` cents against the buyer's role, spending limit and the
+    /// approval threshold inside the purchase's transaction. The member row stays locked
+    /// until the purchase commits, so concurrent purchases are checked one after the other;
+    /// a matching approved request is used up only if the purchase commits.
+    ///
+    /// Returns the organisation the purchase is made for, if any.
+    pub async fn authorize_purchase(
+        conn: &mut PgConnection,
+        user_id: Uuid,
+        subject: PurchaseSubject,
+        amount: i64,
+    ) -> Result<Option<Uuid>, OrganisationError> {
+        let member: Option<(Uuid, OrganisationRole, Option<i32>, Option<i32>)> = sqlx::query_as(
+            r#"
+            SELECT m.organisation_id, m.role, m.spending_limit, o.approval_threshold
+            FROM organisation_members m
+            JOIN client_organisations o ON o.id = m.organisation_id
+            WHERE m.user_id = $1
+            FOR UPDATE OF m
+            "#,
+        )
+        .bind(user_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        let Some((organisation_id, role, spending_limit, threshold)) = member else {
+            return Ok(None);
+        };
+
+        let approval: Option<Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT id FROM purchase_approvals
+            WHERE organisation_id = $1 AND requested_by = $2
+              AND status = 'approved' AND used_at IS NULL AND amount >= $3
+              AND service_id IS NOT DISTINCT FROM $4 AND proposal_id IS NOT DISTINCT FROM $5
+            ORDER BY decided_at
+            LIMIT 1
+            FOR UPDATE
+            "#,
+        )
+        .bind(organisation_id)
+        .bind(user_id)
+        .bind(amount)
+        .bind(subject.service_id)
+        .bind(subject.proposal_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        let spent = Self::spent_this_month(conn, organisation_id, user_id).await?;
+        check_purchase(role, spending_limit, spent, threshold, amount, approval.is_some())
+            .map_err(|block| OrganisationError::Forbidden(block.to_string()))?;
+
+        if let Some(approval_id) = approval {
+            sqlx::query("UPDATE purchase_approvals SET used_at = NOW(), updated_at = NOW() WHERE id = $1")
+                .bind(approval_id)
+                .execute(&mut *conn)
+                .await?;
+        }
+
+        Ok(Some(organisation_id))
+    }
+
+    /// Projects bought for the organisation, newest first
+    pub async fn list_projects(
+        db: &Database,
+        organisation_id: Uuid,
+        page: u32,
+        per_page: u32,
+    ) -> Result<(Vec<Project>, i64), sqlx::Error> {
+        let offset = (page.saturating_sub(1)) * per_page;
+
+        let projects = sqlx::query_as::<_, Project>(
+            "SELECT * FROM projects WHERE organisation_id = $1 ORDER BY created_at DESC LIMIT $2 OFFSET $3",
+        )
+        .bind(organisation_id)
+        .bind(per_page as i64)
+        .bind(offset as i64)
+        .fetch_all(&db.pool)
+        .await?;
+
+        let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM projects WHERE organisation_id = $1")
+            .bind(organisation_id)
+            .fetch_one(&db.pool)
+            .await?;
+
+        Ok((projects, total))
+    }
+
+    /// Role of the user in the organisation a project was bought for
+    pub async fn project_role(
+        db: &Database,
+        project: &Project,
+        user_id: Uuid,
+    ) -> Result<Option<OrganisationRole>, sqlx::Error> {
+        let Some(organisation_id) = project.organisation_id else {
+            return Ok(None);
+        };
+
+        Self::role(db, organisation_id, user_id).await
+    }
+
+    /// Conversations of the organisation's members with experts, latest first
+    pub async fn conversations(
+        db: &Database,
+        organisation_id: Uuid,
+        page: u32,
+        per_page: u32,
+    ) -> Result<(Vec<Conversation>, i64), sqlx::Error> {
+        let offset = (page.saturating_sub(1)) * per_page;
+
+        let conversations = sqlx::query_as::<_, Conversation>(
+            r#"
+            SELECT * FROM conversations
+            WHERE organisation_id = $1
+            ORDER BY last_message_at DESC NULLS LAST
+            LIMIT $2 OFFSET $3
+            "#,
+        )
+        .bind(organisation_id)
+        .bind(per_page as i64)
+        .bind(offset as i64)
+        .fetch_all(&db.pool)
+        .await?;
+
+        let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM conversations WHERE organisation_id = $1")
+            .bind(organisation_id)
+            .fetch_one(&db.pool)
+            .await?;
+
+        Ok((conversations, total))
+    }
+
+    /// Shared conversation with the member the organisation replies for
+    pub async fn conversation(
+        db: &Database,
+        organisation_id: Uuid,
+        conversation_id: Uuid,
+    ) -> Result<Option<(Conversation, Option<Uuid>)>, sqlx::Error> {
+        let conversation = sqlx::query_as::<_, Conversation>(
+            "SELECT * FROM conversations WHERE id = $1 AND organisation_id = $2",
+        )
+        .bind(conversation_id)
+        .bind(organisation_id)
+        .fetch_optional(&db.pool)
+        .await?;
+
+        let Some(conversation) = conversation else {
+            return Ok(None);
+        };
+
+        let participant: Option<Uuid> = sqlx::query_scalar(
+            "SELECT user_id FROM organisation_members WHERE organisation_id = $1 AND user_id IN ($2, $3) LIMIT 1",
+        )
+        .bind(organisation_id)
+        .bind(conversation.participant_one_id)
+        .bind(conversation.participant_two_id)
+        .fetch_optional(&db.pool)
+        .await?;
+
+        Ok(Some((conversation, participant)))
+    }
+
+    /// Consolidated invoices addressed to the organisation
+    pub async fn list_invoices(db: &Database, organisation_id: Uuid) -> Result<Vec<Invoice>, sqlx::Error> {
+        sqlx::query_as::<_, Invoice>(
+            "SELECT * FROM invoices WHERE organisation_id = $1 ORDER BY created_at DESC",
+        )
+        .bind(organisation_id)
+        .fetch_all(&db.pool)
+        .await
+    }
+
+    // ==================== Purchase approvals ====================
+
+    pub async fn list_approvals(db: &Database, organisation_id: Uuid) -> Result<Vec<PurchaseApproval>, sqlx::Error> {
+        sqlx::query_as::<_, PurchaseApproval>(
+            "SELECT * FROM purchase_approvals WHERE organisation_id = $1 ORDER BY created_at DESC",
+        )
+        .bind(organisation_id)
+        .fetch_all(&db.pool)
+        .await
+    }
+
+    /// Ask the organisation's admins to approve a purchase
+    pub async fn request_approval(
+        db: &Database,
+        organisation_id: Uuid,
+        user_id: Uuid,
+        req: &CreatePurchaseApprovalRequest,
+    ) -> Result<PurchaseApproval, OrganisationError> {
+        if req.service_id.is_some() && req.proposal_id.is_some() {
+            return Err(OrganisationError::Invalid(
+                "An approval is either for a service or for a proposal".to_string(),
+            ));
+        }
+
+        let mut tx = db.pool.begin().await?;
+
+        let approval = sqlx::query_as::<_, PurchaseApproval>(
+            r#"
+            INSERT INTO purchase_approvals (organisation_id, requested_by, service_id, proposal_id, amount, currency, note)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING *
+            "#,
+        )
+        .bind(organisation_id)
+        .bind(user_id)
+        .bind(req.service_id)
+        .bind(req.proposal_id)
+        .bind(req.amount)
+        .bind(&req.currency)
+        .bind(&req.note)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let admins: Vec<Uuid> = sqlx::query_scalar(
+            "SELECT user_id FROM organisation_members WHERE organisation_id = $1 AND role = 'admin'",
+        )
+        .bind(organisation_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        for admin_id in admins {
+            NotificationService::create(
+                &mut *tx,
+                admin_id,
+                "purchase_approval_requested",
+                "Freigabe angefragt",
+                &format!(
+                    "Ein Einkauf über {:.2} {:?} wartet auf Ihre Freigabe.",
+                    req.amount as f64 / 100.0,
+                    req.currency
+                ),
+                Some(serde_json::json!({ "organisationId": organisation_id, "approvalId": approval.id })),
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(approval)
+    }
+
+    /// Approve or reject a pending purchase request and notify the buyer
+    pub async fn decide(
+        db: &Database,
+        organisation_id: Uuid,
+        id: Uuid,
+        admin_id: Uuid,
+        status: PurchaseApprovalStatus,
+        message: Option<&str>,
+    ) -> Result<PurchaseApproval, OrganisationError> {
+        let mut tx = db.pool.begin().await?;
+
+        let approval = sqlx::query_as::<_, PurchaseApproval>(
+            r#"
+            UPDATE purchase_approvals
+            SET status = $3, decided_by = $4, decision_note = $5, decided_at = NOW(), updated_at = NOW()
+            WHERE id = $1 AND organisation_id = $2 AND status = 'pending'
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(organisation_id)
+        .bind(status)
+        .bind(admin_id)
+        .bind(message)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(approval) = approval else {
+            let exists: bool = sqlx::query_scalar(
+                "SELECT EXISTS (SELECT 1 FROM purchase_approvals WHERE id = $1 AND organisation_id = $2)",
+            )
+            .bind(id)
+            .bind(organisation_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            return Err(if exists {
+                OrganisationError::Conflict("Purchase has already been decided".to_string())
+            } else {
+                OrganisationError::NotFound
+            });
+        };
+
+        let (title, text) = if status == PurchaseApprovalStatus::Approved {
+            ("Einkauf freigegeben", "Ihr Einkauf wurde freigegeben und kann jetzt abgeschlossen werden.")
+        } else {
+            ("Einkauf abgelehnt", "Ihr Einkauf wurde nicht freigegeben.")
+        };
+        NotificationService::create(
+            &mut *tx,
+            approval.requested_by,
+            "purchase_approval_decided",
+            title,
+            text,
+            Some(serde_json::json!({ "organisationId": organisation_id, "approvalId": approval.id })),
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(approval)
+    }
+}
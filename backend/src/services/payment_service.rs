@@ -1,13 +1,32 @@
 //! Payment service using Stripe
 //! This module handles all payment operations including Stripe Connect for marketplace payments.
 
-use sqlx::PgPool;
+use sqlx::{PgExecutor, PgPool};
 use uuid::Uuid;
-use crate::models::{Payment, PaymentStatus, Payout, Invoice, CreatePaymentRequest};
+use crate::models::{
+    Payment, PaymentStatus, Payout, Invoice, CreatePaymentRequest, ProjectActor, ProjectEventType, ProjectStatus,
+};
+use crate::services::{NewProjectEvent, ProjectEventService, ProjectService, TransitionError};
 
 /// Platform fee percentage (e.g., 10% = 0.10)
 const PLATFORM_FEE_RATE: f64 = 0.10;
 
+/// Card payment confirmed by a completed checkout session
+#[derive(Debug, Clone)]
+pub struct CheckoutPayment {
+    pub session_id: String,
+    /// Project the checkout paid for
+    pub project_id: Uuid,
+    pub payer_id: Uuid,
+    pub payee_id: Uuid,
+    pub amount: i32,
+    pub currency: String,
+    pub payment_intent_id: Option<String>,
+    pub description: String,
+    /// Stored with the payment; the session id is added to it
+    pub metadata: serde_json::Value,
+}
+
 pub struct PaymentService;
 
 impl PaymentService {
@@ -40,6 +59,166 @@ impl PaymentService {
         .await
     }
 
+    /// Record the payment of a completed checkout and move its project to Paid in the same
+    /// transaction. Returns `None` if the session was already recorded (webhook redelivery).
+    pub async fn record_checkout(pool: &PgPool, checkout: &CheckoutPayment) -> Result<Option<Payment>, TransitionError> {
+        let platform_fee = ((checkout.amount as f64) * PLATFORM_FEE_RATE) as i32;
+        let net_amount = checkout.amount - platform_fee;
+
+        let mut tx = pool.begin().await?;
+
+        let payment: Option<Payment> = sqlx::query_as(
+            r#"
+            INSERT INTO payments (
+                project_id, payer_id, payee_id, amount, currency,
+                platform_fee, net_amount, status,
+                stripe_payment_intent_id, paid_at,
+                description, metadata
+            )
+            SELECT $1, $2, $3, $4, $5, $6, $7, 'succeeded', $8, NOW(), $9,
+                   $10::jsonb || jsonb_build_object('checkout_session_id', $11::text)
+            WHERE NOT EXISTS (SELECT 1 FROM payments WHERE metadata->>'checkout_session_id' = $11)
+            RETURNING *
+            "#,
+        )
+        .bind(checkout.project_id)
+        .bind(checkout.payer_id)
+        .bind(checkout.payee_id)
+        .bind(checkout.amount)
+        .bind(&checkout.currency)
+        .bind(platform_fee)
+        .bind(net_amount)
+        .bind(checkout.payment_intent_id.as_deref())
+        .bind(&checkout.description)
+        .bind(sqlx::types::Json(&checkout.metadata))
+        .bind(&checkout.session_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(payment) = payment else {
+            return Ok(None);
+        };
+
+        ProjectEventService::record(&mut *tx, Self::received_event(&payment)).await?;
+
+        match ProjectService::transition_tx(
+            &mut tx,
+            checkout.project_id,
+            ProjectStatus::Paid,
+            ProjectActor::System,
+            None,
+            None,
+        )
+        .await
+        {
+            Ok(_) => {}
+            // Keep the money on record even if the project moved on meanwhile (e.g. was cancelled)
+            Err(e @ (TransitionError::NotAllowed { .. } | TransitionError::GuardFailed(_))) => {
+                tracing::warn!(
+                    "Paid checkout {} could not mark project {} as paid: {}",
+                    checkout.session_id, checkout.project_id, e
+                );
+            }
+            Err(e) => return Err(e),
+        }
+
+        tx.commit().await?;
+
+        Ok(Some(payment))
+    }
+
+    /// Mark the pending payments of a succeeded payment intent and note them on their projects' timelines
+    pub async fn record_intent_succeeded(pool: &PgPool, payment_intent_id: &str) -> Result<Vec<Payment>, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let payments: Vec<Payment> = sqlx::query_as(
+            r#"
+            UPDATE payments
+            SET status = 'succeeded', paid_at = NOW(), updated_at = NOW()
+            WHERE stripe_payment_intent_id = $1 AND status = 'pending'
+            RETURNING *
+            "#,
+        )
+        .bind(payment_intent_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        for payment in &payments {
+            ProjectEventService::record(&mut *tx, Self::received_event(payment)).await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(payments)
+    }
+
+    /// Record a (partial) refund made at Stripe; returns the affected payments
+    pub async fn record_refund(pool: &PgPool, payment_intent_id: &str, refund_amount: i32) -> Result<Vec<Payment>, sqlx::Error> {
+        let payments: Vec<Payment> = sqlx::query_as(
+            r#"
+            UPDATE payments
+            SET status = CASE
+                WHEN $2 >= amount THEN 'refunded'::payment_status
+                ELSE 'partially_refunded'::payment_status
+            END,
+            refund_amount = $2,
+            refunded_at = NOW(),
+            updated_at = NOW()
+            WHERE stripe_payment_intent_id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(payment_intent_id)
+        .bind(refund_amount)
+        .fetch_all(pool)
+        .await?;
+
+        for payment in &payments {
+            let event = NewProjectEvent::system(payment.project_id, ProjectEventType::PaymentRefunded)
+                .data(serde_json::json!({ "paymentIntentId": payment_intent_id, "amount": refund_amount }));
+            if let Err(e) = ProjectEventService::record(pool, event).await {
+                tracing::error!("Failed to record refund of payment {} on the timeline: {}", payment.id, e);
+            }
+        }
+
+        Ok(payments)
+    }
+
+    /// Record a dispute opened at Stripe; returns the affected payments
+    pub async fn record_dispute(pool: &PgPool, payment_intent_id: &str) -> Result<Vec<Payment>, sqlx::Error> {
+        let payments: Vec<Payment> = sqlx::query_as(
+            r#"
+            UPDATE payments
+            SET status = 'disputed'::payment_status, updated_at = NOW()
+            WHERE stripe_payment_intent_id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(payment_intent_id)
+        .fetch_all(pool)
+        .await?;
+
+        for payment in &payments {
+            let event = NewProjectEvent::system(payment.project_id, ProjectEventType::PaymentDisputed)
+                .data(serde_json::json!({ "paymentIntentId": payment_intent_id }));
+            if let Err(e) = ProjectEventService::record(pool, event).await {
+                tracing::error!("Failed to record dispute of payment {} on the timeline: {}", payment.id, e);
+            }
+        }
+
+        Ok(payments)
+    }
+
+    /// Timeline entry for a project payment that went through
+    fn received_event(payment: &Payment) -> NewProjectEvent {
+        NewProjectEvent::system(payment.project_id, ProjectEventType::PaymentReceived).data(serde_json::json!({
+            "kind": "project",
+            "paymentId": payment.id,
+            "amount": payment.amount,
+            "currency": payment.currency,
+        }))
+    }
+
     /// Get payment by ID
     pub async fn get_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Payment>, sqlx::Error> {
         sqlx::query_as::<_, Payment>("SELECT * FROM payments WHERE id = $1")
@@ -82,8 +261,8 @@ impl PaymentService {
     }
 
     /// Update payment status
-    pub async fn update_status(
-        pool: &PgPool,
+    pub async fn update_status<'e, E: PgExecutor<'e>>(
+        executor: E,
         id: Uuid,
         status: PaymentStatus,
         stripe_payment_intent_id: Option<&str>,
@@ -107,7 +286,7 @@ impl PaymentService {
         .bind(status)
         .bind(stripe_payment_intent_id)
         .bind(paid_at)
-        .fetch_one(pool)
+        .fetch_one(executor)
         .await
     }
 
@@ -137,13 +316,35 @@ impl PaymentService {
         .await
     }
 
-    /// Get expert's pending payout balance
+    /// Make a payment eligible for payout
+    pub async fn release<'e, E: PgExecutor<'e>>(executor: E, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE payments SET released_at = COALESCE(released_at, NOW()), updated_at = NOW() WHERE id = $1")
+            .bind(id)
+            .execute(executor)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Get expert's pending payout balance. Agency payments count with the member's share;
+    /// the agency's share is paid out to the agency owner.
     pub async fn get_pending_balance(pool: &PgPool, expert_id: Uuid) -> Result<i64, sqlx::Error> {
         let balance: Option<i64> = sqlx::query_scalar(
             r#"
-            SELECT COALESCE(SUM(net_amount), 0)
-            FROM payments
-            WHERE payee_id = $1 AND status = 'succeeded' AND stripe_transfer_id IS NULL
+            SELECT COALESCE(SUM(amount), 0)::BIGINT FROM (
+                SELECT COALESCE(s.member_amount, p.net_amount) AS amount
+                FROM payments p
+                LEFT JOIN agency_revenue_splits s ON s.payment_id = p.id
+                WHERE p.payee_id = $1 AND p.status IN ('succeeded', 'partially_refunded')
+                  AND p.released_at IS NOT NULL AND p.stripe_transfer_id IS NULL
+                UNION ALL
+                SELECT s.agency_amount
+                FROM agency_revenue_splits s
+                JOIN payments p ON p.id = s.payment_id
+                JOIN agency_members m ON m.agency_id = s.agency_id AND m.role = 'owner'
+                WHERE m.user_id = $1 AND p.status IN ('succeeded', 'partially_refunded')
+                  AND p.stripe_transfer_id IS NULL
+            ) balance
             "#,
         )
         .bind(expert_id)
@@ -208,14 +409,14 @@ impl PaymentService {
     }
 
     /// Generate invoice number
-    fn generate_invoice_number() -> String {
+    pub(crate) fn generate_invoice_number() -> String {
         let now = chrono::Utc::now();
         format!("INV-{}-{:04}", now.format("%Y%m"), rand::random::<u16>())
     }
 
     /// Create invoice
-    pub async fn create_invoice(
-        pool: &PgPool,
+    pub async fn create_invoice<'e, E: PgExecutor<'e>>(
+        executor: E,
         issuer_id: Uuid,
         recipient_id: Uuid,
         project_id: Option<Uuid>,
@@ -245,7 +446,27 @@ impl PaymentService {
         .bind(total)
         .bind(currency)
         .bind(line_items)
-        .fetch_one(pool)
+        .fetch_one(executor)
+        .await
+    }
+
+    /// Mark invoice as paid and link it to the settling payment
+    pub async fn mark_invoice_paid<'e, E: PgExecutor<'e>>(
+        executor: E,
+        invoice_id: Uuid,
+        payment_id: Option<Uuid>,
+    ) -> Result<Invoice, sqlx::Error> {
+        sqlx::query_as::<_, Invoice>(
+            r#"
+            UPDATE invoices
+            SET status = 'paid', paid_at = NOW(), payment_id = COALESCE($2, payment_id), updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(invoice_id)
+        .bind(payment_id)
+        .fetch_one(executor)
         .await
     }
 
@@ -307,10 +528,24 @@ pub mod stripe_service {
     use stripe::{Client, CreatePaymentIntent, Currency, PaymentIntent, PaymentIntentId, CheckoutSession, CreateCheckoutSession};
     use std::collections::HashMap;
 
+    use crate::models::InvoiceLineItem;
+
     pub struct StripeService {
         client: Client,
     }
 
+    /// Positions and routing of an itemized checkout session
+    pub struct ItemizedCheckout<'a> {
+        pub items: &'a [InvoiceLineItem],
+        pub currency: &'a str,
+        pub success_url: &'a str,
+        pub cancel_url: &'a str,
+        pub metadata: HashMap<String, String>,
+        /// Connected account receiving the payment minus the platform fee
+        pub expert_stripe_account_id: Option<&'a str>,
+        pub platform_fee: i64,
+    }
+
     impl StripeService {
         pub fn new(secret_key: &str) -> Self {
             let client = Client::new(secret_key);
@@ -329,6 +564,40 @@ pub mod stripe_service {
             expert_stripe_account_id: Option<&str>,
             platform_fee: i64,
         ) -> Result<CheckoutSession, stripe::StripeError> {
+            let item = InvoiceLineItem {
+                description: service_title.to_string(),
+                quantity: 1,
+                unit_price: amount as i32,
+                amount: amount as i32,
+            };
+
+            self.create_itemized_checkout_session(ItemizedCheckout {
+                items: &[item],
+                currency,
+                success_url,
+                cancel_url,
+                metadata,
+                expert_stripe_account_id,
+                platform_fee,
+            })
+            .await
+        }
+
+        /// Create a Stripe Checkout Session with one line item per position (service, add-ons)
+        pub async fn create_itemized_checkout_session(
+            &self,
+            checkout: ItemizedCheckout<'_>,
+        ) -> Result<CheckoutSession, stripe::StripeError> {
+            let ItemizedCheckout {
+                items,
+                currency,
+                success_url,
+                cancel_url,
+                metadata,
+                expert_stripe_account_id,
+                platform_fee,
+            } = checkout;
+
             let currency_enum = match currency.to_lowercase().as_str() {
                 "chf" => Currency::CHF,
                 "eur" => Currency::EUR,
@@ -342,19 +611,24 @@ pub mod stripe_service {
             params.metadata = Some(metadata);
 
             // Line items
-            params.line_items = Some(vec![stripe::CreateCheckoutSessionLineItems {
-                price_data: Some(stripe::CreateCheckoutSessionLineItemsPriceData {
-                    currency: currency_enum,
-                    unit_amount: Some(amount),
-                    product_data: Some(stripe::CreateCheckoutSessionLineItemsPriceDataProductData {
-                        name: service_title.to_string(),
+            params.line_items = Some(
+                items
+                    .iter()
+                    .map(|item| stripe::CreateCheckoutSessionLineItems {
+                        price_data: Some(stripe::CreateCheckoutSessionLineItemsPriceData {
+                            currency: currency_enum,
+                            unit_amount: Some(item.unit_price as i64),
+                            product_data: Some(stripe::CreateCheckoutSessionLineItemsPriceDataProductData {
+                                name: item.description.clone(),
+                                ..Default::default()
+                            }),
+                            ..Default::default()
+                        }),
+                        quantity: Some(item.quantity as u64),
                         ..Default::default()
-                    }),
-                    ..Default::default()
-                }),
-                quantity: Some(1),
-                ..Default::default()
-            }]);
+                    })
+                    .collect(),
+            );
 
             // If expert has Stripe Connect account, set up application fee
             if let Some(account_id) = expert_stripe_account_id {
@@ -459,6 +733,19 @@ pub mod stripe_service {
             stripe::Transfer::create(&self.client, params).await
         }
 
+        /// Refund (part of) a payment intent
+        pub async fn create_refund(
+            &self,
+            payment_intent_id: &str,
+            amount: i64,
+        ) -> Result<stripe::Refund, stripe::StripeError> {
+            let mut params = stripe::CreateRefund::new();
+            params.payment_intent = Some(payment_intent_id.parse().unwrap());
+            params.amount = Some(amount);
+
+            stripe::Refund::create(&self.client, params).await
+        }
+
         /// Retrieve a checkout session by ID
         pub async fn get_checkout_session(
             &self,
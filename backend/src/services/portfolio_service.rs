@@ -2,6 +2,9 @@ use uuid::Uuid;
 use crate::db::Database;
 use crate::models::{PortfolioItem, CreatePortfolioItemRequest, UpdatePortfolioItemRequest};
 
+/// Portfolio items an expert can feature at the top of their profile
+pub const MAX_FEATURED_PORTFOLIO_ITEMS: i64 = 3;
+
 pub struct PortfolioService;
 
 impl PortfolioService {
@@ -57,7 +60,7 @@ impl PortfolioService {
             r#"
             SELECT * FROM portfolio_items 
             WHERE expert_id = $1 
-            ORDER BY sort_order ASC, created_at DESC
+            ORDER BY is_featured DESC, sort_order ASC, created_at DESC
             "#,
         )
         .bind(expert_id)
@@ -112,6 +115,44 @@ impl PortfolioService {
         Ok(item)
     }
 
+    /// Number of featured items of an expert, not counting `except`
+    pub async fn count_featured(db: &Database, expert_id: Uuid, except: Option<Uuid>) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar(
+            "SELECT COUNT(*) FROM portfolio_items WHERE expert_id = $1 AND is_featured AND ($2::UUID IS NULL OR id <> $2)",
+        )
+        .bind(expert_id)
+        .bind(except)
+        .fetch_one(&db.pool)
+        .await
+    }
+
+    /// Set `sort_order` to the position of each item in `item_ids`.
+    /// Returns false (and changes nothing) if an item does not belong to the expert.
+    pub async fn reorder(db: &Database, expert_id: Uuid, item_ids: &[Uuid]) -> Result<bool, sqlx::Error> {
+        let mut tx = db.pool.begin().await?;
+
+        let result = sqlx::query(
+            r#"
+            UPDATE portfolio_items p
+            SET sort_order = o.position - 1, updated_at = NOW()
+            FROM unnest($2::UUID[]) WITH ORDINALITY AS o(id, position)
+            WHERE p.id = o.id AND p.expert_id = $1
+            "#,
+        )
+        .bind(expert_id)
+        .bind(item_ids)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() != item_ids.len() as u64 {
+            tx.rollback().await?;
+            return Ok(false);
+        }
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
     /// Delete a portfolio item
     pub async fn delete(db: &Database, id: Uuid) -> Result<(), sqlx::Error> {
         sqlx::query("DELETE FROM portfolio_items WHERE id = $1")
@@ -0,0 +1,323 @@
+//! Invitations to bid: clients invite experts to a posting, experts accept or decline.
+//! Invite-only postings accept proposals from invited experts only.
+
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::models::{
+    InviteExpertsRequest, PostingInvitation, PostingInvitationStatus, PostingVisibility, ProjectPosting,
+    ProjectPostingStatus, ReceivedPostingInvitation,
+};
+use crate::services::NotificationService;
+
+/// Error of a posting invitation operation
+#[derive(Debug, thiserror::Error)]
+pub enum InvitationError {
+    #[error("Invitation not found")]
+    NotFound,
+
+    #[error("{0}")]
+    Forbidden(String),
+
+    #[error("{0}")]
+    Conflict(String),
+
+    #[error("{0}")]
+    Invalid(String),
+
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+const INVITATION_COLUMNS: &str = r#"
+    i.id, i.project_posting_id, pp.title AS posting_title, i.expert_id,
+    u.first_name AS expert_first_name, u.last_name AS expert_last_name,
+    i.invited_by, i.message, i.status, i.responded_at, i.created_at
+"#;
+
+pub struct PostingInvitationService;
+
+impl PostingInvitationService {
+    async fn get(db: &Database, id: Uuid) -> Result<Option<PostingInvitation>, sqlx::Error> {
+        sqlx::query_as::<_, PostingInvitation>(&format!(
+            r#"
+            SELECT {INVITATION_COLUMNS}
+            FROM posting_invitations i
+            JOIN project_postings pp ON pp.id = i.project_posting_id
+            JOIN users u ON u.id = i.expert_id
+            WHERE i.id = $1
+            "#
+        ))
+        .bind(id)
+        .fetch_optional(&db.pool)
+        .await
+    }
+
+    /// Invitations of a posting (its client)
+    pub async fn list_for_posting(
+        db: &Database,
+        posting_id: Uuid,
+        client_id: Uuid,
+    ) -> Result<Vec<PostingInvitation>, sqlx::Error> {
+        sqlx::query_as::<_, PostingInvitation>(&format!(
+            r#"
+            SELECT {INVITATION_COLUMNS}
+            FROM posting_invitations i
+            JOIN project_postings pp ON pp.id = i.project_posting_id
+            JOIN users u ON u.id = i.expert_id
+            WHERE i.project_posting_id = $1 AND pp.client_id = $2
+            ORDER BY i.created_at
+            "#
+        ))
+        .bind(posting_id)
+        .bind(client_id)
+        .fetch_all(&db.pool)
+        .await
+    }
+
+    /// Invitations received by an expert with their postings, newest first
+    pub async fn list_for_expert(db: &Database, expert_id: Uuid) -> Result<Vec<ReceivedPostingInvitation>, sqlx::Error> {
+        let invitations = sqlx::query_as::<_, PostingInvitation>(&format!(
+            r#"
+            SELECT {INVITATION_COLUMNS}
+            FROM posting_invitations i
+            JOIN project_postings pp ON pp.id = i.project_posting_id
+            JOIN users u ON u.id = i.expert_id
+            WHERE i.expert_id = $1
+            ORDER BY i.created_at DESC
+            "#
+        ))
+        .bind(expert_id)
+        .fetch_all(&db.pool)
+        .await?;
+
+        let posting_ids: Vec<Uuid> = invitations.iter().map(|i| i.project_posting_id).collect();
+        let mut postings: HashMap<Uuid, ProjectPosting> =
+            sqlx::query_as::<_, ProjectPosting>("SELECT * FROM project_postings WHERE id = ANY($1)")
+                .bind(&posting_ids)
+                .fetch_all(&db.pool)
+                .await?
+                .into_iter()
+                .map(|p| (p.id, p))
+                .collect();
+
+        Ok(invitations
+            .into_iter()
+            .filter_map(|invitation| {
+                let posting = postings.remove(&invitation.project_posting_id)?;
+                Some(ReceivedPostingInvitation { invitation, posting })
+            })
+            .collect())
+    }
+
+    /// Invite experts to an open posting and notify them; already invited experts are skipped
+    pub async fn invite(
+        db: &Database,
+        posting_id: Uuid,
+        client_id: Uuid,
+        req: &InviteExpertsRequest,
+    ) -> Result<Vec<PostingInvitation>, InvitationError> {
+        let mut tx = db.pool.begin().await?;
+
+        let posting = sqlx::query_as::<_, ProjectPosting>(
+            "SELECT * FROM project_postings WHERE id = $1 AND client_id = $2 FOR UPDATE",
+        )
+        .bind(posting_id)
+        .bind(client_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| InvitationError::Invalid("Project posting not found".to_string()))?;
+
+        if !matches!(posting.status, ProjectPostingStatus::Draft | ProjectPostingStatus::Open) {
+            return Err(InvitationError::Conflict("The posting no longer accepts proposals".to_string()));
+        }
+
+        let mut expert_ids = req.expert_ids.clone();
+        expert_ids.sort();
+        expert_ids.dedup();
+
+        let experts: Vec<Uuid> = sqlx::query_scalar(
+            "SELECT user_id FROM expert_profiles WHERE user_id = ANY($1) AND user_id <> $2",
+        )
+        .bind(&expert_ids)
+        .bind(client_id)
+        .fetch_all(&mut *tx)
+        .await?;
+        if experts.len() != expert_ids.len() {
+            return Err(InvitationError::Invalid("Only experts can be invited".to_string()));
+        }
+
+        let invited: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            INSERT INTO posting_invitations (project_posting_id, expert_id, invited_by, message)
+            SELECT $1, expert_id, $2, $3 FROM UNNEST($4::UUID[]) AS expert_id
+            ON CONFLICT (project_posting_id, expert_id) DO NOTHING
+            RETURNING expert_id
+            "#,
+        )
+        .bind(posting_id)
+        .bind(client_id)
+        .bind(&req.message)
+        .bind(&experts)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        for expert_id in invited {
+            NotificationService::create(
+                &mut *tx,
+                expert_id,
+                "posting_invitation",
+                "Einladung zu einem Projekt",
+                &format!("Sie wurden eingeladen, ein Angebot für \"{}\" abzugeben.", posting.title),
+                Some(serde_json::json!({ "postingId": posting_id })),
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(Self::list_for_posting(db, posting_id, client_id).await?)
+    }
+
+    /// Accept or decline a pending invitation and notify the client
+    pub async fn respond(
+        db: &Database,
+        id: Uuid,
+        expert_id: Uuid,
+        accept: bool,
+    ) -> Result<PostingInvitation, InvitationError> {
+        let mut tx = db.pool.begin().await?;
+
+        let status = if accept { PostingInvitationStatus::Accepted } else { PostingInvitationStatus::Declined };
+        let responded: Option<(Uuid, String)> = sqlx::query_as(
+            r#"
+            UPDATE posting_invitations i
+            SET status = $3, responded_at = NOW(), updated_at = NOW()
+            FROM project_postings pp
+            WHERE i.id = $1 AND i.expert_id = $2 AND i.status = 'pending' AND pp.id = i.project_posting_id
+            RETURNING pp.client_id, pp.title
+            "#,
+        )
+        .bind(id)
+        .bind(expert_id)
+        .bind(status)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some((client_id, title)) = responded else {
+            let exists: bool = sqlx::query_scalar(
+                "SELECT EXISTS (SELECT 1 FROM posting_invitations WHERE id = $1 AND expert_id = $2)",
+            )
+            .bind(id)
+            .bind(expert_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            return Err(if exists {
+                InvitationError::Conflict("Invitation has already been answered".to_string())
+            } else {
+                InvitationError::NotFound
+            });
+        };
+
+        let (notification_title, message) = if accept {
+            ("Einladung angenommen", format!("Ein Experte hat Ihre Einladung zu \"{}\" angenommen.", title))
+        } else {
+            ("Einladung abgelehnt", format!("Ein Experte hat Ihre Einladung zu \"{}\" abgelehnt.", title))
+        };
+        NotificationService::create(
+            &mut *tx,
+            client_id,
+            "posting_invitation_answered",
+            notification_title,
+            &message,
+            Some(serde_json::json!({ "invitationId": id, "expertId": expert_id })),
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Self::get(db, id).await?.ok_or(InvitationError::NotFound)
+    }
+
+    /// Whether the expert has an invitation they did not decline
+    pub async fn is_invited(db: &Database, posting_id: Uuid, expert_id: Uuid) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM posting_invitations
+                WHERE project_posting_id = $1 AND expert_id = $2 AND status <> 'declined'
+            )
+            "#,
+        )
+        .bind(posting_id)
+        .bind(expert_id)
+        .fetch_one(&db.pool)
+        .await
+    }
+
+    /// Invited status of the user, looked up for invite-only postings only
+    async fn invited(db: &Database, posting: &ProjectPosting, user_id: Uuid) -> Result<bool, sqlx::Error> {
+        if posting.visibility == PostingVisibility::Public {
+            return Ok(false);
+        }
+
+        Self::is_invited(db, posting.id, user_id).await
+    }
+
+    /// Public postings are visible to everyone, invite-only ones to their client and the
+    /// invited experts
+    pub async fn can_view(db: &Database, posting: &ProjectPosting, user_id: Option<Uuid>) -> Result<bool, sqlx::Error> {
+        let Some(user_id) = user_id else {
+            return Ok(admits(posting.visibility, false, false));
+        };
+        let invited = Self::invited(db, posting, user_id).await?;
+
+        Ok(admits(posting.visibility, posting.client_id == user_id, invited))
+    }
+
+    /// Invite-only postings take proposals from invited experts only; submitting a
+    /// proposal accepts a pending invitation
+    pub async fn check_proposal(db: &Database, posting: &ProjectPosting, expert_id: Uuid) -> Result<(), InvitationError> {
+        let invited = Self::invited(db, posting, expert_id).await?;
+        if !admits(posting.visibility, false, invited) {
+            return Err(InvitationError::Forbidden(
+                "This posting only accepts proposals from invited experts".to_string(),
+            ));
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE posting_invitations SET status = 'accepted', responded_at = NOW(), updated_at = NOW()
+            WHERE project_posting_id = $1 AND expert_id = $2 AND status = 'pending'
+            "#,
+        )
+        .bind(posting.id)
+        .bind(expert_id)
+        .execute(&db.pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// Whether a posting is open to a user: public postings to everyone, invite-only ones
+/// to their client and invited experts
+fn admits(visibility: PostingVisibility, is_owner: bool, invited: bool) -> bool {
+    visibility == PostingVisibility::Public || is_owner || invited
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_admits() {
+        assert!(admits(PostingVisibility::Public, false, false));
+        assert!(!admits(PostingVisibility::InviteOnly, false, false));
+        assert!(admits(PostingVisibility::InviteOnly, true, false));
+        assert!(admits(PostingVisibility::InviteOnly, false, true));
+    }
+}
@@ -0,0 +1,377 @@
+//! Posting lifecycle: postings expire at the end of their lifetime or at their deadline,
+//! can be extended or reposted by the client, and are closed if the client does neither.
+//! Closing or cancelling a posting rejects the proposals still waiting for an answer.
+
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::config::PostingSettings;
+use crate::db::Database;
+use crate::models::{ExtendPostingRequest, ProjectPosting, ProjectPostingStatus, RepostPostingRequest};
+use crate::services::NotificationService;
+
+/// Error of a posting lifecycle operation
+#[derive(Debug, thiserror::Error)]
+pub enum PostingError {
+    #[error("Project posting not found")]
+    NotFound,
+
+    #[error("{0}")]
+    Conflict(String),
+
+    #[error("{0}")]
+    Invalid(String),
+
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+pub struct PostingService;
+
+impl PostingService {
+    /// End of a posting's lifetime counted from `from`, or its deadline if that is earlier
+    pub fn expiry_for(from: DateTime<Utc>, deadline: Option<DateTime<Utc>>, lifetime_days: i64) -> DateTime<Utc> {
+        let end = from + Duration::days(lifetime_days);
+        deadline.map_or(end, |deadline| deadline.min(end))
+    }
+
+    /// Proposals can only be submitted while the posting is running
+    pub fn check_accepts_proposals(posting: &ProjectPosting) -> Result<(), PostingError> {
+        let now = Utc::now();
+        let running = matches!(
+            posting.status,
+            ProjectPostingStatus::Draft | ProjectPostingStatus::Open | ProjectPostingStatus::InReview
+        );
+        let ran_out = posting.expires_at.is_some_and(|at| at <= now) || posting.deadline.is_some_and(|at| at <= now);
+
+        if !running || ran_out {
+            return Err(PostingError::Conflict("The posting no longer accepts proposals".to_string()));
+        }
+
+        Ok(())
+    }
+
+    async fn lock_for_client(conn: &mut PgConnection, id: Uuid, client_id: Uuid) -> Result<ProjectPosting, PostingError> {
+        sqlx::query_as::<_, ProjectPosting>("SELECT * FROM project_postings WHERE id = $1 AND client_id = $2 FOR UPDATE")
+            .bind(id)
+            .bind(client_id)
+            .fetch_optional(&mut *conn)
+            .await?
+            .ok_or(PostingError::NotFound)
+    }
+
+    /// Cancel a posting and reject its pending and shortlisted proposals, notifying their experts
+    async fn close_tx(
+        conn: &mut PgConnection,
+        posting: &ProjectPosting,
+        cancellation_reason: Option<&str>,
+        rejection_reason: &str,
+        message: &str,
+    ) -> Result<ProjectPosting, sqlx::Error> {
+        let closed = sqlx::query_as::<_, ProjectPosting>(
+            r#"
+            UPDATE project_postings
+            SET status = 'cancelled', cancelled_at = NOW(), cancellation_reason = $2, updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(posting.id)
+        .bind(cancellation_reason)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        let rejected: Vec<(Uuid, Uuid)> = sqlx::query_as(
+            r#"
+            UPDATE proposals
+            SET status = 'rejected', rejected_at = NOW(), rejection_reason = $2, updated_at = NOW()
+            WHERE project_posting_id = $1 AND status IN ('pending', 'shortlisted')
+            RETURNING id, expert_id
+            "#,
+        )
+        .bind(posting.id)
+        .bind(rejection_reason)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        for (proposal_id, expert_id) in &rejected {
+            NotificationService::create(
+                &mut *conn,
+                *expert_id,
+                "proposal_rejected",
+                "Ausschreibung geschlossen",
+                message,
+                Some(serde_json::json!({ "proposalId": proposal_id, "postingId": posting.id })),
+            )
+            .await?;
+        }
+
+        Ok(closed)
+    }
+
+    /// Cancel a posting that has not been assigned yet
+    pub async fn cancel(
+        db: &Database,
+        id: Uuid,
+        client_id: Uuid,
+        reason: Option<&str>,
+    ) -> Result<ProjectPosting, PostingError> {
+        let mut tx = db.pool.begin().await?;
+        let posting = Self::lock_for_client(&mut tx, id, client_id).await?;
+
+        if !matches!(
+            posting.status,
+            ProjectPostingStatus::Draft
+                | ProjectPostingStatus::Open
+                | ProjectPostingStatus::InReview
+                | ProjectPostingStatus::Expired
+        ) {
+            return Err(PostingError::Conflict("Posting can no longer be cancelled".to_string()));
+        }
+
+        let message = match reason {
+            Some(reason) => format!(
+                "Die Ausschreibung \"{}\" wurde zurückgezogen. Ihr Angebot wurde abgelehnt. Begründung: {}",
+                posting.title, reason
+            ),
+            None => format!("Die Ausschreibung \"{}\" wurde zurückgezogen. Ihr Angebot wurde abgelehnt.", posting.title),
+        };
+        let posting = Self::close_tx(
+            &mut tx,
+            &posting,
+            reason,
+            reason.unwrap_or("The posting was cancelled"),
+            &message,
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(posting)
+    }
+
+    /// Keep a running or expired posting open for more days; an expired posting is reopened
+    pub async fn extend(
+        db: &Database,
+        id: Uuid,
+        client_id: Uuid,
+        req: &ExtendPostingRequest,
+    ) -> Result<ProjectPosting, PostingError> {
+        let mut tx = db.pool.begin().await?;
+        let posting = Self::lock_for_client(&mut tx, id, client_id).await?;
+
+        if !matches!(
+            posting.status,
+            ProjectPostingStatus::Draft
+                | ProjectPostingStatus::Open
+                | ProjectPostingStatus::InReview
+                | ProjectPostingStatus::Expired
+        ) {
+            return Err(PostingError::Conflict("Only running or expired postings can be extended".to_string()));
+        }
+
+        let now = Utc::now();
+        if req.deadline.is_some_and(|deadline| deadline <= now) {
+            return Err(PostingError::Invalid("The deadline must be in the future".to_string()));
+        }
+        let deadline = req.deadline.or(posting.deadline);
+        if deadline.is_some_and(|deadline| deadline <= now) {
+            return Err(PostingError::Invalid(
+                "The deadline has passed; set a new deadline to extend the posting".to_string(),
+            ));
+        }
+
+        let from = posting.expires_at.filter(|at| *at > now).unwrap_or(now);
+        let expires_at = Self::expiry_for(from, deadline, req.days);
+
+        let posting = sqlx::query_as::<_, ProjectPosting>(
+            r#"
+            UPDATE project_postings
+            SET status = CASE WHEN status = 'expired' THEN 'open' ELSE status END,
+                deadline = $2, expires_at = $3, expired_at = NULL, updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(deadline)
+        .bind(expires_at)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(posting)
+    }
+
+    /// Publish an expired or cancelled posting again as a new open posting. Invitations are
+    /// carried over; an expired original is closed and its pending proposals are rejected.
+    pub async fn repost(
+        db: &Database,
+        id: Uuid,
+        client_id: Uuid,
+        req: &RepostPostingRequest,
+        settings: &PostingSettings,
+    ) -> Result<ProjectPosting, PostingError> {
+        let mut tx = db.pool.begin().await?;
+        let original = Self::lock_for_client(&mut tx, id, client_id).await?;
+
+        if !matches!(original.status, ProjectPostingStatus::Expired | ProjectPostingStatus::Cancelled) {
+            return Err(PostingError::Conflict("Only expired or cancelled postings can be reposted".to_string()));
+        }
+
+        let now = Utc::now();
+        if req.deadline.is_some_and(|deadline| deadline <= now) {
+            return Err(PostingError::Invalid("The deadline must be in the future".to_string()));
+        }
+        let deadline = req.deadline.or(original.deadline.filter(|deadline| *deadline > now));
+        let expires_at = Self::expiry_for(now, deadline, settings.lifetime_days);
+
+        let posting = sqlx::query_as::<_, ProjectPosting>(
+            r#"
+            INSERT INTO project_postings (
+                client_id, title, description, requirements, category_id, skills_required, tools_required,
+                budget_type, budget_min, budget_max, currency, deadline, estimated_duration, is_urgent,
+                attachments, visibility, status, expires_at, reposted_from_id
+            )
+            SELECT client_id, title, description, requirements, category_id, skills_required, tools_required,
+                   budget_type, budget_min, budget_max, currency, $2, estimated_duration, is_urgent,
+                   attachments, visibility, 'open'::project_posting_status, $3, id
+            FROM project_postings
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(deadline)
+        .bind(expires_at)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO posting_invitations (project_posting_id, expert_id, invited_by, message)
+            SELECT $2, expert_id, invited_by, message
+            FROM posting_invitations
+            WHERE project_posting_id = $1 AND status <> 'declined'
+            "#,
+        )
+        .bind(id)
+        .bind(posting.id)
+        .execute(&mut *tx)
+        .await?;
+
+        if original.status == ProjectPostingStatus::Expired {
+            let message = format!(
+                "Die Ausschreibung \"{}\" wurde neu ausgeschrieben. Ihr bisheriges Angebot wurde abgelehnt, Sie können ein neues Angebot abgeben.",
+                original.title
+            );
+            Self::close_tx(&mut tx, &original, Some("Reposted"), "The posting was reposted", &message).await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(posting)
+    }
+
+    /// Expire running postings past their expiry or deadline and notify their clients
+    pub async fn expire_due(db: &Database, settings: &PostingSettings) -> Result<Vec<ProjectPosting>, sqlx::Error> {
+        let mut tx = db.pool.begin().await?;
+
+        let expired = sqlx::query_as::<_, ProjectPosting>(
+            r#"
+            UPDATE project_postings
+            SET status = 'expired', expired_at = NOW(), updated_at = NOW()
+            WHERE status IN ('draft', 'open', 'in_review') AND (expires_at <= NOW() OR deadline <= NOW())
+            RETURNING *
+            "#,
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        for posting in &expired {
+            NotificationService::create(
+                &mut *tx,
+                posting.client_id,
+                "posting_expired",
+                "Ausschreibung abgelaufen",
+                &format!(
+                    "Ihre Ausschreibung \"{}\" ist abgelaufen. Verlängern Sie sie oder schreiben Sie sie neu aus, sonst wird sie in {} Tagen geschlossen.",
+                    posting.title, settings.close_after_days
+                ),
+                Some(serde_json::json!({
+                    "postingId": posting.id,
+                    "proposalCount": posting.proposal_count,
+                    "actions": ["extend", "repost"],
+                })),
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(expired)
+    }
+
+    /// Close postings that stayed expired for the grace period without being extended or reposted
+    pub async fn close_expired(db: &Database, settings: &PostingSettings) -> Result<Vec<ProjectPosting>, sqlx::Error> {
+        let mut tx = db.pool.begin().await?;
+
+        let due = sqlx::query_as::<_, ProjectPosting>(
+            r#"
+            SELECT * FROM project_postings
+            WHERE status = 'expired' AND expired_at <= NOW() - make_interval(days => $1)
+            FOR UPDATE SKIP LOCKED
+            "#,
+        )
+        .bind(settings.close_after_days as i32)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut closed = Vec::with_capacity(due.len());
+        for posting in &due {
+            let message = format!(
+                "Die Ausschreibung \"{}\" ist abgelaufen und wurde geschlossen. Ihr Angebot wurde abgelehnt.",
+                posting.title
+            );
+            closed.push(Self::close_tx(&mut tx, posting, Some("Expired"), "The posting expired", &message).await?);
+
+            NotificationService::create(
+                &mut *tx,
+                posting.client_id,
+                "posting_closed",
+                "Ausschreibung geschlossen",
+                &format!(
+                    "Ihre abgelaufene Ausschreibung \"{}\" wurde geschlossen. Sie können sie jederzeit neu ausschreiben.",
+                    posting.title
+                ),
+                Some(serde_json::json!({ "postingId": posting.id, "actions": ["repost"] })),
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(closed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_expiry_for() {
+        let now = Utc.with_ymd_and_hms(2024, 12, 1, 12, 0, 0).unwrap();
+
+        assert_eq!(PostingService::expiry_for(now, None, 30), now + Duration::days(30));
+
+        let deadline = now + Duration::days(10);
+        assert_eq!(PostingService::expiry_for(now, Some(deadline), 30), deadline);
+
+        let late_deadline = now + Duration::days(60);
+        assert_eq!(PostingService::expiry_for(now, Some(late_deadline), 30), now + Duration::days(30));
+    }
+}
@@ -0,0 +1,112 @@
+use sqlx::PgExecutor;
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::models::{PaginationParams, ProjectActor, ProjectEvent, ProjectEventType, ProjectStatus};
+
+/// Timeline entry to be recorded
+#[derive(Debug, Clone)]
+pub struct NewProjectEvent {
+    pub project_id: Uuid,
+    pub event_type: ProjectEventType,
+    pub actor: ProjectActor,
+    pub actor_id: Option<Uuid>,
+    pub from_status: Option<ProjectStatus>,
+    pub to_status: Option<ProjectStatus>,
+    pub message: Option<String>,
+    pub data: Option<serde_json::Value>,
+}
+
+impl NewProjectEvent {
+    pub fn new(project_id: Uuid, event_type: ProjectEventType, actor: ProjectActor, actor_id: Option<Uuid>) -> Self {
+        Self {
+            project_id,
+            event_type,
+            actor,
+            actor_id,
+            from_status: None,
+            to_status: None,
+            message: None,
+            data: None,
+        }
+    }
+
+    /// Event triggered by a background job or payment webhook
+    pub fn system(project_id: Uuid, event_type: ProjectEventType) -> Self {
+        Self::new(project_id, event_type, ProjectActor::System, None)
+    }
+
+    pub fn status(mut self, from: Option<ProjectStatus>, to: ProjectStatus) -> Self {
+        self.from_status = from;
+        self.to_status = Some(to);
+        self
+    }
+
+    pub fn message(mut self, message: Option<&str>) -> Self {
+        self.message = message.map(str::to_string);
+        self
+    }
+
+    pub fn data(mut self, data: serde_json::Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+}
+
+pub struct ProjectEventService;
+
+impl ProjectEventService {
+    /// Append an event to the project timeline (inside or outside a transaction)
+    pub async fn record<'e, E: PgExecutor<'e>>(executor: E, event: NewProjectEvent) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO project_events (
+                project_id, event_type, actor, actor_id, from_status, to_status, message, data
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(event.project_id)
+        .bind(event.event_type)
+        .bind(event.actor)
+        .bind(event.actor_id)
+        .bind(event.from_status)
+        .bind(event.to_status)
+        .bind(event.message)
+        .bind(event.data.map(sqlx::types::Json))
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Timeline of a project, newest first
+    pub async fn get_timeline(
+        db: &Database,
+        project_id: Uuid,
+        pagination: &PaginationParams,
+    ) -> Result<(Vec<ProjectEvent>, i64), sqlx::Error> {
+        let offset = (pagination.page.saturating_sub(1)) * pagination.per_page;
+
+        let total: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM project_events WHERE project_id = $1")
+            .bind(project_id)
+            .fetch_one(&db.pool)
+            .await?;
+
+        let events = sqlx::query_as::<_, ProjectEvent>(
+            r#"
+            SELECT * FROM project_events
+            WHERE project_id = $1
+            ORDER BY created_at DESC, id
+            LIMIT $2 OFFSET $3
+            "#,
+        )
+        .bind(project_id)
+        .bind(pagination.per_page as i64)
+        .bind(offset as i64)
+        .fetch_all(&db.pool)
+        .await?;
+
+        Ok((events, total.0))
+    }
+}
@@ -1,29 +1,119 @@
-use chrono::Utc;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgConnection;
 use uuid::Uuid;
 
 use crate::db::Database;
-use crate::models::{Project, CreateProjectRequest, ProjectStatus, ProjectFilters, PaginationParams};
+use crate::models::{
+    BillingMode, Project, ProjectActor, ProjectEventType, ProjectMilestone, CreateMilestoneRequest, CreateProjectRequest,
+    MilestoneStatus, ProjectAddon, ProjectStatus, ProjectFilters, PaginationParams, PricedAddon, RequirementField, RequirementValue,
+};
+use crate::services::{
+    AgencyService, DeliverableService, ExpertMetricsService, ExpertService, NewDeliverable, NewProjectEvent, NotificationService, ProjectEventService,
+    RequirementService,
+};
+use crate::utils::SecretKey;
+
+/// Error of a project status transition
+#[derive(Debug, thiserror::Error)]
+pub enum TransitionError {
+    #[error("Cannot change project status from {from:?} to {to:?} as {actor:?}")]
+    NotAllowed {
+        from: ProjectStatus,
+        to: ProjectStatus,
+        actor: ProjectActor,
+    },
+
+    #[error("{0}")]
+    GuardFailed(String),
+
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+/// What the client ordered from a service, checked against it before the project is created
+#[derive(Debug, Clone, Default)]
+pub struct ServiceOrder {
+    /// Snapshot of the service's requirements form
+    pub requirements_form: Vec<RequirementField>,
+    /// Checked answers submitted with the order
+    pub answers: Vec<(RequirementField, RequirementValue)>,
+    pub addons: Vec<PricedAddon>,
+    /// Delivery time of the service or package (used if the client sets no deadline)
+    pub delivery_days: Option<i16>,
+    /// Agency the service is sold under
+    pub agency_id: Option<Uuid>,
+}
+
+impl ServiceOrder {
+    fn addons_amount(&self) -> i32 {
+        self.addons.iter().map(|a| a.amount).sum()
+    }
+
+    /// Delivery date after add-ons; express add-ons never move it before tomorrow
+    fn delivery_date(&self, deadline: Option<DateTime<Utc>>) -> Option<DateTime<Utc>> {
+        let now = Utc::now();
+        let extra_days: i64 = self.addons.iter().map(|a| a.extra_days as i64).sum();
+
+        deadline
+            .or_else(|| self.delivery_days.map(|days| now + Duration::days(days as i64)))
+            .map(|date| (date + Duration::days(extra_days)).max(now + Duration::days(1)))
+    }
+}
 
 pub struct ProjectService;
 
 impl ProjectService {
-    /// Create a new project
+    /// Create a new project with the service's requirements form, the client's (checked) answers and
+    /// add-ons inside the caller's transaction
     pub async fn create(
-        db: &Database,
+        conn: &mut PgConnection,
+        secret_key: &SecretKey,
         client_id: Uuid,
         req: CreateProjectRequest,
+        billing_mode: BillingMode,
+        order: &ServiceOrder,
+    ) -> Result<Project, sqlx::Error> {
+        let mut project = Self::create_tx(conn, client_id, &req, billing_mode, None, order).await?;
+        if !order.answers.is_empty() {
+            RequirementService::save_answers_tx(conn, secret_key, project.id, client_id, &order.answers).await?;
+            if RequirementService::refresh_completion_tx(conn, project.id).await? {
+                project = sqlx::query_as::<_, Project>("SELECT * FROM projects WHERE id = $1")
+                    .bind(project.id)
+                    .fetch_one(&mut *conn)
+                    .await?;
+            }
+        }
+
+        Ok(project)
+    }
+
+    /// Create a project inside an existing transaction. Add-ons are added to the price,
+    /// delivery date and revisions; the requirements form is complete right away if it
+    /// has no required fields. Projects of organisation buyers belong to the organisation.
+    pub async fn create_tx(
+        conn: &mut PgConnection,
+        client_id: Uuid,
+        req: &CreateProjectRequest,
+        billing_mode: BillingMode,
+        proposal_id: Option<Uuid>,
+        order: &ServiceOrder,
     ) -> Result<Project, sqlx::Error> {
-        let price = req.budget.unwrap_or(0);
+        let price = req.budget.unwrap_or(0) + order.addons_amount();
         let (platform_fee, expert_payout) = Self::calculate_fees(price);
+        let extra_revisions: i32 = order.addons.iter().map(|a| a.extra_revisions).sum();
+        let revisions_allowed = (2 + extra_revisions).min(i16::MAX as i32) as i16;
 
-        sqlx::query_as::<_, Project>(
+        let project = sqlx::query_as::<_, Project>(
             r#"
             INSERT INTO projects (
                 client_id, expert_id, service_id, package_id, title, description,
                 requirements, price, currency, platform_fee, expert_payout,
-                delivery_date, revisions_allowed
+                delivery_date, revisions_allowed, po_number, billing_mode, proposal_id,
+                requirements_form, requirements_completed_at, agency_id, organisation_id
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, 2)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $18, $13, $14, $15,
+                    $16, CASE WHEN $17 THEN NULL ELSE NOW() END, $19,
+                    (SELECT organisation_id FROM organisation_members WHERE user_id = $1 AND role <> 'viewer'))
             RETURNING *
             "#
         )
@@ -38,9 +128,46 @@ impl ProjectService {
         .bind(&req.currency)
         .bind(platform_fee)
         .bind(expert_payout)
-        .bind(req.deadline)
-        .fetch_one(&db.pool)
-        .await
+        .bind(order.delivery_date(req.deadline))
+        .bind(&req.po_number)
+        .bind(billing_mode)
+        .bind(proposal_id)
+        .bind(sqlx::types::Json(&order.requirements_form))
+        .bind(order.requirements_form.iter().any(|f| f.required))
+        .bind(revisions_allowed)
+        .bind(order.agency_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        for addon in &order.addons {
+            sqlx::query(
+                r#"
+                INSERT INTO project_addons (
+                    project_id, addon_id, title, unit_price, quantity, extra_days, extra_revisions, amount
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                "#,
+            )
+            .bind(project.id)
+            .bind(addon.addon_id)
+            .bind(&addon.title)
+            .bind(addon.unit_price)
+            .bind(addon.quantity)
+            .bind(addon.extra_days)
+            .bind(addon.extra_revisions)
+            .bind(addon.amount)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        ProjectEventService::record(
+            &mut *conn,
+            NewProjectEvent::new(project.id, ProjectEventType::ProjectCreated, ProjectActor::Client, Some(client_id))
+                .status(None, project.status.clone()),
+        )
+        .await?;
+
+        Ok(project)
     }
 
     /// Get project by ID
@@ -51,107 +178,478 @@ impl ProjectService {
             .await
     }
 
-    /// Update project status
-    pub async fn update_status(
+    /// Move a project to `to` on behalf of `actor`, enforcing the lifecycle state machine
+    pub async fn transition(
         db: &Database,
         id: Uuid,
-        status: ProjectStatus,
-    ) -> Result<Project, sqlx::Error> {
-        let now = Utc::now();
+        to: ProjectStatus,
+        actor: ProjectActor,
+        actor_id: Option<Uuid>,
+        reason: Option<&str>,
+    ) -> Result<Project, TransitionError> {
+        let mut tx = db.pool.begin().await?;
+        let project = Self::transition_tx(&mut tx, id, to, actor, actor_id, reason).await?;
+        tx.commit().await?;
 
-        // Update timestamps based on status
-        let (delivered_at, completed_at, cancelled_at) = match status {
-            ProjectStatus::Delivered => (Some(now), None, None),
-            ProjectStatus::Completed => (None, Some(now), None),
-            ProjectStatus::Cancelled => (None, None, Some(now)),
-            _ => (None, None, None),
+        Ok(project)
+    }
+
+    /// Cancel a project whose checkout ended unpaid. Returns `None` if it was paid or
+    /// moved on meanwhile, so repeated webhook deliveries are harmless.
+    pub async fn cancel_unpaid(db: &Database, id: Uuid, reason: &str) -> Result<Option<Project>, sqlx::Error> {
+        let mut tx = db.pool.begin().await?;
+        let status: Option<ProjectStatus> = sqlx::query_scalar("SELECT status FROM projects WHERE id = $1 FOR UPDATE")
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        if status != Some(ProjectStatus::Accepted) {
+            return Ok(None);
+        }
+
+        let project = match Self::transition_tx(&mut tx, id, ProjectStatus::Cancelled, ProjectActor::System, None, Some(reason)).await {
+            Ok(project) => project,
+            Err(TransitionError::Database(e)) => return Err(e),
+            Err(_) => return Ok(None),
         };
+        tx.commit().await?;
+
+        Ok(Some(project))
+    }
+
+    /// Transition inside an existing transaction: checks the edge and its guard
+    /// conditions, updates the project, applies the side effects and records the change.
+    pub async fn transition_tx(
+        conn: &mut PgConnection,
+        id: Uuid,
+        to: ProjectStatus,
+        actor: ProjectActor,
+        actor_id: Option<Uuid>,
+        reason: Option<&str>,
+    ) -> Result<Project, TransitionError> {
+        let project = sqlx::query_as::<_, Project>("SELECT * FROM projects WHERE id = $1 FOR UPDATE")
+            .bind(id)
+            .fetch_optional(&mut *conn)
+            .await?
+            .ok_or(TransitionError::Database(sqlx::Error::RowNotFound))?;
 
-        sqlx::query_as::<_, Project>(
+        if !project.status.can_transition_to(&to, actor) {
+            return Err(TransitionError::NotAllowed {
+                from: project.status,
+                to,
+                actor,
+            });
+        }
+
+        // Guard conditions
+        match to {
+            ProjectStatus::Paid if project.billing_mode == BillingMode::Card => {
+                let paid: bool = sqlx::query_scalar(
+                    r#"
+                    SELECT EXISTS (
+                        SELECT 1 FROM payments
+                        WHERE project_id = $1 AND status = 'succeeded'
+                          AND metadata->>'kind' IS DISTINCT FROM 'tip'
+                    )
+                    "#,
+                )
+                .bind(id)
+                .fetch_one(&mut *conn)
+                .await?;
+                if !paid {
+                    return Err(TransitionError::GuardFailed("Payment has not succeeded yet".to_string()));
+                }
+            }
+            ProjectStatus::InProgress if project.requirements_completed_at.is_none() => {
+                return Err(TransitionError::GuardFailed(
+                    "The client has not completed the requirements form yet".to_string(),
+                ));
+            }
+            ProjectStatus::Revision if project.revisions_used >= project.revisions_allowed => {
+                return Err(TransitionError::GuardFailed("Revision limit reached".to_string()));
+            }
+            ProjectStatus::Delivered => {
+                let deliverables: i64 =
+                    sqlx::query_scalar(
+                        "SELECT COUNT(*) FROM project_deliverables WHERE project_id = $1 AND uploaded_at IS NOT NULL",
+                    )
+                        .bind(id)
+                        .fetch_one(&mut *conn)
+                        .await?;
+                if deliverables == 0 {
+                    return Err(TransitionError::GuardFailed("At least one deliverable is required".to_string()));
+                }
+            }
+            _ => {}
+        }
+
+        // Fees are fixed once the expert accepts the price
+        let (platform_fee, expert_payout) = Self::calculate_fees(project.price);
+        let reset_fees = to == ProjectStatus::Accepted;
+
+        let updated = sqlx::query_as::<_, Project>(
             r#"
             UPDATE projects
             SET status = $2,
-                updated_at = NOW(),
-                delivered_at = COALESCE($3, delivered_at),
-                completed_at = COALESCE($4, completed_at),
-                cancelled_at = COALESCE($5, cancelled_at)
+                delivered_at = CASE WHEN $2 = 'delivered'::project_status THEN NOW() ELSE delivered_at END,
+                auto_complete_reminders = CASE WHEN $2 = 'delivered'::project_status THEN 0 ELSE auto_complete_reminders END,
+                completed_at = CASE WHEN $2 = 'completed'::project_status THEN NOW() ELSE completed_at END,
+                cancelled_at = CASE WHEN $2 = 'cancelled'::project_status THEN NOW() ELSE cancelled_at END,
+                cancellation_reason = CASE WHEN $2 = 'cancelled'::project_status THEN $3 ELSE cancellation_reason END,
+                revisions_used = CASE WHEN $2 = 'revision'::project_status THEN revisions_used + 1 ELSE revisions_used END,
+                is_disputed = CASE WHEN $2 = 'disputed'::project_status THEN TRUE ELSE is_disputed END,
+                dispute_reason = CASE WHEN $2 = 'disputed'::project_status THEN $3 ELSE dispute_reason END,
+                platform_fee = CASE WHEN $4 THEN $5 ELSE platform_fee END,
+                expert_payout = CASE WHEN $4 THEN $6 ELSE expert_payout END,
+                updated_at = NOW()
             WHERE id = $1
             RETURNING *
-            "#
+            "#,
         )
         .bind(id)
-        .bind(status)
-        .bind(delivered_at)
-        .bind(completed_at)
-        .bind(cancelled_at)
-        .fetch_one(&db.pool)
-        .await
+        .bind(&to)
+        .bind(reason)
+        .bind(reset_fees)
+        .bind(platform_fee)
+        .bind(expert_payout)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        ProjectEventService::record(
+            &mut *conn,
+            NewProjectEvent::new(id, ProjectEventType::StatusChanged, actor, actor_id)
+                .status(Some(project.status.clone()), to.clone())
+                .message(reason),
+        )
+        .await?;
+
+        ExpertMetricsService::on_project_transition(&mut *conn, &updated, &project.status).await?;
+
+        // Side effects
+        match to {
+            ProjectStatus::Completed => {
+                let profile_id: Option<(Uuid,)> = sqlx::query_as("SELECT id FROM expert_profiles WHERE user_id = $1")
+                    .bind(updated.expert_id)
+                    .fetch_optional(&mut *conn)
+                    .await?;
+                if let Some((profile_id,)) = profile_id {
+                    ExpertService::increment_projects_tx(&mut *conn, profile_id).await?;
+                    ExpertService::add_earnings_tx(&mut *conn, profile_id, updated.expert_payout as i64).await?;
+                }
+
+                Self::release_payments(&mut *conn, id).await?;
+
+                NotificationService::create(
+                    &mut *conn,
+                    updated.client_id,
+                    "review_request",
+                    "Wie war die Zusammenarbeit?",
+                    &format!("Das Projekt \"{}\" ist abgeschlossen. Bitte bewerten Sie den Experten.", updated.title),
+                    Some(serde_json::json!({ "projectId": id, "expertId": updated.expert_id })),
+                )
+                .await?;
+            }
+            // Whatever the expert keeps after the refund becomes payable
+            ProjectStatus::Cancelled => Self::release_payments(&mut *conn, id).await?,
+            // Work starts once the client has answered the requirements form
+            ProjectStatus::Paid if updated.requirements_completed_at.is_none() => {
+                return Box::pin(Self::transition_tx(
+                    conn,
+                    id,
+                    ProjectStatus::AwaitingRequirements,
+                    ProjectActor::System,
+                    None,
+                    None,
+                ))
+                .await;
+            }
+            ProjectStatus::AwaitingRequirements => {
+                NotificationService::create(
+                    &mut *conn,
+                    updated.client_id,
+                    "requirements_needed",
+                    "Anforderungen ausfüllen",
+                    &format!(
+                        "Damit die Arbeit an \"{}\" beginnen kann, füllen Sie bitte die Anforderungen des Experten aus.",
+                        updated.title
+                    ),
+                    Some(serde_json::json!({ "projectId": id })),
+                )
+                .await?;
+            }
+            _ => {}
+        }
+
+        // Access credentials are not kept once the project is closed
+        if to.is_terminal() {
+            RequirementService::purge_secrets_tx(conn, id).await?;
+        }
+
+        Ok(updated)
     }
 
-    /// Deliver project (expert submits)
-    pub async fn deliver(db: &Database, id: Uuid, _message: &str) -> Result<Project, sqlx::Error> {
-        sqlx::query_as::<_, Project>(
+    /// Make the project's settled payments eligible for payout and split agency payments
+    async fn release_payments(conn: &mut PgConnection, project_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query(
             r#"
-            UPDATE projects
-            SET status = 'delivered', delivered_at = NOW(), updated_at = NOW()
-            WHERE id = $1 AND status IN ('in_progress', 'revision')
-            RETURNING *
-            "#
+            UPDATE payments
+            SET released_at = NOW(), updated_at = NOW()
+            WHERE project_id = $1 AND released_at IS NULL
+              AND status IN ('succeeded', 'partially_refunded')
+            "#,
         )
-        .bind(id)
-        .fetch_one(&db.pool)
-        .await
+        .bind(project_id)
+        .execute(&mut *conn)
+        .await?;
+
+        AgencyService::record_splits(conn, project_id).await
     }
 
-    /// Request revision (client)
-    pub async fn request_revision(db: &Database, id: Uuid, _feedback: &str) -> Result<Project, sqlx::Error> {
-        sqlx::query_as::<_, Project>(
-            r#"
-            UPDATE projects
-            SET status = 'revision',
-                revisions_used = revisions_used + 1,
-                updated_at = NOW()
-            WHERE id = $1 AND status = 'delivered' AND revisions_used < revisions_allowed
-            RETURNING *
-            "#
+    /// Deliver project (expert submits uploaded deliverables); URL attachments are stored as new versions
+    pub async fn deliver(
+        db: &Database,
+        id: Uuid,
+        expert_id: Uuid,
+        message: &str,
+        attachments: &[String],
+        deliverable_ids: &[Uuid],
+    ) -> Result<Project, TransitionError> {
+        let mut tx = db.pool.begin().await?;
+
+        let mut ids = deliverable_ids.to_vec();
+        ids.sort();
+        ids.dedup();
+        if !DeliverableService::all_belong_to_project(&mut tx, id, &ids).await? {
+            return Err(TransitionError::GuardFailed("Unknown or unconfirmed deliverable".to_string()));
+        }
+
+        for url in attachments {
+            let deliverable = DeliverableService::insert_tx(&mut tx, id, expert_id, NewDeliverable::link(url)).await?;
+            ids.push(deliverable.id);
+        }
+
+        if ids.is_empty() {
+            return Err(TransitionError::GuardFailed("At least one deliverable is required".to_string()));
+        }
+
+        let delivery = DeliverableService::create_delivery_tx(&mut tx, id, expert_id, message, &ids).await?;
+
+        ProjectEventService::record(
+            &mut *tx,
+            NewProjectEvent::new(id, ProjectEventType::DeliverySubmitted, ProjectActor::Expert, Some(expert_id))
+                .message(Some(message))
+                .data(serde_json::json!({ "deliveryId": delivery.id, "deliverableIds": delivery.deliverable_ids })),
         )
-        .bind(id)
-        .fetch_one(&db.pool)
-        .await
+        .await?;
+
+        let project = Self::transition_tx(
+            &mut tx,
+            id,
+            ProjectStatus::Delivered,
+            ProjectActor::Expert,
+            Some(expert_id),
+            None,
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(project)
+    }
+
+    /// Request revision (client); without explicit versions the feedback refers to the latest delivery
+    pub async fn request_revision(
+        db: &Database,
+        id: Uuid,
+        client_id: Uuid,
+        feedback: &str,
+        deliverable_ids: &[Uuid],
+    ) -> Result<Project, TransitionError> {
+        let mut tx = db.pool.begin().await?;
+
+        let project = Self::transition_tx(
+            &mut tx,
+            id,
+            ProjectStatus::Revision,
+            ProjectActor::Client,
+            Some(client_id),
+            None,
+        )
+        .await?;
+
+        let delivery = DeliverableService::latest_delivery_tx(&mut tx, id).await?;
+        let ids = if deliverable_ids.is_empty() {
+            delivery.as_ref().map(|d| d.deliverable_ids.clone()).unwrap_or_default()
+        } else {
+            let mut ids = deliverable_ids.to_vec();
+            ids.sort();
+            ids.dedup();
+            if !DeliverableService::all_belong_to_project(&mut tx, id, &ids).await? {
+                return Err(TransitionError::GuardFailed("Unknown deliverable".to_string()));
+            }
+            ids
+        };
+
+        let revision = DeliverableService::create_revision_request_tx(
+            &mut tx,
+            id,
+            delivery.as_ref().map(|d| d.id),
+            client_id,
+            feedback,
+            &ids,
+        )
+        .await?;
+
+        ProjectEventService::record(
+            &mut *tx,
+            NewProjectEvent::new(id, ProjectEventType::RevisionRequested, ProjectActor::Client, Some(client_id))
+                .message(Some(feedback))
+                .data(serde_json::json!({
+                    "revisionsUsed": project.revisions_used,
+                    "revisionsAllowed": project.revisions_allowed,
+                    "deliveryId": revision.delivery_id,
+                    "deliverableIds": revision.deliverable_ids,
+                })),
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(project)
     }
 
     /// Complete project (client approves)
-    pub async fn complete(db: &Database, id: Uuid) -> Result<Project, sqlx::Error> {
-        sqlx::query_as::<_, Project>(
+    pub async fn complete(
+        db: &Database,
+        id: Uuid,
+        actor: ProjectActor,
+        actor_id: Option<Uuid>,
+    ) -> Result<Project, TransitionError> {
+        Self::transition(db, id, ProjectStatus::Completed, actor, actor_id, None).await
+    }
+
+    /// Cancel project
+    pub async fn cancel(
+        db: &Database,
+        id: Uuid,
+        actor: ProjectActor,
+        actor_id: Option<Uuid>,
+        reason: Option<&str>,
+    ) -> Result<Project, TransitionError> {
+        Self::transition(db, id, ProjectStatus::Cancelled, actor, actor_id, reason).await
+    }
+
+    /// Get milestones of a project
+    pub async fn get_milestones(db: &Database, project_id: Uuid) -> Result<Vec<ProjectMilestone>, sqlx::Error> {
+        sqlx::query_as::<_, ProjectMilestone>(
+            "SELECT * FROM project_milestones WHERE project_id = $1 ORDER BY sort_order, created_at"
+        )
+        .bind(project_id)
+        .fetch_all(&db.pool)
+        .await
+    }
+
+    /// Add-ons ordered with a project
+    pub async fn get_addons(db: &Database, project_id: Uuid) -> Result<Vec<ProjectAddon>, sqlx::Error> {
+        let mut conn = db.pool.acquire().await?;
+        Self::get_addons_tx(&mut conn, project_id).await
+    }
+
+    /// Add-ons ordered with a project, inside the caller's transaction
+    pub async fn get_addons_tx(conn: &mut PgConnection, project_id: Uuid) -> Result<Vec<ProjectAddon>, sqlx::Error> {
+        sqlx::query_as::<_, ProjectAddon>("SELECT * FROM project_addons WHERE project_id = $1 ORDER BY created_at")
+            .bind(project_id)
+            .fetch_all(conn)
+            .await
+    }
+
+    /// Add a milestone to a project
+    pub async fn create_milestone(
+        db: &Database,
+        project_id: Uuid,
+        actor: ProjectActor,
+        actor_id: Uuid,
+        req: &CreateMilestoneRequest,
+    ) -> Result<ProjectMilestone, sqlx::Error> {
+        let mut tx = db.pool.begin().await?;
+        let milestone = Self::create_milestone_tx(&mut tx, project_id, actor, actor_id, req).await?;
+        tx.commit().await?;
+
+        Ok(milestone)
+    }
+
+    /// Add a milestone inside an existing transaction
+    pub async fn create_milestone_tx(
+        conn: &mut PgConnection,
+        project_id: Uuid,
+        actor: ProjectActor,
+        actor_id: Uuid,
+        req: &CreateMilestoneRequest,
+    ) -> Result<ProjectMilestone, sqlx::Error> {
+        let milestone = sqlx::query_as::<_, ProjectMilestone>(
             r#"
-            UPDATE projects
-            SET status = 'completed', completed_at = NOW(), updated_at = NOW()
-            WHERE id = $1 AND status = 'delivered'
+            INSERT INTO project_milestones (project_id, title, description, amount, due_date, sort_order)
+            VALUES ($1, $2, $3, $4, $5,
+                    (SELECT COALESCE(MAX(sort_order) + 1, 0) FROM project_milestones WHERE project_id = $1))
             RETURNING *
             "#
         )
-        .bind(id)
-        .fetch_one(&db.pool)
-        .await
+        .bind(project_id)
+        .bind(&req.title)
+        .bind(&req.description)
+        .bind(req.amount)
+        .bind(req.due_date)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        ProjectEventService::record(
+            &mut *conn,
+            NewProjectEvent::new(project_id, ProjectEventType::MilestoneCreated, actor, Some(actor_id))
+                .message(Some(&milestone.title))
+                .data(serde_json::json!({ "milestoneId": milestone.id, "amount": milestone.amount })),
+        )
+        .await?;
+
+        Ok(milestone)
     }
 
-    /// Cancel project
-    pub async fn cancel(db: &Database, id: Uuid, reason: Option<&str>) -> Result<Project, sqlx::Error> {
-        sqlx::query_as::<_, Project>(
+    /// Change the status of a milestone
+    pub async fn update_milestone_status(
+        db: &Database,
+        project_id: Uuid,
+        milestone_id: Uuid,
+        status: MilestoneStatus,
+        actor: ProjectActor,
+        actor_id: Uuid,
+    ) -> Result<Option<ProjectMilestone>, sqlx::Error> {
+        let mut tx = db.pool.begin().await?;
+
+        let milestone = sqlx::query_as::<_, ProjectMilestone>(
             r#"
-            UPDATE projects
-            SET status = 'cancelled',
-                cancelled_at = NOW(),
-                cancellation_reason = $2,
-                updated_at = NOW()
-            WHERE id = $1 AND status NOT IN ('completed', 'cancelled', 'refunded')
+            UPDATE project_milestones
+            SET status = $3,
+                completed_at = CASE WHEN $3 = 'completed'::milestone_status THEN NOW() ELSE NULL END
+            WHERE id = $2 AND project_id = $1
             RETURNING *
             "#
         )
-        .bind(id)
-        .bind(reason)
-        .fetch_one(&db.pool)
-        .await
+        .bind(project_id)
+        .bind(milestone_id)
+        .bind(&status)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if let Some(milestone) = &milestone {
+            ProjectEventService::record(
+                &mut *tx,
+                NewProjectEvent::new(project_id, ProjectEventType::MilestoneUpdated, actor, Some(actor_id))
+                    .message(Some(&milestone.title))
+                    .data(serde_json::json!({ "milestoneId": milestone.id, "status": milestone.status })),
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(milestone)
     }
 
     /// Get projects for user (as client or expert)
@@ -0,0 +1,703 @@
+//! Proposal workflow: review by the client (viewed, shortlisted, rejected), editing and
+//! withdrawal by the expert, and acceptance, which turns the winning proposal into a
+//! project and closes the posting.
+
+use chrono::{Duration, Utc};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::config::ProposalSettings;
+use crate::db::Database;
+use crate::models::{
+    BillingMode, CreateMilestoneRequest, CreateProjectRequest, CreateProposalRequest, Project, ProjectActor, ProjectPosting,
+    ProjectPostingStatus, ProjectStatus, Proposal, ProposalAcceptance, ProposalComparison, ProposalComparisonQuery,
+    ProposalSortBy, ProposalStatus, UpdateProposalRequest,
+};
+use crate::services::{ClientService, MessageService, NotificationService, ProjectService, ServiceOrder, TransitionError};
+
+/// Error of a proposal operation
+#[derive(Debug, thiserror::Error)]
+pub enum ProposalError {
+    #[error("Proposal not found")]
+    NotFound,
+
+    #[error("{0}")]
+    Conflict(String),
+
+    #[error("{0}")]
+    Invalid(String),
+
+    #[error("You can submit at most {limit} proposals per {days} days")]
+    LimitReached { limit: i64, days: i64 },
+
+    #[error(transparent)]
+    Transition(#[from] TransitionError),
+
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+pub struct ProposalService;
+
+impl ProposalService {
+    pub async fn get_by_id(db: &Database, id: Uuid) -> Result<Option<Proposal>, sqlx::Error> {
+        sqlx::query_as::<_, Proposal>("SELECT * FROM proposals WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&db.pool)
+            .await
+    }
+
+    /// Proposal on one of the client's own postings
+    pub async fn get_for_client(db: &Database, id: Uuid, client_id: Uuid) -> Result<Option<Proposal>, sqlx::Error> {
+        sqlx::query_as::<_, Proposal>(
+            r#"
+            SELECT p.* FROM proposals p
+            JOIN project_postings pp ON pp.id = p.project_posting_id
+            WHERE p.id = $1 AND pp.client_id = $2
+            "#,
+        )
+        .bind(id)
+        .bind(client_id)
+        .fetch_optional(&db.pool)
+        .await
+    }
+
+    /// Submit a proposal unless the expert has used up their proposals for the current
+    /// period. The expert row is locked, so concurrent submissions cannot exceed the limit.
+    pub async fn submit(
+        db: &Database,
+        expert_id: Uuid,
+        settings: &ProposalSettings,
+        req: CreateProposalRequest,
+    ) -> Result<Proposal, ProposalError> {
+        let mut tx = db.pool.begin().await?;
+
+        sqlx::query("SELECT id FROM users WHERE id = $1 FOR UPDATE")
+            .bind(expert_id)
+            .execute(&mut *tx)
+            .await?;
+        Self::check_limit(&mut tx, expert_id, settings).await?;
+
+        let proposal = ClientService::create_proposal(&mut tx, expert_id, req).await?;
+
+        tx.commit().await?;
+
+        Ok(proposal)
+    }
+
+    /// Fails once the expert has used up their proposals for the current period
+    async fn check_limit(conn: &mut PgConnection, expert_id: Uuid, settings: &ProposalSettings) -> Result<(), ProposalError> {
+        if settings.max_per_period <= 0 {
+            return Ok(());
+        }
+
+        // Withdrawn proposals count as well, otherwise the limit could be bypassed
+        let submitted: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM proposals WHERE expert_id = $1 AND created_at > $2",
+        )
+        .bind(expert_id)
+        .bind(Utc::now() - Duration::days(settings.period_days))
+        .fetch_one(&mut *conn)
+        .await?;
+
+        if submitted >= settings.max_per_period {
+            return Err(ProposalError::LimitReached {
+                limit: settings.max_per_period,
+                days: settings.period_days,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Lock a proposal together with its posting for a change by the posting's client
+    async fn lock_for_client(
+        conn: &mut PgConnection,
+        id: Uuid,
+        client_id: Uuid,
+    ) -> Result<(Proposal, ProjectPosting), ProposalError> {
+        let posting = sqlx::query_as::<_, ProjectPosting>(
+            r#"
+            SELECT pp.* FROM project_postings pp
+            JOIN proposals p ON p.project_posting_id = pp.id
+            WHERE p.id = $1 AND pp.client_id = $2
+            FOR UPDATE OF pp
+            "#,
+        )
+        .bind(id)
+        .bind(client_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or(ProposalError::NotFound)?;
+
+        let proposal = sqlx::query_as::<_, Proposal>("SELECT * FROM proposals WHERE id = $1 FOR UPDATE")
+            .bind(id)
+            .fetch_one(&mut *conn)
+            .await?;
+
+        Ok((proposal, posting))
+    }
+
+    /// Lock a proposal for a change by its expert
+    async fn lock_for_expert(conn: &mut PgConnection, id: Uuid, expert_id: Uuid) -> Result<Proposal, ProposalError> {
+        sqlx::query_as::<_, Proposal>("SELECT * FROM proposals WHERE id = $1 AND expert_id = $2 FOR UPDATE")
+            .bind(id)
+            .bind(expert_id)
+            .fetch_optional(conn)
+            .await?
+            .ok_or(ProposalError::NotFound)
+    }
+
+    /// Proposal as seen by the posting's client; marks it as viewed
+    pub async fn view(db: &Database, id: Uuid, client_id: Uuid) -> Result<Proposal, ProposalError> {
+        sqlx::query_as::<_, Proposal>(
+            r#"
+            UPDATE proposals p
+            SET client_viewed_at = COALESCE(p.client_viewed_at, NOW())
+            FROM project_postings pp
+            WHERE p.id = $1 AND p.project_posting_id = pp.id AND pp.client_id = $2
+            RETURNING p.*
+            "#,
+        )
+        .bind(id)
+        .bind(client_id)
+        .fetch_optional(&db.pool)
+        .await?
+        .ok_or(ProposalError::NotFound)
+    }
+
+    /// Shortlist a pending proposal; the posting moves into review
+    pub async fn shortlist(db: &Database, id: Uuid, client_id: Uuid) -> Result<Proposal, ProposalError> {
+        let mut tx = db.pool.begin().await?;
+        let (proposal, posting) = Self::lock_for_client(&mut tx, id, client_id).await?;
+
+        if proposal.status != ProposalStatus::Pending {
+            return Err(ProposalError::Conflict("Only pending proposals can be shortlisted".to_string()));
+        }
+        if !matches!(
+            posting.status,
+            ProjectPostingStatus::Draft | ProjectPostingStatus::Open | ProjectPostingStatus::InReview
+        ) {
+            return Err(ProposalError::Conflict("Posting is no longer open".to_string()));
+        }
+
+        let proposal = sqlx::query_as::<_, Proposal>(
+            r#"
+            UPDATE proposals
+            SET status = 'shortlisted', shortlisted_at = NOW(),
+                client_viewed_at = COALESCE(client_viewed_at, NOW()), updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            "UPDATE project_postings SET status = 'in_review', updated_at = NOW() WHERE id = $1 AND status IN ('draft', 'open')",
+        )
+        .bind(posting.id)
+        .execute(&mut *tx)
+        .await?;
+
+        NotificationService::create(
+            &mut *tx,
+            proposal.expert_id,
+            "proposal_shortlisted",
+            "Angebot in der engeren Auswahl",
+            &format!("Ihr Angebot für \"{}\" ist in der engeren Auswahl.", posting.title),
+            Some(serde_json::json!({ "proposalId": proposal.id, "postingId": posting.id })),
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(proposal)
+    }
+
+    /// Reject a pending or shortlisted proposal
+    pub async fn reject(
+        db: &Database,
+        id: Uuid,
+        client_id: Uuid,
+        reason: Option<&str>,
+    ) -> Result<Proposal, ProposalError> {
+        let mut tx = db.pool.begin().await?;
+        let (proposal, posting) = Self::lock_for_client(&mut tx, id, client_id).await?;
+
+        if !matches!(proposal.status, ProposalStatus::Pending | ProposalStatus::Shortlisted) {
+            return Err(ProposalError::Conflict("Proposal can no longer be rejected".to_string()));
+        }
+
+        let proposal = sqlx::query_as::<_, Proposal>(
+            r#"
+            UPDATE proposals
+            SET status = 'rejected', rejected_at = NOW(), rejection_reason = $2,
+                client_viewed_at = COALESCE(client_viewed_at, NOW()), updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(reason)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let message = match reason {
+            Some(reason) => format!("Ihr Angebot für \"{}\" wurde abgelehnt. Begründung: {}", posting.title, reason),
+            None => format!("Ihr Angebot für \"{}\" wurde abgelehnt.", posting.title),
+        };
+        NotificationService::create(
+            &mut *tx,
+            proposal.expert_id,
+            "proposal_rejected",
+            "Angebot abgelehnt",
+            &message,
+            Some(serde_json::json!({ "proposalId": proposal.id, "postingId": posting.id })),
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(proposal)
+    }
+
+    /// Edit a proposal; only possible while pending and not yet viewed by the client
+    pub async fn update(
+        db: &Database,
+        id: Uuid,
+        expert_id: Uuid,
+        req: &UpdateProposalRequest,
+    ) -> Result<Proposal, ProposalError> {
+        let mut tx = db.pool.begin().await?;
+        let proposal = Self::lock_for_expert(&mut tx, id, expert_id).await?;
+
+        if proposal.status != ProposalStatus::Pending || proposal.client_viewed_at.is_some() {
+            return Err(ProposalError::Conflict(
+                "Proposal can no longer be edited once the client has reviewed it".to_string(),
+            ));
+        }
+
+        let proposal = sqlx::query_as::<_, Proposal>(
+            r#"
+            UPDATE proposals SET
+                cover_letter = COALESCE($2, cover_letter),
+                proposed_price = COALESCE($3, proposed_price),
+                currency = COALESCE($4, currency),
+                proposed_duration = COALESCE($5, proposed_duration),
+                proposed_milestones = COALESCE($6, proposed_milestones),
+                updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(&req.cover_letter)
+        .bind(req.proposed_price)
+        .bind(&req.currency)
+        .bind(&req.proposed_duration)
+        .bind(&req.proposed_milestones)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(proposal)
+    }
+
+    /// Withdraw a pending or shortlisted proposal
+    pub async fn withdraw(db: &Database, id: Uuid, expert_id: Uuid) -> Result<Proposal, ProposalError> {
+        let mut tx = db.pool.begin().await?;
+        let proposal = Self::lock_for_expert(&mut tx, id, expert_id).await?;
+
+        if !matches!(proposal.status, ProposalStatus::Pending | ProposalStatus::Shortlisted) {
+            return Err(ProposalError::Conflict("Proposal can no longer be withdrawn".to_string()));
+        }
+
+        let proposal = sqlx::query_as::<_, Proposal>(
+            r#"
+            UPDATE proposals
+            SET status = 'withdrawn', withdrawn_at = NOW(), updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let (client_id, title): (Uuid, String) = sqlx::query_as(
+            r#"
+            UPDATE project_postings
+            SET proposal_count = GREATEST(proposal_count - 1, 0), updated_at = NOW()
+            WHERE id = $1
+            RETURNING client_id, title
+            "#,
+        )
+        .bind(proposal.project_posting_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        NotificationService::create(
+            &mut *tx,
+            client_id,
+            "proposal_withdrawn",
+            "Angebot zurückgezogen",
+            &format!("Ein Experte hat sein Angebot für \"{}\" zurückgezogen.", title),
+            Some(serde_json::json!({ "proposalId": proposal.id, "postingId": proposal.project_posting_id })),
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(proposal)
+    }
+
+    /// Proposals of a posting side by side with the figures of their experts
+    pub async fn compare(
+        db: &Database,
+        posting_id: Uuid,
+        client_id: Uuid,
+        query: &ProposalComparisonQuery,
+    ) -> Result<Vec<ProposalComparison>, ProposalError> {
+        let posting = sqlx::query_as::<_, ProjectPosting>("SELECT * FROM project_postings WHERE id = $1 AND client_id = $2")
+            .bind(posting_id)
+            .bind(client_id)
+            .fetch_optional(&db.pool)
+            .await?
+            .ok_or(ProposalError::NotFound)?;
+
+        let proposals = sqlx::query_as::<_, Proposal>(
+            r#"
+            SELECT * FROM proposals
+            WHERE project_posting_id = $1 AND ($2 OR status IN ('pending', 'shortlisted', 'accepted'))
+            ORDER BY created_at
+            "#,
+        )
+        .bind(posting_id)
+        .bind(query.include_closed.unwrap_or(false))
+        .fetch_all(&db.pool)
+        .await?;
+
+        let expert_ids: Vec<Uuid> = proposals.iter().map(|p| p.expert_id).collect();
+        let experts: Vec<ExpertRow> = sqlx::query_as(
+            r#"
+            SELECT u.id, u.first_name || ' ' || u.last_name, u.avatar_url, ep.headline,
+                   COALESCE(ep.rating_average, 0), COALESCE(ep.rating_count, 0),
+                   ep.completion_rate, ep.response_time_hours,
+                   COALESCE(ep.skills, '{}') || COALESCE(ep.tools, '{}')
+            FROM users u
+            LEFT JOIN expert_profiles ep ON ep.user_id = u.id
+            WHERE u.id = ANY($1)
+            "#,
+        )
+        .bind(&expert_ids)
+        .fetch_all(&db.pool)
+        .await?;
+
+        let required: Vec<String> = posting
+            .skills_required
+            .iter()
+            .chain(&posting.tools_required)
+            .cloned()
+            .collect();
+
+        let mut comparison: Vec<ProposalComparison> = proposals
+            .into_iter()
+            .filter_map(|proposal| {
+                let expert = experts.iter().find(|e| e.0 == proposal.expert_id)?;
+                let (matched_skills, fit_score) = fit(&required, &expert.8);
+                Some(ProposalComparison {
+                    proposal,
+                    expert_name: expert.1.clone(),
+                    expert_avatar: expert.2.clone(),
+                    expert_headline: expert.3.clone(),
+                    rating_average: expert.4,
+                    rating_count: expert.5,
+                    completion_rate: expert.6,
+                    response_time_hours: expert.7,
+                    matched_skills,
+                    fit_score,
+                })
+            })
+            .collect();
+
+        sort_comparison(&mut comparison, query.sort_by.unwrap_or_default());
+
+        Ok(comparison)
+    }
+
+    /// Accept a proposal inside the caller's transaction: create the project from its terms,
+    /// assign the posting, reject competing proposals and open a conversation between
+    /// client and expert.
+    pub async fn accept_tx(
+        conn: &mut PgConnection,
+        proposal_id: Uuid,
+        client_id: Uuid,
+        billing_mode: BillingMode,
+    ) -> Result<ProposalAcceptance, ProposalError> {
+        // Locks the posting first so concurrent acceptances serialize on it
+        let (proposal, posting) = Self::lock_for_client(conn, proposal_id, client_id).await?;
+
+        if !matches!(
+            posting.status,
+            ProjectPostingStatus::Draft | ProjectPostingStatus::Open | ProjectPostingStatus::InReview
+        ) {
+            return Err(ProposalError::Conflict("Posting is no longer open".to_string()));
+        }
+
+        if !matches!(proposal.status, ProposalStatus::Pending | ProposalStatus::Shortlisted) {
+            return Err(ProposalError::Conflict("Proposal can no longer be accepted".to_string()));
+        }
+
+        let milestones = proposal
+            .milestones()
+            .ok_or_else(|| ProposalError::Invalid("Proposal milestones are malformed".to_string()))?;
+
+        let proposal = sqlx::query_as::<_, Proposal>(
+            r#"
+            UPDATE proposals
+            SET status = 'accepted', accepted_at = NOW(), updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(proposal_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        let request = CreateProjectRequest {
+            expert_id: proposal.expert_id,
+            service_id: None,
+            package_id: None,
+            title: posting.title.clone(),
+            description: posting.description.clone(),
+            requirements: posting.requirements.clone(),
+            budget: Some(proposal.proposed_price),
+            currency: proposal.currency.clone(),
+            deadline: posting.deadline,
+            po_number: None,
+            requirement_answers: None,
+            addons: None,
+        };
+        let order = ServiceOrder::default();
+        let project =
+            ProjectService::create_tx(conn, client_id, &request, billing_mode, Some(proposal.id), &order).await?;
+
+        for milestone in &milestones {
+            let req = CreateMilestoneRequest {
+                title: milestone.title.clone(),
+                description: milestone.description.clone(),
+                amount: milestone.amount,
+                due_date: milestone.due_date,
+            };
+            ProjectService::create_milestone_tx(conn, project.id, ProjectActor::Expert, proposal.expert_id, &req)
+                .await?;
+        }
+
+        // The expert already agreed to these terms with the proposal
+        let mut project: Project = ProjectService::transition_tx(
+            conn,
+            project.id,
+            ProjectStatus::Accepted,
+            ProjectActor::System,
+            None,
+            Some("Angebot angenommen"),
+        )
+        .await?;
+        if billing_mode == BillingMode::Invoice {
+            project = ProjectService::transition_tx(conn, project.id, ProjectStatus::Paid, ProjectActor::System, None, None)
+                .await?;
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE project_postings
+            SET status = 'assigned', assigned_expert_id = $2, assigned_at = NOW(), updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(posting.id)
+        .bind(proposal.expert_id)
+        .execute(&mut *conn)
+        .await?;
+
+        let rejected: Vec<(Uuid,)> = sqlx::query_as(
+            r#"
+            UPDATE proposals
+            SET status = 'rejected', rejected_at = NOW(),
+                rejection_reason = 'Another proposal was accepted', updated_at = NOW()
+            WHERE project_posting_id = $1 AND id <> $2 AND status IN ('pending', 'shortlisted')
+            RETURNING expert_id
+            "#,
+        )
+        .bind(posting.id)
+        .bind(proposal.id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        NotificationService::create(
+            &mut *conn,
+            proposal.expert_id,
+            "proposal_accepted",
+            "Angebot angenommen",
+            &format!("Ihr Angebot für \"{}\" wurde angenommen. Das Projekt wurde erstellt.", posting.title),
+            Some(serde_json::json!({ "proposalId": proposal.id, "projectId": project.id })),
+        )
+        .await?;
+
+        for (expert_id,) in &rejected {
+            NotificationService::create(
+                &mut *conn,
+                *expert_id,
+                "proposal_rejected",
+                "Auftrag vergeben",
+                &format!("Der Auftrag \"{}\" wurde an einen anderen Experten vergeben.", posting.title),
+                Some(serde_json::json!({ "postingId": posting.id })),
+            )
+            .await?;
+        }
+
+        let message = MessageService::send_system_message_tx(
+            conn,
+            client_id,
+            proposal.expert_id,
+            Some(project.id),
+            &format!(
+                "Angebot für \"{}\" angenommen. Das Projekt wurde erstellt – hier können Sie die Details besprechen.",
+                posting.title
+            ),
+        )
+        .await?;
+
+        Ok(ProposalAcceptance {
+            proposal,
+            project,
+            conversation_id: message.conversation_id,
+            rejected_proposals: rejected.len() as i64,
+        })
+    }
+}
+
+/// id, name, avatar, headline, rating, rating count, completion rate, response time, skills and tools
+type ExpertRow = (
+    Uuid,
+    String,
+    Option<String>,
+    Option<String>,
+    f32,
+    i32,
+    Option<f32>,
+    Option<i16>,
+    Vec<String>,
+);
+
+/// Required skills the expert offers (case-insensitive) and the matched share in percent
+pub fn fit(required: &[String], offered: &[String]) -> (Vec<String>, Option<f32>) {
+    if required.is_empty() {
+        return (Vec::new(), None);
+    }
+
+    let matched: Vec<String> = required
+        .iter()
+        .filter(|skill| offered.iter().any(|o| o.trim().eq_ignore_ascii_case(skill.trim())))
+        .cloned()
+        .collect();
+    let score = matched.len() as f32 / required.len() as f32 * 100.0;
+
+    (matched, Some(score))
+}
+
+/// Order proposals for comparison; ties fall back to the better rated expert, then the earlier proposal
+pub fn sort_comparison(proposals: &mut [ProposalComparison], sort_by: ProposalSortBy) {
+    let by_rating = |a: &ProposalComparison, b: &ProposalComparison| {
+        b.rating_average
+            .total_cmp(&a.rating_average)
+            .then(b.rating_count.cmp(&a.rating_count))
+    };
+
+    proposals.sort_by(|a, b| {
+        let primary = match sort_by {
+            ProposalSortBy::Price => a.proposal.proposed_price.cmp(&b.proposal.proposed_price),
+            ProposalSortBy::Rating => by_rating(a, b),
+            ProposalSortBy::Fit => b.fit_score.unwrap_or(-1.0).total_cmp(&a.fit_score.unwrap_or(-1.0)),
+        };
+        primary
+            .then_with(|| by_rating(a, b))
+            .then(a.proposal.created_at.cmp(&b.proposal.created_at))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Currency;
+
+    fn skills(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn comparison(price: i32, rating: f32, fit_score: Option<f32>) -> ProposalComparison {
+        let now = Utc::now();
+        ProposalComparison {
+            proposal: Proposal {
+                id: Uuid::new_v4(),
+                project_posting_id: Uuid::nil(),
+                expert_id: Uuid::new_v4(),
+                cover_letter: String::new(),
+                proposed_price: price,
+                currency: Currency::CHF,
+                proposed_duration: None,
+                proposed_milestones: None,
+                attachments: Vec::new(),
+                status: ProposalStatus::Pending,
+                is_featured: false,
+                client_viewed_at: None,
+                shortlisted_at: None,
+                accepted_at: None,
+                rejected_at: None,
+                rejection_reason: None,
+                withdrawn_at: None,
+                created_at: now,
+                updated_at: now,
+            },
+            expert_name: String::new(),
+            expert_avatar: None,
+            expert_headline: None,
+            rating_average: rating,
+            rating_count: 10,
+            completion_rate: None,
+            response_time_hours: None,
+            matched_skills: Vec::new(),
+            fit_score,
+        }
+    }
+
+    #[test]
+    fn test_fit() {
+        let (matched, score) = fit(&skills(&["n8n", "Python", "OpenAI"]), &skills(&["python", "N8N ", "Make"]));
+        assert_eq!(matched, skills(&["n8n", "Python"]));
+        assert!((score.unwrap() - 66.666).abs() < 0.01);
+
+        assert_eq!(fit(&[], &skills(&["Python"])), (Vec::new(), None));
+    }
+
+    #[test]
+    fn test_sort_comparison() {
+        let mut proposals = vec![
+            comparison(5000, 4.2, Some(50.0)),
+            comparison(3000, 4.9, None),
+            comparison(8000, 3.5, Some(100.0)),
+        ];
+
+        sort_comparison(&mut proposals, ProposalSortBy::Price);
+        assert_eq!(proposals.iter().map(|p| p.proposal.proposed_price).collect::<Vec<_>>(), [3000, 5000, 8000]);
+
+        sort_comparison(&mut proposals, ProposalSortBy::Rating);
+        assert_eq!(proposals.iter().map(|p| p.proposal.proposed_price).collect::<Vec<_>>(), [3000, 5000, 8000]);
+
+        sort_comparison(&mut proposals, ProposalSortBy::Fit);
+        assert_eq!(proposals.iter().map(|p| p.proposal.proposed_price).collect::<Vec<_>>(), [8000, 5000, 3000]);
+    }
+}
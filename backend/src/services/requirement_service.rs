@@ -0,0 +1,245 @@
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::models::{
+    Project, ProjectActor, ProjectEventType, ProjectRequirementAnswer, ProjectStatus, RequirementField,
+    RequirementFieldType, RequirementValue,
+};
+use crate::services::{NewProjectEvent, NotificationService, ProjectEventService, ProjectService, TransitionError};
+use crate::utils::SecretKey;
+
+/// Uploaded file answering a file field
+#[derive(Debug, Clone)]
+pub struct RequirementFile {
+    pub file_name: String,
+    pub file_type: String,
+    pub file_size: i64,
+    pub storage_key: String,
+}
+
+pub struct RequirementService;
+
+impl RequirementService {
+    /// Answers of a project
+    pub async fn list_answers(db: &Database, project_id: Uuid) -> Result<Vec<ProjectRequirementAnswer>, sqlx::Error> {
+        sqlx::query_as::<_, ProjectRequirementAnswer>(
+            "SELECT * FROM project_requirement_answers WHERE project_id = $1 ORDER BY created_at",
+        )
+        .bind(project_id)
+        .fetch_all(&db.pool)
+        .await
+    }
+
+    /// Answer of a single field
+    pub async fn get_answer(
+        db: &Database,
+        project_id: Uuid,
+        field_id: &str,
+    ) -> Result<Option<ProjectRequirementAnswer>, sqlx::Error> {
+        sqlx::query_as::<_, ProjectRequirementAnswer>(
+            "SELECT * FROM project_requirement_answers WHERE project_id = $1 AND field_id = $2",
+        )
+        .bind(project_id)
+        .bind(field_id)
+        .fetch_optional(&db.pool)
+        .await
+    }
+
+    /// Required fields without an answer
+    pub fn missing_fields(form: &[RequirementField], answers: &[ProjectRequirementAnswer]) -> Vec<String> {
+        form.iter()
+            .filter(|field| field.required)
+            .filter(|field| {
+                !answers
+                    .iter()
+                    .any(|a| a.field_id == field.id && a.is_answered())
+            })
+            .map(|field| field.id.clone())
+            .collect()
+    }
+
+    /// Store checked answers (replacing earlier answers to the same fields). Secret
+    /// answers are encrypted with `secret_key`.
+    pub async fn save_answers_tx(
+        conn: &mut PgConnection,
+        secret_key: &SecretKey,
+        project_id: Uuid,
+        answered_by: Uuid,
+        answers: &[(RequirementField, RequirementValue)],
+    ) -> Result<(), sqlx::Error> {
+        for (field, value) in answers {
+            let (value, encrypted_value) = if field.field_type == RequirementFieldType::Secret {
+                let plaintext = serde_json::to_vec(value).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+                (None, Some(secret_key.encrypt(&plaintext)))
+            } else {
+                (Some(sqlx::types::Json(value)), None)
+            };
+
+            sqlx::query(
+                r#"
+                INSERT INTO project_requirement_answers (project_id, field_id, field_type, value, encrypted_value, answered_by)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT (project_id, field_id) DO UPDATE
+                SET value = EXCLUDED.value, encrypted_value = EXCLUDED.encrypted_value,
+                    answered_by = EXCLUDED.answered_by, updated_at = NOW()
+                "#,
+            )
+            .bind(project_id)
+            .bind(&field.id)
+            .bind(field.field_type.as_str())
+            .bind(value)
+            .bind(encrypted_value)
+            .bind(answered_by)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Submit answers of the client. Completing the form starts the work on a
+    /// project that waits for its requirements.
+    pub async fn submit(
+        db: &Database,
+        secret_key: &SecretKey,
+        project_id: Uuid,
+        client_id: Uuid,
+        answers: &[(RequirementField, RequirementValue)],
+    ) -> Result<Project, TransitionError> {
+        let mut tx = db.pool.begin().await?;
+        Self::save_answers_tx(&mut tx, secret_key, project_id, client_id, answers).await?;
+        let project = Self::after_answer(&mut tx, project_id, client_id).await?;
+        tx.commit().await?;
+
+        Ok(project)
+    }
+
+    /// Store an uploaded file as answer; returns the storage key of the replaced file
+    pub async fn submit_file(
+        db: &Database,
+        project_id: Uuid,
+        client_id: Uuid,
+        field: &RequirementField,
+        file: RequirementFile,
+    ) -> Result<(Project, Option<String>), TransitionError> {
+        let mut tx = db.pool.begin().await?;
+
+        let previous: Option<String> = sqlx::query_scalar(
+            "SELECT storage_key FROM project_requirement_answers WHERE project_id = $1 AND field_id = $2 FOR UPDATE",
+        )
+        .bind(project_id)
+        .bind(&field.id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .flatten();
+
+        sqlx::query(
+            r#"
+            INSERT INTO project_requirement_answers
+                (project_id, field_id, field_type, file_name, file_type, file_size, storage_key, answered_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (project_id, field_id) DO UPDATE
+            SET file_name = EXCLUDED.file_name, file_type = EXCLUDED.file_type, file_size = EXCLUDED.file_size,
+                storage_key = EXCLUDED.storage_key, answered_by = EXCLUDED.answered_by, updated_at = NOW()
+            "#,
+        )
+        .bind(project_id)
+        .bind(&field.id)
+        .bind(RequirementFieldType::File.as_str())
+        .bind(&file.file_name)
+        .bind(&file.file_type)
+        .bind(file.file_size)
+        .bind(&file.storage_key)
+        .bind(client_id)
+        .execute(&mut *tx)
+        .await?;
+
+        let project = Self::after_answer(&mut tx, project_id, client_id).await?;
+        tx.commit().await?;
+
+        Ok((project, previous))
+    }
+
+    /// Record the submission and, once every required field is answered, mark the
+    /// form complete and start the work
+    async fn after_answer(conn: &mut PgConnection, project_id: Uuid, client_id: Uuid) -> Result<Project, TransitionError> {
+        ProjectEventService::record(
+            &mut *conn,
+            NewProjectEvent::new(project_id, ProjectEventType::RequirementsSubmitted, ProjectActor::Client, Some(client_id)),
+        )
+        .await?;
+
+        let completed = Self::refresh_completion_tx(&mut *conn, project_id).await?;
+        let project = sqlx::query_as::<_, Project>("SELECT * FROM projects WHERE id = $1")
+            .bind(project_id)
+            .fetch_one(&mut *conn)
+            .await?;
+
+        if !completed {
+            return Ok(project);
+        }
+
+        NotificationService::create(
+            &mut *conn,
+            project.expert_id,
+            "requirements_completed",
+            "Anforderungen vollständig",
+            &format!("Der Kunde hat die Anforderungen für \"{}\" vollständig ausgefüllt.", project.title),
+            Some(serde_json::json!({ "projectId": project_id })),
+        )
+        .await?;
+
+        if project.status == ProjectStatus::AwaitingRequirements {
+            return ProjectService::transition_tx(conn, project_id, ProjectStatus::InProgress, ProjectActor::System, None, None)
+                .await;
+        }
+
+        Ok(project)
+    }
+
+    /// Mark the form of a project complete if all required fields are answered;
+    /// returns whether it was completed just now
+    pub async fn refresh_completion_tx(conn: &mut PgConnection, project_id: Uuid) -> Result<bool, sqlx::Error> {
+        let project = sqlx::query_as::<_, Project>("SELECT * FROM projects WHERE id = $1 FOR UPDATE")
+            .bind(project_id)
+            .fetch_one(&mut *conn)
+            .await?;
+        if project.requirements_completed_at.is_some() {
+            return Ok(false);
+        }
+
+        let answers = sqlx::query_as::<_, ProjectRequirementAnswer>(
+            "SELECT * FROM project_requirement_answers WHERE project_id = $1",
+        )
+        .bind(project_id)
+        .fetch_all(&mut *conn)
+        .await?;
+        if !Self::missing_fields(&project.requirements_form, &answers).is_empty() {
+            return Ok(false);
+        }
+
+        sqlx::query("UPDATE projects SET requirements_completed_at = NOW(), updated_at = NOW() WHERE id = $1")
+            .bind(project_id)
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(true)
+    }
+
+    /// Delete secret answers of a closed project
+    pub async fn purge_secrets_tx(conn: &mut PgConnection, project_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE project_requirement_answers
+            SET value = NULL, encrypted_value = NULL, purged_at = NOW(), updated_at = NOW()
+            WHERE project_id = $1 AND field_type = 'secret' AND purged_at IS NULL
+            "#,
+        )
+        .bind(project_id)
+        .execute(conn)
+        .await?;
+
+        Ok(())
+    }
+}
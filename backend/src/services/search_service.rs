@@ -28,6 +28,9 @@ pub struct ExpertSearchDocument {
     pub rating_average: f32,
     pub country: String,
     pub is_verified: bool,
+    pub response_time_hours: Option<i16>,
+    pub completion_rate: Option<f32>,
+    pub on_time_delivery_rate: Option<f32>,
 }
 
 #[cfg(feature = "search")]
@@ -60,10 +63,22 @@ impl SearchService {
             .set_searchable_attributes(&["headline", "bio", "skills", "tools", "industries"])
             .await?;
         experts_index
-            .set_filterable_attributes(&["country", "is_verified", "hourly_rate", "rating_average", "skills", "tools"])
+            .set_filterable_attributes(&[
+                "country", "is_verified", "hourly_rate", "rating_average", "skills", "tools",
+                "response_time_hours", "completion_rate",
+            ])
             .await?;
         experts_index
-            .set_sortable_attributes(&["hourly_rate", "rating_average"])
+            .set_sortable_attributes(&[
+                "hourly_rate", "rating_average", "response_time_hours", "completion_rate", "on_time_delivery_rate",
+            ])
+            .await?;
+        // Reliable experts rank higher among equally relevant matches
+        experts_index
+            .set_ranking_rules(&[
+                "words", "typo", "proximity", "attribute", "sort", "exactness",
+                "rating_average:desc", "completion_rate:desc", "on_time_delivery_rate:desc",
+            ])
             .await?;
 
         // Create services index
@@ -95,6 +110,9 @@ impl SearchService {
             rating_average: expert.rating_average,
             country: "ch".to_string(), // TODO: Get from user
             is_verified: expert.is_verified,
+            response_time_hours: expert.response_time_hours,
+            completion_rate: expert.completion_rate,
+            on_time_delivery_rate: expert.on_time_delivery_rate,
         };
 
         self.client
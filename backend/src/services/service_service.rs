@@ -1,7 +1,13 @@
 use uuid::Uuid;
 
 use crate::db::Database;
-use crate::models::{Service, ServicePackage, CreateServiceRequest, CreateServicePackageRequest, ServiceSearchFilters, PaginationParams};
+use crate::models::{
+    Service, ServiceAddon, ServicePackage, CreateServiceAddonRequest, CreateServiceRequest, CreateServicePackageRequest,
+    ServiceSearchFilters, PaginationParams,
+};
+
+/// Maximum number of add-ons per service
+pub const MAX_SERVICE_ADDONS: i64 = 20;
 
 pub struct ServiceService;
 
@@ -19,13 +25,13 @@ impl ServiceService {
             INSERT INTO services (
                 id, expert_id, category_id, title, slug, description, short_description,
                 pricing_type, price, currency, delivery_time_days, revisions_included,
-                features, requirements, tags, images, is_active, is_featured,
+                features, requirements, requirements_form, tags, images, is_active, is_featured,
                 view_count, order_count, rating_average, rating_count,
-                created_at, updated_at
+                cancellation_policy_id, agency_id, created_at, updated_at
             )
             VALUES (
-                $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15,
-                ARRAY[]::text[], true, false, 0, 0, 0.0, 0, NOW(), NOW()
+                $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $17, $15,
+                ARRAY[]::text[], true, false, 0, 0, 0.0, 0, $16, $18, NOW(), NOW()
             )
             RETURNING *
             "#,
@@ -45,6 +51,9 @@ impl ServiceService {
         .bind(&req.features)
         .bind(&req.requirements)
         .bind(&req.tags.unwrap_or_default())
+        .bind(req.cancellation_policy_id)
+        .bind(sqlx::types::Json(req.requirements_form.unwrap_or_default()))
+        .bind(req.agency_id)
         .fetch_one(&db.pool)
         .await?;
 
@@ -108,6 +117,39 @@ impl ServiceService {
         Ok((services, total))
     }
 
+    /// Active services sold under an agency
+    pub async fn get_by_agency(
+        db: &Database,
+        agency_id: Uuid,
+        page: u32,
+        per_page: u32,
+    ) -> Result<(Vec<Service>, i64), sqlx::Error> {
+        let offset = (page.saturating_sub(1)) * per_page;
+
+        let services: Vec<Service> = sqlx::query_as(
+            r#"
+            SELECT * FROM services
+            WHERE agency_id = $1 AND is_active = true
+            ORDER BY created_at DESC
+            LIMIT $2 OFFSET $3
+            "#,
+        )
+        .bind(agency_id)
+        .bind(per_page as i64)
+        .bind(offset as i64)
+        .fetch_all(&db.pool)
+        .await?;
+
+        let total: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM services WHERE agency_id = $1 AND is_active = true"
+        )
+        .bind(agency_id)
+        .fetch_one(&db.pool)
+        .await?;
+
+        Ok((services, total))
+    }
+
     /// Get services by category
     pub async fn get_by_category(
         db: &Database,
@@ -253,6 +295,9 @@ impl ServiceService {
                 features = $11,
                 requirements = $12,
                 tags = $13,
+                cancellation_policy_id = $14,
+                requirements_form = $15,
+                agency_id = $16,
                 updated_at = NOW()
             WHERE id = $1
             RETURNING *
@@ -271,6 +316,9 @@ impl ServiceService {
         .bind(&req.features)
         .bind(&req.requirements)
         .bind(&req.tags.unwrap_or_default())
+        .bind(req.cancellation_policy_id)
+        .bind(sqlx::types::Json(req.requirements_form.unwrap_or_default()))
+        .bind(req.agency_id)
         .fetch_one(&db.pool)
         .await?;
 
@@ -421,5 +469,130 @@ impl ServiceService {
             .await?;
         Ok(())
     }
-}
 
+    /// Add-ons of a service (only active ones unless `include_inactive`)
+    pub async fn get_addons(
+        db: &Database,
+        service_id: Uuid,
+        include_inactive: bool,
+    ) -> Result<Vec<ServiceAddon>, sqlx::Error> {
+        sqlx::query_as::<_, ServiceAddon>(
+            r#"
+            SELECT * FROM service_addons
+            WHERE service_id = $1 AND ($2 OR is_active)
+            ORDER BY sort_order, created_at
+            "#,
+        )
+        .bind(service_id)
+        .bind(include_inactive)
+        .fetch_all(&db.pool)
+        .await
+    }
+
+    /// Number of add-ons of a service
+    pub async fn count_addons(db: &Database, service_id: Uuid) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar("SELECT COUNT(*) FROM service_addons WHERE service_id = $1")
+            .bind(service_id)
+            .fetch_one(&db.pool)
+            .await
+    }
+
+    /// Create an add-on for a service
+    pub async fn create_addon(
+        db: &Database,
+        service_id: Uuid,
+        req: &CreateServiceAddonRequest,
+    ) -> Result<ServiceAddon, sqlx::Error> {
+        sqlx::query_as::<_, ServiceAddon>(
+            r#"
+            INSERT INTO service_addons (
+                service_id, title, description, price, extra_days, extra_revisions,
+                max_quantity, is_active, sort_order
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, COALESCE($7, 1), COALESCE($8, TRUE), COALESCE($9, 0))
+            RETURNING *
+            "#,
+        )
+        .bind(service_id)
+        .bind(&req.title)
+        .bind(&req.description)
+        .bind(req.price)
+        .bind(req.extra_days)
+        .bind(req.extra_revisions)
+        .bind(req.max_quantity)
+        .bind(req.is_active)
+        .bind(req.sort_order)
+        .fetch_one(&db.pool)
+        .await
+    }
+
+    /// Update an add-on of a service; `None` if it does not belong to the service
+    pub async fn update_addon(
+        db: &Database,
+        service_id: Uuid,
+        addon_id: Uuid,
+        req: &CreateServiceAddonRequest,
+    ) -> Result<Option<ServiceAddon>, sqlx::Error> {
+        sqlx::query_as::<_, ServiceAddon>(
+            r#"
+            UPDATE service_addons SET
+                title = $3,
+                description = $4,
+                price = $5,
+                extra_days = $6,
+                extra_revisions = $7,
+                max_quantity = COALESCE($8, max_quantity),
+                is_active = COALESCE($9, is_active),
+                sort_order = COALESCE($10, sort_order),
+                updated_at = NOW()
+            WHERE id = $1 AND service_id = $2
+            RETURNING *
+            "#,
+        )
+        .bind(addon_id)
+        .bind(service_id)
+        .bind(&req.title)
+        .bind(&req.description)
+        .bind(req.price)
+        .bind(req.extra_days)
+        .bind(req.extra_revisions)
+        .bind(req.max_quantity)
+        .bind(req.is_active)
+        .bind(req.sort_order)
+        .fetch_optional(&db.pool)
+        .await
+    }
+
+    /// Delete an add-on; projects that ordered it keep their priced copy
+    pub async fn delete_addon(db: &Database, service_id: Uuid, addon_id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM service_addons WHERE id = $1 AND service_id = $2")
+            .bind(addon_id)
+            .bind(service_id)
+            .execute(&db.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Active service offered by the expert (user id), directly or through their agency
+    pub async fn get_offered(
+        db: &Database,
+        service_id: Uuid,
+        expert_user_id: Uuid,
+    ) -> Result<Option<Service>, sqlx::Error> {
+        sqlx::query_as::<_, Service>(
+            r#"
+            SELECT s.* FROM services s
+            JOIN expert_profiles e ON e.id = s.expert_id
+            WHERE s.id = $1 AND s.is_active = TRUE
+              AND (e.user_id = $2 OR EXISTS (
+                  SELECT 1 FROM agency_members m WHERE m.agency_id = s.agency_id AND m.user_id = $2
+              ))
+            "#,
+        )
+        .bind(service_id)
+        .bind(expert_user_id)
+        .fetch_optional(&db.pool)
+        .await
+    }
+}
@@ -99,6 +99,40 @@ impl StorageService {
         Ok(url)
     }
 
+    /// Storage key for a private project deliverable
+    pub fn deliverable_key(project_id: Uuid, filename: &str) -> String {
+        let extension = filename.rsplit('.').next().unwrap_or("bin");
+        format!("deliverables/{}/{}.{}", project_id, Uuid::new_v4(), extension)
+    }
+
+    /// Upload a file under a fixed key (no public URL is derived)
+    pub async fn put_object(&self, key: &str, data: &[u8], content_type: &str) -> Result<(), S3Error> {
+        self.bucket
+            .put_object_with_content_type(key, data, content_type)
+            .await?;
+        Ok(())
+    }
+
+    /// Presigned PUT URL for a fixed key
+    pub async fn presign_upload(&self, key: &str, expires_in_secs: u32) -> Result<String, S3Error> {
+        self.bucket.presign_put(key, expires_in_secs, None, None).await
+    }
+
+    /// Presigned GET URL for a private object
+    pub async fn presign_download(&self, key: &str, expires_in_secs: u32) -> Result<String, S3Error> {
+        self.bucket.presign_get(key, expires_in_secs, None).await
+    }
+
+    /// Size of an uploaded object, `None` if it does not exist
+    pub async fn object_size(&self, key: &str) -> Result<Option<i64>, S3Error> {
+        match self.bucket.head_object(key).await {
+            Ok((head, 200)) => Ok(head.content_length),
+            Ok(_) => Ok(None),
+            Err(S3Error::HttpFailWithBody(404, _)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
     /// Upload avatar
     pub async fn upload_avatar(
         &self,
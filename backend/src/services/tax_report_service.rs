@@ -0,0 +1,488 @@
+//! Quarterly VAT reporting for platform revenue (platform fees and platform-issued invoices)
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+
+use crate::models::{
+    CustomerType, OssLine, TaxReport, TaxReportKind, TaxReportLine, TaxableItem, VatReturnField,
+};
+use crate::utils::{calculate_net_from_gross, vat_rate_for_supply, vat_treatment, VatTreatment};
+
+/// Aggregation key: source, customer country, customer type, treatment, currency and VAT rate
+type GroupKey = (String, String, CustomerType, VatTreatment, String, Decimal);
+
+pub struct TaxReportService;
+
+impl TaxReportService {
+    /// First day of the quarter and first day of the following quarter
+    pub fn quarter_bounds(year: i32, quarter: u32) -> Option<(NaiveDate, NaiveDate)> {
+        if !(1..=4).contains(&quarter) {
+            return None;
+        }
+        let start = NaiveDate::from_ymd_opt(year, (quarter - 1) * 3 + 1, 1)?;
+        let end = if quarter == 4 {
+            NaiveDate::from_ymd_opt(year + 1, 1, 1)?
+        } else {
+            NaiveDate::from_ymd_opt(year, quarter * 3 + 1, 1)?
+        };
+        Some((start, end))
+    }
+
+    /// Platform revenue items in `[start, end)`
+    pub async fn get_taxable_items(
+        pool: &PgPool,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<TaxableItem>, sqlx::Error> {
+        let start: DateTime<Utc> = start.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+        let end: DateTime<Utc> = end.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+
+        // Platform fees are charged to the expert and retained from the payment
+        sqlx::query_as::<_, TaxableItem>(
+            r#"
+            SELECT 'platform_fee' AS source, u.country::text AS customer_country, u.vat_id AS customer_vat_id,
+                   UPPER(p.currency) AS currency, p.platform_fee::BIGINT AS amount, NULL::BIGINT AS vat_amount
+            FROM payments p
+            JOIN users u ON u.id = p.payee_id
+            WHERE p.status IN ('succeeded', 'partially_refunded', 'refunded')
+              AND p.platform_fee > 0
+              AND p.paid_at >= $1 AND p.paid_at < $2
+            UNION ALL
+            SELECT 'platform_sale', i.recipient_details->>'country', i.recipient_details->>'vatId',
+                   UPPER(i.currency), i.subtotal::BIGINT, COALESCE(i.tax_amount, 0)::BIGINT
+            FROM invoices i
+            WHERE i.issuer_id IS NULL
+              AND i.status NOT IN ('draft', 'void')
+              AND i.created_at >= $1 AND i.created_at < $2
+            "#,
+        )
+        .bind(start)
+        .bind(end)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Aggregate items by source, customer country, customer type, treatment, currency and rate
+    pub fn aggregate(seller_country: &str, items: &[TaxableItem]) -> Vec<TaxReportLine> {
+        let mut groups: BTreeMap<GroupKey, (i64, i64, i64)> = BTreeMap::new();
+
+        for item in items {
+            let country = item.customer_country.as_deref().unwrap_or("").to_uppercase();
+            let has_vat_id = item.customer_vat_id.as_deref().is_some_and(|v| !v.trim().is_empty());
+            let customer_type = if has_vat_id { CustomerType::Business } else { CustomerType::Consumer };
+            let treatment = vat_treatment(seller_country, &country, has_vat_id);
+            let rate = vat_rate_for_supply(seller_country, &country, has_vat_id);
+
+            let (net, vat) = match item.vat_amount {
+                Some(vat) => (item.amount, vat),
+                None => {
+                    let calc = calculate_net_from_gross(Decimal::from(item.amount), rate);
+                    let net = calc.net_amount.round().to_i64().unwrap_or(item.amount);
+                    (net, item.amount - net)
+                }
+            };
+
+            let entry = groups
+                .entry((item.source.clone(), country, customer_type, treatment, item.currency.clone(), rate.rate()))
+                .or_insert((0, 0, 0));
+            entry.0 += net;
+            entry.1 += vat;
+            entry.2 += 1;
+        }
+
+        groups
+            .into_iter()
+            .map(
+                |((source, customer_country, customer_type, treatment, currency, vat_rate), (net, vat, count))| {
+                    TaxReportLine {
+                        source,
+                        customer_country,
+                        customer_type,
+                        treatment,
+                        currency,
+                        vat_rate,
+                        net_amount: net,
+                        vat_amount: vat,
+                        transactions: count,
+                    }
+                },
+            )
+            .collect()
+    }
+
+    /// Build the full report from aggregated lines
+    pub fn build(seller_country: &str, year: i32, quarter: u32, items: &[TaxableItem]) -> Option<TaxReport> {
+        let (period_start, next_start) = Self::quarter_bounds(year, quarter)?;
+        let seller_country = seller_country.to_uppercase();
+        let lines = Self::aggregate(&seller_country, items);
+
+        Some(TaxReport {
+            year,
+            quarter,
+            period_start,
+            period_end: next_start.pred_opt()?,
+            seller_country: seller_country.clone(),
+            domestic: Self::domestic_return(&seller_country, &lines),
+            oss: Self::oss_lines(&lines),
+            estv: Self::estv_statement(&seller_country, &lines),
+            lines,
+        })
+    }
+
+    /// German (UStVA) or Austrian (UVA) return fields
+    fn domestic_return(seller_country: &str, lines: &[TaxReportLine]) -> Vec<VatReturnField> {
+        let codes: [(&str, &str, &[VatTreatment]); 3] = match seller_country {
+            "DE" => [
+                ("81", "Steuerpflichtige Umsätze zum Steuersatz von 19 %", &[VatTreatment::Domestic]),
+                ("21", "Nicht steuerbare sonstige Leistungen (EU, Reverse Charge)", &[VatTreatment::ReverseCharge]),
+                (
+                    "45",
+                    "Übrige nicht steuerbare Umsätze (Leistungsort nicht im Inland)",
+                    &[VatTreatment::SwissRegistration, VatTreatment::OutOfScope],
+                ),
+            ],
+            "AT" => [
+                ("022", "Umsätze zum Normalsteuersatz von 20 %", &[VatTreatment::Domestic]),
+                ("ZM", "Sonstige Leistungen an Unternehmer in der EU (Zusammenfassende Meldung)", &[VatTreatment::ReverseCharge]),
+                (
+                    "non_taxable",
+                    "Nicht steuerbare Umsätze (Leistungsort nicht im Inland)",
+                    &[VatTreatment::SwissRegistration, VatTreatment::OutOfScope],
+                ),
+            ],
+            _ => return Vec::new(),
+        };
+
+        codes
+            .iter()
+            .flat_map(|(code, label, treatments)| {
+                Self::sum_by_currency(lines, |line| treatments.contains(&line.treatment))
+                    .into_iter()
+                    .map(move |(currency, (net, vat))| VatReturnField {
+                        code: code.to_string(),
+                        label: label.to_string(),
+                        currency,
+                        net_amount: net,
+                        vat_amount: vat,
+                    })
+            })
+            .collect()
+    }
+
+    /// OSS lines per member state of consumption
+    fn oss_lines(lines: &[TaxReportLine]) -> Vec<OssLine> {
+        let mut groups: BTreeMap<(String, Decimal, String), (i64, i64)> = BTreeMap::new();
+        for line in lines.iter().filter(|l| l.treatment == VatTreatment::Oss) {
+            let entry = groups
+                .entry((line.customer_country.clone(), line.vat_rate, line.currency.clone()))
+                .or_insert((0, 0));
+            entry.0 += line.net_amount;
+            entry.1 += line.vat_amount;
+        }
+
+        groups
+            .into_iter()
+            .map(|((member_state, vat_rate, currency), (taxable, vat))| OssLine {
+                member_state,
+                vat_rate,
+                currency,
+                taxable_amount: taxable,
+                vat_amount: vat,
+            })
+            .collect()
+    }
+
+    /// Swiss ESTV statement (effective method). For a Swiss seller all revenue is declared,
+    /// otherwise only supplies to Swiss consumers under the Swiss registration.
+    fn estv_statement(seller_country: &str, lines: &[TaxReportLine]) -> Vec<VatReturnField> {
+        let swiss_seller = seller_country == "CH";
+        let is_taxable = |line: &TaxReportLine| {
+            if swiss_seller {
+                line.treatment == VatTreatment::Domestic
+            } else {
+                line.treatment == VatTreatment::SwissRegistration
+            }
+        };
+
+        let taxable = Self::sum_by_currency(lines, is_taxable);
+        let abroad = if swiss_seller {
+            Self::sum_by_currency(lines, |line| !is_taxable(line))
+        } else {
+            BTreeMap::new()
+        };
+
+        let mut currencies: Vec<&String> = taxable.keys().chain(abroad.keys()).collect();
+        currencies.sort();
+        currencies.dedup();
+
+        let mut fields = Vec::new();
+        for currency in currencies {
+            let (taxable_net, taxable_vat) = taxable.get(currency).copied().unwrap_or((0, 0));
+            let (abroad_net, _) = abroad.get(currency).copied().unwrap_or((0, 0));
+            let field = |code: &str, label: &str, net: i64, vat: i64| VatReturnField {
+                code: code.to_string(),
+                label: label.to_string(),
+                currency: currency.clone(),
+                net_amount: net,
+                vat_amount: vat,
+            };
+
+            fields.push(field("200", "Total der vereinbarten Entgelte", taxable_net + abroad_net, 0));
+            fields.push(field("221", "Leistungen im Ausland", abroad_net, 0));
+            fields.push(field("289", "Total Abzüge", abroad_net, 0));
+            fields.push(field("299", "Steuerbares Gesamtentgelt", taxable_net, 0));
+            fields.push(field("303", "Leistungen zum Normalsatz 8,1 %", taxable_net, taxable_vat));
+        }
+
+        fields
+    }
+
+    fn sum_by_currency(
+        lines: &[TaxReportLine],
+        filter: impl Fn(&TaxReportLine) -> bool,
+    ) -> BTreeMap<String, (i64, i64)> {
+        let mut sums: BTreeMap<String, (i64, i64)> = BTreeMap::new();
+        for line in lines.iter().filter(|line| filter(line)) {
+            let entry = sums.entry(line.currency.clone()).or_insert((0, 0));
+            entry.0 += line.net_amount;
+            entry.1 += line.vat_amount;
+        }
+        sums
+    }
+
+    /// Render a report section as CSV (semicolon separated, amounts in currency units)
+    pub fn to_csv(report: &TaxReport, kind: TaxReportKind) -> String {
+        let mut out = String::new();
+        match kind {
+            TaxReportKind::Lines => {
+                out.push_str("source;customer_country;customer_type;treatment;currency;vat_rate;net_amount;vat_amount;transactions\n");
+                for line in &report.lines {
+                    out.push_str(&format!(
+                        "{};{};{};{};{};{};{};{};{}\n",
+                        line.source,
+                        line.customer_country,
+                        enum_str(&line.customer_type),
+                        enum_str(&line.treatment),
+                        line.currency,
+                        line.vat_rate,
+                        format_cents(line.net_amount),
+                        format_cents(line.vat_amount),
+                        line.transactions
+                    ));
+                }
+            }
+            TaxReportKind::Domestic | TaxReportKind::Estv => {
+                out.push_str("code;label;currency;net_amount;vat_amount\n");
+                let fields = if kind == TaxReportKind::Domestic { &report.domestic } else { &report.estv };
+                for field in fields {
+                    out.push_str(&format!(
+                        "{};{};{};{};{}\n",
+                        field.code,
+                        csv_escape(&field.label),
+                        field.currency,
+                        format_cents(field.net_amount),
+                        format_cents(field.vat_amount)
+                    ));
+                }
+            }
+            TaxReportKind::Oss => {
+                out.push_str("member_state;vat_rate;currency;taxable_amount;vat_amount\n");
+                for line in &report.oss {
+                    out.push_str(&format!(
+                        "{};{};{};{};{}\n",
+                        line.member_state,
+                        line.vat_rate,
+                        line.currency,
+                        format_cents(line.taxable_amount),
+                        format_cents(line.vat_amount)
+                    ));
+                }
+            }
+        }
+        out
+    }
+
+    /// Render a report section as XML
+    pub fn to_xml(report: &TaxReport, kind: TaxReportKind) -> String {
+        let (name, body) = match kind {
+            TaxReportKind::Lines => (
+                "lines",
+                report
+                    .lines
+                    .iter()
+                    .map(|line| {
+                        format!(
+                            "    <Line source=\"{}\" customerCountry=\"{}\" customerType=\"{}\" treatment=\"{}\" currency=\"{}\" vatRate=\"{}\" netAmount=\"{}\" vatAmount=\"{}\" transactions=\"{}\"/>\n",
+                            xml_escape(&line.source),
+                            xml_escape(&line.customer_country),
+                            enum_str(&line.customer_type),
+                            enum_str(&line.treatment),
+                            xml_escape(&line.currency),
+                            line.vat_rate,
+                            format_cents(line.net_amount),
+                            format_cents(line.vat_amount),
+                            line.transactions
+                        )
+                    })
+                    .collect::<String>(),
+            ),
+            TaxReportKind::Domestic | TaxReportKind::Estv => {
+                let fields = if kind == TaxReportKind::Domestic { &report.domestic } else { &report.estv };
+                (
+                    if kind == TaxReportKind::Domestic { "domestic" } else { "estv" },
+                    fields
+                        .iter()
+                        .map(|field| {
+                            format!(
+                                "    <Field code=\"{}\" currency=\"{}\" netAmount=\"{}\" vatAmount=\"{}\">{}</Field>\n",
+                                xml_escape(&field.code),
+                                xml_escape(&field.currency),
+                                format_cents(field.net_amount),
+                                format_cents(field.vat_amount),
+                                xml_escape(&field.label)
+                            )
+                        })
+                        .collect::<String>(),
+                )
+            }
+            TaxReportKind::Oss => (
+                "oss",
+                report
+                    .oss
+                    .iter()
+                    .map(|line| {
+                        format!(
+                            "    <Supply memberState=\"{}\" vatRate=\"{}\" currency=\"{}\" taxableAmount=\"{}\" vatAmount=\"{}\"/>\n",
+                            xml_escape(&line.member_state),
+                            line.vat_rate,
+                            xml_escape(&line.currency),
+                            format_cents(line.taxable_amount),
+                            format_cents(line.vat_amount)
+                        )
+                    })
+                    .collect::<String>(),
+            ),
+        };
+
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<TaxReport type=\"{}\" year=\"{}\" quarter=\"{}\" periodStart=\"{}\" periodEnd=\"{}\" sellerCountry=\"{}\">\n{}</TaxReport>\n",
+            name,
+            report.year,
+            report.quarter,
+            report.period_start,
+            report.period_end,
+            xml_escape(&report.seller_country),
+            body
+        )
+    }
+}
+
+fn format_cents(amount: i64) -> String {
+    let sign = if amount < 0 { "-" } else { "" };
+    format!("{}{}.{:02}", sign, amount.abs() / 100, amount.abs() % 100)
+}
+
+fn enum_str<T: serde::Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn csv_escape(value: &str) -> String {
+    if value.contains([';', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn fee(country: &str, vat_id: Option<&str>, amount: i64) -> TaxableItem {
+        TaxableItem {
+            source: "platform_fee".to_string(),
+            customer_country: Some(country.to_string()),
+            customer_vat_id: vat_id.map(str::to_string),
+            currency: "CHF".to_string(),
+            amount,
+            vat_amount: None,
+        }
+    }
+
+    #[test]
+    fn test_quarter_bounds() {
+        let (start, end) = TaxReportService::quarter_bounds(2024, 4).unwrap();
+        assert_eq!(start, NaiveDate::from_ymd_opt(2024, 10, 1).unwrap());
+        assert_eq!(end, NaiveDate::from_ymd_opt(2025, 1, 1).unwrap());
+        assert!(TaxReportService::quarter_bounds(2024, 5).is_none());
+    }
+
+    #[test]
+    fn test_aggregate_swiss_seller() {
+        let items = vec![
+            fee("ch", None, 10_810),
+            fee("ch", None, 10_810),
+            fee("de", Some("DE123456789"), 50_00),
+            fee("at", None, 12_000),
+        ];
+        let report = TaxReportService::build("CH", 2024, 1, &items).unwrap();
+
+        let domestic = report.lines.iter().find(|l| l.treatment == VatTreatment::Domestic).unwrap();
+        assert_eq!(domestic.net_amount, 20_000);
+        assert_eq!(domestic.vat_amount, 16_20);
+        assert_eq!(domestic.transactions, 2);
+
+        assert_eq!(report.oss.len(), 1);
+        assert_eq!(report.oss[0].member_state, "AT");
+        assert_eq!(report.oss[0].vat_rate, dec!(20.0));
+        assert_eq!(report.oss[0].taxable_amount, 10_000);
+
+        assert!(report.domestic.is_empty());
+        let total = report.estv.iter().find(|f| f.code == "200").unwrap();
+        assert_eq!(total.net_amount, 20_000 + 50_00 + 10_000);
+        let standard = report.estv.iter().find(|f| f.code == "303").unwrap();
+        assert_eq!(standard.vat_amount, 16_20);
+    }
+
+    #[test]
+    fn test_german_seller_return() {
+        let items = vec![fee("de", None, 11_900), fee("at", Some("ATU12345678"), 40_00), fee("ch", None, 10_810)];
+        let report = TaxReportService::build("DE", 2024, 2, &items).unwrap();
+
+        let kz81 = report.domestic.iter().find(|f| f.code == "81").unwrap();
+        assert_eq!((kz81.net_amount, kz81.vat_amount), (10_000, 19_00));
+        let kz21 = report.domestic.iter().find(|f| f.code == "21").unwrap();
+        assert_eq!(kz21.net_amount, 40_00);
+
+        // Swiss consumers are declared under the Swiss registration
+        let estv = report.estv.iter().find(|f| f.code == "303").unwrap();
+        assert_eq!((estv.net_amount, estv.vat_amount), (10_000, 8_10));
+    }
+
+    #[test]
+    fn test_csv_export() {
+        let report = TaxReportService::build("CH", 2024, 1, &[fee("at", None, 12_000)]).unwrap();
+        let csv = TaxReportService::to_csv(&report, TaxReportKind::Oss);
+        assert_eq!(
+            csv,
+            "member_state;vat_rate;currency;taxable_amount;vat_amount\nAT;20.0;CHF;100.00;20.00\n"
+        );
+        assert!(TaxReportService::to_xml(&report, TaxReportKind::Oss).contains("memberState=\"AT\""));
+    }
+}
@@ -0,0 +1,281 @@
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::models::{PaymentStatus, Project, ProjectEventType, ProjectTip};
+use crate::services::{MessageService, NewProjectEvent, NotificationService, PaymentService, ProjectEventService};
+
+pub struct TipService;
+
+impl TipService {
+    /// Amount for a preset percentage of the project price (in cents)
+    pub fn preset_amount(project_price: i32, percent: i16) -> i32 {
+        ((project_price as i64 * percent as i64) / 100) as i32
+    }
+
+    /// Calculate platform fee and expert net amount for a tip
+    pub fn calculate_fee(amount: i32, fee_rate: f64) -> (i32, i32) {
+        let platform_fee = ((amount as f64) * fee_rate).round() as i32;
+        (platform_fee, amount - platform_fee)
+    }
+
+    /// Create a pending tip together with its pending payment record
+    pub async fn create(
+        db: &Database,
+        project: &Project,
+        amount: i32,
+        fee_rate: f64,
+        preset_percent: Option<i16>,
+        message: Option<&str>,
+    ) -> Result<ProjectTip, sqlx::Error> {
+        let (platform_fee, net_amount) = Self::calculate_fee(amount, fee_rate);
+        let currency = format!("{:?}", project.currency);
+        let tip_id = Uuid::new_v4();
+
+        let mut tx = db.pool.begin().await?;
+
+        let payment_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO payments (project_id, payer_id, payee_id, amount, currency, platform_fee, net_amount, description, metadata)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING id
+            "#,
+        )
+        .bind(project.id)
+        .bind(project.client_id)
+        .bind(project.expert_id)
+        .bind(amount)
+        .bind(&currency)
+        .bind(platform_fee)
+        .bind(net_amount)
+        .bind(format!("Tip - {}", project.title))
+        .bind(sqlx::types::Json(serde_json::json!({
+            "kind": "tip",
+            "tip_id": tip_id,
+        })))
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let tip = sqlx::query_as::<_, ProjectTip>(
+            r#"
+            INSERT INTO project_tips (
+                id, project_id, client_id, expert_id, payment_id,
+                amount, currency, platform_fee, net_amount, preset_percent, message
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            RETURNING *
+            "#,
+        )
+        .bind(tip_id)
+        .bind(project.id)
+        .bind(project.client_id)
+        .bind(project.expert_id)
+        .bind(payment_id)
+        .bind(amount)
+        .bind(&currency)
+        .bind(platform_fee)
+        .bind(net_amount)
+        .bind(preset_percent)
+        .bind(message)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(tip)
+    }
+
+    /// Attach the gateway checkout session to a tip
+    pub async fn set_checkout_session(
+        db: &Database,
+        tip_id: Uuid,
+        session_id: &str,
+    ) -> Result<ProjectTip, sqlx::Error> {
+        sqlx::query_as::<_, ProjectTip>(
+            "UPDATE project_tips SET stripe_checkout_session_id = $2 WHERE id = $1 RETURNING *"
+        )
+        .bind(tip_id)
+        .bind(session_id)
+        .fetch_one(&db.pool)
+        .await
+    }
+
+    /// Get tips for a project
+    pub async fn get_for_project(db: &Database, project_id: Uuid) -> Result<Vec<ProjectTip>, sqlx::Error> {
+        sqlx::query_as::<_, ProjectTip>(
+            "SELECT * FROM project_tips WHERE project_id = $1 ORDER BY created_at DESC"
+        )
+        .bind(project_id)
+        .fetch_all(&db.pool)
+        .await
+    }
+
+    /// Mark a tip as paid once the gateway confirms the charge.
+    ///
+    /// Settles the payment, issues a paid invoice, books the earnings and
+    /// informs the expert in one transaction. Returns `None` if the tip was
+    /// already processed, so repeated webhook deliveries are harmless.
+    pub async fn mark_paid(
+        db: &Database,
+        tip_id: Uuid,
+        stripe_payment_intent_id: Option<&str>,
+    ) -> Result<Option<ProjectTip>, sqlx::Error> {
+        let mut tx = db.pool.begin().await?;
+
+        // Lock the tip so concurrent deliveries of the same webhook settle it once
+        let tip: Option<ProjectTip> = sqlx::query_as(
+            "SELECT * FROM project_tips WHERE id = $1 AND status = 'pending' FOR UPDATE"
+        )
+        .bind(tip_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(tip) = tip else {
+            return Ok(None);
+        };
+
+        if let Some(payment_id) = tip.payment_id {
+            PaymentService::update_status(&mut *tx, payment_id, PaymentStatus::Succeeded, stripe_payment_intent_id).await?;
+            // The project is already completed, so tips can be paid out right away
+            PaymentService::release(&mut *tx, payment_id).await?;
+        }
+
+        ProjectEventService::record(
+            &mut *tx,
+            NewProjectEvent::system(tip.project_id, ProjectEventType::PaymentReceived).data(serde_json::json!({
+                "kind": "tip",
+                "tipId": tip.id,
+                "paymentId": tip.payment_id,
+                "amount": tip.amount,
+                "currency": tip.currency,
+            })),
+        )
+        .await?;
+
+        // Invoice from expert to client
+        let line_items = serde_json::json!([{
+            "description": "Trinkgeld / Bonus",
+            "quantity": 1,
+            "unitPrice": tip.amount,
+            "amount": tip.amount,
+        }]);
+        let invoice = PaymentService::create_invoice(
+            &mut *tx,
+            tip.expert_id,
+            tip.client_id,
+            Some(tip.project_id),
+            tip.amount,
+            0.0,
+            &tip.currency,
+            line_items,
+        )
+        .await?;
+        PaymentService::mark_invoice_paid(&mut *tx, invoice.id, tip.payment_id).await?;
+
+        let tip: ProjectTip = sqlx::query_as(
+            r#"
+            UPDATE project_tips
+            SET status = 'paid', paid_at = NOW(), invoice_id = $2
+            WHERE id = $1 AND status = 'pending'
+            RETURNING *
+            "#,
+        )
+        .bind(tip.id)
+        .bind(invoice.id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            "UPDATE expert_profiles SET total_earnings = total_earnings + $2, updated_at = NOW() WHERE user_id = $1"
+        )
+        .bind(tip.expert_id)
+        .bind(tip.net_amount as i64)
+        .execute(&mut *tx)
+        .await?;
+
+        let amount = format!("{:.2} {}", tip.amount as f64 / 100.0, tip.currency);
+
+        NotificationService::create(
+            &mut *tx,
+            tip.expert_id,
+            "tip_received",
+            "Trinkgeld erhalten",
+            &format!("Sie haben ein Trinkgeld von {} erhalten.", amount),
+            Some(serde_json::json!({
+                "projectId": tip.project_id,
+                "tipId": tip.id,
+                "amount": tip.amount,
+                "currency": tip.currency,
+            })),
+        )
+        .await?;
+
+        let content = Self::thank_you_message(&amount, tip.message.as_deref());
+        MessageService::send_system_message_tx(
+            &mut tx,
+            tip.client_id,
+            tip.expert_id,
+            Some(tip.project_id),
+            &content,
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(Some(tip))
+    }
+
+    /// System message posted to the project conversation for a paid tip
+    fn thank_you_message(amount: &str, note: Option<&str>) -> String {
+        match note {
+            Some(note) if !note.trim().is_empty() => {
+                format!("Der Kunde hat ein Trinkgeld von {} gesendet: \"{}\"", amount, note.trim())
+            }
+            _ => format!("Der Kunde hat ein Trinkgeld von {} gesendet.", amount),
+        }
+    }
+
+    /// Cancel a pending tip whose checkout was abandoned or expired
+    pub async fn cancel_pending(db: &Database, tip_id: Uuid) -> Result<(), sqlx::Error> {
+        let payment_id: Option<Option<Uuid>> = sqlx::query_scalar(
+            "UPDATE project_tips SET status = 'cancelled' WHERE id = $1 AND status = 'pending' RETURNING payment_id"
+        )
+        .bind(tip_id)
+        .fetch_optional(&db.pool)
+        .await?;
+
+        if let Some(Some(payment_id)) = payment_id {
+            PaymentService::update_status(&db.pool, payment_id, PaymentStatus::Cancelled, None).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preset_amount_and_fee() {
+        assert_eq!(TipService::preset_amount(100_000, 10), 10_000);
+        assert_eq!(TipService::preset_amount(99_999, 15), 14_999);
+        assert_eq!(TipService::calculate_fee(5_000, 0.1), (500, 4_500));
+        assert_eq!(TipService::calculate_fee(1_005, 0.1), (101, 904));
+    }
+
+    #[test]
+    fn test_thank_you_message() {
+        assert_eq!(
+            TipService::thank_you_message("50.00 CHF", None),
+            "Der Kunde hat ein Trinkgeld von 50.00 CHF gesendet."
+        );
+        assert_eq!(
+            TipService::thank_you_message("50.00 CHF", Some("  ")),
+            "Der Kunde hat ein Trinkgeld von 50.00 CHF gesendet."
+        );
+        assert_eq!(
+            TipService::thank_you_message("50.00 CHF", Some(" Danke! ")),
+            "Der Kunde hat ein Trinkgeld von 50.00 CHF gesendet: \"Danke!\""
+        );
+    }
+}
@@ -0,0 +1,55 @@
+//! Business day arithmetic (Monday to Friday; public holidays are not considered)
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+/// Whether `date` falls on a weekday
+pub fn is_business_day(date: NaiveDate) -> bool {
+    !matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+/// Date `days` business days after `start`
+pub fn add_business_days(start: NaiveDate, days: i64) -> NaiveDate {
+    let mut date = start;
+    let mut remaining = days;
+    while remaining > 0 {
+        date += Duration::days(1);
+        if is_business_day(date) {
+            remaining -= 1;
+        }
+    }
+    date
+}
+
+/// Business days after `from` up to and including `to` (0 if `to` is not later)
+pub fn business_days_between(from: NaiveDate, to: NaiveDate) -> i64 {
+    from.iter_days()
+        .skip(1)
+        .take_while(|d| *d <= to)
+        .filter(|d| is_business_day(*d))
+        .count() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_add_business_days_skips_weekend() {
+        // Thursday + 2 business days = Monday
+        assert_eq!(add_business_days(date(2024, 12, 12), 2), date(2024, 12, 16));
+        // Saturday + 1 business day = Monday
+        assert_eq!(add_business_days(date(2024, 12, 14), 1), date(2024, 12, 16));
+        assert_eq!(add_business_days(date(2024, 12, 12), 0), date(2024, 12, 12));
+    }
+
+    #[test]
+    fn test_business_days_between() {
+        assert_eq!(business_days_between(date(2024, 12, 12), date(2024, 12, 16)), 2);
+        assert_eq!(business_days_between(date(2024, 12, 16), date(2024, 12, 12)), 0);
+        assert_eq!(business_days_between(date(2024, 12, 9), date(2024, 12, 23)), 10);
+    }
+}
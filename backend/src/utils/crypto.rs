@@ -1,6 +1,46 @@
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use rand::Rng;
 
+/// Length of the random nonce stored in front of every ciphertext
+const NONCE_LENGTH: usize = 12;
+
+/// AES-256-GCM key for data encrypted at rest (e.g. secret requirement answers)
+#[derive(Clone)]
+pub struct SecretKey([u8; 32]);
+
+impl SecretKey {
+    pub fn new(key: [u8; 32]) -> Self {
+        Self(key)
+    }
+
+    /// Encrypt with a fresh random nonce; returns nonce and ciphertext
+    pub fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
+        let nonce: [u8; NONCE_LENGTH] = rand::rng().random();
+        let ciphertext = Aes256Gcm::new(&self.0.into())
+            .encrypt(&Nonce::from(nonce), plaintext)
+            .expect("AES-GCM encryption of an in-memory buffer cannot fail");
+
+        [nonce.as_slice(), &ciphertext].concat()
+    }
+
+    /// Decrypt data produced by [`SecretKey::encrypt`]; `None` if it was tampered with
+    /// or encrypted with another key
+    pub fn decrypt(&self, data: &[u8]) -> Option<Vec<u8>> {
+        let (nonce, ciphertext) = data.split_at_checked(NONCE_LENGTH)?;
+        let nonce: [u8; NONCE_LENGTH] = nonce.try_into().ok()?;
+
+        Aes256Gcm::new(&self.0.into()).decrypt(&Nonce::from(nonce), ciphertext).ok()
+    }
+}
+
+impl std::fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SecretKey(..)")
+    }
+}
+
 /// Generate a random token (URL-safe base64)
 pub fn generate_token(length: usize) -> String {
     let mut rng = rand::rng();
@@ -37,6 +77,22 @@ mod tests {
         assert!(!token1.is_empty());
     }
 
+    #[test]
+    fn test_secret_key_roundtrip() {
+        let key = SecretKey::new([7; 32]);
+        let encrypted = key.encrypt(b"hunter2");
+
+        assert_ne!(&encrypted[NONCE_LENGTH..], b"hunter2");
+        assert_ne!(encrypted, key.encrypt(b"hunter2"));
+        assert_eq!(key.decrypt(&encrypted).unwrap(), b"hunter2");
+
+        let mut tampered = encrypted.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(key.decrypt(&tampered).is_none());
+        assert!(SecretKey::new([8; 32]).decrypt(&encrypted).is_none());
+        assert!(key.decrypt(b"short").is_none());
+    }
+
     #[test]
     fn test_generate_short_code() {
         let code = generate_short_code();
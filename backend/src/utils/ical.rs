@@ -0,0 +1,748 @@
+//! iCalendar (RFC 5545) reading and writing
+//! Reading covers what is needed to know when someone is busy: VEVENTs with start, end or
+//! duration, TZID references, recurrence (RRULE, EXDATE, RECURRENCE-ID overrides) and
+//! transparency. Wall-clock times are kept together with their time zone name; converting them
+//! to UTC is left to the caller. Rule parts other than FREQ, INTERVAL, COUNT, UNTIL, BYDAY,
+//! BYMONTHDAY and BYMONTH are ignored, which can only produce additional occurrences.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, NaiveDateTime, NaiveTime, Utc, Weekday};
+
+/// Upper bound of recurrence periods expanded per event
+const MAX_RECURRENCE_PERIODS: u32 = 10_000;
+
+/// Maximum line length in octets before folding
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Date or time value of a calendar property
+#[derive(Debug, Clone, PartialEq)]
+pub enum IcsTime {
+    /// All-day value (`VALUE=DATE`)
+    Date(NaiveDate),
+    /// UTC time (`Z` suffix)
+    Utc(NaiveDateTime),
+    /// Wall-clock time in the named time zone; floating if there is none
+    Local(NaiveDateTime, Option<String>),
+}
+
+impl IcsTime {
+    fn naive(&self) -> NaiveDateTime {
+        match self {
+            IcsTime::Date(date) => date.and_time(NaiveTime::MIN),
+            IcsTime::Utc(time) | IcsTime::Local(time, _) => *time,
+        }
+    }
+
+    /// Same kind of value (and time zone) at another wall-clock time
+    fn with_naive(&self, time: NaiveDateTime) -> IcsTime {
+        match self {
+            IcsTime::Date(_) => IcsTime::Date(time.date()),
+            IcsTime::Utc(_) => IcsTime::Utc(time),
+            IcsTime::Local(_, tzid) => IcsTime::Local(time, tzid.clone()),
+        }
+    }
+
+    /// Whether an occurrence starting at `start` is the one this value refers to
+    fn matches(&self, start: NaiveDateTime) -> bool {
+        match self {
+            IcsTime::Date(date) => start.date() == *date,
+            _ => start == self.naive(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// Parsed RRULE
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecurrenceRule {
+    pub frequency: Frequency,
+    pub interval: u32,
+    pub count: Option<u32>,
+    /// Last possible start (wall-clock, inclusive)
+    pub until: Option<NaiveDateTime>,
+    /// Weekdays, optionally with ordinal ("-1FR" = last Friday)
+    pub by_day: Vec<(Option<i32>, Weekday)>,
+    pub by_month_day: Vec<i32>,
+    pub by_month: Vec<u32>,
+}
+
+/// VEVENT relevant for busy times
+#[derive(Debug, Clone, Default)]
+pub struct IcsEvent {
+    pub uid: Option<String>,
+    pub start: Option<IcsTime>,
+    pub end: Option<IcsTime>,
+    pub duration: Option<Duration>,
+    pub rule: Option<RecurrenceRule>,
+    pub exdates: Vec<IcsTime>,
+    /// Set on overrides of a single occurrence of a recurring event
+    pub recurrence_id: Option<IcsTime>,
+    /// Transparent or cancelled; does not block time
+    pub free: bool,
+}
+
+/// Single occurrence of an event
+#[derive(Debug, Clone, PartialEq)]
+pub struct Occurrence {
+    pub start: IcsTime,
+    /// Exclusive end (the day after the last day for all-day events)
+    pub end: IcsTime,
+}
+
+/// Event written to an exported calendar
+#[derive(Debug, Clone)]
+pub struct ExportEvent {
+    pub uid: String,
+    pub summary: String,
+    pub description: Option<String>,
+    pub start: IcsTime,
+    pub end: IcsTime,
+}
+
+/// Parse the events of an iCalendar object
+pub fn parse(input: &str) -> Result<Vec<IcsEvent>, String> {
+    let lines = unfold(input);
+    if !lines.iter().any(|l| l.eq_ignore_ascii_case("BEGIN:VCALENDAR")) {
+        return Err("Not an iCalendar file".to_string());
+    }
+
+    let mut events = Vec::new();
+    let mut aliases = HashMap::new();
+    let mut components: Vec<String> = Vec::new();
+    let mut event: Option<IcsEvent> = None;
+    let mut timezone: (Option<String>, Option<String>) = (None, None);
+
+    for line in &lines {
+        let Some((name, params, value)) = parse_line(line) else {
+            continue;
+        };
+
+        match name.as_str() {
+            "BEGIN" => {
+                let component = value.to_ascii_uppercase();
+                match component.as_str() {
+                    "VEVENT" => event = Some(IcsEvent::default()),
+                    "VTIMEZONE" => timezone = (None, None),
+                    _ => {}
+                }
+                components.push(component);
+                continue;
+            }
+            "END" => {
+                match components.pop().as_deref() {
+                    Some("VEVENT") => events.extend(event.take()),
+                    Some("VTIMEZONE") => {
+                        if let (Some(tzid), Some(location)) = timezone.clone() {
+                            aliases.insert(tzid, location);
+                        }
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+            _ => {}
+        }
+
+        match (components.last().map(String::as_str), &mut event) {
+            (Some("VEVENT"), Some(event)) => apply_event_property(event, &name, &params, value),
+            (Some("VTIMEZONE"), _) => match name.as_str() {
+                "TZID" => timezone.0 = Some(value.to_string()),
+                "X-LIC-LOCATION" => timezone.1 = Some(value.to_string()),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    for event in &mut events {
+        for time in event
+            .start
+            .iter_mut()
+            .chain(event.end.iter_mut())
+            .chain(event.recurrence_id.iter_mut())
+            .chain(event.exdates.iter_mut())
+        {
+            if let IcsTime::Local(_, Some(tzid)) = time
+                && let Some(name) = aliases.get(tzid.as_str()).cloned().or_else(|| windows_zone(tzid))
+            {
+                *tzid = name;
+            }
+        }
+    }
+
+    Ok(events)
+}
+
+fn apply_event_property(event: &mut IcsEvent, name: &str, params: &[(String, String)], value: &str) {
+    match name {
+        "UID" => event.uid = Some(value.to_string()),
+        "DTSTART" => event.start = parse_time(value, params),
+        "DTEND" => event.end = parse_time(value, params),
+        "DURATION" => event.duration = parse_duration(value),
+        "RRULE" => event.rule = parse_rule(value),
+        "EXDATE" => event.exdates.extend(value.split(',').filter_map(|v| parse_time(v, params))),
+        "RECURRENCE-ID" => event.recurrence_id = parse_time(value, params),
+        "TRANSP" if value.eq_ignore_ascii_case("TRANSPARENT") => event.free = true,
+        "STATUS" if value.eq_ignore_ascii_case("CANCELLED") => event.free = true,
+        _ => {}
+    }
+}
+
+/// Busy occurrences that overlap `from..to` (compared in wall-clock time)
+pub fn busy_occurrences(events: &[IcsEvent], from: NaiveDateTime, to: NaiveDateTime) -> Vec<Occurrence> {
+    // Occurrences replaced by an override (which may itself be cancelled)
+    let overridden: HashSet<(&str, NaiveDateTime)> = events
+        .iter()
+        .filter_map(|e| Some((e.uid.as_deref()?, e.recurrence_id.as_ref()?.naive())))
+        .collect();
+
+    let mut occurrences = Vec::new();
+    for event in events.iter().filter(|e| !e.free) {
+        let Some(start) = &event.start else {
+            continue;
+        };
+        let length = match (&event.end, event.duration) {
+            (Some(end), _) => end.naive() - start.naive(),
+            (None, Some(duration)) => duration,
+            (None, None) if matches!(start, IcsTime::Date(_)) => Duration::days(1),
+            (None, None) => Duration::zero(),
+        };
+        if length <= Duration::zero() {
+            continue;
+        }
+
+        let starts = match (&event.rule, event.recurrence_id.is_none()) {
+            (Some(rule), true) => rule.starts(start.naive(), to),
+            _ => vec![start.naive()],
+        };
+
+        for occurrence in starts {
+            let replaced = event.recurrence_id.is_none()
+                && (event.exdates.iter().any(|ex| ex.matches(occurrence))
+                    || event.uid.as_deref().is_some_and(|uid| overridden.contains(&(uid, occurrence))));
+
+            if !replaced && occurrence < to && occurrence + length > from {
+                occurrences.push(Occurrence {
+                    start: start.with_naive(occurrence),
+                    end: start.with_naive(occurrence + length),
+                });
+            }
+        }
+    }
+
+    occurrences
+}
+
+impl RecurrenceRule {
+    /// Occurrence starts from `start` (the DTSTART) up to `limit`, as restricted by COUNT and UNTIL
+    pub fn starts(&self, start: NaiveDateTime, limit: NaiveDateTime) -> Vec<NaiveDateTime> {
+        let mut starts = Vec::new();
+        let mut emitted = 0;
+
+        for period in 0..MAX_RECURRENCE_PERIODS {
+            let Some(candidates) = self.period_starts(start, period * self.interval) else {
+                break;
+            };
+            if candidates.first().is_some_and(|first| *first > limit) {
+                break;
+            }
+
+            for candidate in candidates.into_iter().filter(|c| *c >= start) {
+                if candidate > limit
+                    || self.until.is_some_and(|until| candidate > until)
+                    || self.count.is_some_and(|count| emitted >= count)
+                {
+                    return starts;
+                }
+                emitted += 1;
+                starts.push(candidate);
+            }
+        }
+
+        starts
+    }
+
+    /// Sorted candidate starts of the period `offset` frequency units after the one of `start`
+    fn period_starts(&self, start: NaiveDateTime, offset: u32) -> Option<Vec<NaiveDateTime>> {
+        let date = start.date();
+        let mut days = match self.frequency {
+            Frequency::Daily => {
+                let day = date.checked_add_signed(Duration::days(offset as i64))?;
+                let weekday_ok = self.by_day.is_empty() || self.by_day.iter().any(|(_, w)| *w == day.weekday());
+                let month_ok = self.by_month.is_empty() || self.by_month.contains(&day.month());
+                if weekday_ok && month_ok { vec![day] } else { Vec::new() }
+            }
+            Frequency::Weekly => {
+                let week = date
+                    .checked_sub_signed(Duration::days(date.weekday().num_days_from_monday() as i64))?
+                    .checked_add_signed(Duration::weeks(offset as i64))?;
+                if self.by_day.is_empty() {
+                    vec![week + Duration::days(date.weekday().num_days_from_monday() as i64)]
+                } else {
+                    self.by_day
+                        .iter()
+                        .map(|(_, w)| week + Duration::days(w.num_days_from_monday() as i64))
+                        .collect()
+                }
+            }
+            Frequency::Monthly => {
+                let month = date.with_day(1)?.checked_add_months(Months::new(offset))?;
+                self.days_in_month(month, date.day())
+            }
+            Frequency::Yearly => {
+                let year = NaiveDate::from_ymd_opt(date.year().checked_add(offset as i32)?, 1, 1)?;
+                let months = if self.by_month.is_empty() { vec![date.month()] } else { self.by_month.clone() };
+                months
+                    .into_iter()
+                    .filter_map(|m| year.with_month(m))
+                    .flat_map(|month| self.days_in_month(month, date.day()))
+                    .collect()
+            }
+        };
+
+        days.sort();
+        days.dedup();
+        Some(days.into_iter().map(|d| d.and_time(start.time())).collect())
+    }
+
+    /// Days of the month starting at `first` selected by BYMONTHDAY/BYDAY, else `default_day`
+    fn days_in_month(&self, first: NaiveDate, default_day: u32) -> Vec<NaiveDate> {
+        let all: Vec<NaiveDate> = first.iter_days().take_while(|d| d.month() == first.month()).collect();
+
+        if !self.by_month_day.is_empty() {
+            self.by_month_day
+                .iter()
+                .filter_map(|&n| match n {
+                    n if n > 0 => all.get(n as usize - 1),
+                    n if n < 0 => all.len().checked_sub(n.unsigned_abs() as usize).and_then(|i| all.get(i)),
+                    _ => None,
+                })
+                .copied()
+                .collect()
+        } else if !self.by_day.is_empty() {
+            self.by_day
+                .iter()
+                .flat_map(|&(ordinal, weekday)| {
+                    let matching: Vec<NaiveDate> = all.iter().filter(|d| d.weekday() == weekday).copied().collect();
+                    match ordinal {
+                        None => matching,
+                        Some(n) if n > 0 => matching.get(n as usize - 1).copied().into_iter().collect(),
+                        Some(n) if n < 0 => matching
+                            .len()
+                            .checked_sub(n.unsigned_abs() as usize)
+                            .and_then(|i| matching.get(i).copied())
+                            .into_iter()
+                            .collect(),
+                        Some(_) => Vec::new(),
+                    }
+                })
+                .collect()
+        } else {
+            all.get(default_day as usize - 1).copied().into_iter().collect()
+        }
+    }
+}
+
+/// Write events as an iCalendar object
+pub fn write_calendar(name: &str, events: &[ExportEvent], stamp: DateTime<Utc>) -> String {
+    let mut out = String::new();
+    let stamp = stamp.format("%Y%m%dT%H%M%SZ").to_string();
+
+    for line in ["BEGIN:VCALENDAR", "VERSION:2.0", "PRODID:-//DACH Marketplace//Calendar//DE", "CALSCALE:GREGORIAN", "METHOD:PUBLISH"] {
+        fold(line, &mut out);
+    }
+    fold(&format!("X-WR-CALNAME:{}", escape(name)), &mut out);
+
+    for event in events {
+        fold("BEGIN:VEVENT", &mut out);
+        fold(&format!("UID:{}", event.uid), &mut out);
+        fold(&format!("DTSTAMP:{}", stamp), &mut out);
+        fold(&format!("DTSTART{}", format_time(&event.start)), &mut out);
+        fold(&format!("DTEND{}", format_time(&event.end)), &mut out);
+        fold(&format!("SUMMARY:{}", escape(&event.summary)), &mut out);
+        if let Some(description) = &event.description {
+            fold(&format!("DESCRIPTION:{}", escape(description)), &mut out);
+        }
+        fold("TRANSP:OPAQUE", &mut out);
+        fold("END:VEVENT", &mut out);
+    }
+
+    fold("END:VCALENDAR", &mut out);
+    out
+}
+
+/// Join folded lines (continuations start with a space or tab)
+fn unfold(input: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in input.split('\n') {
+        let raw = raw.strip_suffix('\r').unwrap_or(raw);
+        if let Some(continuation) = raw.strip_prefix([' ', '\t'])
+            && let Some(last) = lines.last_mut()
+        {
+            last.push_str(continuation);
+            continue;
+        }
+        if !raw.trim().is_empty() {
+            lines.push(raw.to_string());
+        }
+    }
+    lines
+}
+
+/// Content line: upper-case name, parameters and value
+type ContentLine<'a> = (String, Vec<(String, String)>, &'a str);
+
+/// Split a content line into its parts
+fn parse_line(line: &str) -> Option<ContentLine<'_>> {
+    let mut in_quotes = false;
+    let mut separators = Vec::new();
+    let mut colon = None;
+
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ';' if !in_quotes => separators.push(i),
+            ':' if !in_quotes => {
+                colon = Some(i);
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    let colon = colon?;
+    let mut bounds = separators;
+    bounds.push(colon);
+
+    let name = line[..bounds[0]].trim().to_ascii_uppercase();
+    let params = bounds
+        .windows(2)
+        .filter_map(|w| {
+            let (key, value) = line[w[0] + 1..w[1]].split_once('=')?;
+            Some((key.trim().to_ascii_uppercase(), value.trim().trim_matches('"').to_string()))
+        })
+        .collect();
+
+    Some((name, params, line[colon + 1..].trim()))
+}
+
+fn param<'a>(params: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    params.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+}
+
+fn parse_time(value: &str, params: &[(String, String)]) -> Option<IcsTime> {
+    let value = value.trim();
+    if param(params, "VALUE").is_some_and(|v| v.eq_ignore_ascii_case("DATE")) || value.len() == 8 {
+        return NaiveDate::parse_from_str(value, "%Y%m%d").ok().map(IcsTime::Date);
+    }
+
+    match value.strip_suffix(['Z', 'z']) {
+        Some(utc) => NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok().map(IcsTime::Utc),
+        None => {
+            let time = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+            // Some producers prefix global zone names with a slash
+            let tzid = param(params, "TZID").map(|t| t.trim_start_matches('/').to_string());
+            Some(IcsTime::Local(time, tzid))
+        }
+    }
+}
+
+/// Parse a DURATION value such as `PT1H30M`, `P1D` or `-P1W`
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let (sign, rest) = match value.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, value.strip_prefix('+').unwrap_or(value)),
+    };
+
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    let mut in_time = false;
+
+    for c in rest.strip_prefix('P')?.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => in_time = true,
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                total += match (unit, in_time) {
+                    ('W', false) => Duration::weeks(n),
+                    ('D', false) => Duration::days(n),
+                    ('H', true) => Duration::hours(n),
+                    ('M', true) => Duration::minutes(n),
+                    ('S', true) => Duration::seconds(n),
+                    _ => return None,
+                };
+            }
+        }
+    }
+
+    number.is_empty().then_some(total * sign)
+}
+
+fn parse_rule(value: &str) -> Option<RecurrenceRule> {
+    let mut frequency = None;
+    let mut rule = RecurrenceRule {
+        frequency: Frequency::Daily,
+        interval: 1,
+        count: None,
+        until: None,
+        by_day: Vec::new(),
+        by_month_day: Vec::new(),
+        by_month: Vec::new(),
+    };
+
+    for part in value.split(';').filter(|p| !p.is_empty()) {
+        let (key, value) = part.split_once('=')?;
+        match key.trim().to_ascii_uppercase().as_str() {
+            "FREQ" => {
+                frequency = Some(match value.to_ascii_uppercase().as_str() {
+                    "DAILY" => Frequency::Daily,
+                    "WEEKLY" => Frequency::Weekly,
+                    "MONTHLY" => Frequency::Monthly,
+                    "YEARLY" => Frequency::Yearly,
+                    // Sub-daily rules are not expanded
+                    _ => return None,
+                })
+            }
+            "INTERVAL" => rule.interval = value.parse().ok().filter(|i| *i > 0)?,
+            "COUNT" => rule.count = value.parse().ok(),
+            "UNTIL" => {
+                rule.until = Some(match parse_time(value, &[])? {
+                    IcsTime::Date(date) => date.and_hms_opt(23, 59, 59)?,
+                    other => other.naive(),
+                })
+            }
+            "BYDAY" => {
+                for day in value.split(',') {
+                    rule.by_day.push(parse_weekday(day)?);
+                }
+            }
+            "BYMONTHDAY" => rule.by_month_day = value.split(',').filter_map(|d| d.parse().ok()).collect(),
+            "BYMONTH" => rule.by_month = value.split(',').filter_map(|m| m.parse().ok()).collect(),
+            _ => {}
+        }
+    }
+
+    rule.frequency = frequency?;
+    Some(rule)
+}
+
+/// Parse a BYDAY entry such as `MO`, `2TU` or `-1FR`
+fn parse_weekday(value: &str) -> Option<(Option<i32>, Weekday)> {
+    let value = value.trim();
+    let split = value.len().checked_sub(2)?;
+    if !value.is_char_boundary(split) {
+        return None;
+    }
+    let (ordinal, day) = value.split_at(split);
+
+    let weekday = match day.to_ascii_uppercase().as_str() {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    };
+    let ordinal = match ordinal {
+        "" => None,
+        n => Some(n.trim_start_matches('+').parse().ok()?),
+    };
+
+    Some((ordinal, weekday))
+}
+
+/// IANA name of a Windows time zone name as used by Outlook/Exchange
+fn windows_zone(name: &str) -> Option<String> {
+    let iana = match name {
+        "W. Europe Standard Time" => "Europe/Berlin",
+        "Central Europe Standard Time" => "Europe/Budapest",
+        "Central European Standard Time" => "Europe/Warsaw",
+        "Romance Standard Time" => "Europe/Paris",
+        "GMT Standard Time" => "Europe/London",
+        "Greenwich Standard Time" => "Atlantic/Reykjavik",
+        "GTB Standard Time" => "Europe/Bucharest",
+        "FLE Standard Time" => "Europe/Kiev",
+        "E. Europe Standard Time" => "Europe/Chisinau",
+        "Russian Standard Time" => "Europe/Moscow",
+        "Eastern Standard Time" => "America/New_York",
+        "Central Standard Time" => "America/Chicago",
+        "Mountain Standard Time" => "America/Denver",
+        "Pacific Standard Time" => "America/Los_Angeles",
+        "UTC" | "Coordinated Universal Time" => "UTC",
+        _ => return None,
+    };
+    Some(iana.to_string())
+}
+
+fn format_time(time: &IcsTime) -> String {
+    match time {
+        IcsTime::Date(date) => format!(";VALUE=DATE:{}", date.format("%Y%m%d")),
+        IcsTime::Utc(time) => format!(":{}", time.format("%Y%m%dT%H%M%SZ")),
+        IcsTime::Local(time, Some(tzid)) => format!(";TZID={}:{}", tzid, time.format("%Y%m%dT%H%M%S")),
+        IcsTime::Local(time, None) => format!(":{}", time.format("%Y%m%dT%H%M%S")),
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Append `line` folded to 75 octets with CRLF line breaks
+fn fold(line: &str, out: &mut String) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(y: i32, m: u32, d: u32, h: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap().and_hms_opt(h, min, 0).unwrap()
+    }
+
+    fn calendar(body: &str) -> String {
+        format!("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n{}END:VCALENDAR\r\n", body)
+    }
+
+    #[test]
+    fn test_parse_event_with_time_zones() {
+        let input = calendar(concat!(
+            "BEGIN:VTIMEZONE\r\nTZID:Custom Zurich\r\nX-LIC-LOCATION:Europe/Zurich\r\n",
+            "BEGIN:STANDARD\r\nTZOFFSETTO:+0100\r\nEND:STANDARD\r\nEND:VTIMEZONE\r\n",
+            "BEGIN:VEVENT\r\nUID:a\r\nDTSTART;TZID=\"Custom Zurich\":20241216T090000\r\n",
+            "DTEND;TZID=W. Europe Standard Time:20241216T10\r\n 3000\r\n",
+            "BEGIN:VALARM\r\nTRIGGER:-PT15M\r\nEND:VALARM\r\nEND:VEVENT\r\n",
+            "BEGIN:VEVENT\r\nUID:b\r\nDTSTART:20241217T080000Z\r\nDURATION:PT45M\r\nEND:VEVENT\r\n",
+        ));
+
+        let events = parse(&input).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].start, Some(IcsTime::Local(at(2024, 12, 16, 9, 0), Some("Europe/Zurich".to_string()))));
+        assert_eq!(events[0].end, Some(IcsTime::Local(at(2024, 12, 16, 10, 30), Some("Europe/Berlin".to_string()))));
+        assert_eq!(events[1].start, Some(IcsTime::Utc(at(2024, 12, 17, 8, 0))));
+        assert_eq!(events[1].duration, Some(Duration::minutes(45)));
+
+        assert!(parse("not a calendar").is_err());
+    }
+
+    #[test]
+    fn test_weekly_recurrence_with_exceptions() {
+        let input = calendar(concat!(
+            "BEGIN:VEVENT\r\nUID:standup\r\nDTSTART;TZID=Europe/Vienna:20241202T090000\r\n",
+            "DTEND;TZID=Europe/Vienna:20241202T100000\r\nRRULE:FREQ=WEEKLY;BYDAY=MO,WE;COUNT=6\r\n",
+            "EXDATE;TZID=Europe/Vienna:20241204T090000\r\nEND:VEVENT\r\n",
+            "BEGIN:VEVENT\r\nUID:standup\r\nRECURRENCE-ID;TZID=Europe/Vienna:20241209T090000\r\n",
+            "DTSTART;TZID=Europe/Vienna:20241209T140000\r\nDTEND;TZID=Europe/Vienna:20241209T150000\r\nEND:VEVENT\r\n",
+            "BEGIN:VEVENT\r\nUID:standup\r\nRECURRENCE-ID;TZID=Europe/Vienna:20241211T090000\r\n",
+            "DTSTART;TZID=Europe/Vienna:20241211T090000\r\nSTATUS:CANCELLED\r\nEND:VEVENT\r\n",
+            "BEGIN:VEVENT\r\nUID:free\r\nDTSTART:20241203T090000\r\nDTEND:20241203T100000\r\n",
+            "TRANSP:TRANSPARENT\r\nEND:VEVENT\r\n",
+        ));
+
+        let events = parse(&input).unwrap();
+        let busy = busy_occurrences(&events, at(2024, 12, 1, 0, 0), at(2025, 1, 1, 0, 0));
+        let mut starts: Vec<NaiveDateTime> = busy.iter().map(|o| o.start.naive()).collect();
+        starts.sort();
+
+        // 2, 4 (excluded), 9 (moved), 11 (cancelled), 16, 18
+        assert_eq!(
+            starts,
+            vec![at(2024, 12, 2, 9, 0), at(2024, 12, 9, 14, 0), at(2024, 12, 16, 9, 0), at(2024, 12, 18, 9, 0)]
+        );
+        assert_eq!(busy[0].end, IcsTime::Local(at(2024, 12, 2, 10, 0), Some("Europe/Vienna".to_string())));
+    }
+
+    #[test]
+    fn test_monthly_and_yearly_rules() {
+        let last_friday = parse_rule("FREQ=MONTHLY;BYDAY=-1FR;UNTIL=20250301").unwrap();
+        assert_eq!(
+            last_friday.starts(at(2024, 12, 27, 16, 0), at(2026, 1, 1, 0, 0)),
+            vec![at(2024, 12, 27, 16, 0), at(2025, 1, 31, 16, 0), at(2025, 2, 28, 16, 0)]
+        );
+
+        // Months without a 31st are skipped
+        let month_end = parse_rule("FREQ=MONTHLY;INTERVAL=1;COUNT=3").unwrap();
+        assert_eq!(
+            month_end.starts(at(2025, 1, 31, 8, 0), at(2026, 1, 1, 0, 0)),
+            vec![at(2025, 1, 31, 8, 0), at(2025, 3, 31, 8, 0), at(2025, 5, 31, 8, 0)]
+        );
+
+        let yearly = parse_rule("FREQ=YEARLY;INTERVAL=2").unwrap();
+        assert_eq!(
+            yearly.starts(at(2024, 8, 1, 0, 0), at(2029, 1, 1, 0, 0)),
+            vec![at(2024, 8, 1, 0, 0), at(2026, 8, 1, 0, 0), at(2028, 8, 1, 0, 0)]
+        );
+
+        assert!(parse_rule("FREQ=HOURLY").is_none());
+        assert_eq!(parse_duration("P1DT2H"), Some(Duration::hours(26)));
+        assert_eq!(parse_duration("-PT15M"), Some(Duration::minutes(-15)));
+    }
+
+    #[test]
+    fn test_all_day_events() {
+        let input = calendar(concat!(
+            "BEGIN:VEVENT\r\nUID:holiday\r\nDTSTART;VALUE=DATE:20241223\r\nDTEND;VALUE=DATE:20241228\r\nEND:VEVENT\r\n",
+            "BEGIN:VEVENT\r\nUID:day\r\nDTSTART;VALUE=DATE:20250106\r\nEND:VEVENT\r\n",
+        ));
+
+        let events = parse(&input).unwrap();
+        let busy = busy_occurrences(&events, at(2024, 12, 24, 0, 0), at(2025, 2, 1, 0, 0));
+        assert_eq!(busy.len(), 2);
+        assert_eq!(busy[0].end, IcsTime::Date(NaiveDate::from_ymd_opt(2024, 12, 28).unwrap()));
+        assert_eq!(busy[1].end, IcsTime::Date(NaiveDate::from_ymd_opt(2025, 1, 7).unwrap()));
+    }
+
+    #[test]
+    fn test_write_calendar() {
+        let events = vec![ExportEvent {
+            uid: "booking-1".to_string(),
+            summary: "Termin: Müller, Beratung; Workflow".to_string(),
+            description: Some("Zeile 1\nZeile 2 ".repeat(10)),
+            start: IcsTime::Utc(at(2024, 12, 16, 8, 0)),
+            end: IcsTime::Utc(at(2024, 12, 16, 9, 0)),
+        }];
+
+        let ics = write_calendar("Buchungen", &events, Utc::now());
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(ics.contains("SUMMARY:Termin: Müller\\, Beratung\\; Workflow\r\n"));
+        assert!(ics.split("\r\n").all(|line| line.len() <= MAX_LINE_OCTETS));
+
+        let parsed = parse(&ics).unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].start, Some(IcsTime::Utc(at(2024, 12, 16, 8, 0))));
+        assert_eq!(parsed[0].uid.as_deref(), Some("booking-1"));
+    }
+}
@@ -1,9 +1,14 @@
+pub mod business_days;
 pub mod crypto;
+pub mod ical;
 pub mod jwt;
+pub mod pdf;
+pub mod qr_bill;
 pub mod slug;
 pub mod validation;
 pub mod vat;
 
+pub use business_days::*;
 pub use crypto::*;
 pub use jwt::*;
 pub use slug::*;
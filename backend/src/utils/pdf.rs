@@ -0,0 +1,195 @@
+//! Minimal PDF writer for plain-text documents (contracts, agreements)
+//! Uses the standard Helvetica fonts with WinAnsi encoding, so no fonts are embedded.
+//! The output is deterministic: the same text always produces the same bytes.
+
+const PAGE_WIDTH: u32 = 595; // A4 in points
+const PAGE_HEIGHT: u32 = 842;
+const MARGIN: u32 = 56;
+const FONT_SIZE: u32 = 10;
+const LEADING: u32 = 14;
+const TITLE_SIZE: u32 = 14;
+/// Characters per line; Helvetica averages about half the font size per character
+const LINE_CHARS: usize = 92;
+
+/// Render a title and a body of paragraphs (separated by newlines) as A4 PDF
+pub fn render_text_pdf(title: &str, body: &str) -> Vec<u8> {
+    let lines = wrap(body, LINE_CHARS);
+    let first_page = ((PAGE_HEIGHT - 2 * MARGIN - 2 * LEADING) / LEADING) as usize;
+    let per_page = ((PAGE_HEIGHT - 2 * MARGIN) / LEADING) as usize;
+
+    let mut pages: Vec<&[String]> = Vec::new();
+    let (head, mut rest) = lines.split_at(lines.len().min(first_page));
+    pages.push(head);
+    while !rest.is_empty() {
+        let (page, remaining) = rest.split_at(rest.len().min(per_page));
+        pages.push(page);
+        rest = remaining;
+    }
+
+    // Objects: 1 catalog, 2 page tree, 3 regular font, 4 bold font, then page + content per page
+    let page_count = pages.len();
+    let mut objects: Vec<Vec<u8>> = Vec::with_capacity(4 + 2 * page_count);
+    let kids: Vec<String> = (0..page_count).map(|i| format!("{} 0 R", 5 + 2 * i)).collect();
+
+    objects.push(b"<< /Type /Catalog /Pages 2 0 R >>".to_vec());
+    objects.push(format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), page_count).into_bytes());
+    objects.push(b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>".to_vec());
+    objects.push(b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>".to_vec());
+
+    for (index, page_lines) in pages.iter().enumerate() {
+        let mut content = Vec::new();
+        let mut top = PAGE_HEIGHT - MARGIN;
+
+        if index == 0 {
+            content.extend_from_slice(format!("BT /F2 {} Tf {} {} Td (", TITLE_SIZE, MARGIN, top).as_bytes());
+            content.extend(encode(title));
+            content.extend_from_slice(b") Tj ET\n");
+            top -= 2 * LEADING;
+        }
+
+        content.extend_from_slice(format!("BT /F1 {} Tf {} TL {} {} Td\n", FONT_SIZE, LEADING, MARGIN, top).as_bytes());
+        for line in page_lines.iter() {
+            content.push(b'(');
+            content.extend(encode(line));
+            content.extend_from_slice(b") Tj T*\n");
+        }
+        content.extend_from_slice(b"ET\n");
+
+        let footer = format!("{} / {}", index + 1, page_count);
+        content.extend_from_slice(
+            format!("BT /F1 8 Tf {} {} Td ({}) Tj ET\n", PAGE_WIDTH - MARGIN - 20, MARGIN / 2, footer).as_bytes(),
+        );
+
+        objects.push(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+                PAGE_WIDTH,
+                PAGE_HEIGHT,
+                6 + 2 * index
+            )
+            .into_bytes(),
+        );
+
+        let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+        stream.extend(content);
+        stream.extend_from_slice(b"endstream");
+        objects.push(stream);
+    }
+
+    let mut pdf = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+        pdf.extend_from_slice(object);
+        pdf.extend_from_slice(b"\nendobj\n");
+    }
+
+    let xref = pdf.len();
+    pdf.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+    for offset in offsets {
+        pdf.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+    }
+    pdf.extend_from_slice(
+        format!("trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", objects.len() + 1, xref).as_bytes(),
+    );
+
+    pdf
+}
+
+/// Word-wrap paragraphs to lines of at most `width` characters; overlong words are split
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let mut word: Vec<char> = word.chars().collect();
+            while word.len() > width {
+                if !line.is_empty() {
+                    lines.push(std::mem::take(&mut line));
+                }
+                lines.push(word.drain(..width).collect());
+            }
+            let word: String = word.into_iter().collect();
+
+            if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&word);
+        }
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// Encode text for a PDF literal string in WinAnsi; unsupported characters become '?'
+fn encode(text: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(text.len());
+
+    for c in text.chars() {
+        let byte = match c {
+            '(' | ')' | '\\' => {
+                bytes.push(b'\\');
+                c as u8
+            }
+            ' '..='~' => c as u8,
+            '\u{a0}'..='\u{ff}' => c as u32 as u8,
+            '€' => 0x80,
+            '‚' => 0x82,
+            '„' => 0x84,
+            '…' => 0x85,
+            '‘' => 0x91,
+            '’' => 0x92,
+            '“' => 0x93,
+            '”' => 0x94,
+            '•' => 0x95,
+            '–' => 0x96,
+            '—' => 0x97,
+            '\t' => b' ',
+            _ => b'?',
+        };
+        bytes.push(byte);
+    }
+
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pdf_structure() {
+        let pdf = render_text_pdf("Vertraulichkeitsvereinbarung", "Zwischen (A) und B.\n\nGrüße");
+        assert!(pdf.starts_with(b"%PDF-1.4"));
+        assert!(pdf.ends_with(b"%%EOF\n"));
+
+        // startxref points at the cross-reference table
+        let text = String::from_utf8_lossy(&pdf);
+        let offset: usize = text.rsplit("startxref\n").next().unwrap().lines().next().unwrap().parse().unwrap();
+        assert!(pdf[offset..].starts_with(b"xref"));
+
+        assert!(pdf.windows(7).any(|w| w == b"\\(A\\) u"));
+        assert!(pdf.windows(5).any(|w| w == b"Gr\xFC\xDFe"));
+        assert_eq!(pdf, render_text_pdf("Vertraulichkeitsvereinbarung", "Zwischen (A) und B.\n\nGrüße"));
+    }
+
+    #[test]
+    fn test_long_text_is_paginated() {
+        let body = "Absatz mit etwas Text.\n".repeat(200);
+        let pdf = render_text_pdf("AVV", &body);
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.contains("/Count 4"));
+    }
+
+    #[test]
+    fn test_wrap() {
+        let lines = wrap("aaa bbb ccc\n\ndddddddddd", 7);
+        assert_eq!(lines, vec!["aaa bbb", "ccc", "", "ddddddd", "ddd"]);
+    }
+}
@@ -0,0 +1,152 @@
+//! Swiss QR-bill payload and payment reference utilities
+//! Implements the "SPC" data structure of the Swiss Implementation Guidelines for the QR-bill.
+
+/// Structured address for QR-bill parties
+#[derive(Debug, Clone, Default)]
+pub struct QrBillAddress {
+    pub name: String,
+    pub street: String,
+    pub postal_code: String,
+    pub town: String,
+    pub country: String,
+}
+
+/// Data required to build a QR-bill payload
+#[derive(Debug, Clone)]
+pub struct QrBillData {
+    pub iban: String,
+    pub creditor: QrBillAddress,
+    /// Amount in cents
+    pub amount: i64,
+    pub currency: String,
+    pub debtor: Option<QrBillAddress>,
+    pub reference: String,
+    pub message: String,
+}
+
+/// Whether the IBAN is a QR-IBAN (institution ID 30000-31999), which requires a QR reference
+pub fn is_qr_iban(iban: &str) -> bool {
+    let iban: String = iban.chars().filter(|c| !c.is_whitespace()).collect();
+    iban.get(4..9)
+        .and_then(|iid| iid.parse::<u32>().ok())
+        .is_some_and(|iid| (30000..=31999).contains(&iid))
+}
+
+/// Build a 27-digit QR reference (26 digits + mod 10 recursive check digit)
+pub fn qr_reference(seed: &str) -> String {
+    let digits: String = seed.chars().filter(|c| c.is_ascii_digit()).collect();
+    let start = digits.len().saturating_sub(26);
+    let body = format!("{:0>26}", &digits[start..]);
+    format!("{}{}", body, mod10_recursive(&body))
+}
+
+/// Build an ISO 11649 creditor reference ("RF" + check digits + reference)
+pub fn creditor_reference(seed: &str) -> String {
+    let reference: String = seed
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .take(21)
+        .collect();
+    let check = 98 - mod97(&format!("{}RF00", reference));
+    format!("RF{:02}{}", check, reference)
+}
+
+/// Payment reference matching the IBAN type (QR reference or creditor reference)
+pub fn payment_reference(iban: &str, seed: &str) -> String {
+    if is_qr_iban(iban) {
+        qr_reference(seed)
+    } else {
+        creditor_reference(seed)
+    }
+}
+
+/// Build the QR code payload ("SPC" format, version 0200)
+pub fn build_payload(data: &QrBillData) -> String {
+    let iban: String = data.iban.chars().filter(|c| !c.is_whitespace()).collect();
+    let reference_type = if data.reference.is_empty() {
+        "NON"
+    } else if data.reference.starts_with("RF") {
+        "SCOR"
+    } else {
+        "QRR"
+    };
+
+    let mut lines: Vec<String> = vec![
+        "SPC".into(),
+        "0200".into(),
+        "1".into(),
+        iban,
+    ];
+    lines.extend(address_lines(Some(&data.creditor)));
+    // Ultimate creditor (reserved for future use)
+    lines.extend(std::iter::repeat_n(String::new(), 7));
+    lines.push(format!("{}.{:02}", data.amount / 100, data.amount % 100));
+    lines.push(data.currency.to_uppercase());
+    lines.extend(address_lines(data.debtor.as_ref()));
+    lines.push(reference_type.into());
+    lines.push(data.reference.clone());
+    lines.push(data.message.chars().take(140).collect());
+    lines.push("EPD".into());
+
+    lines.join("\n")
+}
+
+fn address_lines(address: Option<&QrBillAddress>) -> Vec<String> {
+    match address {
+        Some(a) => vec![
+            "S".into(),
+            a.name.chars().take(70).collect(),
+            a.street.chars().take(70).collect(),
+            String::new(),
+            a.postal_code.chars().take(16).collect(),
+            a.town.chars().take(35).collect(),
+            a.country.to_uppercase(),
+        ],
+        None => vec![String::new(); 7],
+    }
+}
+
+fn mod10_recursive(digits: &str) -> u32 {
+    const TABLE: [u32; 10] = [0, 9, 4, 6, 8, 2, 7, 1, 3, 5];
+    let carry = digits
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .fold(0, |carry, d| TABLE[((carry + d) % 10) as usize]);
+    (10 - carry) % 10
+}
+
+fn mod97(input: &str) -> u32 {
+    input.chars().fold(0, |acc, c| {
+        let value = c.to_digit(36).unwrap_or(0);
+        if value >= 10 {
+            (acc * 100 + value) % 97
+        } else {
+            (acc * 10 + value) % 97
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_qr_reference() {
+        // Example from the Swiss implementation guidelines
+        assert_eq!(qr_reference("21000000000313947143000901"), "210000000003139471430009017");
+        assert_eq!(qr_reference("12345").len(), 27);
+    }
+
+    #[test]
+    fn test_creditor_reference() {
+        // Example from ISO 11649
+        assert_eq!(creditor_reference("539007547034"), "RF18539007547034");
+    }
+
+    #[test]
+    fn test_is_qr_iban() {
+        assert!(is_qr_iban("CH44 3199 9123 0008 8901 2"));
+        assert!(!is_qr_iban("CH93 0076 2011 6238 5295 7"));
+    }
+}
@@ -54,6 +54,56 @@ impl VatRate {
     }
 }
 
+/// Determine the VAT rate for a service supplied from `seller_country` to `buyer_country`.
+/// Domestic supplies use the seller's standard rate, cross-border B2B supplies fall under
+/// reverse charge and cross-border B2C supplies are taxed at the buyer's rate.
+pub fn vat_rate_for_supply(seller_country: &str, buyer_country: &str, buyer_has_vat_id: bool) -> VatRate {
+    let seller = VatRate::standard_for_country(seller_country);
+    let buyer = VatRate::standard_for_country(buyer_country);
+
+    if seller == buyer {
+        seller
+    } else if buyer_has_vat_id {
+        VatRate::Exempt
+    } else {
+        buyer
+    }
+}
+
+/// How a supply is treated for VAT purposes, which decides the return it is declared in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VatTreatment {
+    /// Seller and buyer in the same country: seller's national return
+    Domestic,
+    /// Cross-border B2B: buyer accounts for the VAT
+    ReverseCharge,
+    /// Cross-border B2C to an EU country: EU One-Stop-Shop
+    Oss,
+    /// Cross-border B2C to Switzerland: declared with the Swiss VAT registration (ESTV)
+    SwissRegistration,
+    /// Buyer country unknown or outside the DACH region
+    OutOfScope,
+}
+
+/// Determine the VAT treatment of a supply, consistent with [`vat_rate_for_supply`]
+pub fn vat_treatment(seller_country: &str, buyer_country: &str, buyer_has_vat_id: bool) -> VatTreatment {
+    let seller = VatRate::standard_for_country(seller_country);
+    let buyer = VatRate::standard_for_country(buyer_country);
+
+    if buyer == VatRate::Exempt {
+        VatTreatment::OutOfScope
+    } else if seller == buyer {
+        VatTreatment::Domestic
+    } else if buyer_has_vat_id {
+        VatTreatment::ReverseCharge
+    } else if buyer == VatRate::SwitzerlandStandard {
+        VatTreatment::SwissRegistration
+    } else {
+        VatTreatment::Oss
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VatCalculation {
     pub net_amount: Decimal,
@@ -107,6 +157,22 @@ pub fn calculate_net_from_gross(gross_amount: Decimal, vat_rate: VatRate) -> Vat
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_vat_rate_for_supply() {
+        assert_eq!(vat_rate_for_supply("CH", "ch", true), VatRate::SwitzerlandStandard);
+        assert_eq!(vat_rate_for_supply("CH", "DE", true), VatRate::Exempt);
+        assert_eq!(vat_rate_for_supply("CH", "AT", false), VatRate::AustriaStandard);
+    }
+
+    #[test]
+    fn test_vat_treatment() {
+        assert_eq!(vat_treatment("CH", "ch", false), VatTreatment::Domestic);
+        assert_eq!(vat_treatment("CH", "DE", true), VatTreatment::ReverseCharge);
+        assert_eq!(vat_treatment("CH", "AT", false), VatTreatment::Oss);
+        assert_eq!(vat_treatment("DE", "CH", false), VatTreatment::SwissRegistration);
+        assert_eq!(vat_treatment("DE", "US", false), VatTreatment::OutOfScope);
+    }
+
     #[test]
     fn test_swiss_vat() {
         let calc = calculate_vat(dec!(100), VatRate::SwitzerlandStandard);
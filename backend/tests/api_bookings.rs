@@ -0,0 +1,77 @@
+//! Booking API integration tests
+
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::json;
+
+/// Helper macro to skip test if database is not available
+macro_rules! require_db {
+    ($app:ident) => {
+        let Some($app) = common::TestApp::try_new().await else {
+            eprintln!("⚠️ Skipping test: Database not available");
+            return;
+        };
+    };
+}
+
+/// Register an expert available every day from 09:00 to 17:00; returns the user id and profile id
+async fn available_expert(app: &common::TestApp) -> (String, String) {
+    let (token, user_id) = common::register(app, "Expert").await;
+
+    let profile = app.post_auth("/api/v1/experts", &json!({
+        "headline": "n8n & Make.com Specialist",
+        "bio": "Ten years of automation experience across e-commerce and SaaS companies.",
+        "hourlyRate": 15000,
+        "currency": "CHF",
+        "yearsExperience": 10,
+        "skills": ["n8n", "Make"],
+        "tools": ["n8n", "Make.com"],
+        "languagesSpoken": ["de", "en"],
+        "availableHoursPerWeek": 20,
+        "timezone": "Europe/Zurich"
+    }), &token).await;
+    profile.assert_success();
+    let profile_id = profile.json()["data"]["id"].as_str().unwrap().to_string();
+
+    let slots: Vec<_> = (0..=6)
+        .map(|day| json!({ "dayOfWeek": day, "startTime": "09:00", "endTime": "17:00", "isAvailable": true }))
+        .collect();
+    app.put_auth("/api/v1/experts/me/availability", &json!({ "slots": slots }), &token)
+        .await
+        .assert_success();
+
+    (user_id, profile_id)
+}
+
+#[tokio::test]
+async fn test_book_slot_once() {
+    require_db!(app);
+    let (expert_id, profile_id) = available_expert(&app).await;
+    let (client_token, _) = common::register(&app, "Client").await;
+    let (other_token, _) = common::register(&app, "Client").await;
+
+    let day = (chrono::Utc::now() + chrono::Duration::days(7)).date_naive();
+    let slots = app.get(&format!("/api/v1/experts/{}/slots?from={}&to={}", profile_id, day, day)).await;
+    slots.assert_success();
+    let slot_start = slots.json()["data"]["slots"][0]["startsAt"].clone();
+    assert!(slot_start.is_string(), "No bookable slot: {}", slots.body);
+
+    let booking = json!({
+        "expertId": expert_id,
+        "message": "We would like to discuss automating our order processing with n8n in a first call.",
+        "currency": "CHF",
+        "slotStart": slot_start
+    });
+
+    app.post("/api/v1/bookings", &booking).await.assert_status(StatusCode::UNAUTHORIZED);
+
+    let response = app.post_auth("/api/v1/bookings", &booking, &client_token).await;
+    response.assert_status(StatusCode::CREATED);
+    assert_eq!(response.json()["data"]["slotStart"], slot_start);
+
+    // The slot is reserved until the expert responds
+    app.post_auth("/api/v1/bookings", &booking, &other_token)
+        .await
+        .assert_status(StatusCode::CONFLICT);
+}
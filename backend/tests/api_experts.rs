@@ -148,3 +148,194 @@ async fn test_search_experts() {
     assert!(json["data"].is_array());
 }
 
+
+/// Register an expert with a complete profile; returns the access token and profile id
+async fn expert_with_profile(app: &common::TestApp) -> (String, String) {
+    let register = app.post("/api/v1/auth/register", &json!({
+        "email": common::test_email(),
+        "password": "SecurePass123!",
+        "firstName": "Expert",
+        "lastName": "User",
+        "role": "Expert",
+        "country": "ch"
+    })).await;
+    register.assert_success();
+    let token = register.json()["data"]["accessToken"].as_str().unwrap().to_string();
+
+    let profile = app.post_auth("/api/v1/experts", &json!({
+        "headline": "n8n & Make.com Specialist",
+        "bio": "Ten years of automation experience across e-commerce and SaaS companies.",
+        "hourlyRate": 15000,
+        "currency": "CHF",
+        "yearsExperience": 10,
+        "skills": ["n8n", "Make"],
+        "tools": ["n8n", "Make.com"],
+        "languagesSpoken": ["de", "en"],
+        "availableHoursPerWeek": 20,
+        "timezone": "Europe/Zurich"
+    }), &token).await;
+    profile.assert_success();
+    let profile_id = profile.json()["data"]["id"].as_str().unwrap().to_string();
+
+    (token, profile_id)
+}
+
+fn portfolio_item(title: &str) -> serde_json::Value {
+    json!({
+        "title": title,
+        "description": "Automated order processing between shop and ERP."
+    })
+}
+
+#[tokio::test]
+async fn test_availability_requires_auth() {
+    require_db!(app);
+
+    let response = app.put_auth("/api/v1/experts/me/availability", &json!({ "slots": [] }), "invalid").await;
+    response.assert_status(StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_set_availability() {
+    require_db!(app);
+    let (token, profile_id) = expert_with_profile(&app).await;
+
+    let response = app.put_auth("/api/v1/experts/me/availability", &json!({
+        "slots": [
+            { "dayOfWeek": 1, "startTime": "09:00", "endTime": "12:00", "isAvailable": true },
+            { "dayOfWeek": 1, "startTime": "13:00", "endTime": "17:00", "isAvailable": true }
+        ]
+    }), &token).await;
+    response.assert_success();
+    assert_eq!(response.json()["data"].as_array().unwrap().len(), 2);
+
+    let availability = app.get(&format!("/api/v1/experts/{}/availability", profile_id)).await;
+    availability.assert_success();
+    assert_eq!(availability.json()["data"]["weeklySlots"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_set_availability_rejects_invalid_times() {
+    require_db!(app);
+    let (token, _) = expert_with_profile(&app).await;
+
+    let reversed = app.put_auth("/api/v1/experts/me/availability", &json!({
+        "slots": [{ "dayOfWeek": 2, "startTime": "17:00", "endTime": "09:00", "isAvailable": true }]
+    }), &token).await;
+    reversed.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+
+    let overlapping = app.put_auth("/api/v1/experts/me/availability", &json!({
+        "slots": [
+            { "dayOfWeek": 2, "startTime": "09:00", "endTime": "12:00", "isAvailable": true },
+            { "dayOfWeek": 2, "startTime": "11:00", "endTime": "14:00", "isAvailable": true }
+        ]
+    }), &token).await;
+    overlapping.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn test_block_and_unblock_dates() {
+    require_db!(app);
+    let (token, _) = expert_with_profile(&app).await;
+    let start = chrono::Utc::now().date_naive() + chrono::Duration::days(10);
+    let end = start + chrono::Duration::days(5);
+
+    let blocked = app.post_auth("/api/v1/experts/me/availability/blocked", &json!({
+        "startDate": start,
+        "endDate": end,
+        "reason": "Ferien"
+    }), &token).await;
+    blocked.assert_success();
+    let blocked_id = blocked.json()["data"]["id"].as_str().unwrap().to_string();
+
+    // Overlapping range
+    let overlapping = app.post_auth("/api/v1/experts/me/availability/blocked", &json!({
+        "startDate": end,
+        "endDate": end + chrono::Duration::days(3)
+    }), &token).await;
+    overlapping.assert_status(StatusCode::CONFLICT);
+
+    // Another expert cannot remove it
+    let (other_token, _) = expert_with_profile(&app).await;
+    let foreign = app.delete_auth(&format!("/api/v1/experts/me/availability/blocked/{}", blocked_id), &other_token).await;
+    foreign.assert_status(StatusCode::NOT_FOUND);
+
+    let unblocked = app.delete_auth(&format!("/api/v1/experts/me/availability/blocked/{}", blocked_id), &token).await;
+    unblocked.assert_success();
+}
+
+#[tokio::test]
+async fn test_portfolio_crud_and_reorder() {
+    require_db!(app);
+    let (token, profile_id) = expert_with_profile(&app).await;
+
+    let first = app.post_auth("/api/v1/experts/me/portfolio", &portfolio_item("Shop integration"), &token).await;
+    first.assert_success();
+    let first_id = first.json()["data"]["id"].as_str().unwrap().to_string();
+
+    let second = app.post_auth("/api/v1/experts/me/portfolio", &portfolio_item("CRM sync"), &token).await;
+    second.assert_success();
+    let second_id = second.json()["data"]["id"].as_str().unwrap().to_string();
+
+    let updated = app.put_auth(&format!("/api/v1/experts/me/portfolio/{}", first_id), &json!({
+        "title": "Shop and ERP integration"
+    }), &token).await;
+    updated.assert_success();
+    assert_eq!(updated.json()["data"]["title"], "Shop and ERP integration");
+
+    let reordered = app.put_auth("/api/v1/experts/me/portfolio/order", &json!({
+        "itemIds": [second_id, first_id]
+    }), &token).await;
+    reordered.assert_success();
+    let items = reordered.json()["data"].as_array().unwrap().clone();
+    assert_eq!(items[0]["id"], second_id.as_str());
+    assert_eq!(items[1]["sortOrder"], 1);
+
+    let deleted = app.delete_auth(&format!("/api/v1/experts/me/portfolio/{}", second_id), &token).await;
+    deleted.assert_success();
+
+    let portfolio = app.get(&format!("/api/v1/experts/{}/portfolio", profile_id)).await;
+    portfolio.assert_success();
+    assert_eq!(portfolio.json()["data"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_portfolio_ownership() {
+    require_db!(app);
+    let (token, _) = expert_with_profile(&app).await;
+    let (other_token, _) = expert_with_profile(&app).await;
+
+    let item = app.post_auth("/api/v1/experts/me/portfolio", &portfolio_item("Shop integration"), &token).await;
+    item.assert_success();
+    let item_id = item.json()["data"]["id"].as_str().unwrap().to_string();
+
+    let update = app.put_auth(&format!("/api/v1/experts/me/portfolio/{}", item_id), &json!({
+        "title": "Taken over"
+    }), &other_token).await;
+    update.assert_status(StatusCode::FORBIDDEN);
+
+    let delete = app.delete_auth(&format!("/api/v1/experts/me/portfolio/{}", item_id), &other_token).await;
+    delete.assert_status(StatusCode::FORBIDDEN);
+
+    let reorder = app.put_auth("/api/v1/experts/me/portfolio/order", &json!({
+        "itemIds": [item_id]
+    }), &other_token).await;
+    reorder.assert_status(StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_portfolio_featured_limit() {
+    require_db!(app);
+    let (token, _) = expert_with_profile(&app).await;
+
+    for i in 0..3 {
+        let mut item = portfolio_item(&format!("Featured {}", i));
+        item["isFeatured"] = json!(true);
+        app.post_auth("/api/v1/experts/me/portfolio", &item, &token).await.assert_success();
+    }
+
+    let mut item = portfolio_item("One too many");
+    item["isFeatured"] = json!(true);
+    let response = app.post_auth("/api/v1/experts/me/portfolio", &item, &token).await;
+    response.assert_status(StatusCode::CONFLICT);
+}
@@ -0,0 +1,92 @@
+//! Favourites API integration tests
+
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::json;
+use uuid::Uuid;
+
+/// Helper macro to skip test if database is not available
+macro_rules! require_db {
+    ($app:ident) => {
+        let Some($app) = common::TestApp::try_new().await else {
+            eprintln!("⚠️ Skipping test: Database not available");
+            return;
+        };
+    };
+}
+
+#[tokio::test]
+async fn test_shared_list_visible_to_organisation_members() {
+    require_db!(app);
+    let (admin_token, _) = common::register(&app, "Client").await;
+    let (buyer_token, buyer_id) = common::register(&app, "Client").await;
+    let (outsider_token, _) = common::register(&app, "Client").await;
+
+    let response = app.post_auth("/api/v1/organisations", &json!({ "name": "Muster AG" }), &admin_token).await;
+    response.assert_success();
+    let organisation_id = response.json()["data"]["id"].as_str().unwrap().to_string();
+    app.post_auth(&format!("/api/v1/organisations/{}/members", organisation_id), &json!({ "userId": buyer_id }), &admin_token)
+        .await
+        .assert_success();
+
+    let response = app.post_auth("/api/v1/favourites/lists", &json!({ "name": "ERP experts", "shared": true }), &buyer_token).await;
+    response.assert_success();
+    let list = response.json()["data"].clone();
+    assert_eq!(list["organisationId"], organisation_id.as_str());
+
+    let shared = app.get_auth("/api/v1/favourites/lists/shared", &admin_token).await;
+    shared.assert_success();
+    assert_eq!(shared.json()["data"][0]["id"], list["id"]);
+
+    let list_url = format!("/api/v1/favourites/lists/{}", list["id"].as_str().unwrap());
+    app.get_auth(&list_url, &admin_token).await.assert_success();
+    app.get_auth(&list_url, &outsider_token).await.assert_status(StatusCode::NOT_FOUND);
+
+    // Without an organisation there is no one to share with
+    app.post_auth("/api/v1/favourites/lists", &json!({ "name": "ERP experts", "shared": true }), &outsider_token)
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_saved_expert_available_again_notifies_followers() {
+    require_db!(app);
+    let (client_token, client_id) = common::register(&app, "Client").await;
+    let (expert_token, expert_id) = common::register(&app, "Expert").await;
+
+    let profile = app.post_auth("/api/v1/experts", &json!({
+        "headline": "n8n & Make.com Specialist",
+        "bio": "Ten years of automation experience across e-commerce and SaaS companies.",
+        "hourlyRate": 15000,
+        "currency": "CHF",
+        "yearsExperience": 10,
+        "skills": ["n8n", "Make"],
+        "tools": ["n8n", "Make.com"],
+        "languagesSpoken": ["de", "en"],
+        "availableHoursPerWeek": 20,
+        "timezone": "Europe/Zurich"
+    }), &expert_token).await;
+    profile.assert_success();
+    let profile_url = format!("/api/v1/experts/{}", profile.json()["data"]["id"].as_str().unwrap());
+
+    app.post_auth("/api/v1/favourites/experts", &json!({ "expertId": expert_id }), &client_token)
+        .await
+        .assert_success();
+
+    app.put_auth(&profile_url, &json!({ "availabilityStatus": "Busy" }), &expert_token)
+        .await
+        .assert_success();
+    app.put_auth(&profile_url, &json!({ "availabilityStatus": "Available" }), &expert_token)
+        .await
+        .assert_success();
+
+    let notifications: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM notifications WHERE user_id = $1 AND type = 'saved_expert_available'"
+    )
+    .bind(Uuid::parse_str(&client_id).unwrap())
+    .fetch_one(app.db.pool())
+    .await
+    .unwrap();
+    assert_eq!(notifications, 1);
+}
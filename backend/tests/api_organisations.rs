@@ -0,0 +1,117 @@
+//! Client organisation API integration tests
+
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::json;
+use uuid::Uuid;
+
+use dach_marketplace_api::models::{BillingMode, CreateProjectRequest, PurchaseSubject};
+use dach_marketplace_api::services::{OrganisationError, OrganisationService, ProjectService, ServiceOrder};
+
+/// Helper macro to skip test if database is not available
+macro_rules! require_db {
+    ($app:ident) => {
+        let Some($app) = common::TestApp::try_new().await else {
+            eprintln!("⚠️ Skipping test: Database not available");
+            return;
+        };
+    };
+}
+
+/// Organisation with a buyer limited to `spending_limit` cents per month
+struct OrganisationFixture {
+    buyer_token: String,
+    buyer_id: Uuid,
+}
+
+async fn organisation_fixture(app: &common::TestApp, spending_limit: i32) -> OrganisationFixture {
+    let (admin_token, _) = common::register(app, "Client").await;
+    let (buyer_token, buyer_id) = common::register(app, "Client").await;
+
+    let response = app.post_auth("/api/v1/organisations", &json!({ "name": "Muster AG" }), &admin_token).await;
+    response.assert_success();
+    let organisation_id = response.json()["data"]["id"].as_str().unwrap().to_string();
+
+    app.post_auth(&format!("/api/v1/organisations/{}/members", organisation_id), &json!({
+        "userId": buyer_id,
+        "role": "buyer",
+        "spendingLimit": spending_limit
+    }), &admin_token)
+    .await
+    .assert_success();
+
+    OrganisationFixture { buyer_token, buyer_id: buyer_id.parse().unwrap() }
+}
+
+async fn create_project(app: &common::TestApp, token: &str, expert_id: &str, budget: i32) -> common::TestResponse {
+    app.post_auth("/api/v1/projects", &json!({
+        "expertId": expert_id,
+        "title": "Automate order processing",
+        "description": "Connect the web shop with the ERP so that new orders are created automatically.",
+        "budget": budget,
+        "currency": "CHF"
+    }), token).await
+}
+
+#[tokio::test]
+async fn test_open_checkouts_count_against_spending_limit() {
+    require_db!(app);
+    let fixture = organisation_fixture(&app, 100000).await;
+    let (_, expert_id) = common::register(&app, "Expert").await;
+
+    create_project(&app, &fixture.buyer_token, &expert_id, 60000).await.assert_success();
+
+    // A service checkout creates its project accepted and waiting for payment
+    let response = create_project(&app, &fixture.buyer_token, &expert_id, 30000).await;
+    response.assert_success();
+    let checkout_project_id: Uuid = response.json()["data"]["id"].as_str().unwrap().parse().unwrap();
+    sqlx::query("UPDATE projects SET status = 'accepted' WHERE id = $1")
+        .bind(checkout_project_id)
+        .execute(app.db.pool())
+        .await
+        .unwrap();
+
+    let response = create_project(&app, &fixture.buyer_token, &expert_id, 20000).await;
+    response.assert_status(StatusCode::FORBIDDEN);
+
+    // An expired checkout cancels its project, which no longer counts
+    ProjectService::cancel_unpaid(&app.db, checkout_project_id, "Checkout expired").await.unwrap().unwrap();
+    create_project(&app, &fixture.buyer_token, &expert_id, 20000).await.assert_success();
+}
+
+#[tokio::test]
+async fn test_concurrent_purchases_respect_spending_limit() {
+    require_db!(app);
+    let fixture = organisation_fixture(&app, 100000).await;
+    let (_, expert_id) = common::register(&app, "Expert").await;
+    let subject = PurchaseSubject::default();
+    let request: CreateProjectRequest = serde_json::from_value(json!({
+        "expertId": expert_id,
+        "title": "Automate order processing",
+        "description": "Connect the web shop with the ERP so that new orders are created automatically.",
+        "budget": 80000,
+        "currency": "CHF"
+    }))
+    .unwrap();
+
+    let mut first = app.db.pool().begin().await.unwrap();
+    OrganisationService::authorize_purchase(&mut first, fixture.buyer_id, subject, 80000).await.unwrap();
+    ProjectService::create_tx(&mut first, fixture.buyer_id, &request, BillingMode::Card, None, &ServiceOrder::default())
+        .await
+        .unwrap();
+
+    // The second purchase waits for the first one's member lock and then sees its spend
+    let db = app.db.clone();
+    let buyer_id = fixture.buyer_id;
+    let second = tokio::spawn(async move {
+        let mut tx = db.pool().begin().await.unwrap();
+        OrganisationService::authorize_purchase(&mut tx, buyer_id, subject, 80000).await
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    assert!(!second.is_finished(), "The second purchase must wait for the first");
+    first.commit().await.unwrap();
+
+    let result = second.await.unwrap();
+    assert!(matches!(result, Err(OrganisationError::Forbidden(_))), "Got {:?}", result);
+}
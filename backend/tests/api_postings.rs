@@ -0,0 +1,269 @@
+//! Project postings and proposals API integration tests
+
+mod common;
+
+use axum::http::StatusCode;
+use dach_marketplace_api::config::{PostingSettings, ProposalSettings};
+use dach_marketplace_api::models::CreateProposalRequest;
+use dach_marketplace_api::services::{PostingService, ProposalService};
+use serde_json::json;
+
+/// Helper macro to skip test if database is not available
+macro_rules! require_db {
+    ($app:ident) => {
+        let Some($app) = common::TestApp::try_new().await else {
+            eprintln!("⚠️ Skipping test: Database not available");
+            return;
+        };
+    };
+}
+
+/// Create a posting as the given client; returns its id
+async fn create_posting(app: &common::TestApp, client_token: &str, visibility: &str) -> String {
+    let response = app.post_auth("/api/v1/postings", &json!({
+        "title": "Migrate invoices to the new ERP",
+        "description": "We need all open and archived invoices moved from the legacy system into the new ERP.",
+        "budgetType": "Fixed",
+        "budgetMin": 200000,
+        "budgetMax": 300000,
+        "currency": "CHF",
+        "visibility": visibility
+    }), client_token).await;
+    response.assert_success();
+
+    response.json()["data"]["id"].as_str().unwrap().to_string()
+}
+
+/// Submit a proposal as the given expert; returns its id
+async fn submit_proposal(app: &common::TestApp, expert_token: &str, posting_id: &str) -> String {
+    let response = app.post_auth(&format!("/api/v1/postings/{}/proposals", posting_id), &json!({
+        "projectPostingId": posting_id,
+        "coverLetter": "I have migrated invoice data between several ERP systems and can take over the complete migration including a validation of all totals afterwards.",
+        "proposedPrice": 250000,
+        "currency": "CHF",
+        "proposedDuration": "3 weeks"
+    }), expert_token).await;
+    response.assert_success();
+
+    response.json()["data"]["id"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn test_proposal_routes_require_auth() {
+    require_db!(app);
+    let (client_token, _) = common::register(&app, "Client").await;
+    let (expert_token, _) = common::register(&app, "Expert").await;
+    let posting_id = create_posting(&app, &client_token, "public").await;
+    let proposal_id = submit_proposal(&app, &expert_token, &posting_id).await;
+
+    let response = app.get(&format!("/api/v1/postings/{}/proposals/compare", posting_id)).await;
+    response.assert_status(StatusCode::UNAUTHORIZED);
+
+    let response = app.get(&format!("/api/v1/postings/proposals/{}", proposal_id)).await;
+    response.assert_status(StatusCode::UNAUTHORIZED);
+
+    for action in ["shortlist", "accept"] {
+        let response = app
+            .post(&format!("/api/v1/postings/proposals/{}/{}", proposal_id, action), &json!({}))
+            .await;
+        response.assert_status(StatusCode::UNAUTHORIZED);
+    }
+
+    // The posting itself stays public
+    app.get(&format!("/api/v1/postings/{}", posting_id)).await.assert_success();
+}
+
+#[tokio::test]
+async fn test_accept_proposal_only_by_posting_owner() {
+    require_db!(app);
+    let (client_token, _) = common::register(&app, "Client").await;
+    let (other_token, _) = common::register(&app, "Client").await;
+    let (expert_token, _) = common::register(&app, "Expert").await;
+    let posting_id = create_posting(&app, &client_token, "public").await;
+    let proposal_id = submit_proposal(&app, &expert_token, &posting_id).await;
+    let accept_url = format!("/api/v1/postings/proposals/{}/accept", proposal_id);
+
+    let response = app.post_auth(&accept_url, &json!({}), &other_token).await;
+    response.assert_status(StatusCode::NOT_FOUND);
+
+    let response = app.post_auth(&accept_url, &json!({}), &expert_token).await;
+    response.assert_status(StatusCode::NOT_FOUND);
+
+    // Still acceptable by the owner afterwards
+    let response = app.post_auth(&accept_url, &json!({}), &client_token).await;
+    response.assert_success();
+}
+
+#[tokio::test]
+async fn test_accept_proposal_creates_project_and_conversation() {
+    require_db!(app);
+    let (client_token, _) = common::register(&app, "Client").await;
+    let (expert_token, expert_id) = common::register(&app, "Expert").await;
+    let (other_expert_token, _) = common::register(&app, "Expert").await;
+    let posting_id = create_posting(&app, &client_token, "public").await;
+    let proposal_id = submit_proposal(&app, &expert_token, &posting_id).await;
+    let other_proposal_id = submit_proposal(&app, &other_expert_token, &posting_id).await;
+
+    let response = app
+        .post_auth(&format!("/api/v1/postings/proposals/{}/accept", proposal_id), &json!({}), &client_token)
+        .await;
+    response.assert_success();
+    let acceptance = response.json()["data"].clone();
+
+    assert_eq!(acceptance["proposal"]["status"], "Accepted");
+    assert_eq!(acceptance["project"]["expertId"], expert_id.as_str());
+    assert_eq!(acceptance["project"]["status"], "Accepted");
+    assert_eq!(acceptance["project"]["price"], 250000);
+    assert_eq!(acceptance["rejectedProposals"], 1);
+
+    // The kick-off message is in the conversation returned with the acceptance
+    let conversation_id: uuid::Uuid = acceptance["conversationId"].as_str().unwrap().parse().unwrap();
+    let messages: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM messages WHERE conversation_id = $1")
+        .bind(conversation_id)
+        .fetch_one(app.db.pool())
+        .await
+        .unwrap();
+    assert_eq!(messages, 1);
+
+    let response = app.get_auth(&format!("/api/v1/postings/proposals/{}", other_proposal_id), &other_expert_token).await;
+    response.assert_success();
+    assert_eq!(response.json()["data"]["status"], "Rejected");
+
+    // A second acceptance is refused
+    let response = app
+        .post_auth(&format!("/api/v1/postings/proposals/{}/accept", other_proposal_id), &json!({}), &client_token)
+        .await;
+    response.assert_status(StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_concurrent_proposals_respect_the_limit() {
+    require_db!(app);
+    let (client_token, _) = common::register(&app, "Client").await;
+    let (_, expert_id) = common::register(&app, "Expert").await;
+    let expert_id: uuid::Uuid = expert_id.parse().unwrap();
+    let first_posting = create_posting(&app, &client_token, "public").await;
+    let second_posting = create_posting(&app, &client_token, "public").await;
+    let settings = ProposalSettings { max_per_period: 1, period_days: 30 };
+
+    let request = |posting_id: &str| -> CreateProposalRequest {
+        serde_json::from_value(json!({
+            "projectPostingId": posting_id,
+            "coverLetter": "I can take this over.",
+            "proposedPrice": 250000,
+            "currency": "CHF"
+        }))
+        .unwrap()
+    };
+
+    let (first, second) = tokio::join!(
+        ProposalService::submit(&app.db, expert_id, &settings, request(&first_posting)),
+        ProposalService::submit(&app.db, expert_id, &settings, request(&second_posting)),
+    );
+    assert_eq!(
+        [first.is_ok(), second.is_ok()].into_iter().filter(|ok| *ok).count(),
+        1,
+        "Exactly one of the concurrent proposals may be submitted"
+    );
+}
+
+/// Create the expert profile invitations require
+async fn create_expert_profile(app: &common::TestApp, expert_token: &str) {
+    app.post_auth("/api/v1/experts", &json!({
+        "headline": "ERP migration specialist",
+        "bio": "Ten years of data migrations between accounting and ERP systems.",
+        "hourlyRate": 15000,
+        "currency": "CHF",
+        "yearsExperience": 10,
+        "skills": ["ERP"],
+        "tools": ["SAP"],
+        "languagesSpoken": ["de", "en"],
+        "availableHoursPerWeek": 20,
+        "timezone": "Europe/Zurich"
+    }), expert_token)
+    .await
+    .assert_success();
+}
+
+/// Invite the given expert to a posting
+async fn invite(app: &common::TestApp, client_token: &str, posting_id: &str, expert_id: &str) {
+    app.post_auth(&format!("/api/v1/postings/{}/invitations", posting_id), &json!({
+        "expertIds": [expert_id]
+    }), client_token)
+    .await
+    .assert_success();
+}
+
+#[tokio::test]
+async fn test_invite_only_posting_visible_to_owner_and_invited_experts() {
+    require_db!(app);
+    let (client_token, _) = common::register(&app, "Client").await;
+    let (invited_token, invited_id) = common::register(&app, "Expert").await;
+    let (other_token, _) = common::register(&app, "Expert").await;
+    let posting_id = create_posting(&app, &client_token, "invite_only").await;
+    create_expert_profile(&app, &invited_token).await;
+    invite(&app, &client_token, &posting_id, &invited_id).await;
+    let posting_url = format!("/api/v1/postings/{}", posting_id);
+
+    app.get(&posting_url).await.assert_status(StatusCode::NOT_FOUND);
+    app.get_auth(&posting_url, &other_token).await.assert_status(StatusCode::NOT_FOUND);
+    app.get_auth(&posting_url, &client_token).await.assert_success();
+    app.get_auth(&posting_url, &invited_token).await.assert_success();
+}
+
+#[tokio::test]
+async fn test_invite_only_posting_takes_proposals_from_invited_experts() {
+    require_db!(app);
+    let (client_token, _) = common::register(&app, "Client").await;
+    let (invited_token, invited_id) = common::register(&app, "Expert").await;
+    let (other_token, _) = common::register(&app, "Expert").await;
+    let posting_id = create_posting(&app, &client_token, "invite_only").await;
+    create_expert_profile(&app, &invited_token).await;
+    invite(&app, &client_token, &posting_id, &invited_id).await;
+
+    let response = app.post_auth(&format!("/api/v1/postings/{}/proposals", posting_id), &json!({
+        "projectPostingId": posting_id,
+        "coverLetter": "I have migrated invoice data between several ERP systems and can take over the complete migration including a validation of all totals afterwards.",
+        "proposedPrice": 250000,
+        "currency": "CHF"
+    }), &other_token).await;
+    response.assert_status(StatusCode::FORBIDDEN);
+
+    submit_proposal(&app, &invited_token, &posting_id).await;
+
+    // Submitting the proposal accepted the invitation
+    let invitations = app.get_auth("/api/v1/postings/invitations", &invited_token).await;
+    invitations.assert_success();
+    assert_eq!(invitations.json()["data"][0]["status"], "accepted");
+}
+
+#[tokio::test]
+async fn test_posting_expires_and_is_extended() {
+    require_db!(app);
+    let (client_token, _) = common::register(&app, "Client").await;
+    let posting_id = create_posting(&app, &client_token, "public").await;
+    let posting_url = format!("/api/v1/postings/{}", posting_id);
+
+    sqlx::query("UPDATE project_postings SET expires_at = NOW() - INTERVAL '1 day' WHERE id = $1")
+        .bind(uuid::Uuid::parse_str(&posting_id).unwrap())
+        .execute(app.db.pool())
+        .await
+        .unwrap();
+
+    let settings = PostingSettings {
+        expiry_enabled: true,
+        expiry_interval_minutes: 60,
+        lifetime_days: 30,
+        close_after_days: 14,
+    };
+    let expired = PostingService::expire_due(&app.db, &settings).await.unwrap();
+    assert!(expired.iter().any(|posting| posting.id.to_string() == posting_id));
+
+    let response = app.get_auth(&posting_url, &client_token).await;
+    response.assert_success();
+    assert_eq!(response.json()["data"]["status"], "Expired");
+
+    let response = app.post_auth(&format!("{}/extend", posting_url), &json!({ "days": 7 }), &client_token).await;
+    response.assert_success();
+    assert_eq!(response.json()["data"]["status"], "Open");
+}
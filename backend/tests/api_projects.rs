@@ -0,0 +1,643 @@
+//! Projects API integration tests
+
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::json;
+use uuid::Uuid;
+
+use dach_marketplace_api::models::PaymentStatus;
+use dach_marketplace_api::services::{AutoCompleteService, CheckoutPayment, PaymentService, TipService};
+
+/// Helper macro to skip test if database is not available
+macro_rules! require_db {
+    ($app:ident) => {
+        let Some($app) = common::TestApp::try_new().await else {
+            eprintln!("⚠️ Skipping test: Database not available");
+            return;
+        };
+    };
+}
+
+/// Client and expert with a pending project between them
+struct ProjectFixture {
+    client_token: String,
+    client_id: String,
+    expert_token: String,
+    expert_id: String,
+    project_id: String,
+}
+
+async fn project_fixture(app: &common::TestApp) -> ProjectFixture {
+    let (client_token, client_id) = common::register(app, "Client").await;
+    let (expert_token, expert_id) = common::register(app, "Expert").await;
+
+    let response = app.post_auth("/api/v1/projects", &json!({
+        "expertId": expert_id,
+        "title": "Automate order processing",
+        "description": "Connect the web shop with the ERP so that new orders are created automatically.",
+        "budget": 100000,
+        "currency": "CHF"
+    }), &client_token).await;
+    response.assert_success();
+    let project_id = response.json()["data"]["id"].as_str().unwrap().to_string();
+
+    ProjectFixture { client_token, client_id, expert_token, expert_id, project_id }
+}
+
+/// Record a successful card payment for the project; returns the payment id
+async fn insert_card_payment(app: &common::TestApp, fixture: &ProjectFixture, amount: i32) -> Uuid {
+    sqlx::query_scalar(
+        r#"
+        INSERT INTO payments (project_id, payer_id, payee_id, amount, currency, platform_fee, net_amount,
+                              status, stripe_payment_intent_id, paid_at)
+        VALUES ($1, $2, $3, $4, 'CHF', $5, $4 - $5, 'succeeded', $6, NOW())
+        RETURNING id
+        "#,
+    )
+    .bind(Uuid::parse_str(&fixture.project_id).unwrap())
+    .bind(Uuid::parse_str(&fixture.client_id).unwrap())
+    .bind(Uuid::parse_str(&fixture.expert_id).unwrap())
+    .bind(amount)
+    .bind(amount / 10)
+    .bind(format!("pi_test_{}", Uuid::new_v4().simple()))
+    .fetch_one(app.db.pool())
+    .await
+    .unwrap()
+}
+
+/// Move a project to a status directly, bypassing the lifecycle checks
+async fn force_status(app: &common::TestApp, project_id: &str, status: &str) {
+    sqlx::query("UPDATE projects SET status = $2::project_status WHERE id = $1")
+        .bind(Uuid::parse_str(project_id).unwrap())
+        .bind(status)
+        .execute(app.db.pool())
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_project_routes_require_auth() {
+    require_db!(app);
+
+    let id = Uuid::new_v4();
+    app.get("/api/v1/projects").await.assert_status(StatusCode::UNAUTHORIZED);
+    app.get(&format!("/api/v1/projects/{}/timeline", id)).await.assert_status(StatusCode::UNAUTHORIZED);
+    app.get(&format!("/api/v1/projects/{}/deliverables", id)).await.assert_status(StatusCode::UNAUTHORIZED);
+    app.get(&format!("/api/v1/projects/{}/requirements", id)).await.assert_status(StatusCode::UNAUTHORIZED);
+    app.get(&format!("/api/v1/projects/{}/contracts", id)).await.assert_status(StatusCode::UNAUTHORIZED);
+    app.get(&format!("/api/v1/projects/{}/change-requests", id)).await.assert_status(StatusCode::UNAUTHORIZED);
+    app.post(&format!("/api/v1/projects/{}/cancel", id), &json!({}))
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_project_hidden_from_other_users() {
+    require_db!(app);
+    let fixture = project_fixture(&app).await;
+    let (stranger_token, _) = common::register(&app, "Client").await;
+
+    app.get_auth(&format!("/api/v1/projects/{}", fixture.project_id), &stranger_token)
+        .await
+        .assert_status(StatusCode::FORBIDDEN);
+    app.get_auth(&format!("/api/v1/projects/{}/timeline", fixture.project_id), &stranger_token)
+        .await
+        .assert_status(StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_timeline_starts_with_creation() {
+    require_db!(app);
+    let fixture = project_fixture(&app).await;
+
+    let response = app
+        .get_auth(&format!("/api/v1/projects/{}/timeline", fixture.project_id), &fixture.expert_token)
+        .await;
+    response.assert_success();
+
+    let events = response.json()["data"]["data"].as_array().unwrap().clone();
+    assert!(events.iter().any(|e| e["eventType"] == "project_created"));
+}
+
+#[tokio::test]
+async fn test_experts_define_cancellation_policies() {
+    require_db!(app);
+    let (client_token, _) = common::register(&app, "Client").await;
+    let (expert_token, _) = common::register(&app, "Expert").await;
+
+    let policy = json!({
+        "name": "Flexible",
+        "freeCancellationHours": 48,
+        "refundBeforeStartPercent": 100,
+        "refundInProgressPercent": 50,
+        "refundAfterDeliveryPercent": 0,
+        "expertCancelRefundPercent": 100,
+        "refundPlatformFee": false
+    });
+
+    app.post("/api/v1/cancellation-policies", &policy).await.assert_status(StatusCode::UNAUTHORIZED);
+    app.post_auth("/api/v1/cancellation-policies", &policy, &client_token)
+        .await
+        .assert_status(StatusCode::FORBIDDEN);
+
+    let response = app.post_auth("/api/v1/cancellation-policies", &policy, &expert_token).await;
+    response.assert_success();
+    let policy_id = response.json()["data"]["id"].clone();
+
+    let policies = app.get_auth("/api/v1/cancellation-policies", &expert_token).await;
+    policies.assert_success();
+    assert!(policies.json()["data"].as_array().unwrap().iter().any(|p| p["id"] == policy_id));
+}
+
+#[tokio::test]
+async fn test_cancel_pending_project() {
+    require_db!(app);
+    let fixture = project_fixture(&app).await;
+    let base = format!("/api/v1/projects/{}", fixture.project_id);
+
+    let preview = app.get_auth(&format!("{}/cancellation/preview", base), &fixture.client_token).await;
+    preview.assert_success();
+    assert_eq!(preview.json()["data"]["paidAmount"], 0);
+
+    app.get_auth(&format!("{}/cancellation", base), &fixture.client_token)
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    let response = app
+        .post_auth(&format!("{}/cancel", base), &json!({ "reason": "Plans changed" }), &fixture.client_token)
+        .await;
+    response.assert_success();
+    assert_eq!(response.json()["data"]["status"], "Cancelled");
+
+    app.get_auth(&format!("{}/cancellation", base), &fixture.expert_token)
+        .await
+        .assert_success();
+}
+
+/// Without a working gateway the refund fails and the cancellation is rolled back
+#[cfg(feature = "payments")]
+#[tokio::test]
+async fn test_cancel_keeps_project_when_refund_fails() {
+    require_db!(app);
+    let fixture = project_fixture(&app).await;
+    force_status(&app, &fixture.project_id, "paid").await;
+    let payment_id = insert_card_payment(&app, &fixture, 100000).await;
+
+    let response = app
+        .post_auth(&format!("/api/v1/projects/{}/cancel", fixture.project_id), &json!({}), &fixture.client_token)
+        .await;
+    assert!(response.status.is_server_error(), "Expected refund failure, got {}", response.status);
+
+    let project = app.get_auth(&format!("/api/v1/projects/{}", fixture.project_id), &fixture.client_token).await;
+    assert_eq!(project.json()["data"]["status"], "Paid");
+
+    let (status, refund_amount): (String, Option<i32>) =
+        sqlx::query_as("SELECT status::text, refund_amount FROM payments WHERE id = $1")
+            .bind(payment_id)
+            .fetch_one(app.db.pool())
+            .await
+            .unwrap();
+    assert_eq!(status, "succeeded");
+    assert_eq!(refund_amount.unwrap_or(0), 0);
+}
+
+#[tokio::test]
+async fn test_deliverables_start_empty() {
+    require_db!(app);
+    let fixture = project_fixture(&app).await;
+
+    let response = app
+        .get_auth(&format!("/api/v1/projects/{}/deliverables", fixture.project_id), &fixture.client_token)
+        .await;
+    response.assert_success();
+    assert_eq!(response.json()["data"].as_array().unwrap().len(), 0);
+}
+
+#[tokio::test]
+async fn test_delivery_requires_uploaded_deliverable() {
+    require_db!(app);
+    let fixture = project_fixture(&app).await;
+    force_status(&app, &fixture.project_id, "in_progress").await;
+    let status_url = format!("/api/v1/projects/{}/status", fixture.project_id);
+
+    let response = app.put_auth(&status_url, &json!({ "status": "Delivered" }), &fixture.expert_token).await;
+    response.assert_status(StatusCode::CONFLICT);
+
+    // A presigned upload that was never confirmed does not count
+    let deliverable_id: Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO project_deliverables (project_id, title, file_name, file_type, file_size, storage_key, uploaded_by)
+        VALUES ($1, 'Export', 'export.csv', 'text/csv', 0, 'deliverables/export.csv', $2)
+        RETURNING id
+        "#,
+    )
+    .bind(Uuid::parse_str(&fixture.project_id).unwrap())
+    .bind(Uuid::parse_str(&fixture.expert_id).unwrap())
+    .fetch_one(app.db.pool())
+    .await
+    .unwrap();
+
+    let response = app.put_auth(&status_url, &json!({ "status": "Delivered" }), &fixture.expert_token).await;
+    response.assert_status(StatusCode::CONFLICT);
+
+    sqlx::query("UPDATE project_deliverables SET uploaded_at = NOW(), file_size = 2048 WHERE id = $1")
+        .bind(deliverable_id)
+        .execute(app.db.pool())
+        .await
+        .unwrap();
+
+    let response = app.put_auth(&status_url, &json!({ "status": "Delivered" }), &fixture.expert_token).await;
+    response.assert_success();
+    assert_eq!(response.json()["data"]["status"], "Delivered");
+}
+
+#[tokio::test]
+async fn test_requirements_without_form() {
+    require_db!(app);
+    let fixture = project_fixture(&app).await;
+    let url = format!("/api/v1/projects/{}/requirements", fixture.project_id);
+
+    let response = app.get_auth(&url, &fixture.client_token).await;
+    response.assert_success();
+    assert_eq!(response.json()["data"]["missing"].as_array().unwrap().len(), 0);
+
+    // Only the client answers the requirements
+    app.put_auth(&url, &json!({ "answers": {} }), &fixture.expert_token)
+        .await
+        .assert_status(StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_secret_answers_encrypted_at_rest() {
+    require_db!(app);
+    let fixture = project_fixture(&app).await;
+    let project_id = Uuid::parse_str(&fixture.project_id).unwrap();
+    sqlx::query("UPDATE projects SET requirements_form = $2 WHERE id = $1")
+        .bind(project_id)
+        .bind(json!([{ "id": "password", "label": "Shop admin password", "type": "secret", "required": true }]))
+        .execute(app.db.pool())
+        .await
+        .unwrap();
+    let url = format!("/api/v1/projects/{}/requirements", fixture.project_id);
+
+    let response = app.put_auth(&url, &json!({ "answers": { "password": "hunter2-secret" } }), &fixture.client_token).await;
+    response.assert_success();
+
+    let (value, encrypted): (Option<serde_json::Value>, Option<Vec<u8>>) = sqlx::query_as(
+        "SELECT value, encrypted_value FROM project_requirement_answers WHERE project_id = $1 AND field_id = 'password'",
+    )
+    .bind(project_id)
+    .fetch_one(app.db.pool())
+    .await
+    .unwrap();
+    assert!(value.is_none());
+    let encrypted = encrypted.unwrap();
+    assert!(!encrypted.windows(b"hunter2".len()).any(|w| w == b"hunter2"));
+
+    let response = app.get_auth(&url, &fixture.expert_token).await;
+    response.assert_success();
+    assert_eq!(response.json()["data"]["answers"][0]["value"], "hunter2-secret");
+    assert_eq!(response.json()["data"]["missing"].as_array().unwrap().len(), 0);
+
+    let response = app.get_auth(&url, &fixture.client_token).await;
+    let answer = response.json()["data"]["answers"][0].clone();
+    assert!(answer["value"].is_null());
+    assert_eq!(answer["isHidden"], true);
+}
+
+#[tokio::test]
+async fn test_change_request_lifecycle() {
+    require_db!(app);
+    let fixture = project_fixture(&app).await;
+    let base = format!("/api/v1/projects/{}/change-requests", fixture.project_id);
+    force_status(&app, &fixture.project_id, "in_progress").await;
+
+    let created = app.post_auth(&base, &json!({
+        "description": "Also connect the warehouse system.",
+        "newPrice": 150000
+    }), &fixture.expert_token).await;
+    created.assert_success();
+    let change_id = created.json()["data"]["id"].as_str().unwrap().to_string();
+
+    // The requester cannot answer their own change request
+    app.post_auth(&format!("{}/{}/reject", base, change_id), &json!({}), &fixture.expert_token)
+        .await
+        .assert_status(StatusCode::FORBIDDEN);
+
+    app.post_auth(&format!("{}/{}/reject", base, change_id), &json!({ "message": "Not now" }), &fixture.client_token)
+        .await
+        .assert_success();
+
+    let list = app.get_auth(&base, &fixture.client_token).await;
+    list.assert_success();
+    assert_eq!(list.json()["data"][0]["status"], "rejected");
+}
+
+/// Without a working gateway a price decrease cannot be refunded and the change stays pending
+#[cfg(feature = "payments")]
+#[tokio::test]
+async fn test_price_decrease_stays_pending_when_refund_fails() {
+    require_db!(app);
+    let fixture = project_fixture(&app).await;
+    let base = format!("/api/v1/projects/{}/change-requests", fixture.project_id);
+    force_status(&app, &fixture.project_id, "in_progress").await;
+    let payment_id = insert_card_payment(&app, &fixture, 100000).await;
+
+    let created = app.post_auth(&base, &json!({
+        "description": "Drop the archive migration.",
+        "newPrice": 60000
+    }), &fixture.client_token).await;
+    created.assert_success();
+    let change_id = created.json()["data"]["id"].as_str().unwrap().to_string();
+
+    let response = app
+        .post_auth(&format!("{}/{}/accept", base, change_id), &json!({}), &fixture.expert_token)
+        .await;
+    assert!(response.status.is_server_error(), "Expected refund failure, got {}", response.status);
+
+    let list = app.get_auth(&base, &fixture.client_token).await;
+    assert_eq!(list.json()["data"][0]["status"], "pending");
+
+    let project = app.get_auth(&format!("/api/v1/projects/{}", fixture.project_id), &fixture.client_token).await;
+    assert_eq!(project.json()["data"]["price"], 100000);
+
+    let (status, refund_amount): (String, Option<i32>) =
+        sqlx::query_as("SELECT status::text, refund_amount FROM payments WHERE id = $1")
+            .bind(payment_id)
+            .fetch_one(app.db.pool())
+            .await
+            .unwrap();
+    assert_eq!(status, "succeeded");
+    assert_eq!(refund_amount.unwrap_or(0), 0);
+}
+
+#[tokio::test]
+async fn test_contracts_require_open_project() {
+    require_db!(app);
+    let fixture = project_fixture(&app).await;
+    let base = format!("/api/v1/projects/{}/contracts", fixture.project_id);
+
+    let list = app.get_auth(&base, &fixture.client_token).await;
+    list.assert_success();
+    assert_eq!(list.json()["data"].as_array().unwrap().len(), 0);
+
+    // Contracts are generated from the admin's active templates
+    app.post_auth(&base, &json!({ "kind": "nda" }), &fixture.expert_token)
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    force_status(&app, &fixture.project_id, "cancelled").await;
+    app.post_auth(&base, &json!({ "kind": "nda" }), &fixture.client_token)
+        .await
+        .assert_status(StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_tips_only_for_completed_projects() {
+    require_db!(app);
+    let fixture = project_fixture(&app).await;
+    let url = format!("/api/v1/projects/{}/tips/options", fixture.project_id);
+
+    app.get_auth(&url, &fixture.client_token)
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+
+    force_status(&app, &fixture.project_id, "completed").await;
+
+    app.get_auth(&url, &fixture.expert_token)
+        .await
+        .assert_status(StatusCode::FORBIDDEN);
+    let response = app.get_auth(&url, &fixture.client_token).await;
+    response.assert_success();
+    assert!(!response.json()["data"]["presets"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_tip_paid_once_for_concurrent_webhooks() {
+    require_db!(app);
+    let fixture = project_fixture(&app).await;
+    force_status(&app, &fixture.project_id, "completed").await;
+
+    let project_id = Uuid::parse_str(&fixture.project_id).unwrap();
+    let project = dach_marketplace_api::services::ProjectService::get_by_id(&app.db, project_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let tip = TipService::create(&app.db, &project, 5000, 0.1, None, Some("Danke!"))
+        .await
+        .unwrap();
+
+    let (first, second) = tokio::join!(
+        TipService::mark_paid(&app.db, tip.id, Some("pi_test")),
+        TipService::mark_paid(&app.db, tip.id, Some("pi_test")),
+    );
+    let settled: Vec<_> = [first.unwrap(), second.unwrap()].into_iter().flatten().collect();
+    assert_eq!(settled.len(), 1);
+    assert!(settled[0].invoice_id.is_some());
+
+    let invoices: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM invoices WHERE project_id = $1")
+        .bind(project_id)
+        .fetch_one(app.db.pool())
+        .await
+        .unwrap();
+    assert_eq!(invoices, 1);
+
+    let notifications: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM notifications WHERE user_id = $1 AND type = 'tip_received'"
+    )
+    .bind(project.expert_id)
+    .fetch_one(app.db.pool())
+    .await
+    .unwrap();
+    assert_eq!(notifications, 1);
+
+    // A late redelivery changes nothing
+    assert!(TipService::mark_paid(&app.db, tip.id, Some("pi_test")).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_card_checkout_pays_accepted_project() {
+    require_db!(app);
+    let fixture = project_fixture(&app).await;
+    let url = format!("/api/v1/projects/{}", fixture.project_id);
+    let status_url = format!("{}/status", url);
+
+    let response = app.put_auth(&status_url, &json!({ "status": "Accepted" }), &fixture.expert_token).await;
+    response.assert_success();
+
+    let checkout = CheckoutPayment {
+        session_id: format!("cs_test_{}", Uuid::new_v4().simple()),
+        project_id: Uuid::parse_str(&fixture.project_id).unwrap(),
+        payer_id: Uuid::parse_str(&fixture.client_id).unwrap(),
+        payee_id: Uuid::parse_str(&fixture.expert_id).unwrap(),
+        amount: 100_000,
+        currency: "chf".to_string(),
+        payment_intent_id: Some(format!("pi_test_{}", Uuid::new_v4().simple())),
+        description: "Service purchase".to_string(),
+        metadata: json!({}),
+    };
+    let payment = PaymentService::record_checkout(app.db.pool(), &checkout).await.unwrap().unwrap();
+    assert_eq!(payment.project_id.to_string(), fixture.project_id);
+
+    // Webhook redelivery records nothing new
+    assert!(PaymentService::record_checkout(app.db.pool(), &checkout).await.unwrap().is_none());
+
+    let response = app.get_auth(&url, &fixture.client_token).await;
+    assert_eq!(response.json()["data"]["status"], "Paid");
+    assert!(timeline_events(&app, &fixture).await.iter().any(|e| e == "payment_received"));
+
+    let response = app.put_auth(&status_url, &json!({ "status": "InProgress" }), &fixture.expert_token).await;
+    response.assert_success();
+    assert_eq!(response.json()["data"]["status"], "InProgress");
+}
+
+/// Without a gateway the checkout returns a mock session, so the project it creates can be inspected
+#[cfg(not(feature = "payments"))]
+#[tokio::test]
+async fn test_service_checkout_creates_accepted_project() {
+    use dach_marketplace_api::services::ProjectService;
+
+    require_db!(app);
+    let (client_token, client_id) = common::register(&app, "Client").await;
+    let (expert_token, _) = common::register(&app, "Expert").await;
+
+    app.post_auth("/api/v1/experts", &json!({
+        "headline": "n8n & Make.com Specialist",
+        "bio": "Ten years of automation experience across e-commerce and SaaS companies.",
+        "hourlyRate": 15000,
+        "currency": "CHF",
+        "yearsExperience": 10,
+        "skills": ["n8n", "Make"],
+        "tools": ["n8n", "Make.com"],
+        "languagesSpoken": ["de", "en"],
+        "availableHoursPerWeek": 20,
+        "timezone": "Europe/Zurich"
+    }), &expert_token).await.assert_success();
+
+    let category_id: Uuid = sqlx::query_scalar("SELECT id FROM categories LIMIT 1")
+        .fetch_one(app.db.pool())
+        .await
+        .unwrap();
+    let response = app.post_auth("/api/v1/services", &json!({
+        "categoryId": category_id,
+        "title": "Shopify to ERP order sync",
+        "description": "I connect your Shopify store with your ERP so that orders, stock levels and invoices stay in sync without manual work.",
+        "shortDescription": "Automated Shopify and ERP order sync",
+        "pricingType": "Fixed",
+        "price": 150000,
+        "currency": "CHF",
+        "deliveryTimeDays": 7,
+        "revisionsIncluded": 2,
+        "features": ["Order sync"]
+    }), &expert_token).await;
+    response.assert_success();
+    let service_id = response.json()["data"]["id"].as_str().unwrap().to_string();
+
+    let response = app.post_auth(&format!("/api/v1/services/{}/addons", service_id), &json!({
+        "title": "Express-Lieferung",
+        "price": 20000,
+        "extraDays": -3
+    }), &expert_token).await;
+    response.assert_success();
+    let addon_id = response.json()["data"]["id"].clone();
+
+    app.post_auth("/api/v1/payments/checkout", &json!({
+        "serviceId": service_id,
+        "addons": [{ "addonId": addon_id, "quantity": 1 }]
+    }), &client_token).await.assert_success();
+
+    let (project_id, status, price): (Uuid, String, i32) = sqlx::query_as(
+        "SELECT id, status::text, price FROM projects WHERE client_id = $1 AND service_id = $2",
+    )
+    .bind(Uuid::parse_str(&client_id).unwrap())
+    .bind(Uuid::parse_str(&service_id).unwrap())
+    .fetch_one(app.db.pool())
+    .await
+    .unwrap();
+    assert_eq!(status, "accepted");
+    assert_eq!(price, 170000);
+
+    // An expired session cancels the unpaid project once
+    assert!(ProjectService::cancel_unpaid(&app.db, project_id, "Checkout expired").await.unwrap().is_some());
+    assert!(ProjectService::cancel_unpaid(&app.db, project_id, "Checkout expired").await.unwrap().is_none());
+}
+
+/// Event types on the project's timeline, oldest first
+async fn timeline_events(app: &common::TestApp, fixture: &ProjectFixture) -> Vec<String> {
+    let response = app
+        .get_auth(&format!("/api/v1/projects/{}/timeline", fixture.project_id), &fixture.client_token)
+        .await;
+    response.assert_success();
+
+    response.json()["data"]["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["eventType"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_payment_webhooks_reach_the_timeline() {
+    require_db!(app);
+    let fixture = project_fixture(&app).await;
+
+    let payment_id = insert_card_payment(&app, &fixture, 100_000).await;
+    let intent_id: String = sqlx::query_scalar("UPDATE payments SET status = 'pending' WHERE id = $1 RETURNING stripe_payment_intent_id")
+        .bind(payment_id)
+        .fetch_one(app.db.pool())
+        .await
+        .unwrap();
+
+    let succeeded = PaymentService::record_intent_succeeded(app.db.pool(), &intent_id).await.unwrap();
+    assert_eq!(succeeded.len(), 1);
+    // Already succeeded payments are not noted twice
+    assert!(PaymentService::record_intent_succeeded(app.db.pool(), &intent_id).await.unwrap().is_empty());
+
+    let refunded = PaymentService::record_refund(app.db.pool(), &intent_id, 40_000).await.unwrap();
+    assert_eq!(refunded[0].status, PaymentStatus::PartiallyRefunded);
+
+    let disputed = PaymentService::record_dispute(app.db.pool(), &intent_id).await.unwrap();
+    assert_eq!(disputed[0].status, PaymentStatus::Disputed);
+
+    let events = timeline_events(&app, &fixture).await;
+    assert_eq!(events.iter().filter(|e| *e == "payment_received").count(), 1);
+    assert!(events.iter().any(|e| e == "payment_refunded"));
+    assert!(events.iter().any(|e| e == "payment_disputed"));
+}
+
+#[tokio::test]
+async fn test_completion_reminder_sent_once() {
+    require_db!(app);
+    let fixture = project_fixture(&app).await;
+    let project_id = Uuid::parse_str(&fixture.project_id).unwrap();
+    sqlx::query("UPDATE projects SET status = 'delivered', delivered_at = NOW() WHERE id = $1")
+        .bind(project_id)
+        .execute(app.db.pool())
+        .await
+        .unwrap();
+
+    let project = AutoCompleteService::get_awaiting_approval(&app.db)
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|p| p.id == project_id)
+        .unwrap();
+    let deadline = project.delivered_at.date_naive() + chrono::Duration::days(14);
+
+    assert!(AutoCompleteService::remind(&app.db, &project, 1, deadline).await.unwrap());
+    assert!(!AutoCompleteService::remind(&app.db, &project, 1, deadline).await.unwrap());
+
+    let reminders: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM notifications WHERE user_id = $1 AND type = 'delivery_approval_reminder'"
+    )
+    .bind(project.client_id)
+    .fetch_one(app.db.pool())
+    .await
+    .unwrap();
+    assert_eq!(reminders, 1);
+
+    let events = timeline_events(&app, &fixture).await;
+    assert_eq!(events.iter().filter(|e| *e == "completion_reminder_sent").count(), 1);
+}
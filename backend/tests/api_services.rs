@@ -138,3 +138,63 @@ async fn test_search_services() {
     assert!(json["data"].is_array());
 }
 
+
+#[tokio::test]
+async fn test_service_requirements_form_is_validated() {
+    require_db!(app);
+
+    let register = app.post("/api/v1/auth/register", &json!({
+        "email": common::test_email(),
+        "password": "SecurePass123!",
+        "firstName": "Expert",
+        "lastName": "User",
+        "role": "Expert",
+        "country": "ch"
+    })).await;
+    register.assert_success();
+    let token = register.json()["data"]["accessToken"].as_str().unwrap().to_string();
+
+    let service = |form: serde_json::Value| json!({
+        "categoryId": "00000000-0000-0000-0000-000000000000",
+        "title": "Shopify to ERP order sync",
+        "description": "I connect your Shopify store with your ERP so that orders, stock levels and invoices stay in sync without manual work.",
+        "shortDescription": "Automated Shopify and ERP order sync",
+        "pricingType": "Fixed",
+        "price": 150000,
+        "currency": "CHF",
+        "deliveryTimeDays": 7,
+        "revisionsIncluded": 2,
+        "features": ["Order sync"],
+        "requirementsForm": form
+    });
+
+    // Duplicate field ids
+    let duplicate = app.post_auth("/api/v1/services", &service(json!([
+        { "id": "shop", "label": "Shop URL", "type": "url", "required": true },
+        { "id": "shop", "label": "Shop admin login", "type": "secret", "required": true }
+    ])), &token).await;
+    duplicate.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+
+    // Choice fields need options
+    let no_options = app.post_auth("/api/v1/services", &service(json!([
+        { "id": "erp", "label": "Which ERP do you use?", "type": "choice" }
+    ])), &token).await;
+    no_options.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn test_service_addons() {
+    require_db!(app);
+
+    // Managing add-ons requires authentication
+    let create = app.post("/api/v1/services/00000000-0000-0000-0000-000000000000/addons", &json!({
+        "title": "Express-Lieferung",
+        "price": 20000,
+        "extraDays": -3
+    })).await;
+    create.assert_status(StatusCode::UNAUTHORIZED);
+
+    // Add-ons of unknown services are not found
+    let list = app.get("/api/v1/services/00000000-0000-0000-0000-000000000000/addons").await;
+    list.assert_status(StatusCode::NOT_FOUND);
+}
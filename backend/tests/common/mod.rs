@@ -1,6 +1,5 @@
 //! Common test utilities and fixtures
 
-use std::sync::Arc;
 use axum::Router;
 use axum::body::Body;
 use axum::http::{Request, StatusCode};
@@ -28,7 +27,7 @@ impl TestApp {
 
         // Load settings from environment
         let settings = match Settings::from_env() {
-            Ok(s) => Arc::new(s),
+            Ok(s) => s,
             Err(e) => {
                 eprintln!("⚠️ Skipping test: Failed to load settings: {}", e);
                 return None;
@@ -51,12 +50,7 @@ impl TestApp {
         }
 
         // Create app state
-        let state = AppState {
-            db: db.clone(),
-            settings,
-            #[cfg(feature = "email")]
-            email: None,
-        };
+        let state = AppState::new(db.clone(), settings);
 
         let app = create_app(state);
 
@@ -133,6 +127,20 @@ impl TestApp {
         let response = self.app.clone().oneshot(request).await.unwrap();
         TestResponse::from_response(response).await
     }
+
+    /// Make an authenticated DELETE request
+    #[allow(dead_code)]
+    pub async fn delete_auth(&self, uri: &str, token: &str) -> TestResponse {
+        let request = Request::builder()
+            .method("DELETE")
+            .uri(uri)
+            .header("Authorization", format!("Bearer {}", token))
+            .body(Body::empty())
+            .unwrap();
+        
+        let response = self.app.clone().oneshot(request).await.unwrap();
+        TestResponse::from_response(response).await
+    }
 }
 
 /// Test response wrapper
@@ -175,3 +183,23 @@ pub fn test_email() -> String {
     format!("test_{}@example.com", uuid::Uuid::new_v4())
 }
 
+/// Register a user; returns the access token and user id
+#[allow(dead_code)]
+pub async fn register(app: &TestApp, role: &str) -> (String, String) {
+    let response = app.post("/api/v1/auth/register", &serde_json::json!({
+        "email": test_email(),
+        "password": "SecurePass123!",
+        "firstName": role,
+        "lastName": "User",
+        "role": role,
+        "country": "ch"
+    })).await;
+    response.assert_success();
+    let json = response.json();
+
+    (
+        json["data"]["accessToken"].as_str().unwrap().to_string(),
+        json["data"]["user"]["id"].as_str().unwrap().to_string(),
+    )
+}
+